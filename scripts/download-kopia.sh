@@ -56,6 +56,23 @@ fi
 echo "📦 Downloading Kopia v${VERSION} binaries..."
 echo ""
 
+# Record a binary's SHA-256 next to it (embedded at build time for integrity checks)
+write_checksum() {
+    local binary="$BIN_DIR/$1"
+    local hash
+
+    if command -v sha256sum >/dev/null 2>&1; then
+        hash=$(sha256sum "$binary" | cut -d' ' -f1)
+    elif command -v shasum >/dev/null 2>&1; then
+        hash=$(shasum -a 256 "$binary" | cut -d' ' -f1)
+    else
+        echo "   ⚠️  No sha256 tool found, skipping checksum"
+        return 0
+    fi
+
+    echo "$hash" > "${binary}.sha256"
+}
+
 # Download function with retry logic
 download() {
     local platform=$1
@@ -131,6 +148,7 @@ download() {
         rm -f "/tmp/${filename}"
 
         if [ -f "$BIN_DIR/$output" ]; then
+            write_checksum "$output"
             echo "   ✓ Installed: $output"
         else
             echo "   ⚠️  Failed to extract binary"
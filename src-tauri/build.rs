@@ -15,5 +15,41 @@ fn main() {
         println!("cargo:warning=Note: This is a warning, not an error. Build will continue.");
     }
 
+    embed_expected_binary_hash(bin_dir);
+
     tauri_build::build();
 }
+
+/// Embed the expected SHA-256 of the target platform's Kopia binary
+///
+/// Uses `KOPIA_BINARY_SHA256` if set, otherwise the `<binary>.sha256` file written
+/// by `scripts/download-kopia.sh`. Without either, no hash is embedded.
+fn embed_expected_binary_hash(bin_dir: &Path) {
+    println!("cargo:rerun-if-env-changed=KOPIA_BINARY_SHA256");
+
+    if std::env::var("KOPIA_BINARY_SHA256").is_ok() {
+        return;
+    }
+
+    let os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let arch = std::env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let binary_name = match (os.as_str(), arch.as_str()) {
+        ("windows", _) => "kopia-windows-x64.exe",
+        ("macos", "aarch64") => "kopia-darwin-arm64",
+        ("macos", _) => "kopia-darwin-x64",
+        ("linux", "aarch64") => "kopia-linux-arm64",
+        _ => "kopia-linux-x64",
+    };
+
+    let hash_file = bin_dir.join(format!("{}.sha256", binary_name));
+    println!("cargo:rerun-if-changed={}", hash_file.display());
+
+    if let Ok(contents) = std::fs::read_to_string(&hash_file) {
+        if let Some(hash) = contents.split_whitespace().next() {
+            println!(
+                "cargo:rustc-env=KOPIA_BINARY_SHA256={}",
+                hash.to_lowercase()
+            );
+        }
+    }
+}
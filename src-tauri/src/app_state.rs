@@ -0,0 +1,335 @@
+//! Application-wide state
+//!
+//! Holds state that belongs to the desktop app process itself rather than to a
+//! repository or its Kopia server.
+//!
+//! # Mutation Locks
+//!
+//! Mutating commands take a per-resource lock (keyed by repository and
+//! source/policy target), so e.g. `policy_set` and `snapshot_create` for the
+//! same path queue instead of interleaving. Read commands don't lock.
+//!
+//! # Task Throttles
+//!
+//! Kopia only throttles whole repositories, so `task_throttle` lowers the
+//! repository upload limit while a task runs. The limits from before the first
+//! override are kept until the last throttled task of the repository ends, or
+//! until the app quits. Overrides are changed under the repository-wide
+//! resource lock (the global policy target).
+//!
+//! # App Operations
+//!
+//! Some long operations run in the app rather than as server tasks (e.g.
+//! `maintenance_run` runs the Kopia CLI, `restore_files` downloads through the
+//! server), so they don't appear in the server's task list. They are
+//! registered here while they run, so they can be found and canceled.
+
+use crate::metrics::MetricsRegistry;
+use crate::types::{PolicyTarget, ThrottleLimits};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::sync::{Notify, OwnedMutexGuard};
+
+/// App state managed by Tauri
+pub struct AppState {
+    /// When the app process started
+    started_at: Instant,
+    /// Set once a graceful quit has started
+    quitting: AtomicBool,
+    /// Serializes mutations of the same resource
+    mutation_locks: MutationLocks,
+    /// Repository throttles lowered for running tasks
+    task_throttles: Arc<TaskThrottles>,
+    /// Operations running in the app rather than on a server
+    app_operations: Arc<AppOperations>,
+    /// Per-command metrics (see `metrics`)
+    metrics: Arc<MetricsRegistry>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            quitting: AtomicBool::new(false),
+            mutation_locks: MutationLocks::default(),
+            task_throttles: Arc::default(),
+            app_operations: Arc::default(),
+            metrics: Arc::default(),
+        }
+    }
+
+    /// Seconds since the app started
+    pub fn uptime_secs(&self) -> u64 {
+        uptime_secs_at(self.started_at, Instant::now())
+    }
+
+    /// Mark the app as quitting
+    ///
+    /// Returns false if a quit is already in progress.
+    pub fn begin_quit(&self) -> bool {
+        !self.quitting.swap(true, Ordering::SeqCst)
+    }
+
+    /// Wait for exclusive access to a source or policy target of a repository
+    ///
+    /// The resource stays locked until the returned guard is dropped.
+    pub async fn lock_resource(&self, repo_id: &str, target: &PolicyTarget) -> OwnedMutexGuard<()> {
+        self.mutation_locks
+            .acquire(resource_key(repo_id, target))
+            .await
+    }
+
+    /// Throttle overrides of running tasks, shared with their watchers
+    pub fn task_throttles(&self) -> Arc<TaskThrottles> {
+        self.task_throttles.clone()
+    }
+
+    /// Register an operation running in the app until the guard is dropped
+    pub fn begin_operation(&self, repo_id: &str, kind: &str) -> AppOperationGuard {
+        AppOperations::begin(&self.app_operations, repo_id, kind)
+    }
+
+    /// Operations running in the app
+    pub fn app_operations(&self) -> &AppOperations {
+        &self.app_operations
+    }
+
+    /// Per-command metrics registry
+    pub fn metrics(&self) -> Arc<MetricsRegistry> {
+        self.metrics.clone()
+    }
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Per-key async locks
+///
+/// Locks are created on demand and dropped once nobody holds or waits for them.
+#[derive(Default)]
+pub struct MutationLocks {
+    locks: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl MutationLocks {
+    /// Wait for exclusive access to `key`
+    pub async fn acquire(&self, key: String) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut locks = self.locks.lock().unwrap();
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(key).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+/// Repository throttle overrides applied on behalf of running tasks
+#[derive(Default)]
+pub struct TaskThrottles {
+    repos: Mutex<HashMap<String, ThrottleOverride>>,
+}
+
+/// Upload limit applied to a repository for one or more tasks
+#[derive(Debug, Clone)]
+pub struct ThrottleOverride {
+    /// Limits to restore once no throttled task is left
+    pub original: ThrottleLimits,
+    /// Upload limit currently applied, in bytes per second
+    pub applied: f64,
+    tasks: HashSet<String>,
+}
+
+impl TaskThrottles {
+    /// Limits saved before the repository was first throttled, if it still is
+    pub fn original(&self, repo_id: &str) -> Option<ThrottleLimits> {
+        self.repos
+            .lock()
+            .unwrap()
+            .get(repo_id)
+            .map(|entry| entry.original.clone())
+    }
+
+    /// Record that `task_id` is throttled to `applied` bytes per second
+    ///
+    /// `original` is ignored while the repository is already throttled.
+    /// Returns false if the task was already throttled.
+    pub fn apply(
+        &self,
+        repo_id: &str,
+        task_id: &str,
+        original: ThrottleLimits,
+        applied: f64,
+    ) -> bool {
+        let mut repos = self.repos.lock().unwrap();
+        let entry = repos
+            .entry(repo_id.to_string())
+            .or_insert_with(|| ThrottleOverride {
+                original,
+                applied,
+                tasks: HashSet::new(),
+            });
+        entry.applied = applied;
+        entry.tasks.insert(task_id.to_string())
+    }
+
+    /// Repositories currently throttled for tasks
+    pub fn repo_ids(&self) -> Vec<String> {
+        self.repos.lock().unwrap().keys().cloned().collect()
+    }
+
+    /// Drop a repository's override regardless of its remaining tasks
+    ///
+    /// Returns the override to undo, if there was one.
+    pub fn take(&self, repo_id: &str) -> Option<ThrottleOverride> {
+        self.repos.lock().unwrap().remove(repo_id)
+    }
+
+    /// Record that a throttled task ended
+    ///
+    /// Returns the override to undo when it was the repository's last
+    /// throttled task.
+    pub fn finish(&self, repo_id: &str, task_id: &str) -> Option<ThrottleOverride> {
+        let mut repos = self.repos.lock().unwrap();
+        let entry = repos.get_mut(repo_id)?;
+        entry.tasks.remove(task_id);
+        if entry.tasks.is_empty() {
+            repos.remove(repo_id)
+        } else {
+            None
+        }
+    }
+}
+
+/// Operations running in the app, by ID
+#[derive(Default)]
+pub struct AppOperations {
+    next_id: AtomicU64,
+    running: Mutex<BTreeMap<u64, AppOperation>>,
+}
+
+/// An operation running in the app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppOperation {
+    pub id: String,
+    pub repo_id: String,
+    /// Same names as server task kinds, e.g. "Maintenance"
+    pub kind: String,
+    #[serde(skip)]
+    cancel: Arc<Notify>,
+}
+
+impl AppOperations {
+    fn begin(this: &Arc<Self>, repo_id: &str, kind: &str) -> AppOperationGuard {
+        let key = this.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let id = format!("app-{}", key);
+        let cancel = Arc::new(Notify::new());
+        this.running.lock().unwrap().insert(
+            key,
+            AppOperation {
+                id: id.clone(),
+                repo_id: repo_id.to_string(),
+                kind: kind.to_string(),
+                cancel: cancel.clone(),
+            },
+        );
+        AppOperationGuard {
+            operations: this.clone(),
+            key,
+            id,
+            cancel,
+        }
+    }
+
+    /// Running operations, of one repository or all, oldest first
+    pub fn list(&self, repo_id: Option<&str>) -> Vec<AppOperation> {
+        self.running
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|op| repo_id.is_none_or(|r| r == op.repo_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Cancel the running operations of `kind` for a repository
+    ///
+    /// Returns the IDs of the canceled operations.
+    pub fn cancel(&self, repo_id: &str, kind: &str) -> Vec<String> {
+        Self::notify_cancel(
+            self.list(Some(repo_id))
+                .into_iter()
+                .filter(|op| op.kind == kind),
+        )
+    }
+
+    /// Cancel every running operation of a repository
+    ///
+    /// Returns the IDs of the canceled operations.
+    pub fn cancel_all(&self, repo_id: &str) -> Vec<String> {
+        Self::notify_cancel(self.list(Some(repo_id)))
+    }
+
+    fn notify_cancel(operations: impl IntoIterator<Item = AppOperation>) -> Vec<String> {
+        operations
+            .into_iter()
+            .map(|op| {
+                op.cancel.notify_one();
+                op.id
+            })
+            .collect()
+    }
+}
+
+/// Keeps an app operation registered until dropped
+pub struct AppOperationGuard {
+    operations: Arc<AppOperations>,
+    key: u64,
+    id: String,
+    cancel: Arc<Notify>,
+}
+
+impl AppOperationGuard {
+    /// ID of the operation
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Wait until the operation is canceled
+    pub async fn canceled(&self) {
+        self.cancel.notified().await
+    }
+}
+
+impl Drop for AppOperationGuard {
+    fn drop(&mut self) {
+        self.operations.running.lock().unwrap().remove(&self.key);
+    }
+}
+
+/// Lock key for a source or policy target, e.g. `repo/user@host:/data`
+///
+/// Sources and policy targets for the same path share a key.
+pub fn resource_key(repo_id: &str, target: &PolicyTarget) -> String {
+    format!("{}/{}", repo_id, target)
+}
+
+/// Whole seconds elapsed between `started_at` and `now`
+pub fn uptime_secs_at(started_at: Instant, now: Instant) -> u64 {
+    now.saturating_duration_since(started_at).as_secs()
+}
+
+/// App and server uptime for the status bar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UptimeInfo {
+    pub app_uptime_secs: u64,
+    /// None when the repository's server isn't running
+    pub server_uptime_secs: Option<u64>,
+}
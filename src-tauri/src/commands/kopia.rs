@@ -5,13 +5,22 @@
 //!
 //! All commands now take a `repo_id` parameter to support multiple repositories.
 
-use crate::error::{HttpResultExt, KopiaError, Result};
-use crate::kopia_server::{KopiaServerInfo, KopiaServerStatus};
+use crate::app_state::{AppState, UptimeInfo};
+use crate::error::{find_ignore_ascii_case, HttpResultExt, KopiaError, Result};
+use crate::fan_out::{fan_out, fan_out_bounded, fan_out_timeout, FanOutResult};
+use crate::kopia_cli;
+use crate::kopia_server::{
+    ExternalServer, KopiaServerInfo, KopiaServerStatus, ServerStartOptions, ServerStartPhase,
+    ServerStartPhaseEvent, SERVER_START_PHASE_EVENT,
+};
+use crate::metrics::CommandTimer;
 use crate::server_manager::{RepositoryEntry, ServerManagerState};
-use crate::types::{RepositoryConnectRequest, RepositoryStatus, StorageConfig};
+use crate::settings::SettingsState;
+use crate::types::{RepositoryConnectRequest, RepositoryStatus, SourceIdentity, StorageConfig};
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use tauri::State;
+use std::collections::HashMap;
+use tauri::{AppHandle, Emitter, State};
 
 // ============================================================================
 // Repository Management Commands (Multi-repo)
@@ -23,9 +32,10 @@ use tauri::State;
 /// in the config directory.
 #[tauri::command]
 pub async fn list_repositories(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
 ) -> Result<Vec<RepositoryEntry>> {
-    manager.lock().unwrap().list_repositories()
+    timer.finish(manager.lock().unwrap().list_repositories())
 }
 
 /// Add a new repository configuration
@@ -34,10 +44,11 @@ pub async fn list_repositories(
 /// Returns the repository ID.
 #[tauri::command]
 pub async fn add_repository(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: Option<String>,
 ) -> Result<String> {
-    manager.lock().unwrap().add_repository(repo_id)
+    timer.finish(manager.lock().unwrap().add_repository(repo_id))
 }
 
 /// Remove a repository configuration
@@ -46,10 +57,75 @@ pub async fn add_repository(
 /// Cannot remove the default "repository" entry.
 #[tauri::command]
 pub async fn remove_repository(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<()> {
-    manager.lock().unwrap().remove_repository(&repo_id)
+    timer.finish(manager.lock().unwrap().remove_repository(&repo_id))
+}
+
+/// Import an existing Kopia config and start a server for it
+///
+/// Lets users who already use the Kopia CLI adopt their repository without
+/// re-entering connection details. The config is copied into the app's config
+/// directory; if the repository can't be opened with it, the import is rolled back.
+#[tauri::command]
+pub async fn adopt_existing_config(
+    timer: CommandTimer,
+    app: AppHandle,
+    manager: State<'_, ServerManagerState>,
+    config_path: String,
+) -> Result<RepositoryEntry> {
+    let repo_id = manager
+        .lock()
+        .unwrap()
+        .import_config(std::path::Path::new(&config_path))?;
+
+    timer.finish(match start_adopted_server(&app, &manager, &repo_id).await {
+        Ok(status) => Ok(RepositoryEntry {
+            display_name: status
+                .description
+                .clone()
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| repo_id.clone()),
+            config_file: manager.lock().unwrap().get_config_file_path(&repo_id),
+            id: repo_id,
+            status: "running".to_string(),
+            connected: true,
+            storage: status.storage,
+            error: None,
+        }),
+        Err(e) => {
+            if let Err(cleanup_err) = manager.lock().unwrap().discard_repository(&repo_id) {
+                log::warn!(
+                    "Failed to roll back import of '{}': {}",
+                    repo_id,
+                    cleanup_err
+                );
+            }
+            Err(e)
+        }
+    })
+}
+
+/// Connect to a Kopia server the app didn't start
+///
+/// Registers the server as a repository whose commands are sent to `info.url`
+/// instead of an embedded server, after checking the certificate fingerprint
+/// and credentials. The connection isn't saved and lasts until the repository
+/// is removed or the app exits. Commands that run the Kopia CLI need a local
+/// config file and aren't available for external servers.
+#[tauri::command]
+pub async fn connect_external_server(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    info: ExternalServer,
+) -> Result<RepositoryEntry> {
+    let (client, download_client) = crate::kopia_server::connect_external_server(&info).await?;
+
+    let mut manager_guard = manager.lock().unwrap();
+    let repo_id = manager_guard.add_external_server(&info.url, client, download_client);
+    timer.finish(Ok(manager_guard.repository_entry(&repo_id)))
 }
 
 // ============================================================================
@@ -65,9 +141,12 @@ pub async fn remove_repository(
 /// `KopiaServerInfo` containing server URL, username, password, and CSRF token
 #[tauri::command]
 pub async fn kopia_server_start(
+    timer: CommandTimer,
+    app: AppHandle,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<KopiaServerInfo> {
+    emit_start_phase(&app, &repo_id, ServerStartPhase::Starting);
     let (info, ready_waiter) = {
         let mut manager_guard = manager.lock().unwrap();
         let info = manager_guard.start_server(&repo_id)?;
@@ -75,8 +154,53 @@ pub async fn kopia_server_start(
         (info, waiter)
     };
 
-    ready_waiter.await?;
-    Ok(info)
+    wait_until_ready(&app, &repo_id, ready_waiter).await?;
+    timer.finish(Ok(info))
+}
+
+/// Restart the Kopia server for a repository
+///
+/// Stops the running server (waiting for it to exit) and starts it again against
+/// the same config file, optionally with updated start options. If the server
+/// wasn't running it is simply started. Progress is reported via `server-start-phase`.
+#[tauri::command]
+pub async fn kopia_server_restart(
+    timer: CommandTimer,
+    app: AppHandle,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    options: Option<ServerStartOptions>,
+) -> Result<KopiaServerInfo> {
+    let (info, ready_waiter) = {
+        let mut manager_guard = manager.lock().unwrap();
+
+        let running = manager_guard
+            .get_server_status(&repo_id)
+            .map(|status| status.running)
+            .unwrap_or(false);
+        if running {
+            emit_start_phase(&app, &repo_id, ServerStartPhase::Stopping);
+            manager_guard.stop_server(&repo_id)?;
+        }
+
+        if let Some(options) = options {
+            manager_guard.set_start_options(&repo_id, options)?;
+        }
+
+        emit_start_phase(&app, &repo_id, ServerStartPhase::Starting);
+        let info = match manager_guard.start_server(&repo_id) {
+            Ok(info) => info,
+            Err(e) => {
+                emit_start_phase(&app, &repo_id, ServerStartPhase::Failed);
+                return Err(e);
+            }
+        };
+        let waiter = manager_guard.get_ready_waiter(&repo_id)?;
+        (info, waiter)
+    };
+
+    wait_until_ready(&app, &repo_id, ready_waiter).await?;
+    timer.finish(Ok(info))
 }
 
 /// Stop the Kopia server for a repository
@@ -84,10 +208,338 @@ pub async fn kopia_server_start(
 /// Gracefully terminates the Kopia server process and cleans up resources.
 #[tauri::command]
 pub async fn kopia_server_stop(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<()> {
+    timer.finish(manager.lock().unwrap().stop_server(&repo_id))
+}
+
+/// Task kinds that `kopia_server_stop_safe` won't interrupt without `force`
+const STOP_BLOCKING_TASK_KINDS: &[&str] = &["Snapshot", "Restore"];
+
+/// How long `kopia_server_stop_safe` waits for canceled tasks to end
+const STOP_CANCEL_TIMEOUT_SECS: u64 = 30;
+
+/// Stop a repository's server unless a snapshot or restore is running
+///
+/// Meant for the "stop server" button; shutdown paths keep using
+/// `kopia_server_stop`. When a snapshot or restore is running the server is
+/// left alone and the reason is returned. App operations of the repository
+/// (file restores and downloads, CLI maintenance and verification) count as
+/// running too. With `force`, those tasks and operations are canceled first
+/// (an interrupted snapshot keeps its checkpoint) and the server is stopped
+/// once they end, or after `STOP_CANCEL_TIMEOUT_SECS`.
+#[tauri::command]
+pub async fn kopia_server_stop_safe(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+    force: bool,
+) -> Result<crate::types::SafeStopResult> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let tasks = fetch_tasks(&client, &server_url).await?;
+    let blocking = stop_blocking_tasks(&tasks);
+    let operations = app_state.app_operations().list(Some(&repo_id));
+
+    if (!blocking.is_empty() || !operations.is_empty()) && !force {
+        return timer.finish(Ok(crate::types::SafeStopResult {
+            stopped: false,
+            refused_reason: stop_refused_reason(&blocking, &operations),
+            canceled_tasks: 0,
+        }));
+    }
+
+    let canceled_operations = app_state.app_operations().cancel_all(&repo_id);
+    if !canceled_operations.is_empty() {
+        log::info!(
+            "Canceling {} app operation(s) before stopping server for '{}'",
+            canceled_operations.len(),
+            repo_id
+        );
+        wait_for_operations_to_end(&app_state, &canceled_operations).await;
+    }
+
+    let task_ids: Vec<String> = blocking.into_iter().map(|task| task.id).collect();
+    if !task_ids.is_empty() {
+        log::info!(
+            "Canceling {} task(s) before stopping server for '{}'",
+            task_ids.len(),
+            repo_id
+        );
+        for task_id in &task_ids {
+            if let Err(e) = cancel_task(&client, &server_url, task_id).await {
+                log::warn!("Failed to cancel task {}: {}", task_id, e);
+            }
+        }
+        wait_for_tasks_to_end(&client, &server_url, &task_ids).await;
+    }
+
+    manager.lock().unwrap().stop_server(&repo_id)?;
+    timer.finish(Ok(crate::types::SafeStopResult {
+        stopped: true,
+        refused_reason: None,
+        canceled_tasks: task_ids.len() + canceled_operations.len(),
+    }))
+}
+
+/// Running tasks that stopping the server would interrupt
+pub(crate) fn stop_blocking_tasks(tasks: &[crate::types::Task]) -> Vec<crate::types::Task> {
+    tasks
+        .iter()
+        .filter(|task| {
+            is_task_active(&task.status) && STOP_BLOCKING_TASK_KINDS.contains(&task.kind.as_str())
+        })
+        .cloned()
+        .collect()
+}
+
+/// Why the server wasn't stopped, e.g. "2 tasks are running (Restore, Snapshot)"
+///
+/// App operations count as tasks of their kind.
+pub(crate) fn stop_refused_reason(
+    blocking: &[crate::types::Task],
+    operations: &[crate::app_state::AppOperation],
+) -> Option<String> {
+    let active = summarize_active_operations(blocking);
+    let count = active.count + operations.len();
+    if count == 0 {
+        return None;
+    }
+
+    let mut kinds = active.kinds;
+    kinds.extend(operations.iter().map(|op| op.kind.clone()));
+    kinds.sort();
+    kinds.dedup();
+
+    Some(format!(
+        "{} {} running ({})",
+        count,
+        if count == 1 { "task is" } else { "tasks are" },
+        kinds.join(", ")
+    ))
+}
+
+/// Wait until none of the app operations `ids` is running, up to
+/// `STOP_CANCEL_TIMEOUT_SECS`
+async fn wait_for_operations_to_end(app_state: &AppState, ids: &[String]) {
+    let deadline =
+        std::time::Instant::now() + std::time::Duration::from_secs(STOP_CANCEL_TIMEOUT_SECS);
+
+    while std::time::Instant::now() < deadline {
+        let running = app_state
+            .app_operations()
+            .list(None)
+            .iter()
+            .any(|op| ids.contains(&op.id));
+        if !running {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+
+    log::warn!(
+        "Canceled app operations still running after {}s",
+        STOP_CANCEL_TIMEOUT_SECS
+    );
+}
+
+/// Poll until none of `task_ids` is running, up to `STOP_CANCEL_TIMEOUT_SECS`
+async fn wait_for_tasks_to_end(client: &reqwest::Client, server_url: &str, task_ids: &[String]) {
+    let deadline =
+        std::time::Instant::now() + std::time::Duration::from_secs(STOP_CANCEL_TIMEOUT_SECS);
+
+    while std::time::Instant::now() < deadline {
+        let Ok(tasks) = fetch_tasks(client, server_url).await else {
+            return;
+        };
+        let running = tasks
+            .iter()
+            .any(|task| task_ids.contains(&task.id) && is_task_active(&task.status));
+        if !running {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+
+    log::warn!(
+        "Canceled tasks still running after {}s",
+        STOP_CANCEL_TIMEOUT_SECS
+    );
+}
+
+/// Flush the server's in-memory repository state to storage
+///
+/// Uses the server control API, so recently written index and pack data is
+/// persisted even if the app is killed afterwards.
+#[tauri::command]
+pub async fn server_flush(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<()> {
-    manager.lock().unwrap().stop_server(&repo_id)
+    let (server_url, control_client) = {
+        let manager = manager.lock().unwrap();
+        let server_url = manager
+            .get_server_url(&repo_id)
+            .ok_or(KopiaError::ServerNotRunning)?;
+        (server_url, manager.get_control_client(&repo_id)?)
+    };
+
+    let response = control_client
+        .post(format!("{}/api/v1/control/flush", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to flush server")?;
+
+    timer.finish(handle_empty_response(response, "Flush server").await)
+}
+
+/// Get the version of the bundled Kopia binary
+///
+/// Runs `kopia --version` once; the result is cached for the app's lifetime
+/// since the binary doesn't change while it runs. Servers started by the app
+/// use this binary, so the frontend can gate features on `version` at startup.
+#[tauri::command]
+pub async fn server_version(timer: CommandTimer) -> Result<crate::types::KopiaVersion> {
+    static VERSION: std::sync::OnceLock<crate::types::KopiaVersion> = std::sync::OnceLock::new();
+
+    if let Some(version) = VERSION.get() {
+        return timer.finish(Ok(version.clone()));
+    }
+
+    let output = kopia_cli::version().await?;
+    let version = parse_kopia_version(&output).ok_or_else(|| {
+        KopiaError::operation_failed_with_details(
+            "Get Kopia version",
+            "Unrecognized `kopia --version` output",
+            output.trim().to_string(),
+        )
+    })?;
+
+    log::info!("Bundled Kopia version: {}", version.build_version);
+    timer.finish(Ok(VERSION.get_or_init(|| version).clone()))
+}
+
+/// Parse `kopia --version` output
+///
+/// The format is `<version> build: <build info> from: <GitHub repo>`, where
+/// everything after the version may be missing in development builds.
+pub(crate) fn parse_kopia_version(output: &str) -> Option<crate::types::KopiaVersion> {
+    let line = output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+    let (build_version, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+    let (build_info, source_repo) = match rest.trim().strip_prefix("build:") {
+        Some(build) => match build.split_once(" from:") {
+            Some((info, repo)) => (info.trim(), Some(repo.trim())),
+            None => (build.trim(), None),
+        },
+        None => ("", None),
+    };
+
+    Some(crate::types::KopiaVersion {
+        version: build_version.trim_start_matches('v').to_string(),
+        build_version: build_version.to_string(),
+        build_info: build_info.to_string(),
+        source_repo: source_repo
+            .filter(|repo| !repo.is_empty())
+            .map(String::from),
+    })
+}
+
+/// Check whether the repository's server handles an API endpoint
+///
+/// Lets the UI hide features the bundled Kopia version doesn't have. `GET`
+/// and `HEAD` endpoints are probed with the request itself, and a 404 or 405
+/// means unsupported. Other methods could change state, so they're probed
+/// with `OPTIONS` instead, which only tells whether the path exists (404).
+/// Results are cached until the server restarts.
+#[tauri::command]
+pub async fn server_supports_endpoint(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    path: String,
+    method: Option<String>,
+) -> Result<bool> {
+    let (method, path) = endpoint_probe(method.as_deref().unwrap_or("GET"), &path)?;
+    let endpoint = format!("{} {}", method, path);
+    if let Some(supported) = manager
+        .lock()
+        .unwrap()
+        .endpoint_support(&repo_id, &endpoint)
+    {
+        return timer.finish(Ok(supported));
+    }
+
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let probe_method = if method.is_safe() {
+        method.clone()
+    } else {
+        reqwest::Method::OPTIONS
+    };
+
+    let response = client
+        .request(probe_method.clone(), format!("{}{}", server_url, path))
+        .send()
+        .await
+        .map_http_error("Failed to probe endpoint")?;
+    let supported = endpoint_supported(&probe_method, response.status().as_u16());
+
+    log::debug!(
+        "Endpoint {} is {}supported",
+        endpoint,
+        if supported { "" } else { "not " }
+    );
+    manager
+        .lock()
+        .unwrap()
+        .record_endpoint_support(&repo_id, &endpoint, supported);
+    timer.finish(Ok(supported))
+}
+
+/// Validate and normalize the method and path of an endpoint to probe
+pub(crate) fn endpoint_probe(method: &str, path: &str) -> Result<(reqwest::Method, String)> {
+    let method = match method.to_ascii_uppercase().as_str() {
+        "GET" => reqwest::Method::GET,
+        "HEAD" => reqwest::Method::HEAD,
+        "POST" => reqwest::Method::POST,
+        "PUT" => reqwest::Method::PUT,
+        "PATCH" => reqwest::Method::PATCH,
+        "DELETE" => reqwest::Method::DELETE,
+        other => {
+            return Err(KopiaError::invalid_input(
+                "method",
+                format!("Unsupported HTTP method '{}'", other),
+            ))
+        }
+    };
+
+    let path = format!("/{}", path.trim().trim_start_matches('/'));
+    if !path.starts_with("/api/") {
+        return Err(KopiaError::invalid_input(
+            "path",
+            "Path must be an API path like /api/v1/sources",
+        ));
+    }
+    Ok((method, path))
+}
+
+/// Whether a probe response shows the endpoint exists
+///
+/// `OPTIONS` isn't routed by the server, so it answers 405 for paths that
+/// exist and 404 for paths that don't.
+pub(crate) fn endpoint_supported(probe_method: &reqwest::Method, status: u16) -> bool {
+    match status {
+        404 => false,
+        405 => probe_method == reqwest::Method::OPTIONS,
+        _ => true,
+    }
 }
 
 /// Get Kopia server status for a repository
@@ -96,10 +548,29 @@ pub async fn kopia_server_stop(
 /// server URL (if running), and uptime.
 #[tauri::command]
 pub async fn kopia_server_status(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<KopiaServerStatus> {
-    manager.lock().unwrap().get_server_status(&repo_id)
+    timer.finish(manager.lock().unwrap().get_server_status(&repo_id))
+}
+
+/// Get app and server uptime
+///
+/// The app start time is tracked in the backend so it survives frontend reloads.
+#[tauri::command]
+pub async fn uptime_info(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<UptimeInfo> {
+    let status = manager.lock().unwrap().get_server_status(&repo_id)?;
+
+    timer.finish(Ok(UptimeInfo {
+        app_uptime_secs: app_state.uptime_secs(),
+        server_uptime_secs: status.uptime,
+    }))
 }
 
 /// Get the default Kopia configuration directory
@@ -159,23 +630,18 @@ pub fn get_default_config_dir() -> Result<String> {
 /// connection status, and repository description.
 #[tauri::command]
 pub async fn repository_status(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<RepositoryStatus> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
-
-    let response = client
-        .get(format!("{}/api/v1/repo/status", server_url))
-        .send()
-        .await
-        .map_http_error("Get repository status")?;
-
-    handle_response(response, "Get repository status").await
+    timer.finish(retry_request(|| fetch_repository_status(&client, &server_url)).await)
 }
 
 /// Connect to an existing repository
 #[tauri::command]
 pub async fn repository_connect(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
     config: RepositoryConnectRequest,
@@ -190,14 +656,21 @@ pub async fn repository_connect(
         .map_http_error("Failed to connect to repository")?;
 
     handle_empty_response(response, "Connect to repository").await?;
+    // Client options may override the username and hostname
+    {
+        let mut manager_guard = manager.lock().unwrap();
+        manager_guard.clear_user_disconnect(&repo_id);
+        manager_guard.set_default_identity(&repo_id, None);
+    }
 
     // Return updated status
-    repository_status(manager, repo_id).await
+    timer.finish(retry_request(|| fetch_repository_status(&client, &server_url)).await)
 }
 
 /// Disconnect from repository
 #[tauri::command]
 pub async fn repository_disconnect(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<()> {
@@ -209,7 +682,12 @@ pub async fn repository_disconnect(
         .await
         .map_http_error("Failed to disconnect")?;
 
-    handle_empty_response(response, "Disconnect from repository").await
+    handle_empty_response(response, "Disconnect from repository").await?;
+    let mut manager_guard = manager.lock().unwrap();
+    manager_guard.record_user_disconnect(&repo_id);
+    manager_guard.set_default_identity(&repo_id, None);
+    manager_guard.clear_error_notifications(&repo_id);
+    timer.finish(Ok(()))
 }
 
 /// Sync repository metadata
@@ -219,18 +697,24 @@ pub async fn repository_disconnect(
 /// they all see the latest snapshots and policies.
 #[tauri::command]
 pub async fn repository_sync(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<()> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
 
-    let response = client
-        .post(format!("{}/api/v1/repo/sync", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to sync repository")?;
+    timer.finish(
+        retry_while_busy(|| async {
+            let response = client
+                .post(format!("{}/api/v1/repo/sync", server_url))
+                .send()
+                .await
+                .map_http_error("Failed to sync repository")?;
 
-    handle_empty_response(response, "Sync repository").await
+            handle_empty_response(response, "Sync repository").await
+        })
+        .await,
+    )
 }
 
 /// Create a new repository
@@ -239,6 +723,7 @@ pub async fn repository_sync(
 /// We just need to verify the request succeeded, no task ID is returned.
 #[tauri::command]
 pub async fn repository_create(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
     config: crate::types::RepositoryCreateRequest,
@@ -254,7 +739,13 @@ pub async fn repository_create(
 
     // API returns empty object on success, just validate response
     handle_empty_response(response, "Create repository").await?;
-    Ok("Repository created successfully".to_string())
+    // Client options may override the username and hostname
+    {
+        let mut manager_guard = manager.lock().unwrap();
+        manager_guard.clear_user_disconnect(&repo_id);
+        manager_guard.set_default_identity(&repo_id, None);
+    }
+    timer.finish(Ok("Repository created successfully".to_string()))
 }
 
 /// Check if repository exists
@@ -264,6 +755,7 @@ pub async fn repository_create(
 /// - Error with `{"code": "NOT_INITIALIZED", "error": "..."}` if repository doesn't exist
 #[tauri::command]
 pub async fn repository_exists(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
     storage: StorageConfig,
@@ -293,7 +785,7 @@ pub async fn repository_exists(
         if let Ok(err) = serde_json::from_str::<ErrorResponse>(&error_text) {
             if err.code.as_deref() == Some("NOT_INITIALIZED") {
                 // Repository location is accessible but not initialized - return false
-                return Ok(false);
+                return timer.finish(Ok(false));
             }
             // Return the detailed error message
             return Err(KopiaError::operation_failed(
@@ -309,7 +801,7 @@ pub async fn repository_exists(
     }
 
     // Success response is just an empty object {}, which means repository exists
-    Ok(true)
+    timer.finish(Ok(true))
 }
 
 /// Get available algorithms
@@ -318,11 +810,19 @@ pub async fn repository_exists(
 /// supported by the Kopia server for repository creation.
 #[tauri::command]
 pub async fn repository_get_algorithms(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
 ) -> Result<crate::types::AlgorithmsResponse> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(fetch_algorithms(&client, &server_url).await)
+}
 
+/// Fetch the algorithms the server supports
+async fn fetch_algorithms(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<crate::types::AlgorithmsResponse> {
     let response = client
         .get(format!("{}/api/v1/repo/algorithms", server_url))
         .send()
@@ -332,15 +832,175 @@ pub async fn repository_get_algorithms(
     handle_response(response, "Get algorithms").await
 }
 
+/// Short descriptions of Kopia's splitters, keyed by splitter ID
+const SPLITTER_DESCRIPTIONS: &[(&str, &str)] = &[
+    ("FIXED", "Legacy alias for FIXED-4M"),
+    ("DYNAMIC", "Legacy alias for DYNAMIC-4M-BUZHASH"),
+    (
+        "FIXED-1M",
+        "Fixed 1 MiB chunks; fast, but inserted data shifts every later chunk",
+    ),
+    (
+        "FIXED-2M",
+        "Fixed 2 MiB chunks; fast, but inserted data shifts every later chunk",
+    ),
+    (
+        "FIXED-4M",
+        "Fixed 4 MiB chunks; fast, but inserted data shifts every later chunk",
+    ),
+    (
+        "FIXED-8M",
+        "Fixed 8 MiB chunks; fast, but inserted data shifts every later chunk",
+    ),
+    (
+        "DYNAMIC-1M-BUZHASH",
+        "Content-defined ~1 MiB chunks (Buzhash); best deduplication of edited files",
+    ),
+    (
+        "DYNAMIC-2M-BUZHASH",
+        "Content-defined ~2 MiB chunks (Buzhash); good deduplication of edited files",
+    ),
+    (
+        "DYNAMIC-4M-BUZHASH",
+        "Content-defined ~4 MiB chunks (Buzhash); balanced deduplication and index size",
+    ),
+    (
+        "DYNAMIC-8M-BUZHASH",
+        "Content-defined ~8 MiB chunks (Buzhash); smallest index, suited to large files",
+    ),
+    (
+        "DYNAMIC-1M-RABINKARP",
+        "Content-defined ~1 MiB chunks (Rabin-Karp); slower than Buzhash",
+    ),
+    (
+        "DYNAMIC-2M-RABINKARP",
+        "Content-defined ~2 MiB chunks (Rabin-Karp); slower than Buzhash",
+    ),
+    (
+        "DYNAMIC-4M-RABINKARP",
+        "Content-defined ~4 MiB chunks (Rabin-Karp); slower than Buzhash",
+    ),
+    (
+        "DYNAMIC-8M-RABINKARP",
+        "Content-defined ~8 MiB chunks (Rabin-Karp); slower than Buzhash",
+    ),
+];
+
+/// List the server's splitters with descriptions and the recommended choice
+#[tauri::command]
+pub async fn splitter_algorithms_with_info(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<Vec<crate::types::SplitterInfo>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let algorithms = fetch_algorithms(&client, &server_url).await?;
+    timer.finish(Ok(annotate_splitters(&algorithms)))
+}
+
+/// Annotate the splitter list of an algorithms response, keeping the server's order
+pub(crate) fn annotate_splitters(
+    algorithms: &crate::types::AlgorithmsResponse,
+) -> Vec<crate::types::SplitterInfo> {
+    algorithms
+        .splitter
+        .iter()
+        .map(|option| {
+            let description = SPLITTER_DESCRIPTIONS
+                .iter()
+                .find(|(id, _)| id.eq_ignore_ascii_case(&option.id))
+                .map(|(_, description)| description.to_string())
+                .unwrap_or_else(|| {
+                    if option.id.starts_with("DYNAMIC") {
+                        "Content-defined chunks".to_string()
+                    } else if option.id.starts_with("FIXED") {
+                        "Fixed-size chunks".to_string()
+                    } else {
+                        String::new()
+                    }
+                });
+
+            crate::types::SplitterInfo {
+                recommended: option.id == algorithms.default_splitter,
+                deprecated: option.deprecated.unwrap_or(false),
+                id: option.id.clone(),
+                description,
+            }
+        })
+        .collect()
+}
+
 /// Update repository description
 #[tauri::command]
 pub async fn repository_update_description(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
     description: String,
 ) -> Result<()> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(post_description(&client, &server_url, &description).await)
+}
+
+/// Maximum length of a repository name (description)
+const MAX_REPOSITORY_NAME_LEN: usize = 128;
+
+/// Rename a repository
+///
+/// The name shown in the UI is the repository description, so this updates the
+/// description on the server and the locally cached display name together.
+/// Returns the updated repository entry.
+#[tauri::command]
+pub async fn repository_rename(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    new_name: String,
+) -> Result<RepositoryEntry> {
+    let name = validate_repository_name(&new_name)?;
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
 
+    post_description(&client, &server_url, &name).await?;
+
+    let mut entry = {
+        let mut manager_guard = manager.lock().unwrap();
+        manager_guard.set_display_name(&repo_id, &name);
+        manager_guard.repository_entry(&repo_id)
+    };
+    match fetch_repository_status(&client, &server_url).await {
+        Ok(status) => {
+            entry.connected = status.connected;
+            entry.storage = status.storage;
+        }
+        Err(e) => log::debug!("Failed to refresh status after rename: {}", e),
+    }
+    timer.finish(Ok(entry))
+}
+
+/// Trim a repository name and check it's non-empty and not too long
+pub(crate) fn validate_repository_name(name: &str) -> Result<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(KopiaError::invalid_input("name", "Name cannot be empty"));
+    }
+    if name.chars().count() > MAX_REPOSITORY_NAME_LEN {
+        return Err(KopiaError::invalid_input(
+            "name",
+            format!(
+                "Name must be at most {} characters",
+                MAX_REPOSITORY_NAME_LEN
+            ),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// Set the repository description
+async fn post_description(
+    client: &reqwest::Client,
+    server_url: &str,
+    description: &str,
+) -> Result<()> {
     let response = client
         .post(format!("{}/api/v1/repo/description", server_url))
         .json(&serde_json::json!({ "description": description }))
@@ -351,1097 +1011,6972 @@ pub async fn repository_update_description(
     handle_empty_response(response, "Update description").await
 }
 
-/// Get throttling limits for repository operations
+/// Set how long the repository format blob is cached, in seconds
+///
+/// The format blob is re-read from storage when the cache expires, which is slow
+/// on remote storage. The setting is saved to the repository config via
+/// `kopia repository set-client`; the running server picks it up the next time
+/// it opens the repository. The returned status reports the saved value.
 #[tauri::command]
-pub async fn repository_get_throttle(
+pub async fn repository_set_format_cache_duration(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-) -> Result<crate::types::ThrottleLimits> {
+    seconds: i64,
+) -> Result<RepositoryStatus> {
+    let arg = format_cache_duration_arg(seconds)?;
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
 
-    let response = client
-        .get(format!("{}/api/v1/repo/throttle", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to get throttle limits")?;
+    kopia_cli::run(
+        &config_file,
+        &["repository", "set-client", &arg],
+        "Set format cache duration",
+    )
+    .await?;
 
-    handle_response(response, "Get throttle limits").await
+    let mut status = fetch_repository_status(&client, &server_url).await?;
+    let config = read_config_file(&config_file, "Set format cache duration")?;
+    status.format_blob_cache_duration = read_format_blob_cache_duration(&config);
+
+    timer.finish(Ok(status))
 }
 
-/// Set throttling limits for repository operations
+/// Enable or disable permissive cache loading
+///
+/// With permissive loading, cache entries that fail to load are treated as
+/// missing and re-fetched from storage instead of failing the operation. This
+/// helps recover from a partially corrupt cache, but can also hide real cache
+/// or disk problems, so it's best turned off again once things work. Saved to
+/// the repository config via `kopia repository set-client`; the running server
+/// picks it up the next time it opens the repository. The returned status
+/// reports the saved value.
 #[tauri::command]
-pub async fn repository_set_throttle(
+pub async fn repository_set_permissive_cache(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    limits: crate::types::ThrottleLimits,
-) -> Result<()> {
+    enabled: bool,
+) -> Result<RepositoryStatus> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
 
-    let response = client
-        .put(format!("{}/api/v1/repo/throttle", server_url))
-        .json(&limits)
-        .send()
-        .await
-        .map_http_error("Failed to set throttle limits")?;
+    let flag = if enabled {
+        "--permissive-cache-loading"
+    } else {
+        "--disable-permissive-cache-loading"
+    };
+    kopia_cli::run(
+        &config_file,
+        &["repository", "set-client", flag],
+        "Set permissive cache loading",
+    )
+    .await?;
 
-    handle_empty_response(response, "Set throttle limits").await
+    let mut status = fetch_repository_status(&client, &server_url).await?;
+    let config = read_config_file(&config_file, "Set permissive cache loading")?;
+    status.permissive_cache_loading = read_permissive_cache_loading(&config);
+
+    timer.finish(Ok(status))
 }
 
-// ============================================================================
-// Snapshot Sources Commands
-// ============================================================================
+/// Smallest maximum pack size Kopia accepts, in MiB
+pub(crate) const MAX_PACK_SIZE_MIN_MB: i64 = 10;
+/// Largest maximum pack size Kopia accepts, in MiB
+pub(crate) const MAX_PACK_SIZE_MAX_MB: i64 = 120;
 
-/// List all snapshot sources
+/// Get the repository's maximum pack size in bytes
 ///
-/// Returns information about all backup sources in the repository, including last snapshot
-/// time, upload progress, next scheduled snapshot, and source path.
+/// None when the server doesn't report it (e.g. the repository isn't connected).
 #[tauri::command]
-pub async fn sources_list(
+pub async fn repository_get_max_pack_size(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-) -> Result<crate::types::SourcesResponse> {
+) -> Result<Option<i64>> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
-
-    let response = client
-        .get(format!("{}/api/v1/sources", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to list sources")?;
-
-    handle_response(response, "List sources").await
+    let status = fetch_repository_status(&client, &server_url).await?;
+    timer.finish(Ok(status.max_pack_size.map(i64::from)))
 }
 
-/// Create a snapshot source and optionally start a snapshot
+/// Change the repository's maximum pack size
+///
+/// `bytes` must be a whole number of MiB between `MAX_PACK_SIZE_MIN_MB` and
+/// `MAX_PACK_SIZE_MAX_MB`. The size is stored in the repository format blob
+/// via `kopia repository set-parameters` and applies to packs written after
+/// servers re-read the format, which may take until the format cache expires
+/// or the server restarts. Existing packs are not rewritten.
 #[tauri::command]
-pub async fn snapshot_create(
+pub async fn repository_set_max_pack_size(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    path: String,
-    user_name: Option<String>,
-    host: Option<String>,
-    create_snapshot: Option<bool>,
-    policy: Option<crate::types::PolicyDefinition>,
-) -> Result<crate::types::SourceInfo> {
+    bytes: i64,
+) -> Result<RepositoryStatus> {
+    let arg = max_pack_size_arg(bytes)?;
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
 
-    // First, resolve the path to get source info (userName@host)
-    log::info!("Resolving path: {}", path);
-    let source_info = {
-        let resolve_response = client
-            .post(format!("{}/api/v1/paths/resolve", server_url))
-            .json(&serde_json::json!({ "path": path }))
-            .send()
-            .await
-            .map_http_error("Failed to resolve path")?;
+    kopia_cli::run(
+        &config_file,
+        &["repository", "set-parameters", &arg],
+        "Set maximum pack size",
+    )
+    .await?;
 
-        let status = resolve_response.status();
-        log::info!("Resolve response status: {}", status);
+    timer.finish(fetch_repository_status(&client, &server_url).await)
+}
 
-        if !status.is_success() {
-            let error_text = resolve_response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-            log::error!("Failed to resolve path: {}", error_text);
-            return Err(KopiaError::operation_failed(
-                "path resolution",
-                format!("Failed to resolve path '{}': {}", path, error_text),
-            ));
-        }
-
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct ResolveResponse {
-            source: crate::types::SourceInfo,
-        }
-
-        let resolve_result: ResolveResponse = resolve_response
-            .json()
-            .await
-            .map_http_error("Failed to parse resolve response")?;
+/// Build the `--max-pack-size-mb` flag, rejecting sizes Kopia doesn't accept
+pub(crate) fn max_pack_size_arg(bytes: i64) -> Result<String> {
+    const MIB: i64 = 1 << 20;
 
-        resolve_result.source
-    };
-
-    // Note: userName and host are derived from repository client options by the server,
-    // so we only send path, createSnapshot, and policy as per official API spec.
-    // The user_name/host parameters are kept for logging purposes only.
-    let final_user_name = user_name.unwrap_or(source_info.user_name.clone());
-    let final_host = host.unwrap_or(source_info.host.clone());
+    if bytes % MIB != 0 {
+        return Err(KopiaError::invalid_input(
+            "bytes",
+            "Maximum pack size must be a whole number of MiB",
+        ));
+    }
+    let mb = bytes / MIB;
+    if !(MAX_PACK_SIZE_MIN_MB..=MAX_PACK_SIZE_MAX_MB).contains(&mb) {
+        return Err(KopiaError::invalid_input(
+            "bytes",
+            format!(
+                "Maximum pack size must be between {} and {} MiB",
+                MAX_PACK_SIZE_MIN_MB, MAX_PACK_SIZE_MAX_MB
+            ),
+        ));
+    }
+    Ok(format!("--max-pack-size-mb={}", mb))
+}
 
-    // Create source and optionally start snapshot
-    // API only expects: path, createSnapshot, policy
-    let should_create_snapshot = create_snapshot.unwrap_or(false);
-    let payload = serde_json::json!({
-        "path": source_info.path,
-        "createSnapshot": should_create_snapshot,
-        "policy": policy.unwrap_or_default()
-    });
+/// Open a filesystem repository's storage folder in the file manager
+///
+/// The storage path is read from the repository config. Other storage types
+/// (and repositories reached through a Kopia server) can't be browsed locally
+/// and return an error. Returns the opened path.
+#[tauri::command]
+pub async fn open_repository_storage(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<String> {
+    let config_file = get_config_file(&manager, &repo_id)?;
+    let config = read_config_file(&config_file, "Open repository storage")?;
+    let path = filesystem_storage_path(&config)?;
 
-    log::info!(
-        "Creating snapshot for {}@{}:{}",
-        final_user_name,
-        final_host,
-        source_info.path
-    );
-    log::debug!("Snapshot payload: {:?}", payload);
+    if !std::path::Path::new(&path).is_dir() {
+        return Err(KopiaError::operation_failed(
+            "Open repository storage",
+            format!("Storage folder {} doesn't exist or isn't mounted", path),
+        ));
+    }
 
-    let response = client
-        .post(format!("{}/api/v1/sources", server_url))
-        .json(&payload)
-        .send()
-        .await
-        .map_http_error("Failed to create snapshot")?;
+    open_in_file_manager(&path)?;
+    timer.finish(Ok(path))
+}
 
-    let status = response.status();
-    log::info!("Create snapshot response status: {}", status);
+/// Get the storage path of a filesystem repository from its config
+pub(crate) fn filesystem_storage_path(config: &str) -> Result<String> {
+    let config: serde_json::Value = serde_json::from_str(config)?;
 
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        log::error!("Failed to create snapshot: {}", error_text);
+    if config.get("apiServer").is_some() {
         return Err(KopiaError::operation_failed(
-            "snapshot creation",
-            format!(
-                "Failed to create snapshot for path '{}': {}",
-                path, error_text
-            ),
+            "Open repository storage",
+            "Repository is reached through a Kopia server, so its storage isn't locally browsable",
         ));
     }
 
-    // API returns {"snapshotted": bool}
-    #[derive(Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct CreateResponse {
-        snapshotted: bool,
+    let storage = config
+        .get("storage")
+        .ok_or(KopiaError::RepositoryNotConnected {
+            api_error_code: None,
+        })?;
+    match storage["type"].as_str() {
+        Some("filesystem") => storage["config"]["path"]
+            .as_str()
+            .filter(|path| !path.is_empty())
+            .map(String::from)
+            .ok_or_else(|| {
+                KopiaError::operation_failed(
+                    "Open repository storage",
+                    "Repository config has no storage path",
+                )
+            }),
+        storage_type => Err(KopiaError::operation_failed(
+            "Open repository storage",
+            format!(
+                "{} storage isn't locally browsable",
+                storage_type.unwrap_or("Unknown")
+            ),
+        )),
     }
+}
 
-    let result: CreateResponse = response
-        .json()
-        .await
-        .map_http_error("Failed to parse response")?;
+/// Open a folder with the platform's file manager
+fn open_in_file_manager(path: &str) -> Result<()> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
 
-    // Only check if snapshot was started if we requested it
-    if should_create_snapshot && !result.snapshotted {
-        return Err(KopiaError::operation_failed(
-            "snapshot creation",
-            format!("Snapshot was not started by server for path '{}'", path),
-        ));
-    }
+    let mut child = std::process::Command::new(program)
+        .arg(path)
+        .spawn()
+        .map_err(|e| {
+            KopiaError::operation_failed_with_details(
+                "Open repository storage",
+                format!("Failed to run {}", program),
+                e.to_string(),
+            )
+        })?;
 
-    Ok(source_info)
+    // Only reap the process: Explorer exits with a non-zero status even on success
+    std::thread::spawn(move || child.wait());
+    Ok(())
 }
 
-/// Start a snapshot upload for an existing source
-///
-/// This uses `/api/v1/sources/upload` which triggers a snapshot on an existing
-/// source. Unlike `snapshot_create`, this endpoint doesn't create the source
-/// if it doesn't exist.
+/// Read a repository config file
+fn read_config_file(config_file: &str, operation: &str) -> Result<String> {
+    std::fs::read_to_string(config_file).map_err(|e| {
+        KopiaError::operation_failed_with_details(
+            operation,
+            format!("Failed to read {}", config_file),
+            e.to_string(),
+        )
+    })
+}
+
+/// Get repository content statistics
 ///
-/// This is the correct endpoint to use when the user wants to manually trigger
-/// a backup on an existing backup source.
+/// The server API has no content statistics, so this runs
+/// `kopia content stats --raw` and parses its totals.
 #[tauri::command]
-pub async fn snapshot_upload(
+pub async fn content_stats(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: String,
-    host: String,
-    path: String,
-) -> Result<()> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+) -> Result<crate::types::RepositoryContentStats> {
+    let config_file = get_config_file(&manager, &repo_id)?;
+    let output = kopia_cli::run(
+        &config_file,
+        &["content", "stats", "--raw"],
+        "Get content statistics",
+    )
+    .await?;
 
-    let query_params = build_source_query(&user_name, &host, &path);
+    timer.finish(parse_content_stats(&output).ok_or_else(|| {
+        KopiaError::operation_failed_with_details(
+            "Get content statistics",
+            "Unexpected output from kopia content stats",
+            output,
+        )
+    }))
+}
 
-    let response = client
-        .post(format!(
-            "{}/api/v1/sources/upload{}",
-            server_url, query_params
-        ))
-        .send()
-        .await
-        .map_http_error("Failed to start snapshot upload")?;
+/// Parse the totals printed by `kopia content stats --raw`
+///
+/// ```text
+/// Count: 1234
+/// Total Bytes: 5678901
+/// Total Packed: 3456789 (compression 39.1%)
+/// ```
+pub(crate) fn parse_content_stats(output: &str) -> Option<crate::types::RepositoryContentStats> {
+    // Indented lines (per compression method) repeat the labels in lowercase
+    let total = |label: &str| {
+        output.lines().find_map(|line| {
+            line.strip_prefix(label)?
+                .split_whitespace()
+                .next()?
+                .parse::<u64>()
+                .ok()
+        })
+    };
+
+    let count = total("Count:")?;
+    let total_original_size = total("Total Bytes:")?;
+    let total_packed_size = total("Total Packed:")?;
+
+    Some(crate::types::RepositoryContentStats {
+        count,
+        total_packed_size,
+        total_original_size,
+        dedup_ratio: dedup_ratio(total_original_size, total_packed_size),
+    })
+}
 
-    handle_empty_response(response, "Start snapshot upload").await
+/// Original size divided by packed size (1.0 for an empty repository)
+pub(crate) fn dedup_ratio(original_size: u64, packed_size: u64) -> f64 {
+    if packed_size == 0 {
+        return 1.0;
+    }
+    original_size as f64 / packed_size as f64
 }
 
-/// Cancel a snapshot
+/// List the contents stored in a pack blob, for diagnosing storage issues
+///
+/// Requires developer mode. The server API can't introspect blobs, so this
+/// runs `kopia content list --json` and keeps the index entries pointing into
+/// `blob_id`, ordered by offset. The whole index is read, which can take a
+/// while on large repositories. Deleted contents aren't listed; an unknown or
+/// non-pack blob returns an empty list.
 #[tauri::command]
-pub async fn snapshot_cancel(
+pub async fn blob_contents(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
     repo_id: String,
-    user_name: String,
-    host: String,
-    path: String,
-) -> Result<()> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    blob_id: String,
+) -> Result<Vec<crate::types::BlobContent>> {
+    if !settings.lock().unwrap().get().developer_mode {
+        return Err(KopiaError::operation_failed(
+            "List blob contents",
+            "Blob introspection requires developer mode",
+        ));
+    }
+    validate_blob_id(&blob_id)?;
 
-    let query_params = build_source_query(&user_name, &host, &path);
+    let config_file = get_config_file(&manager, &repo_id)?;
+    let contents: Vec<crate::types::ContentIndexEntry> =
+        kopia_cli::run_json(&config_file, &["content", "list"], "List blob contents").await?;
 
-    let response = client
-        .post(format!(
-            "{}/api/v1/sources/cancel{}",
-            server_url, query_params
-        ))
-        .send()
-        .await
-        .map_http_error("Failed to cancel snapshot")?;
+    timer.finish(Ok(pack_contents(contents, &blob_id)))
+}
 
-    handle_empty_response(response, "Cancel snapshot").await
+/// Reject blob IDs that aren't plain identifiers (and could be read as flags)
+pub(crate) fn validate_blob_id(blob_id: &str) -> Result<()> {
+    let valid = !blob_id.is_empty()
+        && !blob_id.starts_with('-')
+        && blob_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !valid {
+        return Err(KopiaError::invalid_input(
+            "blobId",
+            format!("Invalid blob ID '{}'", blob_id),
+        ));
+    }
+    Ok(())
 }
 
-/// Pause a snapshot source
+/// Contents stored in `blob_id`, ordered by offset
+pub(crate) fn pack_contents(
+    contents: Vec<crate::types::ContentIndexEntry>,
+    blob_id: &str,
+) -> Vec<crate::types::BlobContent> {
+    let mut contents: Vec<crate::types::BlobContent> = contents
+        .into_iter()
+        .filter(|entry| entry.pack_file == blob_id && !entry.deleted)
+        .map(|entry| crate::types::BlobContent {
+            content_id: entry.content_id,
+            length: entry.length,
+            offset: entry.pack_offset,
+            original_length: entry.original_length,
+        })
+        .collect();
+    contents.sort_by_key(|content| content.offset);
+    contents
+}
+
+/// Get throttling limits for repository operations
 #[tauri::command]
-pub async fn snapshot_pause(
+pub async fn repository_get_throttle(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: String,
-    host: String,
-    path: String,
-) -> Result<crate::types::MultipleSourceActionResponse> {
+) -> Result<crate::types::ThrottleLimits> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(fetch_throttle(&client, &server_url).await)
+}
 
-    let query_params = build_source_query(&user_name, &host, &path);
-
+/// Fetch the repository's throttling limits
+pub(crate) async fn fetch_throttle(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<crate::types::ThrottleLimits> {
     let response = client
-        .post(format!(
-            "{}/api/v1/control/pause-source{}",
-            server_url, query_params
-        ))
+        .get(format!("{}/api/v1/repo/throttle", server_url))
         .send()
         .await
-        .map_http_error("Failed to pause snapshot")?;
+        .map_http_error("Failed to get throttle limits")?;
 
-    handle_response(response, "Pause snapshot").await
+    handle_response(response, "Get throttle limits").await
 }
 
-/// Resume a paused snapshot source
+/// Set throttling limits for repository operations
 #[tauri::command]
-pub async fn snapshot_resume(
+pub async fn repository_set_throttle(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: String,
-    host: String,
-    path: String,
-) -> Result<crate::types::MultipleSourceActionResponse> {
+    limits: crate::types::ThrottleLimits,
+) -> Result<()> {
+    validate_throttle_limits(&limits)?;
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(put_throttle(&client, &server_url, &limits).await)
+}
 
-    let query_params = build_source_query(&user_name, &host, &path);
+/// Check no throttle limit is negative (or not a number)
+pub(crate) fn validate_throttle_limits(limits: &crate::types::ThrottleLimits) -> Result<()> {
+    let rates = [
+        ("readsPerSecond", limits.reads_per_second),
+        ("writesPerSecond", limits.writes_per_second),
+        ("listsPerSecond", limits.lists_per_second),
+        (
+            "maxUploadSpeedBytesPerSecond",
+            limits.max_upload_speed_bytes_per_second,
+        ),
+        (
+            "maxDownloadSpeedBytesPerSecond",
+            limits.max_download_speed_bytes_per_second,
+        ),
+    ];
+    for (field, value) in rates {
+        if value.is_some_and(|v| !(v >= 0.0 && v.is_finite())) {
+            return Err(KopiaError::invalid_input(
+                field,
+                "Limit must be a non-negative number",
+            ));
+        }
+    }
+
+    let counts = [
+        ("concurrentReads", limits.concurrent_reads),
+        ("concurrentWrites", limits.concurrent_writes),
+    ];
+    for (field, value) in counts {
+        if value.is_some_and(|v| v < 0) {
+            return Err(KopiaError::invalid_input(field, "Limit can't be negative"));
+        }
+    }
+
+    Ok(())
+}
 
+/// Replace the repository's throttling limits
+async fn put_throttle(
+    client: &reqwest::Client,
+    server_url: &str,
+    limits: &crate::types::ThrottleLimits,
+) -> Result<()> {
     let response = client
-        .post(format!(
-            "{}/api/v1/control/resume-source{}",
-            server_url, query_params
-        ))
+        .put(format!("{}/api/v1/repo/throttle", server_url))
+        .json(limits)
         .send()
         .await
-        .map_http_error("Failed to resume snapshot")?;
+        .map_http_error("Failed to set throttle limits")?;
 
-    handle_response(response, "Resume snapshot").await
+    handle_empty_response(response, "Set throttle limits").await
 }
 
 // ============================================================================
-// Snapshot History Commands
+// Snapshot Sources Commands
 // ============================================================================
 
-/// List snapshots for a source
+/// List all snapshot sources
+///
+/// Returns information about all backup sources in the repository, including last snapshot
+/// time, upload progress, next scheduled snapshot, and source path.
 #[tauri::command]
-pub async fn snapshots_list(
+pub async fn sources_list(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: String,
-    host: String,
-    path: String,
-    all: bool,
-) -> Result<crate::types::SnapshotsResponse> {
+) -> Result<crate::types::SourcesResponse> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let sources = retry_request(|| fetch_sources(&client, &server_url)).await?;
 
-    let query_params = format!(
-        "{}&all={}",
-        build_source_query(&user_name, &host, &path),
-        if all { "1" } else { "0" }
+    let manager_guard = manager.lock().unwrap();
+    manager_guard.set_default_identity(&repo_id, Some(SourceIdentity::from(&sources)));
+    manager_guard.record_sources(
+        &repo_id,
+        sources.sources.clone(),
+        &chrono::Utc::now().to_rfc3339(),
     );
+    timer.finish(Ok(sources))
+}
 
-    let response = client
-        .get(format!("{}/api/v1/snapshots{}", server_url, query_params))
-        .send()
-        .await
-        .map_http_error("Failed to list snapshots")?;
-
-    handle_response(response, "List snapshots").await
+/// Get the sources and tasks last fetched from a repository's server
+///
+/// Kopia has no push API; the UI polls `sources_list` and `tasks_list`, and
+/// their latest results are kept for the server session. A newly mounted view
+/// can render this right away instead of waiting for the next poll. Returns
+/// empty lists (and an empty `receivedAt`) if nothing was fetched yet.
+#[tauri::command]
+pub async fn last_known_state(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::LastKnownState> {
+    timer.finish(Ok(manager.lock().unwrap().last_known_state(&repo_id)))
 }
 
-/// Edit snapshot metadata
+/// Get the default `user@host` identity for new sources
+///
+/// Taken from the server's sources list and cached for the server session, so
+/// forms can be prefilled without resolving a path. The cache is cleared when
+/// the server restarts or the repository is (re)connected, since client options
+/// may override the identity.
 #[tauri::command]
-pub async fn snapshot_edit(
+pub async fn get_default_source_identity(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    request: crate::types::SnapshotEditRequest,
-) -> Result<()> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+) -> Result<SourceIdentity> {
+    if let Some(identity) = manager.lock().unwrap().default_identity(&repo_id) {
+        return timer.finish(Ok(identity));
+    }
 
-    let response = client
-        .post(format!("{}/api/v1/snapshots/edit", server_url))
-        .json(&request)
-        .send()
-        .await
-        .map_http_error("Failed to edit snapshot")?;
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let identity = SourceIdentity::from(&fetch_sources(&client, &server_url).await?);
 
-    handle_empty_response(response, "Edit snapshot").await
+    manager
+        .lock()
+        .unwrap()
+        .set_default_identity(&repo_id, Some(identity.clone()));
+    timer.finish(Ok(identity))
 }
 
-/// Delete snapshots
+/// Create a snapshot source and optionally start a snapshot
 #[tauri::command]
-pub async fn snapshot_delete(
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn snapshot_create(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: String,
-    host: String,
     path: String,
-    manifest_ids: Vec<String>,
-) -> Result<i64> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
-
-    // API expects source info + manifest IDs
-    let payload = crate::types::SnapshotDeleteRequest {
-        source: crate::types::SourceInfo {
-            user_name,
-            host,
-            path,
-        },
-        snapshot_manifest_ids: manifest_ids,
-        delete_source_and_policy: Some(false),
-    };
+    user_name: Option<String>,
+    host: Option<String>,
+    create_snapshot: Option<bool>,
+    policy: Option<crate::types::PolicyDefinition>,
+) -> Result<crate::types::SourceInfo> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
 
-    let response = client
-        .post(format!("{}/api/v1/snapshots/delete", server_url))
-        .json(&payload)
-        .send()
-        .await
-        .map_http_error("Failed to delete snapshots")?;
+    // First, resolve the path to get source info (userName@host)
+    let source_info = resolve_source(&client, &server_url, &path).await?;
+    let _guard = app_state
+        .lock_resource(&repo_id, &(&source_info).into())
+        .await;
 
-    #[derive(Deserialize)]
-    struct DeleteResponse {
-        deleted: i64,
-    }
+    // Note: userName and host are derived from repository client options by the server,
+    // so we only send path, createSnapshot, and policy as per official API spec.
+    // The user_name/host parameters are kept for logging purposes only.
+    let final_user_name = user_name.unwrap_or(source_info.user_name.clone());
+    let final_host = host.unwrap_or(source_info.host.clone());
+
+    log::info!(
+        "Creating snapshot for {}@{}:{}",
+        final_user_name,
+        final_host,
+        source_info.path
+    );
 
-    let result: DeleteResponse = handle_response(response, "Delete snapshots").await?;
+    let should_create_snapshot = create_snapshot.unwrap_or(false);
+    post_source(
+        &client,
+        &server_url,
+        &source_info.path,
+        should_create_snapshot,
+        &policy.unwrap_or_default(),
+    )
+    .await?;
 
-    Ok(result.deleted)
+    timer.finish(Ok(source_info))
 }
 
-// ============================================================================
-// Snapshot Browsing & Restore Commands
-// ============================================================================
+/// How long `snapshot_create_with_override` waits for the upload to start
+const POLICY_OVERRIDE_START_TIMEOUT_SECS: u64 = 60;
 
-/// Browse directory contents in a snapshot
+/// Snapshot a path once with a policy override
+///
+/// The fields set in `policy_override` are layered over the source's defined
+/// policy for this snapshot only; the stored policy is not changed. Kopia has
+/// no per-snapshot policy: the create endpoint's `policy` field is saved as
+/// the source's policy, and `kopia snapshot create` has no flags for policy
+/// settings such as ignore rules. So the merged policy is stored just long
+/// enough for the upload to load it, and the previous policy is then put back
+/// (or deleted, if the source had none). Scheduled snapshots of the same
+/// source starting in that window would also use it.
+///
+/// The previous policy is put back whether or not the upload started. Fails
+/// if the upload doesn't start within `POLICY_OVERRIDE_START_TIMEOUT_SECS`.
+/// The policy to put back is also written to a journal next to the
+/// repository config first, so it's still restored on the next start if the
+/// app exits before putting it back (see `restore_pending_policy`).
 #[tauri::command]
-pub async fn object_browse(
+pub async fn snapshot_create_with_override(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    object_id: String,
-) -> Result<crate::types::DirectoryObject> {
+    path: String,
+    policy_override: crate::types::PolicyDefinition,
+) -> Result<crate::types::SourceInfo> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = resolve_source(&client, &server_url, &path).await?;
+    let _guard = app_state.lock_resource(&repo_id, &(&source).into()).await;
+
+    let original = fetch_defined_policy(
+        &client,
+        &server_url,
+        Some(&source.user_name),
+        Some(&source.host),
+        Some(&source.path),
+    )
+    .await?;
+    let merged = overlay_policy(&original.clone().unwrap_or_default(), &policy_override)?;
+    let previous_snapshot = find_snapshot_source(&client, &server_url, &source)
+        .await?
+        .and_then(|status| status.last_snapshot)
+        .map(|snapshot| snapshot.id);
+
+    let journal = policy_restore_journal_path(&manager, &repo_id);
+    let pending = PendingPolicyRestore {
+        source: source.clone(),
+        policy: original,
+    };
+    write_policy_restore_journal(&journal, &pending)?;
 
-    let response = client
-        .get(format!("{}/api/v1/objects/{}", server_url, object_id))
-        .send()
-        .await
-        .map_http_error("Failed to browse object")?;
-
-    handle_response(response, "Browse object").await
-}
-
-/// Download a single file from a snapshot
-#[tauri::command]
-pub async fn object_download(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-    object_id: String,
-    filename: String,
-    target_path: String,
-) -> Result<()> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    log::info!(
+        "Creating snapshot with policy override for {}@{}:{}",
+        source.user_name,
+        source.host,
+        source.path
+    );
+    let started = match post_source(&client, &server_url, &source.path, true, &merged).await {
+        Ok(()) => {
+            wait_for_upload_start(&client, &server_url, &source, previous_snapshot.as_deref()).await
+        }
+        Err(e) => Err(e),
+    };
 
-    let query_params = format!("?fname={}", urlencoding::encode(&filename));
+    let restored = restore_policy(&client, &server_url, &pending).await;
+    match &restored {
+        Ok(()) => {
+            if let Err(e) = std::fs::remove_file(&journal) {
+                log::warn!("Failed to remove {}: {}", journal.display(), e);
+            }
+        }
+        Err(e) => log::error!("Failed to restore policy of '{}': {}", source.path, e),
+    }
 
-    let response = client
-        .get(format!(
-            "{}/api/v1/objects/{}{}",
-            server_url, object_id, query_params
-        ))
-        .send()
-        .await
-        .map_http_error("Failed to download object")?;
+    started?;
+    restored?;
+    timer.finish(Ok(source))
+}
 
-    // Check status before reading bytes
-    let status = response.status();
-    if !status.is_success() {
-        return Err(http_request_failed(
-            "Failed to download object",
-            status.as_u16(),
-        ));
-    }
+/// Policy to put back after `snapshot_create_with_override`
+#[derive(Debug, Clone, serde::Serialize, Deserialize)]
+pub(crate) struct PendingPolicyRestore {
+    pub source: crate::types::SourceInfo,
+    /// The source's defined policy, `None` if it had none
+    pub policy: Option<crate::types::PolicyDefinition>,
+}
 
-    // Get the file content
-    let bytes = response
-        .bytes()
-        .await
-        .map_http_error("Failed to read response")?;
+/// Journal of the policy `snapshot_create_with_override` has to put back
+fn policy_restore_journal_path(manager: &ServerManagerState, repo_id: &str) -> std::path::PathBuf {
+    std::path::Path::new(manager.lock().unwrap().config_dir())
+        .join(format!("{}.policy-restore.json", repo_id))
+}
 
-    // Write to target path
-    std::fs::write(&target_path, bytes).map_err(|e| {
+/// Write a policy restore journal
+pub(crate) fn write_policy_restore_journal(
+    path: &std::path::Path,
+    pending: &PendingPolicyRestore,
+) -> Result<()> {
+    std::fs::write(path, serde_json::to_vec(pending)?).map_err(|e| {
         KopiaError::operation_failed(
             "file write",
-            format!("Failed to write to '{}': {}", target_path, e),
+            format!("Failed to write '{}': {}", path.display(), e),
         )
-    })?;
+    })
+}
 
-    Ok(())
+/// Read a policy restore journal, `None` if there is none
+pub(crate) fn read_policy_restore_journal(
+    path: &std::path::Path,
+) -> Result<Option<PendingPolicyRestore>> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(KopiaError::operation_failed(
+            "file read",
+            format!("Failed to read '{}': {}", path.display(), e),
+        )),
+    }
 }
 
-/// Start a restore operation
-#[tauri::command]
-pub async fn restore_start(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-    request: crate::types::RestoreRequest,
-) -> Result<String> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+/// Put back a source's previous policy, deleting it if there was none
+async fn restore_policy(
+    client: &reqwest::Client,
+    server_url: &str,
+    pending: &PendingPolicyRestore,
+) -> Result<()> {
+    let source = &pending.source;
+    let (user_name, host, path) = (
+        Some(source.user_name.as_str()),
+        Some(source.host.as_str()),
+        Some(source.path.as_str()),
+    );
+    match &pending.policy {
+        Some(policy) => put_policy(client, server_url, user_name, host, path, policy).await,
+        None => match delete_policy(client, server_url, user_name, host, path).await {
+            Err(KopiaError::NotFound { .. }) | Err(KopiaError::PolicyNotFound { .. }) => Ok(()),
+            result => result,
+        },
+    }
+}
 
-    let response = client
-        .post(format!("{}/api/v1/restore", server_url))
-        .json(&request)
-        .send()
-        .await
-        .map_http_error("Failed to start restore")?;
+/// How long `restore_pending_policy` waits for the repository to open
+const POLICY_RESTORE_OPEN_TIMEOUT_SECS: u64 = 60;
 
-    #[derive(Deserialize)]
-    struct RestoreResponse {
-        id: String,
-    }
+/// Put back a policy left over by an interrupted `snapshot_create_with_override`
+///
+/// Called once a repository's server has started. The journal is kept if the
+/// repository doesn't open or the policy can't be restored, so the next start
+/// tries again.
+pub(crate) async fn restore_pending_policy(manager: &ServerManagerState, repo_id: &str) {
+    let journal = policy_restore_journal_path(manager, repo_id);
+    let pending = match read_policy_restore_journal(&journal) {
+        Ok(Some(pending)) => pending,
+        Ok(None) => return,
+        Err(e) => {
+            log::error!("{}", e);
+            return;
+        }
+    };
+    let Some((server_url, client)) = ({
+        let manager = manager.lock().unwrap();
+        manager
+            .get_server_url(repo_id)
+            .zip(manager.get_http_client(repo_id))
+    }) else {
+        return;
+    };
 
-    let result: RestoreResponse = handle_response(response, "Start restore").await?;
+    let timeout = std::time::Duration::from_secs(POLICY_RESTORE_OPEN_TIMEOUT_SECS);
+    match wait_for_repository_open(&client, &server_url, timeout).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return,
+        Err(e) => {
+            log::warn!(
+                "Can't restore policy of '{}' yet: {}",
+                pending.source.path,
+                e
+            );
+            return;
+        }
+    }
 
-    Ok(result.id)
+    log::info!(
+        "Restoring policy of '{}' left by an interrupted snapshot",
+        pending.source.path
+    );
+    match restore_policy(&client, &server_url, &pending).await {
+        Ok(()) => {
+            if let Err(e) = std::fs::remove_file(&journal) {
+                log::warn!("Failed to remove {}: {}", journal.display(), e);
+            }
+        }
+        Err(e) => log::error!(
+            "Failed to restore policy of '{}': {}",
+            pending.source.path,
+            e
+        ),
+    }
 }
 
-/// Mount a snapshot
-#[tauri::command]
-pub async fn mount_snapshot(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-    root: String,
-) -> Result<String> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+/// Layer the fields set in `overlay` over `base`
+///
+/// Nested sections are merged field by field, so an override that only sets
+/// `files.ignore` keeps the base policy's other `files` settings.
+pub(crate) fn overlay_policy(
+    base: &crate::types::PolicyDefinition,
+    overlay: &crate::types::PolicyDefinition,
+) -> Result<crate::types::PolicyDefinition> {
+    fn merge(base: &mut serde_json::Value, overlay: serde_json::Value) {
+        match (base, overlay) {
+            (serde_json::Value::Object(base), serde_json::Value::Object(overlay)) => {
+                for (key, value) in overlay {
+                    if value.is_null() {
+                        continue;
+                    }
+                    match base.get_mut(&key) {
+                        Some(existing) => merge(existing, value),
+                        None => {
+                            base.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (base, overlay) => *base = overlay,
+        }
+    }
 
-    let response = client
-        .post(format!("{}/api/v1/mounts", server_url))
-        .json(&serde_json::json!({ "root": root }))
-        .send()
-        .await
-        .map_http_error("Failed to mount snapshot")?;
+    let mut merged = serde_json::to_value(base)?;
+    merge(&mut merged, serde_json::to_value(overlay)?);
+    Ok(serde_json::from_value(merged)?)
+}
 
-    let result: crate::types::MountResponse = handle_response(response, "Mount snapshot").await?;
+/// Wait until the server has started uploading a snapshot of `source`
+///
+/// Gives up after `POLICY_OVERRIDE_START_TIMEOUT_SECS`, or once an upload was
+/// seen and the source went idle again.
+async fn wait_for_upload_start(
+    client: &reqwest::Client,
+    server_url: &str,
+    source: &crate::types::SourceInfo,
+    previous_snapshot: Option<&str>,
+) -> Result<()> {
+    let deadline = std::time::Instant::now()
+        + std::time::Duration::from_secs(POLICY_OVERRIDE_START_TIMEOUT_SECS);
+    let mut saw_uploading = false;
+
+    while std::time::Instant::now() < deadline {
+        if let Some(status) = find_snapshot_source(client, server_url, source).await? {
+            if upload_started(&status, previous_snapshot) {
+                return Ok(());
+            }
+            if status.status == "UPLOADING" {
+                saw_uploading = true;
+            } else if saw_uploading {
+                return Ok(());
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
 
-    Ok(result.path)
+    Err(KopiaError::operation_failed(
+        "snapshot creation",
+        format!(
+            "Snapshot of '{}' didn't start within {}s",
+            source.path, POLICY_OVERRIDE_START_TIMEOUT_SECS
+        ),
+    ))
 }
 
-/// List all mounted snapshots
+/// Check whether an upload has loaded its policy and begun reading files
 ///
-/// Returns all currently mounted snapshots with their mount paths and root object IDs.
-/// Mounted snapshots appear as local filesystems for easy file browsing and restoration.
-#[tauri::command]
-pub async fn mounts_list(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-) -> Result<crate::types::MountsResponse> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
-
-    let response = client
-        .get(format!("{}/api/v1/mounts", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to list mounts")?;
+/// Kopia reports a source as uploading slightly before the policy is read, so
+/// the upload only counts as started once its progress shows a directory or
+/// file, or a new snapshot has completed.
+pub(crate) fn upload_started(
+    status: &crate::types::SnapshotSource,
+    previous_snapshot: Option<&str>,
+) -> bool {
+    let last_snapshot = status.last_snapshot.as_ref().map(|s| s.id.as_str());
+    if last_snapshot.is_some() && last_snapshot != previous_snapshot {
+        return true;
+    }
 
-    handle_response(response, "List mounts").await
+    status.status == "UPLOADING"
+        && status.upload.as_ref().is_some_and(|upload| {
+            !upload.directory.is_empty()
+                || upload.hashed_files + upload.cached_files + upload.excluded_files > 0
+        })
 }
 
-/// Unmount a snapshot
-#[tauri::command]
-pub async fn mount_unmount(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-    object_id: String,
-) -> Result<()> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+/// Find a source's status in the server's sources list
+async fn find_snapshot_source(
+    client: &reqwest::Client,
+    server_url: &str,
+    source: &crate::types::SourceInfo,
+) -> Result<Option<crate::types::SnapshotSource>> {
+    Ok(fetch_sources(client, server_url)
+        .await?
+        .sources
+        .into_iter()
+        .find(|status| status.source == *source))
+}
 
-    let response = client
-        .delete(format!("{}/api/v1/mounts/{}", server_url, object_id))
+/// Resolve a local path to the source it would be snapshotted as
+async fn resolve_source(
+    client: &reqwest::Client,
+    server_url: &str,
+    path: &str,
+) -> Result<crate::types::SourceInfo> {
+    log::info!("Resolving path: {}", path);
+    let resolve_response = client
+        .post(format!("{}/api/v1/paths/resolve", server_url))
+        .json(&serde_json::json!({ "path": path }))
         .send()
         .await
-        .map_http_error("Failed to unmount snapshot")?;
+        .map_http_error("Failed to resolve path")?;
 
-    handle_empty_response(response, "Unmount snapshot").await
-}
+    let status = resolve_response.status();
+    log::info!("Resolve response status: {}", status);
 
-// ============================================================================
-// Policy Commands
-// ============================================================================
+    if !status.is_success() {
+        let error_text = resolve_response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        log::error!("Failed to resolve path: {}", error_text);
+        return Err(KopiaError::operation_failed(
+            "path resolution",
+            format!("Failed to resolve path '{}': {}", path, error_text),
+        ));
+    }
 
-/// List all policies
-///
-/// Returns all backup policies in the repository with their inheritance hierarchy.
-/// Policies control retention, scheduling, compression, and other backup behavior.
-#[tauri::command]
-pub async fn policies_list(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-) -> Result<crate::types::PoliciesResponse> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ResolveResponse {
+        source: crate::types::SourceInfo,
+    }
 
-    let response = client
-        .get(format!("{}/api/v1/policies", server_url))
-        .send()
+    let resolve_result: ResolveResponse = resolve_response
+        .json()
         .await
-        .map_http_error("Failed to list policies")?;
+        .map_http_error("Failed to parse resolve response")?;
 
-    handle_response(response, "List policies").await
+    Ok(resolve_result.source)
 }
 
-/// Get policy for a specific target
-#[tauri::command]
-pub async fn policy_get(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-    user_name: Option<String>,
-    host: Option<String>,
-    path: Option<String>,
-) -> Result<crate::types::PolicyWithTarget> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
-
-    let query_string = build_policy_query(user_name.as_deref(), host.as_deref(), path.as_deref());
+/// Create a source, saving `policy` as its policy, and optionally start a snapshot
+async fn post_source(
+    client: &reqwest::Client,
+    server_url: &str,
+    path: &str,
+    create_snapshot: bool,
+    policy: &crate::types::PolicyDefinition,
+) -> Result<()> {
+    // API only expects: path, createSnapshot, policy
+    let payload = serde_json::json!({
+        "path": path,
+        "createSnapshot": create_snapshot,
+        "policy": policy
+    });
+    log::debug!("Snapshot payload: {:?}", payload);
 
     let response = client
-        .get(format!("{}/api/v1/policy{}", server_url, query_string))
+        .post(format!("{}/api/v1/sources", server_url))
+        .json(&payload)
         .send()
         .await
-        .map_http_error("Failed to get policy")?;
+        .map_http_error("Failed to create snapshot")?;
 
-    handle_response(response, "Get policy").await
-}
+    let status = response.status();
+    log::info!("Create snapshot response status: {}", status);
 
-/// Resolve effective policy with inheritance
-#[tauri::command]
-pub async fn policy_resolve(
-    manager: State<'_, ServerManagerState>,
-    repo_id: String,
-    user_name: Option<String>,
-    host: Option<String>,
-    path: Option<String>,
-    updates: Option<crate::types::PolicyDefinition>,
-) -> Result<crate::types::ResolvedPolicyResponse> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        log::error!("Failed to create snapshot: {}", error_text);
+        return Err(KopiaError::operation_failed(
+            "snapshot creation",
+            format!(
+                "Failed to create snapshot for path '{}': {}",
+                path, error_text
+            ),
+        ));
+    }
 
-    let query_string = build_policy_query(user_name.as_deref(), host.as_deref(), path.as_deref());
+    // API returns {"snapshotted": bool}
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CreateResponse {
+        snapshotted: bool,
+    }
 
-    // Build request payload - KopiaUI always sends updates field and numUpcomingSnapshotTimes
-    let mut payload = serde_json::Map::new();
+    let result: CreateResponse = response
+        .json()
+        .await
+        .map_http_error("Failed to parse response")?;
 
-    if let Some(upd) = updates {
-        let value = serde_json::to_value(upd)?;
-        payload.insert("updates".to_string(), value);
-    } else {
-        // Send null for updates when just fetching (not modifying)
-        payload.insert("updates".to_string(), serde_json::Value::Null);
+    // Only check if snapshot was started if we requested it
+    if create_snapshot && !result.snapshotted {
+        return Err(KopiaError::operation_failed(
+            "snapshot creation",
+            format!("Snapshot was not started by server for path '{}'", path),
+        ));
     }
 
-    // Always request upcoming snapshot times
-    payload.insert("numUpcomingSnapshotTimes".to_string(), serde_json::json!(5));
+    Ok(())
+}
+
+/// Start a snapshot upload for an existing source
+///
+/// This uses `/api/v1/sources/upload` which triggers a snapshot on an existing
+/// source. Unlike `snapshot_create`, this endpoint doesn't create the source
+/// if it doesn't exist.
+///
+/// This is the correct endpoint to use when the user wants to manually trigger
+/// a backup on an existing backup source.
+#[tauri::command]
+pub async fn snapshot_upload(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(&repo_id, &source_target(&user_name, &host, &path))
+        .await;
+
+    let query_params = build_source_query(&user_name, &host, &path);
 
     let response = client
         .post(format!(
-            "{}/api/v1/policy/resolve{}",
-            server_url, query_string
+            "{}/api/v1/sources/upload{}",
+            server_url, query_params
         ))
-        .json(&payload)
         .send()
         .await
-        .map_http_error("Failed to resolve policy")?;
+        .map_http_error("Failed to start snapshot upload")?;
 
-    handle_response(response, "Resolve policy").await
+    timer.finish(handle_empty_response(response, "Start snapshot upload").await)
 }
 
-/// Set/update policy
+/// Cancel a snapshot
 #[tauri::command]
-pub async fn policy_set(
+pub async fn snapshot_cancel(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: Option<String>,
-    host: Option<String>,
-    path: Option<String>,
-    policy: crate::types::PolicyDefinition,
+    user_name: String,
+    host: String,
+    path: String,
 ) -> Result<()> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
 
-    let query_string = build_policy_query(user_name.as_deref(), host.as_deref(), path.as_deref());
+    let query_params = build_source_query(&user_name, &host, &path);
 
     let response = client
-        .put(format!("{}/api/v1/policy{}", server_url, query_string))
-        .json(&policy) // API expects policy directly, not wrapped
+        .post(format!(
+            "{}/api/v1/sources/cancel{}",
+            server_url, query_params
+        ))
         .send()
         .await
-        .map_http_error("Failed to set policy")?;
+        .map_http_error("Failed to cancel snapshot")?;
 
-    handle_empty_response(response, "Set policy").await
+    timer.finish(handle_empty_response(response, "Cancel snapshot").await)
 }
 
-/// Delete policy (revert to inherited)
+/// Pause a snapshot source
 #[tauri::command]
-pub async fn policy_delete(
+pub async fn snapshot_pause(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    user_name: Option<String>,
-    host: Option<String>,
-    path: Option<String>,
-) -> Result<()> {
+    user_name: String,
+    host: String,
+    path: String,
+) -> Result<crate::types::MultipleSourceActionResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    timer.finish(set_source_paused(&client, &server_url, &source, true).await)
+}
+
+/// Resume a paused snapshot source
+#[tauri::command]
+pub async fn snapshot_resume(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+) -> Result<crate::types::MultipleSourceActionResponse> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    timer.finish(set_source_paused(&client, &server_url, &source, false).await)
+}
 
-    let query_string = build_policy_query(user_name.as_deref(), host.as_deref(), path.as_deref());
+/// Pause or resume one snapshot source
+pub(crate) async fn set_source_paused(
+    client: &reqwest::Client,
+    server_url: &str,
+    source: &crate::types::SourceInfo,
+    paused: bool,
+) -> Result<crate::types::MultipleSourceActionResponse> {
+    let (action, operation) = if paused {
+        ("pause-source", "Pause snapshot")
+    } else {
+        ("resume-source", "Resume snapshot")
+    };
+    let query_params = build_source_query(&source.user_name, &source.host, &source.path);
 
     let response = client
-        .delete(format!("{}/api/v1/policy{}", server_url, query_string))
+        .post(format!(
+            "{}/api/v1/control/{}{}",
+            server_url, action, query_params
+        ))
         .send()
         .await
-        .map_http_error("Failed to delete policy")?;
+        .map_http_error(&format!("Failed to {}", operation.to_lowercase()))?;
 
-    handle_empty_response(response, "Delete policy").await
+    handle_response(response, operation).await
 }
 
-// ============================================================================
-// Task Commands
-// ============================================================================
-
-/// List all tasks
+/// Pause all snapshot sources
 ///
-/// Returns all active and recent tasks including snapshots, maintenance, and restore operations.
-/// Each task includes status, progress, start time, and error information (if failed).
+/// Stops scheduled snapshots for every source until they are resumed.
 #[tauri::command]
-pub async fn tasks_list(
+pub async fn sources_pause_all(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-) -> Result<crate::types::TasksResponse> {
+) -> Result<crate::types::MultipleSourceActionResponse> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(set_all_sources_paused(&client, &server_url, true).await)
+}
 
-    let response = client
-        .get(format!("{}/api/v1/tasks", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to list tasks")?;
-
-    handle_response(response, "List tasks").await
+/// Resume all paused snapshot sources
+#[tauri::command]
+pub async fn sources_resume_all(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::MultipleSourceActionResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(set_all_sources_paused(&client, &server_url, false).await)
 }
 
-/// Get task details
+/// Summarize snapshots of all sources
+///
+/// Sources are queried concurrently. A source that doesn't answer within
+/// `timeout_secs` (default: the `fanOutTimeoutSecs` setting) is listed in
+/// `timedOutSources` and the remaining sources are returned with `partial` set.
 #[tauri::command]
-pub async fn task_get(
+pub async fn sources_summary(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
     repo_id: String,
-    task_id: String,
-) -> Result<crate::types::TaskDetail> {
+    timeout_secs: Option<u64>,
+) -> Result<crate::types::SourcesSummary> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let timeout = fan_out_timeout(
+        timeout_secs,
+        settings.lock().unwrap().get().fan_out_timeout_secs,
+    );
 
-    let response = client
-        .get(format!("{}/api/v1/tasks/{}", server_url, task_id))
-        .send()
-        .await
-        .map_http_error("Failed to get task")?;
+    let sources = fetch_sources(&client, &server_url).await?;
+
+    let requests = sources
+        .sources
+        .into_iter()
+        .map(|s| {
+            let client = client.clone();
+            let server_url = server_url.clone();
+            let source = s.source;
+            (source.to_string(), async move {
+                let snapshots =
+                    fetch_source_snapshots(&client, &server_url, &source, false).await?;
+                Ok(summarize_source(source, &snapshots))
+            })
+        })
+        .collect();
 
-    handle_response(response, "Get task").await
+    timer.finish(Ok(build_sources_summary(fan_out(requests, timeout).await)))
 }
 
-/// Get task logs
+/// Find sources without snapshots and optionally delete them
+///
+/// Lists sources that have never completed a snapshot and aren't uploading,
+/// e.g. left behind by failed setups or removed folders. Nothing is deleted
+/// unless `remove_without_snapshots` is set; then each candidate's source and
+/// policy are removed. Sources that fail to delete are listed in `failed`.
 #[tauri::command]
-pub async fn task_logs(
+pub async fn sources_prune(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    task_id: String,
-) -> Result<Vec<String>> {
+    remove_without_snapshots: bool,
+) -> Result<crate::types::SourcesPruneResult> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let sources = fetch_sources(&client, &server_url).await?;
+
+    // The sources list only reports the latest complete snapshot, so make sure
+    // there are no others (e.g. incomplete ones) before treating it as empty
+    let mut result = crate::types::SourcesPruneResult::default();
+    for source in prune_candidates(&sources.sources) {
+        let snapshots = fetch_source_snapshots(&client, &server_url, &source, true).await?;
+        if snapshots.snapshots.is_empty() {
+            result.candidates.push(source);
+        }
+    }
 
-    let response = client
-        .get(format!("{}/api/v1/tasks/{}/logs", server_url, task_id))
-        .send()
-        .await
-        .map_http_error("Failed to get task logs")?;
-
-    #[derive(Deserialize)]
-    struct LogsResponse {
-        logs: Vec<String>,
+    if !remove_without_snapshots {
+        return timer.finish(Ok(result));
     }
 
-    let result: LogsResponse = handle_response(response, "Get task logs").await?;
+    for source in &result.candidates {
+        let _guard = app_state.lock_resource(&repo_id, &source.into()).await;
+        let payload = crate::types::SnapshotDeleteRequest {
+            source: source.clone(),
+            snapshot_manifest_ids: Vec::new(),
+            delete_source_and_policy: Some(true),
+        };
+        match delete_snapshots(&client, &server_url, &payload).await {
+            Ok(_) => result.removed.push(source.clone()),
+            Err(e) => {
+                log::warn!("Failed to remove source {}: {}", source, e);
+                result.failed.push(source.to_string());
+            }
+        }
+    }
+    timer.finish(Ok(result))
+}
 
-    Ok(result.logs)
+/// Sources that report no snapshot and aren't currently uploading
+pub(crate) fn prune_candidates(
+    sources: &[crate::types::SnapshotSource],
+) -> Vec<crate::types::SourceInfo> {
+    sources
+        .iter()
+        .filter(|s| s.last_snapshot.is_none() && s.status != "UPLOADING")
+        .map(|s| s.source.clone())
+        .collect()
 }
 
-/// Cancel a task
+/// List sources whose scheduled snapshot was missed
+///
+/// A snapshot counts as missed when its scheduled time passed more than
+/// `MISSED_SNAPSHOT_GRACE_SECS` ago and the source isn't busy or paused, which
+/// is typically because the machine was asleep or the app wasn't running.
 #[tauri::command]
-pub async fn task_cancel(
+pub async fn missed_snapshots(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    task_id: String,
-) -> Result<()> {
+) -> Result<Vec<crate::types::MissedSnapshot>> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let sources = fetch_sources(&client, &server_url).await?;
 
-    let response = client
-        .post(format!("{}/api/v1/tasks/{}/cancel", server_url, task_id))
-        .send()
-        .await
-        .map_http_error("Failed to cancel task")?;
-
-    handle_empty_response(response, "Cancel task").await
+    timer.finish(Ok(find_missed_snapshots(
+        &sources.sources,
+        chrono::Utc::now(),
+    )))
 }
 
-/// Get task summary
+/// List sources by how long ago they were last backed up, stalest first
+///
+/// Sources that were never backed up come first.
 #[tauri::command]
-pub async fn tasks_summary(
+pub async fn sources_by_staleness(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-) -> Result<crate::types::TasksSummary> {
+) -> Result<Vec<crate::types::SourceStaleness>> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let sources = fetch_sources(&client, &server_url).await?;
 
-    let response = client
-        .get(format!("{}/api/v1/tasks-summary", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to get tasks summary")?;
+    timer.finish(Ok(sort_by_staleness(&sources.sources, chrono::Utc::now())))
+}
 
-    handle_response(response, "Get tasks summary").await
+/// Age of each source's last snapshot, never backed up first, then oldest first
+///
+/// The age runs from the snapshot's end time (or its start time if it has
+/// none). Ties are ordered by source.
+pub(crate) fn sort_by_staleness(
+    sources: &[crate::types::SnapshotSource],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<crate::types::SourceStaleness> {
+    let mut staleness: Vec<crate::types::SourceStaleness> = sources
+        .iter()
+        .map(|s| {
+            let last_snapshot_time = s.last_snapshot.as_ref().map(|snapshot| {
+                snapshot
+                    .end_time
+                    .clone()
+                    .unwrap_or_else(|| snapshot.start_time.clone())
+            });
+            let age_secs = last_snapshot_time
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| (now - t.with_timezone(&chrono::Utc)).num_seconds().max(0) as u64);
+
+            crate::types::SourceStaleness {
+                source: s.source.clone(),
+                last_snapshot_time,
+                age_secs,
+            }
+        })
+        .collect();
+
+    staleness.sort_by(|a, b| {
+        let age = |s: &crate::types::SourceStaleness| s.age_secs.unwrap_or(u64::MAX);
+        age(b)
+            .cmp(&age(a))
+            .then_with(|| a.source.to_string().cmp(&b.source.to_string()))
+    });
+    staleness
+}
+
+/// Start snapshots for all sources with a missed scheduled snapshot
+///
+/// Uploads are started concurrently; sources that don't respond within the
+/// fan-out timeout are reported in `timedOutSources`.
+#[tauri::command]
+pub async fn run_missed_now(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+) -> Result<crate::types::RunMissedResult> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let timeout = fan_out_timeout(None, settings.lock().unwrap().get().fan_out_timeout_secs);
+
+    let sources = fetch_sources(&client, &server_url).await?;
+    let missed = find_missed_snapshots(&sources.sources, chrono::Utc::now());
+
+    let requests = missed
+        .into_iter()
+        .map(|m| {
+            let client = client.clone();
+            let server_url = server_url.clone();
+            (m.source.to_string(), async move {
+                let query_params =
+                    build_source_query(&m.source.user_name, &m.source.host, &m.source.path);
+                let response = client
+                    .post(format!(
+                        "{}/api/v1/sources/upload{}",
+                        server_url, query_params
+                    ))
+                    .send()
+                    .await
+                    .map_http_error("Failed to start snapshot upload")?;
+                handle_empty_response(response, "Start snapshot upload").await?;
+                Ok(m.source)
+            })
+        })
+        .collect();
+
+    let result = fan_out(requests, timeout).await;
+    timer.finish(Ok(crate::types::RunMissedResult {
+        partial: result.is_partial(),
+        triggered: result.completed.into_iter().map(|(_, s)| s).collect(),
+        timed_out_sources: result.timed_out,
+        failed_sources: result.failed.into_iter().map(|(key, _)| key).collect(),
+    }))
 }
 
 // ============================================================================
-// Utility Commands
+// Snapshot History Commands
 // ============================================================================
 
-/// Resolve a file system path to get source info (user@host:/path)
+/// List snapshots for a source
 #[tauri::command]
-pub async fn path_resolve(
+pub async fn snapshots_list(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
+    user_name: String,
+    host: String,
     path: String,
-) -> Result<crate::types::SourceInfo> {
+    all: bool,
+) -> Result<crate::types::SnapshotsResponse> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    timer.finish(fetch_source_snapshots(&client, &server_url, &source, all).await)
+}
 
-    let response = client
-        .post(format!("{}/api/v1/paths/resolve", server_url))
-        .json(&serde_json::json!({ "path": path }))
-        .send()
-        .await
-        .map_http_error("Failed to resolve path")?;
+/// Sources queried at the same time by `snapshots_list_all`
+pub(crate) const SNAPSHOTS_LIST_ALL_CONCURRENCY: usize = 8;
 
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(KopiaError::operation_failed(
-            "path resolution",
-            format!("Failed to resolve path '{}': {}", path, error_text),
-        ));
-    }
+/// List snapshots of every source in the repository
+///
+/// Snapshots are keyed by source (`user@host:path`). Up to
+/// `SNAPSHOTS_LIST_ALL_CONCURRENCY` sources are queried at once so a large
+/// repository doesn't flood the local server. A source that doesn't answer
+/// within `timeout_secs` (default: the `fanOutTimeoutSecs` setting) is listed
+/// in `timedOutSources` and the others are returned with `partial` set.
+#[tauri::command]
+pub async fn snapshots_list_all(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    all: bool,
+    timeout_secs: Option<u64>,
+) -> Result<crate::types::AllSnapshots> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let timeout = fan_out_timeout(
+        timeout_secs,
+        settings.lock().unwrap().get().fan_out_timeout_secs,
+    );
 
-    #[derive(Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct PathResolveResponse {
-        source: crate::types::SourceInfo,
-    }
+    let sources = retry_request(|| fetch_sources(&client, &server_url)).await?;
+
+    let requests = sources
+        .sources
+        .into_iter()
+        .map(|s| {
+            let client = client.clone();
+            let server_url = server_url.clone();
+            let source = s.source;
+            (source.to_string(), async move {
+                fetch_source_snapshots(&client, &server_url, &source, all).await
+            })
+        })
+        .collect();
 
-    let result: PathResolveResponse = response
-        .json()
-        .await
-        .map_http_error("Failed to parse response")?;
+    timer.finish(Ok(build_all_snapshots(
+        fan_out_bounded(requests, timeout, SNAPSHOTS_LIST_ALL_CONCURRENCY).await,
+    )))
+}
 
-    Ok(result.source)
+/// Build the `snapshots_list_all` result from the per-source fan-out
+pub(crate) fn build_all_snapshots(
+    result: FanOutResult<crate::types::SnapshotsResponse>,
+) -> crate::types::AllSnapshots {
+    crate::types::AllSnapshots {
+        partial: result.is_partial(),
+        snapshots: result.completed.into_iter().collect(),
+        timed_out_sources: result.timed_out,
+        failed_sources: result.failed.into_iter().map(|(key, _)| key).collect(),
+    }
 }
 
-/// Estimate snapshot size
+/// Export a source's snapshot history as CSV
+///
+/// Writes one row per snapshot (including ones no longer retained) with the
+/// columns id, start_time, end_time, size, file_count, incomplete, pins and
+/// retention. Missing values are left blank. Returns the number of rows written.
 #[tauri::command]
-pub async fn estimate_snapshot(
+pub async fn snapshots_export_csv(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
     path: String,
-    max_examples_per_bucket: Option<i64>,
+    target_path: String,
+) -> Result<usize> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    let snapshots = fetch_source_snapshots(&client, &server_url, &source, true).await?;
+
+    std::fs::write(&target_path, snapshots_to_csv(&snapshots.snapshots)).map_err(|e| {
+        KopiaError::operation_failed(
+            "file write",
+            format!("Failed to write to '{}': {}", target_path, e),
+        )
+    })?;
+
+    timer.finish(Ok(snapshots.snapshots.len()))
+}
+
+/// Edit snapshot metadata
+#[tauri::command]
+pub async fn snapshot_edit(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-) -> Result<crate::types::EstimateResponse> {
+    request: crate::types::SnapshotEditRequest,
+) -> Result<()> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
 
-    // Step 1: Resolve the path to get the absolute path
-    let resolved_path = {
-        let resolve_response = client
-            .post(format!("{}/api/v1/paths/resolve", server_url))
-            .json(&serde_json::json!({ "path": path }))
-            .send()
-            .await
-            .map_http_error("Failed to resolve path")?;
+    timer.finish(
+        retry_while_busy(|| async {
+            let response = client
+                .post(format!("{}/api/v1/snapshots/edit", server_url))
+                .json(&request)
+                .send()
+                .await
+                .map_http_error("Failed to edit snapshot")?;
 
-        #[derive(Deserialize)]
-        #[serde(rename_all = "camelCase")]
-        struct ResolveResponse {
-            source: crate::types::SourceInfo,
-        }
+            handle_empty_response(response, "Edit snapshot").await
+        })
+        .await,
+    )
+}
 
-        let resolve_result: ResolveResponse =
-            handle_response(resolve_response, "Resolve path").await?;
+/// Delete snapshots
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn snapshot_delete(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+    manifest_ids: Vec<String>,
+) -> Result<i64> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(&repo_id, &source_target(&user_name, &host, &path))
+        .await;
 
-        resolve_result.source.path
+    // API expects source info + manifest IDs
+    let payload = crate::types::SnapshotDeleteRequest {
+        source: crate::types::SourceInfo {
+            user_name,
+            host,
+            path,
+        },
+        snapshot_manifest_ids: manifest_ids,
+        delete_source_and_policy: Some(false),
     };
+    timer.finish(delete_snapshots(&client, &server_url, &payload).await)
+}
 
-    // Step 2: Start the estimation task
-    let estimate_req = crate::types::EstimateRequest {
-        root: resolved_path,
-        max_examples_per_bucket,
-        policy_override: None,
+/// List a source's incomplete snapshots (e.g. canceled ones), oldest first
+#[tauri::command]
+pub async fn incomplete_snapshots(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+) -> Result<Vec<crate::types::IncompleteSnapshot>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
     };
+    let snapshots = fetch_source_snapshots(&client, &server_url, &source, true).await?;
 
-    let response = client
-        .post(format!("{}/api/v1/estimate", server_url))
-        .json(&estimate_req)
-        .send()
-        .await
-        .map_http_error("Failed to estimate snapshot")?;
+    timer.finish(Ok(incomplete_snapshots_of(&snapshots.snapshots)))
+}
 
-    handle_response(response, "Estimate snapshot").await
+/// Delete incomplete snapshots of a source
+///
+/// Takes the IDs the user confirmed from `incomplete_snapshots`. IDs that don't
+/// belong to an incomplete snapshot of the source are rejected, so a stale
+/// selection can never delete a complete snapshot. Returns the number deleted.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn cleanup_incomplete_snapshots(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+    snapshot_ids: Vec<String>,
+) -> Result<i64> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(&repo_id, &source_target(&user_name, &host, &path))
+        .await;
+
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    let snapshots = fetch_source_snapshots(&client, &server_url, &source, true).await?;
+    let incomplete = incomplete_snapshots_of(&snapshots.snapshots);
+
+    if let Some(id) = snapshot_ids
+        .iter()
+        .find(|id| !incomplete.iter().any(|s| s.id == **id))
+    {
+        return Err(KopiaError::invalid_input(
+            "snapshotIds",
+            format!(
+                "Snapshot '{}' is not an incomplete snapshot of this source",
+                id
+            ),
+        ));
+    }
+    if snapshot_ids.is_empty() {
+        return timer.finish(Ok(0));
+    }
+
+    let payload = crate::types::SnapshotDeleteRequest {
+        source,
+        snapshot_manifest_ids: snapshot_ids,
+        delete_source_and_policy: Some(false),
+    };
+    timer.finish(delete_snapshots(&client, &server_url, &payload).await)
 }
 
-// ============================================================================
-// Notification Commands
-// ============================================================================
+/// Snapshots with an incomplete reason, oldest first
+pub(crate) fn incomplete_snapshots_of(
+    snapshots: &[crate::types::Snapshot],
+) -> Vec<crate::types::IncompleteSnapshot> {
+    let mut incomplete: Vec<crate::types::IncompleteSnapshot> = snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            let reason = snapshot.incomplete.as_deref()?.trim();
+            (!reason.is_empty()).then(|| crate::types::IncompleteSnapshot {
+                id: snapshot.id.clone(),
+                time: snapshot.start_time.clone(),
+                reason: reason.to_string(),
+            })
+        })
+        .collect();
+    incomplete.sort_by(|a, b| a.time.cmp(&b.time));
+    incomplete
+}
 
-/// List notification profiles
+/// Preview which snapshots of a source a retention policy would keep
+///
+/// Read-only: applies `retention` to the source's current snapshots the way
+/// Kopia does and returns the outcome without deleting anything. Pinned
+/// snapshots are always kept.
 #[tauri::command]
-pub async fn notification_profiles_list(
+pub async fn retention_preview(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-) -> Result<Vec<crate::types::NotificationProfile>> {
+    user_name: String,
+    host: String,
+    path: String,
+    retention: crate::types::RetentionPolicy,
+) -> Result<crate::types::RetentionPreview> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    let snapshots = fetch_source_snapshots(&client, &server_url, &source, true).await?;
 
-    let response = client
-        .get(format!("{}/api/v1/notificationProfiles", server_url))
-        .send()
-        .await
-        .map_http_error("Failed to list notification profiles")?;
+    timer.finish(Ok(preview_retention(
+        &snapshots.snapshots,
+        &retention,
+        &chrono::Local,
+    )))
+}
 
-    // Handle 404 as "no profiles configured" (common case)
-    if response.status().as_u16() == 404 {
-        return Ok(vec![]);
+/// Apply a retention policy to snapshots, mirroring Kopia's retention rules
+///
+/// Snapshots are walked newest first. Each rule keeps the first snapshot of
+/// every period (hour, day, ISO week, month, year) up to its limit, as long as
+/// the period isn't older than the limit counted back from the newest
+/// snapshot. Incomplete snapshots are only kept while no complete snapshot is
+/// newer. Periods are taken in `tz`, which is local time for Kopia.
+pub(crate) fn preview_retention<Tz: chrono::TimeZone>(
+    snapshots: &[crate::types::Snapshot],
+    retention: &crate::types::RetentionPolicy,
+    tz: &Tz,
+) -> crate::types::RetentionPreview {
+    use chrono::{Datelike, Months, NaiveDateTime};
+
+    let mut preview = crate::types::RetentionPreview::default();
+
+    let mut dated = Vec::new();
+    for snapshot in snapshots {
+        match chrono::DateTime::parse_from_rfc3339(&snapshot.start_time) {
+            Ok(time) => dated.push((snapshot, time.with_timezone(tz).naive_local())),
+            // Never prune a snapshot that can't be placed in time
+            Err(_) => preview.kept.push(snapshot.id.clone()),
+        }
     }
+    dated.sort_by_key(|(_, time)| std::cmp::Reverse(*time));
 
-    // For other non-success status codes, return error
-    if !response.status().is_success() {
-        return Err(http_request_failed(
-            "Failed to list notification profiles",
-            response.status().as_u16(),
-        ));
+    let is_incomplete =
+        |s: &crate::types::Snapshot| s.incomplete.as_deref().is_some_and(|r| !r.is_empty());
+    let Some(newest) = dated.first().map(|(_, time)| *time) else {
+        return preview;
+    };
+    let newest_complete = dated
+        .iter()
+        .find(|(s, _)| !is_incomplete(s))
+        .map(|(_, time)| *time);
+
+    let limit = |max: Option<i64>| max.map(|n| n.max(0) as u32);
+    let months_back = |months: u32| {
+        newest
+            .checked_sub_months(Months::new(months))
+            .unwrap_or(NaiveDateTime::MIN)
+    };
+    let rules = [
+        ("latest", limit(retention.keep_latest), NaiveDateTime::MIN),
+        (
+            "annual",
+            limit(retention.keep_annual),
+            months_back(limit(retention.keep_annual).unwrap_or(0).saturating_mul(12)),
+        ),
+        (
+            "monthly",
+            limit(retention.keep_monthly),
+            months_back(limit(retention.keep_monthly).unwrap_or(0)),
+        ),
+        (
+            "weekly",
+            limit(retention.keep_weekly),
+            newest - chrono::Duration::days(7 * limit(retention.keep_weekly).unwrap_or(0) as i64),
+        ),
+        (
+            "daily",
+            limit(retention.keep_daily),
+            newest - chrono::Duration::days(limit(retention.keep_daily).unwrap_or(0) as i64),
+        ),
+        (
+            "hourly",
+            limit(retention.keep_hourly),
+            newest - chrono::Duration::hours(limit(retention.keep_hourly).unwrap_or(0) as i64),
+        ),
+    ];
+
+    let mut periods_kept: HashMap<&str, std::collections::HashSet<String>> = HashMap::new();
+
+    for (index, (snapshot, time)) in dated.into_iter().enumerate() {
+        let mut reasons = Vec::new();
+
+        if is_incomplete(snapshot) {
+            if newest_complete.is_none_or(|complete| time >= complete) {
+                reasons.push("incomplete".to_string());
+            }
+        } else {
+            let week = time.iso_week();
+            for (kind, max, cutoff) in &rules {
+                let Some(max) = max else { continue };
+                if time < *cutoff {
+                    continue;
+                }
+                let period = match *kind {
+                    "latest" => index.to_string(),
+                    "annual" => time.format("%Y").to_string(),
+                    "monthly" => time.format("%Y-%m").to_string(),
+                    "weekly" => format!("{:04}-{:02}", week.year(), week.week()),
+                    "daily" => time.format("%Y-%m-%d").to_string(),
+                    _ => time.format("%Y-%m-%d %H").to_string(),
+                };
+                let kept = periods_kept.entry(kind).or_default();
+                if kept.len() >= *max as usize || !kept.insert(period) {
+                    continue;
+                }
+                reasons.push(format!("{}-{}", kind, kept.len()));
+            }
+        }
+
+        let pinned = snapshot.pins.as_ref().is_some_and(|pins| !pins.is_empty());
+        if reasons.is_empty() && !pinned {
+            preview.pruned.push(snapshot.id.clone());
+        } else {
+            preview.kept.push(snapshot.id.clone());
+        }
+        if !reasons.is_empty() {
+            preview.reasons.insert(snapshot.id.clone(), reasons);
+        }
     }
 
-    // Parse response - handle null as empty array (Kopia returns null when no profiles)
-    let result: Option<Vec<crate::types::NotificationProfile>> = response
-        .json()
-        .await
-        .map_http_error("Failed to parse notification profiles response")?;
+    preview
+}
 
-    Ok(result.unwrap_or_default())
+/// Delete snapshots, and with `delete_source_and_policy` the source itself
+async fn delete_snapshots(
+    client: &reqwest::Client,
+    server_url: &str,
+    payload: &crate::types::SnapshotDeleteRequest,
+) -> Result<i64> {
+    #[derive(Deserialize)]
+    struct DeleteResponse {
+        deleted: i64,
+    }
+
+    let result: DeleteResponse = retry_while_busy(|| async {
+        let response = client
+            .post(format!("{}/api/v1/snapshots/delete", server_url))
+            .json(payload)
+            .send()
+            .await
+            .map_http_error("Failed to delete snapshots")?;
+
+        handle_response(response, "Delete snapshots").await
+    })
+    .await?;
+
+    Ok(result.deleted)
 }
 
-/// Create notification profile
+// ============================================================================
+// Snapshot Browsing & Restore Commands
+// ============================================================================
+
+/// Browse directory contents in a snapshot
 #[tauri::command]
-pub async fn notification_profile_create(
+pub async fn object_browse(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    profile: crate::types::NotificationProfile,
-) -> Result<()> {
+    object_id: String,
+) -> Result<crate::types::DirectoryObject> {
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
-
-    let response = client
-        .post(format!("{}/api/v1/notificationProfiles", server_url))
-        .json(&profile)
-        .send()
-        .await
-        .map_http_error("Failed to create notification profile")?;
-
-    handle_empty_response(response, "Create notification profile").await
+    timer.finish(fetch_directory(&client, &server_url, &object_id).await)
 }
 
-/// Delete notification profile
+/// Browse a directory below a snapshot root by its relative path
+///
+/// Walks down from `root_id` one path component at a time, so the UI can open
+/// a known subfolder (e.g. `home/user/Documents`) directly. Returns
+/// `PathNotFound` if a component doesn't exist or isn't a directory.
 #[tauri::command]
-pub async fn notification_profile_delete(
+pub async fn browse_path(
+    timer: CommandTimer,
     manager: State<'_, ServerManagerState>,
     repo_id: String,
-    profile_name: String,
-) -> Result<()> {
+    root_id: String,
+    relative_path: String,
+) -> Result<crate::types::DirectoryObject> {
+    let components = split_relative_path(&relative_path)?;
     let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(walk_directory(&client, &server_url, &root_id, &components).await)
+}
 
-    let response = client
-        .delete(format!(
-            "{}/api/v1/notificationProfiles/{}",
-            server_url, profile_name
-        ))
-        .send()
-        .await
-        .map_http_error("Failed to delete notification profile")?;
+/// Snapshots searched at the same time by `file_versions`
+pub(crate) const FILE_VERSIONS_CONCURRENCY: usize = 8;
+
+/// List the distinct versions of a file across a source's snapshots
+///
+/// Looks the file up in every snapshot of the source, skipping snapshots that
+/// don't contain it. Consecutive or repeated snapshots of an unchanged file
+/// share an object ID and are reported once, under the oldest snapshot. Any
+/// version can then be fetched with `object_download`. Up to
+/// `FILE_VERSIONS_CONCURRENCY` snapshots are searched at once; snapshots that
+/// don't answer within the `fanOutTimeoutSecs` setting or fail are listed and
+/// the result is marked `partial`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn file_versions(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+    relative_file_path: String,
+) -> Result<crate::types::FileVersions> {
+    let components = split_relative_path(&relative_file_path)?;
+    let Some((file_name, parents)) = components.split_last() else {
+        return Err(KopiaError::invalid_input(
+            "relativeFilePath",
+            "File path must not be empty",
+        ));
+    };
+    let file_name = file_name.to_string();
+    let parents: std::sync::Arc<[String]> = parents.iter().map(|p| p.to_string()).collect();
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let timeout = fan_out_timeout(None, settings.lock().unwrap().get().fan_out_timeout_secs);
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    let snapshots = fetch_source_snapshots(&client, &server_url, &source, true).await?;
+
+    let requests = snapshots
+        .snapshots
+        .into_iter()
+        .filter_map(|snapshot| {
+            let root_id = snapshot.root_id.clone()?;
+            let client = client.clone();
+            let server_url = server_url.clone();
+            let file_name = file_name.clone();
+            let parents = parents.clone();
+            Some((snapshot.id.clone(), async move {
+                let parents: Vec<&str> = parents.iter().map(String::as_str).collect();
+                let directory = match walk_directory(&client, &server_url, &root_id, &parents).await
+                {
+                    Ok(directory) => directory,
+                    Err(KopiaError::PathNotFound { .. }) => return Ok(None),
+                    Err(e) => return Err(e),
+                };
+                Ok(directory
+                    .entries
+                    .into_iter()
+                    .find(|entry| entry.entry_type == "f" && entry.name == file_name)
+                    .map(|entry| crate::types::FileVersion {
+                        snapshot_id: snapshot.id,
+                        snapshot_time: snapshot.start_time,
+                        object_id: entry.obj,
+                        size: entry.size,
+                    }))
+            }))
+        })
+        .collect();
+
+    let result = fan_out_bounded(requests, timeout, FILE_VERSIONS_CONCURRENCY).await;
+    timer.finish(Ok(crate::types::FileVersions {
+        partial: result.is_partial(),
+        versions: dedupe_file_versions(
+            result
+                .completed
+                .into_iter()
+                .filter_map(|(_, version)| version)
+                .collect(),
+        ),
+        timed_out_snapshots: result.timed_out,
+        failed_snapshots: result.failed.into_iter().map(|(key, _)| key).collect(),
+    }))
+}
 
-    handle_empty_response(response, "Delete notification profile").await
+/// Sort file versions oldest first and drop later copies of the same object
+///
+/// Snapshot times are compared as instants; unparseable times sort last.
+pub(crate) fn dedupe_file_versions(
+    mut versions: Vec<crate::types::FileVersion>,
+) -> Vec<crate::types::FileVersion> {
+    versions.sort_by_cached_key(|version| {
+        match chrono::DateTime::parse_from_rfc3339(&version.snapshot_time) {
+            Ok(time) => (false, Some(time)),
+            Err(_) => (true, None),
+        }
+    });
+    let mut seen = std::collections::HashSet::new();
+    versions.retain(|version| seen.insert(version.object_id.clone()));
+    versions
 }
 
-/// Test notification profile (send test notification)
+/// Event emitted periodically while `object_download` writes a file
+pub const DOWNLOAD_PROGRESS_EVENT: &str = "download-progress";
+
+/// Minimum time between two `download-progress` events for one download
+const DOWNLOAD_PROGRESS_INTERVAL_MS: u64 = 250;
+
+/// Download a single file from a snapshot
+///
+/// The response is streamed to `target_path`, so memory use doesn't depend
+/// on the file size. `download-progress` events report the bytes written so
+/// far. A partially written file is removed if the download fails or is
+/// canceled. The download is registered as a "Restore" app operation.
 #[tauri::command]
-pub async fn notification_profile_test(
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn object_download(
+    timer: CommandTimer,
+    app: AppHandle,
     manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
     repo_id: String,
-    profile: crate::types::NotificationProfile,
+    object_id: String,
+    filename: String,
+    target_path: String,
 ) -> Result<()> {
-    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let (server_url, client) = get_download_client(&manager, &repo_id)?;
+    let operation = app_state.begin_operation(&repo_id, RESTORE_OPERATION_KIND);
+
+    let query_params = format!("?fname={}", urlencoding::encode(&filename));
 
     let response = client
-        .post(format!("{}/api/v1/testNotificationProfile", server_url))
-        .json(&profile)
+        .get(format!(
+            "{}/api/v1/objects/{}{}",
+            server_url, object_id, query_params
+        ))
         .send()
         .await
-        .map_http_error("Failed to test notification profile")?;
+        .map_http_error("Failed to download object")?;
+
+    // Check status before reading bytes
+    let status = response.status();
+    if !status.is_success() {
+        return Err(http_request_failed(
+            "Failed to download object",
+            status.as_u16(),
+        ));
+    }
+
+    let destination = std::path::Path::new(&target_path);
+    let file = tokio::fs::File::create(destination).await.map_err(|e| {
+        KopiaError::operation_failed(
+            "file write",
+            format!("Failed to write to '{}': {}", target_path, e),
+        )
+    })?;
+
+    let mut progress = crate::types::DownloadProgress {
+        object_id,
+        bytes_written: 0,
+        total_bytes: response.content_length(),
+    };
+    let emit_progress = |progress: &crate::types::DownloadProgress| {
+        if let Err(e) = app.emit(DOWNLOAD_PROGRESS_EVENT, progress) {
+            log::debug!("Failed to emit download progress: {}", e);
+        }
+    };
+
+    let interval = std::time::Duration::from_millis(DOWNLOAD_PROGRESS_INTERVAL_MS);
+    let mut last_emit = std::time::Instant::now();
+    let write = write_chunks(DownloadBody(response), file, destination, |written| {
+        if last_emit.elapsed() >= interval {
+            last_emit = std::time::Instant::now();
+            emit_progress(&crate::types::DownloadProgress {
+                bytes_written: written,
+                ..progress.clone()
+            });
+        }
+    });
+    let written = tokio::select! {
+        written = write => written,
+        _ = operation.canceled() => {
+            Err(KopiaError::operation_failed("Download object", "Canceled"))
+        }
+    };
 
-    handle_empty_response(response, "Test notification profile").await
+    timer.finish(match written {
+        Ok(written) => {
+            progress.bytes_written = written;
+            emit_progress(&progress);
+            Ok(())
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(destination).await;
+            Err(e)
+        }
+    })
 }
 
-// ============================================================================
-// Helper Functions
-// ============================================================================
+/// Source of the chunks `write_chunks` writes
+pub(crate) trait ChunkSource {
+    /// Next chunk, `None` at the end
+    async fn next_chunk(&mut self) -> Result<Option<impl AsRef<[u8]>>>;
+}
 
-/// Get server URL and HTTP client for a specific repository
-fn get_server_client(
-    manager: &State<'_, ServerManagerState>,
-    repo_id: &str,
-) -> Result<(String, reqwest::Client)> {
-    let manager_guard = manager.lock().unwrap();
+/// Body of a download response
+///
+/// Each chunk must arrive within `DOWNLOAD_IDLE_TIMEOUT_SECS`, so a stalled
+/// download fails while a slow but progressing one can take as long as it
+/// needs.
+struct DownloadBody(reqwest::Response);
+
+impl ChunkSource for DownloadBody {
+    async fn next_chunk(&mut self) -> Result<Option<impl AsRef<[u8]>>> {
+        let idle_timeout =
+            std::time::Duration::from_secs(crate::kopia_server::DOWNLOAD_IDLE_TIMEOUT_SECS);
+        tokio::time::timeout(idle_timeout, self.0.chunk())
+            .await
+            .map_err(|_| {
+                KopiaError::operation_failed(
+                    "download",
+                    format!(
+                        "No data received for {}s",
+                        crate::kopia_server::DOWNLOAD_IDLE_TIMEOUT_SECS
+                    ),
+                )
+            })?
+            .map_http_error("Failed to read response")
+    }
+}
 
-    let server_url = manager_guard.get_server_url(repo_id).ok_or_else(|| {
+/// Write chunks to `writer` through a buffer
+///
+/// `on_progress` is called with the running total after each chunk.
+/// `destination` is only used in error messages. Returns the bytes written.
+pub(crate) async fn write_chunks<S, W>(
+    mut chunks: S,
+    writer: W,
+    destination: &std::path::Path,
+    mut on_progress: impl FnMut(u64),
+) -> Result<u64>
+where
+    S: ChunkSource,
+    W: tokio::io::AsyncWrite + Unpin,
+{
+    use tokio::io::AsyncWriteExt;
+
+    let write_error = |e: std::io::Error| {
         KopiaError::operation_failed(
-            "repository lookup",
-            format!("Repository '{}' not found", repo_id),
+            "file write",
+            format!("Failed to write to '{}': {}", destination.display(), e),
         )
-    })?;
+    };
 
-    let client = manager_guard
-        .get_http_client(repo_id)
-        .ok_or(KopiaError::ServerNotRunning)?;
+    let mut writer = tokio::io::BufWriter::new(writer);
+    let mut written = 0u64;
+    while let Some(chunk) = chunks.next_chunk().await? {
+        writer
+            .write_all(chunk.as_ref())
+            .await
+            .map_err(write_error)?;
+        written += chunk.as_ref().len() as u64;
+        on_progress(written);
+    }
+    writer.flush().await.map_err(write_error)?;
 
-    Ok((server_url, client))
+    Ok(written)
 }
 
-/// Handle API response - check status and parse JSON
-async fn handle_response<T: DeserializeOwned>(
-    response: reqwest::Response,
-    operation: &str,
-) -> Result<T> {
-    let status = response.status();
-    if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(KopiaError::from_api_response(
-            status.as_u16(),
-            &error_text,
-            operation,
+/// Event emitted by `restore_files` after each file
+pub const RESTORE_FILES_PROGRESS_EVENT: &str = "restore-files-progress";
+
+/// Files downloaded at the same time by `restore_files`
+pub(crate) const RESTORE_FILES_CONCURRENCY: usize = 4;
+
+/// Restore selected files from a snapshot into a folder
+///
+/// Each file is streamed to `target_dir` joined with its relative path,
+/// creating intermediate directories and overwriting existing files. Up to
+/// `RESTORE_FILES_CONCURRENCY` files are downloaded at once, and a
+/// `restore-files-progress` event is emitted as each one finishes. A failed
+/// file doesn't stop the others; results are returned in the order given.
+/// The restore is registered as a "Restore" app operation; canceling it stops
+/// the downloads still in progress and fails the command.
+#[tauri::command]
+pub async fn restore_files(
+    timer: CommandTimer,
+    app: AppHandle,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+    items: Vec<crate::types::RestoreFileItem>,
+    target_dir: String,
+) -> Result<Vec<crate::types::RestoreFileResult>> {
+    let (server_url, client) = get_download_client(&manager, &repo_id)?;
+    let operation = app_state.begin_operation(&repo_id, RESTORE_OPERATION_KIND);
+
+    if !std::path::Path::new(&target_dir).is_dir() {
+        return Err(KopiaError::invalid_input(
+            "targetDir",
+            format!("'{}' is not a directory", target_dir),
         ));
     }
 
-    response
-        .json()
-        .await
-        .map_err(|e| KopiaError::ResponseParseError {
-            message: e.to_string(),
-            expected_type: std::any::type_name::<T>().to_string(),
+    let total_files = items.len();
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(RESTORE_FILES_CONCURRENCY));
+    let mut set = tokio::task::JoinSet::new();
+
+    for (index, item) in items.iter().cloned().enumerate() {
+        let semaphore = semaphore.clone();
+        let client = client.clone();
+        let server_url = server_url.clone();
+        let target_dir = target_dir.clone();
+
+        set.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let result = match restore_file_destination(&target_dir, &item.relative_path) {
+                Ok(destination) => {
+                    download_object_to(&client, &server_url, &item.object_id, &destination).await
+                }
+                Err(e) => Err(e),
+            };
+            (index, item.relative_path, result)
+        });
+    }
+
+    let mut results: Vec<Option<crate::types::RestoreFileResult>> = vec![None; total_files];
+    let mut progress = crate::types::RestoreFilesProgress {
+        repo_id: repo_id.clone(),
+        total_files,
+        ..Default::default()
+    };
+
+    loop {
+        let joined = tokio::select! {
+            joined = set.join_next() => joined,
+            _ = operation.canceled() => {
+                // Dropping the set aborts the downloads
+                log::info!("Restore {} for '{}' canceled", operation.id(), repo_id);
+                return Err(KopiaError::operation_failed("Restore files", "Canceled"));
+            }
+        };
+        let Some(joined) = joined else {
+            break;
+        };
+        let (index, relative_path, result) = match joined {
+            Ok(done) => done,
+            Err(e) => {
+                log::warn!("File restore task panicked: {}", e);
+                continue;
+            }
+        };
+
+        progress.completed_files += 1;
+        results[index] = Some(match result {
+            Ok(bytes) => {
+                progress.bytes_written += bytes;
+                crate::types::RestoreFileResult {
+                    relative_path,
+                    success: true,
+                    bytes,
+                    error: None,
+                }
+            }
+            Err(e) => {
+                progress.failed_files += 1;
+                crate::types::RestoreFileResult {
+                    relative_path,
+                    success: false,
+                    bytes: 0,
+                    error: Some(e.to_string()),
+                }
+            }
+        });
+
+        if let Err(e) = app.emit(RESTORE_FILES_PROGRESS_EVENT, &progress) {
+            log::debug!("Failed to emit restore progress: {}", e);
+        }
+    }
+
+    timer.finish(Ok(results
+        .into_iter()
+        .zip(items)
+        .map(|(result, item)| {
+            result.unwrap_or_else(|| crate::types::RestoreFileResult {
+                relative_path: item.relative_path,
+                success: false,
+                bytes: 0,
+                error: Some("Restore task failed".to_string()),
+            })
         })
+        .collect()))
 }
 
-/// Handle API response that returns empty/unit result
-async fn handle_empty_response(response: reqwest::Response, operation: &str) -> Result<()> {
+/// Destination of a restored file: `target_dir` joined with `relative_path`
+///
+/// Rejects absolute paths and `..` components so a file can't be written
+/// outside `target_dir`.
+pub(crate) fn restore_file_destination(
+    target_dir: &str,
+    relative_path: &str,
+) -> Result<std::path::PathBuf> {
+    use std::path::Component;
+
+    let relative = std::path::Path::new(relative_path);
+    let mut has_name = false;
+    for component in relative.components() {
+        match component {
+            Component::Normal(_) => has_name = true,
+            Component::CurDir => {}
+            _ => {
+                return Err(KopiaError::invalid_input(
+                    "relativePath",
+                    format!("'{}' must stay inside the target folder", relative_path),
+                ))
+            }
+        }
+    }
+    if !has_name {
+        return Err(KopiaError::invalid_input(
+            "relativePath",
+            "Path must name a file",
+        ));
+    }
+
+    Ok(std::path::Path::new(target_dir).join(relative))
+}
+
+/// Stream an object to a local file, returning the number of bytes written
+///
+/// `client` should be the download client (see `get_download_client`).
+/// Creates missing parent directories. A partially written file is removed.
+async fn download_object_to(
+    client: &reqwest::Client,
+    server_url: &str,
+    object_id: &str,
+    destination: &std::path::Path,
+) -> Result<u64> {
+    let response = client
+        .get(format!("{}/api/v1/objects/{}", server_url, object_id))
+        .send()
+        .await
+        .map_http_error("Failed to download object")?;
+
     let status = response.status();
     if !status.is_success() {
-        let error_text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(KopiaError::from_api_response(
+        return Err(http_request_failed(
+            "Failed to download object",
             status.as_u16(),
-            &error_text,
-            operation,
         ));
     }
-    Ok(())
+
+    let write_error = |e: std::io::Error| {
+        KopiaError::operation_failed(
+            "file write",
+            format!("Failed to write to '{}': {}", destination.display(), e),
+        )
+    };
+
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(write_error)?;
+    }
+    let file = tokio::fs::File::create(destination)
+        .await
+        .map_err(write_error)?;
+
+    let written = write_chunks(DownloadBody(response), file, destination, |_| {}).await;
+    if written.is_err() {
+        let _ = tokio::fs::remove_file(destination).await;
+    }
+    written
 }
 
-/// Create an HttpRequestFailed error with consistent structure
-///
-/// This helper reduces boilerplate when constructing HTTP request failures
-/// throughout the API commands.
-///
-/// # Arguments
-/// * `message` - Error message describing what failed
-/// * `status_code` - HTTP status code from the response
-///
-/// # Returns
-/// A `KopiaError::HttpRequestFailed` with url set to None (not tracked for localhost API)
-fn http_request_failed(message: impl Into<String>, status_code: u16) -> KopiaError {
-    KopiaError::HttpRequestFailed {
-        message: message.into(),
-        status_code: Some(status_code),
-        operation: "API Request".to_string(),
+/// Start a restore operation
+#[tauri::command]
+pub async fn restore_start(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    request: crate::types::RestoreRequest,
+) -> Result<String> {
+    validate_restore_request(&request)?;
+
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .post(format!("{}/api/v1/restore", server_url))
+        .json(&request)
+        .send()
+        .await
+        .map_http_error("Failed to start restore")?;
+
+    #[derive(Deserialize)]
+    struct RestoreResponse {
+        id: String,
     }
+
+    let result: RestoreResponse = handle_response(response, "Start restore").await?;
+
+    timer.finish(Ok(result.id))
 }
 
-/// Build URL query string for snapshot source (user@host:/path)
-///
-/// # Arguments
-/// * `user_name` - Username for the snapshot source
-/// * `host` - Hostname for the snapshot source
-/// * `path` - File path for the snapshot source
+/// Get the progress of a restore started with `restore_start`
+#[tauri::command]
+pub async fn restore_status(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    task_id: String,
+) -> Result<crate::types::RestoreStatus> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let task = fetch_task(&client, &server_url, &task_id).await?;
+    timer.finish(Ok(restore_status_from_task(&task)))
+}
+
+/// Read restore progress from a restore task's counters
 ///
-/// # Returns
-/// URL-encoded query string in format: `?userName=X&host=Y&path=Z`
-fn build_source_query(user_name: &str, host: &str, path: &str) -> String {
-    format!(
-        "?userName={}&host={}&path={}",
-        urlencoding::encode(user_name),
-        urlencoding::encode(host),
-        urlencoding::encode(path)
-    )
+/// Missing counters (e.g. before the restore has started) count as 0.
+/// See: internal/server/api_restore.go
+pub(crate) fn restore_status_from_task(
+    task: &crate::types::TaskDetail,
+) -> crate::types::RestoreStatus {
+    let counters = task.counters.as_ref().unwrap_or(&task.task.counters);
+    let counter = |name: &str| counters.get(name).map_or(0, |c| c.value);
+
+    crate::types::RestoreStatus {
+        status: task.task.status.clone(),
+        restored_files: counter("Restored Files"),
+        restored_directories: counter("Restored Directories"),
+        restored_bytes: counter("Restored Bytes"),
+        skipped_files: counter("Skipped Files"),
+        skipped_bytes: counter("Skipped Bytes"),
+        ignored_errors: counter("Ignored Errors"),
+        finished: !is_task_active(&task.task.status),
+        error_message: task.task.error_message.clone(),
+    }
 }
 
-/// Build URL query string for policy target (optional user/host/path)
+/// Mount a snapshot
+#[tauri::command]
+pub async fn mount_snapshot(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    root: String,
+) -> Result<String> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .post(format!("{}/api/v1/mounts", server_url))
+        .json(&serde_json::json!({ "root": root }))
+        .send()
+        .await
+        .map_http_error("Failed to mount snapshot")?;
+
+    let result: crate::types::MountResponse = handle_response(response, "Mount snapshot").await?;
+
+    timer.finish(Ok(result.path))
+}
+
+/// List all mounted snapshots
 ///
-/// # Arguments
-/// * `user_name` - Optional username filter
-/// * `host` - Optional hostname filter
-/// * `path` - Optional path filter
+/// Returns all currently mounted snapshots with their mount paths and root object IDs.
+/// Mounted snapshots appear as local filesystems for easy file browsing and restoration.
+#[tauri::command]
+pub async fn mounts_list(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::MountsResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .get(format!("{}/api/v1/mounts", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to list mounts")?;
+
+    timer.finish(handle_response(response, "List mounts").await)
+}
+
+/// Mount paths of snapshots mounted by any running server
 ///
-/// # Returns
-/// URL-encoded query string, or empty string if no parameters
-fn build_policy_query(user_name: Option<&str>, host: Option<&str>, path: Option<&str>) -> String {
-    let parts: Vec<_> = [
-        user_name.map(|u| format!("userName={}", urlencoding::encode(u))),
-        host.map(|h| format!("host={}", urlencoding::encode(h))),
-        path.map(|p| format!("path={}", urlencoding::encode(p))),
-    ]
-    .into_iter()
-    .flatten()
-    .collect();
+/// Fails if a server's mounts can't be listed, so a live mount is never
+/// mistaken for an orphaned one.
+pub(crate) async fn active_mount_paths(manager: &ServerManagerState) -> Result<Vec<String>> {
+    let servers = manager.lock().unwrap().running_servers();
+    let mut paths = Vec::new();
+
+    for (_, server_url, client) in servers {
+        let response = client
+            .get(format!("{}/api/v1/mounts", server_url))
+            .send()
+            .await
+            .map_http_error("Failed to list mounts")?;
+        let mounts: crate::types::MountsResponse = handle_response(response, "List mounts").await?;
+        paths.extend(mounts.items.into_iter().map(|mount| mount.path));
+    }
 
-    if parts.is_empty() {
-        String::new()
+    Ok(paths)
+}
+
+/// Unmount a snapshot
+#[tauri::command]
+pub async fn mount_unmount(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    object_id: String,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .delete(format!("{}/api/v1/mounts/{}", server_url, object_id))
+        .send()
+        .await
+        .map_http_error("Failed to unmount snapshot")?;
+
+    timer.finish(handle_empty_response(response, "Unmount snapshot").await)
+}
+
+/// Longest a snapshot verification may take
+const SNAPSHOT_VERIFY_TIMEOUT_SECS: u64 = 6 * 60 * 60;
+
+/// Verify the snapshots of a source
+///
+/// Kopia's REST API has no verification endpoint, so this runs
+/// `kopia snapshot verify` for the source and returns once it's done, with the
+/// CLI's output. Found errors fail the command with the CLI's error output as
+/// details. `verify_files_percent` (0-100) also reads back that share of files.
+/// The CLI run is registered as a "Verify" app operation.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn snapshot_verify(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+    verify_files_percent: Option<f64>,
+    max_errors: Option<u32>,
+) -> Result<String> {
+    let config_file = get_config_file(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    let request = crate::types::VerifyRequest {
+        max_errors,
+        verify_files_percent,
+        ..Default::default()
+    };
+
+    let args = snapshot_verify_args(Some(&source), &request)?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = run_app_operation(
+        &app_state,
+        &repo_id,
+        VERIFY_OPERATION_KIND,
+        "Verify snapshots",
+        kopia_cli::run_with_timeout(
+            &config_file,
+            &args,
+            "Verify snapshots",
+            std::time::Duration::from_secs(SNAPSHOT_VERIFY_TIMEOUT_SECS),
+        ),
+    )
+    .await?;
+
+    timer.finish(Ok(output.trim().to_string()))
+}
+
+/// Build the `kopia snapshot verify` arguments
+///
+/// With a source, all of its snapshots are verified; without one, only the
+/// directory or file named in `request`.
+pub(crate) fn snapshot_verify_args(
+    source: Option<&crate::types::SourceInfo>,
+    request: &crate::types::VerifyRequest,
+) -> Result<Vec<String>> {
+    let mut args = vec!["snapshot".to_string(), "verify".to_string()];
+    if let Some(source) = source {
+        args.push(format!("--sources={}", source));
+    }
+
+    if let Some(percent) = request.verify_files_percent {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(KopiaError::invalid_input(
+                "verifyFilesPercent",
+                "Must be between 0 and 100",
+            ));
+        }
+        args.push(format!("--verify-files-percent={}", percent));
+    }
+    if let Some(max_errors) = request.max_errors {
+        args.push(format!("--max-errors={}", max_errors));
+    }
+    if let Some(directory_id) = &request.directory_id {
+        args.push(format!("--directory-id={}", directory_id));
+    }
+    if let Some(file_id) = &request.file_id {
+        args.push(format!("--file-id={}", file_id));
+    }
+
+    Ok(args)
+}
+
+/// Snapshot a path, wait for it to finish, then verify the new snapshot
+///
+/// Verification reads back every object of the new snapshot's tree with
+/// `kopia snapshot verify`, so it adds roughly the time of a full listing of
+/// the snapshot, plus reading `verify_files_percent` of the file contents
+/// (none by default; 100 downloads the whole snapshot again). Problems found
+/// are returned in `errors` rather than failing the command.
+#[tauri::command]
+pub async fn snapshot_create_and_verify(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    path: String,
+    verify_files_percent: Option<f64>,
+) -> Result<crate::types::SnapshotVerification> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    let source = resolve_source(&client, &server_url, &path).await?;
+    let snapshot = {
+        let _guard = app_state.lock_resource(&repo_id, &(&source).into()).await;
+
+        let previous_snapshot = find_snapshot_source(&client, &server_url, &source)
+            .await?
+            .and_then(|status| status.last_snapshot)
+            .map(|snapshot| snapshot.id);
+
+        log::info!("Creating snapshot of '{}' to verify", source.path);
+        post_source(
+            &client,
+            &server_url,
+            &source.path,
+            true,
+            &crate::types::PolicyDefinition::default(),
+        )
+        .await?;
+        wait_for_new_snapshot(&client, &server_url, &source, previous_snapshot.as_deref()).await?
+    };
+
+    let root_id = snapshot.root_id.clone().ok_or_else(|| {
+        KopiaError::operation_failed(
+            "Verify snapshot",
+            format!("Snapshot {} has no root object", snapshot.id),
+        )
+    })?;
+    let request = crate::types::VerifyRequest {
+        verify_files_percent,
+        directory_id: Some(root_id),
+        ..Default::default()
+    };
+    let args = snapshot_verify_args(None, &request)?;
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let verified = run_app_operation(
+        &app_state,
+        &repo_id,
+        VERIFY_OPERATION_KIND,
+        "Verify snapshot",
+        kopia_cli::run_with_timeout(
+            &config_file,
+            &args,
+            "Verify snapshot",
+            std::time::Duration::from_secs(SNAPSHOT_VERIFY_TIMEOUT_SECS),
+        ),
+    )
+    .await;
+
+    let errors = match verified {
+        Ok(_) => Vec::new(),
+        Err(KopiaError::OperationFailed {
+            details: Some(details),
+            ..
+        }) if !details.trim().is_empty() => verification_errors(&details),
+        Err(e) => return Err(e),
+    };
+
+    timer.finish(Ok(crate::types::SnapshotVerification {
+        snapshot_id: snapshot.id,
+        verified: errors.is_empty(),
+        errors,
+    }))
+}
+
+/// Poll a source until a snapshot newer than `previous_snapshot` has finished
+///
+/// Fails if the source goes idle without producing one (e.g. the upload was
+/// canceled or failed).
+async fn wait_for_new_snapshot(
+    client: &reqwest::Client,
+    server_url: &str,
+    source: &crate::types::SourceInfo,
+    previous_snapshot: Option<&str>,
+) -> Result<crate::types::Snapshot> {
+    let start_deadline = std::time::Instant::now()
+        + std::time::Duration::from_secs(POLICY_OVERRIDE_START_TIMEOUT_SECS);
+    let mut saw_uploading = false;
+
+    loop {
+        if let Some(status) = find_snapshot_source(client, server_url, source).await? {
+            if let Some(snapshot) = finished_snapshot(&status, previous_snapshot) {
+                return Ok(snapshot.clone());
+            }
+            if matches!(status.status.as_str(), "UPLOADING" | "PENDING") {
+                saw_uploading = true;
+            } else if saw_uploading || std::time::Instant::now() >= start_deadline {
+                return Err(KopiaError::operation_failed(
+                    "Create snapshot",
+                    format!("Snapshot of '{}' didn't complete", source.path),
+                ));
+            }
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+    }
+}
+
+/// New snapshot of a source once its upload has finished
+pub(crate) fn finished_snapshot<'a>(
+    status: &'a crate::types::SnapshotSource,
+    previous_snapshot: Option<&str>,
+) -> Option<&'a crate::types::Snapshot> {
+    if matches!(status.status.as_str(), "UPLOADING" | "PENDING") {
+        return None;
+    }
+    status
+        .last_snapshot
+        .as_ref()
+        .filter(|snapshot| Some(snapshot.id.as_str()) != previous_snapshot)
+}
+
+/// Error lines from `kopia snapshot verify` output
+///
+/// Falls back to every non-empty line if none mentions an error.
+pub(crate) fn verification_errors(output: &str) -> Vec<String> {
+    let lines: Vec<String> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+
+    let errors: Vec<String> = lines
+        .iter()
+        .filter(|line| line.to_ascii_lowercase().contains("error"))
+        .cloned()
+        .collect();
+
+    if errors.is_empty() {
+        lines
+    } else {
+        errors
+    }
+}
+
+/// Default maximum directory depth walked by `verify_restore`
+const VERIFY_RESTORE_MAX_DEPTH: u32 = 64;
+/// Default maximum number of files checked by `verify_restore`
+const VERIFY_RESTORE_MAX_FILES: u64 = 10_000;
+
+/// Verify restored files against a snapshot
+///
+/// Walks the snapshot tree from `root_id` and compares every file against the
+/// matching path under `local_path`: sizes always, contents only when
+/// `compare_contents` is set (streamed from the server, never buffered whole).
+/// The walk is bounded by `max_depth` and `max_files`. Entry names that
+/// aren't a single plain path component (`..`, absolute paths) fail the
+/// verification rather than being resolved outside `local_path`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn verify_restore(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    root_id: String,
+    local_path: String,
+    compare_contents: Option<bool>,
+    max_depth: Option<u32>,
+    max_files: Option<u64>,
+) -> Result<crate::types::RestoreVerification> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let mut tree = ServerTree {
+        client: &client,
+        server_url: &server_url,
+    };
+
+    timer.finish(
+        verify_restore_tree(
+            &mut tree,
+            root_id,
+            std::path::Path::new(&local_path),
+            compare_contents.unwrap_or(false),
+            max_depth.unwrap_or(VERIFY_RESTORE_MAX_DEPTH),
+            max_files.unwrap_or(VERIFY_RESTORE_MAX_FILES),
+        )
+        .await,
+    )
+}
+
+/// Snapshot tree walked by `verify_restore_tree`
+pub(crate) trait SnapshotTree {
+    /// Directory object with the given ID
+    async fn list_directory(&mut self, object_id: &str) -> Result<crate::types::DirectoryObject>;
+
+    /// Whether the object's content equals the local file's
+    async fn matches_file(&mut self, object_id: &str, local: &std::path::Path) -> Result<bool>;
+}
+
+/// Snapshot tree read from a running server
+struct ServerTree<'a> {
+    client: &'a reqwest::Client,
+    server_url: &'a str,
+}
+
+impl SnapshotTree for ServerTree<'_> {
+    async fn list_directory(&mut self, object_id: &str) -> Result<crate::types::DirectoryObject> {
+        browse_directory(self.client, self.server_url, object_id).await
+    }
+
+    async fn matches_file(&mut self, object_id: &str, local: &std::path::Path) -> Result<bool> {
+        object_matches_file(self.client, self.server_url, object_id, local).await
+    }
+}
+
+/// Whether a directory entry name is a single plain path component
+///
+/// Names come from the repository, so `..`, absolute paths and names with
+/// separators are refused before they're joined onto a local path.
+pub(crate) fn is_safe_entry_name(name: &str) -> bool {
+    use std::path::{Component, Path};
+
+    if name.is_empty() || name.contains(['/', '\\']) {
+        return false;
+    }
+
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+/// Walk `tree` from `root_id` and compare it against `local_root`
+///
+/// See `verify_restore`. Fails on entry names that aren't safe to join onto
+/// `local_root`.
+pub(crate) async fn verify_restore_tree<T: SnapshotTree>(
+    tree: &mut T,
+    root_id: String,
+    local_root: &std::path::Path,
+    compare_contents: bool,
+    max_depth: u32,
+    max_files: u64,
+) -> Result<crate::types::RestoreVerification> {
+    let mut result = crate::types::RestoreVerification::default();
+    let mut checked_files: u64 = 0;
+    // (object ID, relative path, depth)
+    let mut pending = vec![(root_id, String::new(), 0u32)];
+
+    while let Some((object_id, relative_dir, depth)) = pending.pop() {
+        let directory = tree.list_directory(&object_id).await?;
+
+        for entry in directory.entries {
+            if !is_safe_entry_name(&entry.name) {
+                return Err(KopiaError::operation_failed(
+                    "verify restore",
+                    format!(
+                        "Snapshot entry {:?} in {:?} is not a plain file name",
+                        entry.name, relative_dir
+                    ),
+                ));
+            }
+
+            let relative_path = if relative_dir.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{}/{}", relative_dir, entry.name)
+            };
+            let local = local_root.join(&relative_path);
+
+            match entry.entry_type.as_str() {
+                "d" => {
+                    if !local.is_dir() {
+                        result.missing.push(relative_path);
+                    } else if depth + 1 >= max_depth {
+                        result.truncated = true;
+                    } else {
+                        pending.push((entry.obj, relative_path, depth + 1));
+                    }
+                }
+                "f" => {
+                    if checked_files >= max_files {
+                        result.truncated = true;
+                        return Ok(result);
+                    }
+                    checked_files += 1;
+
+                    let metadata = match std::fs::metadata(&local) {
+                        Ok(metadata) if metadata.is_file() => metadata,
+                        _ => {
+                            result.missing.push(relative_path);
+                            continue;
+                        }
+                    };
+
+                    let size_matches = entry.size.unwrap_or(0) == metadata.len() as i64;
+                    let matches = if size_matches && compare_contents {
+                        tree.matches_file(&entry.obj, &local).await?
+                    } else {
+                        size_matches
+                    };
+
+                    if matches {
+                        result.matched += 1;
+                    } else {
+                        result.mismatched.push(relative_path);
+                    }
+                }
+                // Symlinks and special files are not compared
+                _ => {}
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Default maximum directory depth written by `snapshot_tree_export`
+const TREE_EXPORT_MAX_DEPTH: u32 = 64;
+/// Default maximum number of entries written by `snapshot_tree_export`
+const TREE_EXPORT_MAX_ENTRIES: u64 = 1_000_000;
+
+/// Export a snapshot's file tree as a JSON document
+///
+/// Walks the tree from `root_id` and writes each entry's name, type, size,
+/// modification time and object ID to `target_path`, nesting directory
+/// contents under `entries`. The document is written as the walk goes, so
+/// only the directories currently being walked are held in memory. When
+/// `max_depth` or `max_entries` stops the walk, directories that weren't
+/// expanded and the document itself are marked `truncated`.
+#[tauri::command]
+pub async fn snapshot_tree_export(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    root_id: String,
+    max_depth: Option<u32>,
+    max_entries: Option<u64>,
+    target_path: String,
+) -> Result<crate::types::TreeExportSummary> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let max_depth = max_depth.unwrap_or(TREE_EXPORT_MAX_DEPTH);
+    let max_entries = max_entries.unwrap_or(TREE_EXPORT_MAX_ENTRIES);
+    if max_depth == 0 {
+        return Err(KopiaError::invalid_input(
+            "maxDepth",
+            "Depth must be at least 1",
+        ));
+    }
+
+    let root = browse_directory(&client, &server_url, &root_id).await?;
+
+    let file = std::fs::File::create(&target_path).map_err(|e| {
+        KopiaError::operation_failed(
+            "file write",
+            format!("Failed to write to '{}': {}", target_path, e),
+        )
+    })?;
+
+    let exported = async {
+        let mut writer = TreeExportWriter::new(std::io::BufWriter::new(file), &root_id)?;
+        let mut summary = crate::types::TreeExportSummary::default();
+        // Entries left to write at each level, root first
+        let mut pending = vec![root.entries.into_iter()];
+
+        while let Some(entries) = pending.last_mut() {
+            let Some(entry) = entries.next() else {
+                pending.pop();
+                if !pending.is_empty() {
+                    writer.close_directory()?;
+                }
+                continue;
+            };
+
+            if summary.entries >= max_entries {
+                summary.truncated = true;
+                break;
+            }
+            summary.entries += 1;
+
+            if entry.entry_type != "d" {
+                writer.write_entry(&entry)?;
+            } else if pending.len() >= max_depth as usize {
+                summary.truncated = true;
+                writer.write_truncated_directory(&entry)?;
+            } else {
+                let directory = browse_directory(&client, &server_url, &entry.obj).await?;
+                writer.open_directory(&entry)?;
+                pending.push(directory.entries.into_iter());
+            }
+        }
+
+        // Close the directories left open when a limit stopped the walk
+        for _ in 1..pending.len() {
+            writer.close_directory()?;
+        }
+        writer.finish(&summary)?;
+        Ok(summary)
+    }
+    .await;
+
+    if exported.is_err() {
+        let _ = std::fs::remove_file(&target_path);
+    }
+    timer.finish(exported)
+}
+
+/// Fields written for each entry by `snapshot_tree_export`
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportedTreeEntry<'a> {
+    name: &'a str,
+    #[serde(rename = "type")]
+    entry_type: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<i64>,
+    mtime: &'a str,
+    #[serde(rename = "objectID")]
+    object_id: &'a str,
+}
+
+/// Incremental writer for the `snapshot_tree_export` document
+pub(crate) struct TreeExportWriter<W: std::io::Write> {
+    out: W,
+    /// Whether the next entry is the first one, per open `entries` array
+    first: Vec<bool>,
+}
+
+impl<W: std::io::Write> TreeExportWriter<W> {
+    /// Start the document and the root's `entries` array
+    pub(crate) fn new(mut out: W, root_id: &str) -> Result<Self> {
+        write!(
+            out,
+            "{{\"rootID\":{},\"entries\":[",
+            serde_json::to_string(root_id)?
+        )?;
+        Ok(Self {
+            out,
+            first: vec![true],
+        })
+    }
+
+    /// Write a file, symlink or other non-expanded entry
+    pub(crate) fn write_entry(&mut self, entry: &crate::types::DirectoryEntry) -> Result<()> {
+        self.start_entry(entry)?;
+        write!(self.out, "}}")?;
+        Ok(())
+    }
+
+    /// Write a directory whose contents were left out by a limit
+    pub(crate) fn write_truncated_directory(
+        &mut self,
+        entry: &crate::types::DirectoryEntry,
+    ) -> Result<()> {
+        self.start_entry(entry)?;
+        write!(self.out, ",\"truncated\":true}}")?;
+        Ok(())
+    }
+
+    /// Write a directory and start its `entries` array
+    pub(crate) fn open_directory(&mut self, entry: &crate::types::DirectoryEntry) -> Result<()> {
+        self.start_entry(entry)?;
+        write!(self.out, ",\"entries\":[")?;
+        self.first.push(true);
+        Ok(())
+    }
+
+    /// End the innermost open directory
+    pub(crate) fn close_directory(&mut self) -> Result<()> {
+        self.first.pop();
+        write!(self.out, "]}}")?;
+        Ok(())
+    }
+
+    /// End the document, returning the underlying writer
+    pub(crate) fn finish(mut self, summary: &crate::types::TreeExportSummary) -> Result<W> {
+        write!(
+            self.out,
+            "],\"entryCount\":{},\"truncated\":{}}}",
+            summary.entries, summary.truncated
+        )?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// Write an entry's fields, leaving its object open
+    fn start_entry(&mut self, entry: &crate::types::DirectoryEntry) -> Result<()> {
+        if let Some(first) = self.first.last_mut() {
+            if !std::mem::take(first) {
+                write!(self.out, ",")?;
+            }
+        }
+
+        let mut fields = serde_json::to_string(&ExportedTreeEntry {
+            name: &entry.name,
+            entry_type: &entry.entry_type,
+            size: entry
+                .size
+                .or_else(|| entry.summ.as_ref().map(|summary| summary.size)),
+            mtime: &entry.mtime,
+            object_id: &entry.obj,
+        })?;
+        fields.pop(); // closing brace
+        self.out.write_all(fields.as_bytes())?;
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Policy Commands
+// ============================================================================
+
+/// List all policies
+///
+/// Returns all backup policies in the repository with their inheritance hierarchy.
+/// Policies control retention, scheduling, compression, and other backup behavior.
+#[tauri::command]
+pub async fn policies_list(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::PoliciesResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(fetch_policies(&client, &server_url).await)
+}
+
+/// Fetch all policies, retrying transient failures
+async fn fetch_policies(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<crate::types::PoliciesResponse> {
+    retry_request(|| async {
+        let response = client
+            .get(format!("{}/api/v1/policies", server_url))
+            .send()
+            .await
+            .map_http_error("Failed to list policies")?;
+
+        handle_response(response, "List policies").await
+    })
+    .await
+}
+
+/// Get policy for a specific target
+#[tauri::command]
+pub async fn policy_get(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+) -> Result<crate::types::PolicyWithTarget> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let query_string = build_policy_query(user_name.as_deref(), host.as_deref(), path.as_deref());
+
+    let response = client
+        .get(format!("{}/api/v1/policy{}", server_url, query_string))
+        .send()
+        .await
+        .map_http_error("Failed to get policy")?;
+
+    timer.finish(handle_response(response, "Get policy").await)
+}
+
+/// Resolve effective policy with inheritance
+#[tauri::command]
+pub async fn policy_resolve(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+    updates: Option<crate::types::PolicyDefinition>,
+) -> Result<crate::types::ResolvedPolicyResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let query_string = build_policy_query(user_name.as_deref(), host.as_deref(), path.as_deref());
+
+    // Build request payload - KopiaUI always sends updates field and numUpcomingSnapshotTimes
+    let mut payload = serde_json::Map::new();
+
+    if let Some(upd) = updates {
+        let value = serde_json::to_value(upd)?;
+        payload.insert("updates".to_string(), value);
+    } else {
+        // Send null for updates when just fetching (not modifying)
+        payload.insert("updates".to_string(), serde_json::Value::Null);
+    }
+
+    // Always request upcoming snapshot times
+    payload.insert("numUpcomingSnapshotTimes".to_string(), serde_json::json!(5));
+
+    let response = client
+        .post(format!(
+            "{}/api/v1/policy/resolve{}",
+            server_url, query_string
+        ))
+        .json(&payload)
+        .send()
+        .await
+        .map_http_error("Failed to resolve policy")?;
+
+    timer.finish(handle_response(response, "Resolve policy").await)
+}
+
+/// Resolve the ignore rules that apply to a target across policy inheritance
+///
+/// Collects ignore patterns and dot-ignore files from the global, host, user and
+/// parent directory policies down to the target itself, recording which policy
+/// defined each rule.
+#[tauri::command]
+pub async fn resolve_effective_ignores(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+) -> Result<crate::types::EffectiveIgnores> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let policies = fetch_policies(&client, &server_url).await?.policies;
+    let target = crate::types::PolicyTarget {
+        user_name,
+        host,
+        path,
+    };
+
+    timer.finish(Ok(effective_ignores(&policies, &target)))
+}
+
+/// Merge ignore rules of the policies that apply to `target`
+///
+/// Policies are applied from the most general to the most specific. A policy
+/// with `noParent` drops everything inherited so far; `noParentIgnore` and
+/// `noParentDotFiles` drop only the inherited patterns or dot-ignore files.
+pub(crate) fn effective_ignores(
+    policies: &[crate::types::PolicyWithTarget],
+    target: &crate::types::PolicyTarget,
+) -> crate::types::EffectiveIgnores {
+    use crate::types::IgnoreRuleOrigin;
+
+    let mut applicable: Vec<(u8, usize, &crate::types::PolicyWithTarget)> = policies
+        .iter()
+        .filter_map(|p| {
+            policy_level(&p.target, target).map(|level| {
+                let path_len = p.target.path.as_deref().map_or(0, str::len);
+                (level, path_len, p)
+            })
+        })
+        .collect();
+    applicable.sort_by_key(|(level, path_len, _)| (*level, *path_len));
+
+    let mut origins: Vec<IgnoreRuleOrigin> = Vec::new();
+    for (_, _, policy) in applicable {
+        let definition = &policy.policy;
+        let files = definition.files.clone().unwrap_or_default();
+
+        if definition.no_parent == Some(true) {
+            origins.clear();
+        }
+        if files.no_parent_ignore == Some(true) {
+            origins.retain(|o| o.dot_ignore_file);
+        }
+        if files.no_parent_dot_files == Some(true) {
+            origins.retain(|o| !o.dot_ignore_file);
+        }
+
+        let rules = files
+            .ignore
+            .unwrap_or_default()
+            .into_iter()
+            .map(|rule| (rule, false))
+            .chain(
+                files
+                    .ignore_dot_files
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|rule| (rule, true)),
+            );
+        for (rule, dot_ignore_file) in rules {
+            if !origins
+                .iter()
+                .any(|o| o.rule == rule && o.dot_ignore_file == dot_ignore_file)
+            {
+                origins.push(IgnoreRuleOrigin {
+                    rule,
+                    dot_ignore_file,
+                    target: policy.target.clone(),
+                });
+            }
+        }
+    }
+
+    let mut inherited_from: Vec<crate::types::PolicyTarget> = Vec::new();
+    for origin in &origins {
+        if !inherited_from.contains(&origin.target) {
+            inherited_from.push(origin.target.clone());
+        }
+    }
+
+    crate::types::EffectiveIgnores {
+        explicit: origins
+            .iter()
+            .filter(|o| !o.dot_ignore_file)
+            .map(|o| o.rule.clone())
+            .collect(),
+        dot_ignore_files: origins
+            .iter()
+            .filter(|o| o.dot_ignore_file)
+            .map(|o| o.rule.clone())
+            .collect(),
+        inherited_from,
+        origins,
+    }
+}
+
+/// Inheritance level of `policy` if it applies to `target`
+///
+/// 0 = global, 1 = host, 2 = user@host, 3 = directory (the target's own path or
+/// one of its parents).
+fn policy_level(
+    policy: &crate::types::PolicyTarget,
+    target: &crate::types::PolicyTarget,
+) -> Option<u8> {
+    let non_empty = |s: &Option<String>| s.clone().filter(|s| !s.is_empty());
+    let (host, user_name, path) = (
+        non_empty(&policy.host),
+        non_empty(&policy.user_name),
+        non_empty(&policy.path),
+    );
+
+    let Some(host) = host else {
+        return Some(0);
+    };
+    if non_empty(&target.host).as_ref() != Some(&host) {
+        return None;
+    }
+
+    let Some(user_name) = user_name else {
+        return Some(1);
+    };
+    if non_empty(&target.user_name).as_ref() != Some(&user_name) {
+        return None;
+    }
+
+    let Some(path) = path else {
+        return Some(2);
+    };
+    let target_path = non_empty(&target.path)?;
+    let is_parent = target_path == path
+        || (target_path.starts_with(&path)
+            && (path.ends_with(['/', '\\']) || target_path[path.len()..].starts_with(['/', '\\'])));
+
+    is_parent.then_some(3)
+}
+
+/// Compare a target's own policy with its effective policy
+///
+/// Returns every policy field with its defined and effective value, marking
+/// the ones the target doesn't set itself as inherited. Sorted by field.
+#[tauri::command]
+pub async fn policy_overrides(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+) -> Result<Vec<crate::types::PolicyFieldOverride>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let resolved = resolve_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+    )
+    .await?;
+
+    timer.finish(diff_policy(resolved.defined.as_ref(), &resolved.effective))
+}
+
+/// Diff the JSON fields of a defined and an effective policy
+///
+/// Objects are compared field by field; arrays and scalars are compared as a
+/// whole. Null values count as unset.
+pub(crate) fn diff_policy(
+    defined: Option<&crate::types::PolicyDefinition>,
+    effective: &crate::types::PolicyDefinition,
+) -> Result<Vec<crate::types::PolicyFieldOverride>> {
+    fn leaves(
+        prefix: &str,
+        value: serde_json::Value,
+        out: &mut std::collections::BTreeMap<String, serde_json::Value>,
+    ) {
+        match value {
+            serde_json::Value::Null => {}
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    let path = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    leaves(&path, value, out);
+                }
+            }
+            value => {
+                out.insert(prefix.to_string(), value);
+            }
+        }
+    }
+
+    let mut defined_fields = std::collections::BTreeMap::new();
+    if let Some(defined) = defined {
+        leaves("", serde_json::to_value(defined)?, &mut defined_fields);
+    }
+    let mut effective_fields = std::collections::BTreeMap::new();
+    leaves("", serde_json::to_value(effective)?, &mut effective_fields);
+
+    let fields: std::collections::BTreeSet<&String> = defined_fields
+        .keys()
+        .chain(effective_fields.keys())
+        .collect();
+
+    Ok(fields
+        .into_iter()
+        .map(|field| {
+            let defined_value = defined_fields.get(field).cloned();
+            crate::types::PolicyFieldOverride {
+                field: field.clone(),
+                inherited: defined_value.is_none(),
+                defined_value,
+                effective_value: effective_fields.get(field).cloned(),
+            }
+        })
+        .collect())
+}
+
+/// Set/update policy
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn policy_set(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+    policy: crate::types::PolicyDefinition,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(
+        set_policy_locked(
+            &app_state,
+            &client,
+            &server_url,
+            &repo_id,
+            user_name.as_deref(),
+            host.as_deref(),
+            path.as_deref(),
+            &policy,
+        )
+        .await,
+    )
+}
+
+/// Set a policy while holding its target's resource lock
+#[allow(clippy::too_many_arguments)] // Mirrors `policy_set`
+pub(crate) async fn set_policy_locked(
+    app_state: &AppState,
+    client: &reqwest::Client,
+    server_url: &str,
+    repo_id: &str,
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+    policy: &crate::types::PolicyDefinition,
+) -> Result<()> {
+    let _guard = app_state
+        .lock_resource(repo_id, &policy_target(user_name, host, path))
+        .await;
+    put_policy(client, server_url, user_name, host, path, policy).await
+}
+
+/// Delete policy (revert to inherited)
+#[tauri::command]
+pub async fn policy_delete(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(
+            &repo_id,
+            &policy_target(user_name.as_deref(), host.as_deref(), path.as_deref()),
+        )
+        .await;
+    timer.finish(
+        delete_policy(
+            &client,
+            &server_url,
+            user_name.as_deref(),
+            host.as_deref(),
+            path.as_deref(),
+        )
+        .await,
+    )
+}
+
+/// Delete several policies (revert to inherited) concurrently
+///
+/// Failures don't stop the batch; each target gets its own result. Duplicate
+/// targets are only deleted once.
+#[tauri::command]
+pub async fn policies_delete_batch(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    targets: Vec<crate::types::PolicyTarget>,
+) -> Result<Vec<crate::types::PolicyDeleteResult>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let timeout = fan_out_timeout(None, settings.lock().unwrap().get().fan_out_timeout_secs);
+
+    let mut unique: Vec<crate::types::PolicyTarget> = Vec::new();
+    for target in targets {
+        if !unique.iter().any(|t| t.to_string() == target.to_string()) {
+            unique.push(target);
+        }
+    }
+
+    // Lock all targets up front, in a fixed order so concurrent batches can't deadlock
+    let mut locked: Vec<&crate::types::PolicyTarget> = unique.iter().collect();
+    locked.sort_by_key(|t| t.to_string());
+    let mut guards = Vec::with_capacity(locked.len());
+    for target in locked {
+        guards.push(app_state.lock_resource(&repo_id, target).await);
+    }
+
+    let requests = unique
+        .iter()
+        .map(|target| {
+            let client = client.clone();
+            let server_url = server_url.clone();
+            let target = target.clone();
+            (target.to_string(), async move {
+                delete_policy(
+                    &client,
+                    &server_url,
+                    target.user_name.as_deref(),
+                    target.host.as_deref(),
+                    target.path.as_deref(),
+                )
+                .await
+            })
+        })
+        .collect();
+
+    let result = fan_out(requests, timeout).await;
+    timer.finish(Ok(build_policy_delete_results(unique, result, timeout)))
+}
+
+/// Enable or disable the "one file system" boundary for a policy target
+///
+/// When enabled, snapshots of the target don't descend into other mounted
+/// filesystems (e.g. network mounts under a root). Only `files.oneFileSystem`
+/// is changed; the rest of the defined policy is preserved.
+/// Returns the resolved policy after the change.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn source_set_one_filesystem(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+    enabled: bool,
+) -> Result<crate::types::ResolvedPolicyResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(
+            &repo_id,
+            &policy_target(user_name.as_deref(), host.as_deref(), path.as_deref()),
+        )
+        .await;
+
+    let mut policy = get_defined_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+    )
+    .await?;
+
+    let files = policy.files.get_or_insert_with(Default::default);
+    files.one_file_system = Some(enabled);
+
+    put_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+        &policy,
+    )
+    .await?;
+
+    timer.finish(
+        resolve_policy(
+            &client,
+            &server_url,
+            user_name.as_deref(),
+            host.as_deref(),
+            path.as_deref(),
+        )
+        .await,
+    )
+}
+
+/// Apply a recommended starting policy to a policy target
+///
+/// Sets the retention, compression and ignore rules of the profile (see
+/// `recommended_policy` for a preview). Other settings of the defined policy
+/// are kept, and existing ignore rules are kept alongside the recommended ones.
+/// Returns the resolved policy after the change.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn apply_recommended_policy(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+    profile: String,
+) -> Result<crate::types::ResolvedPolicyResponse> {
+    let recommended = crate::commands::policy_helpers::recommended_policy_for(&profile)?;
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(
+            &repo_id,
+            &policy_target(user_name.as_deref(), host.as_deref(), path.as_deref()),
+        )
+        .await;
+
+    let existing = get_defined_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+    )
+    .await?;
+
+    put_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+        &merge_recommended_policy(&existing, &recommended)?,
+    )
+    .await?;
+
+    timer.finish(
+        resolve_policy(
+            &client,
+            &server_url,
+            user_name.as_deref(),
+            host.as_deref(),
+            path.as_deref(),
+        )
+        .await,
+    )
+}
+
+/// Layer a recommended policy over a defined one, keeping existing ignore rules
+pub(crate) fn merge_recommended_policy(
+    existing: &crate::types::PolicyDefinition,
+    recommended: &crate::types::PolicyDefinition,
+) -> Result<crate::types::PolicyDefinition> {
+    let mut merged = overlay_policy(existing, recommended)?;
+
+    let existing_ignore = existing.files.as_ref().and_then(|f| f.ignore.clone());
+    if let (Some(mut ignore), Some(files)) = (existing_ignore, merged.files.as_mut()) {
+        for rule in files.ignore.take().unwrap_or_default() {
+            if !ignore.contains(&rule) {
+                ignore.push(rule);
+            }
+        }
+        files.ignore = Some(ignore);
+    }
+
+    Ok(merged)
+}
+
+/// Get the "one file system" setting effective for a policy target
+#[tauri::command]
+pub async fn source_get_one_filesystem(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+) -> Result<bool> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let resolved = resolve_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+    )
+    .await?;
+
+    timer.finish(Ok(resolved
+        .effective
+        .files
+        .and_then(|files| files.one_file_system)
+        .unwrap_or(false)))
+}
+
+/// Set the upload parallelism for a policy target
+///
+/// Only the `upload` section of the defined policy is replaced; fields left
+/// empty are inherited from the parent policy. Values must be positive.
+/// Returns the resolved policy after the change.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn source_set_parallelism(
+    timer: CommandTimer,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+    upload: crate::types::UploadPolicy,
+) -> Result<crate::types::ResolvedPolicyResponse> {
+    validate_upload_policy(&upload)?;
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let _guard = app_state
+        .lock_resource(
+            &repo_id,
+            &policy_target(user_name.as_deref(), host.as_deref(), path.as_deref()),
+        )
+        .await;
+
+    let mut policy = get_defined_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+    )
+    .await?;
+    policy.upload = Some(upload);
+
+    put_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+        &policy,
+    )
+    .await?;
+
+    timer.finish(
+        resolve_policy(
+            &client,
+            &server_url,
+            user_name.as_deref(),
+            host.as_deref(),
+            path.as_deref(),
+        )
+        .await,
+    )
+}
+
+/// Get the upload parallelism effective for a policy target
+#[tauri::command]
+pub async fn source_get_parallelism(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: Option<String>,
+    host: Option<String>,
+    path: Option<String>,
+) -> Result<crate::types::UploadPolicy> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let resolved = resolve_policy(
+        &client,
+        &server_url,
+        user_name.as_deref(),
+        host.as_deref(),
+        path.as_deref(),
+    )
+    .await?;
+
+    timer.finish(Ok(resolved.effective.upload.unwrap_or_default()))
+}
+
+/// Choose whether failed snapshots of one source raise a notification
+///
+/// Overrides the global notification settings for this source only, e.g. to
+/// notify just for critical sources. The source must exist in the repository.
+/// Returns the updated settings.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)] // Tauri command parameters map to invoke arguments
+pub async fn source_set_notify_on_error(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+    enabled: bool,
+) -> Result<crate::settings::AppSettings> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+
+    let sources = fetch_sources(&client, &server_url).await?;
+    if !sources.sources.iter().any(|s| s.source == source) {
+        return Err(KopiaError::NotFound {
+            resource: format!("Source {}", source),
+        });
+    }
+
+    timer.finish(
+        settings
+            .lock()
+            .unwrap()
+            .update(|s| s.set_source_notify_on_error(&repo_id, &source, enabled)),
+    )
+}
+
+/// Check whether a failed task should raise a notification
+///
+/// False only for snapshot tasks whose source has notifications turned off
+/// with `source_set_notify_on_error`.
+#[tauri::command]
+pub async fn task_notify_on_failure(
+    timer: CommandTimer,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    task: crate::types::Task,
+) -> Result<bool> {
+    timer.finish(Ok(notify_on_task_failure(
+        &settings.lock().unwrap().get(),
+        &repo_id,
+        &task,
+    )))
+}
+
+/// Whether a failed task should notify, given the per-source overrides
+pub(crate) fn notify_on_task_failure(
+    settings: &crate::settings::AppSettings,
+    repo_id: &str,
+    task: &crate::types::Task,
+) -> bool {
+    if task.kind != "Snapshot" {
+        return true;
+    }
+    parse_task_source(&task.description)
+        .and_then(|source| settings.source_notify_on_error(repo_id, &source))
+        .unwrap_or(true)
+}
+
+// ============================================================================
+// Task Commands
+// ============================================================================
+
+/// List all tasks
+///
+/// Returns all active and recent tasks including snapshots, maintenance, and restore operations.
+/// Each task includes status, progress, start time, and error information (if failed).
+/// Finished tasks hidden with `tasks_clear_completed` are left out.
+/// Completed snapshots are added to the upload throughput history.
+#[tauri::command]
+pub async fn tasks_list(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+) -> Result<crate::types::TasksResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let mut tasks = retry_request(|| fetch_tasks(&client, &server_url)).await?;
+
+    {
+        let mut settings = settings.lock().unwrap();
+        let current = settings.get();
+
+        let samples: Vec<_> = throughput_samples(&repo_id, &tasks)
+            .into_iter()
+            .filter(|sample| !current.has_throughput_sample(sample))
+            .collect();
+        if !samples.is_empty() {
+            if let Err(e) = settings.update(|s| s.record_throughput(samples)) {
+                log::warn!("Failed to save upload throughput history: {}", e);
+            }
+        }
+
+        if let Some(cutoff) = current.tasks_cleared_before(&repo_id) {
+            tasks.retain(|task| !is_cleared_task(task, cutoff));
+        }
+    }
+
+    manager
+        .lock()
+        .unwrap()
+        .record_tasks(&repo_id, tasks.clone(), &chrono::Utc::now().to_rfc3339());
+    timer.finish(Ok(crate::types::TasksResponse { tasks }))
+}
+
+/// Get the upload throughput of recently completed snapshots
+///
+/// Samples are taken from successful snapshot tasks seen by `tasks_list`
+/// (uploaded bytes over the task's duration) and kept in the settings, so the
+/// history survives restarts. Snapshots that uploaded nothing are left out.
+/// Returns up to `limit` samples across all repositories, newest first.
+#[tauri::command]
+pub async fn upload_throughput_history(
+    timer: CommandTimer,
+    settings: State<'_, SettingsState>,
+    limit: usize,
+) -> Result<Vec<crate::settings::ThroughputSample>> {
+    let mut samples = settings.lock().unwrap().get().upload_throughput;
+    samples.reverse();
+    samples.truncate(limit);
+    timer.finish(Ok(samples))
+}
+
+/// Throughput of the successful snapshot tasks that uploaded data
+pub(crate) fn throughput_samples(
+    repo_id: &str,
+    tasks: &[crate::types::Task],
+) -> Vec<crate::settings::ThroughputSample> {
+    let parse = |t: &str| chrono::DateTime::parse_from_rfc3339(t).ok();
+
+    tasks
+        .iter()
+        .filter(|task| task.kind == "Snapshot" && task.status == "SUCCESS")
+        .filter_map(|task| {
+            let end_time = task.end_time.as_deref()?;
+            let seconds =
+                (parse(end_time)? - parse(&task.start_time)?).num_milliseconds() as f64 / 1000.0;
+            let uploaded = task.counters.get("Uploaded Bytes")?.value;
+            if seconds <= 0.0 || uploaded <= 0 {
+                return None;
+            }
+
+            let source = parse_task_source(&task.description)
+                .map(|s| format!("{}@{}:{}", s.user_name, s.host, s.path))
+                .unwrap_or_else(|| task.description.clone());
+            Some(crate::settings::ThroughputSample {
+                repo_id: repo_id.to_string(),
+                time: end_time.to_string(),
+                bytes_per_second: uploaded as f64 / seconds,
+                source,
+            })
+        })
+        .collect()
+}
+
+/// Get task details
+#[tauri::command]
+pub async fn task_get(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    task_id: String,
+) -> Result<crate::types::TaskDetail> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(fetch_task(&client, &server_url, &task_id).await)
+}
+
+/// Fetch a task with its counters and logs
+async fn fetch_task(
+    client: &reqwest::Client,
+    server_url: &str,
+    task_id: &str,
+) -> Result<crate::types::TaskDetail> {
+    let response = client
+        .get(format!("{}/api/v1/tasks/{}", server_url, task_id))
+        .send()
+        .await
+        .map_http_error("Failed to get task")?;
+
+    handle_response(response, "Get task").await
+}
+
+/// Get task counters flagged as warnings or errors
+///
+/// Returns counters whose level is "warning" or "error" (e.g. ignored errors
+/// that indicate a partial backup), errors first.
+#[tauri::command]
+pub async fn task_warnings(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    task_id: String,
+) -> Result<Vec<crate::types::TaskCounterWarning>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let detail = fetch_task(&client, &server_url, &task_id).await?;
+
+    let counters = detail
+        .counters
+        .filter(|counters| !counters.is_empty())
+        .unwrap_or(detail.task.counters);
+
+    timer.finish(Ok(counter_warnings(&counters)))
+}
+
+/// Get task logs
+#[tauri::command]
+pub async fn task_logs(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    task_id: String,
+) -> Result<Vec<String>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .get(format!("{}/api/v1/tasks/{}/logs", server_url, task_id))
+        .send()
+        .await
+        .map_http_error("Failed to get task logs")?;
+
+    #[derive(Deserialize)]
+    struct LogsResponse {
+        logs: Vec<String>,
+    }
+
+    let result: LogsResponse = handle_response(response, "Get task logs").await?;
+
+    timer.finish(Ok(result.logs))
+}
+
+/// Cancel a task
+#[tauri::command]
+pub async fn task_cancel(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    task_id: String,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(cancel_task(&client, &server_url, &task_id).await)
+}
+
+/// How often a throttled task is checked for completion
+const TASK_THROTTLE_POLL_SECS: u64 = 5;
+
+/// Limit the upload speed of a running task
+///
+/// Kopia has no per-task throttling, so as a workaround this lowers the
+/// repository-wide upload limit, which also slows other uploads to the same
+/// repository. The previous limits are restored once the task (and any other
+/// task throttled this way) has finished, unless the upload limit was changed
+/// in the meantime. Returns the applied limit in bytes per second.
+#[tauri::command]
+pub async fn task_throttle(
+    timer: CommandTimer,
+    app: AppHandle,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+    task_id: String,
+    bytes_per_second: f64,
+) -> Result<f64> {
+    if !bytes_per_second.is_finite() || bytes_per_second <= 0.0 {
+        return Err(KopiaError::invalid_input(
+            "bytesPerSecond",
+            "Upload limit must be a positive number of bytes per second",
+        ));
+    }
+
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let detail = fetch_task(&client, &server_url, &task_id).await?;
+    if !is_task_active(&detail.task.status) {
+        return Err(KopiaError::invalid_input(
+            "taskId",
+            format!("Task '{}' is not running", task_id),
+        ));
+    }
+
+    // Held until the override is recorded, so the original limits can't
+    // be read while another override is applied or undone
+    let _guard = app_state
+        .lock_resource(&repo_id, &crate::types::PolicyTarget::default())
+        .await;
+    let throttles = app_state.task_throttles();
+    let original = match throttles.original(&repo_id) {
+        Some(original) => original,
+        None => fetch_throttle(&client, &server_url).await?,
+    };
+    put_throttle(
+        &client,
+        &server_url,
+        &throttled_limits(&original, bytes_per_second),
+    )
+    .await?;
+
+    if throttles.apply(&repo_id, &task_id, original, bytes_per_second) {
+        tauri::async_runtime::spawn(restore_throttle_when_done(
+            app, client, server_url, repo_id, task_id,
+        ));
+    }
+
+    timer.finish(Ok(bytes_per_second))
+}
+
+/// Copy of `limits` with the upload speed capped at `bytes_per_second`
+pub(crate) fn throttled_limits(
+    limits: &crate::types::ThrottleLimits,
+    bytes_per_second: f64,
+) -> crate::types::ThrottleLimits {
+    crate::types::ThrottleLimits {
+        max_upload_speed_bytes_per_second: Some(bytes_per_second),
+        ..limits.clone()
+    }
+}
+
+/// Check whether a task status means the task hasn't finished
+fn is_task_active(status: &str) -> bool {
+    matches!(status, "RUNNING" | "CANCELING")
+}
+
+/// Wait for a throttled task to end, then undo its throttle override
+async fn restore_throttle_when_done(
+    app: AppHandle,
+    client: reqwest::Client,
+    server_url: String,
+    repo_id: String,
+    task_id: String,
+) {
+    use tauri::Manager;
+
+    let mut interval =
+        tokio::time::interval(std::time::Duration::from_secs(TASK_THROTTLE_POLL_SECS));
+    loop {
+        interval.tick().await;
+        match fetch_task(&client, &server_url, &task_id).await {
+            Ok(detail) if is_task_active(&detail.task.status) => continue,
+            Ok(_) => break,
+            Err(e) => {
+                log::debug!("Stopped watching throttled task '{}': {}", task_id, e);
+                break;
+            }
+        }
+    }
+
+    let app_state = app.state::<AppState>();
+    let _guard = app_state
+        .lock_resource(&repo_id, &crate::types::PolicyTarget::default())
+        .await;
+    if let Some(throttle) = app_state.task_throttles().finish(&repo_id, &task_id) {
+        restore_throttle(&client, &server_url, &repo_id, &throttle).await;
+    }
+}
+
+/// Undo a task throttle override
+///
+/// The caller must hold the repository-wide resource lock. The limits are
+/// left alone if the user changed the upload limit meanwhile.
+pub(crate) async fn restore_throttle(
+    client: &reqwest::Client,
+    server_url: &str,
+    repo_id: &str,
+    throttle: &crate::app_state::ThrottleOverride,
+) {
+    match fetch_throttle(client, server_url).await {
+        Ok(current) if current.max_upload_speed_bytes_per_second != Some(throttle.applied) => {
+            log::info!(
+                "Upload limit of '{}' changed while throttled, not restoring it",
+                repo_id
+            );
+            return;
+        }
+        Ok(_) => {}
+        Err(e) => {
+            log::warn!("Failed to restore throttle limits of '{}': {}", repo_id, e);
+            return;
+        }
+    }
+
+    if let Err(e) = put_throttle(client, server_url, &throttle.original).await {
+        log::warn!("Failed to restore throttle limits of '{}': {}", repo_id, e);
+    }
+}
+
+/// Get task summary
+#[tauri::command]
+pub async fn tasks_summary(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::TasksSummary> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(fetch_tasks_summary(&client, &server_url).await)
+}
+
+/// Fetch task counts by status
+async fn fetch_tasks_summary(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<crate::types::TasksSummary> {
+    let response = client
+        .get(format!("{}/api/v1/tasks-summary", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to get tasks summary")?;
+
+    handle_response(response, "Get tasks summary").await
+}
+
+/// Check whether any operation is in progress
+///
+/// Without `repo_id`, all running servers are checked, including external
+/// ones. Servers that don't answer within the fan-out timeout or fail are
+/// listed in `failedRepoIds` and the result is marked `partial`.
+#[tauri::command]
+pub async fn has_active_operations(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: Option<String>,
+) -> Result<crate::types::ActiveOperations> {
+    let timeout = fan_out_timeout(None, settings.lock().unwrap().get().fan_out_timeout_secs);
+    timer.finish(Ok(collect_active_operations(
+        &manager,
+        repo_id.as_deref(),
+        true,
+        timeout,
+    )
+    .await))
+}
+
+/// Get the combined progress of all running tasks
+///
+/// Sums the byte counters of every running task for a single progress bar.
+/// Tasks without an estimate still add to `bytesDone` but are left out of
+/// `percent`, which is a best-effort figure weighted by estimated bytes.
+#[tauri::command]
+pub async fn overall_progress(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::OverallProgress> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let tasks = fetch_tasks(&client, &server_url).await?;
+    timer.finish(Ok(compute_overall_progress(&tasks)))
+}
+
+/// Sum the byte counters of running tasks
+pub(crate) fn compute_overall_progress(
+    tasks: &[crate::types::Task],
+) -> crate::types::OverallProgress {
+    let counter = |task: &crate::types::Task, name: &str| {
+        task.counters.get(name).map(|counter| counter.value.max(0))
+    };
+
+    let mut progress = crate::types::OverallProgress::default();
+    let mut estimated_done = 0i64;
+    for task in tasks.iter().filter(|task| is_task_active(&task.status)) {
+        progress.active_tasks += 1;
+
+        // Older servers don't report "Processed Bytes"
+        let done = counter(task, "Processed Bytes").unwrap_or_else(|| {
+            counter(task, "Hashed Bytes").unwrap_or(0) + counter(task, "Cached Bytes").unwrap_or(0)
+        });
+        progress.bytes_done += done;
+
+        if let Some(total) = counter(task, "Estimated Bytes").filter(|total| *total > 0) {
+            progress.bytes_total += total;
+            estimated_done += done.min(total);
+        }
+    }
+
+    if progress.bytes_total > 0 {
+        progress.percent = estimated_done as f64 * 100.0 / progress.bytes_total as f64;
+    }
+    progress
+}
+
+/// Gap between the two samples `source_progress` uses for throughput
+const SOURCE_PROGRESS_SAMPLE_INTERVAL_MS: u64 = 500;
+
+/// Get upload progress of a single source
+///
+/// Reads the source's upload counters twice, `SOURCE_PROGRESS_SAMPLE_INTERVAL_MS`
+/// apart, and reports the second sample with a throughput derived from the
+/// difference. Fields are empty when the source isn't uploading.
+#[tauri::command]
+pub async fn source_progress(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    user_name: String,
+    host: String,
+    path: String,
+) -> Result<crate::types::UploadProgress> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let source = crate::types::SourceInfo {
+        user_name,
+        host,
+        path,
+    };
+    let not_found = || KopiaError::NotFound {
+        resource: format!("source {}", source),
+    };
+
+    let first = find_snapshot_source(&client, &server_url, &source)
+        .await?
+        .ok_or_else(not_found)?;
+    let sampled_at = std::time::Instant::now();
+    tokio::time::sleep(std::time::Duration::from_millis(
+        SOURCE_PROGRESS_SAMPLE_INTERVAL_MS,
+    ))
+    .await;
+    let second = find_snapshot_source(&client, &server_url, &source)
+        .await?
+        .ok_or_else(not_found)?;
+
+    timer.finish(Ok(build_upload_progress(
+        &first,
+        &second,
+        sampled_at.elapsed(),
+    )))
+}
+
+/// Build `UploadProgress` from two samples of a source's status
+pub(crate) fn build_upload_progress(
+    first: &crate::types::SnapshotSource,
+    second: &crate::types::SnapshotSource,
+    elapsed: std::time::Duration,
+) -> crate::types::UploadProgress {
+    let mut progress = crate::types::UploadProgress {
+        status: second.status.clone(),
+        ..Default::default()
+    };
+    let Some(upload) = &second.upload else {
+        return progress;
+    };
+
+    progress.bytes_done = upload_bytes_done(upload);
+    progress.estimated_bytes = upload.estimated_bytes;
+    progress.percent_complete = upload_percent(upload);
+    progress.hashed_files = upload.hashed_files;
+    progress.cached_files = upload.cached_files;
+    progress.estimated_files = upload.estimated_files;
+    progress.errors = upload.errors;
+    progress.current_directory = Some(upload.directory.clone()).filter(|dir| !dir.is_empty());
+    progress.throughput_bytes_per_sec = first.upload.as_ref().and_then(|before| {
+        upload_throughput(upload_bytes_done(before), progress.bytes_done, elapsed)
+    });
+    progress
+}
+
+/// Bytes an upload has processed so far (hashed + cached)
+fn upload_bytes_done(upload: &crate::types::UploadCounters) -> i64 {
+    upload.hashed_bytes.max(0) + upload.cached_bytes.max(0)
+}
+
+/// Upload completion, 0-100, or `None` while there's no estimate
+pub(crate) fn upload_percent(upload: &crate::types::UploadCounters) -> Option<f64> {
+    if upload.estimated_bytes <= 0 {
+        return None;
+    }
+    let percent = upload_bytes_done(upload) as f64 * 100.0 / upload.estimated_bytes as f64;
+    Some(percent.min(100.0))
+}
+
+/// Bytes per second between two samples
+///
+/// `None` when no time passed or the counter went backwards (a new upload
+/// started between the samples).
+pub(crate) fn upload_throughput(
+    before: i64,
+    after: i64,
+    elapsed: std::time::Duration,
+) -> Option<f64> {
+    if elapsed.is_zero() || after < before {
+        return None;
+    }
+    Some((after - before) as f64 / elapsed.as_secs_f64())
+}
+
+/// Get task counts grouped by kind
+///
+/// Returns running/success/failed/canceled counts for each task kind
+/// (Snapshot, Maintenance, Restore, ...) plus a `total` entry across all kinds.
+#[tauri::command]
+pub async fn tasks_summary_by_kind(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<HashMap<String, crate::types::TasksSummary>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let tasks = fetch_tasks(&client, &server_url).await?;
+    timer.finish(Ok(summarize_tasks_by_kind(&tasks)))
+}
+
+/// Hide finished tasks from `tasks_list`
+///
+/// Kopia keeps finished tasks in memory until the server restarts and has no
+/// endpoint to remove them, so they're hidden on the client instead: tasks
+/// that succeeded, failed or were canceled more than `older_than_secs` ago
+/// (default: now) stop being listed. The cutoff is saved in the settings, and
+/// running tasks are never hidden. Returns how many listed tasks were hidden.
+#[tauri::command]
+pub async fn tasks_clear_completed(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    older_than_secs: Option<u64>,
+) -> Result<usize> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let tasks = fetch_tasks(&client, &server_url).await?;
+
+    let now = chrono::Utc::now();
+    let cutoff = older_than_secs
+        .and_then(|secs| chrono::Duration::try_seconds(i64::try_from(secs).ok()?))
+        .and_then(|age| now.checked_sub_signed(age))
+        .unwrap_or(now);
+
+    let mut settings = settings.lock().unwrap();
+    let previous = settings.get().tasks_cleared_before(&repo_id);
+    let cleared = tasks
+        .iter()
+        .filter(|task| is_cleared_task(task, cutoff))
+        .filter(|task| previous.is_none_or(|previous| !is_cleared_task(task, previous)))
+        .count();
+
+    settings.update(|s| s.clear_tasks_before(&repo_id, cutoff))?;
+    timer.finish(Ok(cleared))
+}
+
+/// Check whether a task finished at or before `cutoff`
+pub(crate) fn is_cleared_task(
+    task: &crate::types::Task,
+    cutoff: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    if is_task_active(&task.status) {
+        return false;
+    }
+    task.end_time
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|end| end <= cutoff)
+}
+
+/// Get recent activity as a single chronological feed
+///
+/// Combines finished snapshot, restore and maintenance tasks and failures of
+/// any task, newest first.
+#[tauri::command]
+pub async fn activity_feed(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    limit: usize,
+) -> Result<Vec<crate::types::ActivityItem>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let tasks = fetch_tasks(&client, &server_url).await?;
+    timer.finish(Ok(build_activity_feed(&tasks, limit)))
+}
+
+/// Find running tasks that appear to be stuck
+///
+/// Returns tasks that have been running for at least `older_than_secs` and made
+/// no progress between two samples taken a few seconds apart. Stuck tasks can
+/// be cleared with `task_cancel`.
+#[tauri::command]
+pub async fn tasks_find_stuck(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    older_than_secs: u64,
+) -> Result<Vec<crate::types::Task>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let now = chrono::Utc::now();
+    let candidates: Vec<_> = fetch_tasks(&client, &server_url)
+        .await?
+        .into_iter()
+        .filter(|task| is_long_running(task, now, older_than_secs))
+        .collect();
+
+    if candidates.is_empty() {
+        return timer.finish(Ok(Vec::new()));
+    }
+
+    tokio::time::sleep(std::time::Duration::from_secs(STUCK_TASK_SAMPLE_DELAY_SECS)).await;
+
+    let current = fetch_tasks(&client, &server_url).await?;
+    timer.finish(Ok(find_stuck_tasks(&candidates, &current)))
+}
+
+/// Get content-level storage statistics of a snapshot
+///
+/// The REST API leaves out the storage stats recorded in snapshot manifests, so
+/// the manifests are read via `kopia snapshot list --all --json`. Snapshots
+/// without storage stats return `statsAvailable: false`.
+#[tauri::command]
+pub async fn snapshot_content_stats(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    manifest_id: String,
+) -> Result<crate::types::SnapshotContentStats> {
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    let snapshots: Vec<crate::types::Snapshot> = kopia_cli::run_json(
+        &config_file,
+        &["snapshot", "list", "--all"],
+        "List snapshot manifests",
+    )
+    .await?;
+
+    let snapshot = snapshots
+        .iter()
+        .find(|s| s.id == manifest_id)
+        .ok_or_else(|| KopiaError::NotFound {
+            resource: format!("Snapshot {}", manifest_id),
+        })?;
+
+    timer.finish(Ok(snapshot_content_stats_of(snapshot)))
+}
+
+/// Extract the content statistics from a snapshot manifest
+pub(crate) fn snapshot_content_stats_of(
+    snapshot: &crate::types::Snapshot,
+) -> crate::types::SnapshotContentStats {
+    let stats = snapshot.storage_stats.as_ref();
+    crate::types::SnapshotContentStats {
+        snapshot_id: snapshot.id.clone(),
+        stats_available: stats.is_some(),
+        new_data: stats.map(|s| (&s.new_data).into()),
+        running_total: stats.map(|s| (&s.running_total).into()),
+    }
+}
+
+/// Get the paths excluded by ignore rules during a snapshot task
+///
+/// Parses the task logs for the entries Kopia logs when it skips ignored files and
+/// directories. Kopia only logs these when the logging policy's `ignored` level is
+/// above zero, so the effective policy of the snapshotted source is checked first.
+#[tauri::command]
+pub async fn snapshot_excluded_paths(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    task_id: String,
+) -> Result<Vec<String>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .get(format!("{}/api/v1/tasks/{}", server_url, task_id))
+        .send()
+        .await
+        .map_http_error("Failed to get task")?;
+
+    let task: crate::types::Task = handle_response(response, "Get task").await?;
+
+    // Fall back to the global policy if the source can't be derived from the task
+    let source = parse_task_source(&task.description);
+    let resolved = resolve_policy(
+        &client,
+        &server_url,
+        source.as_ref().map(|s| s.user_name.as_str()),
+        source.as_ref().map(|s| s.host.as_str()),
+        source.as_ref().map(|s| s.path.as_str()),
+    )
+    .await?;
+
+    if !logs_ignored_entries(&resolved.effective) {
+        return Err(KopiaError::operation_failed(
+            "excluded paths",
+            "Excluded entries are not logged for this source. Set the logging policy's \
+     'ignored' level above 0 and take a new snapshot.",
+        ));
+    }
+
+    let response = client
+        .get(format!("{}/api/v1/tasks/{}/logs", server_url, task_id))
+        .send()
+        .await
+        .map_http_error("Failed to get task logs")?;
+
+    #[derive(Deserialize)]
+    struct LogsResponse {
+        #[serde(default)]
+        logs: Vec<serde_json::Value>,
+    }
+
+    let result: LogsResponse = handle_response(response, "Get task logs").await?;
+
+    timer.finish(Ok(parse_excluded_paths(&result.logs)))
+}
+
+// ============================================================================
+// Maintenance Commands
+// ============================================================================
+
+/// Get the repository's maintenance owner
+///
+/// Only the owner (`user@host`) runs maintenance on a repository, so maintenance
+/// controls have no effect on other clients. `isSelf` tells whether this client
+/// is the owner.
+#[tauri::command]
+pub async fn maintenance_owner(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::MaintenanceOwner> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    let info = fetch_maintenance_info(&config_file).await?;
+    let identity = fetch_current_identity(&client, &server_url).await?;
+
+    timer.finish(Ok(crate::types::MaintenanceOwner {
+        is_self: info.params.owner == identity,
+        owner: info.params.owner,
+    }))
+}
+
+/// Take ownership of repository maintenance
+///
+/// Makes this client (`user@host` of the server) the maintenance owner, as
+/// `kopia maintenance set --owner=me` does. Returns the new owner.
+#[tauri::command]
+pub async fn maintenance_claim(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::MaintenanceOwner> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    kopia_cli::run(
+        &config_file,
+        &["maintenance", "set", "--owner=me"],
+        "Claim maintenance ownership",
+    )
+    .await?;
+
+    let info = fetch_maintenance_info(&config_file).await?;
+    let identity = fetch_current_identity(&client, &server_url).await?;
+
+    timer.finish(Ok(crate::types::MaintenanceOwner {
+        is_self: info.params.owner == identity,
+        owner: info.params.owner,
+    }))
+}
+
+/// Get the maintenance parameters and schedule
+#[tauri::command]
+pub async fn maintenance_info(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::MaintenanceInfo> {
+    let config_file = get_config_file(&manager, &repo_id)?;
+    timer.finish(fetch_maintenance_info(&config_file).await)
+}
+
+/// Run quick (or with `full`, full) maintenance now
+///
+/// Runs `kopia maintenance run`, which fails unless this client is the
+/// maintenance owner. Returns once maintenance has finished, or with an error
+/// if it was stopped with `maintenance_cancel`.
+#[tauri::command]
+pub async fn maintenance_run(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+    full: bool,
+) -> Result<()> {
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    timer.finish(run_maintenance(&app_state, &config_file, &repo_id, full).await)
+}
+
+/// Cancel running maintenance
+///
+/// Stops maintenance started with `maintenance_run` or `maintenance_run_if_due`
+/// by killing its Kopia CLI process, and cancels a maintenance task the server
+/// runs on its own. Returns the IDs of the canceled operations and tasks
+/// (empty if no maintenance was running).
+#[tauri::command]
+pub async fn maintenance_cancel(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+) -> Result<Vec<String>> {
+    let mut canceled = app_state
+        .app_operations()
+        .cancel(&repo_id, MAINTENANCE_OPERATION_KIND);
+
+    match get_server_client(&manager, &repo_id) {
+        Ok((server_url, client)) => {
+            let tasks = fetch_tasks(&client, &server_url).await?;
+            if let Some(task) = running_maintenance_task(&tasks) {
+                cancel_task(&client, &server_url, &task.id).await?;
+                canceled.push(task.id.clone());
+            }
+        }
+        // CLI runs don't need the server
+        Err(e) if canceled.is_empty() => return Err(e),
+        Err(_) => {}
+    }
+
+    if !canceled.is_empty() {
+        log::info!(
+            "Canceled maintenance {} for '{}'",
+            canceled.join(", "),
+            repo_id
+        );
+    }
+    timer.finish(Ok(canceled))
+}
+
+/// Kind of maintenance runs, as used for server tasks and app operations
+const MAINTENANCE_OPERATION_KIND: &str = "Maintenance";
+/// Kind of `restore_files` and `object_download` app operations, as used for
+/// server restore tasks
+const RESTORE_OPERATION_KIND: &str = "Restore";
+/// Kind of CLI snapshot verifications run as app operations
+const VERIFY_OPERATION_KIND: &str = "Verify";
+
+/// The running maintenance task, if any
+pub(crate) fn running_maintenance_task(
+    tasks: &[crate::types::Task],
+) -> Option<&crate::types::Task> {
+    tasks
+        .iter()
+        .find(|task| task.kind == MAINTENANCE_OPERATION_KIND && task.status == "RUNNING")
+}
+
+/// Longest a maintenance run may take
+const MAINTENANCE_RUN_TIMEOUT_SECS: u64 = 6 * 60 * 60;
+
+/// Run maintenance if it's due
+///
+/// Checks the maintenance schedule and runs quick (or with `full`, full)
+/// maintenance via `kopia maintenance run` only when this client is the owner,
+/// the cycle is enabled and its next run time has passed. Returns once
+/// maintenance has finished; calling it again afterwards does nothing until the
+/// next run is due. Kopia's maintenance lock keeps overlapping runs out.
+#[tauri::command]
+pub async fn maintenance_run_if_due(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    app_state: State<'_, AppState>,
+    repo_id: String,
+    full: bool,
+) -> Result<crate::types::MaintenanceRunIfDueResult> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    let info = fetch_maintenance_info(&config_file).await?;
+    let identity = fetch_current_identity(&client, &server_url).await?;
+
+    if let Some(reason) = maintenance_skip_reason(&info, &identity, full, chrono::Utc::now()) {
+        log::debug!("Not running maintenance for '{}': {}", repo_id, reason);
+        return timer.finish(Ok(crate::types::MaintenanceRunIfDueResult {
+            ran: false,
+            reason: Some(reason),
+        }));
+    }
+
+    run_maintenance(&app_state, &config_file, &repo_id, full).await?;
+
+    timer.finish(Ok(crate::types::MaintenanceRunIfDueResult {
+        ran: true,
+        reason: None,
+    }))
+}
+
+/// Run quick or full maintenance via the CLI, as an app operation
+async fn run_maintenance(
+    app_state: &AppState,
+    config_file: &str,
+    repo_id: &str,
+    full: bool,
+) -> Result<()> {
+    let mut args = vec!["maintenance", "run"];
+    if full {
+        args.push("--full");
+    }
+    log::info!(
+        "Running {} maintenance for '{}'",
+        if full { "full" } else { "quick" },
+        repo_id
+    );
+    let run = kopia_cli::run_with_timeout(
+        config_file,
+        &args,
+        "Run maintenance",
+        std::time::Duration::from_secs(MAINTENANCE_RUN_TIMEOUT_SECS),
+    );
+
+    run_app_operation(
+        app_state,
+        repo_id,
+        MAINTENANCE_OPERATION_KIND,
+        "Run maintenance",
+        run,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Run `body` registered as an app operation of `kind`
+///
+/// Canceling the operation drops `body` (killing a CLI process it runs) and
+/// fails with `operation`.
+async fn run_app_operation<T>(
+    app_state: &AppState,
+    repo_id: &str,
+    kind: &str,
+    operation: &str,
+    body: impl std::future::Future<Output = Result<T>>,
+) -> Result<T> {
+    let registered = app_state.begin_operation(repo_id, kind);
+    tokio::select! {
+        result = body => result,
+        _ = registered.canceled() => {
+            log::info!("{} {} for '{}' canceled", kind, registered.id(), repo_id);
+            Err(KopiaError::operation_failed(operation, "Canceled"))
+        }
+    }
+}
+
+/// Why maintenance shouldn't run now, or None if it's due
+pub(crate) fn maintenance_skip_reason(
+    info: &crate::types::MaintenanceInfo,
+    identity: &str,
+    full: bool,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<String> {
+    let (kind, cycle, next) = if full {
+        (
+            "Full",
+            &info.params.full,
+            &info.schedule.next_full_maintenance,
+        )
+    } else {
+        (
+            "Quick",
+            &info.params.quick,
+            &info.schedule.next_quick_maintenance,
+        )
+    };
+
+    if info.params.owner != identity {
+        return Some(format!(
+            "Maintenance is owned by {}",
+            if info.params.owner.is_empty() {
+                "nobody"
+            } else {
+                &info.params.owner
+            }
+        ));
+    }
+    if !cycle.enabled {
+        return Some(format!("{} maintenance is disabled", kind));
+    }
+
+    // A cycle that never ran has no next time and is due right away
+    match next
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+    {
+        Some(next) if next > now => Some(format!(
+            "{} maintenance isn't due until {}",
+            kind,
+            next.to_rfc3339()
+        )),
+        _ => None,
+    }
+}
+
+/// Next scheduled maintenance time (RFC 3339)
+///
+/// The earlier of the next quick and full maintenance runs. Returns `None`
+/// when both cycles are disabled or another client owns maintenance, since
+/// this client won't run it then.
+#[tauri::command]
+pub async fn maintenance_next_time(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<Option<String>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    let info = fetch_maintenance_info(&config_file).await?;
+    let identity = fetch_current_identity(&client, &server_url).await?;
+
+    timer.finish(Ok(next_maintenance_time(
+        &info,
+        &identity,
+        chrono::Utc::now(),
+    )
+    .map(|t| t.to_rfc3339())))
+}
+
+/// Compute when this client next runs maintenance
+///
+/// Uses the schedule's next run times. A cycle without one falls back to its
+/// most recent run plus the interval, or `now` if it never ran.
+pub(crate) fn next_maintenance_time(
+    info: &crate::types::MaintenanceInfo,
+    identity: &str,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    if info.params.owner != identity {
+        return None;
+    }
+
+    let parse = |t: &str| {
+        chrono::DateTime::parse_from_rfc3339(t)
+            .ok()
+            .map(|t| t.with_timezone(&chrono::Utc))
+    };
+    let last_run = info
+        .schedule
+        .runs
+        .values()
+        .flatten()
+        .filter_map(|run| parse(&run.end))
+        .max();
+
+    [
+        (&info.params.quick, &info.schedule.next_quick_maintenance),
+        (&info.params.full, &info.schedule.next_full_maintenance),
+    ]
+    .into_iter()
+    .filter(|(cycle, _)| cycle.enabled)
+    .map(|(cycle, next)| {
+        next.as_deref()
+            .and_then(parse)
+            .or_else(|| last_run.map(|last| last + chrono::Duration::nanoseconds(cycle.interval)))
+            .unwrap_or(now)
+    })
+    .min()
+}
+
+/// Assess repository health
+///
+/// Combines maintenance state, failed tasks and errors in the latest snapshots
+/// into a score from 0 to 100 with a list of issues and suggested actions. See
+/// `compute_repository_health` for the scoring. If maintenance info can't be
+/// read, the maintenance checks are skipped.
+#[tauri::command]
+pub async fn repository_health(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::RepositoryHealth> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let config_file = get_config_file(&manager, &repo_id)?;
+
+    let (maintenance, tasks, sources) = tokio::join!(
+        fetch_maintenance_info(&config_file),
+        fetch_tasks_summary(&client, &server_url),
+        fetch_sources(&client, &server_url),
+    );
+    let maintenance = maintenance
+        .inspect_err(|e| log::warn!("Health check skipping maintenance: {}", e))
+        .ok();
+
+    timer.finish(Ok(compute_repository_health(
+        maintenance.as_ref(),
+        &tasks?,
+        &sources?.sources,
+        chrono::Utc::now(),
+    )))
+}
+
+// ============================================================================
+// Utility Commands
+// ============================================================================
+
+/// Resolve a file system path to get source info (user@host:/path)
+#[tauri::command]
+pub async fn path_resolve(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    path: String,
+) -> Result<crate::types::SourceInfo> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .post(format!("{}/api/v1/paths/resolve", server_url))
+        .json(&serde_json::json!({ "path": path }))
+        .send()
+        .await
+        .map_http_error("Failed to resolve path")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(KopiaError::operation_failed(
+            "path resolution",
+            format!("Failed to resolve path '{}': {}", path, error_text),
+        ));
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct PathResolveResponse {
+        source: crate::types::SourceInfo,
+    }
+
+    let result: PathResolveResponse = response
+        .json()
+        .await
+        .map_http_error("Failed to parse response")?;
+
+    timer.finish(Ok(result.source))
+}
+
+/// Estimate snapshot size
+#[tauri::command]
+pub async fn estimate_snapshot(
+    timer: CommandTimer,
+    path: String,
+    max_examples_per_bucket: Option<i64>,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::EstimateResponse> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(start_estimate(&client, &server_url, &path, max_examples_per_bucket).await)
+}
+
+/// Estimate snapshot size and wait for the result
+///
+/// Polls the estimate task until it finishes. If it's still running after
+/// `timeout_seconds`, the task is canceled and `Timeout` is returned.
+#[tauri::command]
+pub async fn estimate_snapshot_blocking(
+    timer: CommandTimer,
+    path: String,
+    max_examples_per_bucket: Option<i64>,
+    timeout_seconds: u64,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<crate::types::EstimateResult> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let task_id = start_estimate(&client, &server_url, &path, max_examples_per_bucket)
+        .await?
+        .id;
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_seconds);
+
+    loop {
+        let task = fetch_task(&client, &server_url, &task_id).await?;
+
+        if !is_task_active(&task.task.status) {
+            if task.task.status != "SUCCESS" {
+                return Err(KopiaError::operation_failed(
+                    "Estimate snapshot",
+                    task.task
+                        .error_message
+                        .unwrap_or_else(|| format!("Estimate ended as {}", task.task.status)),
+                ));
+            }
+            return timer.finish(Ok(estimate_result(&task)));
+        }
+
+        if std::time::Instant::now() >= deadline {
+            if let Err(e) = cancel_task(&client, &server_url, &task_id).await {
+                log::warn!("Failed to cancel estimate task {}: {}", task_id, e);
+            }
+            return Err(KopiaError::Timeout {
+                operation: "Estimate snapshot".to_string(),
+                seconds: timeout_seconds,
+            });
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
+
+/// Resolve `path` and start an estimate task for it
+async fn start_estimate(
+    client: &reqwest::Client,
+    server_url: &str,
+    path: &str,
+    max_examples_per_bucket: Option<i64>,
+) -> Result<crate::types::EstimateResponse> {
+    // Step 1: Resolve the path to get the absolute path
+    let resolved_path = {
+        let resolve_response = client
+            .post(format!("{}/api/v1/paths/resolve", server_url))
+            .json(&serde_json::json!({ "path": path }))
+            .send()
+            .await
+            .map_http_error("Failed to resolve path")?;
+
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ResolveResponse {
+            source: crate::types::SourceInfo,
+        }
+
+        let resolve_result: ResolveResponse =
+            handle_response(resolve_response, "Resolve path").await?;
+
+        resolve_result.source.path
+    };
+
+    // Step 2: Start the estimation task
+    let estimate_req = crate::types::EstimateRequest {
+        root: resolved_path,
+        max_examples_per_bucket,
+        policy_override: None,
+    };
+
+    let response = client
+        .post(format!("{}/api/v1/estimate", server_url))
+        .json(&estimate_req)
+        .send()
+        .await
+        .map_http_error("Failed to estimate snapshot")?;
+
+    handle_response(response, "Estimate snapshot").await
+}
+
+/// Read the totals of a finished estimate task from its counters
+pub(crate) fn estimate_result(task: &crate::types::TaskDetail) -> crate::types::EstimateResult {
+    let counters = task.counters.as_ref().unwrap_or(&task.task.counters);
+    let counter = |name: &str| counters.get(name).map_or(0, |c| c.value);
+
+    crate::types::EstimateResult {
+        total_bytes: counter("Bytes"),
+        file_count: counter("Files"),
+        directory_count: counter("Directories"),
+        excluded_count: counter("Excluded Files") + counter("Excluded Directories"),
+        excluded_bytes: counter("Excluded Bytes"),
+        error_count: counter("Errors"),
+    }
+}
+
+// ============================================================================
+// Notification Commands
+// ============================================================================
+
+/// Notification severities supported by Kopia, lowest first
+/// See: notification/notifydata/severity.go
+const NOTIFICATION_SEVERITIES: &[(i32, &str)] = &[
+    (-100, "Verbose"),
+    (-10, "Success"),
+    (0, "Report"),
+    (10, "Warning"),
+    (20, "Error"),
+];
+
+/// List the severities a notification profile can use as its minimum
+#[tauri::command]
+pub async fn notification_severity_levels(
+    timer: CommandTimer,
+) -> Result<Vec<crate::types::SeverityLevel>> {
+    timer.finish(Ok(severity_levels()))
+}
+
+/// Severities Kopia knows, lowest first
+pub(crate) fn severity_levels() -> Vec<crate::types::SeverityLevel> {
+    NOTIFICATION_SEVERITIES
+        .iter()
+        .map(|(value, label)| crate::types::SeverityLevel {
+            value: *value,
+            label: label.to_string(),
+        })
+        .collect()
+}
+
+/// Label of a notification severity, if Kopia knows it
+pub(crate) fn severity_label(value: i32) -> Option<&'static str> {
+    NOTIFICATION_SEVERITIES
+        .iter()
+        .find(|(v, _)| *v == value)
+        .map(|(_, label)| *label)
+}
+
+/// Check that a profile's minimum severity is a known level
+pub(crate) fn validate_notification_profile(
+    profile: &crate::types::NotificationProfile,
+) -> Result<()> {
+    if severity_label(profile.min_severity).is_some() {
+        return Ok(());
+    }
+
+    let expected: Vec<String> = NOTIFICATION_SEVERITIES
+        .iter()
+        .map(|(value, label)| format!("{} ({})", value, label))
+        .collect();
+    Err(KopiaError::invalid_input(
+        "minSeverity",
+        format!(
+            "Unknown severity {}, expected one of {}",
+            profile.min_severity,
+            expected.join(", ")
+        ),
+    ))
+}
+
+/// Get recent error notifications reported by a repository's server
+///
+/// Returns up to `limit` notifications, newest first. The history survives
+/// server restarts and is cleared when the repository is disconnected.
+#[tauri::command]
+pub async fn error_notifications(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    limit: usize,
+) -> Result<Vec<crate::types::KopiaNotification>> {
+    timer.finish(Ok(manager
+        .lock()
+        .unwrap()
+        .error_notifications(&repo_id, limit)))
+}
+
+/// List notification profiles
+#[tauri::command]
+pub async fn notification_profiles_list(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<Vec<crate::types::NotificationProfile>> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(fetch_notification_profiles(&client, &server_url).await)
+}
+
+/// Fetch all notification profiles
+async fn fetch_notification_profiles(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<Vec<crate::types::NotificationProfile>> {
+    let response = client
+        .get(format!("{}/api/v1/notificationProfiles", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to list notification profiles")?;
+
+    // Handle 404 as "no profiles configured" (common case)
+    if response.status().as_u16() == 404 {
+        return Ok(vec![]);
+    }
+
+    // For other non-success status codes, return error
+    if !response.status().is_success() {
+        return Err(http_request_failed(
+            "Failed to list notification profiles",
+            response.status().as_u16(),
+        ));
+    }
+
+    // Parse response - handle null as empty array (Kopia returns null when no profiles)
+    let result: Option<Vec<crate::types::NotificationProfile>> = response
+        .json()
+        .await
+        .map_http_error("Failed to parse notification profiles response")?;
+
+    Ok(result.unwrap_or_default())
+}
+
+/// Create notification profile
+#[tauri::command]
+pub async fn notification_profile_create(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    profile: crate::types::NotificationProfile,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    timer.finish(create_notification_profile(&client, &server_url, &profile).await)
+}
+
+/// Create (or replace) a notification profile
+async fn create_notification_profile(
+    client: &reqwest::Client,
+    server_url: &str,
+    profile: &crate::types::NotificationProfile,
+) -> Result<()> {
+    validate_notification_profile(profile)?;
+
+    let response = client
+        .post(format!("{}/api/v1/notificationProfiles", server_url))
+        .json(profile)
+        .send()
+        .await
+        .map_http_error("Failed to create notification profile")?;
+
+    handle_empty_response(response, "Create notification profile").await
+}
+
+/// Delete notification profile
+#[tauri::command]
+pub async fn notification_profile_delete(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    profile_name: String,
+) -> Result<()> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .delete(format!(
+            "{}/api/v1/notificationProfiles/{}",
+            server_url, profile_name
+        ))
+        .send()
+        .await
+        .map_http_error("Failed to delete notification profile")?;
+
+    timer.finish(handle_empty_response(response, "Delete notification profile").await)
+}
+
+/// Test notification profile (send test notification)
+///
+/// Failures of email profiles are returned as `NotificationTestFailed` with the
+/// SMTP stage that failed, when it can be told from the error message.
+#[tauri::command]
+pub async fn notification_profile_test(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    profile: crate::types::NotificationProfile,
+) -> Result<()> {
+    validate_notification_profile(&profile)?;
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let response = client
+        .post(format!("{}/api/v1/testNotificationProfile", server_url))
+        .json(&profile)
+        .send()
+        .await
+        .map_http_error("Failed to test notification profile")?;
+
+    timer.finish(
+        handle_empty_response(response, "Test notification profile")
+            .await
+            .map_err(|e| notification_test_error(&profile.method.method_type, e)),
+    )
+}
+
+/// Turn a server-reported failure of an email test into `NotificationTestFailed`
+///
+/// Other errors and other notification methods are returned unchanged.
+pub(crate) fn notification_test_error(method_type: &str, error: KopiaError) -> KopiaError {
+    match error {
+        KopiaError::OperationFailed { message, .. } if method_type == "email" => {
+            KopiaError::NotificationTestFailed {
+                stage: smtp_failure_stage(&message).map(String::from),
+                message,
+            }
+        }
+        error => error,
+    }
+}
+
+/// Tell the SMTP stage ("connect", "auth" or "send") from a Go SMTP error
+///
+/// Besides Go's network and TLS errors, this relies on SMTP reply codes:
+/// 530/534/535 reject authentication, 550-554 reject the sender or recipients.
+pub(crate) fn smtp_failure_stage(message: &str) -> Option<&'static str> {
+    let lower = message.to_lowercase();
+    let has_reply_code = |codes: &[&str]| {
+        codes
+            .iter()
+            .any(|code| lower.contains(&format!("{} ", code)))
+    };
+
+    if [
+        "dial tcp",
+        "no such host",
+        "connection refused",
+        "connection reset",
+        "i/o timeout",
+        "network is unreachable",
+        "x509:",
+        "tls:",
+    ]
+    .iter()
+    .any(|marker| lower.contains(marker))
+    {
+        Some("connect")
+    } else if has_reply_code(&["530", "534", "535"])
+        || [
+            "auth",
+            "username and password",
+            "credentials",
+            "unencrypted connection",
+        ]
+        .iter()
+        .any(|marker| lower.contains(marker))
+    {
+        Some("auth")
+    } else if has_reply_code(&["550", "551", "552", "553", "554"])
+        || ["recipient", "rcpt", "mail from", "sender"]
+            .iter()
+            .any(|marker| lower.contains(marker))
+    {
+        Some("send")
+    } else {
+        None
+    }
+}
+
+/// Format version of notification profile exports
+const NOTIFICATION_EXPORT_VERSION: u32 = 1;
+
+/// Method config keys holding secrets, besides keys that look like passwords or tokens
+const NOTIFICATION_SECRET_KEYS: &[&str] = &["userKey", "headers"];
+
+/// Export all notification profiles as JSON
+///
+/// Secrets (SMTP passwords, Pushover keys, webhook headers) are removed, and
+/// each profile records which fields were removed so they can be re-entered
+/// after import.
+#[tauri::command]
+pub async fn notification_profiles_export(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+) -> Result<String> {
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+    let profiles = fetch_notification_profiles(&client, &server_url).await?;
+
+    let export = crate::types::NotificationProfilesExport {
+        version: NOTIFICATION_EXPORT_VERSION,
+        profiles: profiles
+            .into_iter()
+            .map(redact_notification_profile)
+            .collect(),
+    };
+    timer.finish(Ok(serde_json::to_string_pretty(&export)?))
+}
+
+/// Import notification profiles exported with `notification_profiles_export`
+///
+/// Each profile is created like `notification_profile_create`, replacing any
+/// profile with the same name. Unless `include_secrets` is set, secrets present
+/// in the JSON are dropped too. Profiles whose secrets are missing are still
+/// created, and the result lists the secrets to re-enter.
+#[tauri::command]
+pub async fn notification_profiles_import(
+    timer: CommandTimer,
+    manager: State<'_, ServerManagerState>,
+    repo_id: String,
+    json: String,
+    include_secrets: bool,
+) -> Result<Vec<crate::types::NotificationProfileImportResult>> {
+    let export = parse_notification_profiles_export(&json)?;
+    let (server_url, client) = get_server_client(&manager, &repo_id)?;
+
+    let mut results = Vec::with_capacity(export.profiles.len());
+    for exported in export.profiles {
+        let exported = if include_secrets {
+            exported
+        } else {
+            let mut redacted = redact_notification_profile(exported.profile);
+            for field in exported.redacted_fields {
+                if !redacted.redacted_fields.contains(&field) {
+                    redacted.redacted_fields.push(field);
+                }
+            }
+            redacted
+        };
+
+        let result = create_notification_profile(&client, &server_url, &exported.profile).await;
+        results.push(crate::types::NotificationProfileImportResult {
+            profile: exported.profile.profile,
+            imported: result.is_ok(),
+            missing_secrets: exported.redacted_fields,
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+    timer.finish(Ok(results))
+}
+
+/// Parse and check a notification profile export
+pub(crate) fn parse_notification_profiles_export(
+    json: &str,
+) -> Result<crate::types::NotificationProfilesExport> {
+    let export: crate::types::NotificationProfilesExport = serde_json::from_str(json)
+        .map_err(|e| KopiaError::invalid_input("json", format!("Not a profile export: {}", e)))?;
+
+    if export.version > NOTIFICATION_EXPORT_VERSION {
+        return Err(KopiaError::invalid_input(
+            "json",
+            format!("Unsupported export version {}", export.version),
+        ));
+    }
+    Ok(export)
+}
+
+/// Remove secrets from a profile's method config
+pub(crate) fn redact_notification_profile(
+    mut profile: crate::types::NotificationProfile,
+) -> crate::types::ExportedNotificationProfile {
+    let mut redacted_fields = Vec::new();
+
+    if let Some(config) = profile.method.config.as_object_mut() {
+        for (key, value) in config.iter_mut() {
+            let has_value = match value {
+                serde_json::Value::Null => false,
+                serde_json::Value::String(s) => !s.is_empty(),
+                serde_json::Value::Array(a) => !a.is_empty(),
+                serde_json::Value::Object(o) => !o.is_empty(),
+                _ => true,
+            };
+            if has_value && is_secret_config_key(key) {
+                *value = match value {
+                    serde_json::Value::Array(_) => serde_json::json!([]),
+                    serde_json::Value::Object(_) => serde_json::json!({}),
+                    _ => serde_json::json!(""),
+                };
+                redacted_fields.push(key.clone());
+            }
+        }
+    }
+
+    crate::types::ExportedNotificationProfile {
+        profile,
+        redacted: !redacted_fields.is_empty(),
+        redacted_fields,
+    }
+}
+
+/// Whether a notification method config key holds a secret
+fn is_secret_config_key(key: &str) -> bool {
+    let lower = key.to_lowercase();
+    NOTIFICATION_SECRET_KEYS.contains(&key)
+        || ["password", "token", "secret"]
+            .iter()
+            .any(|marker| lower.contains(marker))
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Get server URL and HTTP client for a specific repository
+fn get_server_client(
+    manager: &State<'_, ServerManagerState>,
+    repo_id: &str,
+) -> Result<(String, reqwest::Client)> {
+    let manager_guard = manager.lock().unwrap();
+
+    let server_url = manager_guard.get_server_url(repo_id).ok_or_else(|| {
+        KopiaError::operation_failed(
+            "repository lookup",
+            format!("Repository '{}' not found", repo_id),
+        )
+    })?;
+
+    let client = manager_guard
+        .get_http_client(repo_id)
+        .ok_or(KopiaError::ServerNotRunning)?;
+
+    Ok((server_url, client))
+}
+
+/// Like `get_server_client`, returning the download client
+///
+/// Use it for requests whose body can take longer than
+/// `HTTP_OPERATION_TIMEOUT_SECS` to read, and read the body with
+/// `DownloadBody`.
+fn get_download_client(
+    manager: &State<'_, ServerManagerState>,
+    repo_id: &str,
+) -> Result<(String, reqwest::Client)> {
+    let (server_url, _) = get_server_client(manager, repo_id)?;
+    let client = manager
+        .lock()
+        .unwrap()
+        .get_download_client(repo_id)
+        .ok_or(KopiaError::ServerNotRunning)?;
+
+    Ok((server_url, client))
+}
+
+/// Check that a restore request specifies exactly one output
+///
+/// Kopia restores to either a filesystem path, a ZIP file or a TAR file.
+pub(crate) fn validate_restore_request(request: &crate::types::RestoreRequest) -> Result<()> {
+    let outputs = [
+        request.fs_output.is_some(),
+        request.zip_file.is_some(),
+        request.tar_file.is_some(),
+    ];
+
+    match outputs.iter().filter(|&&set| set).count() {
+        1 => Ok(()),
+        0 => Err(KopiaError::invalid_input(
+            "output",
+            "One of fsOutput, zipFile or tarFile must be specified",
+        )),
+        _ => Err(KopiaError::invalid_input(
+            "output",
+            "Only one of fsOutput, zipFile or tarFile may be specified",
+        )),
+    }
+}
+
+/// Get the config file path of a known repository
+fn get_config_file(manager: &State<'_, ServerManagerState>, repo_id: &str) -> Result<String> {
+    let manager_guard = manager.lock().unwrap();
+
+    if manager_guard.is_external(repo_id) {
+        return Err(KopiaError::operation_failed(
+            "repository lookup",
+            format!(
+                "Repository '{}' is on an external server and has no local config",
+                repo_id
+            ),
+        ));
+    }
+
+    if !manager_guard.repository_exists(repo_id) {
+        return Err(KopiaError::operation_failed(
+            "repository lookup",
+            format!("Repository '{}' not found", repo_id),
+        ));
+    }
+
+    Ok(manager_guard.get_config_file_path(repo_id))
+}
+
+/// Attempts made by `retry_while_busy`, including the first one
+pub(crate) const BUSY_RETRY_ATTEMPTS: u32 = 3;
+/// Delay before the first retry; doubled for each further retry
+pub(crate) const BUSY_RETRY_INITIAL_DELAY_MS: u64 = 250;
+
+/// Run a request, retrying with backoff while the repository reports it's busy
+///
+/// Busy states such as a flush in progress clear up quickly, so the request is
+/// retried up to `BUSY_RETRY_ATTEMPTS` times before `RepositoryBusy` is returned.
+/// `request` must be safe to repeat.
+pub(crate) async fn retry_while_busy<T, F, Fut>(mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut delay = std::time::Duration::from_millis(BUSY_RETRY_INITIAL_DELAY_MS);
+    let mut attempt = 1;
+
+    loop {
+        match request().await {
+            Err(KopiaError::RepositoryBusy { message }) if attempt < BUSY_RETRY_ATTEMPTS => {
+                log::debug!(
+                    "Repository busy (attempt {}/{}), retrying in {:?}: {}",
+                    attempt,
+                    BUSY_RETRY_ATTEMPTS,
+                    delay,
+                    message
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Attempts made by `retry_request`, including the first one
+pub(crate) const TRANSIENT_RETRY_ATTEMPTS: u32 = 4;
+/// Delay before the first retry; doubled for each further retry
+pub(crate) const TRANSIENT_RETRY_INITIAL_DELAY_MS: u64 = 100;
+
+/// Run a GET request, retrying with backoff on connection errors
+///
+/// A server that is still warming up may reset connections or time out;
+/// those requests are retried up to `TRANSIENT_RETRY_ATTEMPTS` times in all
+/// (100ms, 200ms, 400ms apart). Errors with an HTTP status are returned right
+/// away. `request` must be idempotent.
+pub(crate) async fn retry_request<T, F, Fut>(mut request: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut delay = std::time::Duration::from_millis(TRANSIENT_RETRY_INITIAL_DELAY_MS);
+    let mut attempt = 1;
+
+    loop {
+        match request().await {
+            Err(e) if is_transient_error(&e) && attempt < TRANSIENT_RETRY_ATTEMPTS => {
+                log::debug!(
+                    "Request failed (attempt {}/{}), retrying in {:?}: {}",
+                    attempt,
+                    TRANSIENT_RETRY_ATTEMPTS,
+                    delay,
+                    e
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether an error is a connection or timeout failure rather than an HTTP error
+pub(crate) fn is_transient_error(error: &KopiaError) -> bool {
+    matches!(
+        error,
+        KopiaError::HttpRequestFailed {
+            status_code: None,
+            ..
+        }
+    )
+}
+
+/// Handle API response - check status and parse JSON
+async fn handle_response<T: DeserializeOwned>(
+    response: reqwest::Response,
+    operation: &str,
+) -> Result<T> {
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(KopiaError::from_api_response(
+            status.as_u16(),
+            &error_text,
+            operation,
+        ));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| KopiaError::ResponseParseError {
+            message: e.to_string(),
+            expected_type: std::any::type_name::<T>().to_string(),
+        })
+}
+
+/// Handle API response that returns empty/unit result
+async fn handle_empty_response(response: reqwest::Response, operation: &str) -> Result<()> {
+    let status = response.status();
+    if !status.is_success() {
+        let error_text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(KopiaError::from_api_response(
+            status.as_u16(),
+            &error_text,
+            operation,
+        ));
+    }
+    Ok(())
+}
+
+/// Emit a `server-start-phase` event (failures to emit are only logged)
+fn emit_start_phase(app: &AppHandle, repo_id: &str, phase: ServerStartPhase) {
+    let event = ServerStartPhaseEvent {
+        repo_id: repo_id.to_string(),
+        phase,
+    };
+    if let Err(e) = app.emit(SERVER_START_PHASE_EVENT, event) {
+        log::debug!("Failed to emit server start phase: {}", e);
+    }
+}
+
+/// Await server readiness, reporting the outcome as a start phase
+async fn wait_until_ready(
+    app: &AppHandle,
+    repo_id: &str,
+    ready_waiter: impl std::future::Future<Output = Result<()>>,
+) -> Result<()> {
+    emit_start_phase(app, repo_id, ServerStartPhase::WaitingForReady);
+    match ready_waiter.await {
+        Ok(()) => {
+            emit_start_phase(app, repo_id, ServerStartPhase::Ready);
+            Ok(())
+        }
+        Err(e) => {
+            emit_start_phase(app, repo_id, ServerStartPhase::Failed);
+            Err(e)
+        }
+    }
+}
+
+/// Maximum time to wait for an adopted repository to finish connecting (60 seconds)
+const ADOPT_CONNECT_TIMEOUT_SECS: u64 = 60;
+
+/// Start the server of an imported config and wait until its repository is open
+async fn start_adopted_server(
+    app: &AppHandle,
+    manager: &State<'_, ServerManagerState>,
+    repo_id: &str,
+) -> Result<RepositoryStatus> {
+    emit_start_phase(app, repo_id, ServerStartPhase::Starting);
+    let ready_waiter = {
+        let mut manager_guard = manager.lock().unwrap();
+        manager_guard.start_server(repo_id)?;
+        manager_guard.get_ready_waiter(repo_id)?
+    };
+    wait_until_ready(app, repo_id, ready_waiter).await?;
+
+    let (server_url, client) = get_server_client(manager, repo_id)?;
+    let timeout = std::time::Duration::from_secs(ADOPT_CONNECT_TIMEOUT_SECS);
+
+    wait_for_repository_open(&client, &server_url, timeout)
+        .await?
+        .ok_or_else(|| {
+            KopiaError::operation_failed(
+                "config import",
+                "The repository referenced by the config could not be opened",
+            )
+        })
+}
+
+/// Poll the status until the server has opened its repository
+///
+/// The server opens the repository asynchronously (--async-repo-connect).
+/// Returns None if opening failed or didn't finish within `timeout`.
+pub(crate) async fn wait_for_repository_open(
+    client: &reqwest::Client,
+    server_url: &str,
+    timeout: std::time::Duration,
+) -> Result<Option<RepositoryStatus>> {
+    let deadline = std::time::Instant::now() + timeout;
+
+    loop {
+        let status = fetch_repository_status(client, server_url).await?;
+
+        if status.connected {
+            return Ok(Some(status));
+        }
+        if status.init_task_id.is_none() || std::time::Instant::now() >= deadline {
+            return Ok(None);
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
+
+/// Create an HttpRequestFailed error with consistent structure
+///
+/// This helper reduces boilerplate when constructing HTTP request failures
+/// throughout the API commands.
+///
+/// # Arguments
+/// * `message` - Error message describing what failed
+/// * `status_code` - HTTP status code from the response
+///
+/// # Returns
+/// A `KopiaError::HttpRequestFailed` with url set to None (not tracked for localhost API)
+fn http_request_failed(message: impl Into<String>, status_code: u16) -> KopiaError {
+    KopiaError::HttpRequestFailed {
+        message: message.into(),
+        status_code: Some(status_code),
+        operation: "API Request".to_string(),
+    }
+}
+
+/// Build URL query string for snapshot source (user@host:/path)
+///
+/// # Arguments
+/// * `user_name` - Username for the snapshot source
+/// * `host` - Hostname for the snapshot source
+/// * `path` - File path for the snapshot source
+///
+/// # Returns
+/// URL-encoded query string in format: `?userName=X&host=Y&path=Z`
+fn build_source_query(user_name: &str, host: &str, path: &str) -> String {
+    format!(
+        "?userName={}&host={}&path={}",
+        urlencoding::encode(user_name),
+        urlencoding::encode(host),
+        urlencoding::encode(path)
+    )
+}
+
+/// Delete the policy defined for a target
+async fn delete_policy(
+    client: &reqwest::Client,
+    server_url: &str,
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+) -> Result<()> {
+    let query_string = build_policy_query(user_name, host, path);
+
+    let response = client
+        .delete(format!("{}/api/v1/policy{}", server_url, query_string))
+        .send()
+        .await
+        .map_http_error("Failed to delete policy")?;
+
+    handle_empty_response(response, "Delete policy").await
+}
+
+/// Turn fan-out results into one result per policy target, in target order
+pub(crate) fn build_policy_delete_results(
+    targets: Vec<crate::types::PolicyTarget>,
+    result: FanOutResult<()>,
+    timeout: std::time::Duration,
+) -> Vec<crate::types::PolicyDeleteResult> {
+    let failed: HashMap<String, String> = result.failed.into_iter().collect();
+
+    targets
+        .into_iter()
+        .map(|target| {
+            let key = target.to_string();
+            let error = if result.timed_out.contains(&key) {
+                Some(format!("Timed out after {}s", timeout.as_secs()))
+            } else {
+                failed.get(&key).cloned()
+            };
+
+            crate::types::PolicyDeleteResult {
+                target,
+                success: error.is_none(),
+                error,
+            }
+        })
+        .collect()
+}
+
+/// Policy target for optional user/host/path parameters
+fn policy_target(
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+) -> crate::types::PolicyTarget {
+    crate::types::PolicyTarget {
+        user_name: user_name.map(String::from),
+        host: host.map(String::from),
+        path: path.map(String::from),
+    }
+}
+
+/// Policy target for a snapshot source
+fn source_target(user_name: &str, host: &str, path: &str) -> crate::types::PolicyTarget {
+    policy_target(Some(user_name), Some(host), Some(path))
+}
+
+/// Build URL query string for policy target (optional user/host/path)
+///
+/// # Arguments
+/// * `user_name` - Optional username filter
+/// * `host` - Optional hostname filter
+/// * `path` - Optional path filter
+///
+/// # Returns
+/// URL-encoded query string, or empty string if no parameters
+fn build_policy_query(user_name: Option<&str>, host: Option<&str>, path: Option<&str>) -> String {
+    let parts: Vec<_> = [
+        user_name.map(|u| format!("userName={}", urlencoding::encode(u))),
+        host.map(|h| format!("host={}", urlencoding::encode(h))),
+        path.map(|p| format!("path={}", urlencoding::encode(p))),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("?{}", parts.join("&"))
+    }
+}
+
+/// Get maintenance parameters and schedule via `kopia maintenance info`
+pub(crate) async fn fetch_maintenance_info(
+    config_file: &str,
+) -> Result<crate::types::MaintenanceInfo> {
+    kopia_cli::run_json(
+        config_file,
+        &["maintenance", "info"],
+        "Get maintenance info",
+    )
+    .await
+}
+
+// Health score penalties; the score starts at 100 and can't go below 0
+/// Full maintenance is turned off, so unused data is never cleaned up
+const HEALTH_PENALTY_MAINTENANCE_DISABLED: u8 = 20;
+/// Full maintenance is past due by more than `MAINTENANCE_OVERDUE_GRACE_HOURS`
+const HEALTH_PENALTY_MAINTENANCE_OVERDUE: u8 = 20;
+/// The most recent maintenance run failed
+const HEALTH_PENALTY_MAINTENANCE_FAILED: u8 = 15;
+/// Per failed task, up to `HEALTH_MAX_FAILED_TASKS_PENALTY`
+const HEALTH_PENALTY_PER_FAILED_TASK: u8 = 5;
+const HEALTH_MAX_FAILED_TASKS_PENALTY: u8 = 25;
+/// Per source whose latest snapshot had errors, up to `HEALTH_MAX_SNAPSHOT_ERRORS_PENALTY`
+const HEALTH_PENALTY_PER_SOURCE_WITH_ERRORS: u8 = 10;
+const HEALTH_MAX_SNAPSHOT_ERRORS_PENALTY: u8 = 30;
+/// Per source without any snapshot, up to `HEALTH_MAX_NO_SNAPSHOTS_PENALTY`
+const HEALTH_PENALTY_PER_SOURCE_WITHOUT_SNAPSHOT: u8 = 5;
+const HEALTH_MAX_NO_SNAPSHOTS_PENALTY: u8 = 15;
+
+/// How late full maintenance may be before it counts as overdue
+const MAINTENANCE_OVERDUE_GRACE_HOURS: i64 = 24;
+
+/// Score repository health from maintenance info, task counts and sources
+pub(crate) fn compute_repository_health(
+    maintenance: Option<&crate::types::MaintenanceInfo>,
+    tasks: &crate::types::TasksSummary,
+    sources: &[crate::types::SnapshotSource],
+    now: chrono::DateTime<chrono::Utc>,
+) -> crate::types::RepositoryHealth {
+    use crate::types::{HealthIssue, HealthIssueKind};
+
+    let mut issues = Vec::new();
+    let mut issue = |kind, penalty, message: String, suggested_action: &str| {
+        issues.push(HealthIssue {
+            kind,
+            message,
+            suggested_action: suggested_action.to_string(),
+            penalty,
+        })
+    };
+
+    if let Some(info) = maintenance {
+        let next_full = info
+            .schedule
+            .next_full_maintenance
+            .as_deref()
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok());
+
+        if !info.params.full.enabled {
+            issue(
+                HealthIssueKind::MaintenanceDisabled,
+                HEALTH_PENALTY_MAINTENANCE_DISABLED,
+                "Full maintenance is disabled".to_string(),
+                "Enable full maintenance so unused data is cleaned up",
+            );
+        } else if let Some(next_full) = next_full.filter(|next| {
+            now.signed_duration_since(*next)
+                > chrono::Duration::hours(MAINTENANCE_OVERDUE_GRACE_HOURS)
+        }) {
+            issue(
+                HealthIssueKind::MaintenanceOverdue,
+                HEALTH_PENALTY_MAINTENANCE_OVERDUE,
+                format!(
+                    "Full maintenance is {} days overdue",
+                    now.signed_duration_since(next_full).num_days().max(1)
+                ),
+                "Run maintenance, and check that the maintenance owner still uses this repository",
+            );
+        }
+
+        let latest_run = info
+            .schedule
+            .runs
+            .values()
+            .flatten()
+            .max_by(|a, b| a.end.cmp(&b.end));
+        if let Some(run) = latest_run.filter(|run| !run.success) {
+            issue(
+                HealthIssueKind::MaintenanceFailed,
+                HEALTH_PENALTY_MAINTENANCE_FAILED,
+                match run.error {
+                    Some(ref error) => format!("The last maintenance run failed: {}", error),
+                    None => "The last maintenance run failed".to_string(),
+                },
+                "Check the maintenance logs and run maintenance again",
+            );
+        }
+    }
+
+    if tasks.failed > 0 {
+        issue(
+            HealthIssueKind::FailedTasks,
+            capped_penalty(
+                tasks.failed as usize,
+                HEALTH_PENALTY_PER_FAILED_TASK,
+                HEALTH_MAX_FAILED_TASKS_PENALTY,
+            ),
+            format!("{} task(s) failed", tasks.failed),
+            "Review the failed tasks for errors",
+        );
+    }
+
+    let with_errors: Vec<String> = sources
+        .iter()
+        .filter(|s| {
+            s.last_snapshot.as_ref().is_some_and(|snapshot| {
+                let stats_errors = snapshot.stats.as_ref().map_or(0, |st| st.error_count);
+                let summary_errors = snapshot
+                    .summary
+                    .as_ref()
+                    .and_then(|su| su.error_count)
+                    .unwrap_or(0);
+                stats_errors.max(summary_errors) > 0
+            })
+        })
+        .map(|s| s.source.to_string())
+        .collect();
+    if !with_errors.is_empty() {
+        issue(
+            HealthIssueKind::SnapshotErrors,
+            capped_penalty(
+                with_errors.len(),
+                HEALTH_PENALTY_PER_SOURCE_WITH_ERRORS,
+                HEALTH_MAX_SNAPSHOT_ERRORS_PENALTY,
+            ),
+            format!(
+                "The latest snapshot had errors for: {}",
+                with_errors.join(", ")
+            ),
+            "Check which files couldn't be read and adjust permissions or ignore rules",
+        );
+    }
+
+    let without_snapshots: Vec<String> = sources
+        .iter()
+        .filter(|s| s.last_snapshot.is_none())
+        .map(|s| s.source.to_string())
+        .collect();
+    if !without_snapshots.is_empty() {
+        issue(
+            HealthIssueKind::NoSnapshots,
+            capped_penalty(
+                without_snapshots.len(),
+                HEALTH_PENALTY_PER_SOURCE_WITHOUT_SNAPSHOT,
+                HEALTH_MAX_NO_SNAPSHOTS_PENALTY,
+            ),
+            format!("No snapshot yet for: {}", without_snapshots.join(", ")),
+            "Take a snapshot now or check the source's schedule",
+        );
+    }
+
+    let penalty: u32 = issues.iter().map(|i| u32::from(i.penalty)).sum();
+    crate::types::RepositoryHealth {
+        score: 100u32.saturating_sub(penalty) as u8,
+        issues,
+    }
+}
+
+/// `per_item` points for each of `count` items, capped at `max`
+fn capped_penalty(count: usize, per_item: u8, max: u8) -> u8 {
+    count
+        .saturating_mul(usize::from(per_item))
+        .min(usize::from(max)) as u8
+}
+
+/// Get the `user@host` identity the server uses for this client
+async fn fetch_current_identity(client: &reqwest::Client, server_url: &str) -> Result<String> {
+    #[derive(Deserialize)]
+    struct CurrentUserResponse {
+        username: String,
+        hostname: String,
+    }
+
+    let response = client
+        .get(format!("{}/api/v1/current-user", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to get current user")?;
+
+    let user: CurrentUserResponse = handle_response(response, "Get current user").await?;
+    Ok(format!("{}@{}", user.username, user.hostname))
+}
+
+/// Pause or resume every snapshot source
+///
+/// The control endpoints apply to all sources when no source is given.
+async fn set_all_sources_paused(
+    client: &reqwest::Client,
+    server_url: &str,
+    paused: bool,
+) -> Result<crate::types::MultipleSourceActionResponse> {
+    let (action, operation) = if paused {
+        ("pause-source", "Pause all sources")
     } else {
-        format!("?{}", parts.join("&"))
+        ("resume-source", "Resume all sources")
+    };
+
+    let response = client
+        .post(format!("{}/api/v1/control/{}", server_url, action))
+        .send()
+        .await
+        .map_http_error(&format!("Failed to {}", operation.to_lowercase()))?;
+
+    handle_response(response, operation).await
+}
+
+/// Get the policy defined directly on a target
+///
+/// Returns an empty policy if the target has no policy of its own yet.
+async fn get_defined_policy(
+    client: &reqwest::Client,
+    server_url: &str,
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+) -> Result<crate::types::PolicyDefinition> {
+    Ok(
+        fetch_defined_policy(client, server_url, user_name, host, path)
+            .await?
+            .unwrap_or_default(),
+    )
+}
+
+/// Fetch the policy defined for a target, `None` if it has none
+async fn fetch_defined_policy(
+    client: &reqwest::Client,
+    server_url: &str,
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+) -> Result<Option<crate::types::PolicyDefinition>> {
+    let query_string = build_policy_query(user_name, host, path);
+
+    let response = client
+        .get(format!("{}/api/v1/policy{}", server_url, query_string))
+        .send()
+        .await
+        .map_http_error("Failed to get policy")?;
+
+    match handle_response(response, "Get policy").await {
+        Ok(policy) => Ok(Some(policy)),
+        Err(KopiaError::NotFound { .. }) | Err(KopiaError::PolicyNotFound { .. }) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Fetch a directory object from a snapshot
+async fn fetch_directory(
+    client: &reqwest::Client,
+    server_url: &str,
+    object_id: &str,
+) -> Result<crate::types::DirectoryObject> {
+    let response = client
+        .get(format!("{}/api/v1/objects/{}", server_url, object_id))
+        .send()
+        .await
+        .map_http_error("Failed to browse object")?;
+
+    handle_response(response, "Browse object").await
+}
+
+/// Walk down from `root_id` through the named subdirectories
+///
+/// Returns `PathNotFound` if a component doesn't exist or isn't a directory.
+async fn walk_directory(
+    client: &reqwest::Client,
+    server_url: &str,
+    root_id: &str,
+    components: &[&str],
+) -> Result<crate::types::DirectoryObject> {
+    let mut directory = fetch_directory(client, server_url, root_id).await?;
+    for (depth, name) in components.iter().enumerate() {
+        let object_id = find_subdirectory(&directory, name)
+            .map(|entry| entry.obj.clone())
+            .ok_or_else(|| KopiaError::PathNotFound {
+                path: components[..=depth].join("/"),
+            })?;
+        directory = fetch_directory(client, server_url, &object_id).await?;
+    }
+
+    Ok(directory)
+}
+
+/// Split a path relative to a snapshot root into its components
+///
+/// Accepts `/` and `\` separators and ignores empty and `.` components.
+pub(crate) fn split_relative_path(relative_path: &str) -> Result<Vec<&str>> {
+    let components: Vec<&str> = relative_path
+        .split(['/', '\\'])
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect();
+
+    if components.contains(&"..") {
+        return Err(KopiaError::invalid_input(
+            "relativePath",
+            "Path must not contain '..'",
+        ));
+    }
+    Ok(components)
+}
+
+/// Find a subdirectory entry by name
+pub(crate) fn find_subdirectory<'a>(
+    directory: &'a crate::types::DirectoryObject,
+    name: &str,
+) -> Option<&'a crate::types::DirectoryEntry> {
+    directory
+        .entries
+        .iter()
+        .find(|entry| entry.entry_type == "d" && entry.name == name)
+}
+
+/// Save the policy defined for a target
+async fn put_policy(
+    client: &reqwest::Client,
+    server_url: &str,
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+    policy: &crate::types::PolicyDefinition,
+) -> Result<()> {
+    let query_string = build_policy_query(user_name, host, path);
+
+    retry_while_busy(|| async {
+        let response = client
+            .put(format!("{}/api/v1/policy{}", server_url, query_string))
+            .json(policy) // API expects policy directly, not wrapped
+            .send()
+            .await
+            .map_http_error("Failed to set policy")?;
+
+        handle_empty_response(response, "Set policy").await
+    })
+    .await
+}
+
+/// Check that all set upload parallelism values are positive
+pub(crate) fn validate_upload_policy(upload: &crate::types::UploadPolicy) -> Result<()> {
+    let fields = [
+        ("maxParallelSnapshots", upload.max_parallel_snapshots),
+        ("maxParallelFileReads", upload.max_parallel_file_reads),
+        ("parallelUploadAboveSize", upload.parallel_upload_above_size),
+    ];
+
+    for (field, value) in fields {
+        if value.is_some_and(|v| v <= 0) {
+            return Err(KopiaError::invalid_input(
+                field,
+                "Must be a positive number",
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Resolve the effective policy for a target without applying any updates
+async fn resolve_policy(
+    client: &reqwest::Client,
+    server_url: &str,
+    user_name: Option<&str>,
+    host: Option<&str>,
+    path: Option<&str>,
+) -> Result<crate::types::ResolvedPolicyResponse> {
+    let query_string = build_policy_query(user_name, host, path);
+
+    let response = client
+        .post(format!(
+            "{}/api/v1/policy/resolve{}",
+            server_url, query_string
+        ))
+        .json(&serde_json::json!({ "updates": null, "numUpcomingSnapshotTimes": 0 }))
+        .send()
+        .await
+        .map_http_error("Failed to resolve policy")?;
+
+    handle_response(response, "Resolve policy").await
+}
+
+/// Build the activity feed from tasks, newest first, keeping at most `limit` items
+pub(crate) fn build_activity_feed(
+    tasks: &[crate::types::Task],
+    limit: usize,
+) -> Vec<crate::types::ActivityItem> {
+    use crate::types::ActivityItem;
+
+    let mut items: Vec<(chrono::DateTime<chrono::FixedOffset>, ActivityItem)> = tasks
+        .iter()
+        .filter_map(|task| {
+            let time = task
+                .end_time
+                .clone()
+                .unwrap_or_else(|| task.start_time.clone());
+            let sort_key = chrono::DateTime::parse_from_rfc3339(&time).ok()?;
+            let outcome = match task.status.as_str() {
+                "SUCCESS" => "success",
+                "CANCELED" => "canceled",
+                "FAILED" => {
+                    return Some((
+                        sort_key,
+                        ActivityItem::Error {
+                            task_id: task.id.clone(),
+                            time,
+                            kind: task.kind.clone(),
+                            description: task.description.clone(),
+                            message: task.error_message.clone(),
+                        },
+                    ))
+                }
+                // Still running
+                _ => return None,
+            }
+            .to_string();
+
+            let item = match task.kind.as_str() {
+                "Snapshot" => ActivityItem::SnapshotCompleted {
+                    task_id: task.id.clone(),
+                    time,
+                    source: task.description.clone(),
+                    outcome,
+                },
+                "Restore" => ActivityItem::RestoreCompleted {
+                    task_id: task.id.clone(),
+                    time,
+                    target: task.description.clone(),
+                    outcome,
+                },
+                "Maintenance" => ActivityItem::MaintenanceRun {
+                    task_id: task.id.clone(),
+                    time,
+                    description: task.description.clone(),
+                    outcome,
+                },
+                _ => return None,
+            };
+            Some((sort_key, item))
+        })
+        .collect();
+
+    items.sort_by_key(|(time, _)| std::cmp::Reverse(*time));
+    items
+        .into_iter()
+        .take(limit)
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// Delay between the two task samples used to detect stalled progress
+const STUCK_TASK_SAMPLE_DELAY_SECS: u64 = 5;
+
+/// Fetch all tasks
+pub(crate) async fn fetch_tasks(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<Vec<crate::types::Task>> {
+    let response = client
+        .get(format!("{}/api/v1/tasks", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to list tasks")?;
+
+    let tasks: crate::types::TasksResponse = handle_response(response, "List tasks").await?;
+    Ok(tasks.tasks)
+}
+
+/// Check whether a task is running and started at least `older_than_secs` before `now`
+pub(crate) fn is_long_running(
+    task: &crate::types::Task,
+    now: chrono::DateTime<chrono::Utc>,
+    older_than_secs: u64,
+) -> bool {
+    if task.status != "RUNNING" {
+        return false;
+    }
+
+    chrono::DateTime::parse_from_rfc3339(&task.start_time)
+        .map(|start| {
+            let age = now.signed_duration_since(start);
+            age.num_seconds() >= i64::try_from(older_than_secs).unwrap_or(i64::MAX)
+        })
+        .unwrap_or(false)
+}
+
+/// Tasks from `current` that are still running with the same progress as in `earlier`
+pub(crate) fn find_stuck_tasks(
+    earlier: &[crate::types::Task],
+    current: &[crate::types::Task],
+) -> Vec<crate::types::Task> {
+    let counter_values = |task: &crate::types::Task| -> HashMap<String, i64> {
+        task.counters
+            .iter()
+            .map(|(name, counter)| (name.clone(), counter.value))
+            .collect()
+    };
+
+    current
+        .iter()
+        .filter(|task| task.status == "RUNNING")
+        .filter(|task| {
+            earlier.iter().any(|before| {
+                before.id == task.id
+                    && before.progress_info == task.progress_info
+                    && counter_values(before) == counter_values(task)
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Select counters with a "warning" or "error" level, errors first then by name
+pub(crate) fn counter_warnings(
+    counters: &HashMap<String, crate::types::CounterValue>,
+) -> Vec<crate::types::TaskCounterWarning> {
+    let mut warnings: Vec<_> = counters
+        .iter()
+        .filter(|(_, counter)| matches!(counter.level.as_str(), "warning" | "error"))
+        .map(|(name, counter)| crate::types::TaskCounterWarning {
+            name: name.clone(),
+            value: counter.value,
+            level: counter.level.clone(),
+        })
+        .collect();
+
+    warnings.sort_by(|a, b| (a.level != "error", &a.name).cmp(&(b.level != "error", &b.name)));
+    warnings
+}
+
+/// Fetch the repository status from a server
+pub(crate) async fn fetch_repository_status(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<RepositoryStatus> {
+    let response = client
+        .get(format!("{}/api/v1/repo/status", server_url))
+        .send()
+        .await
+        .map_http_error("Get repository status")?;
+
+    handle_response(response, "Get repository status").await
+}
+
+/// Fetch all snapshot sources with their status
+pub(crate) async fn fetch_sources(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<crate::types::SourcesResponse> {
+    let response = client
+        .get(format!("{}/api/v1/sources", server_url))
+        .send()
+        .await
+        .map_http_error("Failed to list sources")?;
+
+    handle_response(response, "List sources").await
+}
+
+/// How long past its scheduled time a snapshot must be before it counts as missed
+///
+/// Gives the server's own scheduler time to start it.
+pub(crate) const MISSED_SNAPSHOT_GRACE_SECS: i64 = 60;
+
+/// Find sources whose scheduled snapshot time has passed without a snapshot
+///
+/// Uses the server's `nextSnapshotTime`, falling back to the last snapshot time
+/// plus the scheduling interval. Manual, paused and busy sources are skipped.
+pub(crate) fn find_missed_snapshots(
+    sources: &[crate::types::SnapshotSource],
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<crate::types::MissedSnapshot> {
+    let mut missed: Vec<_> = sources
+        .iter()
+        .filter(|s| s.scheduling_policy.manual != Some(true))
+        .filter(|s| s.status == "IDLE" && s.current_task.is_none())
+        .filter_map(|s| {
+            let scheduled = match &s.next_snapshot_time {
+                Some(next) => chrono::DateTime::parse_from_rfc3339(next).ok()?,
+                None => {
+                    let interval = s.scheduling_policy.interval_seconds.filter(|&i| i > 0)?;
+                    let last = s.last_snapshot.as_ref()?;
+                    chrono::DateTime::parse_from_rfc3339(&last.start_time).ok()?
+                        + chrono::Duration::seconds(interval)
+                }
+            };
+
+            let missed_by_secs = (now - scheduled.with_timezone(&chrono::Utc)).num_seconds();
+            (missed_by_secs > MISSED_SNAPSHOT_GRACE_SECS).then(|| crate::types::MissedSnapshot {
+                source: s.source.clone(),
+                scheduled_time: scheduled.to_rfc3339(),
+                missed_by_secs,
+            })
+        })
+        .collect();
+
+    missed.sort_by_key(|m| std::cmp::Reverse(m.missed_by_secs));
+    missed
+}
+
+/// Fetch the snapshots of a source (`all` includes ones no longer retained)
+async fn fetch_source_snapshots(
+    client: &reqwest::Client,
+    server_url: &str,
+    source: &crate::types::SourceInfo,
+    all: bool,
+) -> Result<crate::types::SnapshotsResponse> {
+    let query_params = format!(
+        "{}&all={}",
+        build_source_query(&source.user_name, &source.host, &source.path),
+        if all { "1" } else { "0" }
+    );
+
+    let response = client
+        .get(format!("{}/api/v1/snapshots{}", server_url, query_params))
+        .send()
+        .await
+        .map_http_error("Failed to list snapshots")?;
+
+    handle_response(response, "List snapshots").await
+}
+
+/// Columns written by `snapshots_export_csv`
+const SNAPSHOT_CSV_HEADER: &str =
+    "id,start_time,end_time,size,file_count,incomplete,pins,retention";
+
+/// Render snapshots as CSV (RFC 4180, CRLF line endings)
+///
+/// Pins and retention reasons are joined with `; ` within their cell.
+pub(crate) fn snapshots_to_csv(snapshots: &[crate::types::Snapshot]) -> String {
+    let mut csv = format!("{}\r\n", SNAPSHOT_CSV_HEADER);
+
+    for snapshot in snapshots {
+        let summary = snapshot.summary.as_ref();
+        let optional = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+
+        let row = [
+            snapshot.id.clone(),
+            snapshot.start_time.clone(),
+            snapshot.end_time.clone().unwrap_or_default(),
+            optional(summary.and_then(|s| s.size)),
+            optional(summary.and_then(|s| s.files)),
+            snapshot.incomplete.clone().unwrap_or_default(),
+            snapshot.pins.as_deref().unwrap_or_default().join("; "),
+            snapshot.retention.as_deref().unwrap_or_default().join("; "),
+        ];
+
+        let cells: Vec<String> = row.iter().map(|cell| csv_field(cell)).collect();
+        csv.push_str(&cells.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Summarize a source's snapshots
+pub(crate) fn summarize_source(
+    source: crate::types::SourceInfo,
+    snapshots: &crate::types::SnapshotsResponse,
+) -> crate::types::SourceSummary {
+    let last = snapshots
+        .snapshots
+        .iter()
+        .max_by_key(|s| chrono::DateTime::parse_from_rfc3339(&s.start_time).ok());
+
+    crate::types::SourceSummary {
+        source,
+        snapshot_count: snapshots.snapshots.len(),
+        last_snapshot_time: last.map(|s| s.start_time.clone()),
+        last_snapshot_size: last.and_then(|s| s.summary.as_ref()?.size),
+    }
+}
+
+/// Combine per-source fan-out results into a summary
+pub(crate) fn build_sources_summary(
+    result: FanOutResult<crate::types::SourceSummary>,
+) -> crate::types::SourcesSummary {
+    crate::types::SourcesSummary {
+        partial: result.is_partial(),
+        sources: result.completed.into_iter().map(|(_, s)| s).collect(),
+        timed_out_sources: result.timed_out,
+        failed_sources: result.failed.into_iter().map(|(key, _)| key).collect(),
+    }
+}
+
+/// Build the `--format-blob-cache-duration` flag, rejecting negative durations
+pub(crate) fn format_cache_duration_arg(seconds: i64) -> Result<String> {
+    if seconds < 0 {
+        return Err(KopiaError::invalid_input(
+            "seconds",
+            "Format cache duration must not be negative",
+        ));
+    }
+    Ok(format!("--format-blob-cache-duration={}s", seconds))
+}
+
+/// Read `formatBlobCacheDuration` (nanoseconds) from a repository config file
+///
+/// Kopia omits the field when it's zero.
+pub(crate) fn read_format_blob_cache_duration(config: &str) -> Option<i64> {
+    let config: serde_json::Value = serde_json::from_str(config).ok()?;
+    Some(config["formatBlobCacheDuration"].as_i64().unwrap_or(0))
+}
+
+/// Read `permissiveCacheLoading` from a repository config file
+///
+/// Kopia omits the field when it's false.
+pub(crate) fn read_permissive_cache_loading(config: &str) -> Option<bool> {
+    let config: serde_json::Value = serde_json::from_str(config).ok()?;
+    Some(config["permissiveCacheLoading"].as_bool().unwrap_or(false))
+}
+
+/// Cancel a running task
+pub(crate) async fn cancel_task(
+    client: &reqwest::Client,
+    server_url: &str,
+    task_id: &str,
+) -> Result<()> {
+    let response = client
+        .post(format!("{}/api/v1/tasks/{}/cancel", server_url, task_id))
+        .send()
+        .await
+        .map_http_error("Failed to cancel task")?;
+
+    handle_empty_response(response, "Cancel task").await
+}
+
+/// Collect running tasks from all running servers (or just `repo_id`'s)
+///
+/// External servers are only checked with `include_external`; their tasks
+/// keep running when the app quits.
+pub(crate) async fn collect_active_operations(
+    manager: &ServerManagerState,
+    repo_id: Option<&str>,
+    include_external: bool,
+    timeout: std::time::Duration,
+) -> crate::types::ActiveOperations {
+    let servers = {
+        let manager = manager.lock().unwrap();
+        if include_external {
+            manager.connected_servers()
+        } else {
+            manager.running_servers()
+        }
+    };
+
+    let requests = servers
+        .into_iter()
+        .filter(|(id, _, _)| repo_id.is_none_or(|r| r == id))
+        .map(|(id, server_url, client)| {
+            (id, async move { fetch_tasks(&client, &server_url).await })
+        })
+        .collect();
+
+    active_operations_from(fan_out(requests, timeout).await)
+}
+
+/// Summarize a fan-out over the servers' task lists
+pub(crate) fn active_operations_from(
+    result: FanOutResult<Vec<crate::types::Task>>,
+) -> crate::types::ActiveOperations {
+    let partial = result.is_partial();
+    let failed_repo_ids = result
+        .timed_out
+        .into_iter()
+        .chain(result.failed.into_iter().map(|(repo_id, _)| repo_id))
+        .collect();
+
+    let tasks: Vec<crate::types::Task> = result
+        .completed
+        .into_iter()
+        .flat_map(|(_, tasks)| tasks)
+        .collect();
+
+    crate::types::ActiveOperations {
+        partial,
+        failed_repo_ids,
+        ..summarize_active_operations(&tasks)
+    }
+}
+
+/// Summarize running tasks
+pub(crate) fn summarize_active_operations(
+    tasks: &[crate::types::Task],
+) -> crate::types::ActiveOperations {
+    let running: Vec<&crate::types::Task> = tasks
+        .iter()
+        .filter(|t| matches!(t.status.as_str(), "RUNNING" | "CANCELING"))
+        .collect();
+
+    let mut kinds: Vec<String> = running.iter().map(|t| t.kind.clone()).collect();
+    kinds.sort();
+    kinds.dedup();
+
+    crate::types::ActiveOperations {
+        active: !running.is_empty(),
+        count: running.len(),
+        kinds,
+        ..Default::default()
+    }
+}
+
+/// Key of the entry summing all kinds in `tasks_summary_by_kind`
+pub(crate) const TASKS_SUMMARY_TOTAL_KEY: &str = "total";
+
+/// Count tasks by kind and status
+pub(crate) fn summarize_tasks_by_kind(
+    tasks: &[crate::types::Task],
+) -> HashMap<String, crate::types::TasksSummary> {
+    let mut summary: HashMap<String, crate::types::TasksSummary> = HashMap::new();
+    summary.insert(TASKS_SUMMARY_TOTAL_KEY.to_string(), Default::default());
+
+    for task in tasks {
+        for key in [task.kind.as_str(), TASKS_SUMMARY_TOTAL_KEY] {
+            let counts = summary.entry(key.to_string()).or_default();
+            match task.status.as_str() {
+                "RUNNING" | "CANCELING" => counts.running += 1,
+                "SUCCESS" => counts.success += 1,
+                "FAILED" => counts.failed += 1,
+                "CANCELED" => counts.canceled += 1,
+                _ => {}
+            }
+        }
+    }
+
+    summary
+}
+
+/// Extract the snapshot source from a task description
+///
+/// Kopia describes snapshot tasks as `user@host:/path`, optionally followed by
+/// ` at <time>`. Returns None if the description doesn't contain a source.
+pub(crate) fn parse_task_source(description: &str) -> Option<crate::types::SourceInfo> {
+    let text = description
+        .rsplit_once(" at ")
+        .map(|(source, _)| source)
+        .unwrap_or(description);
+
+    let at = text.find('@')?;
+    let user_start = text[..at].rfind(' ').map(|i| i + 1).unwrap_or(0);
+    let user_name = &text[user_start..at];
+    let (host, path) = text[at + 1..].split_once(':')?;
+
+    if user_name.is_empty() || host.is_empty() || path.is_empty() {
+        return None;
+    }
+
+    Some(crate::types::SourceInfo {
+        user_name: user_name.to_string(),
+        host: host.to_string(),
+        path: path.trim().to_string(),
+    })
+}
+
+/// Check whether a policy logs ignored entries (any non-zero `ignored` level)
+fn logs_ignored_entries(policy: &crate::types::PolicyDefinition) -> bool {
+    policy.logging.as_ref().is_some_and(|logging| {
+        let dirs = logging.directories.as_ref().and_then(|d| d.ignored);
+        let entries = logging.entries.as_ref().and_then(|e| e.ignored);
+        dirs.unwrap_or(0) > 0 || entries.unwrap_or(0) > 0
+    })
+}
+
+/// Extract excluded paths from task log entries
+///
+/// Entries may be plain strings or structured JSON log entries with `msg` and
+/// `path` fields. Lines about ignored *errors* are skipped, and duplicates removed.
+pub(crate) fn parse_excluded_paths(entries: &[serde_json::Value]) -> Vec<String> {
+    fn is_exclusion(message: &str) -> bool {
+        let lower = message.to_lowercase();
+        (lower.contains("excluded") || lower.contains("ignored")) && !lower.contains("error")
+    }
+
+    fn path_from_text(line: &str) -> Option<String> {
+        let start = ["excluded", "ignored"]
+            .iter()
+            .filter_map(|keyword| find_ignore_ascii_case(line, keyword).map(|i| i + keyword.len()))
+            .min()?;
+        let rest = line[start..].trim();
+
+        // Structured suffix: `ignored file {"path":"..."}`
+        if let Some(json_start) = rest.find('{') {
+            if let Ok(value) = serde_json::from_str::<serde_json::Value>(&rest[json_start..]) {
+                return value["path"].as_str().map(String::from);
+            }
+        }
+
+        // Plain text: `excluded dir: /path`, `ignored: /path` or `ignored file /path`
+        let path = match rest.split_once(':') {
+            // A single letter before ':' is a Windows drive, not an entry kind
+            Some((kind, path))
+                if kind.len() != 1 && kind.chars().all(|c| c.is_alphabetic() || c == ' ') =>
+            {
+                path
+            }
+            _ => ["directory ", "dir ", "file ", "entry "]
+                .iter()
+                .find_map(|kind| rest.strip_prefix(kind))
+                .unwrap_or(rest),
+        };
+
+        let path = path.trim().trim_matches('"');
+        (!path.is_empty()).then(|| path.to_string())
+    }
+
+    let mut paths: Vec<String> = Vec::new();
+    for entry in entries {
+        let path = match entry {
+            serde_json::Value::String(line) if is_exclusion(line) => path_from_text(line),
+            serde_json::Value::Object(fields) => {
+                let message = fields.get("msg").and_then(|m| m.as_str()).unwrap_or("");
+                if !is_exclusion(message) {
+                    continue;
+                }
+                fields
+                    .get("path")
+                    .and_then(|p| p.as_str())
+                    .map(String::from)
+                    .or_else(|| path_from_text(message))
+            }
+            _ => None,
+        };
+
+        if let Some(path) = path {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
     }
+    paths
+}
+
+/// Fetch a directory object from the server
+async fn browse_directory(
+    client: &reqwest::Client,
+    server_url: &str,
+    object_id: &str,
+) -> Result<crate::types::DirectoryObject> {
+    let response = client
+        .get(format!("{}/api/v1/objects/{}", server_url, object_id))
+        .send()
+        .await
+        .map_http_error("Failed to browse object")?;
+
+    handle_response(response, "Browse object").await
+}
+
+/// Compare an object's content with a local file, streaming both sides
+async fn object_matches_file(
+    client: &reqwest::Client,
+    server_url: &str,
+    object_id: &str,
+    local: &std::path::Path,
+) -> Result<bool> {
+    use tokio::io::AsyncReadExt;
+
+    let mut response = client
+        .get(format!("{}/api/v1/objects/{}", server_url, object_id))
+        .send()
+        .await
+        .map_http_error("Failed to download object")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(http_request_failed(
+            "Failed to download object",
+            status.as_u16(),
+        ));
+    }
+
+    let mut file = tokio::fs::File::open(local).await?;
+    let mut buffer = Vec::new();
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_http_error("Failed to read response")?
+    {
+        buffer.resize(chunk.len(), 0);
+        if file.read_exact(&mut buffer).await.is_err() || buffer[..] != chunk[..] {
+            return Ok(false);
+        }
+    }
+
+    // The local file must not have trailing data
+    Ok(file.read(&mut [0u8; 1]).await? == 0)
 }
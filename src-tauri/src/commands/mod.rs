@@ -2,10 +2,12 @@
 //!
 //! Organizes all Tauri commands into logical groupings:
 //! - `kopia`: Kopia API operations (40+ commands)
+//! - `policy_helpers`: Local policy editing helpers (3 commands)
 //! - `system`: System utilities (4 commands)
 //! - `windows_service`: Windows service management (5 commands, Windows only)
 
 pub mod kopia;
+pub mod policy_helpers;
 pub mod system;
 
 #[cfg(windows)]
@@ -13,6 +15,7 @@ pub mod windows_service;
 
 // Re-export all commands for easy registration
 pub use kopia::*;
+pub use policy_helpers::*;
 pub use system::*;
 
 #[cfg(windows)]
@@ -0,0 +1,878 @@
+//! Local policy helpers for Tauri
+//!
+//! Commands that help editing policies without a round-trip to the Kopia server.
+//!
+//! Ignore rules follow the `.gitignore` syntax Kopia uses for `files.ignore` and
+//! `.kopiaignore` files (see: snapshot/ignorefs/ignorefs.go):
+//! - `*` and `?` match within a path segment, `[...]` matches a character class
+//! - `**` matches any number of directories
+//! - a leading `!` re-includes paths matched by an earlier rule
+//! - a trailing `/` only matches directories
+//! - a pattern containing `/` is relative to the source root, otherwise it
+//!   matches a file or directory name at any depth
+//! - the last matching rule wins, and nothing below an ignored directory can be
+//!   re-included
+//!
+//! Cron expressions are checked and described locally as well, so schedules
+//! can be confirmed before the policy is saved.
+//!
+//! Recommended starting policies for common kinds of data are defined here, so
+//! they can be previewed before `apply_recommended_policy` saves one.
+
+use crate::error::{KopiaError, Result};
+use crate::types::{CompressionPolicy, FilesPolicy, PolicyDefinition, RetentionPolicy};
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Result of testing ignore rules against one sample path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobMatch {
+    pub path: String,
+    /// Whether the path is ignored by the rules
+    pub matched: bool,
+    /// The rule that decided the result, if any matched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+}
+
+/// Test ignore rules against sample paths
+///
+/// Sample paths are relative to the source root (a leading `/` is ignored);
+/// directories are marked with a trailing `/`.
+#[tauri::command]
+pub async fn test_globs(
+    patterns: Vec<String>,
+    sample_paths: Vec<String>,
+) -> Result<Vec<GlobMatch>> {
+    Ok(match_globs(&patterns, &sample_paths))
+}
+
+/// Evaluate ignore rules against sample paths
+pub fn match_globs(patterns: &[String], sample_paths: &[String]) -> Vec<GlobMatch> {
+    let rules: Vec<IgnoreRule> = patterns
+        .iter()
+        .filter_map(|p| IgnoreRule::parse(p))
+        .collect();
+
+    sample_paths
+        .iter()
+        .map(|path| {
+            let rule = evaluate(&rules, path);
+            GlobMatch {
+                path: path.clone(),
+                matched: rule.is_some_and(|r| !r.negate),
+                pattern: rule.map(|r| r.source.clone()),
+            }
+        })
+        .collect()
+}
+
+/// A single parsed ignore rule
+struct IgnoreRule {
+    /// Original pattern text
+    source: String,
+    /// Pattern segments (split on `/`)
+    segments: Vec<Vec<char>>,
+    negate: bool,
+    dir_only: bool,
+    /// Whether the pattern is relative to the root rather than matching names
+    anchored: bool,
+}
+
+impl IgnoreRule {
+    /// Parse a rule, returning None for blank lines and comments
+    fn parse(pattern: &str) -> Option<Self> {
+        let source = pattern.trim();
+        if source.is_empty() || source.starts_with('#') {
+            return None;
+        }
+
+        let (negate, rest) = match source.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, source),
+        };
+        let (dir_only, rest) = match rest.strip_suffix('/') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        if rest.is_empty() {
+            return None;
+        }
+
+        let anchored = rest.contains('/');
+        let segments = rest
+            .trim_start_matches('/')
+            .split('/')
+            .map(|s| s.chars().collect())
+            .collect();
+
+        Some(Self {
+            source: source.to_string(),
+            segments,
+            negate,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// Check whether the rule matches a path given as segments
+    fn matches(&self, path: &[&str], is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored {
+            match_segments(&self.segments, path)
+        } else {
+            let name: Vec<char> = path.last().map_or_else(Vec::new, |n| n.chars().collect());
+            match_wildcard(&self.segments[0], &name)
+        }
+    }
+}
+
+/// Find the rule deciding whether `path` is ignored
+///
+/// Parent directories are checked first; once a parent is ignored, its rule
+/// decides the result for everything below it.
+fn evaluate<'a>(rules: &'a [IgnoreRule], path: &str) -> Option<&'a IgnoreRule> {
+    let normalized = path.replace('\\', "/");
+    let is_dir = normalized.ends_with('/');
+    let segments: Vec<&str> = normalized.split('/').filter(|s| !s.is_empty()).collect();
+
+    let mut decided = None;
+    for depth in 1..=segments.len() {
+        let prefix = &segments[..depth];
+        let prefix_is_dir = depth < segments.len() || is_dir;
+
+        decided = rules
+            .iter()
+            .rev()
+            .find(|r| r.matches(prefix, prefix_is_dir));
+
+        if depth < segments.len() && decided.is_some_and(|r| !r.negate) {
+            break;
+        }
+    }
+    decided
+}
+
+/// Match pattern segments against path segments, with `**` spanning directories
+fn match_segments(pattern: &[Vec<char>], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first.as_slice() == ['*', '*'] => {
+            // A trailing `**` matches everything inside, but not the directory itself
+            let min = usize::from(rest.is_empty());
+            (min..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                let name: Vec<char> = name.chars().collect();
+                match_wildcard(first, &name) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match a single segment against a wildcard pattern (`*`, `?`, `[...]`, `\`)
+fn match_wildcard(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some(('*', rest)) => {
+            let rest = rest
+                .iter()
+                .position(|&c| c != '*')
+                .map_or(&[][..], |i| &rest[i..]);
+            (0..=text.len()).any(|i| match_wildcard(rest, &text[i..]))
+        }
+        Some(('?', rest)) => !text.is_empty() && match_wildcard(rest, &text[1..]),
+        Some(('[', rest)) => match parse_class(rest) {
+            Some((class, after)) => text.split_first().is_some_and(|(&c, text_rest)| {
+                class.matches(c) && match_wildcard(after, text_rest)
+            }),
+            // Unterminated class: treat `[` literally
+            None => text.first() == Some(&'[') && match_wildcard(rest, &text[1..]),
+        },
+        Some(('\\', rest)) if !rest.is_empty() => {
+            text.first() == Some(&rest[0]) && match_wildcard(&rest[1..], &text[1..])
+        }
+        Some((&c, rest)) => text.first() == Some(&c) && match_wildcard(rest, &text[1..]),
+    }
+}
+
+/// Character class such as `[a-z]` or `[!0-9]`
+struct CharClass {
+    negate: bool,
+    ranges: Vec<(char, char)>,
+}
+
+impl CharClass {
+    fn matches(&self, c: char) -> bool {
+        self.ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != self.negate
+    }
+}
+
+/// Parse a character class following `[`, returning it and the rest of the pattern
+fn parse_class(pattern: &[char]) -> Option<(CharClass, &[char])> {
+    let (negate, mut i) = match pattern.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+
+    let mut ranges = Vec::new();
+    let start = i;
+    while i < pattern.len() {
+        let mut c = pattern[i];
+        if c == ']' && i > start {
+            return Some((CharClass { negate, ranges }, &pattern[i + 1..]));
+        }
+        if c == '\\' && i + 1 < pattern.len() {
+            i += 1;
+            c = pattern[i];
+        }
+
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            ranges.push((c, pattern[i + 2]));
+            i += 3;
+        } else {
+            ranges.push((c, c));
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Number of upcoming run times returned by `describe_cron`
+const CRON_NEXT_RUN_COUNT: usize = 5;
+
+/// How far ahead to look for run times (covers Feb 29 and leap-day schedules)
+const CRON_SEARCH_DAYS: usize = 366 * 8;
+
+/// Validated cron expression with a plain-English description
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CronDescription {
+    pub valid: bool,
+    /// Expression with macros expanded, comments removed and whitespace collapsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalized: Option<String>,
+    /// When the schedule runs, e.g. "Every day at 2:00 AM"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Next run times in local time (RFC 3339)
+    pub next_runs: Vec<String>,
+    /// Why the expression is invalid
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Validate a cron expression and describe when it runs
+///
+/// Accepts the 5-field syntax Kopia uses for `scheduling.cron` (minute, hour,
+/// day of month, month, day of week) with month and weekday names, `@daily`
+/// style macros and a trailing `# comment`.
+#[tauri::command]
+pub async fn describe_cron(expr: String) -> Result<CronDescription> {
+    Ok(describe_cron_at(&expr, Local::now().naive_local()))
+}
+
+/// Describe a cron expression, with run times following `now` (local time)
+pub fn describe_cron_at(expr: &str, now: NaiveDateTime) -> CronDescription {
+    match CronSchedule::parse(expr) {
+        Ok(schedule) => CronDescription {
+            valid: true,
+            description: Some(schedule.describe()),
+            next_runs: schedule
+                .next_runs(now, CRON_NEXT_RUN_COUNT)
+                .iter()
+                // Times skipped by a DST change don't exist locally
+                .filter_map(|time| Local.from_local_datetime(time).earliest())
+                .map(|time| time.to_rfc3339())
+                .collect(),
+            normalized: Some(schedule.normalized),
+            error: None,
+        },
+        Err(error) => CronDescription {
+            valid: false,
+            normalized: None,
+            description: None,
+            next_runs: Vec::new(),
+            error: Some(error),
+        },
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+/// Range and names accepted by one cron field
+struct FieldDef {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    names: &'static [&'static str],
+}
+
+const MINUTE: FieldDef = FieldDef {
+    name: "minute",
+    min: 0,
+    max: 59,
+    names: &[],
+};
+const HOUR: FieldDef = FieldDef {
+    name: "hour",
+    min: 0,
+    max: 23,
+    names: &[],
+};
+const DAY_OF_MONTH: FieldDef = FieldDef {
+    name: "day of month",
+    min: 1,
+    max: 31,
+    names: &[],
+};
+const MONTH: FieldDef = FieldDef {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &MONTH_NAMES,
+};
+// 7 is accepted as an alias for Sunday
+const DAY_OF_WEEK: FieldDef = FieldDef {
+    name: "day of week",
+    min: 0,
+    max: 7,
+    names: &WEEKDAY_NAMES,
+};
+
+/// Values allowed by one cron field
+#[derive(Debug, Clone)]
+struct CronField {
+    bits: u64,
+    min: u32,
+    max: u32,
+    /// Whether the field was given as something other than `*`
+    restricted: bool,
+}
+
+/// Shape of a cron field, for describing it
+#[derive(Debug, PartialEq)]
+enum FieldShape {
+    Any,
+    /// Every n-th value starting at the minimum
+    Every(u32),
+    Values(Vec<u32>),
+}
+
+impl CronField {
+    fn parse(text: &str, def: &FieldDef) -> std::result::Result<Self, String> {
+        let invalid = |reason: &str| format!("Invalid {} field '{}': {}", def.name, text, reason);
+
+        let mut bits = 0u64;
+        for item in text.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => match step.parse::<u32>() {
+                    Ok(step) if step > 0 => (range, Some(step)),
+                    _ => return Err(invalid("step must be a positive number")),
+                },
+                None => (item, None),
+            };
+
+            let (start, end) = if range == "*" {
+                (def.min, def.max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (
+                    field_value(start, def).ok_or_else(|| invalid("value out of range"))?,
+                    field_value(end, def).ok_or_else(|| invalid("value out of range"))?,
+                )
+            } else {
+                let value = field_value(range, def).ok_or_else(|| invalid("value out of range"))?;
+                // "5/15" means every 15 starting at 5
+                (value, if step.is_some() { def.max } else { value })
+            };
+            if start > end {
+                return Err(invalid("range start is after its end"));
+            }
+
+            for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+                bits |= 1 << value;
+            }
+        }
+
+        let mut max = def.max;
+        if def.name == DAY_OF_WEEK.name {
+            if bits & (1 << 7) != 0 {
+                bits = (bits & !(1 << 7)) | 1;
+            }
+            max = 6;
+        }
+
+        Ok(Self {
+            bits,
+            min: def.min,
+            max,
+            restricted: !text.starts_with('*'),
+        })
+    }
+
+    fn contains(&self, value: u32) -> bool {
+        self.bits & (1 << value) != 0
+    }
+
+    fn values(&self) -> Vec<u32> {
+        (self.min..=self.max)
+            .filter(|v| self.contains(*v))
+            .collect()
+    }
+
+    fn shape(&self) -> FieldShape {
+        let values = self.values();
+        if values.len() as u32 == self.max - self.min + 1 {
+            return FieldShape::Any;
+        }
+        if values.len() > 1 && values[0] == self.min {
+            let step = values[1] - values[0];
+            let evenly_spaced = values.windows(2).all(|w| w[1] - w[0] == step);
+            if evenly_spaced && values[values.len() - 1] + step > self.max {
+                return FieldShape::Every(step);
+            }
+        }
+        FieldShape::Values(values)
+    }
+}
+
+/// Parse a number or (for months and weekdays) a name within the field's range
+fn field_value(text: &str, def: &FieldDef) -> Option<u32> {
+    let value = match text.parse::<u32>() {
+        Ok(value) => value,
+        Err(_) => {
+            let index = def
+                .names
+                .iter()
+                .position(|name| text.len() == 3 && name[..3].eq_ignore_ascii_case(text))?;
+            index as u32 + if def.names.len() == 12 { 1 } else { 0 }
+        }
+    };
+    (def.min..=def.max).contains(&value).then_some(value)
+}
+
+/// Parsed 5-field cron expression
+#[derive(Debug, Clone)]
+pub struct CronSchedule {
+    normalized: String,
+    minutes: CronField,
+    hours: CronField,
+    days_of_month: CronField,
+    months: CronField,
+    days_of_week: CronField,
+}
+
+impl CronSchedule {
+    /// Parse a cron expression, returning a message describing the problem if invalid
+    pub fn parse(expr: &str) -> std::result::Result<Self, String> {
+        let expr = expr.split('#').next().unwrap_or_default().trim();
+        let expr = match expr.to_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ if expr.starts_with('@') => {
+                return Err(format!("Unsupported cron macro '{}'", expr));
+            }
+            _ => expr,
+        };
+
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "Expected 5 fields (minute hour day-of-month month day-of-week), got {}",
+                fields.len()
+            ));
+        }
+
+        Ok(Self {
+            normalized: fields.join(" "),
+            minutes: CronField::parse(fields[0], &MINUTE)?,
+            hours: CronField::parse(fields[1], &HOUR)?,
+            days_of_month: CronField::parse(fields[2], &DAY_OF_MONTH)?,
+            months: CronField::parse(fields[3], &MONTH)?,
+            days_of_week: CronField::parse(fields[4], &DAY_OF_WEEK)?,
+        })
+    }
+
+    /// Whether the schedule runs on `date`
+    ///
+    /// As in standard cron, when both day fields are restricted a day matching
+    /// either one is enough.
+    fn runs_on(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(date.month()) {
+            return false;
+        }
+        let day_of_month = self.days_of_month.contains(date.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(date.weekday().num_days_from_sunday());
+
+        if self.days_of_month.restricted && self.days_of_week.restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+
+    /// The next `count` run times strictly after `after`
+    pub fn next_runs(&self, after: NaiveDateTime, count: usize) -> Vec<NaiveDateTime> {
+        let mut runs = Vec::new();
+        let hours = self.hours.values();
+        let minutes = self.minutes.values();
+
+        let mut date = after.date();
+        for _ in 0..CRON_SEARCH_DAYS {
+            if self.runs_on(date) {
+                for &hour in &hours {
+                    for &minute in &minutes {
+                        let Some(time) = date.and_hms_opt(hour, minute, 0) else {
+                            continue;
+                        };
+                        if time > after {
+                            runs.push(time);
+                            if runs.len() == count {
+                                return runs;
+                            }
+                        }
+                    }
+                }
+            }
+            match date.succ_opt() {
+                Some(next) => date = next,
+                None => break,
+            }
+        }
+        runs
+    }
+
+    /// Describe the schedule in plain English, e.g. "At 6:30 PM on weekdays"
+    pub fn describe(&self) -> String {
+        let days = self.describe_days();
+        let months = match self.months.shape() {
+            FieldShape::Any => None,
+            _ => Some(format!(
+                "in {}",
+                join_list(
+                    self.months
+                        .values()
+                        .iter()
+                        .map(|m| MONTH_NAMES[*m as usize - 1].to_string())
+                )
+            )),
+        };
+
+        let mut description = match self.times_of_day() {
+            Some(times) if days.is_none() && months.is_none() => {
+                format!("every day at {}", join_list(times))
+            }
+            Some(times) => format!("at {}", join_list(times)),
+            None => self.describe_time(),
+        };
+        for part in [days, months].into_iter().flatten() {
+            description.push(' ');
+            description.push_str(&part);
+        }
+
+        let mut chars = description.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => description,
+        }
+    }
+
+    /// The times of day the schedule runs at, if there are only a few
+    fn times_of_day(&self) -> Option<Vec<String>> {
+        match (self.minutes.shape(), self.hours.shape()) {
+            (FieldShape::Values(minutes), FieldShape::Values(hours))
+                if minutes.len() * hours.len() <= 4 =>
+            {
+                Some(
+                    hours
+                        .iter()
+                        .flat_map(|h| minutes.iter().map(move |m| format_time(*h, *m)))
+                        .collect(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Describe the minute and hour fields when they don't reduce to a few times of day
+    fn describe_time(&self) -> String {
+        let every_minutes = |shape: &FieldShape| match shape {
+            FieldShape::Every(n) => format!("every {} minutes", n),
+            _ => "every minute".to_string(),
+        };
+
+        match (self.minutes.shape(), self.hours.shape()) {
+            (FieldShape::Values(minutes), FieldShape::Any) if minutes == [0] => {
+                "every hour".to_string()
+            }
+            (FieldShape::Values(minutes), FieldShape::Any) => {
+                format!("every hour at {}", minute_list(&minutes))
+            }
+            (FieldShape::Values(minutes), FieldShape::Every(n)) if minutes == [0] => {
+                format!("every {} hours", n)
+            }
+            (FieldShape::Values(minutes), FieldShape::Every(n)) => {
+                format!("every {} hours at {}", n, minute_list(&minutes))
+            }
+            (FieldShape::Values(minutes), FieldShape::Values(hours)) => match hour_range(&hours) {
+                Some(range) => format!("at {} past the hour {}", minute_list(&minutes), range),
+                None => format!("at {} past {}", minute_list(&minutes), hour_list(&hours)),
+            },
+            (minutes, FieldShape::Any) => every_minutes(&minutes),
+            (minutes, FieldShape::Every(n)) => {
+                format!("{}, every {} hours", every_minutes(&minutes), n)
+            }
+            (minutes, FieldShape::Values(hours)) => match hour_range(&hours) {
+                Some(range) => format!("{} {}", every_minutes(&minutes), range),
+                None => format!("{} during {}", every_minutes(&minutes), hour_list(&hours)),
+            },
+        }
+    }
+
+    fn describe_days(&self) -> Option<String> {
+        let day_of_month = match self.days_of_month.shape() {
+            FieldShape::Any => None,
+            FieldShape::Every(n) => Some(format!("every {} days", n)),
+            FieldShape::Values(days) => Some(format!(
+                "on {} {} of the month",
+                if days.len() == 1 { "day" } else { "days" },
+                join_list(days.iter().map(u32::to_string))
+            )),
+        };
+        let day_of_week = match self.days_of_week.shape() {
+            FieldShape::Any => None,
+            _ => {
+                let days = self.days_of_week.values();
+                Some(match days.as_slice() {
+                    [1, 2, 3, 4, 5] => "on weekdays".to_string(),
+                    [0, 6] => "on weekends".to_string(),
+                    _ => format!(
+                        "on {}",
+                        join_list(days.iter().map(|d| WEEKDAY_NAMES[*d as usize].to_string()))
+                    ),
+                })
+            }
+        };
+
+        match (day_of_month, day_of_week) {
+            (Some(dom), Some(dow))
+                if self.days_of_month.restricted && self.days_of_week.restricted =>
+            {
+                Some(format!("{} or {}", dom, dow))
+            }
+            (Some(dom), Some(dow)) => Some(format!("{} {}", dom, dow)),
+            (dom, dow) => dom.or(dow),
+        }
+    }
+}
+
+/// Format a time of day as e.g. "2:05 PM"
+fn format_time(hour: u32, minute: u32) -> String {
+    let period = if hour < 12 { "AM" } else { "PM" };
+    let hour = match hour % 12 {
+        0 => 12,
+        h => h,
+    };
+    format!("{}:{:02} {}", hour, minute, period)
+}
+
+fn minute_list(minutes: &[u32]) -> String {
+    format!(
+        "{} {}",
+        if minutes.len() == 1 {
+            "minute"
+        } else {
+            "minutes"
+        },
+        join_list(minutes.iter().map(u32::to_string))
+    )
+}
+
+fn hour_list(hours: &[u32]) -> String {
+    let names = hours.iter().map(|h| {
+        let time = format_time(*h, 0);
+        time.replace(":00", "")
+    });
+    format!(
+        "the {} {}",
+        join_list(names),
+        if hours.len() == 1 { "hour" } else { "hours" }
+    )
+}
+
+/// Describe 3 or more consecutive hours as e.g. "between 9:00 AM and 5:59 PM"
+fn hour_range(hours: &[u32]) -> Option<String> {
+    let (first, last) = (*hours.first()?, *hours.last()?);
+    let consecutive = hours.windows(2).all(|w| w[1] == w[0] + 1);
+    (consecutive && hours.len() >= 3).then(|| {
+        format!(
+            "between {} and {}",
+            format_time(first, 0),
+            format_time(last, 59)
+        )
+    })
+}
+
+/// Join items as "a, b and c"
+fn join_list(items: impl IntoIterator<Item = String>) -> String {
+    let items: Vec<String> = items.into_iter().collect();
+    match items.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.clone(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+// ============================================================================
+// Recommended Policies
+// ============================================================================
+
+/// Curated starting policy for a kind of data
+struct RecommendedPolicy {
+    profile: &'static str,
+    /// Latest, hourly, daily, weekly, monthly and annual snapshots to keep
+    retention: [i64; 6],
+    compressor: &'static str,
+    ignore: &'static [&'static str],
+}
+
+/// Recommended policies by profile
+///
+/// - `documents`: long history (a month of dailies, two years of monthlies),
+///   zstd compression, skips office lock and temporary files
+/// - `photos`: fewer snapshots since photos rarely change once taken, no
+///   compression since image and video formats are already compressed, skips
+///   thumbnail caches
+/// - `code`: frequent snapshots, zstd compression, skips dependency and build
+///   output directories that can be recreated (`.git` is kept)
+/// - `general`: Kopia's default retention with fast zstd compression
+const RECOMMENDED_POLICIES: &[RecommendedPolicy] = &[
+    RecommendedPolicy {
+        profile: "documents",
+        retention: [10, 24, 30, 8, 24, 5],
+        compressor: "zstd",
+        ignore: &["~$*", ".~lock.*#", "*.tmp", "Thumbs.db", ".DS_Store"],
+    },
+    RecommendedPolicy {
+        profile: "photos",
+        retention: [5, 0, 14, 8, 12, 10],
+        compressor: "none",
+        ignore: &[
+            "Thumbs.db",
+            ".DS_Store",
+            ".thumbnails/",
+            "*.lrdata/",
+            "@eaDir/",
+        ],
+    },
+    RecommendedPolicy {
+        profile: "code",
+        retention: [20, 48, 14, 8, 6, 1],
+        compressor: "zstd",
+        ignore: &[
+            "node_modules/",
+            "target/",
+            "build/",
+            "dist/",
+            ".venv/",
+            "__pycache__/",
+            ".gradle/",
+            "*.o",
+        ],
+    },
+    RecommendedPolicy {
+        profile: "general",
+        retention: [10, 48, 7, 4, 24, 3],
+        compressor: "zstd-fastest",
+        ignore: &["*.tmp", "Thumbs.db", ".DS_Store"],
+    },
+];
+
+/// Preview the recommended policy for a profile
+///
+/// Profiles: "documents", "photos", "code" or "general". Only retention,
+/// compression and ignore rules are set.
+#[tauri::command]
+pub async fn recommended_policy(profile: String) -> Result<PolicyDefinition> {
+    recommended_policy_for(&profile)
+}
+
+/// Build the recommended policy for a profile
+pub fn recommended_policy_for(profile: &str) -> Result<PolicyDefinition> {
+    let recommended = RECOMMENDED_POLICIES
+        .iter()
+        .find(|p| p.profile == profile)
+        .ok_or_else(|| {
+            let profiles: Vec<&str> = RECOMMENDED_POLICIES.iter().map(|p| p.profile).collect();
+            KopiaError::invalid_input(
+                "profile",
+                format!(
+                    "Unknown profile '{}', expected one of {}",
+                    profile,
+                    profiles.join(", ")
+                ),
+            )
+        })?;
+
+    let [latest, hourly, daily, weekly, monthly, annual] = recommended.retention;
+    Ok(PolicyDefinition {
+        retention: Some(RetentionPolicy {
+            keep_latest: Some(latest),
+            keep_hourly: Some(hourly),
+            keep_daily: Some(daily),
+            keep_weekly: Some(weekly),
+            keep_monthly: Some(monthly),
+            keep_annual: Some(annual),
+            ignore_identical_snapshots: None,
+        }),
+        compression: Some(CompressionPolicy {
+            compressor_name: Some(recommended.compressor.to_string()),
+            min_size: None,
+            max_size: None,
+            only_compress: None,
+            no_parent_only_compress: None,
+            never_compress: None,
+            no_parent_never_compress: None,
+        }),
+        files: Some(FilesPolicy {
+            ignore: Some(recommended.ignore.iter().map(|p| p.to_string()).collect()),
+            ignore_cache_dirs: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
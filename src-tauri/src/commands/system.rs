@@ -3,9 +3,19 @@
 //! Provides system-level utility commands for file/folder selection, system information,
 //! and user detection.
 
+use crate::app_state::AppState;
 use crate::error::{HttpResultExt, KopiaError, Result};
-use crate::kopia_server::KopiaServerState;
+use crate::fan_out::{fan_out_timeout, MAX_FAN_OUT_TIMEOUT_SECS};
+use crate::kopia_server::{KopiaServer, KopiaServerState};
+use crate::metrics::CommandMetrics;
+use crate::power::PowerState;
+use crate::server_manager::ServerManagerState;
+use crate::settings::{AppSettings, SettingsState};
+use crate::types::FilesystemOutput;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
 use tauri::{AppHandle, State};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -25,6 +35,1713 @@ pub async fn get_system_info() -> Result<SystemInfo> {
     })
 }
 
+/// Expected SHA-256 of the bundled Kopia binary, embedded at build time
+const EXPECTED_BINARY_SHA256: Option<&str> = option_env!("KOPIA_BINARY_SHA256");
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryIntegrity {
+    pub path: String,
+    pub sha256: String,
+    /// None when no expected hash was embedded in this build
+    pub matches_expected: Option<bool>,
+}
+
+/// Verify the Kopia binary against the SHA-256 embedded at build time
+///
+/// The expected hash comes from `KOPIA_BINARY_SHA256` or the `.sha256` file
+/// written by `scripts/download-kopia.sh`.
+#[tauri::command]
+pub async fn verify_binary_integrity() -> Result<BinaryIntegrity> {
+    let path = KopiaServer::get_kopia_binary_path()?;
+
+    let hash_path = path.clone();
+    let sha256 = tokio::task::spawn_blocking(move || sha256_file(&hash_path))
+        .await
+        .map_err(|e| KopiaError::operation_failed("verify binary", e.to_string()))??;
+
+    let matches_expected = EXPECTED_BINARY_SHA256.map(|expected| hash_matches(&sha256, expected));
+    if matches_expected == Some(false) {
+        log::warn!("Kopia binary {} does not match the expected checksum", path);
+    }
+
+    Ok(BinaryIntegrity {
+        path,
+        sha256,
+        matches_expected,
+    })
+}
+
+/// Hash a file with SHA-256, returning lowercase hex
+pub(crate) fn sha256_file(path: &str) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = std::fs::File::open(path).map_err(|e| {
+        KopiaError::operation_failed_with_details(
+            "verify binary",
+            format!("Failed to open {}", path),
+            e.to_string(),
+        )
+    })?;
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).map_err(|e| {
+        KopiaError::operation_failed_with_details(
+            "verify binary",
+            format!("Failed to read {}", path),
+            e.to_string(),
+        )
+    })?;
+
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Compare hex digests, ignoring case and surrounding whitespace
+pub(crate) fn hash_matches(actual: &str, expected: &str) -> bool {
+    actual.trim().eq_ignore_ascii_case(expected.trim())
+}
+
+/// Default number of files hashed by `hash_directory`
+const HASH_DIRECTORY_MAX_FILES: u64 = 10_000;
+/// Default time spent hashing by `hash_directory`
+const HASH_DIRECTORY_TIME_LIMIT_SECS: u64 = 30;
+/// Number of file hashes returned as examples by `hash_directory`
+const HASH_DIRECTORY_SAMPLES: usize = 20;
+
+/// Hash of one file found by `hash_directory`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHash {
+    /// Path relative to the hashed directory
+    pub path: String,
+    pub size: u64,
+    pub hash: String,
+}
+
+/// Result of `hash_directory`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryHashSummary {
+    pub algorithm: String,
+    /// Files hashed
+    pub file_count: u64,
+    /// Bytes hashed
+    pub total_bytes: u64,
+    /// Files whose content matched an earlier file
+    pub duplicate_files: u64,
+    /// Bytes in those duplicate files
+    pub duplicate_bytes: u64,
+    /// Files that couldn't be read, including one cut off by the time limit
+    pub skipped_files: u64,
+    /// First files hashed, as examples
+    pub sample_hashes: Vec<FileHash>,
+    /// True when the file or time limit stopped the walk early
+    pub truncated: bool,
+}
+
+/// Hash local files to show how much of a directory is duplicated
+///
+/// A local diagnostic to set expectations before a first backup, next to the
+/// server's `estimate_snapshot`. Whole files are compared, so this is a lower
+/// bound: Kopia also deduplicates matching chunks of files that differ.
+/// Symlinks aren't followed. Hashing stops after `max_files` files or
+/// `time_limit_seconds`, whichever comes first. `algorithm` is `sha256`
+/// (default) or `sha512`.
+#[tauri::command]
+pub async fn hash_directory(
+    path: String,
+    algorithm: Option<String>,
+    max_files: Option<u64>,
+    time_limit_seconds: Option<u64>,
+) -> Result<DirectoryHashSummary> {
+    let algorithm = algorithm.unwrap_or_else(|| "sha256".to_string());
+    let max_files = max_files.unwrap_or(HASH_DIRECTORY_MAX_FILES);
+    let deadline = std::time::Instant::now()
+        + Duration::from_secs(time_limit_seconds.unwrap_or(HASH_DIRECTORY_TIME_LIMIT_SECS));
+
+    tokio::task::spawn_blocking(move || {
+        hash_directory_tree(Path::new(&path), &algorithm, max_files, deadline)
+    })
+    .await
+    .map_err(|e| KopiaError::operation_failed("hash directory", e.to_string()))?
+}
+
+/// Walk `root` in name order, hashing files until a limit is reached
+pub(crate) fn hash_directory_tree(
+    root: &Path,
+    algorithm: &str,
+    max_files: u64,
+    deadline: std::time::Instant,
+) -> Result<DirectoryHashSummary> {
+    let algorithm = algorithm.trim().to_ascii_lowercase();
+    if !matches!(algorithm.as_str(), "sha256" | "sha512") {
+        return Err(KopiaError::invalid_input(
+            "algorithm",
+            format!("Unsupported hash algorithm '{}'", algorithm),
+        ));
+    }
+    if !root.is_dir() {
+        return Err(KopiaError::PathNotFound {
+            path: root.display().to_string(),
+        });
+    }
+
+    let mut summary = DirectoryHashSummary {
+        algorithm: algorithm.clone(),
+        ..Default::default()
+    };
+    let mut seen = std::collections::HashSet::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut entries: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+        entries.sort();
+
+        let mut subdirs = Vec::new();
+        for path in entries {
+            let Ok(metadata) = std::fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if metadata.is_dir() {
+                subdirs.push(path);
+                continue;
+            }
+            if !metadata.is_file() {
+                continue;
+            }
+
+            if summary.file_count >= max_files || std::time::Instant::now() >= deadline {
+                summary.truncated = true;
+                return Ok(summary);
+            }
+
+            let hash = match hash_file(&path, &algorithm, deadline) {
+                Ok(hash) => hash,
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                    summary.skipped_files += 1;
+                    summary.truncated = true;
+                    return Ok(summary);
+                }
+                Err(_) => {
+                    summary.skipped_files += 1;
+                    continue;
+                }
+            };
+            let size = metadata.len();
+            summary.file_count += 1;
+            summary.total_bytes += size;
+            if !seen.insert(hash.clone()) {
+                summary.duplicate_files += 1;
+                summary.duplicate_bytes += size;
+            }
+            if summary.sample_hashes.len() < HASH_DIRECTORY_SAMPLES {
+                summary.sample_hashes.push(FileHash {
+                    path: path
+                        .strip_prefix(root)
+                        .unwrap_or(&path)
+                        .to_string_lossy()
+                        .into_owned(),
+                    size,
+                    hash,
+                });
+            }
+        }
+
+        // Visit subdirectories in name order
+        pending.extend(subdirs.into_iter().rev());
+    }
+
+    Ok(summary)
+}
+
+/// Chunk size for hashing files
+const HASH_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Hash a file with `sha256` or `sha512`, returning lowercase hex
+///
+/// The deadline is checked between chunks, so a large file can't run far past
+/// it; in that case a `TimedOut` error is returned.
+pub(crate) fn hash_file(
+    path: &Path,
+    algorithm: &str,
+    deadline: std::time::Instant,
+) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256, Sha512};
+    use std::io::Read;
+
+    fn hex_digest<D: Digest>(path: &Path, deadline: std::time::Instant) -> std::io::Result<String> {
+        let mut hasher = D::new();
+        let mut file = std::fs::File::open(path)?;
+        let mut buf = vec![0u8; HASH_CHUNK_BYTES];
+        loop {
+            if std::time::Instant::now() >= deadline {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    format!("Time limit reached while hashing '{}'", path.display()),
+                ));
+            }
+            match file.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => hasher.update(&buf[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+
+    match algorithm {
+        "sha512" => hex_digest::<Sha512>(path, deadline),
+        _ => hex_digest::<Sha256>(path, deadline),
+    }
+}
+
+/// Get per-command metrics
+///
+/// Returns call count, error count and estimated p50/p95 latency for every
+/// Kopia command called since the app started, keyed by command name (e.g.
+/// `snapshots_list`).
+#[tauri::command]
+pub async fn get_command_metrics(
+    app_state: State<'_, AppState>,
+) -> Result<HashMap<String, CommandMetrics>> {
+    Ok(app_state.metrics().snapshot())
+}
+
+/// Log level at startup
+pub const DEFAULT_LOG_LEVEL: log::LevelFilter = log::LevelFilter::Info;
+
+/// Change the log level until the app exits
+///
+/// Meant for reproducing bugs without restarting: raise to `debug` or `trace`,
+/// reproduce, then revert. Applies to stderr and the log file (see
+/// `logging`), and also governs the forwarded output of Kopia servers, which
+/// is logged at `debug`.
+#[tauri::command]
+pub async fn set_log_level(level: String) -> Result<String> {
+    let filter = parse_log_level(&level)?;
+    log::set_max_level(filter);
+    log::info!("Log level set to {}", filter);
+    Ok(log_level_name(filter))
+}
+
+/// Get the current log level
+#[tauri::command]
+pub async fn get_log_level() -> Result<String> {
+    Ok(log_level_name(log::max_level()))
+}
+
+/// Get the configuration the app is running with, for diagnostics
+///
+/// Merges compiled defaults with the settings and includes the repository's
+/// throttle limits when its server can be reached.
+#[tauri::command]
+pub async fn get_effective_config(
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+) -> Result<crate::types::EffectiveConfig> {
+    let server = {
+        let manager = manager.lock().unwrap();
+        manager
+            .get_server_url(&repo_id)
+            .zip(manager.get_http_client(&repo_id))
+    };
+
+    let throttle = match server {
+        Some((server_url, client)) => {
+            match crate::commands::kopia::fetch_throttle(&client, &server_url).await {
+                Ok(limits) => Some(limits),
+                Err(e) => {
+                    log::debug!("Failed to get throttle limits for '{}': {}", repo_id, e);
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    let settings = settings.lock().unwrap().get();
+    Ok(effective_config(&settings, throttle))
+}
+
+/// Merge compiled defaults with the settings
+pub(crate) fn effective_config(
+    settings: &AppSettings,
+    throttle: Option<crate::types::ThrottleLimits>,
+) -> crate::types::EffectiveConfig {
+    use crate::kopia_server;
+
+    crate::types::EffectiveConfig {
+        health_check_retries: kopia_server::HEALTH_CHECK_RETRIES,
+        health_check_interval_ms: kopia_server::HEALTH_CHECK_INTERVAL_MS,
+        heartbeat_interval_secs: kopia_server::HEARTBEAT_INTERVAL_SECS,
+        heartbeat_timeout_secs: kopia_server::HEARTBEAT_TIMEOUT_SECS,
+        heartbeat_failure_threshold: kopia_server::HEARTBEAT_FAILURE_THRESHOLD,
+        http_operation_timeout_secs: kopia_server::HTTP_OPERATION_TIMEOUT_SECS,
+        http_connect_timeout_secs: kopia_server::HTTP_CONNECT_TIMEOUT_SECS,
+        download_idle_timeout_secs: kopia_server::DOWNLOAD_IDLE_TIMEOUT_SECS,
+        cli_timeout_secs: crate::kopia_cli::CLI_TIMEOUT_SECS,
+        fan_out_timeout_secs: fan_out_timeout(None, settings.fan_out_timeout_secs).as_secs(),
+        restore_files_concurrency: crate::commands::kopia::RESTORE_FILES_CONCURRENCY,
+        busy_retry_attempts: crate::commands::kopia::BUSY_RETRY_ATTEMPTS,
+        busy_retry_initial_delay_ms: crate::commands::kopia::BUSY_RETRY_INITIAL_DELAY_MS,
+        auto_reconnect: settings.auto_reconnect,
+        reconnect_initial_backoff_secs: crate::reconnect::RECONNECT_INITIAL_BACKOFF_SECS,
+        reconnect_max_backoff_secs: crate::reconnect::RECONNECT_MAX_BACKOFF_SECS,
+        wait_for_tasks_on_quit: settings.wait_for_tasks_on_quit,
+        quit_wait_secs: crate::quit::DEFAULT_QUIT_WAIT_SECS,
+        insecure_skip_cert_verify: settings.insecure_skip_cert_verify,
+        server_check_for_updates: settings.server_check_for_updates,
+        developer_mode: settings.developer_mode,
+        log_level: log_level_name(log::max_level()),
+        throttle,
+    }
+}
+
+/// Parse a log level name ("error", "warn", "info", "debug" or "trace")
+pub(crate) fn parse_log_level(level: &str) -> Result<log::LevelFilter> {
+    match level.trim().to_ascii_lowercase().as_str() {
+        "error" => Ok(log::LevelFilter::Error),
+        "warn" => Ok(log::LevelFilter::Warn),
+        "info" => Ok(log::LevelFilter::Info),
+        "debug" => Ok(log::LevelFilter::Debug),
+        "trace" => Ok(log::LevelFilter::Trace),
+        _ => Err(KopiaError::invalid_input(
+            "level",
+            format!(
+                "Unknown log level '{}' (expected error, warn, info, debug or trace)",
+                level
+            ),
+        )),
+    }
+}
+
+/// Lowercase name of a log level, as accepted by `set_log_level`
+pub(crate) fn log_level_name(filter: log::LevelFilter) -> String {
+    filter.as_str().to_ascii_lowercase()
+}
+
+/// Get the application settings
+#[tauri::command]
+pub async fn get_app_settings(settings: State<'_, SettingsState>) -> Result<AppSettings> {
+    Ok(settings.lock().unwrap().get())
+}
+
+/// Enable or disable power-aware scheduling
+///
+/// When enabled, all sources are paused while on battery or a metered connection
+/// and resumed afterwards. Takes effect on the next power state check.
+#[tauri::command]
+pub async fn power_aware_scheduling(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<AppSettings> {
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.power_aware_scheduling = enabled)
+}
+
+/// Enable or disable skipping TLS certificate verification (debugging only)
+///
+/// Lets the app talk to a manually-run server whose certificate differs from the
+/// pinned one. Only available in debug builds or with
+/// `KOPIA_DESKTOP_ALLOW_INSECURE_TLS=1`. Applies the next time servers start.
+#[tauri::command]
+pub async fn set_insecure_skip_cert_verify(
+    settings: State<'_, SettingsState>,
+    manager: State<'_, ServerManagerState>,
+    enabled: bool,
+) -> Result<AppSettings> {
+    manager
+        .lock()
+        .unwrap()
+        .set_insecure_skip_cert_verify(enabled)?;
+
+    if enabled {
+        log::warn!("TLS certificate verification disabled for Kopia servers");
+    }
+
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.insecure_skip_cert_verify = enabled)
+}
+
+/// Set the per-request timeout for aggregate commands (None = default)
+///
+/// Sources that don't answer within the timeout are reported as timed out and
+/// the command returns partial results.
+#[tauri::command]
+pub async fn set_fan_out_timeout(
+    settings: State<'_, SettingsState>,
+    timeout_secs: Option<u64>,
+) -> Result<AppSettings> {
+    if let Some(secs) = timeout_secs {
+        if !(1..=MAX_FAN_OUT_TIMEOUT_SECS).contains(&secs) {
+            return Err(KopiaError::invalid_input(
+                "timeoutSecs",
+                format!(
+                    "Timeout must be between 1 and {} seconds",
+                    MAX_FAN_OUT_TIMEOUT_SECS
+                ),
+            ));
+        }
+    }
+
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.fan_out_timeout_secs = timeout_secs)
+}
+
+/// Let running tasks finish before quitting from the tray or closing the window
+#[tauri::command]
+pub async fn set_wait_for_tasks_on_quit(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<AppSettings> {
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.wait_for_tasks_on_quit = enabled)
+}
+
+/// Enable or disable automatic repository reconnection
+///
+/// When enabled, repositories that become unreachable (e.g. network storage
+/// after roaming between networks) are reconnected once their storage is
+/// reachable again. Takes effect on the next connectivity check.
+#[tauri::command]
+pub async fn set_auto_reconnect(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<AppSettings> {
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.auto_reconnect = enabled)
+}
+
+/// Enable or disable developer mode
+///
+/// Developer mode unlocks low-level repository diagnostics (e.g.
+/// `blob_contents`) that are only useful when investigating storage issues.
+#[tauri::command]
+pub async fn set_developer_mode(
+    settings: State<'_, SettingsState>,
+    enabled: bool,
+) -> Result<AppSettings> {
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.developer_mode = enabled)
+}
+
+/// Update check state of the Kopia servers (see `get_server_update_check`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUpdateCheck {
+    pub enabled: bool,
+    /// Newer Kopia version announced by a server, if any
+    pub available_version: Option<String>,
+}
+
+/// Get whether servers check for Kopia updates and any version they found
+#[tauri::command]
+pub async fn get_server_update_check(
+    settings: State<'_, SettingsState>,
+    manager: State<'_, ServerManagerState>,
+) -> Result<ServerUpdateCheck> {
+    Ok(ServerUpdateCheck {
+        enabled: settings.lock().unwrap().get().server_check_for_updates,
+        available_version: manager.lock().unwrap().available_update(),
+    })
+}
+
+/// Enable or disable the Kopia server's update check
+///
+/// Sets `KOPIA_CHECK_FOR_UPDATES` for servers started afterwards.
+#[tauri::command]
+pub async fn set_server_check_for_updates(
+    settings: State<'_, SettingsState>,
+    manager: State<'_, ServerManagerState>,
+    enabled: bool,
+) -> Result<AppSettings> {
+    manager.lock().unwrap().set_check_for_updates(enabled);
+
+    settings
+        .lock()
+        .unwrap()
+        .update(|s| s.server_check_for_updates = enabled)
+}
+
+/// Repository and screen to reopen the app to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UiState {
+    pub repo_id: String,
+    pub view: Option<String>,
+}
+
+/// Remember the repository and screen the user has open
+#[tauri::command]
+pub async fn save_ui_state(
+    settings: State<'_, SettingsState>,
+    repo_id: String,
+    view: String,
+) -> Result<AppSettings> {
+    if repo_id.trim().is_empty() {
+        return Err(KopiaError::invalid_input(
+            "repoId",
+            "Repository ID cannot be empty",
+        ));
+    }
+
+    let view = Some(view.trim().to_string()).filter(|v| !v.is_empty());
+
+    settings.lock().unwrap().update(|s| {
+        s.last_repo_id = Some(repo_id);
+        s.last_view = view;
+    })
+}
+
+/// Get the repository and screen to restore on startup
+///
+/// Falls back to the default repository when the last one no longer exists.
+#[tauri::command]
+pub async fn get_ui_state(
+    settings: State<'_, SettingsState>,
+    manager: State<'_, ServerManagerState>,
+) -> Result<UiState> {
+    let settings = settings.lock().unwrap().get();
+    let repo_ids: Vec<String> = manager
+        .lock()
+        .unwrap()
+        .list_repositories()?
+        .into_iter()
+        .map(|entry| entry.id)
+        .collect();
+
+    Ok(restore_ui_state(&settings, &repo_ids))
+}
+
+/// Resolve the saved UI state against the repositories that still exist
+pub(crate) fn restore_ui_state(settings: &AppSettings, repo_ids: &[String]) -> UiState {
+    let repo_id = settings
+        .last_repo_id
+        .as_ref()
+        .filter(|id| repo_ids.contains(id))
+        .cloned()
+        .unwrap_or_else(|| crate::server_manager::DEFAULT_REPO_ID.to_string());
+
+    UiState {
+        repo_id,
+        view: settings.last_view.clone(),
+    }
+}
+
+/// Stop all servers and quit, optionally waiting for running tasks first
+///
+/// With `wait`, running tasks get up to `timeout_secs` to finish while
+/// `quit-waiting` events report progress. Tasks still running afterwards (or
+/// right away without `wait`) are canceled before the servers are stopped.
+#[tauri::command]
+pub async fn quit_gracefully(
+    app: AppHandle,
+    app_state: State<'_, AppState>,
+    manager: State<'_, ServerManagerState>,
+    settings: State<'_, SettingsState>,
+    wait: bool,
+    timeout_secs: u64,
+) -> Result<()> {
+    if !app_state.begin_quit() {
+        log::debug!("Quit already in progress");
+        return Ok(());
+    }
+
+    let request_timeout =
+        fan_out_timeout(None, settings.lock().unwrap().get().fan_out_timeout_secs);
+    crate::quit::run_graceful_quit(
+        app,
+        manager.inner().clone(),
+        wait,
+        Duration::from_secs(timeout_secs),
+        request_timeout,
+    )
+    .await;
+    Ok(())
+}
+
+/// Get the detected power and network state
+#[tauri::command]
+pub async fn get_power_state() -> Result<PowerState> {
+    tokio::task::spawn_blocking(crate::power::detect_power_state)
+        .await
+        .map_err(|e| KopiaError::operation_failed("detect power state", e.to_string()))
+}
+
+/// Result of `check_binary_compatibility`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BinaryCompatibility {
+    pub path: String,
+    /// Format and architecture this system runs, e.g. "ELF x86_64"
+    pub expected: String,
+    /// Format and architecture of the binary, or "unknown"
+    pub resolved: String,
+    pub compatible: bool,
+}
+
+/// Check that the Kopia binary is built for this platform
+///
+/// Reads the executable header (ELF, Mach-O or PE) of the binary the server
+/// would start. Binaries in an unrecognized format are reported as
+/// incompatible. Starting a server with a known mismatch fails with
+/// `BinaryIncompatible` instead of an exec error.
+#[tauri::command]
+pub async fn check_binary_compatibility() -> Result<BinaryCompatibility> {
+    let path = KopiaServer::get_kopia_binary_path()?;
+    let format = crate::kopia_server::read_binary_format(&path)?;
+    let host = crate::kopia_server::host_binary_format();
+
+    Ok(BinaryCompatibility {
+        path,
+        expected: host.to_string(),
+        resolved: format.map_or_else(|| "unknown".to_string(), |f| f.to_string()),
+        compatible: format.is_some_and(|f| crate::kopia_server::binary_runs_on(f, host)),
+    })
+}
+
+/// Disk space used by the app's own files in the config directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStorageUsage {
+    /// Settings and repository configs (everything except `cache/` and `logs/`)
+    pub config_bytes: u64,
+    pub cache_bytes: u64,
+    pub logs_bytes: u64,
+    pub total_bytes: u64,
+    /// Paths that couldn't be read and were counted as zero
+    pub inaccessible: Vec<String>,
+}
+
+/// Get the disk usage of the config directory, split into config, cache and logs
+///
+/// Only covers local app data, not the repositories themselves.
+#[tauri::command]
+pub async fn app_storage_usage(manager: State<'_, ServerManagerState>) -> Result<AppStorageUsage> {
+    let config_dir = manager.lock().unwrap().config_dir().to_string();
+
+    tokio::task::spawn_blocking(move || storage_usage(std::path::Path::new(&config_dir)))
+        .await
+        .map_err(|e| KopiaError::operation_failed("app storage usage", e.to_string()))
+}
+
+/// Size the top-level entries of `config_dir` by category
+///
+/// Entries that can't be read are counted as zero and listed in `inaccessible`.
+pub(crate) fn storage_usage(config_dir: &std::path::Path) -> AppStorageUsage {
+    let mut usage = AppStorageUsage::default();
+
+    let entries = match std::fs::read_dir(config_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return usage,
+        Err(e) => {
+            log::warn!("Failed to read '{}': {}", config_dir.display(), e);
+            usage.inaccessible.push(config_dir.display().to_string());
+            return usage;
+        }
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let bytes = match path_size(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                log::warn!("Failed to size '{}': {}", path.display(), e);
+                usage.inaccessible.push(path.display().to_string());
+                0
+            }
+        };
+
+        match entry.file_name().to_str() {
+            Some("cache") => usage.cache_bytes += bytes,
+            Some("logs") => usage.logs_bytes += bytes,
+            _ => usage.config_bytes += bytes,
+        }
+    }
+
+    usage.total_bytes = usage.config_bytes + usage.cache_bytes + usage.logs_bytes;
+    usage
+}
+
+/// Total size of a file or directory tree, without following symlinks
+fn path_size(path: &std::path::Path) -> std::io::Result<u64> {
+    let metadata = std::fs::symlink_metadata(path)?;
+    if !metadata.is_dir() {
+        return Ok(metadata.len());
+    }
+
+    let mut total = 0;
+    for entry in std::fs::read_dir(path)? {
+        total += path_size(&entry?.path())?;
+    }
+    Ok(total)
+}
+
+/// Free space required at the new config location beyond the data being moved
+const RELOCATE_SPACE_MARGIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Move the repository configs, caches and logs to another directory
+///
+/// Stops all servers, copies the config directory to `new_path` and verifies
+/// the copy, then switches to it and restarts the servers that were running.
+/// Servers can't be started while the files are copied. The old files are
+/// only removed once everything succeeded; on failure the copy is removed and
+/// the servers restart from the old location. The settings file stays in the
+/// default config directory and records the new location.
+/// Returns the new config directory.
+#[tauri::command]
+pub async fn relocate_config_dir(
+    settings: State<'_, SettingsState>,
+    manager: State<'_, ServerManagerState>,
+    new_path: String,
+) -> Result<String> {
+    let settings = settings.inner().clone();
+    let manager = manager.inner().clone();
+
+    tokio::task::spawn_blocking(move || relocate(&settings, &manager, Path::new(&new_path)))
+        .await
+        .map_err(|e| KopiaError::operation_failed("relocate config directory", e.to_string()))?
+}
+
+fn relocate(
+    settings: &SettingsState,
+    manager_state: &ServerManagerState,
+    new_dir: &Path,
+) -> Result<String> {
+    let default_dir = crate::commands::kopia::get_default_config_dir()?;
+    let old_dir = std::path::PathBuf::from(manager_state.lock().unwrap().begin_relocation()?);
+    if let Err(e) = check_relocation_target(&old_dir, new_dir) {
+        manager_state.lock().unwrap().end_relocation();
+        return Err(e);
+    }
+
+    let new_dir_str = new_dir.to_string_lossy().to_string();
+    let old_dir_str = old_dir.to_string_lossy().to_string();
+    let previous_setting = settings.lock().unwrap().get().config_dir;
+    let new_setting = (new_dir_str != default_dir).then(|| new_dir_str.clone());
+
+    // Copy without holding the lock so other commands aren't blocked; servers
+    // can't be started until `end_relocation`
+    let running = {
+        let mut manager = manager_state.lock().unwrap();
+        let running = manager.running_local_repositories();
+        if let Err(e) = manager.stop_all() {
+            manager.end_relocation();
+            return Err(e);
+        }
+        running
+    };
+
+    let copied = copy_config_dir(&old_dir, new_dir);
+
+    let mut manager = manager_state.lock().unwrap();
+    manager.end_relocation();
+    let entries = match copied {
+        Ok(entries) => entries,
+        Err(e) => {
+            log::error!("Copying the config directory failed, rolling back: {}", e);
+            restart_servers(&mut manager, &running);
+            return Err(e);
+        }
+    };
+
+    let switched = settings
+        .lock()
+        .unwrap()
+        .update(|s| s.config_dir = new_setting)
+        .and_then(|_| manager.set_config_dir(&new_dir_str));
+    let failures = match switched {
+        Ok(()) => restart_servers(&mut manager, &running),
+        Err(e) => vec![e.to_string()],
+    };
+
+    if !failures.is_empty() {
+        log::error!("Switching to '{}' failed, rolling back", new_dir_str);
+        if let Err(e) = manager.stop_all() {
+            log::warn!("Failed to stop servers during rollback: {}", e);
+        }
+        if let Err(e) = manager.set_config_dir(&old_dir_str) {
+            log::error!("Failed to restore config directory: {}", e);
+        }
+        if let Err(e) = settings
+            .lock()
+            .unwrap()
+            .update(|s| s.config_dir = previous_setting)
+        {
+            log::error!("Failed to restore config directory setting: {}", e);
+        }
+        remove_entries(new_dir, &entries);
+        restart_servers(&mut manager, &running);
+
+        return Err(KopiaError::operation_failed_with_details(
+            "relocate config directory",
+            "Servers failed to start from the new location; the move was rolled back",
+            failures.join(", "),
+        ));
+    }
+
+    remove_entries(&old_dir, &entries);
+    log::info!(
+        "Moved config directory from {} to {}",
+        old_dir_str,
+        new_dir_str
+    );
+    Ok(new_dir_str)
+}
+
+/// Validate and prepare `new_dir`, checking it has room for `old_dir`
+fn check_relocation_target(old_dir: &Path, new_dir: &Path) -> Result<()> {
+    validate_relocation_target(old_dir, new_dir)?;
+    std::fs::create_dir_all(new_dir).map_err(|e| {
+        KopiaError::invalid_input("newPath", format!("Cannot create directory: {}", e))
+    })?;
+    check_writable(new_dir)?;
+
+    let required = path_size(old_dir).unwrap_or(0) + RELOCATE_SPACE_MARGIN_BYTES;
+    if let Some(available) = available_space(new_dir) {
+        if available < required {
+            return Err(KopiaError::invalid_input(
+                "newPath",
+                format!(
+                    "Not enough free space: {} bytes needed, {} available",
+                    required, available
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check `new_dir` can receive the contents of `old_dir`
+///
+/// It must be absolute, outside `old_dir` (and vice versa), and either missing
+/// or empty apart from a settings file.
+pub(crate) fn validate_relocation_target(old_dir: &Path, new_dir: &Path) -> Result<()> {
+    if !new_dir.is_absolute() {
+        return Err(KopiaError::invalid_input(
+            "newPath",
+            "Path must be absolute",
+        ));
+    }
+
+    // Compare canonical paths where they exist so symlinks and `..` can't hide overlap
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let (old_dir, new_dir_canonical) = (canonical(old_dir), canonical(new_dir));
+    if new_dir_canonical.starts_with(&old_dir) || old_dir.starts_with(&new_dir_canonical) {
+        return Err(KopiaError::invalid_input(
+            "newPath",
+            "The new location can't be inside the current config directory or contain it",
+        ));
+    }
+
+    match std::fs::read_dir(new_dir) {
+        Ok(entries) => {
+            let occupied = entries
+                .flatten()
+                .any(|e| e.file_name() != crate::settings::SETTINGS_FILE_NAME);
+            if occupied {
+                return Err(KopiaError::invalid_input(
+                    "newPath",
+                    "The new location must be an empty directory",
+                ));
+            }
+            Ok(())
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(KopiaError::invalid_input(
+            "newPath",
+            format!("Cannot read directory: {}", e),
+        )),
+    }
+}
+
+/// Check files can be created in `dir`
+fn check_writable(dir: &Path) -> Result<()> {
+    let probe = dir.join(".kopia-desktop-write-test");
+    std::fs::write(&probe, b"").map_err(|e| {
+        KopiaError::invalid_input("newPath", format!("Directory is not writable: {}", e))
+    })?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Copy the top-level entries of `from` into `to` and verify the copy
+///
+/// The settings file and its temporary file stay behind (and are logged);
+/// every other entry is copied. Relative cache directories in repository
+/// configs are made absolute, since Kopia resolves them against the config
+/// file's directory. Returns the names of the copied entries; on failure
+/// whatever was copied is removed again.
+pub(crate) fn copy_config_dir(from: &Path, to: &Path) -> Result<Vec<std::ffi::OsString>> {
+    let copy_failed = |e: std::io::Error| {
+        KopiaError::operation_failed_with_details(
+            "relocate config directory",
+            "Failed to copy the config directory",
+            e.to_string(),
+        )
+    };
+
+    let (mut names, left_behind): (Vec<std::ffi::OsString>, Vec<std::ffi::OsString>) =
+        std::fs::read_dir(from)
+            .map_err(copy_failed)?
+            .flatten()
+            .map(|e| e.file_name())
+            .partition(|name| {
+                name != crate::settings::SETTINGS_FILE_NAME
+                    && name != crate::settings::SETTINGS_TEMP_FILE_NAME
+            });
+    names.sort();
+    for name in &left_behind {
+        log::info!(
+            "Leaving {} in {} (app settings don't move with the config directory)",
+            name.to_string_lossy(),
+            from.display()
+        );
+    }
+
+    let mut copied = Vec::new();
+    for name in names {
+        let (source, target) = (from.join(&name), to.join(&name));
+        copied.push(name.clone());
+
+        let config = name
+            .to_str()
+            .filter(|n| n.ends_with(".config") && source.is_file())
+            .and_then(|_| std::fs::read_to_string(&source).ok())
+            .and_then(|contents| absolute_cache_directory(&contents, from));
+
+        let result = match config {
+            Some(contents) => std::fs::write(&target, &contents)
+                .and_then(|_| verify_file(&target, contents.as_bytes())),
+            None => copy_tree(&source, &target),
+        };
+        if let Err(e) = result {
+            remove_entries(to, &copied);
+            return Err(copy_failed(e));
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Copy a file or directory tree, checking each copied file's contents
+fn copy_tree(source: &Path, target: &Path) -> std::io::Result<()> {
+    if source.is_dir() {
+        std::fs::create_dir_all(target)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_tree(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        return Ok(());
+    }
+
+    std::fs::copy(source, target)?;
+    verify_file(target, std::fs::File::open(source)?)
+}
+
+/// Chunk size for comparing copied files
+const VERIFY_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Check a copied file has the expected contents
+///
+/// Compares in chunks, so large files (e.g. caches) aren't read into memory.
+pub(crate) fn verify_file(path: &Path, expected: impl std::io::Read) -> std::io::Result<()> {
+    let differs = || {
+        std::io::Error::other(format!(
+            "'{}' differs from the original after copying",
+            path.display()
+        ))
+    };
+
+    let mut actual = std::fs::File::open(path)?;
+    let mut expected = expected;
+    let mut actual_buf = vec![0u8; VERIFY_CHUNK_BYTES];
+    let mut expected_buf = vec![0u8; VERIFY_CHUNK_BYTES];
+    loop {
+        let actual_len = read_chunk(&mut actual, &mut actual_buf)?;
+        let expected_len = read_chunk(&mut expected, &mut expected_buf)?;
+        if actual_buf[..actual_len] != expected_buf[..expected_len] {
+            return Err(differs());
+        }
+        if actual_len == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Fill `buf` as far as possible, returning the number of bytes read
+///
+/// Only returns less than `buf.len()` at the end of the input.
+fn read_chunk(reader: &mut impl std::io::Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Remove the named top-level entries of `dir`, logging failures
+fn remove_entries(dir: &Path, names: &[std::ffi::OsString]) {
+    for name in names {
+        let path = dir.join(name);
+        let result = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to remove '{}': {}", path.display(), e);
+        }
+    }
+}
+
+/// Start the servers of `repo_ids`, returning the failures
+fn restart_servers(
+    manager: &mut crate::server_manager::ServerManager,
+    repo_ids: &[String],
+) -> Vec<String> {
+    repo_ids
+        .iter()
+        .filter_map(|repo_id| {
+            manager
+                .start_server(repo_id)
+                .err()
+                .map(|e| format!("{}: {}", repo_id, e))
+        })
+        .collect()
+}
+
+/// Rewrite a relative `caching.cacheDirectory` of a repository config as absolute
+///
+/// Returns the updated config, or None if there's nothing to change.
+pub(crate) fn absolute_cache_directory(config: &str, config_dir: &Path) -> Option<String> {
+    let mut value: serde_json::Value = serde_json::from_str(config).ok()?;
+    let cache_dir = value.pointer_mut("/caching/cacheDirectory")?;
+    let relative = cache_dir.as_str().filter(|d| Path::new(d).is_relative())?;
+
+    *cache_dir = serde_json::Value::String(config_dir.join(relative).to_string_lossy().to_string());
+    serde_json::to_string_pretty(&value).ok()
+}
+
+/// Free bytes on the volume holding `path`, if it can be determined
+#[cfg(unix)]
+fn available_space(path: &Path) -> Option<u64> {
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .ok()?;
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(windows)]
+fn available_space(path: &Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0u64;
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut available,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    (ok != 0).then_some(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn available_space(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Parse the available space from POSIX `df -Pk` output
+#[cfg(any(unix, test))]
+pub(crate) fn parse_df_available(output: &str) -> Option<u64> {
+    let fields: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    // Filesystem, 1024-blocks, Used, Available, Capacity, Mounted on; the
+    // filesystem and mount point may contain spaces, so go by the Capacity column
+    let capacity = fields.iter().skip(1).position(|f| f.ends_with('%'))? + 1;
+    let kib: u64 = fields.get(capacity.checked_sub(1)?)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// Windows Volume Shadow Copy (VSS) availability
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VssStatus {
+    pub available: bool,
+    /// Why VSS can't be used, if it can't
+    pub reason: Option<String>,
+    /// Whether the app (and the Kopia servers it starts) runs elevated
+    pub elevated: bool,
+}
+
+/// Check whether VSS snapshots can be used by Kopia
+///
+/// VSS needs the Volume Shadow Copy service and administrator rights; without
+/// them Kopia silently falls back to reading live files. Always unavailable on
+/// other platforms.
+#[tauri::command]
+pub async fn vss_status() -> Result<VssStatus> {
+    #[cfg(windows)]
+    {
+        tokio::task::spawn_blocking(|| vss_availability(vss_service_check(), is_process_elevated()))
+            .await
+            .map_err(|e| KopiaError::operation_failed("check VSS status", e.to_string()))
+    }
+
+    #[cfg(not(windows))]
+    {
+        Ok(VssStatus {
+            available: false,
+            reason: Some("Volume Shadow Copy is only available on Windows".to_string()),
+            elevated: false,
+        })
+    }
+}
+
+/// Combine the VSS service check and elevation into a status
+#[cfg(any(windows, test))]
+pub(crate) fn vss_availability(
+    service: std::result::Result<(), String>,
+    elevated: bool,
+) -> VssStatus {
+    let reason = match service {
+        Err(reason) => Some(reason),
+        Ok(()) if !elevated => Some(
+            "Kopia Desktop is not running as administrator, which VSS requires. \
+             Restart it as administrator or use the Windows service."
+                .to_string(),
+        ),
+        Ok(()) => None,
+    };
+
+    VssStatus {
+        available: reason.is_none(),
+        reason,
+        elevated,
+    }
+}
+
+/// Check that the Volume Shadow Copy service is installed and not disabled
+#[cfg(windows)]
+fn vss_service_check() -> std::result::Result<(), String> {
+    use windows_service::service::{ServiceAccess, ServiceStartType};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+
+    let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT)
+        .map_err(|e| format!("Failed to open service manager: {}", e))?;
+
+    let service = manager
+        .open_service("VSS", ServiceAccess::QUERY_CONFIG)
+        .map_err(|_| "Volume Shadow Copy service is not installed".to_string())?;
+
+    let config = service
+        .query_config()
+        .map_err(|e| format!("Failed to query Volume Shadow Copy service: {}", e))?;
+
+    if config.start_type == ServiceStartType::Disabled {
+        return Err("Volume Shadow Copy service is disabled".to_string());
+    }
+    Ok(())
+}
+
+/// Check whether the current process token is elevated
+#[cfg(windows)]
+fn is_process_elevated() -> bool {
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    let mut token: HANDLE = std::ptr::null_mut();
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) } == 0 {
+        return false;
+    }
+
+    let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+    let mut size = 0u32;
+    let ok = unsafe {
+        GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut TOKEN_ELEVATION as *mut std::ffi::c_void,
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut size,
+        )
+    };
+    unsafe { CloseHandle(token) };
+
+    ok != 0 && elevation.TokenIsElevated != 0
+}
+
+/// Whether a restore option takes effect on the target filesystem
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreOptionSupport {
+    /// Option name as in `FilesystemOutput`
+    pub option: String,
+    pub supported: bool,
+    /// Why the option is ignored, or why support is uncertain
+    pub reason: Option<String>,
+}
+
+/// FAT variants, which have no sparse files, owners or Unix permissions
+const FAT_FILESYSTEMS: &[&str] = &["vfat", "fat", "fat16", "fat32", "msdos", "msdosfs", "exfat"];
+/// Filesystems known to support sparse files
+const SPARSE_FILESYSTEMS: &[&str] = &[
+    "ext2", "ext3", "ext4", "xfs", "btrfs", "zfs", "f2fs", "bcachefs", "jfs", "reiserfs", "tmpfs",
+    "ntfs", "ntfs3", "refs", "apfs",
+];
+/// Network filesystems, where renames aren't guaranteed to be atomic
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "sshfs",
+    "fuse.sshfs",
+    "davfs",
+    "webdav",
+    "afpfs",
+];
+
+/// Check which restore options take effect on the target's filesystem
+///
+/// Kopia silently ignores options the filesystem can't honor, e.g. sparse files
+/// on FAT. Covers `writeSparseFiles` and `writeFilesAtomically` when enabled,
+/// and restoring owners and permissions unless `skipOwners`/`skipPermissions`
+/// are set. The target doesn't need to exist yet; its nearest existing parent
+/// is checked. Options on filesystems that can't be identified are reported as
+/// supported with a reason.
+#[tauri::command]
+pub async fn validate_restore_options(
+    target_path: String,
+    options: FilesystemOutput,
+) -> Result<Vec<RestoreOptionSupport>> {
+    let fs_type = tokio::task::spawn_blocking(move || filesystem_type(Path::new(&target_path)))
+        .await
+        .map_err(|e| KopiaError::operation_failed("validate restore options", e.to_string()))?;
+
+    Ok(restore_option_support(fs_type.as_deref(), &options))
+}
+
+/// Check restore options against a filesystem type (None if unknown)
+pub(crate) fn restore_option_support(
+    fs_type: Option<&str>,
+    options: &FilesystemOutput,
+) -> Vec<RestoreOptionSupport> {
+    let fs = fs_type.map(str::to_lowercase);
+    let is_one_of = |list: &[&str]| fs.as_deref().is_some_and(|fs| list.contains(&fs));
+    let name = fs_type.unwrap_or_default();
+
+    let entry = |option: &str, supported: bool, reason: Option<String>| RestoreOptionSupport {
+        option: option.to_string(),
+        supported,
+        reason,
+    };
+    let known_or_unknown = |option: &str| match fs_type {
+        Some(_) if is_one_of(SPARSE_FILESYSTEMS) || is_one_of(NETWORK_FILESYSTEMS) => {
+            entry(option, true, None)
+        }
+        Some(fs) => entry(option, true, Some(format!("Support on {} is unknown", fs))),
+        None => entry(
+            option,
+            true,
+            Some("The target filesystem couldn't be determined".to_string()),
+        ),
+    };
+
+    let mut results = Vec::new();
+
+    if options.write_sparse_files == Some(true) {
+        results.push(if is_one_of(SPARSE_FILESYSTEMS) {
+            entry("writeSparseFiles", true, None)
+        } else if is_one_of(FAT_FILESYSTEMS) || fs.as_deref() == Some("hfs") {
+            entry(
+                "writeSparseFiles",
+                false,
+                Some(format!(
+                    "{} has no sparse files, so empty regions are written out in full",
+                    name
+                )),
+            )
+        } else {
+            known_or_unknown("writeSparseFiles")
+        });
+    }
+
+    if options.write_files_atomically == Some(true) {
+        results.push(if is_one_of(NETWORK_FILESYSTEMS) {
+            entry(
+                "writeFilesAtomically",
+                false,
+                Some(format!("Renames on {} shares may not be atomic", name)),
+            )
+        } else if is_one_of(FAT_FILESYSTEMS) {
+            entry(
+                "writeFilesAtomically",
+                false,
+                Some(format!(
+                    "{} isn't journaled, so a crash can still leave partial files",
+                    name
+                )),
+            )
+        } else {
+            known_or_unknown("writeFilesAtomically")
+        });
+    }
+
+    for (option, skipped, what) in [
+        ("skipOwners", options.skip_owners, "file owners"),
+        (
+            "skipPermissions",
+            options.skip_permissions,
+            "Unix permissions",
+        ),
+    ] {
+        if skipped == Some(true) {
+            continue;
+        }
+        results.push(if is_one_of(FAT_FILESYSTEMS) {
+            entry(
+                option,
+                false,
+                Some(format!("{} doesn't store {}", name, what)),
+            )
+        } else {
+            known_or_unknown(option)
+        });
+    }
+
+    results
+}
+
+/// Filesystem type of the volume holding `path` (or its nearest existing parent)
+fn filesystem_type(path: &Path) -> Option<String> {
+    let path = path.ancestors().find_map(|p| p.canonicalize().ok())?;
+    detect_filesystem_type(&path)
+}
+
+#[cfg(target_os = "linux")]
+fn detect_filesystem_type(path: &Path) -> Option<String> {
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    mount_filesystem(&parse_proc_mounts(&mounts), path)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_filesystem_type(path: &Path) -> Option<String> {
+    let output = std::process::Command::new("mount").output().ok()?;
+    mount_filesystem(
+        &parse_mount_output(&String::from_utf8_lossy(&output.stdout)),
+        path,
+    )
+}
+
+#[cfg(windows)]
+fn detect_filesystem_type(path: &Path) -> Option<String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::{GetVolumeInformationW, GetVolumePathNameW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut volume = [0u16; 261];
+    if unsafe { GetVolumePathNameW(wide.as_ptr(), volume.as_mut_ptr(), volume.len() as u32) } == 0 {
+        return None;
+    }
+
+    let mut fs_name = [0u16; 261];
+    let ok = unsafe {
+        GetVolumeInformationW(
+            volume.as_ptr(),
+            std::ptr::null_mut(),
+            0,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            fs_name.as_mut_ptr(),
+            fs_name.len() as u32,
+        )
+    };
+    if ok == 0 {
+        return None;
+    }
+
+    let len = fs_name
+        .iter()
+        .position(|&c| c == 0)
+        .unwrap_or(fs_name.len());
+    Some(String::from_utf16_lossy(&fs_name[..len]))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn detect_filesystem_type(_path: &Path) -> Option<String> {
+    None
+}
+
+/// Parse `/proc/self/mounts` into (mount point, filesystem type) pairs
+#[cfg(any(target_os = "linux", test))]
+pub(crate) fn parse_proc_mounts(mounts: &str) -> Vec<(String, String)> {
+    // Whitespace and backslashes in mount points are octal-escaped (e.g. `\040`)
+    fn unescape(field: &str) -> String {
+        let mut result = String::new();
+        let mut rest = field;
+        while let Some(pos) = rest.find('\\') {
+            result.push_str(&rest[..pos]);
+            let code = rest.get(pos + 1..pos + 4);
+            match code.and_then(|c| u8::from_str_radix(c, 8).ok()) {
+                Some(byte) => {
+                    result.push(byte as char);
+                    rest = &rest[pos + 4..];
+                }
+                None => {
+                    result.push('\\');
+                    rest = &rest[pos + 1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?;
+            let fs_type = fields.next()?;
+            Some((unescape(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// Parse macOS `mount` output (`/dev/disk3s1 on / (apfs, local, journaled)`)
+#[cfg(any(target_os = "macos", test))]
+pub(crate) fn parse_mount_output(output: &str) -> Vec<(String, String)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            Some((mount_point.to_string(), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// Filesystem type of the most specific mount containing `path`
+#[cfg(any(target_os = "linux", target_os = "macos", test))]
+pub(crate) fn mount_filesystem(mounts: &[(String, String)], path: &Path) -> Option<String> {
+    mounts
+        .iter()
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| Path::new(mount_point).components().count())
+        .map(|(_, fs_type)| fs_type.clone())
+}
+
+/// Prefix of the temporary directories Kopia mounts snapshots on
+const KOPIA_MOUNT_DIR_PREFIX: &str = "kopia-mount";
+
+/// Find snapshot mounts left behind by a previous crash
+///
+/// Kopia mounts snapshots on `kopia-mount*` directories in the temp directory.
+/// Such a directory is orphaned if no running server reports a mount there:
+/// either it's still mounted (often unresponsive after its server died) or it's
+/// an empty leftover directory.
+#[tauri::command]
+pub async fn find_orphaned_mounts(manager: State<'_, ServerManagerState>) -> Result<Vec<String>> {
+    let active = crate::commands::kopia::active_mount_paths(&manager).await?;
+    let temp_dir = std::env::temp_dir();
+
+    let mut orphaned = orphaned_mount_points(&temp_dir, &system_mounts(), &active);
+    for dir in leftover_mount_dirs(&temp_dir) {
+        if !orphaned.contains(&dir) && !active.contains(&dir) {
+            orphaned.push(dir);
+        }
+    }
+    orphaned.sort();
+    Ok(orphaned)
+}
+
+/// Force-unmount an orphaned mount and remove its directory
+///
+/// Only paths currently reported by `find_orphaned_mounts` are accepted.
+#[tauri::command]
+pub async fn cleanup_orphaned_mount(
+    manager: State<'_, ServerManagerState>,
+    path: String,
+) -> Result<()> {
+    let orphaned = find_orphaned_mounts(manager).await?;
+    if !orphaned.contains(&path) {
+        return Err(KopiaError::invalid_input(
+            "path",
+            format!("'{}' isn't an orphaned Kopia mount", path),
+        ));
+    }
+
+    let mounted = system_mounts()
+        .iter()
+        .any(|(mount_point, _)| *mount_point == path);
+    if mounted {
+        force_unmount(&path)?;
+    }
+
+    std::fs::remove_dir(&path).map_err(|e| {
+        KopiaError::operation_failed(
+            "cleanup orphaned mount",
+            format!("Failed to remove '{}': {}", path, e),
+        )
+    })
+}
+
+/// Mount points of Kopia mount directories in `temp_dir` that no server owns
+pub(crate) fn orphaned_mount_points(
+    temp_dir: &Path,
+    mounts: &[(String, String)],
+    active: &[String],
+) -> Vec<String> {
+    // The mount table shows resolved paths (e.g. /private/var/... on macOS)
+    let resolved = temp_dir.canonicalize().ok();
+
+    mounts
+        .iter()
+        .map(|(mount_point, _)| mount_point)
+        .filter(|mount_point| {
+            let path = Path::new(mount_point.as_str());
+            let in_temp_dir = path
+                .parent()
+                .is_some_and(|parent| parent == temp_dir || Some(parent) == resolved.as_deref());
+            let is_kopia_mount = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(KOPIA_MOUNT_DIR_PREFIX));
+            in_temp_dir && is_kopia_mount && !active.contains(mount_point)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Empty `kopia-mount*` directories in `temp_dir`
+///
+/// A mounted directory that no longer responds can't be read and is skipped;
+/// those are found through the mount table instead.
+pub(crate) fn leftover_mount_dirs(temp_dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(temp_dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with(KOPIA_MOUNT_DIR_PREFIX))
+                && entry.file_type().is_ok_and(|t| t.is_dir())
+                && std::fs::read_dir(entry.path()).is_ok_and(|mut d| d.next().is_none())
+        })
+        .filter_map(|entry| entry.path().to_str().map(String::from))
+        .collect()
+}
+
+/// Current mount table as (mount point, filesystem type) pairs
+#[cfg(target_os = "linux")]
+fn system_mounts() -> Vec<(String, String)> {
+    std::fs::read_to_string("/proc/self/mounts")
+        .map(|mounts| parse_proc_mounts(&mounts))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn system_mounts() -> Vec<(String, String)> {
+    std::process::Command::new("mount")
+        .output()
+        .map(|output| parse_mount_output(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn system_mounts() -> Vec<(String, String)> {
+    Vec::new()
+}
+
+/// Unmount commands tried in order until one succeeds
+#[cfg(target_os = "linux")]
+const FORCE_UNMOUNT_COMMANDS: &[(&str, &[&str])] = &[
+    ("fusermount", &["-u", "-z"]),
+    ("fusermount3", &["-u", "-z"]),
+    ("umount", &["-l"]),
+];
+
+#[cfg(target_os = "macos")]
+const FORCE_UNMOUNT_COMMANDS: &[(&str, &[&str])] =
+    &[("umount", &["-f"]), ("diskutil", &["unmount", "force"])];
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+const FORCE_UNMOUNT_COMMANDS: &[(&str, &[&str])] = &[];
+
+/// Force-unmount `path` with the platform's unmount tools
+fn force_unmount(path: &str) -> Result<()> {
+    let mut failures = Vec::new();
+
+    for (program, args) in FORCE_UNMOUNT_COMMANDS {
+        match std::process::Command::new(program)
+            .args(*args)
+            .arg(path)
+            .output()
+        {
+            Ok(output) if output.status.success() => return Ok(()),
+            Ok(output) => failures.push(format!(
+                "{}: {}",
+                program,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Err(e) => failures.push(format!("{}: {}", program, e)),
+        }
+    }
+
+    Err(KopiaError::operation_failed_with_details(
+        "cleanup orphaned mount",
+        format!("Failed to unmount '{}'", path),
+        failures.join("\n"),
+    ))
+}
+
 /// Get current username and hostname from Kopia server
 ///
 /// When the Kopia server is running, this queries the `/api/v1/current-user` endpoint
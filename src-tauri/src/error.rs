@@ -20,6 +20,16 @@ use thiserror::Error;
 /// - HttpRequestFailed: Policy load fallback
 /// - ResponseParseError: Policy load fallback
 /// - NotFound: Policy load fallback + general 404
+/// - InvalidInput: Highlights the offending form field
+/// - RepositoryUpgradeRequired: Prompts for a repository upgrade or Kopia update
+/// - RepositoryLocked: Shows "another client is using this repository"
+/// - RepositoryBusy: Shows "repository is busy, try again shortly"
+/// - PathNotFound: Deep link into a snapshot points to a missing directory
+/// - ConfigLocked: Offers to stop the Kopia process holding the config
+/// - BinaryIncompatible: Explains that the Kopia binary is built for another platform
+/// - NotificationTestFailed: Points at the SMTP host, credentials or recipients
+/// - Timeout: Offers to try again with a longer timeout
+/// - ControlApiUnavailable: Hides server control actions (e.g. flush)
 ///
 /// All other errors use OperationFailed with a descriptive message.
 #[derive(Debug, Clone, Error, Serialize, Deserialize, PartialEq, Eq)]
@@ -71,6 +81,77 @@ pub enum KopiaError {
     #[error("{resource} not found")]
     NotFound { resource: String },
 
+    /// Repository format isn't supported by the bundled Kopia or needs an upgrade
+    #[error("Repository upgrade required: {message}")]
+    RepositoryUpgradeRequired {
+        /// Format version supported by the bundled Kopia, if reported
+        #[serde(skip_serializing_if = "Option::is_none")]
+        current_version: Option<String>,
+        /// Format version the repository requires, if reported
+        #[serde(skip_serializing_if = "Option::is_none")]
+        required_version: Option<String>,
+        message: String,
+    },
+
+    /// Repository lock is held by another client
+    #[error("Repository is locked by another client")]
+    RepositoryLocked {
+        /// Lock holder (e.g. `user@host`), if reported
+        #[serde(skip_serializing_if = "Option::is_none")]
+        holder: Option<String>,
+    },
+
+    /// Repository stayed busy (e.g. flushing) through all retries
+    #[error("Repository is busy: {message}")]
+    RepositoryBusy { message: String },
+
+    /// Path doesn't exist in a snapshot
+    #[error("Path not found in snapshot: {path}")]
+    PathNotFound {
+        /// Path up to and including the first missing component
+        path: String,
+    },
+
+    /// Config file is locked by another Kopia process (server start)
+    #[error("Config file is locked by another process: {config_file}")]
+    ConfigLocked {
+        config_file: String,
+        /// Process holding the lock, if reported
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pid: Option<u32>,
+    },
+
+    /// Kopia binary's format or CPU architecture doesn't match this system
+    #[error("Kopia binary {path} is built for {found}, but this system needs {expected}")]
+    BinaryIncompatible {
+        path: String,
+        /// Host format and architecture, e.g. "ELF aarch64"
+        expected: String,
+        /// Binary format and architecture, e.g. "ELF x86_64"
+        found: String,
+    },
+
+    /// Sending a test notification failed
+    #[error("Test notification failed: {message}")]
+    NotificationTestFailed {
+        /// Where an email test failed ("connect", "auth" or "send"), if recognized
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stage: Option<String>,
+        message: String,
+    },
+
+    /// Waiting for a server-side operation took longer than allowed
+    #[error("{operation} timed out after {seconds}s")]
+    Timeout { operation: String, seconds: u64 },
+
+    /// Server has no control API (older Kopia or an external server)
+    #[error("Server control API is unavailable: {reason}")]
+    ControlApiUnavailable { reason: String },
+
+    /// Invalid command input, rejected before calling Kopia
+    #[error("Invalid {field}: {message}")]
+    InvalidInput { field: String, message: String },
+
     // ============================================================================
     // Generic Fallback (replaces all unused specific error codes)
     // ============================================================================
@@ -122,6 +203,28 @@ impl KopiaError {
             Err(_) => (None, body.to_string()),
         };
 
+        // Format/upgrade errors are reported with generic codes, so check the message
+        if code.as_deref() == Some("UPGRADE_REQUIRED") || is_upgrade_required_message(&message) {
+            let (current_version, required_version) = parse_upgrade_versions(&message);
+            return KopiaError::RepositoryUpgradeRequired {
+                current_version,
+                required_version,
+                message,
+            };
+        }
+
+        // Lock contention is also only recognizable from the message (or 423 Locked)
+        if status_code == 423 || is_lock_contention_message(&message) {
+            return KopiaError::RepositoryLocked {
+                holder: parse_lock_holder(&message),
+            };
+        }
+
+        // So is a transient busy state; callers may retry it (see `retry_while_busy`)
+        if is_busy_message(&message) {
+            return KopiaError::RepositoryBusy { message };
+        }
+
         // Handle specific error codes that drive different UI behaviors
         if let Some(ref error_code) = code {
             match error_code.as_str() {
@@ -177,6 +280,14 @@ impl KopiaError {
         }
     }
 
+    /// Create invalid input error for a field
+    pub fn invalid_input(field: impl Into<String>, message: impl Into<String>) -> Self {
+        KopiaError::InvalidInput {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
     /// Create operation failed with details
     pub fn operation_failed_with_details(
         operation: impl Into<String>,
@@ -193,6 +304,110 @@ impl KopiaError {
     }
 }
 
+/// Check whether an error message says the repository format is unsupported
+/// or the repository needs an upgrade
+fn is_upgrade_required_message(message: &str) -> bool {
+    let lower = message.to_lowercase();
+
+    (lower.contains("upgrade")
+        && (lower.contains("required")
+            || lower.contains("in progress")
+            || lower.contains("must be upgraded")
+            || lower.contains("needs to be upgraded")))
+        || (lower.contains("format version")
+            && (lower.contains("not supported") || lower.contains("unsupported")))
+        || lower.contains("newer version of kopia")
+}
+
+/// Check whether an error message reports a lock held by another client
+fn is_lock_contention_message(message: &str) -> bool {
+    let lower = message.to_lowercase();
+
+    lower.contains("lock")
+        && (lower.contains("held by")
+            || lower.contains("locked by")
+            || lower.contains("already locked")
+            || lower.contains("unable to acquire")
+            || lower.contains("failed to acquire")
+            || lower.contains("could not acquire"))
+}
+
+/// Check whether an error message reports a transient busy state
+fn is_busy_message(message: &str) -> bool {
+    let lower = message.to_lowercase();
+
+    [
+        "flush in progress",
+        "flush is in progress",
+        "repository is busy",
+        "server is busy",
+    ]
+    .iter()
+    .any(|marker| lower.contains(marker))
+}
+
+/// Byte offset of an ASCII `needle` in `haystack`, ignoring ASCII case
+///
+/// Unlike searching `haystack.to_lowercase()`, the offset is valid for
+/// slicing `haystack` itself: lowercasing non-ASCII text can change its byte
+/// length, e.g. "İ" (2 bytes) lowercases to "i̇" (3 bytes).
+pub(crate) fn find_ignore_ascii_case(haystack: &str, needle: &str) -> Option<usize> {
+    if needle.is_empty() {
+        return Some(0);
+    }
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Extract the lock holder from a lock contention message
+///
+/// E.g. "unable to acquire lock: held by alice@laptop (pid 42)" → "alice@laptop"
+fn parse_lock_holder(message: &str) -> Option<String> {
+    ["held by", "locked by", "owner:"]
+        .iter()
+        .find_map(|marker| {
+            let pos = find_ignore_ascii_case(message, marker)? + marker.len();
+            let holder: String = message[pos..]
+                .trim_start()
+                .chars()
+                .take_while(|c| !c.is_whitespace() && !matches!(c, ',' | ';' | '(' | ')'))
+                .collect();
+            let holder = holder.trim_matches(|c| matches!(c, '"' | '\'' | '.' | ':'));
+            (!holder.is_empty()).then(|| holder.to_string())
+        })
+}
+
+/// Extract (supported, required) format versions from an upgrade error message
+///
+/// E.g. "repository format version 4 is not supported (max 3)" → (3, 4)
+fn parse_upgrade_versions(message: &str) -> (Option<String>, Option<String>) {
+    let lower = message.to_lowercase();
+
+    let required =
+        number_after(&lower, "format version").or_else(|| number_after(&lower, "version"));
+    let current = ["max", "maximum", "<=", "up to", "supported:"]
+        .iter()
+        .find_map(|marker| number_after(&lower, marker));
+
+    (current, required)
+}
+
+/// Get the version number following `marker`, e.g. "version 3" or "version v1.2"
+fn number_after(text: &str, marker: &str) -> Option<String> {
+    text.match_indices(marker).find_map(|(pos, _)| {
+        let rest = text[pos + marker.len()..].trim_start();
+        let rest = rest.strip_prefix('v').unwrap_or(rest);
+        let number: String = rest
+            .chars()
+            .take_while(|c| c.is_ascii_digit() || *c == '.')
+            .collect();
+        let number = number.trim_end_matches('.');
+        (!number.is_empty()).then(|| number.to_string())
+    })
+}
+
 /// Extension trait for Result<T, reqwest::Error> to convert to Result<T, KopiaError>
 pub trait HttpResultExt<T> {
     fn map_http_error(self, operation: &str) -> Result<T>;
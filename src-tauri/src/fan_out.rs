@@ -0,0 +1,134 @@
+//! Concurrent fan-out for aggregate commands
+//!
+//! Aggregate commands (e.g. `sources_summary`) issue one request per source and
+//! combine the results. A single stalled request must not hang the whole
+//! command, so every sub-request runs concurrently with its own timeout.
+//! Sub-requests that time out or fail are reported by key, and whatever did
+//! complete is returned as a partial result.
+//!
+//! The timeout defaults to [`DEFAULT_FAN_OUT_TIMEOUT_SECS`] and can be changed
+//! with the `fanOutTimeoutSecs` setting or per call.
+
+use crate::error::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// Default per-sub-request timeout
+pub const DEFAULT_FAN_OUT_TIMEOUT_SECS: u64 = 15;
+
+/// Upper bound for configurable fan-out timeouts
+pub const MAX_FAN_OUT_TIMEOUT_SECS: u64 = 600;
+
+/// Outcome of a fan-out, in the order the sub-requests were given
+#[derive(Debug)]
+pub struct FanOutResult<T> {
+    /// Completed sub-requests as (key, result)
+    pub completed: Vec<(String, T)>,
+    /// Keys of sub-requests that didn't finish in time
+    pub timed_out: Vec<String>,
+    /// Keys of sub-requests that failed, with the error message
+    pub failed: Vec<(String, String)>,
+}
+
+impl<T> FanOutResult<T> {
+    /// Whether any sub-request is missing from the results
+    pub fn is_partial(&self) -> bool {
+        !self.timed_out.is_empty() || !self.failed.is_empty()
+    }
+}
+
+enum Outcome<T> {
+    Completed(T),
+    TimedOut,
+    Failed(String),
+}
+
+/// Run keyed sub-requests concurrently, each limited to `timeout`
+pub async fn fan_out<T, F>(requests: Vec<(String, F)>, timeout: Duration) -> FanOutResult<T>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T>> + Send + 'static,
+{
+    let max_concurrent = requests.len().max(1);
+    fan_out_bounded(requests, timeout, max_concurrent).await
+}
+
+/// Like [`fan_out`], running at most `max_concurrent` sub-requests at a time
+///
+/// Each sub-request's timeout starts when it begins running, not while it
+/// waits for a slot.
+pub async fn fan_out_bounded<T, F>(
+    requests: Vec<(String, F)>,
+    timeout: Duration,
+    max_concurrent: usize,
+) -> FanOutResult<T>
+where
+    T: Send + 'static,
+    F: Future<Output = Result<T>> + Send + 'static,
+{
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
+    let mut set = JoinSet::new();
+    let mut keys = Vec::with_capacity(requests.len());
+
+    for (index, (key, request)) in requests.into_iter().enumerate() {
+        keys.push(key);
+        let semaphore = semaphore.clone();
+        set.spawn(async move {
+            // The semaphore is never closed
+            let _permit = semaphore.acquire_owned().await;
+            let outcome = match tokio::time::timeout(timeout, request).await {
+                Ok(Ok(value)) => Outcome::Completed(value),
+                Ok(Err(e)) => Outcome::Failed(e.to_string()),
+                Err(_) => Outcome::TimedOut,
+            };
+            (index, outcome)
+        });
+    }
+
+    let mut outcomes: Vec<Option<Outcome<T>>> = keys.iter().map(|_| None).collect();
+    while let Some(joined) = set.join_next().await {
+        match joined {
+            Ok((index, outcome)) => outcomes[index] = Some(outcome),
+            Err(e) => log::warn!("Fan-out sub-request panicked: {}", e),
+        }
+    }
+
+    let mut result = FanOutResult {
+        completed: Vec::new(),
+        timed_out: Vec::new(),
+        failed: Vec::new(),
+    };
+    for (key, outcome) in keys.into_iter().zip(outcomes) {
+        match outcome {
+            Some(Outcome::Completed(value)) => result.completed.push((key, value)),
+            Some(Outcome::TimedOut) => {
+                log::warn!(
+                    "Fan-out sub-request '{}' timed out after {:?}",
+                    key,
+                    timeout
+                );
+                result.timed_out.push(key);
+            }
+            Some(Outcome::Failed(message)) => {
+                log::warn!("Fan-out sub-request '{}' failed: {}", key, message);
+                result.failed.push((key, message));
+            }
+            None => result
+                .failed
+                .push((key, "Sub-request panicked".to_string())),
+        }
+    }
+    result
+}
+
+/// Resolve the effective timeout from a per-call override and the configured setting
+pub fn fan_out_timeout(override_secs: Option<u64>, configured_secs: Option<u64>) -> Duration {
+    let secs = override_secs
+        .or(configured_secs)
+        .unwrap_or(DEFAULT_FAN_OUT_TIMEOUT_SECS)
+        .clamp(1, MAX_FAN_OUT_TIMEOUT_SECS);
+    Duration::from_secs(secs)
+}
@@ -0,0 +1,122 @@
+//! One-shot Kopia CLI invocations
+//!
+//! Some repository settings (e.g. maintenance ownership) aren't exposed by the
+//! server's REST API. These are read and changed by running the bundled Kopia
+//! binary against the same config file the repository's server uses.
+
+use crate::error::{KopiaError, Result};
+use crate::kopia_server::KopiaServer;
+use serde::de::DeserializeOwned;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Timeout for a single CLI invocation (2 minutes)
+pub(crate) const CLI_TIMEOUT_SECS: u64 = 120;
+
+/// Run `kopia <args> --config-file=<config_file>` and return its stdout
+pub async fn run(config_file: &str, args: &[&str], operation: &str) -> Result<String> {
+    run_with_timeout(
+        config_file,
+        args,
+        operation,
+        Duration::from_secs(CLI_TIMEOUT_SECS),
+    )
+    .await
+}
+
+/// Like `run`, for commands that may take longer than the default timeout
+pub async fn run_with_timeout(
+    config_file: &str,
+    args: &[&str],
+    operation: &str,
+    timeout: Duration,
+) -> Result<String> {
+    let mut cmd = command(args)?;
+    cmd.arg(format!("--config-file={}", config_file));
+
+    let output = tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| {
+            KopiaError::operation_failed(
+                operation,
+                format!("Kopia CLI timed out after {}s", timeout.as_secs()),
+            )
+        })?
+        .map_err(|e| {
+            KopiaError::operation_failed(
+                "binary execution",
+                format!("Failed to run Kopia CLI: {}", e),
+            )
+        })?;
+
+    if !output.status.success() {
+        return Err(KopiaError::operation_failed_with_details(
+            operation,
+            format!("Kopia CLI exited with status: {}", output.status),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Output of `kopia --version` for the bundled binary
+///
+/// Depending on the Kopia version this is printed to stdout or stderr, so
+/// whichever is non-empty is returned.
+pub async fn version() -> Result<String> {
+    let mut cmd = command(&["--version"])?;
+    let output = tokio::time::timeout(Duration::from_secs(CLI_TIMEOUT_SECS), cmd.output())
+        .await
+        .map_err(|_| KopiaError::operation_failed("Get Kopia version", "Kopia CLI timed out"))?
+        .map_err(|e| {
+            KopiaError::operation_failed(
+                "binary execution",
+                format!("Failed to run Kopia CLI: {}", e),
+            )
+        })?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    if stdout.trim().is_empty() {
+        Ok(String::from_utf8_lossy(&output.stderr).into_owned())
+    } else {
+        Ok(stdout)
+    }
+}
+
+/// Command running the bundled binary with `args`, without a config file
+fn command(args: &[&str]) -> Result<tokio::process::Command> {
+    let binary_path = KopiaServer::get_kopia_binary_path()?;
+
+    let mut cmd = tokio::process::Command::new(&binary_path);
+    cmd.args(args)
+        .env("KOPIA_CHECK_FOR_UPDATES", "false")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    // On Windows, prevent console window from appearing
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    log::debug!("Running kopia {}", args.join(" "));
+    Ok(cmd)
+}
+
+/// Run a CLI command with `--json` output and parse the result
+pub async fn run_json<T: DeserializeOwned>(
+    config_file: &str,
+    args: &[&str],
+    operation: &str,
+) -> Result<T> {
+    let mut json_args = args.to_vec();
+    json_args.push("--json");
+
+    let stdout = run(config_file, &json_args, operation).await?;
+
+    serde_json::from_str(&stdout).map_err(|e| KopiaError::ResponseParseError {
+        message: e.to_string(),
+        expected_type: std::any::type_name::<T>().to_string(),
+    })
+}
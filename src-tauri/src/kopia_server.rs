@@ -21,6 +21,16 @@
 //! Graceful shutdown is achieved via `--shutdown-on-stdin` - closing stdin triggers
 //! server shutdown.
 //!
+//! Stderr keeps being read after startup, and error notifications printed by
+//! `--kopiaui-notifications` are kept in a bounded history per server.
+//!
+//! A server the app didn't start (`ExternalServer`) can be used instead; see
+//! `connect_external_server`.
+//!
+//! Servers whose process is still alive but that stopped answering HTTP
+//! requests are restarted by `reconnect::run_server_monitor`, using
+//! `HeartbeatTracker` to count failed checks.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -31,7 +41,9 @@
 //! ```
 
 use crate::error::{KopiaError, Result};
+use crate::types::{KopiaNotification, LastKnownState, SnapshotSource, SourceIdentity, Task};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::io::{BufRead, BufReader};
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
@@ -39,17 +51,35 @@ use std::time::{Duration, SystemTime};
 
 // Constants
 const SERVER_USERNAME: &str = "kopia";
+/// User name of the server control API (Kopia's `--server-control-username` default)
+const SERVER_CONTROL_USERNAME: &str = "server-control";
 
 /// Timeout for parsing server parameters from stderr (30 seconds)
 const SERVER_PARAM_TIMEOUT_SECS: u64 = 30;
+/// Environment variable that allows skipping certificate verification in release builds
+pub const ALLOW_INSECURE_TLS_ENV: &str = "KOPIA_DESKTOP_ALLOW_INSECURE_TLS";
+/// Maximum number of startup warnings kept from stderr
+const MAX_STARTUP_WARNINGS: usize = 50;
+/// Maximum number of error notifications kept per server
+const MAX_ERROR_NOTIFICATIONS: usize = 100;
+/// Lowest severity kept in the error notification history (Error)
+const ERROR_NOTIFICATION_SEVERITY: i32 = 20;
 /// Number of retries when waiting for server to become ready (40 * 500ms = 20s total)
-const HEALTH_CHECK_RETRIES: u32 = 40;
+pub(crate) const HEALTH_CHECK_RETRIES: u32 = 40;
 /// Interval between health check retries (500ms)
-const HEALTH_CHECK_INTERVAL_MS: u64 = 500;
+pub(crate) const HEALTH_CHECK_INTERVAL_MS: u64 = 500;
 /// Maximum timeout for HTTP operations (5 minutes)
-const HTTP_OPERATION_TIMEOUT_SECS: u64 = 300;
+pub(crate) const HTTP_OPERATION_TIMEOUT_SECS: u64 = 300;
 /// Timeout for establishing HTTP connections (10 seconds)
-const HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+pub(crate) const HTTP_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Longest wait for the next chunk of a download (downloads have no total timeout)
+pub(crate) const DOWNLOAD_IDLE_TIMEOUT_SECS: u64 = 60;
+/// How often the heartbeat checks that running servers still respond
+pub const HEARTBEAT_INTERVAL_SECS: u64 = 30;
+/// Timeout of a single heartbeat request
+pub const HEARTBEAT_TIMEOUT_SECS: u64 = 10;
+/// Consecutive failed heartbeats after which a server is restarted
+pub const HEARTBEAT_FAILURE_THRESHOLD: u32 = 3;
 
 /// Server parameters parsed from Kopia's stderr output
 #[derive(Debug, Clone, Default)]
@@ -59,6 +89,10 @@ struct ServerParams {
     control_password: Option<String>,
     cert_sha256: Option<String>,
     certificate: Option<String>,
+    /// Warning lines printed before the server was ready
+    startup_warnings: Vec<String>,
+    /// Line reporting that the config is locked by another process
+    lock_error: Option<String>,
 }
 
 impl ServerParams {
@@ -105,14 +139,416 @@ impl ServerParams {
                     log::debug!("Received notification: {}", value);
                     return Some(value.to_string());
                 }
-                _ => {}
+                _ => self.collect_warning(line),
             }
+        } else {
+            self.collect_warning(line);
         }
         None
     }
+
+    /// Record `line` as a startup warning if it looks like one
+    fn collect_warning(&mut self, line: &str) {
+        if self.lock_error.is_none() && is_config_lock_line(line) {
+            log::warn!("Server reported a config lock: {}", line.trim());
+            self.lock_error = Some(line.trim().to_string());
+        }
+        if self.startup_warnings.len() < MAX_STARTUP_WARNINGS && is_warning_line(line) {
+            log::warn!("Server startup warning: {}", line.trim());
+            self.startup_warnings.push(line.trim().to_string());
+        }
+    }
+}
+
+/// Error notifications received from a server, oldest first
+pub type NotificationLog = Arc<Mutex<VecDeque<KopiaNotification>>>;
+
+/// Store a notification if it's an error, dropping the oldest beyond the limit
+///
+/// Returns false if `json` isn't a notification or is below error severity.
+pub(crate) fn record_notification(log: &NotificationLog, json: &str, received_at: &str) -> bool {
+    let mut notification: KopiaNotification = match serde_json::from_str(json) {
+        Ok(notification) => notification,
+        Err(e) => {
+            log::debug!("Ignoring malformed notification: {}", e);
+            return false;
+        }
+    };
+    if notification.severity < ERROR_NOTIFICATION_SEVERITY {
+        return false;
+    }
+    notification.received_at = received_at.to_string();
+
+    let mut log = log.lock().unwrap();
+    if log.len() == MAX_ERROR_NOTIFICATIONS {
+        log.pop_front();
+    }
+    log.push_back(notification);
+    true
+}
+
+/// Newer Kopia version announced by a server's update check
+pub type AvailableUpdate = Arc<Mutex<Option<String>>>;
+
+/// Extract the new version from Kopia's update notice
+///
+/// With update checks enabled Kopia prints "Upgrade of Kopia from 0.17.0 to
+/// 0.18.1 is available." to stderr.
+pub(crate) fn parse_update_notice(line: &str) -> Option<String> {
+    let rest = line.trim().strip_prefix("Upgrade of Kopia from ")?;
+    let (_, rest) = rest.split_once(" to ")?;
+    let version = rest.strip_suffix(" is available.")?.trim();
+
+    (!version.is_empty()).then(|| version.to_string())
+}
+
+/// Keep reading server stderr after startup, recording error notifications
+/// and update notices
+///
+/// Runs until the server closes stderr. Reading also keeps the pipe from
+/// filling up and blocking the server.
+fn forward_notifications<R: BufRead + Send + 'static>(
+    lines: std::io::Lines<R>,
+    log: NotificationLog,
+    available_update: AvailableUpdate,
+) {
+    std::thread::spawn(move || {
+        for line in lines.map_while(std::result::Result::ok) {
+            match line.strip_prefix("NOTIFICATION: ") {
+                Some(json) => {
+                    log::debug!("Received notification: {}", json);
+                    record_notification(&log, json.trim(), &chrono::Utc::now().to_rfc3339());
+                }
+                None => {
+                    log::debug!("Server stderr: {}", line);
+                    if let Some(version) = parse_update_notice(&line) {
+                        log::info!("Kopia {} is available", version);
+                        *available_update.lock().unwrap() = Some(version);
+                    }
+                }
+            }
+        }
+        log::debug!("Server stderr closed");
+    });
+}
+
+/// Check whether a stderr line is a warning
+///
+/// Matches lines prefixed with a warning level (`WARNING: ...`, `warn ...`)
+/// as well as log lines with a `WARN` level column after the timestamp.
+fn is_warning_line(line: &str) -> bool {
+    let trimmed = line.trim();
+    let lower = trimmed.to_ascii_lowercase();
+
+    if lower.starts_with("warning") || lower.starts_with("warn ") || lower.starts_with("warn:") {
+        return true;
+    }
+
+    trimmed
+        .split_whitespace()
+        .take(3)
+        .any(|token| matches!(token, "WARN" | "WARNING" | "[WARN]" | "[WARNING]"))
+}
+
+/// Check whether a stderr line says the config is locked by another process
+fn is_config_lock_line(line: &str) -> bool {
+    let lower = line.to_lowercase();
+
+    (lower.contains("lock")
+        && (lower.contains("another process")
+            || lower.contains("locked by")
+            || lower.contains("already locked")
+            || lower.contains("unable to acquire")
+            || lower.contains("could not acquire")
+            || lower.contains("resource temporarily unavailable")))
+        // Windows sharing violation
+        || lower.contains("being used by another process")
+}
+
+/// Extract a process ID from a lock error, e.g. "locked by another process (pid 4242)"
+fn parse_lock_pid(line: &str) -> Option<u32> {
+    let lower = line.to_lowercase();
+    let pos = lower.find("pid")? + 3;
+    let digits: String = lower[pos..]
+        .trim_start_matches(|c: char| c == ':' || c == '=' || c.is_whitespace())
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+/// Executable format and CPU architecture of a binary, e.g. "ELF x86_64"
+///
+/// Architectures use `std::env::consts::ARCH` names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BinaryFormat {
+    pub format: &'static str,
+    pub arch: &'static str,
+}
+
+impl std::fmt::Display for BinaryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.format, self.arch)
+    }
+}
+
+/// Bytes read from the start of a binary to detect its format
+const BINARY_HEADER_LEN: usize = 4096;
+
+/// Detect the executable format and architecture from a binary's first bytes
+///
+/// Recognizes ELF, Mach-O (including universal binaries) and PE. Scripts are
+/// reported as format "script", which any platform can run.
+pub(crate) fn parse_binary_format(header: &[u8]) -> Option<BinaryFormat> {
+    let u16_at = |offset: usize, big_endian: bool| {
+        let bytes: [u8; 2] = header.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    };
+    let u32_le_at = |offset: usize| {
+        let bytes: [u8; 4] = header.get(offset..offset + 4)?.try_into().ok()?;
+        Some(u32::from_le_bytes(bytes))
+    };
+
+    let (format, arch) = match header.get(..4)? {
+        [0x7f, b'E', b'L', b'F'] => {
+            let big_endian = *header.get(5)? == 2;
+            let arch = match u16_at(18, big_endian)? {
+                0x03 => "x86",
+                0x28 => "arm",
+                0x3e => "x86_64",
+                0xb7 => "aarch64",
+                _ => "unknown",
+            };
+            ("ELF", arch)
+        }
+        [0xcf, 0xfa, 0xed, 0xfe] | [0xce, 0xfa, 0xed, 0xfe] => {
+            let arch = match u32_le_at(4)? {
+                0x0000_0007 => "x86",
+                0x0100_0007 => "x86_64",
+                0x0100_000c => "aarch64",
+                _ => "unknown",
+            };
+            ("Mach-O", arch)
+        }
+        [0xca, 0xfe, 0xba, 0xbe] => ("Mach-O", "universal"),
+        [b'M', b'Z', ..] => {
+            let pe_offset = u32_le_at(0x3c)? as usize;
+            if header.get(pe_offset..pe_offset + 4)? != b"PE\0\0" {
+                return None;
+            }
+            let arch = match u16_at(pe_offset + 4, false)? {
+                0x014c => "x86",
+                0x8664 => "x86_64",
+                0xaa64 => "aarch64",
+                _ => "unknown",
+            };
+            ("PE", arch)
+        }
+        [b'#', b'!', ..] => ("script", "any"),
+        _ => return None,
+    };
+    Some(BinaryFormat { format, arch })
+}
+
+/// Executable format and architecture this system runs natively
+pub(crate) fn host_binary_format() -> BinaryFormat {
+    let format = match std::env::consts::OS {
+        "windows" => "PE",
+        "macos" | "ios" => "Mach-O",
+        _ => "ELF",
+    };
+    BinaryFormat {
+        format,
+        arch: std::env::consts::ARCH,
+    }
+}
+
+/// Check whether a binary can run on `host`
+///
+/// Besides native binaries, this accepts x86_64 binaries on ARM Macs and
+/// Windows (Rosetta / built-in emulation) and x86 binaries on x86_64 Windows.
+pub(crate) fn binary_runs_on(binary: BinaryFormat, host: BinaryFormat) -> bool {
+    if binary.format == "script" {
+        return true;
+    }
+    if binary.format != host.format {
+        return false;
+    }
+
+    binary.arch == host.arch
+        || binary.arch == "universal"
+        || matches!(
+            (host.format, host.arch, binary.arch),
+            ("Mach-O", "aarch64", "x86_64") | ("PE", "aarch64", "x86_64") | ("PE", "x86_64", "x86")
+        )
+}
+
+/// Read a binary's header and detect its format (None if unrecognized)
+pub(crate) fn read_binary_format(path: &str) -> Result<Option<BinaryFormat>> {
+    use std::io::Read;
+
+    let mut header = Vec::with_capacity(BINARY_HEADER_LEN);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(BINARY_HEADER_LEN as u64).read_to_end(&mut header))
+        .map_err(|e| {
+            KopiaError::operation_failed(
+                "binary check",
+                format!("Failed to read Kopia binary '{}': {}", path, e),
+            )
+        })?;
+    Ok(parse_binary_format(&header))
+}
+
+/// Whether certificate verification may be skipped
+///
+/// Always allowed in debug builds; release builds require `KOPIA_DESKTOP_ALLOW_INSECURE_TLS=1`.
+pub fn insecure_cert_verify_allowed() -> bool {
+    cfg!(debug_assertions) || std::env::var(ALLOW_INSECURE_TLS_ENV).is_ok_and(|v| v == "1")
+}
+
+/// Error returned when skipping certificate verification isn't allowed
+pub(crate) fn insecure_tls_unavailable() -> KopiaError {
+    KopiaError::operation_failed(
+        "insecure TLS",
+        format!(
+            "Skipping certificate verification is only available in debug builds or with {}=1",
+            ALLOW_INSECURE_TLS_ENV
+        ),
+    )
+}
+
+/// Event emitted while a server is being (re)started
+pub const SERVER_START_PHASE_EVENT: &str = "server-start-phase";
+
+/// Event emitted when a running server stopped responding and is restarted
+pub const SERVER_UNHEALTHY_EVENT: &str = "server-unhealthy";
+
+/// Payload of the `server-unhealthy` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerUnhealthyEvent {
+    pub repo_id: String,
+    /// Consecutive heartbeats that failed
+    pub failures: u32,
+}
+
+/// Optional `kopia server start` flags supported by the bundled binary
+///
+/// Older Kopia releases lack some of the flags KopiaUI relies on (0.21.1 has
+/// no `--shutdown-on-stdin`), and passing an unknown flag fails the start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerFlags {
+    pub shutdown_on_stdin: bool,
+    pub random_server_control_password: bool,
+    pub kopiaui_notifications: bool,
 }
 
+impl ServerFlags {
+    /// Assumed when detection fails, matching what the app always passed
+    pub const ALL: Self = Self {
+        shutdown_on_stdin: true,
+        random_server_control_password: true,
+        kopiaui_notifications: true,
+    };
+
+    /// Read the supported flags from `kopia server start --help` output
+    ///
+    /// Returns `None` if the output isn't the help for `server start` (e.g. an
+    /// error about an unknown flag).
+    pub(crate) fn from_help(help: &str) -> Option<Self> {
+        let flags: std::collections::HashSet<&str> = help
+            .split_whitespace()
+            .filter_map(|token| token.strip_prefix("--"))
+            .map(|flag| {
+                // Boolean flags are listed as `--[no-]name`
+                let flag = flag.strip_prefix("[no-]").unwrap_or(flag);
+                flag.split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                    .next()
+                    .unwrap_or(flag)
+            })
+            .collect();
+
+        if !flags.contains("address") {
+            return None;
+        }
+
+        Some(Self {
+            shutdown_on_stdin: flags.contains("shutdown-on-stdin"),
+            random_server_control_password: flags.contains("random-server-control-password"),
+            kopiaui_notifications: flags.contains("kopiaui-notifications"),
+        })
+    }
+}
+
+/// Optional overrides for the flags used when spawning the server
+///
+/// Options are remembered by the server instance, so a later restart without
+/// options reuses the last ones applied.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStartOptions {
+    /// Listen address, must be a loopback address (defaults to `127.0.0.1:0`)
+    pub address: Option<String>,
+    /// Server log level passed as `--log-level` (e.g. "info", "debug")
+    pub log_level: Option<String>,
+}
+
+impl ServerStartOptions {
+    /// Validate the options before they are applied to a server
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ref address) = self.address {
+            let addr: std::net::SocketAddr = address.parse().map_err(|_| {
+                KopiaError::operation_failed(
+                    "server options",
+                    format!("Invalid listen address: {}", address),
+                )
+            })?;
+            if !addr.ip().is_loopback() {
+                return Err(KopiaError::operation_failed(
+                    "server options",
+                    format!("Listen address must be a loopback address: {}", address),
+                ));
+            }
+        }
+
+        if let Some(ref level) = self.log_level {
+            if !["debug", "info", "warning", "error"].contains(&level.as_str()) {
+                return Err(KopiaError::operation_failed(
+                    "server options",
+                    format!("Invalid log level: {}", level),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Phases reported through the `server-start-phase` event
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ServerStartPhase {
+    Stopping,
+    Starting,
+    WaitingForReady,
+    Ready,
+    Failed,
+}
+
+/// Payload of the `server-start-phase` event
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStartPhaseEvent {
+    pub repo_id: String,
+    pub phase: ServerStartPhase,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct KopiaServerInfo {
     pub server_url: String,
     pub port: u16,
@@ -120,6 +556,9 @@ pub struct KopiaServerInfo {
     pub control_password: Option<String>,
     pub cert_sha256: String,
     pub pid: u32,
+    /// Warnings printed by Kopia while starting up (cache, time sync, etc.)
+    #[serde(default)]
+    pub startup_warnings: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -130,15 +569,47 @@ pub struct KopiaServerStatus {
     pub uptime: Option<u64>,
 }
 
+/// Connection details of a Kopia server not started by the app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalServer {
+    /// Server URL, e.g. `https://backup.example.com:51515`
+    pub url: String,
+    pub username: String,
+    pub password: String,
+    /// SHA-256 fingerprint of the server's TLS certificate (hex, colons allowed)
+    pub cert_fingerprint: String,
+}
+
 pub struct KopiaServer {
     process: Option<Child>,
     info: Option<KopiaServerInfo>,
     start_time: Option<SystemTime>,
     http_client: Option<reqwest::Client>,
+    /// Like `http_client`, without a total timeout (see `get_download_client`)
+    download_client: Option<reqwest::Client>,
     /// PEM certificate for TLS validation
     certificate_pem: Option<String>,
     /// Repository ID this server belongs to (for multi-repo support)
     repo_id: Option<String>,
+    /// Flag overrides applied on every start (kept across restarts)
+    start_options: ServerStartOptions,
+    /// Accept any certificate instead of pinning (debugging only)
+    insecure_skip_cert_verify: bool,
+    /// Let the server check for Kopia updates (`KOPIA_CHECK_FOR_UPDATES`)
+    check_for_updates: bool,
+    /// Newer Kopia version reported by the update check (kept across restarts)
+    available_update: AvailableUpdate,
+    /// Default source identity reported by this server session
+    default_identity: Option<SourceIdentity>,
+    /// Error notifications received (kept across restarts)
+    error_notifications: NotificationLog,
+    /// Sources and tasks last fetched from this server session
+    last_state: LastKnownState,
+    /// Endpoint probe results for this server session, by "METHOD path"
+    endpoint_support: std::collections::HashMap<String, bool>,
+    /// Optional start flags the binary supports (detected on first start)
+    server_flags: Option<ServerFlags>,
 }
 
 impl Default for KopiaServer {
@@ -194,11 +665,49 @@ impl KopiaServer {
             info: None,
             start_time: None,
             http_client: None,
+            download_client: None,
             certificate_pem: None,
             repo_id: None,
+            start_options: ServerStartOptions::default(),
+            insecure_skip_cert_verify: false,
+            check_for_updates: false,
+            available_update: AvailableUpdate::default(),
+            default_identity: None,
+            error_notifications: NotificationLog::default(),
+            last_state: LastKnownState::default(),
+            endpoint_support: std::collections::HashMap::new(),
+            server_flags: None,
         }
     }
 
+    /// Accept any server certificate instead of pinning the generated one
+    ///
+    /// For debugging against manually-run servers only. Applies from the next start.
+    pub fn set_insecure_skip_cert_verify(&mut self, enabled: bool) -> Result<()> {
+        if enabled && !insecure_cert_verify_allowed() {
+            return Err(insecure_tls_unavailable());
+        }
+        self.insecure_skip_cert_verify = enabled;
+        Ok(())
+    }
+
+    /// Let the server check for Kopia updates; applies from the next start
+    pub fn set_check_for_updates(&mut self, enabled: bool) {
+        self.check_for_updates = enabled;
+    }
+
+    /// Newer Kopia version announced by the server, if any
+    pub fn available_update(&self) -> Option<String> {
+        self.available_update.lock().unwrap().clone()
+    }
+
+    /// Replace the flag overrides used for the next start
+    pub fn set_start_options(&mut self, options: ServerStartOptions) -> Result<()> {
+        options.validate()?;
+        self.start_options = options;
+        Ok(())
+    }
+
     /// Start the Kopia server process (uses "repository" as default repo_id)
     ///
     /// This is a convenience method for single-repo scenarios like Windows service.
@@ -234,32 +743,73 @@ impl KopiaServer {
             });
         }
 
-        let binary_path = self.get_kopia_binary_path()?;
+        let binary_path = Self::get_kopia_binary_path()?;
         let config_file = format!("{}/{}.config", config_dir, repo_id);
 
         log::info!("Starting Kopia server with binary: {}", binary_path);
+
+        // Fail clearly instead of with an exec format error; unrecognized
+        // formats are left for the spawn to report
+        if let Ok(Some(format)) = read_binary_format(&binary_path) {
+            let host = host_binary_format();
+            if !binary_runs_on(format, host) {
+                return Err(KopiaError::BinaryIncompatible {
+                    path: binary_path,
+                    expected: host.to_string(),
+                    found: format.to_string(),
+                });
+            }
+        }
         log::info!("Config file: {}", config_file);
 
+        let address = format!(
+            "--address={}",
+            self.start_options
+                .address
+                .as_deref()
+                .unwrap_or("127.0.0.1:0") // Let OS pick available port
+        );
+
+        let flags = self.detect_server_flags(&binary_path);
+
         let mut cmd = Command::new(&binary_path);
         cmd.args([
             "server",
             "start",
             "--ui",
-            "--address=127.0.0.1:0", // Let OS pick available port
+            &address,
             "--tls-generate-cert",
             "--tls-generate-cert-name=127.0.0.1",
             "--tls-print-server-cert", // Print certificate to stderr
             "--random-password",       // Kopia generates password, prints to stderr
-            "--random-server-control-password", // For control API
             "--disable-csrf-token-checks",
             "--async-repo-connect",
-            "--shutdown-on-stdin", // Graceful shutdown when stdin closes
             "--error-notifications=always", // Always show error notifications
-            "--kopiaui-notifications", // Print notification JSON to stderr
             "--config-file",
             &config_file,
-        ])
-        .env("KOPIA_CHECK_FOR_UPDATES", "false")
+        ]);
+        if flags.random_server_control_password {
+            cmd.arg("--random-server-control-password"); // For control API
+        }
+        if flags.shutdown_on_stdin {
+            cmd.arg("--shutdown-on-stdin"); // Graceful shutdown when stdin closes
+        }
+        if flags.kopiaui_notifications {
+            cmd.arg("--kopiaui-notifications"); // Print notification JSON to stderr
+        }
+
+        if let Some(ref level) = self.start_options.log_level {
+            cmd.arg(format!("--log-level={}", level));
+        }
+
+        cmd.env(
+            "KOPIA_CHECK_FOR_UPDATES",
+            if self.check_for_updates {
+                "true"
+            } else {
+                "false"
+            },
+        )
         .stdin(Stdio::piped()) // Keep stdin open for shutdown signal
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
@@ -283,7 +833,7 @@ impl KopiaServer {
         log::info!("Kopia server spawned with PID: {}", pid);
 
         // Parse server parameters from stderr
-        let params = self.parse_server_params(&mut child)?;
+        let params = self.parse_server_params(&mut child, &config_file)?;
 
         // Extract port from address URL
         let port = Self::extract_port(params.address.as_ref().unwrap())?;
@@ -298,29 +848,52 @@ impl KopiaServer {
             control_password: params.control_password.clone(),
             cert_sha256: params.cert_sha256.clone().unwrap(),
             pid,
+            startup_warnings: params.startup_warnings.clone(),
         };
 
-        // Create HTTP client with the server's certificate
-        let http_client =
-            match self.create_http_client(SERVER_USERNAME, &info.password, &certificate_pem) {
-                Ok(client) => client,
-                Err(e) => {
-                    log::error!("Failed to create HTTP client, killing spawned process");
-                    drop(child.stdin.take()); // Close stdin to trigger shutdown
-                    std::thread::sleep(Duration::from_millis(100));
-                    let _ = child.kill();
-                    let _ = child.wait();
-                    return Err(e);
-                }
-            };
+        // Create HTTP clients with the server's certificate
+        let api_timeout = Some(Duration::from_secs(HTTP_OPERATION_TIMEOUT_SECS));
+        let clients = self
+            .create_http_client(
+                SERVER_USERNAME,
+                &info.password,
+                &certificate_pem,
+                &info.cert_sha256,
+                api_timeout,
+            )
+            .and_then(|http_client| {
+                let download_client = self.create_http_client(
+                    SERVER_USERNAME,
+                    &info.password,
+                    &certificate_pem,
+                    &info.cert_sha256,
+                    None,
+                )?;
+                Ok((http_client, download_client))
+            });
+        let (http_client, download_client) = match clients {
+            Ok(clients) => clients,
+            Err(e) => {
+                log::error!("Failed to create HTTP client, killing spawned process");
+                drop(child.stdin.take()); // Close stdin to trigger shutdown
+                std::thread::sleep(Duration::from_millis(100));
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(e);
+            }
+        };
 
         // Store state
         self.process = Some(child);
         self.info = Some(info.clone());
         self.start_time = Some(SystemTime::now());
         self.http_client = Some(http_client);
+        self.download_client = Some(download_client);
         self.certificate_pem = Some(certificate_pem);
         self.repo_id = Some(repo_id.to_string());
+        self.default_identity = None;
+        self.last_state = LastKnownState::default();
+        self.endpoint_support.clear();
 
         log::info!(
             "Kopia server started successfully for repo '{}' at {}",
@@ -339,7 +912,10 @@ impl KopiaServer {
     /// - SERVER CONTROL PASSWORD: <random>
     /// - SERVER CERT SHA256: <hex>
     /// - SERVER CERTIFICATE: <base64>
-    fn parse_server_params(&self, child: &mut Child) -> Result<ServerParams> {
+    ///
+    /// If the server exits after reporting that the config is locked, returns
+    /// `ConfigLocked` for `config_file`.
+    fn parse_server_params(&self, child: &mut Child, config_file: &str) -> Result<ServerParams> {
         let stderr = child.stderr.take().ok_or_else(|| {
             KopiaError::operation_failed("server startup", "Failed to capture server stderr")
         })?;
@@ -349,7 +925,8 @@ impl KopiaServer {
         let start = std::time::Instant::now();
         let timeout = Duration::from_secs(SERVER_PARAM_TIMEOUT_SECS);
 
-        for line in reader.lines() {
+        let mut lines = reader.lines();
+        while let Some(line) = lines.next() {
             // Check timeout
             if start.elapsed() > timeout {
                 // Check if process crashed
@@ -371,12 +948,26 @@ impl KopiaServer {
             match line {
                 Ok(line) => {
                     log::debug!("Server stderr: {}", line);
+                    if let Some(version) = parse_update_notice(&line) {
+                        log::info!("Kopia {} is available", version);
+                        *self.available_update.lock().unwrap() = Some(version);
+                    }
                     // parse_line returns Some(notification_json) for NOTIFICATION lines
-                    // We ignore notifications during startup, they'll be handled later
-                    let _ = params.parse_line(&line);
+                    if let Some(json) = params.parse_line(&line) {
+                        record_notification(
+                            &self.error_notifications,
+                            &json,
+                            &chrono::Utc::now().to_rfc3339(),
+                        );
+                    }
 
                     if params.is_complete() {
                         log::info!("All server parameters received");
+                        forward_notifications(
+                            lines,
+                            self.error_notifications.clone(),
+                            self.available_update.clone(),
+                        );
                         return Ok(params);
                     }
                 }
@@ -395,6 +986,16 @@ impl KopiaServer {
         }
 
         // If we get here, stderr was closed but params incomplete
+        if let Some(ref line) = params.lock_error {
+            // The process exits right after stderr closes; don't leave it behind
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(KopiaError::ConfigLocked {
+                config_file: config_file.to_string(),
+                pid: parse_lock_pid(line),
+            });
+        }
+
         if let Ok(Some(status)) = child.try_wait() {
             return Err(KopiaError::operation_failed(
                 "server startup",
@@ -479,8 +1080,13 @@ impl KopiaServer {
         // Close stdin to trigger graceful shutdown
         drop(process.stdin.take());
 
-        // Wait for graceful shutdown (up to 5 seconds)
-        for _ in 0..50 {
+        // Wait for graceful shutdown (up to 5 seconds), unless the server was
+        // started without --shutdown-on-stdin
+        let graceful = self
+            .server_flags
+            .is_none_or(|flags| flags.shutdown_on_stdin);
+        let attempts = if graceful { 50 } else { 0 };
+        for _ in 0..attempts {
             match process.try_wait() {
                 Ok(Some(status)) => {
                     log::info!("Server stopped gracefully with status: {}", status);
@@ -498,7 +1104,11 @@ impl KopiaServer {
         }
 
         // Graceful shutdown didn't work, force kill
-        log::warn!("Graceful shutdown timed out, killing process");
+        if graceful {
+            log::warn!("Graceful shutdown timed out, killing process");
+        } else {
+            log::info!("Server doesn't support --shutdown-on-stdin, killing process");
+        }
         process.kill().map_err(|e| {
             KopiaError::operation_failed(
                 "server shutdown",
@@ -517,13 +1127,117 @@ impl KopiaServer {
         Ok(())
     }
 
+    /// Optional start flags supported by `binary_path`
+    ///
+    /// Runs `kopia server start --help-full` (which also lists hidden flags),
+    /// falling back to `--help` for releases without it, and caches the result
+    /// for this instance. If neither output can be read, all flags are assumed
+    /// supported.
+    pub fn detect_server_flags(&mut self, binary_path: &str) -> ServerFlags {
+        if let Some(flags) = self.server_flags {
+            return flags;
+        }
+
+        let flags = ["--help-full", "--help"]
+            .iter()
+            .find_map(|help| {
+                let mut cmd = Command::new(binary_path);
+                cmd.args(["server", "start", help])
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped());
+
+                #[cfg(target_os = "windows")]
+                {
+                    use std::os::windows::process::CommandExt;
+                    const CREATE_NO_WINDOW: u32 = 0x08000000;
+                    cmd.creation_flags(CREATE_NO_WINDOW);
+                }
+
+                // Depending on the version, help goes to stdout or stderr
+                let output = cmd.output().ok()?;
+                ServerFlags::from_help(&format!(
+                    "{}\n{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                ))
+            })
+            .unwrap_or_else(|| {
+                log::warn!("Couldn't detect Kopia server flags, assuming all are supported");
+                ServerFlags::ALL
+            });
+
+        log::info!("Kopia server flags: {:?}", flags);
+        self.server_flags = Some(flags);
+        flags
+    }
+
     /// Clean up server state
     fn cleanup(&mut self) {
         self.info = None;
         self.start_time = None;
         self.http_client = None;
+        self.download_client = None;
         self.certificate_pem = None;
         self.repo_id = None;
+        self.default_identity = None;
+        self.last_state = LastKnownState::default();
+        self.endpoint_support.clear();
+    }
+
+    /// Default source identity cached for the current server session
+    pub fn default_identity(&self) -> Option<SourceIdentity> {
+        self.default_identity.clone()
+    }
+
+    /// Cache (or with `None`, forget) the default source identity
+    pub fn set_default_identity(&mut self, identity: Option<SourceIdentity>) {
+        self.default_identity = identity;
+    }
+
+    /// Cached result of probing an endpoint in this server session
+    pub fn endpoint_support(&self, endpoint: &str) -> Option<bool> {
+        self.endpoint_support.get(endpoint).copied()
+    }
+
+    /// Remember whether an endpoint is supported for this server session
+    pub fn record_endpoint_support(&mut self, endpoint: &str, supported: bool) {
+        self.endpoint_support
+            .insert(endpoint.to_string(), supported);
+    }
+
+    /// Sources and tasks last fetched from this server session
+    pub fn last_known_state(&self) -> LastKnownState {
+        self.last_state.clone()
+    }
+
+    /// Remember the latest sources list
+    pub fn record_sources(&mut self, sources: Vec<SnapshotSource>, received_at: &str) {
+        self.last_state.sources = sources;
+        self.last_state.received_at = received_at.to_string();
+    }
+
+    /// Remember the latest tasks list
+    pub fn record_tasks(&mut self, tasks: Vec<Task>, received_at: &str) {
+        self.last_state.tasks = tasks;
+        self.last_state.received_at = received_at.to_string();
+    }
+
+    /// Up to `limit` most recent error notifications, newest first
+    pub fn error_notifications(&self, limit: usize) -> Vec<KopiaNotification> {
+        self.error_notifications
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Forget all error notifications
+    pub fn clear_error_notifications(&self) {
+        self.error_notifications.lock().unwrap().clear();
     }
 
     /// Check if the server is currently running and alive
@@ -574,13 +1288,13 @@ impl KopiaServer {
     }
 
     /// Get the path to the Kopia binary
-    fn get_kopia_binary_path(&self) -> Result<String> {
+    pub(crate) fn get_kopia_binary_path() -> Result<String> {
         // Check for custom path via environment variable
         if let Ok(custom_path) = std::env::var("KOPIA_PATH") {
             return Ok(custom_path);
         }
 
-        let binary_name = self.get_platform_binary_name();
+        let binary_name = Self::get_platform_binary_name();
         let exe_dir = std::env::current_exe()
             .ok()
             .and_then(|p| p.parent().map(std::path::PathBuf::from))
@@ -620,7 +1334,7 @@ impl KopiaServer {
     }
 
     /// Get platform-specific binary name
-    fn get_platform_binary_name(&self) -> &'static str {
+    fn get_platform_binary_name() -> &'static str {
         match (std::env::consts::OS, std::env::consts::ARCH) {
             ("windows", _) => "kopia-windows-x64.exe",
             ("macos", "aarch64") => "kopia-darwin-arm64",
@@ -655,56 +1369,40 @@ impl KopiaServer {
     /// Create HTTP client with Basic Auth credentials and certificate validation
     ///
     /// Uses the server's self-signed certificate for TLS validation instead of
-    /// blindly accepting all certificates.
+    /// blindly accepting all certificates. The certificate must match the
+    /// SHA-256 fingerprint the server printed. `timeout` limits each request
+    /// as a whole, including reading the body.
     fn create_http_client(
         &self,
         username: &str,
         password: &str,
         certificate_pem: &str,
+        cert_sha256: &str,
+        timeout: Option<Duration>,
     ) -> Result<reqwest::Client> {
-        use base64::Engine;
-
-        let auth = format!("{}:{}", username, password);
-        let auth_header = format!(
-            "Basic {}",
-            base64::prelude::BASE64_STANDARD.encode(auth.as_bytes())
-        );
+        let builder = authenticated_client_builder(username, password)?;
+        let builder = match timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        };
 
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert(
-            "X-Kopia-Csrf-Token",
-            reqwest::header::HeaderValue::from_static("-"),
-        );
-        headers.insert(
-            reqwest::header::AUTHORIZATION,
-            reqwest::header::HeaderValue::from_str(&auth_header).map_err(|e| {
-                KopiaError::operation_failed(
-                    "http client setup",
-                    format!("Failed to create auth header: {}", e),
-                )
-            })?,
-        );
+        let builder = if self.insecure_skip_cert_verify {
+            log::warn!("==========================================================");
+            log::warn!("TLS CERTIFICATE VERIFICATION IS DISABLED");
+            log::warn!("Any certificate presented by the Kopia server is accepted.");
+            log::warn!("Use this for debugging only.");
+            log::warn!("==========================================================");
+            builder.danger_accept_invalid_certs(true)
+        } else {
+            builder.add_root_certificate(pinned_certificate(certificate_pem, cert_sha256)?)
+        };
 
-        // Parse the certificate for TLS validation
-        let cert = reqwest::Certificate::from_pem(certificate_pem.as_bytes()).map_err(|e| {
+        builder.build().map_err(|e| {
             KopiaError::operation_failed(
                 "http client setup",
-                format!("Failed to parse server certificate: {}", e),
+                format!("Failed to create HTTP client: {}", e),
             )
-        })?;
-
-        reqwest::Client::builder()
-            .default_headers(headers)
-            .timeout(Duration::from_secs(HTTP_OPERATION_TIMEOUT_SECS))
-            .connect_timeout(Duration::from_secs(HTTP_CONNECT_TIMEOUT_SECS))
-            .add_root_certificate(cert)
-            .build()
-            .map_err(|e| {
-                KopiaError::operation_failed(
-                    "http client setup",
-                    format!("Failed to create HTTP client: {}", e),
-                )
-            })
+        })
     }
 
     /// Get the HTTP client for making API requests
@@ -712,6 +1410,42 @@ impl KopiaServer {
         self.http_client.clone()
     }
 
+    /// Get the HTTP client for streaming downloads
+    ///
+    /// It has no total timeout, since a large file can take longer than
+    /// `HTTP_OPERATION_TIMEOUT_SECS` to stream; callers limit the wait for
+    /// each chunk to `DOWNLOAD_IDLE_TIMEOUT_SECS` instead.
+    pub fn get_download_client(&self) -> Option<reqwest::Client> {
+        self.download_client.clone()
+    }
+
+    /// Create an HTTP client for the server control API (`/api/v1/control/*`)
+    ///
+    /// Authenticates with the control password printed at startup. Kopia
+    /// versions that don't print one have no control API.
+    pub fn get_control_client(&self) -> Result<reqwest::Client> {
+        let (info, certificate_pem) = self
+            .info
+            .as_ref()
+            .zip(self.certificate_pem.as_ref())
+            .ok_or(KopiaError::ServerNotRunning)?;
+
+        let control_password =
+            info.control_password
+                .as_deref()
+                .ok_or_else(|| KopiaError::ControlApiUnavailable {
+                    reason: "The Kopia server didn't report a control password".to_string(),
+                })?;
+
+        self.create_http_client(
+            SERVER_CONTROL_USERNAME,
+            control_password,
+            certificate_pem,
+            &info.cert_sha256,
+            Some(Duration::from_secs(HTTP_OPERATION_TIMEOUT_SECS)),
+        )
+    }
+
     /// Get the server URL if the server is running
     #[cfg(test)]
     pub(crate) fn get_server_url(&self) -> Option<String> {
@@ -719,6 +1453,240 @@ impl KopiaServer {
     }
 }
 
+/// Client builder sending Basic Auth credentials, with a connect timeout
+///
+/// API clients add `HTTP_OPERATION_TIMEOUT_SECS` as total timeout; download
+/// clients don't.
+fn authenticated_client_builder(username: &str, password: &str) -> Result<reqwest::ClientBuilder> {
+    use base64::Engine;
+
+    let auth = format!("{}:{}", username, password);
+    let auth_header = format!(
+        "Basic {}",
+        base64::prelude::BASE64_STANDARD.encode(auth.as_bytes())
+    );
+
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(
+        "X-Kopia-Csrf-Token",
+        reqwest::header::HeaderValue::from_static("-"),
+    );
+    headers.insert(
+        reqwest::header::AUTHORIZATION,
+        reqwest::header::HeaderValue::from_str(&auth_header).map_err(|e| {
+            KopiaError::operation_failed(
+                "http client setup",
+                format!("Failed to create auth header: {}", e),
+            )
+        })?,
+    );
+
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .connect_timeout(Duration::from_secs(HTTP_CONNECT_TIMEOUT_SECS)))
+}
+
+/// Normalize a SHA-256 fingerprint to lowercase hex without separators
+///
+/// Returns `None` if it isn't 64 hex digits.
+pub(crate) fn normalize_fingerprint(fingerprint: &str) -> Option<String> {
+    let hex: String = fingerprint
+        .chars()
+        .filter(|c| !matches!(c, ':' | ' '))
+        .collect::<String>()
+        .to_ascii_lowercase();
+
+    (hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit())).then_some(hex)
+}
+
+/// SHA-256 fingerprint of a DER certificate, in the hex form Kopia prints
+pub(crate) fn certificate_fingerprint(der: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Whether a DER certificate has the expected SHA-256 fingerprint
+pub(crate) fn fingerprint_matches(expected: &str, der: &[u8]) -> bool {
+    normalize_fingerprint(expected).is_some_and(|expected| expected == certificate_fingerprint(der))
+}
+
+/// Decode the first certificate of a PEM document to DER
+pub(crate) fn pem_to_der(pem: &str) -> Option<Vec<u8>> {
+    use base64::Engine;
+
+    let body: String = pem
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| *line != "-----BEGIN CERTIFICATE-----")
+        .skip(1)
+        .take_while(|line| *line != "-----END CERTIFICATE-----")
+        .collect();
+
+    if body.is_empty() {
+        return None;
+    }
+    base64::prelude::BASE64_STANDARD.decode(body).ok()
+}
+
+/// Certificate to trust for an embedded server, checked against its fingerprint
+///
+/// Some TLS backends reject the PEM Kopia prints; the certificate is then
+/// loaded from its DER form instead. Either way the certificate is only
+/// trusted if it matches the fingerprint the server printed.
+fn pinned_certificate(certificate_pem: &str, cert_sha256: &str) -> Result<reqwest::Certificate> {
+    let der = pem_to_der(certificate_pem)
+        .filter(|der| fingerprint_matches(cert_sha256, der))
+        .ok_or_else(|| {
+            KopiaError::operation_failed(
+                "http client setup",
+                "Server certificate doesn't match its SHA-256 fingerprint",
+            )
+        })?;
+
+    reqwest::Certificate::from_pem(certificate_pem.as_bytes())
+        .or_else(|e| {
+            log::warn!(
+                "Failed to parse server certificate as PEM ({}), using its DER form",
+                e
+            );
+            reqwest::Certificate::from_der(&der)
+        })
+        .map_err(|e| {
+            KopiaError::operation_failed(
+                "http client setup",
+                format!("Failed to parse server certificate: {}", e),
+            )
+        })
+}
+
+/// Check an external server URL and strip any trailing slash
+pub(crate) fn validate_external_url(url: &str) -> Result<String> {
+    let parsed = url::Url::parse(url)
+        .map_err(|e| KopiaError::invalid_input("url", format!("Invalid URL: {}", e)))?;
+
+    if parsed.scheme() != "https" {
+        return Err(KopiaError::invalid_input(
+            "url",
+            "Kopia servers must be reached over HTTPS",
+        ));
+    }
+    if parsed.host_str().is_none() {
+        return Err(KopiaError::invalid_input("url", "URL has no host"));
+    }
+
+    Ok(url.trim_end_matches('/').to_string())
+}
+
+/// Fetch the certificate an HTTPS server presents, without validating it
+async fn fetch_peer_certificate(url: &str) -> Result<Vec<u8>> {
+    let probe = reqwest::Client::builder()
+        .danger_accept_invalid_certs(true)
+        .tls_info(true)
+        .timeout(Duration::from_secs(HTTP_CONNECT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| {
+            KopiaError::operation_failed(
+                "http client setup",
+                format!("Failed to create HTTP client: {}", e),
+            )
+        })?;
+
+    let response = probe.get(url).send().await.map_err(|e| {
+        KopiaError::operation_failed("external server", format!("Failed to reach {}: {}", url, e))
+    })?;
+
+    response
+        .extensions()
+        .get::<reqwest::tls::TlsInfo>()
+        .and_then(|info| info.peer_certificate())
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| {
+            KopiaError::operation_failed("external server", "Server presented no certificate")
+        })
+}
+
+/// Build an HTTP client for a Kopia server the app didn't start
+///
+/// The server's certificate is fetched without sending credentials and only
+/// trusted if its fingerprint matches `cert_fingerprint`. The client trusts
+/// that certificate alone, so it must be valid for the host name in the URL
+/// (e.g. generated with `--tls-generate-cert-name`). The credentials are
+/// checked before the clients are returned.
+///
+/// Returns the API client and the download client (without a total timeout,
+/// see `KopiaServer::get_download_client`).
+pub async fn connect_external_server(
+    server: &ExternalServer,
+) -> Result<(reqwest::Client, reqwest::Client)> {
+    let url = validate_external_url(&server.url)?;
+    let expected = normalize_fingerprint(&server.cert_fingerprint).ok_or_else(|| {
+        KopiaError::invalid_input(
+            "certFingerprint",
+            "Expected a SHA-256 fingerprint (64 hex digits)",
+        )
+    })?;
+
+    let der = fetch_peer_certificate(&url).await?;
+    let found = certificate_fingerprint(&der);
+    if found != expected {
+        return Err(KopiaError::invalid_input(
+            "certFingerprint",
+            format!("Server certificate fingerprint is {}", found),
+        ));
+    }
+
+    let cert = reqwest::Certificate::from_der(&der).map_err(|e| {
+        KopiaError::operation_failed(
+            "http client setup",
+            format!("Failed to parse server certificate: {}", e),
+        )
+    })?;
+    let build = |timeout: Option<Duration>| {
+        let builder = authenticated_client_builder(&server.username, &server.password)?
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(cert.clone());
+        let builder = match timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        };
+        builder.build().map_err(|e| {
+            KopiaError::operation_failed(
+                "http client setup",
+                format!("Failed to create HTTP client: {}", e),
+            )
+        })
+    };
+    let client = build(Some(Duration::from_secs(HTTP_OPERATION_TIMEOUT_SECS)))?;
+    let download_client = build(None)?;
+
+    let response = client
+        .get(format!("{}/api/v1/repo/status", url))
+        .send()
+        .await
+        .map_err(|e| {
+            KopiaError::operation_failed(
+                "external server",
+                format!("Failed to connect to {}: {}", url, e),
+            )
+        })?;
+
+    match response.status() {
+        reqwest::StatusCode::UNAUTHORIZED => Err(KopiaError::invalid_input(
+            "password",
+            "Server rejected the username or password",
+        )),
+        status if !status.is_success() => Err(KopiaError::operation_failed(
+            "external server",
+            format!("Server returned status: {}", status),
+        )),
+        _ => Ok((client, download_client)),
+    }
+}
+
 /// Global server instance managed by Tauri state
 pub type KopiaServerState = Arc<Mutex<KopiaServer>>;
 
@@ -728,6 +1696,40 @@ pub fn create_server_state() -> KopiaServerState {
     Arc::new(Mutex::new(KopiaServer::new()))
 }
 
+/// Counts consecutive failed heartbeats per server
+#[derive(Debug, Default)]
+pub struct HeartbeatTracker {
+    failures: std::collections::HashMap<String, u32>,
+}
+
+impl HeartbeatTracker {
+    /// Record a heartbeat result
+    ///
+    /// Returns the number of consecutive failures once it reaches
+    /// `HEARTBEAT_FAILURE_THRESHOLD`, and starts counting again from zero.
+    pub fn observe(&mut self, repo_id: &str, healthy: bool) -> Option<u32> {
+        if healthy {
+            self.failures.remove(repo_id);
+            return None;
+        }
+
+        let failures = self.failures.entry(repo_id.to_string()).or_insert(0);
+        *failures += 1;
+        if *failures < HEARTBEAT_FAILURE_THRESHOLD {
+            return None;
+        }
+
+        let reached = *failures;
+        self.failures.remove(repo_id);
+        Some(reached)
+    }
+
+    /// Forget servers that are no longer running
+    pub fn retain_running(&mut self, running: &[String]) {
+        self.failures.retain(|id, _| running.contains(id));
+    }
+}
+
 /// Wait for server to become ready (standalone async function)
 async fn wait_for_server_ready(http_client: reqwest::Client, server_url: String) -> Result<()> {
     let mut last_error = None;
@@ -791,6 +1793,55 @@ mod tests {
         assert!(params.is_complete());
     }
 
+    #[test]
+    fn test_server_flags_from_help() {
+        let help = "usage: kopia server start [<flags>]
+
+Start Kopia server
+
+Flags:
+      --help                     Show context-sensitive help.
+      --config-file=\"~/.config/kopia/repository.config\"
+                                 Specify the config file to use
+      --address=\"http://127.0.0.1:51515\"
+                                 Server address
+      --[no-]ui                  Start the server with HTML UI
+      --random-password          Generate random password and print to stderr
+      --random-server-control-password
+                                 Generate random server control password and print to stderr
+      --[no-]shutdown-on-stdin   Shut down the server when stdin handle has closed.
+      --kopiaui-notifications    Enable notifications to be printed to stdout for KopiaUI
+";
+        assert_eq!(ServerFlags::from_help(help), Some(ServerFlags::ALL));
+
+        // Kopia 0.21.1 has no --shutdown-on-stdin
+        let help = help.replace("--[no-]shutdown-on-stdin", "--[no-]legacy-api");
+        assert_eq!(
+            ServerFlags::from_help(&help),
+            Some(ServerFlags {
+                shutdown_on_stdin: false,
+                ..ServerFlags::ALL
+            })
+        );
+
+        let help = "      --address=ADDR  Server address\n      --[no-]ui\n";
+        assert_eq!(
+            ServerFlags::from_help(help),
+            Some(ServerFlags {
+                shutdown_on_stdin: false,
+                random_server_control_password: false,
+                kopiaui_notifications: false,
+            })
+        );
+
+        // Not the server start help
+        assert_eq!(
+            ServerFlags::from_help("kopia: error: unknown long flag '--help-full', try --help"),
+            None
+        );
+        assert_eq!(ServerFlags::from_help(""), None);
+    }
+
     #[test]
     fn test_server_params_incomplete() {
         let mut params = ServerParams::default();
@@ -815,6 +1866,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_startup_warning_parsing() {
+        let mut params = ServerParams::default();
+
+        params.parse_line("SERVER ADDRESS: https://127.0.0.1:54321");
+        params.parse_line("NOTIFICATION: {\"type\":\"backup\"}");
+        params.parse_line("Server will allow connections from localhost only.");
+        params.parse_line("WARNING: cache directory is on a network drive");
+        params.parse_line("2024-01-01T10:00:00Z WARN clock skew detected");
+        params.parse_line("warn: new version available");
+
+        assert_eq!(
+            params.startup_warnings,
+            vec![
+                "WARNING: cache directory is on a network drive".to_string(),
+                "2024-01-01T10:00:00Z WARN clock skew detected".to_string(),
+                "warn: new version available".to_string(),
+            ]
+        );
+    }
+
     #[test]
     fn test_extract_port() {
         assert_eq!(
@@ -834,4 +1906,335 @@ mod tests {
         let decoded = KopiaServer::decode_certificate(&base64_cert).unwrap();
         assert_eq!(decoded, "test certificate");
     }
+
+    #[test]
+    fn test_config_lock_line_parsing() {
+        assert!(is_config_lock_line(
+            "ERROR unable to acquire lock on config file: locked by another process (pid 4242)"
+        ));
+        assert!(is_config_lock_line(
+            "The process cannot access the file because it is being used by another process."
+        ));
+        assert!(!is_config_lock_line("ERROR repository not connected"));
+
+        assert_eq!(
+            parse_lock_pid("locked by another process (pid 4242)"),
+            Some(4242)
+        );
+        assert_eq!(parse_lock_pid("held by PID: 17"), Some(17));
+        assert_eq!(parse_lock_pid("locked by another process"), None);
+
+        let mut params = ServerParams::default();
+        params.parse_line("ERROR: config file is already locked");
+        assert_eq!(
+            params.lock_error.as_deref(),
+            Some("ERROR: config file is already locked")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_server_params_maps_lock_error() {
+        // Fake binary that fails like Kopia does when another process holds the lock
+        let mut child = Command::new("sh")
+            .args([
+                "-c",
+                "echo 'ERROR unable to acquire lock on config file: locked by another process (pid 4242)' >&2; exit 1",
+            ])
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let server = KopiaServer::new();
+        let result = server.parse_server_params(&mut child, "/tmp/repository.config");
+        assert_eq!(
+            result.unwrap_err(),
+            KopiaError::ConfigLocked {
+                config_file: "/tmp/repository.config".to_string(),
+                pid: Some(4242),
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_server_params_other_failure() {
+        let mut child = Command::new("sh")
+            .args(["-c", "echo 'ERROR invalid repository password' >&2; exit 1"])
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let server = KopiaServer::new();
+        let result = server.parse_server_params(&mut child, "/tmp/repository.config");
+        assert!(matches!(result, Err(KopiaError::OperationFailed { .. })));
+    }
+
+    #[test]
+    fn test_record_notification_keeps_recent_errors() {
+        let log = NotificationLog::default();
+
+        assert!(!record_notification(&log, "not json", "t0"));
+        assert!(!record_notification(
+            &log,
+            r#"{"subject":"Snapshot finished","severity":-10}"#,
+            "t0"
+        ));
+
+        for i in 0..MAX_ERROR_NOTIFICATIONS + 5 {
+            let json = format!(
+                r#"{{"subject":"error {}","body":"failed","severity":20}}"#,
+                i
+            );
+            assert!(record_notification(&log, &json, "t1"));
+        }
+
+        let log = log.lock().unwrap();
+        assert_eq!(log.len(), MAX_ERROR_NOTIFICATIONS);
+        assert_eq!(log.front().unwrap().subject, "error 5");
+        assert_eq!(log.back().unwrap().received_at, "t1");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_parse_server_params_keeps_reading_notifications() {
+        let mut child = Command::new("sh")
+            .args([
+                "-c",
+                r#"echo 'SERVER ADDRESS: https://127.0.0.1:51515' >&2
+                   echo 'SERVER PASSWORD: secret' >&2
+                   echo 'SERVER CERT SHA256: abc' >&2
+                   echo 'SERVER CERTIFICATE: Y2VydA==' >&2
+                   echo 'NOTIFICATION: {"subject":"Snapshot failed","severity":20}' >&2"#,
+            ])
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let server = KopiaServer::new();
+        assert!(server
+            .parse_server_params(&mut child, "/tmp/repository.config")
+            .is_ok());
+        child.wait().unwrap();
+
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while server.error_notifications(10).is_empty() && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let notifications = server.error_notifications(10);
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].subject, "Snapshot failed");
+
+        server.clear_error_notifications();
+        assert!(server.error_notifications(10).is_empty());
+    }
+
+    #[test]
+    fn test_parse_update_notice() {
+        assert_eq!(
+            parse_update_notice("Upgrade of Kopia from 0.17.0 to 0.18.1 is available."),
+            Some("0.18.1".to_string())
+        );
+        assert_eq!(
+            parse_update_notice(
+                "Visit https://github.com/kopia/kopia/releases/latest to download it."
+            ),
+            None
+        );
+        assert_eq!(
+            parse_update_notice("SERVER ADDRESS: https://127.0.0.1:1"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_binary_format() {
+        let mut elf = vec![0u8; 64];
+        elf[..4].copy_from_slice(b"\x7fELF");
+        elf[5] = 1;
+        elf[18] = 0xb7;
+        assert_eq!(
+            parse_binary_format(&elf),
+            Some(BinaryFormat {
+                format: "ELF",
+                arch: "aarch64"
+            })
+        );
+
+        let mut macho = vec![0xcf, 0xfa, 0xed, 0xfe];
+        macho.extend_from_slice(&0x0100_0007u32.to_le_bytes());
+        assert_eq!(
+            parse_binary_format(&macho).unwrap().to_string(),
+            "Mach-O x86_64"
+        );
+        assert_eq!(
+            parse_binary_format(&[0xca, 0xfe, 0xba, 0xbe]).unwrap().arch,
+            "universal"
+        );
+
+        let mut pe = vec![0u8; 0x90];
+        pe[..2].copy_from_slice(b"MZ");
+        pe[0x3c] = 0x80;
+        pe[0x80..0x84].copy_from_slice(b"PE\0\0");
+        pe[0x84..0x86].copy_from_slice(&0x8664u16.to_le_bytes());
+        assert_eq!(parse_binary_format(&pe).unwrap().to_string(), "PE x86_64");
+
+        assert_eq!(
+            parse_binary_format(b"#!/bin/sh\n").unwrap().format,
+            "script"
+        );
+        assert_eq!(parse_binary_format(b"MZ"), None);
+        assert_eq!(parse_binary_format(b"hello world"), None);
+    }
+
+    #[test]
+    fn test_binary_runs_on() {
+        let format = |format, arch| BinaryFormat { format, arch };
+
+        assert!(binary_runs_on(
+            format("ELF", "x86_64"),
+            format("ELF", "x86_64")
+        ));
+        assert!(!binary_runs_on(
+            format("ELF", "x86_64"),
+            format("ELF", "aarch64")
+        ));
+        assert!(!binary_runs_on(
+            format("PE", "x86_64"),
+            format("ELF", "x86_64")
+        ));
+        assert!(binary_runs_on(
+            format("Mach-O", "x86_64"),
+            format("Mach-O", "aarch64")
+        ));
+        assert!(binary_runs_on(
+            format("Mach-O", "universal"),
+            format("Mach-O", "aarch64")
+        ));
+        assert!(binary_runs_on(format("PE", "x86"), format("PE", "x86_64")));
+        assert!(binary_runs_on(
+            format("script", "any"),
+            format("PE", "x86_64")
+        ));
+    }
+
+    #[test]
+    fn test_current_exe_matches_host() {
+        let exe = std::env::current_exe().unwrap();
+        let format = read_binary_format(exe.to_str().unwrap()).unwrap().unwrap();
+        assert!(binary_runs_on(format, host_binary_format()));
+    }
+
+    #[test]
+    fn test_normalize_fingerprint() {
+        let hex = "ab".repeat(32);
+        let colons = vec!["AB"; 32].join(":");
+
+        assert_eq!(normalize_fingerprint(&hex), Some(hex.clone()));
+        assert_eq!(normalize_fingerprint(&colons), Some(hex.clone()));
+        assert_eq!(normalize_fingerprint(&hex[..62]), None);
+        assert_eq!(normalize_fingerprint(&"zz".repeat(32)), None);
+    }
+
+    #[test]
+    fn test_certificate_fingerprint() {
+        // SHA-256 of an empty input
+        assert_eq!(
+            certificate_fingerprint(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_validate_external_url() {
+        assert_eq!(
+            validate_external_url("https://backup.example.com:51515/").unwrap(),
+            "https://backup.example.com:51515"
+        );
+        assert!(matches!(
+            validate_external_url("http://backup.example.com:51515"),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+        assert!(validate_external_url("not a url").is_err());
+    }
+
+    #[test]
+    fn test_get_control_client() {
+        let mut server = KopiaServer::new();
+        assert!(matches!(
+            server.get_control_client(),
+            Err(KopiaError::ServerNotRunning)
+        ));
+
+        server.info = Some(KopiaServerInfo {
+            server_url: "https://127.0.0.1:51515".to_string(),
+            port: 51515,
+            password: "secret".to_string(),
+            control_password: None,
+            cert_sha256: "00".to_string(),
+            pid: 1,
+            startup_warnings: vec![],
+        });
+        server.certificate_pem = Some(String::new());
+        assert!(matches!(
+            server.get_control_client(),
+            Err(KopiaError::ControlApiUnavailable { .. })
+        ));
+
+        server.info.as_mut().unwrap().control_password = Some("control".to_string());
+        server.insecure_skip_cert_verify = true;
+        assert!(server.get_control_client().is_ok());
+    }
+
+    #[test]
+    fn test_fingerprint_matches() {
+        let der = b"not really a certificate";
+        let fingerprint = certificate_fingerprint(der);
+        let colons = fingerprint
+            .as_bytes()
+            .chunks(2)
+            .map(|pair| std::str::from_utf8(pair).unwrap().to_ascii_uppercase())
+            .collect::<Vec<_>>()
+            .join(":");
+
+        assert!(fingerprint_matches(&fingerprint, der));
+        assert!(fingerprint_matches(&colons, der));
+        assert!(!fingerprint_matches(&"00".repeat(32), der));
+        assert!(!fingerprint_matches(&fingerprint, b"another certificate"));
+        assert!(!fingerprint_matches("", der));
+    }
+
+    #[test]
+    fn test_pem_to_der() {
+        use base64::Engine;
+
+        let der = vec![0x30u8; 100];
+        let encoded = base64::prelude::BASE64_STANDARD.encode(&der);
+        let pem = format!(
+            "-----BEGIN CERTIFICATE-----\r\n{}\r\n{}\r\n-----END CERTIFICATE-----\r\n",
+            &encoded[..64],
+            &encoded[64..]
+        );
+
+        assert_eq!(pem_to_der(&pem), Some(der));
+        assert_eq!(pem_to_der("no certificate here"), None);
+        assert_eq!(
+            pem_to_der("-----BEGIN CERTIFICATE-----\n!!!\n-----END CERTIFICATE-----"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pinned_certificate_rejects_mismatched_fingerprint() {
+        use base64::Engine;
+
+        let pem = format!(
+            "-----BEGIN CERTIFICATE-----\n{}\n-----END CERTIFICATE-----\n",
+            base64::prelude::BASE64_STANDARD.encode([0x30u8; 32])
+        );
+        assert!(matches!(
+            pinned_certificate(&pem, &"00".repeat(32)),
+            Err(KopiaError::OperationFailed { .. })
+        ));
+    }
 }
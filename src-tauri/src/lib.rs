@@ -2,10 +2,19 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 // Module declarations
+mod app_state;
 mod commands;
 mod error;
+mod fan_out;
+mod kopia_cli;
 mod kopia_server;
+mod logging;
+mod metrics;
+mod power;
+mod quit;
+mod reconnect;
 mod server_manager;
+mod settings;
 mod types;
 
 // Windows-only modules
@@ -23,6 +32,7 @@ pub use windows_service::run_service;
 mod tests;
 
 use server_manager::{create_server_manager_state, ServerManagerState};
+use settings::{create_settings_state, SettingsState};
 use tauri::{
     menu::{Menu, MenuItem},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
@@ -59,6 +69,30 @@ fn hide_main_window(app: &tauri::AppHandle) {
     }
 }
 
+/// Start a graceful quit if the `waitForTasksOnQuit` setting is enabled
+///
+/// Returns false when the setting is off (the caller should quit right away)
+/// or a quit is already in progress.
+fn start_graceful_quit(
+    app: &tauri::AppHandle,
+    manager_state: &ServerManagerState,
+    settings_state: &SettingsState,
+) -> bool {
+    let settings = settings_state.lock().unwrap().get();
+    if !settings.wait_for_tasks_on_quit || !app.state::<app_state::AppState>().begin_quit() {
+        return false;
+    }
+
+    tauri::async_runtime::spawn(quit::run_graceful_quit(
+        app.clone(),
+        manager_state.clone(),
+        true,
+        std::time::Duration::from_secs(quit::DEFAULT_QUIT_WAIT_SECS),
+        fan_out::fan_out_timeout(None, settings.fan_out_timeout_secs),
+    ));
+    true
+}
+
 /// Auto-start Kopia servers for all discovered repositories on app launch
 async fn auto_start_servers(manager_state: ServerManagerState) -> error::Result<()> {
     // Discover repositories and start servers
@@ -99,7 +133,10 @@ async fn auto_start_servers(manager_state: ServerManagerState) -> error::Result<
         // Wait for server to be ready (outside lock)
         if let Some(waiter) = ready_waiter {
             match waiter.await {
-                Ok(()) => log::info!("Server for '{}' is ready", repo_id),
+                Ok(()) => {
+                    log::info!("Server for '{}' is ready", repo_id);
+                    commands::kopia::restore_pending_policy(&manager_state, &repo_id).await;
+                }
                 Err(e) => log::error!("Server for '{}' failed health check: {}", repo_id, e),
             }
         }
@@ -110,21 +147,66 @@ async fn auto_start_servers(manager_state: ServerManagerState) -> error::Result<
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    // Get config directory for ServerManager
-    let config_dir =
+    let default_config_dir =
         commands::kopia::get_default_config_dir().expect("Failed to determine config directory");
+    logging::init(&default_config_dir, commands::system::DEFAULT_LOG_LEVEL);
+
+    // Load app settings (always kept in the default config directory)
+    let settings_state = create_settings_state(&default_config_dir);
+
+    // Repository configs may have been moved elsewhere with relocate_config_dir
+    let config_dir = match settings_state.lock().unwrap().get().config_dir {
+        Some(dir) if std::path::Path::new(&dir).is_dir() => dir,
+        Some(dir) => {
+            log::warn!(
+                "Relocated config directory '{}' not found, using '{}'",
+                dir,
+                default_config_dir
+            );
+            default_config_dir.clone()
+        }
+        None => default_config_dir.clone(),
+    };
 
     // Initialize ServerManager state (manages multiple repositories)
     let manager_state = create_server_manager_state(&config_dir);
 
-    // Clone manager state for the exit handler (before it's moved into setup closure)
+    // Debugging aid: skip certificate pinning if enabled (and allowed in this build)
+    if settings_state
+        .lock()
+        .unwrap()
+        .get()
+        .insecure_skip_cert_verify
+    {
+        if let Err(e) = manager_state
+            .lock()
+            .unwrap()
+            .set_insecure_skip_cert_verify(true)
+        {
+            log::warn!("Ignoring insecureSkipCertVerify setting: {}", e);
+        }
+    }
+
+    if settings_state
+        .lock()
+        .unwrap()
+        .get()
+        .server_check_for_updates
+    {
+        manager_state.lock().unwrap().set_check_for_updates(true);
+    }
+
+    // Clone states for the exit handler (before they're moved into setup closure)
     let exit_manager_state = manager_state.clone();
+    let exit_settings_state = settings_state.clone();
 
     let app = tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_dialog::init())
+        .manage(app_state::AppState::new())
         .manage(manager_state.clone())
+        .manage(settings_state.clone())
         .setup(move |app| {
             // Create system tray menu
             let show_i = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
@@ -143,8 +225,9 @@ pub fn run() {
                 })?
                 .clone();
 
-            // Clone manager state for tray menu handler
+            // Clone states for tray menu handler
             let tray_manager_state = manager_state.clone();
+            let tray_settings_state = settings_state.clone();
 
             let _tray = TrayIconBuilder::new()
                 .icon(icon)
@@ -154,7 +237,19 @@ pub fn run() {
                     "show" => restore_main_window(app),
                     "hide" => hide_main_window(app),
                     "quit" => {
+                        if start_graceful_quit(app, &tray_manager_state, &tray_settings_state) {
+                            log::info!(
+                                "Quit requested from tray menu, waiting for running tasks..."
+                            );
+                            return;
+                        }
+
                         log::info!("Quit requested from tray menu, stopping all servers...");
+                        quit::restore_task_throttles_now(
+                            app,
+                            &tray_manager_state,
+                            &tray_settings_state,
+                        );
                         // Stop all Kopia servers before exiting
                         if let Err(e) = tray_manager_state.lock().unwrap().stop_all() {
                             log::error!("Failed to stop servers during quit: {}", e);
@@ -186,6 +281,21 @@ pub fn run() {
                     log::info!("You can start servers manually from the UI");
                 }
             });
+
+            // Pause/resume sources on battery or metered connections when enabled
+            tauri::async_runtime::spawn(power::run_power_monitor(
+                app.handle().clone(),
+                manager_state.clone(),
+                settings_state.clone(),
+            ));
+
+            // Restart unresponsive servers, and reconnect repositories after
+            // connectivity loss when enabled
+            tauri::async_runtime::spawn(reconnect::run_server_monitor(
+                app.handle().clone(),
+                manager_state.clone(),
+                settings_state.clone(),
+            ));
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -193,10 +303,18 @@ pub fn run() {
             commands::list_repositories,
             commands::add_repository,
             commands::remove_repository,
+            commands::adopt_existing_config,
+            commands::connect_external_server,
             // Kopia server lifecycle
             commands::kopia_server_start,
             commands::kopia_server_stop,
+            commands::kopia_server_stop_safe,
+            commands::server_flush,
+            commands::server_supports_endpoint,
+            commands::kopia_server_restart,
             commands::kopia_server_status,
+            commands::server_version,
+            commands::uptime_info,
             // Repository management
             commands::repository_status,
             commands::repository_connect,
@@ -205,24 +323,56 @@ pub fn run() {
             commands::repository_create,
             commands::repository_exists,
             commands::repository_get_algorithms,
+            commands::splitter_algorithms_with_info,
             commands::repository_update_description,
+            commands::repository_rename,
+            commands::repository_set_format_cache_duration,
+            commands::repository_set_permissive_cache,
+            commands::repository_get_max_pack_size,
+            commands::repository_set_max_pack_size,
+            commands::open_repository_storage,
+            commands::content_stats,
+            commands::blob_contents,
             commands::repository_get_throttle,
             commands::repository_set_throttle,
             // Snapshot sources
             commands::sources_list,
+            commands::get_default_source_identity,
+            commands::last_known_state,
             commands::snapshot_create,
+            commands::snapshot_create_with_override,
             commands::snapshot_upload,
             commands::snapshot_cancel,
             commands::snapshot_pause,
             commands::snapshot_resume,
+            commands::sources_pause_all,
+            commands::sources_resume_all,
+            commands::sources_summary,
+            commands::sources_prune,
+            commands::missed_snapshots,
+            commands::sources_by_staleness,
+            commands::run_missed_now,
             // Snapshot history
             commands::snapshots_list,
+            commands::snapshots_list_all,
+            commands::snapshots_export_csv,
             commands::snapshot_edit,
             commands::snapshot_delete,
+            commands::snapshot_verify,
+            commands::snapshot_create_and_verify,
+            commands::incomplete_snapshots,
+            commands::retention_preview,
+            commands::cleanup_incomplete_snapshots,
             // Snapshot browsing & restore
             commands::object_browse,
+            commands::browse_path,
+            commands::file_versions,
             commands::object_download,
+            commands::restore_files,
             commands::restore_start,
+            commands::restore_status,
+            commands::verify_restore,
+            commands::snapshot_tree_export,
             commands::mount_snapshot,
             commands::mounts_list,
             commands::mount_unmount,
@@ -230,25 +380,90 @@ pub fn run() {
             commands::policies_list,
             commands::policy_get,
             commands::policy_resolve,
+            commands::resolve_effective_ignores,
+            commands::policy_overrides,
             commands::policy_set,
             commands::policy_delete,
+            commands::policies_delete_batch,
+            commands::source_set_one_filesystem,
+            commands::apply_recommended_policy,
+            commands::source_get_one_filesystem,
+            commands::source_set_parallelism,
+            commands::source_get_parallelism,
+            commands::source_set_notify_on_error,
+            commands::task_notify_on_failure,
+            commands::test_globs,
+            commands::describe_cron,
+            commands::recommended_policy,
             // Tasks
             commands::tasks_list,
             commands::task_get,
             commands::task_logs,
+            commands::task_warnings,
             commands::task_cancel,
+            commands::task_throttle,
+            commands::tasks_clear_completed,
+            commands::upload_throughput_history,
             commands::tasks_summary,
+            commands::tasks_summary_by_kind,
+            commands::has_active_operations,
+            commands::overall_progress,
+            commands::source_progress,
+            commands::tasks_find_stuck,
+            commands::activity_feed,
+            commands::snapshot_excluded_paths,
+            commands::snapshot_content_stats,
+            // Maintenance
+            commands::maintenance_info,
+            commands::maintenance_run,
+            commands::maintenance_cancel,
+            commands::maintenance_owner,
+            commands::maintenance_claim,
+            commands::maintenance_run_if_due,
+            commands::maintenance_next_time,
+            commands::repository_health,
             // Utilities
             commands::path_resolve,
             commands::estimate_snapshot,
+            commands::estimate_snapshot_blocking,
             // Notifications
+            commands::notification_severity_levels,
+            commands::error_notifications,
             commands::notification_profiles_list,
             commands::notification_profile_create,
             commands::notification_profile_delete,
             commands::notification_profile_test,
+            commands::notification_profiles_export,
+            commands::notification_profiles_import,
             // System utilities
             commands::get_system_info,
+            commands::verify_binary_integrity,
+            commands::hash_directory,
+            commands::check_binary_compatibility,
             commands::get_current_user,
+            commands::get_command_metrics,
+            commands::set_log_level,
+            commands::get_log_level,
+            commands::get_effective_config,
+            commands::get_app_settings,
+            commands::power_aware_scheduling,
+            commands::set_insecure_skip_cert_verify,
+            commands::set_fan_out_timeout,
+            commands::set_wait_for_tasks_on_quit,
+            commands::set_auto_reconnect,
+            commands::set_developer_mode,
+            commands::get_server_update_check,
+            commands::set_server_check_for_updates,
+            commands::save_ui_state,
+            commands::get_ui_state,
+            commands::app_storage_usage,
+            commands::relocate_config_dir,
+            commands::find_orphaned_mounts,
+            commands::cleanup_orphaned_mount,
+            commands::quit_gracefully,
+            commands::get_power_state,
+            commands::vss_status,
+            commands::validate_restore_options,
             commands::select_folder,
             commands::save_file,
             // Windows Service (Windows only)
@@ -267,9 +482,20 @@ pub fn run() {
         .expect("error while building tauri application");
 
     // Run the app with cleanup handling
-    app.run(move |_app_handle, event| {
-        if let tauri::RunEvent::ExitRequested { .. } = event {
+    app.run(move |app_handle, event| {
+        if let tauri::RunEvent::ExitRequested { code, api, .. } = event {
+            // Closing the last window (no exit code) can wait for running tasks;
+            // the graceful quit exits with a code once it's done
+            if code.is_none()
+                && start_graceful_quit(app_handle, &exit_manager_state, &exit_settings_state)
+            {
+                log::info!("App exit requested, waiting for running tasks...");
+                api.prevent_exit();
+                return;
+            }
+
             log::info!("App exit requested, stopping all Kopia servers...");
+            quit::restore_task_throttles_now(app_handle, &exit_manager_state, &exit_settings_state);
 
             // Stop all Kopia servers before exit
             if let Err(e) = exit_manager_state.lock().unwrap().stop_all() {
@@ -0,0 +1,118 @@
+//! Application logging
+//!
+//! Log records go to stderr and to `kopia-desktop.log` in the default config
+//! directory, next to the settings file. The level is `log::max_level()`, so
+//! `set_log_level` takes effect immediately. Other crates (tauri, reqwest, ...)
+//! are capped at `info` so raising the level doesn't flood the file with their
+//! internals.
+//!
+//! The file is rotated at startup: once it exceeds `MAX_LOG_FILE_BYTES` it is
+//! renamed to `kopia-desktop.log.1`, replacing the previous one.
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Log file name (in the default config directory)
+pub const LOG_FILE_NAME: &str = "kopia-desktop.log";
+
+/// Size after which the log file is rotated at startup (10 MiB)
+const MAX_LOG_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Target prefix of this crate's log records
+const APP_TARGET: &str = "kopia_desktop_lib";
+
+/// Logger writing to stderr and a log file
+struct AppLogger {
+    file: Option<Mutex<File>>,
+}
+
+impl log::Log for AppLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        record_enabled(metadata.target(), metadata.level(), log::max_level())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format_record(
+            &chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false),
+            record.level(),
+            record.target(),
+            &record.args().to_string(),
+        );
+        eprint!("{}", line);
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(file) = &self.file {
+            let _ = file.lock().unwrap().flush();
+        }
+    }
+}
+
+/// Whether a record passes the filter
+///
+/// Records of other crates are capped at `info`.
+pub(crate) fn record_enabled(target: &str, level: log::Level, max: log::LevelFilter) -> bool {
+    let max = if target.starts_with(APP_TARGET) {
+        max
+    } else {
+        max.min(log::LevelFilter::Info)
+    };
+    level <= max
+}
+
+/// Format a record as one log line, including the newline
+pub(crate) fn format_record(
+    timestamp: &str,
+    level: log::Level,
+    target: &str,
+    message: &str,
+) -> String {
+    format!("{} {:<5} {}: {}\n", timestamp, level, target, message)
+}
+
+/// Path of the log file in `config_dir`
+pub fn log_file_path(config_dir: &str) -> PathBuf {
+    Path::new(config_dir).join(LOG_FILE_NAME)
+}
+
+/// Open the log file for appending, rotating it first if it's too large
+fn open_log_file(path: &Path) -> std::io::Result<File> {
+    if std::fs::metadata(path).is_ok_and(|meta| meta.len() > MAX_LOG_FILE_BYTES) {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        std::fs::rename(path, rotated)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Install the app logger with `level` as the initial level
+///
+/// Logs to stderr only if the log file can't be opened. Does nothing if a
+/// logger is already installed.
+pub fn init(config_dir: &str, level: log::LevelFilter) {
+    let path = log_file_path(config_dir);
+    let (file, error) = match open_log_file(&path) {
+        Ok(file) => (Some(Mutex::new(file)), None),
+        Err(e) => (None, Some(e)),
+    };
+
+    let logger: &'static AppLogger = Box::leak(Box::new(AppLogger { file }));
+    if log::set_logger(logger).is_err() {
+        return;
+    }
+    log::set_max_level(level);
+
+    match error {
+        None => log::info!("Logging to {}", path.display()),
+        Some(e) => log::warn!("Failed to open log file {}: {}", path.display(), e),
+    }
+}
@@ -0,0 +1,181 @@
+//! Per-command metrics collection
+//!
+//! Records call count, error count and a latency histogram for every Kopia
+//! command, keyed by command name (e.g. `snapshots_list`). Commands take a
+//! [`CommandTimer`] argument, which Tauri fills in with the command's name and
+//! the registry kept in `AppState`, and pass their result through
+//! [`CommandTimer::finish`].
+//!
+//! All updates on the hot path are atomic; the map lock is only taken for
+//! writing the first time a command is seen.
+
+use crate::app_state::AppState;
+use crate::error::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tauri::ipc::{CommandArg, CommandItem, InvokeError};
+use tauri::Runtime;
+
+/// Upper bounds (in milliseconds) of the latency histogram buckets.
+/// Latencies above the last bound fall into an overflow bucket.
+const LATENCY_BUCKETS_MS: [u64; 12] =
+    [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000, 30000];
+
+/// Counters for a single command
+#[derive(Default)]
+struct OperationMetrics {
+    count: AtomicU64,
+    errors: AtomicU64,
+    max_ms: AtomicU64,
+    /// One counter per bucket plus the overflow bucket
+    buckets: [AtomicU64; LATENCY_BUCKETS_MS.len() + 1],
+}
+
+impl OperationMetrics {
+    fn record(&self, latency_ms: u64, success: bool) {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+        }
+        self.max_ms.fetch_max(latency_ms, Ordering::Relaxed);
+
+        let bucket = LATENCY_BUCKETS_MS
+            .iter()
+            .position(|&bound| latency_ms <= bound)
+            .unwrap_or(LATENCY_BUCKETS_MS.len());
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Estimate a percentile as the upper bound of the bucket containing it
+    fn percentile(&self, percentile: f64, count: u64) -> u64 {
+        if count == 0 {
+            return 0;
+        }
+
+        let target = ((count as f64) * percentile).ceil().max(1.0) as u64;
+        let max_ms = self.max_ms.load(Ordering::Relaxed);
+        let mut cumulative = 0;
+
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            if cumulative >= target {
+                // Never report more than the slowest call actually observed
+                return LATENCY_BUCKETS_MS
+                    .get(i)
+                    .map_or(max_ms, |&bound| bound.min(max_ms));
+            }
+        }
+        max_ms
+    }
+
+    fn summary(&self) -> CommandMetrics {
+        let count = self.count.load(Ordering::Relaxed);
+        CommandMetrics {
+            count,
+            p50_ms: self.percentile(0.50, count),
+            p95_ms: self.percentile(0.95, count),
+            errors: self.errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Summary of the metrics recorded for one command
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommandMetrics {
+    pub count: u64,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub errors: u64,
+}
+
+/// Registry of metrics keyed by command name
+#[derive(Default)]
+pub struct MetricsRegistry {
+    commands: RwLock<HashMap<String, Arc<OperationMetrics>>>,
+}
+
+impl MetricsRegistry {
+    /// Record one call of `command`
+    pub fn record(&self, command: &str, latency: Duration, success: bool) {
+        let latency_ms = latency.as_millis().min(u64::MAX as u128) as u64;
+
+        let existing = self.commands.read().unwrap().get(command).cloned();
+        let metrics = match existing {
+            Some(metrics) => metrics,
+            None => self
+                .commands
+                .write()
+                .unwrap()
+                .entry(command.to_string())
+                .or_default()
+                .clone(),
+        };
+
+        metrics.record(latency_ms, success);
+    }
+
+    /// Summaries for all commands recorded so far
+    pub fn snapshot(&self) -> HashMap<String, CommandMetrics> {
+        self.commands
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(command, metrics)| (command.clone(), metrics.summary()))
+            .collect()
+    }
+}
+
+/// Times a command and records its outcome in the app's metrics registry
+///
+/// Used as a command argument; it isn't passed by the frontend. The call is
+/// recorded when the timer is dropped, at the end of the command. It counts
+/// as successful only if the command's result went through
+/// [`CommandTimer::finish`], so commands leaving early with `?` (or with
+/// `return Err(..)`) are recorded as errors without extra code.
+pub struct CommandTimer {
+    name: &'static str,
+    registry: Arc<MetricsRegistry>,
+    started: Instant,
+    success: bool,
+}
+
+impl CommandTimer {
+    /// Start timing `name`, recording into `registry`
+    pub fn new(name: &'static str, registry: Arc<MetricsRegistry>) -> Self {
+        Self {
+            name,
+            registry,
+            started: Instant::now(),
+            success: false,
+        }
+    }
+
+    /// Note whether the command succeeded and pass its result through
+    pub fn finish<T>(mut self, result: Result<T>) -> Result<T> {
+        self.success = result.is_ok();
+        result
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        self.registry
+            .record(self.name, self.started.elapsed(), self.success);
+    }
+}
+
+impl<'a, R: Runtime> CommandArg<'a, R> for CommandTimer {
+    fn from_command(command: CommandItem<'a, R>) -> std::result::Result<Self, InvokeError> {
+        let app_state = command
+            .message
+            .state_ref()
+            .try_get::<AppState>()
+            .ok_or_else(|| InvokeError::from("App state is not managed"))?;
+
+        Ok(Self::new(command.name, app_state.metrics()))
+    }
+}
@@ -0,0 +1,396 @@
+//! Power-aware snapshot scheduling
+//!
+//! When the `powerAwareScheduling` setting is enabled, a background task checks
+//! whether the machine runs on battery or a metered network connection and
+//! pauses the snapshot sources of every running server while it does, including
+//! servers that start in the meantime. Once the machine is back on AC power and
+//! an unmetered network, the sources the monitor paused are resumed; sources
+//! that were already paused are left alone.
+//!
+//! Detection is best effort:
+//! - Linux: `/sys/class/power_supply` and NetworkManager's `Metered` property
+//! - macOS: `pmset -g batt`
+//! - Windows: `GetSystemPowerStatus`
+//!
+//! When neither battery nor metered state can be detected, the monitor does nothing.
+
+use crate::commands::kopia::{fetch_sources, set_source_paused};
+use crate::error::Result;
+use crate::fan_out::{fan_out, fan_out_timeout, FanOutResult};
+use crate::server_manager::ServerManagerState;
+use crate::settings::SettingsState;
+use crate::types::{SourceInfo, SourcesResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted when power-aware scheduling pauses or resumes sources
+pub const POWER_STATE_EVENT: &str = "power-state-changed";
+
+/// How often the power state is checked
+const POWER_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Detected power and network state (None = unknown on this platform)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerState {
+    pub on_battery: Option<bool>,
+    pub metered: Option<bool>,
+}
+
+impl PowerState {
+    /// Whether scheduled snapshots should be deferred
+    pub fn should_pause(&self) -> bool {
+        self.on_battery == Some(true) || self.metered == Some(true)
+    }
+
+    /// Whether anything could be detected at all
+    pub fn is_known(&self) -> bool {
+        self.on_battery.is_some() || self.metered.is_some()
+    }
+}
+
+/// Payload of the `power-state-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerStateEvent {
+    #[serde(flatten)]
+    pub state: PowerState,
+    /// Whether sources are currently paused by power-aware scheduling
+    pub paused: bool,
+}
+
+/// Detect the current power and network state
+pub fn detect_power_state() -> PowerState {
+    PowerState {
+        on_battery: detect_on_battery(),
+        metered: detect_metered(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn detect_on_battery() -> Option<bool> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+
+    let mut has_battery = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        match kind.trim() {
+            "Mains" | "USB" => {
+                let online = std::fs::read_to_string(path.join("online")).unwrap_or_default();
+                if online.trim() == "1" {
+                    return Some(false);
+                }
+            }
+            "Battery" => has_battery = true,
+            _ => {}
+        }
+    }
+
+    // Without a battery there's nothing to defer for (desktops)
+    has_battery.then_some(true)
+}
+
+#[cfg(target_os = "macos")]
+fn detect_on_battery() -> Option<bool> {
+    let output = std::process::Command::new("pmset")
+        .args(["-g", "batt"])
+        .output()
+        .ok()?;
+    parse_pmset_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(windows)]
+fn detect_on_battery() -> Option<bool> {
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+    if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+        return None;
+    }
+
+    // ACLineStatus: 0 = offline, 1 = online, 255 = unknown
+    match status.ACLineStatus {
+        0 => Some(true),
+        1 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+fn detect_on_battery() -> Option<bool> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn detect_metered() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nm_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn detect_metered() -> Option<bool> {
+    None
+}
+
+/// Parse `pmset -g batt` output (e.g. "Now drawing from 'Battery Power'")
+#[cfg(any(target_os = "macos", test))]
+pub(crate) fn parse_pmset_output(output: &str) -> Option<bool> {
+    if output.contains("'Battery Power'") {
+        Some(true)
+    } else if output.contains("'AC Power'") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Parse NetworkManager's `Metered` property as printed by busctl (e.g. "u 1")
+///
+/// NMMetered values: 0 = unknown, 1 = yes, 2 = no, 3 = guess yes, 4 = guess no
+#[cfg(any(target_os = "linux", test))]
+pub(crate) fn parse_nm_metered(output: &str) -> Option<bool> {
+    match output.split_whitespace().nth(1)? {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+/// Sources paused by the power monitor, by repository ID
+///
+/// Only these are resumed, so sources the user paused stay paused. A
+/// repository without an entry hasn't been handled yet, e.g. because its server
+/// started while on battery.
+#[derive(Debug, Default)]
+pub(crate) struct MonitorPauses(HashMap<String, Vec<SourceInfo>>);
+
+impl MonitorPauses {
+    /// Whether any repository has sources paused by the monitor
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Forget repositories whose server is no longer running
+    ///
+    /// Pauses don't survive a server restart, so there's nothing to resume.
+    pub fn retain_running(&mut self, running: &[String]) {
+        self.0.retain(|repo_id, _| running.contains(repo_id));
+    }
+
+    /// Whether the sources of a repository have been paused already
+    pub fn contains(&self, repo_id: &str) -> bool {
+        self.0.contains_key(repo_id)
+    }
+
+    /// Record the sources paused for a repository
+    pub fn record(&mut self, repo_id: String, sources: Vec<SourceInfo>) {
+        self.0.insert(repo_id, sources);
+    }
+
+    /// Sources paused for a repository
+    pub fn sources(&self, repo_id: &str) -> &[SourceInfo] {
+        self.0.get(repo_id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Forget a repository once its sources are resumed
+    pub fn remove(&mut self, repo_id: &str) {
+        self.0.remove(repo_id);
+    }
+}
+
+/// Sources that aren't paused yet
+pub(crate) fn unpaused_sources(sources: &SourcesResponse) -> Vec<SourceInfo> {
+    sources
+        .sources
+        .iter()
+        .filter(|s| s.status != "PAUSED")
+        .map(|s| s.source.clone())
+        .collect()
+}
+
+/// Pause the sources of a server that aren't paused yet
+///
+/// Returns the sources that were paused.
+async fn pause_unpaused_sources(
+    client: &reqwest::Client,
+    server_url: &str,
+) -> Result<Vec<SourceInfo>> {
+    let sources = fetch_sources(client, server_url).await?;
+
+    let mut paused = Vec::new();
+    for source in unpaused_sources(&sources) {
+        match set_source_paused(client, server_url, &source, true).await {
+            Ok(_) => paused.push(source),
+            Err(e) => log::warn!("Failed to pause {}: {}", source, e),
+        }
+    }
+    Ok(paused)
+}
+
+/// Resume the given sources of a server
+async fn resume_sources(
+    client: &reqwest::Client,
+    server_url: &str,
+    sources: &[SourceInfo],
+) -> Result<()> {
+    for source in sources {
+        set_source_paused(client, server_url, source, false).await?;
+    }
+    Ok(())
+}
+
+/// Pause the sources of running servers that haven't been handled yet
+///
+/// Servers are contacted concurrently so one unresponsive server doesn't hold
+/// up the others. Servers that fail are retried on the next check.
+async fn pause_running_servers(
+    manager: &ServerManagerState,
+    pauses: &mut MonitorPauses,
+    timeout: Duration,
+) {
+    let servers = manager.lock().unwrap().running_servers();
+    let running: Vec<String> = servers
+        .iter()
+        .map(|(repo_id, _, _)| repo_id.clone())
+        .collect();
+    pauses.retain_running(&running);
+
+    let requests: Vec<_> = servers
+        .into_iter()
+        .filter(|(repo_id, _, _)| !pauses.contains(repo_id))
+        .map(|(repo_id, server_url, client)| {
+            (repo_id, async move {
+                pause_unpaused_sources(&client, &server_url).await
+            })
+        })
+        .collect();
+    if requests.is_empty() {
+        return;
+    }
+
+    let result = fan_out(requests, timeout).await;
+    log_partial("pause", &result);
+    for (repo_id, sources) in result.completed {
+        pauses.record(repo_id, sources);
+    }
+}
+
+/// Resume the sources paused by the monitor
+///
+/// Repositories that fail keep their record and are retried on the next check.
+async fn resume_paused_servers(
+    manager: &ServerManagerState,
+    pauses: &mut MonitorPauses,
+    timeout: Duration,
+) {
+    let servers = manager.lock().unwrap().running_servers();
+    let running: Vec<String> = servers
+        .iter()
+        .map(|(repo_id, _, _)| repo_id.clone())
+        .collect();
+    pauses.retain_running(&running);
+
+    let requests: Vec<_> = servers
+        .into_iter()
+        .filter(|(repo_id, _, _)| pauses.contains(repo_id))
+        .map(|(repo_id, server_url, client)| {
+            let sources = pauses.sources(&repo_id).to_vec();
+            (repo_id, async move {
+                resume_sources(&client, &server_url, &sources).await
+            })
+        })
+        .collect();
+
+    let result = fan_out(requests, timeout).await;
+    log_partial("resume", &result);
+    for (repo_id, _) in result.completed {
+        pauses.remove(&repo_id);
+    }
+}
+
+/// Log repositories whose sources couldn't be paused or resumed
+fn log_partial<T>(action: &str, result: &FanOutResult<T>) {
+    if result.is_partial() {
+        log::warn!(
+            "Failed to {} sources for {} of {} repositories",
+            action,
+            result.timed_out.len() + result.failed.len(),
+            result.timed_out.len() + result.failed.len() + result.completed.len()
+        );
+    }
+}
+
+/// Run the power-aware scheduling loop
+///
+/// Sources are only resumed if they were paused by this monitor, so sources the
+/// user paused manually stay paused. While paused, servers that start are
+/// paused as well.
+pub async fn run_power_monitor(
+    app: AppHandle,
+    manager: ServerManagerState,
+    settings: SettingsState,
+) {
+    let mut paused = false;
+    let mut pauses = MonitorPauses::default();
+    let mut interval = tokio::time::interval(Duration::from_secs(POWER_CHECK_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let app_settings = settings.lock().unwrap().get();
+        let enabled = app_settings.power_aware_scheduling;
+        let state = if enabled {
+            tokio::task::spawn_blocking(detect_power_state)
+                .await
+                .unwrap_or_default()
+        } else {
+            PowerState::default()
+        };
+
+        if enabled && !state.is_known() && !paused && pauses.is_empty() {
+            // Nothing to go on; don't touch the sources
+            continue;
+        }
+
+        let should_pause = enabled && state.should_pause();
+        if should_pause != paused {
+            log::info!(
+                "Power-aware scheduling: {} sources (battery: {:?}, metered: {:?})",
+                if should_pause { "pausing" } else { "resuming" },
+                state.on_battery,
+                state.metered
+            );
+        }
+
+        let timeout = fan_out_timeout(None, app_settings.fan_out_timeout_secs);
+        if should_pause {
+            pause_running_servers(&manager, &mut pauses, timeout).await;
+        } else if !pauses.is_empty() {
+            resume_paused_servers(&manager, &mut pauses, timeout).await;
+        }
+
+        if should_pause == paused {
+            continue;
+        }
+        paused = should_pause;
+
+        if let Err(e) = app.emit(POWER_STATE_EVENT, PowerStateEvent { state, paused }) {
+            log::debug!("Failed to emit power state event: {}", e);
+        }
+    }
+}
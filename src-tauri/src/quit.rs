@@ -0,0 +1,174 @@
+//! Graceful quit
+//!
+//! Quitting stops every Kopia server, which interrupts snapshots in progress.
+//! A graceful quit can first wait for running tasks to finish (up to a timeout),
+//! emitting `quit-waiting` events so the UI can show what it's waiting for.
+//! Tasks still running after that are canceled before the servers are stopped.
+//! Servers that can't be checked are assumed to be busy and waited for too.
+//!
+//! The tray "Quit" and closing the last window use this flow when the
+//! `waitForTasksOnQuit` setting is enabled.
+//!
+//! Every quit puts back the repository limits `task_throttle` lowered, since
+//! the watchers that would restore them stop with the app.
+
+use crate::app_state::AppState;
+use crate::commands::kopia::{
+    cancel_task, collect_active_operations, fetch_tasks, restore_throttle,
+};
+use crate::fan_out::{fan_out, fan_out_timeout};
+use crate::server_manager::ServerManagerState;
+use crate::settings::SettingsState;
+use crate::types::PolicyTarget;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Event emitted while waiting for running tasks before quitting
+pub const QUIT_WAITING_EVENT: &str = "quit-waiting";
+
+/// How long the tray and window-close quit wait for running tasks
+pub const DEFAULT_QUIT_WAIT_SECS: u64 = 300;
+
+/// How often running tasks are checked while waiting
+const QUIT_POLL_INTERVAL_SECS: u64 = 2;
+
+/// Payload of the `quit-waiting` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuitWaitingEvent {
+    /// Number of tasks still running
+    pub count: usize,
+    pub kinds: Vec<String>,
+    /// Seconds left before remaining tasks are canceled
+    pub remaining_secs: u64,
+}
+
+/// Quit the app, optionally waiting up to `timeout` for running tasks
+///
+/// `request_timeout` limits each request to a Kopia server.
+pub async fn run_graceful_quit(
+    app: AppHandle,
+    manager: ServerManagerState,
+    wait: bool,
+    timeout: Duration,
+    request_timeout: Duration,
+) {
+    if wait {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let active = collect_active_operations(&manager, None, false, request_timeout).await;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if !active.may_be_active() || remaining.is_zero() {
+                break;
+            }
+
+            if active.partial {
+                log::warn!(
+                    "Couldn't check tasks of {}, waiting for them as well",
+                    active.failed_repo_ids.join(", ")
+                );
+            }
+
+            log::info!(
+                "Waiting for {} running task(s) before quitting ({}s left)",
+                active.count,
+                remaining.as_secs()
+            );
+            let event = QuitWaitingEvent {
+                count: active.count,
+                kinds: active.kinds,
+                remaining_secs: remaining.as_secs(),
+            };
+            if let Err(e) = app.emit(QUIT_WAITING_EVENT, event) {
+                log::debug!("Failed to emit quit waiting event: {}", e);
+            }
+
+            tokio::time::sleep(remaining.min(Duration::from_secs(QUIT_POLL_INTERVAL_SECS))).await;
+        }
+    }
+
+    cancel_running_tasks(&manager, request_timeout).await;
+    restore_task_throttles(&app, &manager, request_timeout).await;
+
+    log::info!("Stopping all Kopia servers before quitting...");
+    if let Err(e) = manager.lock().unwrap().stop_all() {
+        log::error!("Failed to stop servers during quit: {}", e);
+    }
+    app.exit(0);
+}
+
+/// Cancel running tasks on every running server so they end cleanly
+async fn cancel_running_tasks(manager: &ServerManagerState, request_timeout: Duration) {
+    let servers = manager.lock().unwrap().running_servers();
+
+    let requests = servers
+        .into_iter()
+        .map(|(repo_id, server_url, client)| {
+            (repo_id, async move {
+                let tasks = fetch_tasks(&client, &server_url).await?;
+                for task in tasks.iter().filter(|t| t.status == "RUNNING") {
+                    log::warn!(
+                        "Canceling {} task '{}' to quit",
+                        task.kind,
+                        task.description
+                    );
+                    if let Err(e) = cancel_task(&client, &server_url, &task.id).await {
+                        log::warn!("Failed to cancel task {}: {}", task.id, e);
+                    }
+                }
+                Ok(())
+            })
+        })
+        .collect();
+
+    fan_out(requests, request_timeout).await;
+}
+
+/// Restore the limits of repositories throttled by `task_throttle`
+///
+/// Must run before the servers are stopped.
+pub async fn restore_task_throttles(
+    app: &AppHandle,
+    manager: &ServerManagerState,
+    request_timeout: Duration,
+) {
+    let throttled = app.state::<AppState>().task_throttles().repo_ids();
+    if throttled.is_empty() {
+        return;
+    }
+
+    let servers = manager.lock().unwrap().running_servers();
+    let requests = servers
+        .into_iter()
+        .filter(|(repo_id, _, _)| throttled.contains(repo_id))
+        .map(|(repo_id, server_url, client)| {
+            let app = app.clone();
+            (repo_id.clone(), async move {
+                let app_state = app.state::<AppState>();
+                let _guard = app_state
+                    .lock_resource(&repo_id, &PolicyTarget::default())
+                    .await;
+                if let Some(throttle) = app_state.task_throttles().take(&repo_id) {
+                    log::info!("Restoring throttle limits of '{}' before quitting", repo_id);
+                    restore_throttle(&client, &server_url, &repo_id, &throttle).await;
+                }
+                Ok(())
+            })
+        })
+        .collect();
+
+    fan_out(requests, request_timeout).await;
+}
+
+/// Blocking `restore_task_throttles`, for quits that don't wait for tasks
+pub fn restore_task_throttles_now(
+    app: &AppHandle,
+    manager: &ServerManagerState,
+    settings: &SettingsState,
+) {
+    let request_timeout =
+        fan_out_timeout(None, settings.lock().unwrap().get().fan_out_timeout_secs);
+    tauri::async_runtime::block_on(restore_task_throttles(app, manager, request_timeout));
+}
@@ -0,0 +1,444 @@
+//! Server monitoring and automatic repository reconnection
+//!
+//! `run_server_monitor` is the only background task that restarts servers.
+//! It restarts servers whose process is still alive but that stopped
+//! answering HTTP requests, and reconnects lost repositories as described
+//! below.
+//!
+//! Repositories on network drives or remote storage become unusable when the
+//! machine loses connectivity, and stay broken until reconnected by hand. When
+//! the `autoReconnect` setting is enabled, a background task checks the status
+//! of every running server. Once a repository that was connected stops
+//! reporting as connected (or its status request fails) for
+//! `RECONNECT_FAILURE_THRESHOLD` checks in a row, the monitor re-opens it by
+//! restarting its server, which reconnects using the saved config, and emits
+//! `repository-reconnected` when that succeeds.
+//!
+//! Reconnect attempts back off from `RECONNECT_INITIAL_BACKOFF_SECS` up to
+//! `RECONNECT_MAX_BACKOFF_SECS` while the storage stays unreachable.
+//! Repositories the user stopped or disconnected are never restarted (see
+//! `ServerManager::is_stopped_by_user`), and external servers aren't checked.
+
+use crate::commands::kopia::wait_for_repository_open;
+use crate::fan_out::{fan_out, fan_out_timeout};
+use crate::kopia_server::{
+    HeartbeatTracker, ServerUnhealthyEvent, HEARTBEAT_INTERVAL_SECS, HEARTBEAT_TIMEOUT_SECS,
+    SERVER_UNHEALTHY_EVENT,
+};
+use crate::server_manager::ServerManagerState;
+use crate::settings::SettingsState;
+use crate::types::RepositoryStatus;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted when a lost repository is connected again
+pub const REPOSITORY_RECONNECTED_EVENT: &str = "repository-reconnected";
+
+/// Failed checks in a row after which a repository is considered lost
+pub(crate) const RECONNECT_FAILURE_THRESHOLD: u32 = 3;
+
+/// Minimum time between the first failed check and the first reconnect
+/// attempt, doubled after each failed attempt
+pub(crate) const RECONNECT_INITIAL_BACKOFF_SECS: u64 = 30;
+
+/// Upper bound for the delay between reconnect attempts
+pub(crate) const RECONNECT_MAX_BACKOFF_SECS: u64 = 600;
+
+/// How long a restarted server gets to open the repository
+const RECONNECT_OPEN_TIMEOUT_SECS: u64 = 60;
+
+/// Payload of the `repository-reconnected` event
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryReconnectedEvent {
+    pub repo_id: String,
+    /// Reconnect attempts made (0 if the repository recovered by itself)
+    pub attempts: u32,
+    /// Seconds the repository was unreachable
+    pub downtime_secs: u64,
+}
+
+/// What the monitor should do after a status check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReconnectAction {
+    None,
+    /// Try to reconnect now
+    Reconnect,
+    /// The repository is reachable again after being lost
+    Recovered(RepositoryReconnectedEvent),
+}
+
+/// Connectivity of a repository the monitor has seen connected
+#[derive(Debug, Clone)]
+enum RepoHealth {
+    Connected,
+    /// Checks failed, but not yet long enough to reconnect
+    Failing {
+        since: Instant,
+        failures: u32,
+    },
+    Lost {
+        since: Instant,
+        attempts: u32,
+        next_attempt: Instant,
+    },
+}
+
+/// Tracks per-repository connectivity and decides when to reconnect
+#[derive(Debug, Default)]
+pub struct ReconnectTracker {
+    repos: HashMap<String, RepoHealth>,
+}
+
+impl ReconnectTracker {
+    /// Record a status check result
+    ///
+    /// Repositories are only reconnected after they were seen connected, so a
+    /// server that never opened its repository is left alone. A reconnect
+    /// needs `RECONNECT_FAILURE_THRESHOLD` failed checks in a row, the first
+    /// at least `RECONNECT_INITIAL_BACKOFF_SECS` ago, so a single failed poll
+    /// doesn't restart a server.
+    pub fn observe(&mut self, repo_id: &str, connected: bool, now: Instant) -> ReconnectAction {
+        match (self.repos.get(repo_id).cloned(), connected) {
+            (
+                Some(RepoHealth::Lost {
+                    since, attempts, ..
+                }),
+                true,
+            ) => {
+                self.repos
+                    .insert(repo_id.to_string(), RepoHealth::Connected);
+                ReconnectAction::Recovered(RepositoryReconnectedEvent {
+                    repo_id: repo_id.to_string(),
+                    attempts,
+                    downtime_secs: now.saturating_duration_since(since).as_secs(),
+                })
+            }
+            (_, true) => {
+                self.repos
+                    .insert(repo_id.to_string(), RepoHealth::Connected);
+                ReconnectAction::None
+            }
+            (Some(RepoHealth::Connected), false) => {
+                self.repos.insert(
+                    repo_id.to_string(),
+                    RepoHealth::Failing {
+                        since: now,
+                        failures: 1,
+                    },
+                );
+                self.observe_failing(repo_id, now)
+            }
+            (Some(RepoHealth::Failing { since, failures }), false) => {
+                self.repos.insert(
+                    repo_id.to_string(),
+                    RepoHealth::Failing {
+                        since,
+                        failures: failures + 1,
+                    },
+                );
+                self.observe_failing(repo_id, now)
+            }
+            (Some(RepoHealth::Lost { next_attempt, .. }), false) if now >= next_attempt => {
+                ReconnectAction::Reconnect
+            }
+            _ => ReconnectAction::None,
+        }
+    }
+
+    /// Mark a failing repository as lost once it failed for long enough
+    fn observe_failing(&mut self, repo_id: &str, now: Instant) -> ReconnectAction {
+        let Some(RepoHealth::Failing { since, failures }) = self.repos.get(repo_id).cloned() else {
+            return ReconnectAction::None;
+        };
+        if failures < RECONNECT_FAILURE_THRESHOLD
+            || now < since + Duration::from_secs(RECONNECT_INITIAL_BACKOFF_SECS)
+        {
+            return ReconnectAction::None;
+        }
+
+        log::warn!(
+            "Repository '{}' unreachable for {} checks",
+            repo_id,
+            failures
+        );
+        self.repos.insert(
+            repo_id.to_string(),
+            RepoHealth::Lost {
+                since,
+                attempts: 0,
+                next_attempt: now,
+            },
+        );
+        ReconnectAction::Reconnect
+    }
+
+    /// Record a failed reconnect attempt and schedule the next one
+    pub fn reconnect_failed(&mut self, repo_id: &str, now: Instant) {
+        if let Some(RepoHealth::Lost {
+            attempts,
+            next_attempt,
+            ..
+        }) = self.repos.get_mut(repo_id)
+        {
+            *attempts += 1;
+            *next_attempt = now + reconnect_backoff(*attempts);
+        }
+    }
+
+    /// Forget connected repositories whose server was stopped
+    ///
+    /// Lost repositories are kept even without a running server, so a server
+    /// that failed to restart is retried.
+    pub fn retain_running(&mut self, running: &[String]) {
+        self.repos
+            .retain(|id, health| matches!(health, RepoHealth::Lost { .. }) || running.contains(id));
+    }
+
+    /// Forget a repository
+    pub fn remove(&mut self, repo_id: &str) {
+        self.repos.remove(repo_id);
+    }
+
+    /// Repositories currently considered lost
+    pub fn lost_repos(&self) -> Vec<String> {
+        self.repos
+            .iter()
+            .filter(|(_, health)| matches!(health, RepoHealth::Lost { .. }))
+            .map(|(id, _)| id.clone())
+            .collect()
+    }
+}
+
+/// Delay after `attempts` failed reconnect attempts
+pub fn reconnect_backoff(attempts: u32) -> Duration {
+    let secs = RECONNECT_INITIAL_BACKOFF_SECS
+        .saturating_mul(1u64 << attempts.saturating_sub(1).min(16))
+        .min(RECONNECT_MAX_BACKOFF_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Restart a repository's server and wait until it's ready
+async fn restart(manager: &ServerManagerState, repo_id: &str) -> crate::error::Result<()> {
+    let ready_waiter = {
+        let mut manager_guard = manager.lock().unwrap();
+        manager_guard.restart_server(repo_id)?;
+        manager_guard.get_ready_waiter(repo_id)?
+    };
+    ready_waiter.await
+}
+
+/// Restart a repository's server and wait until it has opened the repository
+async fn reconnect(manager: &ServerManagerState, repo_id: &str) -> crate::error::Result<bool> {
+    restart(manager, repo_id).await?;
+
+    let server = manager
+        .lock()
+        .unwrap()
+        .running_servers()
+        .into_iter()
+        .find(|(id, _, _)| id == repo_id);
+    let Some((_, server_url, client)) = server else {
+        return Ok(false);
+    };
+
+    let timeout = Duration::from_secs(RECONNECT_OPEN_TIMEOUT_SECS);
+    Ok(wait_for_repository_open(&client, &server_url, timeout)
+        .await?
+        .is_some())
+}
+
+/// Result of checking a running server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ServerCheck {
+    /// No answer in time, or a 5xx response
+    Unresponsive,
+    /// The server answers; whether its repository is connected
+    Responsive { connected: bool },
+}
+
+/// Check that a server answers and whether its repository is connected
+async fn check_server(client: &reqwest::Client, server_url: &str) -> ServerCheck {
+    let response = match client
+        .get(format!("{}/api/v1/repo/status", server_url))
+        .timeout(Duration::from_secs(HEARTBEAT_TIMEOUT_SECS))
+        .send()
+        .await
+    {
+        Ok(response) if response.status().as_u16() < 500 => response,
+        _ => return ServerCheck::Unresponsive,
+    };
+
+    let connected = response
+        .json::<RepositoryStatus>()
+        .await
+        .is_ok_and(|status| status.connected);
+    ServerCheck::Responsive { connected }
+}
+
+/// Restart a server that stopped responding
+async fn restart_unresponsive(
+    app: &AppHandle,
+    manager: &ServerManagerState,
+    repo_id: &str,
+    failures: u32,
+) {
+    log::warn!(
+        "Server for '{}' stopped responding ({} failed heartbeats), restarting",
+        repo_id,
+        failures
+    );
+    let event = ServerUnhealthyEvent {
+        repo_id: repo_id.to_string(),
+        failures,
+    };
+    if let Err(e) = app.emit(SERVER_UNHEALTHY_EVENT, event) {
+        log::debug!("Failed to emit server unhealthy event: {}", e);
+    }
+
+    match restart(manager, repo_id).await {
+        Ok(()) => log::info!("Server for '{}' restarted", repo_id),
+        Err(e) => log::error!("Failed to restart server for '{}': {}", repo_id, e),
+    }
+}
+
+/// Feed a connectivity check to the reconnect tracker and act on it
+async fn handle_connectivity(
+    app: &AppHandle,
+    manager: &ServerManagerState,
+    tracker: &mut ReconnectTracker,
+    repo_id: &str,
+    connected: bool,
+) {
+    let event = match tracker.observe(repo_id, connected, Instant::now()) {
+        ReconnectAction::None => return,
+        ReconnectAction::Recovered(event) => event,
+        ReconnectAction::Reconnect => {
+            log::info!("Reconnecting repository '{}'", repo_id);
+            match reconnect(manager, repo_id).await {
+                Ok(true) => match tracker.observe(repo_id, true, Instant::now()) {
+                    ReconnectAction::Recovered(mut event) => {
+                        event.attempts += 1;
+                        event
+                    }
+                    _ => return,
+                },
+                Ok(false) => {
+                    log::warn!("Repository '{}' is still unreachable", repo_id);
+                    tracker.reconnect_failed(repo_id, Instant::now());
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("Failed to reconnect repository '{}': {}", repo_id, e);
+                    tracker.reconnect_failed(repo_id, Instant::now());
+                    return;
+                }
+            }
+        }
+    };
+
+    log::info!(
+        "Repository '{}' reconnected after {}s",
+        event.repo_id,
+        event.downtime_secs
+    );
+    if let Err(e) = app.emit(REPOSITORY_RECONNECTED_EVENT, event) {
+        log::debug!("Failed to emit repository reconnected event: {}", e);
+    }
+}
+
+/// Run the server monitor loop
+///
+/// This is the only background task that restarts servers. Every
+/// `HEARTBEAT_INTERVAL_SECS` it checks the running servers the app started,
+/// concurrently and limited by the fan-out timeout. Servers that stop
+/// answering are restarted after `HEARTBEAT_FAILURE_THRESHOLD` failed checks
+/// (emitting `server-unhealthy`); with `autoReconnect`, servers whose
+/// repository was lost are restarted as described in the module docs.
+pub async fn run_server_monitor(
+    app: AppHandle,
+    manager: ServerManagerState,
+    settings: SettingsState,
+) {
+    let mut heartbeats = HeartbeatTracker::default();
+    let mut reconnects = ReconnectTracker::default();
+    let mut interval = tokio::time::interval(Duration::from_secs(HEARTBEAT_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let (auto_reconnect, timeout) = {
+            let settings = settings.lock().unwrap();
+            let settings = settings.get();
+            (
+                settings.auto_reconnect,
+                fan_out_timeout(None, settings.fan_out_timeout_secs),
+            )
+        };
+        if !auto_reconnect {
+            reconnects = ReconnectTracker::default();
+        }
+
+        let servers: Vec<_> = {
+            let manager = manager.lock().unwrap();
+            manager
+                .running_servers()
+                .into_iter()
+                .filter(|(repo_id, _, _)| !manager.is_stopped_by_user(repo_id))
+                .collect()
+        };
+        let running: Vec<String> = servers.iter().map(|(id, _, _)| id.clone()).collect();
+        heartbeats.retain_running(&running);
+        reconnects.retain_running(&running);
+
+        let requests = servers
+            .into_iter()
+            .map(|(repo_id, server_url, client)| {
+                (repo_id, async move {
+                    Ok(check_server(&client, &server_url).await)
+                })
+            })
+            .collect();
+        let result = fan_out(requests, timeout).await;
+
+        let mut checks: Vec<(String, ServerCheck)> = result.completed;
+        checks.extend(
+            result
+                .timed_out
+                .into_iter()
+                .chain(result.failed.into_iter().map(|(id, _)| id))
+                .map(|id| (id, ServerCheck::Unresponsive)),
+        );
+
+        for (repo_id, check) in checks {
+            let responsive = check != ServerCheck::Unresponsive;
+            if let Some(failures) = heartbeats.observe(&repo_id, responsive) {
+                // The restart reconnects the repository as well
+                restart_unresponsive(&app, &manager, &repo_id, failures).await;
+                continue;
+            }
+            if auto_reconnect {
+                let connected = check == ServerCheck::Responsive { connected: true };
+                handle_connectivity(&app, &manager, &mut reconnects, &repo_id, connected).await;
+            }
+        }
+
+        // Lost repositories whose server failed to restart
+        for repo_id in reconnects.lost_repos() {
+            if running.contains(&repo_id) {
+                continue;
+            }
+            let retry = {
+                let manager = manager.lock().unwrap();
+                manager.repository_exists(&repo_id) && !manager.is_stopped_by_user(&repo_id)
+            };
+            if retry {
+                handle_connectivity(&app, &manager, &mut reconnects, &repo_id, false).await;
+            } else {
+                reconnects.remove(&repo_id);
+            }
+        }
+    }
+}
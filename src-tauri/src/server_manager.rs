@@ -20,20 +20,33 @@
 //! ```
 //!
 //! This matches the official KopiaUI approach for maximum compatibility.
+//!
+//! Servers the app didn't start can be registered with `add_external_server`.
+//! They have no config file and are only kept for the app's lifetime.
 
 use crate::error::{KopiaError, Result};
-use crate::kopia_server::{KopiaServer, KopiaServerInfo, KopiaServerStatus};
+use crate::kopia_server::{
+    insecure_cert_verify_allowed, insecure_tls_unavailable, KopiaServer, KopiaServerInfo,
+    KopiaServerStatus, ServerStartOptions,
+};
+use crate::types::{KopiaNotification, LastKnownState, SnapshotSource, SourceIdentity, Task};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
 /// Config file suffix used by Kopia
 const CONFIG_SUFFIX: &str = ".config";
 
 /// Default repository ID (matches Kopia CLI default)
-const DEFAULT_REPO_ID: &str = "repository";
+pub const DEFAULT_REPO_ID: &str = "repository";
+
+/// Suffix of the file Kopia stores the repository password in (next to the config)
+const PASSWORD_FILE_SUFFIX: &str = ".kopia-password";
+
+/// ID prefix of repositories served by an external server
+const EXTERNAL_REPO_PREFIX: &str = "external";
 
 /// Entry in the repository list
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,12 +68,32 @@ pub struct RepositoryEntry {
     pub error: Option<String>,
 }
 
+/// Kopia server the app didn't start, used in place of a `KopiaServer`
+struct ExternalConnection {
+    url: String,
+    client: reqwest::Client,
+    download_client: reqwest::Client,
+}
+
 /// Server Manager for multi-repository support
 pub struct ServerManager {
     /// Map of repository ID to KopiaServer instance
     servers: HashMap<String, Arc<Mutex<KopiaServer>>>,
     /// Base config directory (e.g., ~/.config/kopia)
     config_dir: String,
+    /// Accept any server certificate (debugging only, see `set_insecure_skip_cert_verify`)
+    insecure_skip_cert_verify: bool,
+    /// Let servers check for Kopia updates (see `set_check_for_updates`)
+    check_for_updates: bool,
+    /// Display names known locally (e.g. after a rename), by repository ID
+    display_names: HashMap<String, String>,
+    /// Connected external servers, by repository ID
+    external_servers: HashMap<String, ExternalConnection>,
+    /// Repositories the user stopped or disconnected, which background
+    /// monitors must not restart (see `is_stopped_by_user`)
+    stopped_by_user: HashSet<String>,
+    /// Whether the config directory is being moved (see `begin_relocation`)
+    relocating: bool,
 }
 
 impl ServerManager {
@@ -74,6 +107,12 @@ impl ServerManager {
         Self {
             servers: HashMap::new(),
             config_dir: config_dir.to_string(),
+            insecure_skip_cert_verify: false,
+            check_for_updates: false,
+            display_names: HashMap::new(),
+            external_servers: HashMap::new(),
+            stopped_by_user: HashSet::new(),
+            relocating: false,
         }
     }
 
@@ -128,13 +167,116 @@ impl ServerManager {
             return server.clone();
         }
 
-        let server = Arc::new(Mutex::new(KopiaServer::new()));
+        let mut new_server = KopiaServer::new();
+        if let Err(e) = new_server.set_insecure_skip_cert_verify(self.insecure_skip_cert_verify) {
+            log::warn!("Failed to apply certificate verification setting: {}", e);
+        }
+        new_server.set_check_for_updates(self.check_for_updates);
+
+        let server = Arc::new(Mutex::new(new_server));
         self.servers.insert(repo_id.to_string(), server.clone());
         server
     }
 
+    /// Skip certificate pinning for all servers (debugging only)
+    ///
+    /// Only allowed in debug builds or when explicitly enabled via environment.
+    /// Takes effect the next time each server starts.
+    pub fn set_insecure_skip_cert_verify(&mut self, enabled: bool) -> Result<()> {
+        if enabled && !insecure_cert_verify_allowed() {
+            return Err(insecure_tls_unavailable());
+        }
+
+        for server in self.servers.values() {
+            server
+                .lock()
+                .unwrap()
+                .set_insecure_skip_cert_verify(enabled)?;
+        }
+        self.insecure_skip_cert_verify = enabled;
+        Ok(())
+    }
+
+    /// Let all servers check for Kopia updates
+    ///
+    /// Takes effect the next time each server starts.
+    pub fn set_check_for_updates(&mut self, enabled: bool) {
+        for server in self.servers.values() {
+            server.lock().unwrap().set_check_for_updates(enabled);
+        }
+        self.check_for_updates = enabled;
+    }
+
+    /// Newer Kopia version announced by any server's update check
+    pub fn available_update(&self) -> Option<String> {
+        self.servers
+            .values()
+            .find_map(|server| server.lock().unwrap().available_update())
+    }
+
+    /// Directory holding the repository configs
+    pub fn config_dir(&self) -> &str {
+        &self.config_dir
+    }
+
+    /// Use a different directory for repository configs
+    ///
+    /// All local servers must be stopped first, since they keep using the config
+    /// files they were started with.
+    pub fn set_config_dir(&mut self, config_dir: &str) -> Result<()> {
+        if !self.running_local_repositories().is_empty() {
+            return Err(KopiaError::operation_failed(
+                "config directory change",
+                "Stop all servers before changing the config directory",
+            ));
+        }
+
+        self.config_dir = config_dir.to_string();
+        log::info!("Config directory changed to {}", config_dir);
+        Ok(())
+    }
+
+    /// Start moving the config directory, returning the current one
+    ///
+    /// Until `end_relocation`, servers can't be started, so the files can be
+    /// copied without holding the manager lock.
+    pub fn begin_relocation(&mut self) -> Result<String> {
+        if self.relocating {
+            return Err(KopiaError::operation_failed(
+                "config directory change",
+                "The config directory is already being moved",
+            ));
+        }
+        self.relocating = true;
+        Ok(self.config_dir.clone())
+    }
+
+    /// Allow servers to start again after moving the config directory
+    pub fn end_relocation(&mut self) {
+        self.relocating = false;
+    }
+
+    /// IDs of the repositories whose local server is running, sorted
+    pub fn running_local_repositories(&self) -> Vec<String> {
+        let mut repo_ids: Vec<String> = self
+            .servers
+            .iter()
+            .filter(|(_, server)| server.lock().unwrap().is_running())
+            .map(|(repo_id, _)| repo_id.clone())
+            .collect();
+        repo_ids.sort();
+        repo_ids
+    }
+
     /// Start a server for a specific repository
     pub fn start_server(&mut self, repo_id: &str) -> Result<KopiaServerInfo> {
+        if self.relocating {
+            return Err(KopiaError::operation_failed(
+                "server start",
+                "The config directory is being moved",
+            ));
+        }
+        self.stopped_by_user.remove(repo_id);
         let config_file = self.get_config_file_path(repo_id);
         let config_dir = self.config_dir.clone();
 
@@ -160,7 +302,11 @@ impl ServerManager {
     }
 
     /// Stop a server for a specific repository
+    ///
+    /// The repository is left alone by background monitors until it's started
+    /// again. Monitors restarting a server use `restart_server` instead.
     pub fn stop_server(&mut self, repo_id: &str) -> Result<()> {
+        self.stopped_by_user.insert(repo_id.to_string());
         let server = self.servers.get(repo_id).ok_or_else(|| {
             KopiaError::operation_failed(
                 "repository lookup",
@@ -173,6 +319,120 @@ impl ServerManager {
         server_guard.stop()
     }
 
+    /// Restart a server without marking it as stopped by the user
+    ///
+    /// Refuses repositories the user stopped or disconnected, so a background
+    /// monitor can't undo that.
+    pub fn restart_server(&mut self, repo_id: &str) -> Result<KopiaServerInfo> {
+        if self.is_stopped_by_user(repo_id) {
+            return Err(KopiaError::operation_failed(
+                "server restart",
+                format!("Repository '{}' was stopped by the user", repo_id),
+            ));
+        }
+
+        if let Some(server) = self.servers.get(repo_id) {
+            let mut server_guard = server.lock().unwrap();
+            if server_guard.is_running() {
+                server_guard.stop()?;
+            }
+        }
+        self.start_server(repo_id)
+    }
+
+    /// Record that the user disconnected a repository
+    ///
+    /// Its server keeps running, but background monitors won't restart it
+    /// until the repository is connected or its server started again.
+    pub fn record_user_disconnect(&mut self, repo_id: &str) {
+        self.stopped_by_user.insert(repo_id.to_string());
+    }
+
+    /// Record that the user connected a repository again
+    pub fn clear_user_disconnect(&mut self, repo_id: &str) {
+        self.stopped_by_user.remove(repo_id);
+    }
+
+    /// Whether the user stopped or disconnected a repository
+    pub fn is_stopped_by_user(&self, repo_id: &str) -> bool {
+        self.stopped_by_user.contains(repo_id)
+    }
+
+    /// Apply start flag overrides to a repository's server (used on next start)
+    pub fn set_start_options(&mut self, repo_id: &str, options: ServerStartOptions) -> Result<()> {
+        let server = self.get_or_create_server(repo_id);
+        let mut server_guard = server.lock().unwrap();
+        server_guard.set_start_options(options)
+    }
+
+    /// Default source identity cached for a repository's server session
+    pub fn default_identity(&self, repo_id: &str) -> Option<SourceIdentity> {
+        self.servers
+            .get(repo_id)
+            .and_then(|server| server.lock().unwrap().default_identity())
+    }
+
+    /// Cache (or with `None`, forget) a repository's default source identity
+    pub fn set_default_identity(&self, repo_id: &str, identity: Option<SourceIdentity>) {
+        if let Some(server) = self.servers.get(repo_id) {
+            server.lock().unwrap().set_default_identity(identity);
+        }
+    }
+
+    /// Cached endpoint probe result for a repository's server session
+    pub fn endpoint_support(&self, repo_id: &str, endpoint: &str) -> Option<bool> {
+        self.servers
+            .get(repo_id)
+            .and_then(|server| server.lock().unwrap().endpoint_support(endpoint))
+    }
+
+    /// Remember an endpoint probe result for a repository's server session
+    pub fn record_endpoint_support(&self, repo_id: &str, endpoint: &str, supported: bool) {
+        if let Some(server) = self.servers.get(repo_id) {
+            server
+                .lock()
+                .unwrap()
+                .record_endpoint_support(endpoint, supported);
+        }
+    }
+
+    /// Sources and tasks last fetched from a repository's server
+    pub fn last_known_state(&self, repo_id: &str) -> LastKnownState {
+        self.servers
+            .get(repo_id)
+            .map(|server| server.lock().unwrap().last_known_state())
+            .unwrap_or_default()
+    }
+
+    /// Remember a repository's latest sources list
+    pub fn record_sources(&self, repo_id: &str, sources: Vec<SnapshotSource>, received_at: &str) {
+        if let Some(server) = self.servers.get(repo_id) {
+            server.lock().unwrap().record_sources(sources, received_at);
+        }
+    }
+
+    /// Remember a repository's latest tasks list
+    pub fn record_tasks(&self, repo_id: &str, tasks: Vec<Task>, received_at: &str) {
+        if let Some(server) = self.servers.get(repo_id) {
+            server.lock().unwrap().record_tasks(tasks, received_at);
+        }
+    }
+
+    /// Up to `limit` most recent error notifications of a repository, newest first
+    pub fn error_notifications(&self, repo_id: &str, limit: usize) -> Vec<KopiaNotification> {
+        self.servers
+            .get(repo_id)
+            .map(|server| server.lock().unwrap().error_notifications(limit))
+            .unwrap_or_default()
+    }
+
+    /// Forget a repository's error notifications
+    pub fn clear_error_notifications(&self, repo_id: &str) {
+        if let Some(server) = self.servers.get(repo_id) {
+            server.lock().unwrap().clear_error_notifications();
+        }
+    }
+
     /// Stop all running servers
     pub fn stop_all(&mut self) -> Result<()> {
         let mut errors = Vec::new();
@@ -201,6 +461,17 @@ impl ServerManager {
 
     /// Get server status for a specific repository
     pub fn get_server_status(&mut self, repo_id: &str) -> Result<KopiaServerStatus> {
+        if let Some(external) = self.external_servers.get(repo_id) {
+            return Ok(KopiaServerStatus {
+                running: true,
+                server_url: Some(external.url.clone()),
+                port: url::Url::parse(&external.url)
+                    .ok()
+                    .and_then(|url| url.port_or_known_default()),
+                uptime: None,
+            });
+        }
+
         let server = self.servers.get(repo_id).ok_or_else(|| {
             KopiaError::operation_failed(
                 "repository lookup",
@@ -216,32 +487,94 @@ impl ServerManager {
     /// List all repositories with their status
     pub fn list_repositories(&mut self) -> Result<Vec<RepositoryEntry>> {
         // First, discover all repos from config files
-        let repo_ids = self.discover_repositories()?;
+        let mut repo_ids = self.discover_repositories()?;
 
-        let mut entries = Vec::new();
+        let mut external_ids: Vec<String> = self.external_servers.keys().cloned().collect();
+        external_ids.sort();
+        repo_ids.extend(external_ids);
 
-        for repo_id in repo_ids {
-            let config_file = self.get_config_file_path(&repo_id);
+        Ok(repo_ids
+            .iter()
+            .map(|repo_id| self.repository_entry(repo_id))
+            .collect())
+    }
 
-            // Get or create server to check status
-            let server = self.get_or_create_server(&repo_id);
-            let mut server_guard = server.lock().unwrap();
+    /// Build the list entry for a repository
+    pub fn repository_entry(&mut self, repo_id: &str) -> RepositoryEntry {
+        if let Some(external) = self.external_servers.get(repo_id) {
+            return RepositoryEntry {
+                id: repo_id.to_string(),
+                display_name: self
+                    .display_names
+                    .get(repo_id)
+                    .cloned()
+                    .unwrap_or_else(|| external.url.clone()),
+                config_file: String::new(),
+                status: "running".to_string(),
+                // Checked by `connect_external_server` before registering
+                connected: true,
+                storage: None,
+                error: None,
+            };
+        }
 
-            let status = server_guard.status();
-            let status_str = if status.running { "running" } else { "stopped" };
+        let config_file = self.get_config_file_path(repo_id);
 
-            entries.push(RepositoryEntry {
-                id: repo_id.clone(),
-                display_name: repo_id.clone(), // Will be updated with actual description
-                config_file,
-                status: status_str.to_string(),
-                connected: false, // Will be updated by checking repo status
-                storage: None,    // Will be updated by checking repo status
-                error: None,
-            });
+        // Get or create server to check status
+        let server = self.get_or_create_server(repo_id);
+        let mut server_guard = server.lock().unwrap();
+
+        let status = server_guard.status();
+        let status_str = if status.running { "running" } else { "stopped" };
+
+        RepositoryEntry {
+            id: repo_id.to_string(),
+            // Will be updated with actual description unless known locally
+            display_name: self
+                .display_names
+                .get(repo_id)
+                .cloned()
+                .unwrap_or_else(|| repo_id.to_string()),
+            config_file,
+            status: status_str.to_string(),
+            connected: false, // Will be updated by checking repo status
+            storage: None,    // Will be updated by checking repo status
+            error: None,
         }
+    }
+
+    /// Remember a repository's display name (its description)
+    pub fn set_display_name(&mut self, repo_id: &str, name: &str) {
+        self.display_names
+            .insert(repo_id.to_string(), name.to_string());
+    }
+
+    /// Register a connected external server as a repository
+    ///
+    /// Returns the new repository ID.
+    pub fn add_external_server(
+        &mut self,
+        url: &str,
+        client: reqwest::Client,
+        download_client: reqwest::Client,
+    ) -> String {
+        let id = generate_repo_id(EXTERNAL_REPO_PREFIX);
+        self.external_servers.insert(
+            id.clone(),
+            ExternalConnection {
+                url: url.trim_end_matches('/').to_string(),
+                client,
+                download_client,
+            },
+        );
+
+        log::info!("Connected external server {} as '{}'", url, id);
+        id
+    }
 
-        Ok(entries)
+    /// Check if a repository is served by an external server
+    pub fn is_external(&self, repo_id: &str) -> bool {
+        self.external_servers.contains_key(repo_id)
     }
 
     /// Add a new repository configuration and start the server
@@ -250,13 +583,7 @@ impl ServerManager {
     /// Also starts the Kopia server for this repository (required for API calls).
     /// Returns the repository ID.
     pub fn add_repository(&mut self, repo_id: Option<String>) -> Result<String> {
-        let id = repo_id.unwrap_or_else(|| {
-            let timestamp = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_millis())
-                .unwrap_or(0);
-            format!("{}-{}", DEFAULT_REPO_ID, timestamp)
-        });
+        let id = repo_id.unwrap_or_else(|| generate_repo_id(DEFAULT_REPO_ID));
 
         // Validate ID doesn't already exist
         let config_file = self.get_config_file_path(&id);
@@ -281,6 +608,84 @@ impl ServerManager {
         Ok(id)
     }
 
+    /// Import an existing Kopia config file as a new repository
+    ///
+    /// Copies the config (and its `.kopia-password` file, if any) into the config
+    /// directory under a new ID. A relative cache directory is resolved against the
+    /// original location so the existing cache keeps being used.
+    /// Returns the new repository ID; the server is not started.
+    pub fn import_config(&mut self, source: &Path) -> Result<String> {
+        let contents = fs::read_to_string(source).map_err(|e| {
+            KopiaError::invalid_input(
+                "configPath",
+                format!("Cannot read {}: {}", source.display(), e),
+            )
+        })?;
+
+        let mut config: serde_json::Value = serde_json::from_str(&contents).map_err(|e| {
+            KopiaError::invalid_input("configPath", format!("Not a Kopia config file: {}", e))
+        })?;
+
+        if config.get("storage").is_none() && config.get("apiServer").is_none() {
+            return Err(KopiaError::invalid_input(
+                "configPath",
+                "Config file doesn't reference a repository",
+            ));
+        }
+
+        let source_dir = source.parent().unwrap_or_else(|| Path::new("."));
+        if let Some(cache_dir) = config
+            .pointer_mut("/caching/cacheDirectory")
+            .filter(|dir| dir.as_str().is_some_and(|d| Path::new(d).is_relative()))
+        {
+            let absolute = source_dir.join(cache_dir.as_str().unwrap_or_default());
+            *cache_dir = serde_json::Value::String(absolute.to_string_lossy().to_string());
+        }
+
+        let id = generate_repo_id(DEFAULT_REPO_ID);
+        let config_file = self.get_config_file_path(&id);
+
+        fs::write(&config_file, serde_json::to_string_pretty(&config)?).map_err(|e| {
+            KopiaError::operation_failed(
+                "config import",
+                format!("Failed to write {}: {}", config_file, e),
+            )
+        })?;
+
+        let password_file = PathBuf::from(format!("{}{}", source.display(), PASSWORD_FILE_SUFFIX));
+        if password_file.exists() {
+            if let Err(e) = fs::copy(
+                &password_file,
+                format!("{}{}", config_file, PASSWORD_FILE_SUFFIX),
+            ) {
+                log::warn!("Failed to copy password file: {}", e);
+            }
+        }
+
+        log::info!("Imported config {} as '{}'", source.display(), id);
+        Ok(id)
+    }
+
+    /// Remove a repository and delete its config and password files
+    ///
+    /// Used to roll back an import that turned out to be unusable.
+    pub fn discard_repository(&mut self, repo_id: &str) -> Result<()> {
+        self.remove_repository(repo_id)?;
+
+        let config_file = self.get_config_file_path(repo_id);
+        for path in [
+            config_file.clone(),
+            format!("{}{}", config_file, PASSWORD_FILE_SUFFIX),
+        ] {
+            if let Err(e) = fs::remove_file(&path) {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    log::warn!("Failed to remove {}: {}", path, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Remove a repository configuration
     ///
     /// Stops the server if running and removes the config file.
@@ -304,6 +709,9 @@ impl ServerManager {
 
         // Remove from servers map
         self.servers.remove(repo_id);
+        self.external_servers.remove(repo_id);
+        self.display_names.remove(repo_id);
+        self.stopped_by_user.remove(repo_id);
 
         // Note: We don't delete the config file automatically
         // User should disconnect first which handles cleanup
@@ -321,26 +729,97 @@ impl ServerManager {
     }
 
     /// Check if a repository exists (has a config file)
-    #[cfg(test)]
     pub fn repository_exists(&self, repo_id: &str) -> bool {
         PathBuf::from(self.get_config_file_path(repo_id)).exists()
     }
 
     /// Get HTTP client for a repository (for API calls)
     pub fn get_http_client(&self, repo_id: &str) -> Option<reqwest::Client> {
+        if let Some(external) = self.external_servers.get(repo_id) {
+            return Some(external.client.clone());
+        }
+
         self.servers
             .get(repo_id)
             .and_then(|server| server.lock().unwrap().get_http_client())
     }
 
+    /// Get the HTTP client for streaming downloads from a repository
+    ///
+    /// See `KopiaServer::get_download_client`.
+    pub fn get_download_client(&self, repo_id: &str) -> Option<reqwest::Client> {
+        if let Some(external) = self.external_servers.get(repo_id) {
+            return Some(external.download_client.clone());
+        }
+
+        self.servers
+            .get(repo_id)
+            .and_then(|server| server.lock().unwrap().get_download_client())
+    }
+
+    /// Get a server control API client for a repository
+    ///
+    /// Only servers started by the app have a control password.
+    pub fn get_control_client(&self, repo_id: &str) -> Result<reqwest::Client> {
+        if self.external_servers.contains_key(repo_id) {
+            return Err(KopiaError::ControlApiUnavailable {
+                reason: "External servers don't share their control password".to_string(),
+            });
+        }
+
+        self.servers
+            .get(repo_id)
+            .ok_or(KopiaError::ServerNotRunning)?
+            .lock()
+            .unwrap()
+            .get_control_client()
+    }
+
     /// Get server URL for a repository
     pub fn get_server_url(&self, repo_id: &str) -> Option<String> {
+        if let Some(external) = self.external_servers.get(repo_id) {
+            return Some(external.url.clone());
+        }
+
         self.servers.get(repo_id).and_then(|server| {
             let mut server_guard = server.lock().unwrap();
             server_guard.status().server_url
         })
     }
 
+    /// Get repository ID, server URL and HTTP client of every running server
+    /// started by the app
+    ///
+    /// External servers are left out: they're shared with other clients, so
+    /// the app must not stop, restart or pause them.
+    pub fn running_servers(&self) -> Vec<(String, String, reqwest::Client)> {
+        self.servers
+            .iter()
+            .filter_map(|(repo_id, server)| {
+                let mut server_guard = server.lock().unwrap();
+                let server_url = server_guard.status().server_url?;
+                let client = server_guard.get_http_client()?;
+                Some((repo_id.clone(), server_url, client))
+            })
+            .collect()
+    }
+
+    /// Like `running_servers`, but including connected external servers
+    ///
+    /// Only for read-only use.
+    pub fn connected_servers(&self) -> Vec<(String, String, reqwest::Client)> {
+        self.running_servers()
+            .into_iter()
+            .chain(self.external_servers.iter().map(|(repo_id, external)| {
+                (
+                    repo_id.clone(),
+                    external.url.clone(),
+                    external.client.clone(),
+                )
+            }))
+            .collect()
+    }
+
     /// Get ready waiter for a repository (for waiting after start)
     pub fn get_ready_waiter(
         &self,
@@ -357,6 +836,15 @@ impl ServerManager {
     }
 }
 
+/// Generate a unique repository ID from `prefix` and the current time
+fn generate_repo_id(prefix: &str) -> String {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    format!("{}-{}", prefix, timestamp)
+}
+
 /// Tauri state type for the ServerManager
 pub type ServerManagerState = Arc<Mutex<ServerManager>>;
 
@@ -464,4 +952,243 @@ mod tests {
         let result = manager.remove_repository("repository");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_import_config() {
+        let source_dir = tempdir().unwrap();
+        let source = source_dir.path().join("repository.config");
+        fs::write(
+            &source,
+            r#"{"storage":{"type":"filesystem","config":{"path":"/backups"}},"caching":{"cacheDirectory":"../cache/abc"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            source_dir.path().join("repository.config.kopia-password"),
+            "secret",
+        )
+        .unwrap();
+
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+
+        let id = manager.import_config(&source).unwrap();
+        assert!(id.starts_with("repository-"));
+        assert!(manager.repository_exists(&id));
+
+        let config_file = manager.get_config_file_path(&id);
+        let imported: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_file).unwrap()).unwrap();
+        let cache_dir = imported["caching"]["cacheDirectory"].as_str().unwrap();
+        assert_eq!(
+            PathBuf::from(cache_dir),
+            source_dir.path().join("../cache/abc")
+        );
+        assert_eq!(
+            fs::read_to_string(format!("{}.kopia-password", config_file)).unwrap(),
+            "secret"
+        );
+
+        manager.discard_repository(&id).unwrap();
+        assert!(!manager.repository_exists(&id));
+        assert!(!PathBuf::from(format!("{}.kopia-password", config_file)).exists());
+    }
+
+    #[test]
+    fn test_import_config_rejects_invalid_files() {
+        let source_dir = tempdir().unwrap();
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+
+        let missing = source_dir.path().join("missing.config");
+        assert!(matches!(
+            manager.import_config(&missing),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+
+        let not_json = source_dir.path().join("not-json.config");
+        fs::write(&not_json, "hello").unwrap();
+        assert!(manager.import_config(&not_json).is_err());
+
+        let no_repo = source_dir.path().join("no-repo.config");
+        fs::write(&no_repo, r#"{"hostname":"laptop"}"#).unwrap();
+        assert!(manager.import_config(&no_repo).is_err());
+
+        assert!(manager.discover_repositories().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_display_name_cache() {
+        let temp_dir = tempdir().unwrap();
+        fs::write(temp_dir.path().join("repo-a.config"), "{}").unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+
+        assert_eq!(manager.repository_entry("repo-a").display_name, "repo-a");
+
+        manager.set_display_name("repo-a", "Photos");
+        let entries = manager.list_repositories().unwrap();
+        assert_eq!(entries[0].display_name, "Photos");
+
+        manager.remove_repository("repo-a").unwrap();
+        assert_eq!(manager.repository_entry("repo-a").display_name, "repo-a");
+    }
+
+    #[test]
+    fn test_default_identity_cache() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+        let identity = SourceIdentity {
+            user_name: "alice".to_string(),
+            host: "laptop".to_string(),
+        };
+
+        // Unknown repositories have no server to cache on
+        manager.set_default_identity("repo-a", Some(identity.clone()));
+        assert_eq!(manager.default_identity("repo-a"), None);
+
+        manager.repository_entry("repo-a");
+        manager.set_default_identity("repo-a", Some(identity.clone()));
+        assert_eq!(manager.default_identity("repo-a"), Some(identity));
+
+        manager.set_default_identity("repo-a", None);
+        assert_eq!(manager.default_identity("repo-a"), None);
+    }
+
+    #[test]
+    fn test_endpoint_support_cache() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+
+        // Unknown repositories have no server to cache on
+        manager.record_endpoint_support("repo-a", "GET /api/v1/sources", true);
+        assert_eq!(
+            manager.endpoint_support("repo-a", "GET /api/v1/sources"),
+            None
+        );
+
+        manager.repository_entry("repo-a");
+        manager.record_endpoint_support("repo-a", "GET /api/v1/sources", true);
+        manager.record_endpoint_support("repo-a", "POST /api/v1/verify", false);
+        assert_eq!(
+            manager.endpoint_support("repo-a", "GET /api/v1/sources"),
+            Some(true)
+        );
+        assert_eq!(
+            manager.endpoint_support("repo-a", "POST /api/v1/verify"),
+            Some(false)
+        );
+        assert_eq!(
+            manager.endpoint_support("repo-a", "GET /api/v1/tasks"),
+            None
+        );
+        assert_eq!(
+            manager.endpoint_support("repo-b", "GET /api/v1/sources"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_last_known_state() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+        let task: Task = serde_json::from_value(serde_json::json!({
+            "id": "t1",
+            "startTime": "2024-01-01T00:00:00Z",
+            "kind": "Snapshot",
+            "description": "",
+            "status": "RUNNING"
+        }))
+        .unwrap();
+
+        let empty = manager.last_known_state("repo-a");
+        assert!(empty.sources.is_empty() && empty.tasks.is_empty());
+        assert_eq!(empty.received_at, "");
+
+        manager.repository_entry("repo-a");
+        manager.record_tasks("repo-a", vec![task], "2024-01-01T00:00:05Z");
+        manager.record_sources("repo-a", Vec::new(), "2024-01-01T00:00:10Z");
+
+        let state = manager.last_known_state("repo-a");
+        assert_eq!(state.tasks.len(), 1);
+        assert_eq!(state.tasks[0].id, "t1");
+        assert_eq!(state.received_at, "2024-01-01T00:00:10Z");
+    }
+
+    #[test]
+    fn test_external_server_routing() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+
+        let repo_id = manager.add_external_server(
+            "https://backup.example.com:51515/",
+            reqwest::Client::new(),
+            reqwest::Client::new(),
+        );
+        assert!(repo_id.starts_with("external-"));
+        assert!(manager.is_external(&repo_id));
+        assert_eq!(
+            manager.get_server_url(&repo_id),
+            Some("https://backup.example.com:51515".to_string())
+        );
+        assert!(manager.get_http_client(&repo_id).is_some());
+        assert!(manager.get_download_client(&repo_id).is_some());
+        assert_eq!(
+            manager.get_server_status(&repo_id).unwrap().port,
+            Some(51515)
+        );
+
+        let repos = manager.list_repositories().unwrap();
+        assert!(repos
+            .iter()
+            .any(|r| r.id == repo_id && r.status == "running" && r.connected));
+
+        // Only read-only callers see external servers
+        assert!(manager.running_servers().is_empty());
+        let connected = manager.connected_servers();
+        assert_eq!(connected.len(), 1);
+        assert_eq!(connected[0].0, repo_id);
+        assert!(manager.servers.is_empty());
+
+        manager.remove_repository(&repo_id).unwrap();
+        assert!(!manager.is_external(&repo_id));
+        assert_eq!(manager.get_server_url(&repo_id), None);
+    }
+
+    #[test]
+    fn test_stopped_by_user() {
+        let temp_dir = tempdir().unwrap();
+        let mut manager = ServerManager::new(temp_dir.path().to_str().unwrap());
+
+        assert!(!manager.is_stopped_by_user("repo-a"));
+
+        // Stopping marks the repository even if its server wasn't running
+        let _ = manager.stop_server("repo-a");
+        assert!(manager.is_stopped_by_user("repo-a"));
+        assert!(manager.restart_server("repo-a").is_err());
+
+        manager.clear_user_disconnect("repo-a");
+        assert!(!manager.is_stopped_by_user("repo-a"));
+
+        manager.record_user_disconnect("repo-a");
+        assert!(manager.is_stopped_by_user("repo-a"));
+        manager.remove_repository("repo-a").unwrap();
+        assert!(!manager.is_stopped_by_user("repo-a"));
+    }
+
+    #[test]
+    fn test_relocation_blocks_server_start() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path().to_str().unwrap();
+        let mut manager = ServerManager::new(config_dir);
+
+        assert_eq!(manager.begin_relocation().unwrap(), config_dir);
+        assert!(manager.begin_relocation().is_err());
+        assert!(manager
+            .start_server("repo-a")
+            .unwrap_err()
+            .to_string()
+            .contains("being moved"));
+
+        manager.end_relocation();
+        assert!(manager.begin_relocation().is_ok());
+    }
 }
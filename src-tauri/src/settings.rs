@@ -0,0 +1,236 @@
+//! Persistent application settings
+//!
+//! Settings that belong to Kopia Desktop itself (not to a repository) are stored
+//! as JSON next to the repository configs:
+//!
+//! ```text
+//! ~/.config/kopia/
+//! ├── repository.config
+//! └── kopia-desktop-settings.json
+//! ```
+//!
+//! The file name deliberately doesn't end in `.config` so it isn't picked up by
+//! repository discovery. Missing or unreadable settings fall back to defaults.
+
+use crate::error::{KopiaError, Result};
+use crate::types::SourceInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Settings file name inside the config directory
+pub const SETTINGS_FILE_NAME: &str = "kopia-desktop-settings.json";
+
+/// Temporary file written by `SettingsStore::save` before replacing the settings file
+pub const SETTINGS_TEMP_FILE_NAME: &str = "kopia-desktop-settings.json.tmp";
+
+/// Upload throughput samples kept; the oldest are dropped first
+pub const MAX_THROUGHPUT_SAMPLES: usize = 500;
+
+/// Application settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// Pause scheduled snapshots while on battery or a metered connection
+    pub power_aware_scheduling: bool,
+    /// Accept any Kopia server certificate instead of pinning it (debugging only;
+    /// ignored in release builds unless explicitly allowed)
+    pub insecure_skip_cert_verify: bool,
+    /// Per-request timeout for aggregate commands that query many sources
+    /// (None = `DEFAULT_FAN_OUT_TIMEOUT_SECS`)
+    pub fan_out_timeout_secs: Option<u64>,
+    /// Let running tasks finish before quitting from the tray or closing the window
+    pub wait_for_tasks_on_quit: bool,
+    /// Reconnect repositories automatically after losing connectivity
+    pub auto_reconnect: bool,
+    /// Let Kopia servers check for updates (off by default)
+    pub server_check_for_updates: bool,
+    /// Where repository configs live if moved by `relocate_config_dir`
+    /// (None = the default config directory, which always holds this file)
+    pub config_dir: Option<String>,
+    /// Per-source overrides of whether failed snapshots raise a notification
+    pub source_notify_overrides: Vec<SourceNotifyOverride>,
+    /// Finished tasks that ended at or before this time (RFC 3339) are hidden
+    /// from `tasks_list`, by repository ID
+    pub tasks_cleared_before: HashMap<String, String>,
+    /// Upload throughput of completed snapshots, oldest first
+    pub upload_throughput: Vec<ThroughputSample>,
+    /// Repository the user last had open (see `get_ui_state`)
+    pub last_repo_id: Option<String>,
+    /// Screen the user last had open, as a frontend route
+    pub last_view: Option<String>,
+    /// Enable low-level diagnostic commands such as `blob_contents`
+    pub developer_mode: bool,
+}
+
+/// Notification override for one source
+///
+/// Kopia's notification profiles apply to every source and policies have no
+/// notification settings, so overrides are kept in the app settings. The UI
+/// asks `task_notify_on_failure` before notifying about a failed task.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceNotifyOverride {
+    pub repo_id: String,
+    pub source: SourceInfo,
+    pub notify_on_error: bool,
+}
+
+/// Upload throughput of one completed snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThroughputSample {
+    pub repo_id: String,
+    /// When the snapshot finished (RFC 3339)
+    pub time: String,
+    pub bytes_per_second: f64,
+    /// Snapshot source as `user@host:/path`
+    pub source: String,
+}
+
+impl AppSettings {
+    /// Set the notification override for a source, replacing any existing one
+    pub fn set_source_notify_on_error(
+        &mut self,
+        repo_id: &str,
+        source: &SourceInfo,
+        enabled: bool,
+    ) {
+        self.source_notify_overrides
+            .retain(|o| !(o.repo_id == repo_id && o.source == *source));
+        self.source_notify_overrides.push(SourceNotifyOverride {
+            repo_id: repo_id.to_string(),
+            source: source.clone(),
+            notify_on_error: enabled,
+        });
+    }
+
+    /// Notification override for a source, if one is set
+    pub fn source_notify_on_error(&self, repo_id: &str, source: &SourceInfo) -> Option<bool> {
+        self.source_notify_overrides
+            .iter()
+            .find(|o| o.repo_id == repo_id && o.source == *source)
+            .map(|o| o.notify_on_error)
+    }
+
+    /// Time up to which finished tasks of a repository are hidden
+    pub fn tasks_cleared_before(&self, repo_id: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.tasks_cleared_before
+            .get(repo_id)
+            .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.with_timezone(&chrono::Utc))
+    }
+
+    /// Hide finished tasks of a repository that ended at or before `cutoff`
+    ///
+    /// An earlier cutoff never un-hides tasks.
+    pub fn clear_tasks_before(&mut self, repo_id: &str, cutoff: chrono::DateTime<chrono::Utc>) {
+        if self
+            .tasks_cleared_before(repo_id)
+            .is_some_and(|existing| existing >= cutoff)
+        {
+            return;
+        }
+        self.tasks_cleared_before
+            .insert(repo_id.to_string(), cutoff.to_rfc3339());
+    }
+
+    /// Whether a sample for the same snapshot was already recorded
+    pub fn has_throughput_sample(&self, sample: &ThroughputSample) -> bool {
+        self.upload_throughput.iter().any(|s| {
+            s.repo_id == sample.repo_id && s.time == sample.time && s.source == sample.source
+        })
+    }
+
+    /// Add new throughput samples, keeping the newest `MAX_THROUGHPUT_SAMPLES`
+    pub fn record_throughput(&mut self, samples: Vec<ThroughputSample>) {
+        for sample in samples {
+            if !self.has_throughput_sample(&sample) {
+                self.upload_throughput.push(sample);
+            }
+        }
+
+        self.upload_throughput
+            .sort_by_key(|s| chrono::DateTime::parse_from_rfc3339(&s.time).ok());
+        let excess = self
+            .upload_throughput
+            .len()
+            .saturating_sub(MAX_THROUGHPUT_SAMPLES);
+        self.upload_throughput.drain(..excess);
+    }
+}
+
+/// Settings loaded from disk, saved on every update
+pub struct SettingsStore {
+    path: PathBuf,
+    settings: AppSettings,
+}
+
+impl SettingsStore {
+    /// Load settings from `config_dir`, falling back to defaults
+    pub fn load(config_dir: &str) -> Self {
+        let path = Path::new(config_dir).join(SETTINGS_FILE_NAME);
+
+        let settings = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                log::warn!("Invalid settings file {}: {}", path.display(), e);
+                AppSettings::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => AppSettings::default(),
+            Err(e) => {
+                log::warn!("Failed to read settings file {}: {}", path.display(), e);
+                AppSettings::default()
+            }
+        };
+
+        Self { path, settings }
+    }
+
+    /// Current settings
+    pub fn get(&self) -> AppSettings {
+        self.settings.clone()
+    }
+
+    /// Apply `update` and save the result
+    ///
+    /// The in-memory settings are only changed if saving succeeds.
+    pub fn update(&mut self, update: impl FnOnce(&mut AppSettings)) -> Result<AppSettings> {
+        let mut settings = self.settings.clone();
+        update(&mut settings);
+        self.save(&settings)?;
+        self.settings = settings;
+        Ok(self.settings.clone())
+    }
+
+    /// Write settings via a temporary file so a crash never leaves a partial file
+    fn save(&self, settings: &AppSettings) -> Result<()> {
+        let contents = serde_json::to_string_pretty(settings)?;
+        let tmp_path = self.path.with_file_name(SETTINGS_TEMP_FILE_NAME);
+
+        fs::write(&tmp_path, contents).map_err(|e| {
+            KopiaError::operation_failed_with_details(
+                "save settings",
+                format!("Failed to write {}", tmp_path.display()),
+                e.to_string(),
+            )
+        })?;
+
+        fs::rename(&tmp_path, &self.path).map_err(|e| {
+            KopiaError::operation_failed_with_details(
+                "save settings",
+                format!("Failed to replace {}", self.path.display()),
+                e.to_string(),
+            )
+        })
+    }
+}
+
+/// Tauri state type for the settings store
+pub type SettingsState = Arc<Mutex<SettingsStore>>;
+
+/// Load settings into a new state
+pub fn create_settings_state(config_dir: &str) -> SettingsState {
+    Arc::new(Mutex::new(SettingsStore::load(config_dir)))
+}
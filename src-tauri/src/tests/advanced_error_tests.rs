@@ -41,6 +41,15 @@ mod tests {
             KopiaError::NotFound {
                 resource: "snapshot abc123".to_string(),
             },
+            KopiaError::InvalidInput {
+                field: "output".to_string(),
+                message: "Only one output may be specified".to_string(),
+            },
+            KopiaError::RepositoryUpgradeRequired {
+                current_version: Some("2".to_string()),
+                required_version: Some("3".to_string()),
+                message: "repository format version 3 is not supported".to_string(),
+            },
             KopiaError::OperationFailed {
                 operation: "server startup".to_string(),
                 message: "Failed to bind port".to_string(),
@@ -202,4 +211,136 @@ mod tests {
         assert!(message.contains("snapshot creation"));
         assert!(message.contains("Permission denied"));
     }
+
+    #[test]
+    fn test_upgrade_required_mapping() {
+        let body =
+            r#"{"code":"INTERNAL","error":"repository format version 4 is not supported (max 3)"}"#;
+        let error = KopiaError::from_api_response(500, body, "Connect repository");
+
+        match error {
+            KopiaError::RepositoryUpgradeRequired {
+                current_version,
+                required_version,
+                message,
+            } => {
+                assert_eq!(current_version.as_deref(), Some("3"));
+                assert_eq!(required_version.as_deref(), Some("4"));
+                assert!(message.contains("format version 4"));
+            }
+            other => panic!("Expected RepositoryUpgradeRequired, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_repository_locked_mapping() {
+        let body = r#"{"code":"INTERNAL","error":"unable to acquire repository lock: lock held by alice@laptop (pid 4242)"}"#;
+        let error = KopiaError::from_api_response(500, body, "Create snapshot");
+        assert_eq!(
+            error,
+            KopiaError::RepositoryLocked {
+                holder: Some("alice@laptop".to_string())
+            }
+        );
+
+        let error =
+            KopiaError::from_api_response(500, "maintenance lock: already locked", "Maintenance");
+        assert_eq!(error, KopiaError::RepositoryLocked { holder: None });
+
+        let error = KopiaError::from_api_response(423, "", "Create snapshot");
+        assert!(matches!(error, KopiaError::RepositoryLocked { .. }));
+
+        // Unrelated mentions of locks aren't contention
+        let error = KopiaError::from_api_response(500, "invalid lock file format", "Connect");
+        assert!(matches!(error, KopiaError::OperationFailed { .. }));
+    }
+
+    #[test]
+    fn test_repository_locked_non_ascii_message() {
+        // "İ" lowercases to a longer byte sequence, shifting offsets found in
+        // the lowercased message
+        let body = r#"{"code":"INTERNAL","error":"unable to acquire lock on C:\\Users\\İsmail İnce\\repo: held by Ümit@PC"}"#;
+        let error = KopiaError::from_api_response(500, body, "Create snapshot");
+        assert_eq!(
+            error,
+            KopiaError::RepositoryLocked {
+                holder: Some("Ümit@PC".to_string())
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_ignore_ascii_case() {
+        use crate::error::find_ignore_ascii_case;
+
+        assert_eq!(
+            find_ignore_ascii_case("Lock HELD BY bob", "held by"),
+            Some(5)
+        );
+        assert_eq!(find_ignore_ascii_case("İİ held by", "held by"), Some(5));
+        assert_eq!(find_ignore_ascii_case("no marker", "held by"), None);
+        assert_eq!(find_ignore_ascii_case("short", "much longer needle"), None);
+    }
+
+    #[test]
+    fn test_path_not_found_serialization() {
+        let error = KopiaError::PathNotFound {
+            path: "home/user".to_string(),
+        };
+        assert_eq!(error.to_string(), "Path not found in snapshot: home/user");
+
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["type"], "PATH_NOT_FOUND");
+        assert_eq!(json["data"]["path"], "home/user");
+    }
+
+    #[test]
+    fn test_upgrade_required_without_versions() {
+        let error = KopiaError::from_api_response(
+            500,
+            r#"{"code":"INTERNAL","error":"repository upgrade in progress"}"#,
+            "Connect repository",
+        );
+        assert_eq!(
+            error,
+            KopiaError::RepositoryUpgradeRequired {
+                current_version: None,
+                required_version: None,
+                message: "INTERNAL: repository upgrade in progress".to_string(),
+            }
+        );
+
+        let error = KopiaError::from_api_response(
+            400,
+            r#"{"code":"UPGRADE_REQUIRED","error":"upgrade needed"}"#,
+            "Connect repository",
+        );
+        assert!(matches!(
+            error,
+            KopiaError::RepositoryUpgradeRequired { .. }
+        ));
+    }
+
+    #[test]
+    fn test_unrelated_errors_not_upgrade_required() {
+        let error = KopiaError::from_api_response(
+            500,
+            r#"{"code":"INTERNAL","error":"failed to open blob: version mismatch"}"#,
+            "Get status",
+        );
+        assert!(matches!(error, KopiaError::OperationFailed { .. }));
+    }
+
+    #[test]
+    fn test_repository_busy_mapping() {
+        let body = r#"{"code":"INTERNAL","error":"unable to write index: flush in progress"}"#;
+        let error = KopiaError::from_api_response(500, body, "Set policy");
+        assert!(matches!(
+            error,
+            KopiaError::RepositoryBusy { ref message } if message.contains("flush in progress")
+        ));
+
+        let error = KopiaError::from_api_response(500, "unable to flush", "Set policy");
+        assert!(matches!(error, KopiaError::OperationFailed { .. }));
+    }
 }
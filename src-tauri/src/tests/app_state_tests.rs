@@ -0,0 +1,206 @@
+/// Unit tests for app_state.rs
+/// Tests app uptime calculation, the quit guard, mutation locks, task throttles
+/// and app operations
+#[cfg(test)]
+mod tests {
+    use crate::app_state::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_uptime_secs_at() {
+        let started_at = Instant::now();
+        assert_eq!(uptime_secs_at(started_at, started_at), 0);
+        assert_eq!(
+            uptime_secs_at(started_at, started_at + Duration::from_millis(1999)),
+            1
+        );
+        assert_eq!(
+            uptime_secs_at(started_at, started_at + Duration::from_secs(3600)),
+            3600
+        );
+    }
+
+    #[test]
+    fn test_uptime_secs_at_clock_before_start() {
+        let started_at = Instant::now() + Duration::from_secs(5);
+        assert_eq!(uptime_secs_at(started_at, Instant::now()), 0);
+    }
+
+    #[test]
+    fn test_new_app_state_uptime() {
+        let state = AppState::new();
+        assert!(state.uptime_secs() < 5);
+    }
+
+    #[test]
+    fn test_begin_quit_only_once() {
+        let state = AppState::new();
+        assert!(state.begin_quit());
+        assert!(!state.begin_quit());
+    }
+
+    fn target(path: &str) -> crate::types::PolicyTarget {
+        crate::types::PolicyTarget {
+            user_name: Some("user".to_string()),
+            host: Some("host".to_string()),
+            path: Some(path.to_string()),
+        }
+    }
+
+    /// Run two simulated `policy_set` calls and return the max number in flight
+    async fn max_concurrent_mutations(first: &str, second: &str) -> usize {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let state = Arc::new(AppState::new());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let calls = [first, second].map(|path| {
+            let (state, in_flight, max_in_flight) =
+                (state.clone(), in_flight.clone(), max_in_flight.clone());
+            let target = target(path);
+            tokio::spawn(async move {
+                let _guard = state.lock_resource("repo", &target).await;
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            })
+        });
+        for call in calls {
+            call.await.unwrap();
+        }
+
+        max_in_flight.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_policy_set_same_target_serializes() {
+        assert_eq!(max_concurrent_mutations("/data", "/data").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_mutations_different_targets_run_in_parallel() {
+        assert_eq!(max_concurrent_mutations("/data", "/other").await, 2);
+    }
+
+    #[test]
+    fn test_resource_key_shared_by_source_and_policy() {
+        let source = crate::types::SourceInfo {
+            user_name: "user".to_string(),
+            host: "host".to_string(),
+            path: "/data".to_string(),
+        };
+
+        assert_eq!(
+            resource_key("repo", &(&source).into()),
+            resource_key("repo", &target("/data"))
+        );
+        assert_eq!(
+            resource_key("repo", &target("/data")),
+            "repo/user@host:/data"
+        );
+        assert_ne!(
+            resource_key("repo", &target("/data")),
+            resource_key("other", &target("/data"))
+        );
+    }
+
+    fn limits(upload: Option<f64>) -> crate::types::ThrottleLimits {
+        crate::types::ThrottleLimits {
+            max_upload_speed_bytes_per_second: upload,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_task_throttles_keep_first_original() {
+        let throttles = TaskThrottles::default();
+        assert!(throttles.original("repo").is_none());
+
+        assert!(throttles.apply("repo", "t1", limits(None), 1000.0));
+        assert!(throttles.apply("repo", "t2", limits(Some(1000.0)), 500.0));
+        assert!(!throttles.apply("repo", "t2", limits(Some(500.0)), 250.0));
+
+        let original = throttles.original("repo").unwrap();
+        assert_eq!(original.max_upload_speed_bytes_per_second, None);
+    }
+
+    #[test]
+    fn test_task_throttles_restore_after_last_task() {
+        let throttles = TaskThrottles::default();
+        throttles.apply("repo", "t1", limits(Some(5000.0)), 1000.0);
+        throttles.apply("repo", "t2", limits(Some(1000.0)), 500.0);
+        throttles.apply("other", "t3", limits(None), 100.0);
+
+        assert!(throttles.finish("repo", "t1").is_none());
+        let restored = throttles.finish("repo", "t2").unwrap();
+        assert_eq!(
+            restored.original.max_upload_speed_bytes_per_second,
+            Some(5000.0)
+        );
+        assert_eq!(restored.applied, 500.0);
+
+        assert!(throttles.original("repo").is_none());
+        assert!(throttles.finish("repo", "t2").is_none());
+        assert!(throttles.original("other").is_some());
+    }
+
+    #[test]
+    fn test_task_throttles_take_on_quit() {
+        let throttles = TaskThrottles::default();
+        throttles.apply("repo", "t1", limits(Some(5000.0)), 1000.0);
+        throttles.apply("repo", "t2", limits(Some(1000.0)), 500.0);
+
+        assert_eq!(throttles.repo_ids(), vec!["repo"]);
+        let taken = throttles.take("repo").unwrap();
+        assert_eq!(
+            taken.original.max_upload_speed_bytes_per_second,
+            Some(5000.0)
+        );
+
+        // The task watchers find nothing left to restore
+        assert!(throttles.repo_ids().is_empty());
+        assert!(throttles.finish("repo", "t1").is_none());
+        assert!(throttles.take("repo").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_app_operations_cancel() {
+        let state = AppState::new();
+        let maintenance = state.begin_operation("repo-a", "Maintenance");
+        let other_repo = state.begin_operation("repo-b", "Maintenance");
+        let verify = state.begin_operation("repo-a", "Verify");
+
+        let ids: Vec<String> = state
+            .app_operations()
+            .list(Some("repo-a"))
+            .into_iter()
+            .map(|op| op.id)
+            .collect();
+        assert_eq!(ids, vec![maintenance.id(), verify.id()]);
+        assert_eq!(state.app_operations().list(None).len(), 3);
+
+        let canceled = state.app_operations().cancel("repo-a", "Maintenance");
+        assert_eq!(canceled, vec![maintenance.id()]);
+        tokio::time::timeout(Duration::from_secs(1), maintenance.canceled())
+            .await
+            .expect("cancel should wake the operation");
+        let not_canceled =
+            tokio::time::timeout(Duration::from_millis(50), other_repo.canceled()).await;
+        assert!(not_canceled.is_err());
+
+        let canceled = state.app_operations().cancel_all("repo-a");
+        assert_eq!(canceled, vec![maintenance.id(), verify.id()]);
+        tokio::time::timeout(Duration::from_secs(1), verify.canceled())
+            .await
+            .expect("cancel_all should wake every operation of the repository");
+
+        drop(maintenance);
+        drop(verify);
+        let remaining = state.app_operations().list(None);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].repo_id, "repo-b");
+    }
+}
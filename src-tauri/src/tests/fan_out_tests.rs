@@ -0,0 +1,138 @@
+/// Unit tests for fan_out.rs
+/// Tests per-request timeouts, failures, result ordering and timeout resolution
+#[cfg(test)]
+mod tests {
+    use crate::error::{KopiaError, Result};
+    use crate::fan_out::*;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::time::Duration;
+
+    type Request = Pin<Box<dyn Future<Output = Result<u32>> + Send>>;
+
+    fn delayed(key: &str, delay_ms: u64, value: u32) -> (String, Request) {
+        (
+            key.to_string(),
+            Box::pin(async move {
+                tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                Ok(value)
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_all_complete_in_order() {
+        let requests = vec![delayed("a", 30, 1), delayed("b", 0, 2), delayed("c", 10, 3)];
+
+        let result = fan_out(requests, Duration::from_secs(5)).await;
+
+        assert!(!result.is_partial());
+        assert_eq!(
+            result.completed,
+            vec![
+                ("a".to_string(), 1),
+                ("b".to_string(), 2),
+                ("c".to_string(), 3)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_timeout_returns_partial() {
+        let requests = vec![delayed("fast", 0, 1), delayed("stalled", 10_000, 2)];
+
+        let start = std::time::Instant::now();
+        let result = fan_out(requests, Duration::from_millis(100)).await;
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(result.is_partial());
+        assert_eq!(result.completed, vec![("fast".to_string(), 1)]);
+        assert_eq!(result.timed_out, vec!["stalled".to_string()]);
+        assert!(result.failed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_failure_returns_partial() {
+        let failing: Request =
+            Box::pin(async { Err(KopiaError::operation_failed("list snapshots", "boom")) });
+        let requests = vec![delayed("ok", 0, 1), ("broken".to_string(), failing)];
+
+        let result = fan_out(requests, Duration::from_secs(5)).await;
+
+        assert!(result.is_partial());
+        assert_eq!(result.completed.len(), 1);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].0, "broken");
+        assert!(result.timed_out.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_empty() {
+        let result = fan_out(Vec::<(String, Request)>::new(), Duration::from_secs(1)).await;
+        assert!(!result.is_partial());
+        assert!(result.completed.is_empty());
+    }
+
+    #[test]
+    fn test_fan_out_timeout_resolution() {
+        assert_eq!(
+            fan_out_timeout(None, None),
+            Duration::from_secs(DEFAULT_FAN_OUT_TIMEOUT_SECS)
+        );
+        assert_eq!(fan_out_timeout(None, Some(30)), Duration::from_secs(30));
+        assert_eq!(fan_out_timeout(Some(5), Some(30)), Duration::from_secs(5));
+        assert_eq!(fan_out_timeout(Some(0), None), Duration::from_secs(1));
+        assert_eq!(
+            fan_out_timeout(Some(u64::MAX), None),
+            Duration::from_secs(MAX_FAN_OUT_TIMEOUT_SECS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_bounded_limits_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let requests: Vec<(String, Request)> = (0..20)
+            .map(|i| {
+                let running = running.clone();
+                let peak = peak.clone();
+                let request: Request = Box::pin(async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(i)
+                });
+                (i.to_string(), request)
+            })
+            .collect();
+
+        let result = fan_out_bounded(requests, Duration::from_secs(5), 3).await;
+
+        assert!(!result.is_partial());
+        assert_eq!(result.completed.len(), 20);
+        assert_eq!(result.completed[7], ("7".to_string(), 7));
+        assert!(peak.load(Ordering::SeqCst) <= 3);
+        assert!(peak.load(Ordering::SeqCst) > 1);
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_bounded_timeout_starts_when_running() {
+        // Each request takes 60ms, well under the timeout, but queued behind
+        // the others they finish after more than the timeout in total
+        let requests = vec![
+            delayed("a", 60, 1),
+            delayed("b", 60, 2),
+            delayed("c", 60, 3),
+        ];
+
+        let result = fan_out_bounded(requests, Duration::from_millis(150), 1).await;
+
+        assert!(!result.is_partial());
+        assert_eq!(result.completed.len(), 3);
+    }
+}
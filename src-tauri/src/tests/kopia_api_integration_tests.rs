@@ -12,10 +12,11 @@
 //! # Test Coverage
 //!
 //! - **Server Lifecycle** (7 tests): Start, stop, status, HTTP client availability, URL retrieval, uptime tracking
-//! - **Repository API** (2 tests): Algorithms endpoint, status endpoint (not connected)
+//! - **Repository API** (3 tests): Algorithms endpoint, status endpoint (not connected),
+//!   sync of a filesystem repository
 //! - **Error Handling** (3 tests): Stop when not running, double start, operations requiring running server
 //!
-//! Total: **10 integration tests** covering core Kopia server functionality
+//! Total: **11 integration tests** covering core Kopia server functionality
 //!
 //! # Important Notes
 //!
@@ -340,6 +341,85 @@ mod tests {
         let _ = server.stop();
     }
 
+    #[tokio::test]
+    #[ignore = "Requires Kopia binary - Run with: cargo test -- --ignored --test-threads=1"]
+    async fn test_repository_sync_filesystem() {
+        let config_dir_temp = get_test_config_dir();
+        let config_dir = config_dir_temp.path().to_str().expect("Invalid temp path");
+        let storage_temp = TempDir::new().expect("Failed to create temp storage directory");
+        let mut server = KopiaServer::new();
+
+        // Start server
+        let _ = server.start(config_dir).expect("Failed to start server");
+        let ready_waiter = server
+            .get_ready_waiter()
+            .expect("Failed to get ready waiter");
+        let _ = ready_waiter.await;
+
+        assert!(
+            wait_for_server_health(&server, 60).await,
+            "Server did not become healthy"
+        );
+
+        let client = server.get_http_client().expect("No HTTP client");
+        let server_url = server.get_server_url().expect("No server URL");
+        let sync_url = format!("{}/api/v1/repo/sync", server_url);
+
+        // Syncing without a repository maps to RepositoryNotConnected
+        let response = client
+            .post(&sync_url)
+            .send()
+            .await
+            .expect("Sync request failed");
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        assert!(
+            matches!(
+                KopiaError::from_api_response(status, &body, "Sync repository"),
+                KopiaError::RepositoryNotConnected { .. }
+            ),
+            "Unexpected sync error before connecting: {} {}",
+            status,
+            body
+        );
+
+        // Create a filesystem repository, which also connects to it
+        let create = serde_json::json!({
+            "storage": {
+                "type": "filesystem",
+                "config": {"path": storage_temp.path().to_str().expect("Invalid temp path")}
+            },
+            "password": "integration-test-password",
+            "options": {},
+            "clientOptions": {}
+        });
+        let response = client
+            .post(format!("{}/api/v1/repo/create", server_url))
+            .json(&create)
+            .send()
+            .await
+            .expect("Create request failed");
+        assert!(
+            response.status().is_success(),
+            "Repository creation failed: {}",
+            response.text().await.unwrap_or_default()
+        );
+
+        let response = client
+            .post(&sync_url)
+            .send()
+            .await
+            .expect("Sync request failed");
+        assert!(
+            response.status().is_success(),
+            "Sync failed: {}",
+            response.text().await.unwrap_or_default()
+        );
+
+        // Stop server
+        let _ = server.stop();
+    }
+
     // ============================================================================
     // Error Handling Tests
     // ============================================================================
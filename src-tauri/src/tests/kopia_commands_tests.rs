@@ -234,4 +234,3262 @@ mod tests {
         assert!(json.contains("binary lookup"));
         assert!(json.contains("/usr/bin/kopia"));
     }
+
+    #[test]
+    fn test_parse_task_source() {
+        use crate::commands::kopia::parse_task_source;
+
+        let source = parse_task_source("alice@laptop:/home/alice at 2024-01-01T00:00:00Z").unwrap();
+        assert_eq!(source.user_name, "alice");
+        assert_eq!(source.host, "laptop");
+        assert_eq!(source.path, "/home/alice");
+
+        let source = parse_task_source("Snapshot bob@pc:C:\\Users\\bob").unwrap();
+        assert_eq!(source.user_name, "bob");
+        assert_eq!(source.path, "C:\\Users\\bob");
+
+        assert!(parse_task_source("Full maintenance").is_none());
+    }
+
+    #[test]
+    fn test_parse_excluded_paths() {
+        use crate::commands::kopia::parse_excluded_paths;
+
+        let logs = vec![
+            serde_json::json!("excluded dir: /home/alice/.cache"),
+            serde_json::json!({"msg": "ignored", "path": "/home/alice/tmp.log"}),
+            serde_json::json!("ignored file {\"path\":\"/home/alice/node_modules\"}"),
+            serde_json::json!("ignored error reading /home/alice/locked"),
+            serde_json::json!("snapshotted directory /home/alice"),
+            serde_json::json!("excluded dir: /home/alice/.cache"),
+        ];
+
+        assert_eq!(
+            parse_excluded_paths(&logs),
+            vec![
+                "/home/alice/.cache",
+                "/home/alice/tmp.log",
+                "/home/alice/node_modules"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_excluded_paths_non_ascii_prefix() {
+        use crate::commands::kopia::parse_excluded_paths;
+
+        // "İ" lowercases to a longer byte sequence, so offsets found in the
+        // lowercased line don't fit the original
+        let logs = vec![serde_json::json!(
+            "İsmail İnce: Excluded dir: /home/İnce/.cache"
+        )];
+
+        assert_eq!(parse_excluded_paths(&logs), vec!["/home/İnce/.cache"]);
+    }
+
+    #[test]
+    fn test_summarize_tasks_by_kind() {
+        use crate::commands::kopia::{summarize_tasks_by_kind, TASKS_SUMMARY_TOTAL_KEY};
+        use crate::types::Task;
+
+        let task = |kind: &str, status: &str| Task {
+            id: "1".to_string(),
+            start_time: "2024-01-01T00:00:00Z".to_string(),
+            end_time: None,
+            kind: kind.to_string(),
+            description: String::new(),
+            status: status.to_string(),
+            progress_info: String::new(),
+            error_message: None,
+            counters: Default::default(),
+        };
+
+        let tasks = vec![
+            task("Snapshot", "RUNNING"),
+            task("Snapshot", "SUCCESS"),
+            task("Snapshot", "FAILED"),
+            task("Maintenance", "SUCCESS"),
+            task("Restore", "CANCELED"),
+        ];
+
+        let summary = summarize_tasks_by_kind(&tasks);
+        assert_eq!(summary.len(), 4);
+
+        let snapshot = &summary["Snapshot"];
+        assert_eq!(
+            (snapshot.running, snapshot.success, snapshot.failed),
+            (1, 1, 1)
+        );
+        assert_eq!(summary["Maintenance"].success, 1);
+        assert_eq!(summary["Restore"].canceled, 1);
+
+        let total = &summary[TASKS_SUMMARY_TOTAL_KEY];
+        assert_eq!(
+            (total.running, total.success, total.failed, total.canceled),
+            (1, 2, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_summarize_tasks_by_kind_empty() {
+        use crate::commands::kopia::{summarize_tasks_by_kind, TASKS_SUMMARY_TOTAL_KEY};
+
+        let summary = summarize_tasks_by_kind(&[]);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[TASKS_SUMMARY_TOTAL_KEY].running, 0);
+    }
+
+    fn restore_request() -> crate::types::RestoreRequest {
+        crate::types::RestoreRequest {
+            root: "k1234".to_string(),
+            fs_output: None,
+            zip_file: None,
+            uncompressed_zip: None,
+            tar_file: None,
+            options: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_restore_request_single_output() {
+        use crate::commands::kopia::validate_restore_request;
+        use crate::types::FilesystemOutput;
+
+        let mut request = restore_request();
+        request.fs_output = Some(FilesystemOutput::default());
+        assert!(validate_restore_request(&request).is_ok());
+
+        let mut request = restore_request();
+        request.zip_file = Some("/tmp/out.zip".to_string());
+        request.uncompressed_zip = Some(true);
+        assert!(validate_restore_request(&request).is_ok());
+
+        let mut request = restore_request();
+        request.tar_file = Some("/tmp/out.tar".to_string());
+        assert!(validate_restore_request(&request).is_ok());
+    }
+
+    #[test]
+    fn test_validate_restore_request_no_output() {
+        use crate::commands::kopia::validate_restore_request;
+
+        let err = validate_restore_request(&restore_request()).unwrap_err();
+        assert!(matches!(err, KopiaError::InvalidInput { ref field, .. } if field == "output"));
+    }
+
+    #[test]
+    fn test_validate_restore_request_multiple_outputs() {
+        use crate::commands::kopia::validate_restore_request;
+        use crate::types::FilesystemOutput;
+
+        let mut request = restore_request();
+        request.fs_output = Some(FilesystemOutput::default());
+        request.zip_file = Some("/tmp/out.zip".to_string());
+        let err = validate_restore_request(&request).unwrap_err();
+        assert!(matches!(err, KopiaError::InvalidInput { ref field, .. } if field == "output"));
+
+        request.tar_file = Some("/tmp/out.tar".to_string());
+        assert!(validate_restore_request(&request).is_err());
+    }
+
+    fn running_task(id: &str, start_time: &str, hashed_files: i64) -> crate::types::Task {
+        let mut counters = std::collections::HashMap::new();
+        counters.insert(
+            "Hashed Files".to_string(),
+            crate::types::CounterValue {
+                value: hashed_files,
+                units: None,
+                level: String::new(),
+            },
+        );
+
+        crate::types::Task {
+            id: id.to_string(),
+            start_time: start_time.to_string(),
+            end_time: None,
+            kind: "Snapshot".to_string(),
+            description: String::new(),
+            status: "RUNNING".to_string(),
+            progress_info: String::new(),
+            error_message: None,
+            counters,
+        }
+    }
+
+    #[test]
+    fn test_is_long_running() {
+        use crate::commands::kopia::is_long_running;
+
+        let now = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let old = running_task("1", "2024-01-01T10:00:00Z", 0);
+        assert!(is_long_running(&old, now, 3600));
+
+        let recent = running_task("2", "2024-01-01T11:59:00Z", 0);
+        assert!(!is_long_running(&recent, now, 3600));
+
+        let mut finished = old.clone();
+        finished.status = "SUCCESS".to_string();
+        assert!(!is_long_running(&finished, now, 3600));
+
+        let invalid = running_task("3", "not a time", 0);
+        assert!(!is_long_running(&invalid, now, 0));
+    }
+
+    #[test]
+    fn test_find_stuck_tasks() {
+        use crate::commands::kopia::find_stuck_tasks;
+
+        let earlier = vec![
+            running_task("stuck", "2024-01-01T10:00:00Z", 10),
+            running_task("moving", "2024-01-01T10:00:00Z", 10),
+            running_task("done", "2024-01-01T10:00:00Z", 10),
+        ];
+
+        let mut done = running_task("done", "2024-01-01T10:00:00Z", 10);
+        done.status = "SUCCESS".to_string();
+        let current = vec![
+            running_task("stuck", "2024-01-01T10:00:00Z", 10),
+            running_task("moving", "2024-01-01T10:00:00Z", 25),
+            done,
+            running_task("new", "2024-01-01T10:00:00Z", 0),
+        ];
+
+        let stuck = find_stuck_tasks(&earlier, &current);
+        assert_eq!(stuck.len(), 1);
+        assert_eq!(stuck[0].id, "stuck");
+    }
+
+    #[test]
+    fn test_counter_warnings() {
+        use crate::commands::kopia::counter_warnings;
+        use crate::types::CounterValue;
+
+        let counter = |value: i64, level: &str| CounterValue {
+            value,
+            units: None,
+            level: level.to_string(),
+        };
+
+        let mut counters = std::collections::HashMap::new();
+        counters.insert("Hashed Files".to_string(), counter(100, ""));
+        counters.insert("Excluded Files".to_string(), counter(3, "notice"));
+        counters.insert("Ignored Errors".to_string(), counter(2, "warning"));
+        counters.insert("Errors".to_string(), counter(1, "error"));
+        counters.insert("Access Denied".to_string(), counter(4, "warning"));
+
+        let warnings = counter_warnings(&counters);
+        let names: Vec<&str> = warnings.iter().map(|w| w.name.as_str()).collect();
+        assert_eq!(names, vec!["Errors", "Access Denied", "Ignored Errors"]);
+        assert_eq!(warnings[0].level, "error");
+        assert_eq!(warnings[0].value, 1);
+
+        assert!(counter_warnings(&std::collections::HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn test_build_activity_feed() {
+        use crate::commands::kopia::build_activity_feed;
+        use crate::types::ActivityItem;
+
+        let task =
+            |id: &str, kind: &str, status: &str, end_time: Option<&str>| crate::types::Task {
+                id: id.to_string(),
+                start_time: "2024-01-01T00:00:00Z".to_string(),
+                end_time: end_time.map(String::from),
+                kind: kind.to_string(),
+                description: format!("{} description", kind),
+                status: status.to_string(),
+                progress_info: String::new(),
+                error_message: (status == "FAILED").then(|| "boom".to_string()),
+                counters: Default::default(),
+            };
+
+        let tasks = vec![
+            task("1", "Snapshot", "SUCCESS", Some("2024-01-01T01:00:00Z")),
+            task("2", "Restore", "CANCELED", Some("2024-01-01T03:00:00Z")),
+            task("3", "Maintenance", "SUCCESS", Some("2024-01-01T02:00:00Z")),
+            task("4", "Snapshot", "FAILED", Some("2024-01-01T04:00:00Z")),
+            task("5", "Snapshot", "RUNNING", None),
+            task("6", "Estimate", "SUCCESS", Some("2024-01-01T05:00:00Z")),
+        ];
+
+        let feed = build_activity_feed(&tasks, 10);
+        assert_eq!(feed.len(), 4);
+
+        assert_eq!(
+            feed[0],
+            ActivityItem::Error {
+                task_id: "4".to_string(),
+                time: "2024-01-01T04:00:00Z".to_string(),
+                kind: "Snapshot".to_string(),
+                description: "Snapshot description".to_string(),
+                message: Some("boom".to_string()),
+            }
+        );
+        assert!(matches!(
+            &feed[1],
+            ActivityItem::RestoreCompleted { outcome, .. } if outcome == "canceled"
+        ));
+        assert!(matches!(&feed[2], ActivityItem::MaintenanceRun { .. }));
+        assert!(matches!(
+            &feed[3],
+            ActivityItem::SnapshotCompleted { source, outcome, .. }
+                if source == "Snapshot description" && outcome == "success"
+        ));
+
+        assert_eq!(build_activity_feed(&tasks, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_activity_item_serialization() {
+        use crate::types::ActivityItem;
+
+        let item = ActivityItem::SnapshotCompleted {
+            task_id: "1".to_string(),
+            time: "2024-01-01T00:00:00Z".to_string(),
+            source: "user@host:/data".to_string(),
+            outcome: "success".to_string(),
+        };
+        let json = serde_json::to_value(&item).unwrap();
+        assert_eq!(json["type"], "SnapshotCompleted");
+        assert_eq!(json["taskId"], "1");
+        assert_eq!(json["source"], "user@host:/data");
+    }
+
+    #[test]
+    fn test_summarize_source() {
+        use crate::commands::kopia::summarize_source;
+
+        let source = crate::types::SourceInfo {
+            user_name: "user".to_string(),
+            host: "host".to_string(),
+            path: "/data".to_string(),
+        };
+        let snapshots: crate::types::SnapshotsResponse = serde_json::from_value(serde_json::json!({
+            "snapshots": [
+                {"id": "a", "startTime": "2024-01-02T00:00:00Z", "summary": {"size": 200}},
+                {"id": "b", "startTime": "2024-01-03T00:00:00.5+01:00", "summary": {"size": 300}},
+                {"id": "c", "startTime": "2024-01-01T00:00:00Z"}
+            ],
+            "unfilteredCount": 3,
+            "uniqueCount": 3
+        }))
+        .unwrap();
+
+        let summary = summarize_source(source.clone(), &snapshots);
+        assert_eq!(summary.snapshot_count, 3);
+        assert_eq!(
+            summary.last_snapshot_time.as_deref(),
+            Some("2024-01-03T00:00:00.5+01:00")
+        );
+        assert_eq!(summary.last_snapshot_size, Some(300));
+
+        let empty: crate::types::SnapshotsResponse = serde_json::from_value(serde_json::json!({
+            "snapshots": [], "unfilteredCount": 0, "uniqueCount": 0
+        }))
+        .unwrap();
+        let summary = summarize_source(source, &empty);
+        assert_eq!(summary.snapshot_count, 0);
+        assert!(summary.last_snapshot_time.is_none());
+    }
+
+    #[test]
+    fn test_build_sources_summary_partial() {
+        use crate::commands::kopia::build_sources_summary;
+        use crate::fan_out::FanOutResult;
+
+        let source = crate::types::SourceInfo {
+            user_name: "user".to_string(),
+            host: "host".to_string(),
+            path: "/data".to_string(),
+        };
+        let result = FanOutResult {
+            completed: vec![(
+                source.to_string(),
+                crate::types::SourceSummary {
+                    source,
+                    snapshot_count: 1,
+                    last_snapshot_time: None,
+                    last_snapshot_size: None,
+                },
+            )],
+            timed_out: vec!["user@host:/slow".to_string()],
+            failed: vec![("user@host:/broken".to_string(), "boom".to_string())],
+        };
+
+        let summary = build_sources_summary(result);
+        assert!(summary.partial);
+        assert_eq!(summary.sources.len(), 1);
+        assert_eq!(summary.timed_out_sources, vec!["user@host:/slow"]);
+        assert_eq!(summary.failed_sources, vec!["user@host:/broken"]);
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["timedOutSources"][0], "user@host:/slow");
+        assert_eq!(json["sources"][0]["source"]["userName"], "user");
+    }
+
+    #[test]
+    fn test_format_cache_duration_arg() {
+        use crate::commands::kopia::format_cache_duration_arg;
+
+        assert_eq!(
+            format_cache_duration_arg(900).unwrap(),
+            "--format-blob-cache-duration=900s"
+        );
+        assert_eq!(
+            format_cache_duration_arg(0).unwrap(),
+            "--format-blob-cache-duration=0s"
+        );
+        assert!(matches!(
+            format_cache_duration_arg(-1),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_max_pack_size_arg() {
+        use crate::commands::kopia::max_pack_size_arg;
+
+        assert_eq!(
+            max_pack_size_arg(20 << 20).unwrap(),
+            "--max-pack-size-mb=20"
+        );
+        assert_eq!(
+            max_pack_size_arg(10 << 20).unwrap(),
+            "--max-pack-size-mb=10"
+        );
+        assert_eq!(
+            max_pack_size_arg(120 << 20).unwrap(),
+            "--max-pack-size-mb=120"
+        );
+
+        for bytes in [0, -(20 << 20), 9 << 20, 121 << 20, (20 << 20) + 1] {
+            assert!(
+                matches!(
+                    max_pack_size_arg(bytes),
+                    Err(KopiaError::InvalidInput { .. })
+                ),
+                "{} should be rejected",
+                bytes
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_format_blob_cache_duration() {
+        use crate::commands::kopia::read_format_blob_cache_duration;
+
+        assert_eq!(
+            read_format_blob_cache_duration(r#"{"formatBlobCacheDuration": 900000000000}"#),
+            Some(900_000_000_000)
+        );
+        assert_eq!(
+            read_format_blob_cache_duration(r#"{"hostname": "host"}"#),
+            Some(0)
+        );
+        assert_eq!(read_format_blob_cache_duration("not json"), None);
+    }
+
+    #[test]
+    fn test_read_permissive_cache_loading() {
+        use crate::commands::kopia::read_permissive_cache_loading;
+
+        assert_eq!(
+            read_permissive_cache_loading(r#"{"permissiveCacheLoading": true}"#),
+            Some(true)
+        );
+        assert_eq!(
+            read_permissive_cache_loading(r#"{"hostname": "host"}"#),
+            Some(false)
+        );
+        assert_eq!(read_permissive_cache_loading("not json"), None);
+    }
+
+    #[test]
+    fn test_find_missed_snapshots() {
+        use crate::commands::kopia::find_missed_snapshots;
+
+        let source =
+            |path: &str, status: &str, schedule: serde_json::Value, extra: serde_json::Value| {
+                let mut value = serde_json::json!({
+                    "source": {"userName": "user", "host": "host", "path": path},
+                    "status": status,
+                    "schedule": schedule,
+                });
+                for (k, v) in extra.as_object().unwrap() {
+                    value[k] = v.clone();
+                }
+                serde_json::from_value::<crate::types::SnapshotSource>(value).unwrap()
+            };
+
+        let sources = vec![
+            // Due an hour ago
+            source(
+                "/a",
+                "IDLE",
+                serde_json::json!({}),
+                serde_json::json!({"nextSnapshotTime": "2024-01-01T11:00:00Z"}),
+            ),
+            // Due within the grace period
+            source(
+                "/b",
+                "IDLE",
+                serde_json::json!({}),
+                serde_json::json!({"nextSnapshotTime": "2024-01-01T11:59:30Z"}),
+            ),
+            // Due in the future
+            source(
+                "/c",
+                "IDLE",
+                serde_json::json!({}),
+                serde_json::json!({"nextSnapshotTime": "2024-01-01T13:00:00Z"}),
+            ),
+            // Already running
+            source(
+                "/d",
+                "UPLOADING",
+                serde_json::json!({}),
+                serde_json::json!({"nextSnapshotTime": "2024-01-01T10:00:00Z"}),
+            ),
+            // Manual only
+            source(
+                "/e",
+                "IDLE",
+                serde_json::json!({"manual": true}),
+                serde_json::json!({"nextSnapshotTime": "2024-01-01T10:00:00Z"}),
+            ),
+            // No next time: last snapshot + 1h interval, due two hours ago
+            source(
+                "/f",
+                "IDLE",
+                serde_json::json!({"intervalSeconds": 3600}),
+                serde_json::json!({"lastSnapshot": {"id": "x", "startTime": "2024-01-01T09:00:00Z"}}),
+            ),
+        ];
+
+        let now = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let missed = find_missed_snapshots(&sources, now);
+
+        let paths: Vec<&str> = missed.iter().map(|m| m.source.path.as_str()).collect();
+        assert_eq!(paths, vec!["/f", "/a"]);
+        assert_eq!(missed[0].missed_by_secs, 7200);
+        assert_eq!(missed[1].missed_by_secs, 3600);
+        assert_eq!(missed[1].scheduled_time, "2024-01-01T11:00:00+00:00");
+    }
+
+    #[test]
+    fn test_build_policy_delete_results() {
+        use crate::commands::kopia::build_policy_delete_results;
+        use crate::fan_out::FanOutResult;
+        use crate::types::PolicyTarget;
+
+        let target = |host: Option<&str>, path: Option<&str>| PolicyTarget {
+            user_name: host.map(|_| "user".to_string()),
+            host: host.map(String::from),
+            path: path.map(String::from),
+        };
+        let targets = vec![
+            target(Some("host"), Some("/a")),
+            target(Some("host"), Some("/b")),
+            target(Some("host"), None),
+            target(None, None),
+        ];
+        assert_eq!(targets[0].to_string(), "user@host:/a");
+        assert_eq!(targets[2].to_string(), "user@host");
+        assert_eq!(targets[3].to_string(), "(global)");
+
+        let result = FanOutResult {
+            completed: vec![
+                ("user@host:/a".to_string(), ()),
+                ("(global)".to_string(), ()),
+            ],
+            timed_out: vec!["user@host".to_string()],
+            failed: vec![("user@host:/b".to_string(), "boom".to_string())],
+        };
+
+        let results =
+            build_policy_delete_results(targets, result, std::time::Duration::from_secs(15));
+        let outcomes: Vec<(bool, Option<&str>)> = results
+            .iter()
+            .map(|r| (r.success, r.error.as_deref()))
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                (true, None),
+                (false, Some("boom")),
+                (false, Some("Timed out after 15s")),
+                (true, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_summarize_active_operations() {
+        use crate::commands::kopia::summarize_active_operations;
+
+        let mut maintenance = running_task("2", "2024-01-01T00:00:00Z", 0);
+        maintenance.kind = "Maintenance".to_string();
+        let mut canceling = running_task("3", "2024-01-01T00:00:00Z", 0);
+        canceling.status = "CANCELING".to_string();
+        let mut finished = running_task("4", "2024-01-01T00:00:00Z", 0);
+        finished.kind = "Restore".to_string();
+        finished.status = "SUCCESS".to_string();
+
+        let tasks = vec![
+            running_task("1", "2024-01-01T00:00:00Z", 0),
+            maintenance,
+            canceling,
+            finished,
+        ];
+
+        let active = summarize_active_operations(&tasks);
+        assert!(active.active);
+        assert_eq!(active.count, 3);
+        assert_eq!(active.kinds, vec!["Maintenance", "Snapshot"]);
+
+        assert_eq!(
+            summarize_active_operations(&[]),
+            crate::types::ActiveOperations::default()
+        );
+    }
+
+    #[test]
+    fn test_validate_upload_policy() {
+        use crate::commands::kopia::validate_upload_policy;
+        use crate::types::UploadPolicy;
+
+        assert!(validate_upload_policy(&UploadPolicy::default()).is_ok());
+        assert!(validate_upload_policy(&UploadPolicy {
+            max_parallel_snapshots: Some(2),
+            max_parallel_file_reads: Some(8),
+            parallel_upload_above_size: Some(2_147_483_648),
+        })
+        .is_ok());
+
+        let error = validate_upload_policy(&UploadPolicy {
+            max_parallel_file_reads: Some(0),
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            KopiaError::invalid_input("maxParallelFileReads", "Must be a positive number")
+        );
+
+        assert!(validate_upload_policy(&UploadPolicy {
+            max_parallel_snapshots: Some(-1),
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_split_relative_path() {
+        use crate::commands::kopia::split_relative_path;
+
+        assert_eq!(
+            split_relative_path("/home/user/Documents/").unwrap(),
+            vec!["home", "user", "Documents"]
+        );
+        assert_eq!(
+            split_relative_path("Users\\me\\./Desktop").unwrap(),
+            vec!["Users", "me", "Desktop"]
+        );
+        assert!(split_relative_path("").unwrap().is_empty());
+        assert!(matches!(
+            split_relative_path("home/../etc"),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_find_subdirectory() {
+        use crate::commands::kopia::find_subdirectory;
+
+        let directory: crate::types::DirectoryObject = serde_json::from_value(serde_json::json!({
+            "stream": "kopia:directory",
+            "entries": [
+                {"name": "docs", "type": "f", "mode": "0644", "mtime": "2024-01-01T00:00:00Z", "obj": "f1"},
+                {"name": "docs", "type": "d", "mode": "0755", "mtime": "2024-01-01T00:00:00Z", "obj": "kdir"},
+                {"name": "notes.txt", "type": "f", "mode": "0644", "mtime": "2024-01-01T00:00:00Z", "obj": "f2"}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(find_subdirectory(&directory, "docs").unwrap().obj, "kdir");
+        assert!(find_subdirectory(&directory, "notes.txt").is_none());
+        assert!(find_subdirectory(&directory, "missing").is_none());
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        use crate::commands::kopia::csv_field;
+
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    #[test]
+    fn test_snapshots_to_csv() {
+        use crate::commands::kopia::snapshots_to_csv;
+
+        let snapshots: Vec<crate::types::Snapshot> = serde_json::from_value(serde_json::json!([
+            {
+                "id": "k1",
+                "startTime": "2024-01-01T00:00:00Z",
+                "endTime": "2024-01-01T00:05:00Z",
+                "summary": {"size": 1024, "files": 10},
+                "pins": ["keep", "audit, 2024"],
+                "retention": ["latest-1", "daily-1"]
+            },
+            {
+                "id": "k2",
+                "startTime": "2024-01-02T00:00:00Z",
+                "incomplete": "canceled"
+            }
+        ]))
+        .unwrap();
+
+        let csv = snapshots_to_csv(&snapshots);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(
+            lines[0],
+            "id,start_time,end_time,size,file_count,incomplete,pins,retention"
+        );
+        assert_eq!(
+            lines[1],
+            "k1,2024-01-01T00:00:00Z,2024-01-01T00:05:00Z,1024,10,,\"keep; audit, 2024\",latest-1; daily-1"
+        );
+        assert_eq!(lines[2], "k2,2024-01-02T00:00:00Z,,,,canceled,,");
+        assert_eq!(lines[3], "");
+    }
+
+    #[test]
+    fn test_validate_repository_name() {
+        use crate::commands::kopia::validate_repository_name;
+
+        assert_eq!(validate_repository_name("  Photos  ").unwrap(), "Photos");
+        assert_eq!(
+            validate_repository_name(&"é".repeat(128))
+                .unwrap()
+                .chars()
+                .count(),
+            128
+        );
+        assert!(matches!(
+            validate_repository_name("   "),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            validate_repository_name(&"a".repeat(129)),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_redact_notification_profile() {
+        use crate::commands::kopia::redact_notification_profile;
+        use crate::types::{NotificationMethod, NotificationProfile};
+
+        let profile = NotificationProfile {
+            profile: "email".to_string(),
+            method: NotificationMethod {
+                method_type: "email".to_string(),
+                config: serde_json::json!({
+                    "smtpServer": "smtp.example.com",
+                    "smtpPort": 587,
+                    "smtpUsername": "alice",
+                    "smtpPassword": "hunter2",
+                    "to": "alice@example.com"
+                }),
+            },
+            min_severity: 10,
+        };
+
+        let exported = redact_notification_profile(profile);
+        assert!(exported.redacted);
+        assert_eq!(exported.redacted_fields, vec!["smtpPassword"]);
+        assert_eq!(exported.profile.method.config["smtpPassword"], "");
+        assert_eq!(exported.profile.method.config["smtpUsername"], "alice");
+
+        let webhook = NotificationProfile {
+            profile: "hook".to_string(),
+            method: NotificationMethod {
+                method_type: "webhook".to_string(),
+                config: serde_json::json!({
+                    "endpoint": "https://example.com/hook",
+                    "headers": "Authorization: Bearer abc",
+                }),
+            },
+            min_severity: 0,
+        };
+        let exported = redact_notification_profile(webhook);
+        assert_eq!(exported.redacted_fields, vec!["headers"]);
+
+        // Empty secrets aren't reported
+        let pushover = NotificationProfile {
+            profile: "push".to_string(),
+            method: NotificationMethod {
+                method_type: "pushover".to_string(),
+                config: serde_json::json!({ "appToken": "", "userKey": null }),
+            },
+            min_severity: 0,
+        };
+        let exported = redact_notification_profile(pushover);
+        assert!(!exported.redacted);
+        assert!(exported.redacted_fields.is_empty());
+    }
+
+    #[test]
+    fn test_parse_notification_profiles_export() {
+        use crate::commands::kopia::parse_notification_profiles_export;
+
+        let json = r#"{
+            "version": 1,
+            "profiles": [{
+                "profile": "push",
+                "method": {"type": "pushover", "config": {"appToken": "", "userKey": ""}},
+                "minSeverity": 20,
+                "redacted": true,
+                "redactedFields": ["appToken", "userKey"]
+            }]
+        }"#;
+        let export = parse_notification_profiles_export(json).unwrap();
+        assert_eq!(export.profiles.len(), 1);
+        assert_eq!(export.profiles[0].profile.profile, "push");
+        assert_eq!(export.profiles[0].profile.min_severity, 20);
+        assert_eq!(
+            export.profiles[0].redacted_fields,
+            vec!["appToken", "userKey"]
+        );
+
+        assert!(matches!(
+            parse_notification_profiles_export("[]"),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+        assert!(matches!(
+            parse_notification_profiles_export(r#"{"version": 2, "profiles": []}"#),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compute_repository_health() {
+        use crate::commands::kopia::compute_repository_health;
+        use crate::types::{HealthIssueKind, MaintenanceInfo, SnapshotSource, TasksSummary};
+
+        let now = chrono::DateTime::parse_from_rfc3339("2024-01-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let source = |path: &str, last_snapshot: serde_json::Value| {
+            let mut value = serde_json::json!({
+                "source": {"userName": "user", "host": "host", "path": path},
+                "status": "IDLE",
+                "schedule": {},
+            });
+            if !last_snapshot.is_null() {
+                value["lastSnapshot"] = last_snapshot;
+            }
+            serde_json::from_value::<SnapshotSource>(value).unwrap()
+        };
+        let snapshot = |errors: i64| {
+            serde_json::json!({
+                "id": "s1",
+                "startTime": "2024-01-10T00:00:00Z",
+                "stats": {"errorCount": errors},
+            })
+        };
+        let healthy_maintenance: MaintenanceInfo = serde_json::from_value(serde_json::json!({
+            "params": {"owner": "user@host", "full": {"enabled": true, "interval": 86400000000000i64}},
+            "schedule": {
+                "nextFullMaintenance": "2024-01-10T18:00:00Z",
+                "runs": {"snapshot-gc": [{"start": "2024-01-09T18:00:00Z", "end": "2024-01-09T18:05:00Z", "success": true}]}
+            }
+        }))
+        .unwrap();
+
+        // Everything fine
+        let health = compute_repository_health(
+            Some(&healthy_maintenance),
+            &TasksSummary::default(),
+            &[source("/a", snapshot(0))],
+            now,
+        );
+        assert_eq!(health.score, 100);
+        assert!(health.issues.is_empty());
+
+        // Overdue and failed maintenance, failed tasks, snapshot errors, missing snapshot
+        let bad_maintenance: MaintenanceInfo = serde_json::from_value(serde_json::json!({
+            "params": {"full": {"enabled": true}},
+            "schedule": {
+                "nextFullMaintenance": "2024-01-05T00:00:00Z",
+                "runs": {
+                    "snapshot-gc": [{"start": "2024-01-04T00:00:00Z", "end": "2024-01-04T00:01:00Z", "success": true}],
+                    "cleanup-logs": [{"start": "2024-01-04T01:00:00Z", "end": "2024-01-04T01:01:00Z", "success": false, "error": "boom"}]
+                }
+            }
+        }))
+        .unwrap();
+        let tasks = TasksSummary {
+            failed: 2,
+            ..Default::default()
+        };
+        let health = compute_repository_health(
+            Some(&bad_maintenance),
+            &tasks,
+            &[
+                source("/a", snapshot(3)),
+                source("/b", serde_json::Value::Null),
+            ],
+            now,
+        );
+        let kinds: Vec<HealthIssueKind> = health.issues.iter().map(|i| i.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                HealthIssueKind::MaintenanceOverdue,
+                HealthIssueKind::MaintenanceFailed,
+                HealthIssueKind::FailedTasks,
+                HealthIssueKind::SnapshotErrors,
+                HealthIssueKind::NoSnapshots,
+            ]
+        );
+        assert!(health.issues[1].message.contains("boom"));
+        // 20 + 15 + 2*5 + 10 + 5
+        assert_eq!(health.score, 40);
+
+        // Disabled maintenance; repeated problems are capped per kind
+        let disabled = MaintenanceInfo::default();
+        let tasks = TasksSummary {
+            failed: 100,
+            ..Default::default()
+        };
+        let sources: Vec<SnapshotSource> = (0..10)
+            .map(|i| source(&format!("/{}", i), snapshot(1)))
+            .chain((10..20).map(|i| source(&format!("/{}", i), serde_json::Value::Null)))
+            .collect();
+        let health = compute_repository_health(Some(&disabled), &tasks, &sources, now);
+        assert_eq!(health.issues[0].kind, HealthIssueKind::MaintenanceDisabled);
+        // 20 + 25 + 30 + 15
+        assert_eq!(health.score, 10);
+
+        // Without maintenance info only the other checks apply
+        let health = compute_repository_health(None, &TasksSummary::default(), &[], now);
+        assert_eq!(health.score, 100);
+    }
+
+    #[test]
+    fn test_throttled_limits_only_changes_upload() {
+        use crate::commands::kopia::throttled_limits;
+
+        let limits = crate::types::ThrottleLimits {
+            reads_per_second: Some(10.0),
+            max_upload_speed_bytes_per_second: Some(5000.0),
+            max_download_speed_bytes_per_second: Some(8000.0),
+            ..Default::default()
+        };
+
+        let throttled = throttled_limits(&limits, 1000.0);
+        assert_eq!(throttled.max_upload_speed_bytes_per_second, Some(1000.0));
+        assert_eq!(throttled.reads_per_second, Some(10.0));
+        assert_eq!(throttled.max_download_speed_bytes_per_second, Some(8000.0));
+    }
+
+    #[test]
+    fn test_dedupe_file_versions() {
+        use crate::commands::kopia::dedupe_file_versions;
+        use crate::types::FileVersion;
+
+        let version = |snapshot_id: &str, time: &str, object_id: &str| FileVersion {
+            snapshot_id: snapshot_id.to_string(),
+            snapshot_time: time.to_string(),
+            object_id: object_id.to_string(),
+            size: Some(10),
+        };
+
+        let versions = dedupe_file_versions(vec![
+            version("s3", "2024-01-03T00:00:00Z", "a"),
+            version("s1", "2024-01-01T00:00:00Z", "a"),
+            version("s2", "2024-01-02T00:00:00Z", "b"),
+            version("s4", "2024-01-04T00:00:00Z", "a"),
+        ]);
+
+        let ids: Vec<_> = versions
+            .iter()
+            .map(|v| (v.snapshot_id.as_str(), v.object_id.as_str()))
+            .collect();
+        assert_eq!(ids, vec![("s1", "a"), ("s2", "b")]);
+
+        // Fractional seconds and offsets don't sort correctly as strings
+        let versions = dedupe_file_versions(vec![
+            version("late", "2024-01-01T10:00:00.5Z", "a"),
+            version("early", "2024-01-01T10:00:00Z", "a"),
+            version("offset", "2024-01-01T11:30:00+02:00", "b"),
+            version("utc", "2024-01-01T10:00:01Z", "b"),
+            version("bad", "not a time", "a"),
+        ]);
+        let ids: Vec<_> = versions.iter().map(|v| v.snapshot_id.as_str()).collect();
+        assert_eq!(ids, vec!["offset", "early"]);
+    }
+
+    #[test]
+    fn test_notification_severity_levels() {
+        use crate::commands::kopia::{severity_label, severity_levels};
+
+        let levels = severity_levels();
+        let values: Vec<i32> = levels.iter().map(|level| level.value).collect();
+        assert_eq!(values, vec![-100, -10, 0, 10, 20]);
+        assert_eq!(levels[3].label, "Warning");
+
+        assert_eq!(severity_label(20), Some("Error"));
+        assert_eq!(severity_label(-10), Some("Success"));
+        assert_eq!(severity_label(5), None);
+    }
+
+    #[test]
+    fn test_validate_notification_profile_severity() {
+        use crate::commands::kopia::validate_notification_profile;
+
+        let mut profile: crate::types::NotificationProfile =
+            serde_json::from_value(serde_json::json!({
+                "profile": "mail",
+                "method": {"type": "email", "config": {}},
+                "minSeverity": 10
+            }))
+            .unwrap();
+        assert!(validate_notification_profile(&profile).is_ok());
+
+        profile.min_severity = 15;
+        assert!(matches!(
+            validate_notification_profile(&profile),
+            Err(KopiaError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_overlay_policy_merges_sections() {
+        use crate::commands::kopia::overlay_policy;
+        use crate::types::PolicyDefinition;
+
+        let base: PolicyDefinition = serde_json::from_value(serde_json::json!({
+            "files": {"ignore": ["*.tmp"], "oneFileSystem": true},
+            "retention": {"keepLatest": 10}
+        }))
+        .unwrap();
+        let overlay: PolicyDefinition = serde_json::from_value(serde_json::json!({
+            "files": {"ignore": []}
+        }))
+        .unwrap();
+
+        let merged = overlay_policy(&base, &overlay).unwrap();
+        let files = merged.files.unwrap();
+        assert_eq!(files.ignore, Some(vec![]));
+        assert_eq!(files.one_file_system, Some(true));
+        assert_eq!(merged.retention.unwrap().keep_latest, Some(10));
+    }
+
+    #[test]
+    fn test_upload_started() {
+        use crate::commands::kopia::upload_started;
+        use crate::types::SnapshotSource;
+
+        let status = |status: &str, directory: &str, last: Option<&str>| -> SnapshotSource {
+            let mut value = serde_json::json!({
+                "source": {"userName": "user", "host": "host", "path": "/data"},
+                "status": status,
+                "schedule": {},
+                "upload": {"directory": directory}
+            });
+            if let Some(id) = last {
+                value["lastSnapshot"] =
+                    serde_json::json!({"id": id, "startTime": "2024-01-01T00:00:00Z"});
+            }
+            serde_json::from_value(value).unwrap()
+        };
+
+        assert!(!upload_started(&status("IDLE", "", Some("s1")), Some("s1")));
+        assert!(!upload_started(
+            &status("UPLOADING", "", Some("s1")),
+            Some("s1")
+        ));
+        assert!(upload_started(
+            &status("UPLOADING", "docs", Some("s1")),
+            Some("s1")
+        ));
+        assert!(upload_started(&status("IDLE", "", Some("s2")), Some("s1")));
+        assert!(upload_started(&status("IDLE", "", Some("s1")), None));
+    }
+
+    #[test]
+    fn test_compute_overall_progress() {
+        use crate::commands::kopia::compute_overall_progress;
+        use crate::types::Task;
+
+        let task = |status: &str, counters: serde_json::Value| -> Task {
+            serde_json::from_value(serde_json::json!({
+                "id": "1",
+                "startTime": "2024-01-01T00:00:00Z",
+                "kind": "Snapshot",
+                "description": "",
+                "status": status,
+                "counters": counters
+            }))
+            .unwrap()
+        };
+
+        let tasks = vec![
+            task(
+                "RUNNING",
+                serde_json::json!({
+                    "Processed Bytes": {"value": 300},
+                    "Estimated Bytes": {"value": 1000}
+                }),
+            ),
+            task(
+                "RUNNING",
+                serde_json::json!({
+                    "Hashed Bytes": {"value": 500},
+                    "Cached Bytes": {"value": 500},
+                    "Estimated Bytes": {"value": 1000}
+                }),
+            ),
+            // No estimate yet: counted in bytesDone only
+            task(
+                "RUNNING",
+                serde_json::json!({"Processed Bytes": {"value": 700}}),
+            ),
+            task(
+                "SUCCESS",
+                serde_json::json!({
+                    "Processed Bytes": {"value": 5000},
+                    "Estimated Bytes": {"value": 5000}
+                }),
+            ),
+        ];
+
+        let progress = compute_overall_progress(&tasks);
+        assert_eq!(progress.active_tasks, 3);
+        assert_eq!(progress.bytes_done, 2000);
+        assert_eq!(progress.bytes_total, 2000);
+        assert!((progress.percent - 65.0).abs() < f64::EPSILON);
+
+        let idle = compute_overall_progress(&[]);
+        assert_eq!(idle.active_tasks, 0);
+        assert_eq!(idle.percent, 0.0);
+    }
+
+    #[test]
+    fn test_prune_candidates() {
+        use crate::commands::kopia::prune_candidates;
+        use crate::types::SnapshotSource;
+
+        let source = |path: &str, status: &str, last: bool| -> SnapshotSource {
+            let mut value = serde_json::json!({
+                "source": {"userName": "user", "host": "host", "path": path},
+                "status": status,
+                "schedule": {}
+            });
+            if last {
+                value["lastSnapshot"] =
+                    serde_json::json!({"id": "s1", "startTime": "2024-01-01T00:00:00Z"});
+            }
+            serde_json::from_value(value).unwrap()
+        };
+
+        let candidates = prune_candidates(&[
+            source("/backed-up", "IDLE", true),
+            source("/never", "IDLE", false),
+            source("/first-run", "UPLOADING", false),
+            source("/paused", "PAUSED", false),
+        ]);
+
+        let paths: Vec<&str> = candidates.iter().map(|s| s.path.as_str()).collect();
+        assert_eq!(paths, vec!["/never", "/paused"]);
+    }
+
+    #[test]
+    fn test_maintenance_skip_reason() {
+        use crate::commands::kopia::maintenance_skip_reason;
+
+        let info: crate::types::MaintenanceInfo = serde_json::from_value(serde_json::json!({
+            "params": {
+                "owner": "alice@laptop",
+                "quick": {"enabled": true, "interval": 3600000000000i64},
+                "full": {"enabled": false, "interval": 86400000000000i64}
+            },
+            "schedule": {"nextQuickMaintenance": "2024-01-01T12:00:00Z"}
+        }))
+        .unwrap();
+        let at = |t: &str| {
+            chrono::DateTime::parse_from_rfc3339(t)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+
+        assert_eq!(
+            maintenance_skip_reason(&info, "alice@laptop", false, at("2024-01-01T12:30:00Z")),
+            None
+        );
+        assert!(
+            maintenance_skip_reason(&info, "alice@laptop", false, at("2024-01-01T11:00:00Z"))
+                .unwrap()
+                .contains("isn't due")
+        );
+        assert!(
+            maintenance_skip_reason(&info, "bob@desktop", false, at("2024-01-01T12:30:00Z"))
+                .unwrap()
+                .contains("owned by alice@laptop")
+        );
+        assert_eq!(
+            maintenance_skip_reason(&info, "alice@laptop", true, at("2024-01-01T12:30:00Z")),
+            Some("Full maintenance is disabled".to_string())
+        );
+    }
+
+    #[test]
+    fn test_is_cleared_task() {
+        use crate::commands::kopia::is_cleared_task;
+
+        let task = |status: &str, end_time: Option<&str>| -> crate::types::Task {
+            serde_json::from_value(serde_json::json!({
+                "id": "1",
+                "startTime": "2024-01-01T00:00:00Z",
+                "endTime": end_time,
+                "kind": "Snapshot",
+                "description": "",
+                "status": status
+            }))
+            .unwrap()
+        };
+        let cutoff = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert!(is_cleared_task(
+            &task("SUCCESS", Some("2024-01-01T11:00:00Z")),
+            cutoff
+        ));
+        assert!(is_cleared_task(
+            &task("FAILED", Some("2024-01-01T12:00:00Z")),
+            cutoff
+        ));
+        assert!(!is_cleared_task(
+            &task("CANCELED", Some("2024-01-01T13:00:00Z")),
+            cutoff
+        ));
+        assert!(!is_cleared_task(&task("RUNNING", None), cutoff));
+        assert!(!is_cleared_task(&task("SUCCESS", None), cutoff));
+    }
+
+    #[test]
+    fn test_next_maintenance_time() {
+        use crate::commands::kopia::next_maintenance_time;
+
+        let info = |schedule: serde_json::Value| -> crate::types::MaintenanceInfo {
+            serde_json::from_value(serde_json::json!({
+                "params": {
+                    "owner": "alice@laptop",
+                    "quick": {"enabled": true, "interval": 3600000000000i64},
+                    "full": {"enabled": true, "interval": 86400000000000i64}
+                },
+                "schedule": schedule
+            }))
+            .unwrap()
+        };
+        let at = |t: &str| {
+            chrono::DateTime::parse_from_rfc3339(t)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let now = at("2024-01-01T10:00:00Z");
+
+        let scheduled = info(serde_json::json!({
+            "nextQuickMaintenance": "2024-01-01T12:00:00Z",
+            "nextFullMaintenance": "2024-01-01T11:00:00Z"
+        }));
+        assert_eq!(
+            next_maintenance_time(&scheduled, "alice@laptop", now),
+            Some(at("2024-01-01T11:00:00Z"))
+        );
+        assert_eq!(next_maintenance_time(&scheduled, "bob@desktop", now), None);
+
+        // Without a next time, fall back to the last run plus the interval
+        let ran = info(serde_json::json!({
+            "nextFullMaintenance": "2024-01-02T00:00:00Z",
+            "runs": {"snapshot-gc": [{"start": "2024-01-01T09:00:00Z", "end": "2024-01-01T09:30:00Z", "success": true}]}
+        }));
+        assert_eq!(
+            next_maintenance_time(&ran, "alice@laptop", now),
+            Some(at("2024-01-01T10:30:00Z"))
+        );
+
+        let mut disabled = scheduled.clone();
+        disabled.params.quick.enabled = false;
+        disabled.params.full.enabled = false;
+        assert_eq!(next_maintenance_time(&disabled, "alice@laptop", now), None);
+    }
+
+    #[test]
+    fn test_snapshot_content_stats_of() {
+        use crate::commands::kopia::snapshot_content_stats_of;
+
+        let snapshot: crate::types::Snapshot = serde_json::from_value(serde_json::json!({
+            "id": "k1",
+            "startTime": "2024-01-01T00:00:00Z",
+            "storageStats": {
+                "newData": {
+                    "objectBytes": 100,
+                    "originalContentBytes": 90,
+                    "packedContentBytes": 40,
+                    "fileObjects": 3,
+                    "dirObjects": 1,
+                    "contents": 4
+                },
+                "runningTotal": {"contents": 10, "packedContentBytes": 400}
+            }
+        }))
+        .unwrap();
+
+        let stats = snapshot_content_stats_of(&snapshot);
+        assert!(stats.stats_available);
+        assert_eq!(
+            stats.new_data,
+            Some(crate::types::ContentStats {
+                contents: 4,
+                packed_bytes: 40,
+                original_bytes: 90,
+                file_objects: 3,
+                dir_objects: 1,
+            })
+        );
+        assert_eq!(stats.running_total.unwrap().contents, 10);
+
+        let mut old = snapshot.clone();
+        old.storage_stats = None;
+        let stats = snapshot_content_stats_of(&old);
+        assert!(!stats.stats_available);
+        assert_eq!(stats.new_data, None);
+    }
+
+    #[test]
+    fn test_smtp_failure_stage() {
+        use crate::commands::kopia::smtp_failure_stage;
+
+        assert_eq!(
+            smtp_failure_stage("unable to send: dial tcp: lookup smtp.example.com: no such host"),
+            Some("connect")
+        );
+        assert_eq!(
+            smtp_failure_stage("dial tcp 192.0.2.1:587: connect: connection refused"),
+            Some("connect")
+        );
+        assert_eq!(
+            smtp_failure_stage(
+                "tls: failed to verify certificate: x509: certificate signed by unknown authority"
+            ),
+            Some("connect")
+        );
+        assert_eq!(
+            smtp_failure_stage("535 5.7.8 Username and Password not accepted"),
+            Some("auth")
+        );
+        assert_eq!(
+            smtp_failure_stage("smtp: server doesn't support AUTH"),
+            Some("auth")
+        );
+        assert_eq!(
+            smtp_failure_stage("550 5.1.1 <nobody@example.com>: Recipient address rejected"),
+            Some("send")
+        );
+        assert_eq!(smtp_failure_stage("something unexpected"), None);
+    }
+
+    #[test]
+    fn test_notification_test_error() {
+        use crate::commands::kopia::notification_test_error;
+
+        let body = r#"{"code":"INTERNAL","error":"unable to send notification: 535 5.7.8 Username and Password not accepted"}"#;
+        let error = KopiaError::from_api_response(500, body, "Test notification profile");
+
+        match notification_test_error("email", error.clone()) {
+            KopiaError::NotificationTestFailed { stage, message } => {
+                assert_eq!(stage.as_deref(), Some("auth"));
+                assert!(message.contains("Username and Password"));
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        // Other methods keep the original error
+        assert_eq!(notification_test_error("webhook", error.clone()), error);
+        assert_eq!(
+            notification_test_error("email", KopiaError::ServerNotRunning),
+            KopiaError::ServerNotRunning
+        );
+    }
+
+    #[test]
+    fn test_merge_recommended_policy() {
+        use crate::commands::kopia::merge_recommended_policy;
+        use crate::commands::policy_helpers::recommended_policy_for;
+
+        let existing: crate::types::PolicyDefinition = serde_json::from_value(serde_json::json!({
+            "retention": {"keepLatest": 3},
+            "files": {"ignore": ["*.iso", "*.tmp"], "oneFileSystem": true},
+            "scheduling": {"intervalSeconds": 3600}
+        }))
+        .unwrap();
+        let recommended = recommended_policy_for("general").unwrap();
+
+        let merged = merge_recommended_policy(&existing, &recommended).unwrap();
+        assert_eq!(merged.retention.unwrap().keep_latest, Some(10));
+        assert!(merged.scheduling.is_some());
+        let files = merged.files.unwrap();
+        assert_eq!(files.one_file_system, Some(true));
+        assert_eq!(
+            files.ignore.unwrap(),
+            vec!["*.iso", "*.tmp", "Thumbs.db", ".DS_Store"]
+        );
+    }
+
+    #[test]
+    fn test_throughput_samples() {
+        use crate::commands::kopia::throughput_samples;
+
+        let tasks: Vec<crate::types::Task> = serde_json::from_value(serde_json::json!([
+            {
+                "id": "1",
+                "startTime": "2024-01-01T00:00:00Z",
+                "endTime": "2024-01-01T00:00:10Z",
+                "kind": "Snapshot",
+                "description": "Snapshot alice@laptop:/docs at 2024-01-01 00:00:00 UTC",
+                "status": "SUCCESS",
+                "counters": {"Uploaded Bytes": {"value": 5000, "units": "bytes", "level": ""}}
+            },
+            {
+                "id": "2",
+                "startTime": "2024-01-01T00:00:00Z",
+                "endTime": "2024-01-01T00:00:10Z",
+                "kind": "Snapshot",
+                "description": "Snapshot alice@laptop:/unchanged",
+                "status": "SUCCESS",
+                "counters": {"Uploaded Bytes": {"value": 0, "units": "bytes", "level": ""}}
+            },
+            {
+                "id": "3",
+                "startTime": "2024-01-01T00:00:00Z",
+                "kind": "Snapshot",
+                "description": "Snapshot alice@laptop:/running",
+                "status": "RUNNING",
+                "counters": {"Uploaded Bytes": {"value": 100, "units": "bytes", "level": ""}}
+            }
+        ]))
+        .unwrap();
+
+        let samples = throughput_samples("repo", &tasks);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].source, "alice@laptop:/docs");
+        assert_eq!(samples[0].time, "2024-01-01T00:00:10Z");
+        assert_eq!(samples[0].bytes_per_second, 500.0);
+    }
+
+    #[test]
+    fn test_filesystem_storage_path() {
+        use crate::commands::kopia::filesystem_storage_path;
+
+        let filesystem =
+            r#"{"storage": {"type": "filesystem", "config": {"path": "/mnt/backup"}}}"#;
+        assert_eq!(filesystem_storage_path(filesystem).unwrap(), "/mnt/backup");
+
+        let s3 = r#"{"storage": {"type": "s3", "config": {"bucket": "b"}}}"#;
+        assert!(filesystem_storage_path(s3)
+            .unwrap_err()
+            .to_string()
+            .contains("s3 storage isn't locally browsable"));
+
+        let api_server = r#"{"apiServer": {"url": "https://kopia.example.com:51515"}}"#;
+        assert!(filesystem_storage_path(api_server).is_err());
+
+        assert!(matches!(
+            filesystem_storage_path("{}"),
+            Err(KopiaError::RepositoryNotConnected { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_retry_while_busy_recovers() {
+        use crate::commands::kopia::retry_while_busy;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = AtomicU32::new(0);
+        let result = retry_while_busy(|| async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(KopiaError::RepositoryBusy {
+                    message: "flush in progress".to_string(),
+                })
+            } else {
+                Ok(42)
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_while_busy_gives_up() {
+        use crate::commands::kopia::retry_while_busy;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), KopiaError> = retry_while_busy(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(KopiaError::RepositoryBusy {
+                message: "flush in progress".to_string(),
+            })
+        })
+        .await;
+
+        assert!(matches!(result, Err(KopiaError::RepositoryBusy { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Other errors aren't retried
+        let calls = AtomicU32::new(0);
+        let result: Result<(), KopiaError> = retry_while_busy(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(KopiaError::ServerNotRunning)
+        })
+        .await;
+        assert_eq!(result, Err(KopiaError::ServerNotRunning));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_annotate_splitters() {
+        use crate::commands::kopia::annotate_splitters;
+        use crate::types::{AlgorithmOption, AlgorithmsResponse};
+
+        let option = |id: &str, deprecated: Option<bool>| AlgorithmOption {
+            id: id.to_string(),
+            deprecated,
+        };
+        let algorithms = AlgorithmsResponse {
+            default_hash: "BLAKE2B-256-128".to_string(),
+            default_encryption: "AES256-GCM-HMAC-SHA256".to_string(),
+            default_splitter: "DYNAMIC-4M-BUZHASH".to_string(),
+            default_ecc: None,
+            hash: vec![],
+            encryption: vec![],
+            splitter: vec![
+                option("FIXED", Some(true)),
+                option("DYNAMIC-4M-BUZHASH", None),
+                option("DYNAMIC-16M-BUZHASH", Some(false)),
+            ],
+            ecc: None,
+            compression: None,
+        };
+
+        let splitters = annotate_splitters(&algorithms);
+        assert_eq!(splitters.len(), 3);
+
+        assert_eq!(splitters[0].id, "FIXED");
+        assert!(splitters[0].deprecated);
+        assert!(!splitters[0].recommended);
+        assert!(splitters[0].description.contains("Legacy"));
+
+        assert!(splitters[1].recommended);
+        assert!(!splitters[1].deprecated);
+        assert!(splitters[1].description.contains("Buzhash"));
+
+        // Unknown splitters fall back to a family description
+        assert!(!splitters[2].recommended);
+        assert_eq!(splitters[2].description, "Content-defined chunks");
+    }
+
+    #[test]
+    fn test_effective_ignores() {
+        use crate::commands::kopia::effective_ignores;
+        use crate::types::{FilesPolicy, PolicyDefinition, PolicyTarget, PolicyWithTarget};
+
+        let target =
+            |user_name: Option<&str>, host: Option<&str>, path: Option<&str>| PolicyTarget {
+                user_name: user_name.map(String::from),
+                host: host.map(String::from),
+                path: path.map(String::from),
+            };
+        let policy = |target: PolicyTarget, files: FilesPolicy| PolicyWithTarget {
+            id: None,
+            target,
+            policy: PolicyDefinition {
+                files: Some(files),
+                ..Default::default()
+            },
+        };
+        let rules = |rules: &[&str]| Some(rules.iter().map(|r| r.to_string()).collect());
+
+        let global = target(None, None, None);
+        let home = target(Some("alice"), Some("laptop"), Some("/home/alice"));
+        let policies = vec![
+            policy(
+                home.clone(),
+                FilesPolicy {
+                    ignore: rules(&["*.tmp", "node_modules"]),
+                    ..Default::default()
+                },
+            ),
+            policy(
+                global.clone(),
+                FilesPolicy {
+                    ignore: rules(&["*.tmp"]),
+                    ignore_dot_files: rules(&[".kopiaignore"]),
+                    ..Default::default()
+                },
+            ),
+            // Different user and a sibling directory don't apply
+            policy(
+                target(Some("bob"), Some("laptop"), None),
+                FilesPolicy {
+                    ignore: rules(&["*.iso"]),
+                    ..Default::default()
+                },
+            ),
+            policy(
+                target(Some("alice"), Some("laptop"), Some("/home/alicia")),
+                FilesPolicy {
+                    ignore: rules(&["*.log"]),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let docs = target(Some("alice"), Some("laptop"), Some("/home/alice/docs"));
+        let ignores = effective_ignores(&policies, &docs);
+        assert_eq!(ignores.explicit, vec!["*.tmp", "node_modules"]);
+        assert_eq!(ignores.dot_ignore_files, vec![".kopiaignore"]);
+        assert_eq!(ignores.inherited_from, vec![global.clone(), home.clone()]);
+        assert_eq!(ignores.origins[0].target, global);
+        assert_eq!(ignores.origins[2].rule, "node_modules");
+        assert_eq!(ignores.origins[2].target, home);
+
+        // noParentIgnore drops inherited patterns but keeps dot-ignore files
+        let mut policies = policies;
+        policies.push(policy(
+            docs.clone(),
+            FilesPolicy {
+                ignore: rules(&["drafts"]),
+                no_parent_ignore: Some(true),
+                ..Default::default()
+            },
+        ));
+        let ignores = effective_ignores(&policies, &docs);
+        assert_eq!(ignores.explicit, vec!["drafts"]);
+        assert_eq!(ignores.dot_ignore_files, vec![".kopiaignore"]);
+        assert_eq!(ignores.inherited_from, vec![global, docs]);
+    }
+
+    #[test]
+    fn test_incomplete_snapshots_of() {
+        use crate::commands::kopia::incomplete_snapshots_of;
+
+        let snapshots: Vec<crate::types::Snapshot> = serde_json::from_value(serde_json::json!([
+            {"id": "k3", "startTime": "2024-01-03T00:00:00Z", "incomplete": "canceled"},
+            {"id": "k1", "startTime": "2024-01-01T00:00:00Z"},
+            {"id": "k2", "startTime": "2024-01-02T00:00:00Z", "incomplete": "checkpoint"},
+            {"id": "k4", "startTime": "2024-01-04T00:00:00Z", "incomplete": ""}
+        ]))
+        .unwrap();
+
+        let incomplete = incomplete_snapshots_of(&snapshots);
+        assert_eq!(incomplete.len(), 2);
+        assert_eq!(incomplete[0].id, "k2");
+        assert_eq!(incomplete[0].reason, "checkpoint");
+        assert_eq!(incomplete[1].id, "k3");
+        assert_eq!(incomplete[1].time, "2024-01-03T00:00:00Z");
+    }
+
+    #[test]
+    fn test_maintenance_info_round_trip() {
+        use crate::types::MaintenanceInfo;
+
+        let json = serde_json::json!({
+            "params": {
+                "owner": "alice@laptop",
+                "quick": {"enabled": true, "interval": 3600000000000i64},
+                "full": {"enabled": true, "interval": 86400000000000i64},
+                "extendObjectLocks": false,
+                "listParallelism": 0
+            },
+            "schedule": {
+                "nextFullMaintenance": "2024-01-02T00:00:00Z",
+                "nextQuickMaintenance": "2024-01-01T01:00:00Z",
+                "runs": {
+                    "snapshot-gc": [{
+                        "start": "2024-01-01T00:00:00Z",
+                        "end": "2024-01-01T00:01:00Z",
+                        "success": true
+                    }]
+                }
+            }
+        });
+
+        let info: MaintenanceInfo = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(info.params.owner, "alice@laptop");
+        assert_eq!(info.params.quick.interval, 3_600_000_000_000);
+        assert_eq!(
+            info.schedule.next_quick_maintenance.as_deref(),
+            Some("2024-01-01T01:00:00Z")
+        );
+        assert_eq!(serde_json::to_value(&info).unwrap(), json);
+    }
+
+    #[test]
+    fn test_running_maintenance_task() {
+        use crate::commands::kopia::running_maintenance_task;
+
+        let tasks: Vec<crate::types::Task> = serde_json::from_value(serde_json::json!([
+            {"id": "1", "startTime": "2024-01-01T00:00:00Z", "kind": "Maintenance",
+             "description": "Periodic maintenance", "status": "SUCCESS"},
+            {"id": "2", "startTime": "2024-01-01T00:00:00Z", "kind": "Snapshot",
+             "description": "Snapshotting", "status": "RUNNING"},
+            {"id": "3", "startTime": "2024-01-01T01:00:00Z", "kind": "Maintenance",
+             "description": "Periodic maintenance", "status": "RUNNING"}
+        ]))
+        .unwrap();
+
+        assert_eq!(running_maintenance_task(&tasks).unwrap().id, "3");
+        assert!(running_maintenance_task(&tasks[..2]).is_none());
+    }
+
+    #[test]
+    fn test_diff_policy() {
+        use crate::commands::kopia::diff_policy;
+        use crate::types::PolicyDefinition;
+
+        let defined: PolicyDefinition = serde_json::from_value(serde_json::json!({
+            "retention": {"keepDaily": 30},
+            "files": {"ignore": ["*.tmp"]}
+        }))
+        .unwrap();
+        let effective: PolicyDefinition = serde_json::from_value(serde_json::json!({
+            "retention": {"keepDaily": 30, "keepLatest": 10},
+            "files": {"ignore": ["*.tmp"], "oneFileSystem": false}
+        }))
+        .unwrap();
+
+        let diff = diff_policy(Some(&defined), &effective).unwrap();
+        let fields: Vec<&str> = diff.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "files.ignore",
+                "files.oneFileSystem",
+                "retention.keepDaily",
+                "retention.keepLatest"
+            ]
+        );
+
+        assert!(!diff[0].inherited);
+        assert_eq!(diff[0].defined_value, Some(serde_json::json!(["*.tmp"])));
+        assert!(diff[1].inherited);
+        assert_eq!(diff[1].effective_value, Some(serde_json::json!(false)));
+        assert!(!diff[2].inherited);
+        assert_eq!(diff[3].defined_value, None);
+
+        // Without a policy of its own everything is inherited
+        let diff = diff_policy(None, &effective).unwrap();
+        assert!(diff.iter().all(|d| d.inherited));
+    }
+
+    #[test]
+    fn test_validate_throttle_limits() {
+        use crate::commands::kopia::validate_throttle_limits;
+        use crate::types::ThrottleLimits;
+
+        assert!(validate_throttle_limits(&ThrottleLimits::default()).is_ok());
+        assert!(validate_throttle_limits(&ThrottleLimits {
+            max_upload_speed_bytes_per_second: Some(0.0),
+            concurrent_reads: Some(4),
+            ..Default::default()
+        })
+        .is_ok());
+
+        let field_of = |limits: ThrottleLimits| match validate_throttle_limits(&limits) {
+            Err(KopiaError::InvalidInput { field, .. }) => field,
+            other => panic!("expected InvalidInput, got {:?}", other),
+        };
+        assert_eq!(
+            field_of(ThrottleLimits {
+                writes_per_second: Some(-1.0),
+                ..Default::default()
+            }),
+            "writesPerSecond"
+        );
+        assert_eq!(
+            field_of(ThrottleLimits {
+                max_download_speed_bytes_per_second: Some(f64::NAN),
+                ..Default::default()
+            }),
+            "maxDownloadSpeedBytesPerSecond"
+        );
+        assert_eq!(
+            field_of(ThrottleLimits {
+                concurrent_writes: Some(-2),
+                ..Default::default()
+            }),
+            "concurrentWrites"
+        );
+    }
+
+    #[test]
+    fn test_sort_by_staleness() {
+        use crate::commands::kopia::sort_by_staleness;
+
+        let source = |path: &str, last_snapshot: serde_json::Value| {
+            let mut value = serde_json::json!({
+                "source": {"userName": "user", "host": "host", "path": path},
+                "status": "IDLE",
+                "schedule": {},
+            });
+            if !last_snapshot.is_null() {
+                value["lastSnapshot"] = last_snapshot;
+            }
+            serde_json::from_value::<crate::types::SnapshotSource>(value).unwrap()
+        };
+
+        let sources = vec![
+            source(
+                "/recent",
+                serde_json::json!({"id": "k1", "startTime": "2024-01-01T11:00:00Z",
+                                   "endTime": "2024-01-01T11:30:00Z"}),
+            ),
+            source("/never", serde_json::Value::Null),
+            source(
+                "/old",
+                serde_json::json!({"id": "k2", "startTime": "2023-12-31T12:00:00Z"}),
+            ),
+        ];
+        let now = chrono::DateTime::parse_from_rfc3339("2024-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let sorted = sort_by_staleness(&sources, now);
+        let paths: Vec<&str> = sorted.iter().map(|s| s.source.path.as_str()).collect();
+        assert_eq!(paths, vec!["/never", "/old", "/recent"]);
+
+        assert_eq!(sorted[0].last_snapshot_time, None);
+        assert_eq!(sorted[0].age_secs, None);
+        assert_eq!(sorted[1].age_secs, Some(24 * 3600));
+        assert_eq!(
+            sorted[2].last_snapshot_time.as_deref(),
+            Some("2024-01-01T11:30:00Z")
+        );
+        assert_eq!(sorted[2].age_secs, Some(1800));
+    }
+
+    #[test]
+    fn test_snapshot_verify_args() {
+        use crate::commands::kopia::snapshot_verify_args;
+        use crate::types::{SourceInfo, VerifyRequest};
+
+        let source = SourceInfo {
+            user_name: "alice".to_string(),
+            host: "laptop".to_string(),
+            path: "/home/alice".to_string(),
+        };
+
+        let args = snapshot_verify_args(Some(&source), &VerifyRequest::default()).unwrap();
+        assert_eq!(
+            args,
+            vec!["snapshot", "verify", "--sources=alice@laptop:/home/alice"]
+        );
+
+        let request = VerifyRequest {
+            max_errors: Some(5),
+            verify_files_percent: Some(12.5),
+            directory_id: Some("k123".to_string()),
+            file_id: None,
+        };
+        let args = snapshot_verify_args(Some(&source), &request).unwrap();
+        assert_eq!(
+            &args[3..],
+            &[
+                "--verify-files-percent=12.5",
+                "--max-errors=5",
+                "--directory-id=k123"
+            ]
+        );
+
+        for percent in [-1.0, 100.5, f64::NAN] {
+            let request = VerifyRequest {
+                verify_files_percent: Some(percent),
+                ..Default::default()
+            };
+            assert!(matches!(
+                snapshot_verify_args(Some(&source), &request),
+                Err(KopiaError::InvalidInput { .. })
+            ));
+        }
+
+        let request = VerifyRequest {
+            max_errors: Some(1),
+            verify_files_percent: None,
+            directory_id: Some("d1".to_string()),
+            file_id: Some("f1".to_string()),
+        };
+        let json = serde_json::to_value(request).unwrap();
+        assert_eq!(json["directoryID"], "d1");
+        assert_eq!(json["fileID"], "f1");
+        assert_eq!(json["maxErrors"], 1);
+        assert!(json.get("verifyFilesPercent").is_none());
+    }
+
+    #[test]
+    fn test_estimate_result_from_counters() {
+        use crate::commands::kopia::estimate_result;
+        use crate::types::TaskDetail;
+
+        let task: TaskDetail = serde_json::from_value(serde_json::json!({
+            "id": "t1",
+            "startTime": "2024-01-01T00:00:00Z",
+            "endTime": "2024-01-01T00:00:05Z",
+            "kind": "Estimate",
+            "description": "Estimating /home/alice",
+            "status": "SUCCESS",
+            "counters": {
+                "Bytes": { "value": 1048576, "units": "bytes", "level": "" },
+                "Excluded Bytes": { "value": 2048, "units": "bytes", "level": "" },
+                "Files": { "value": 120, "level": "" },
+                "Directories": { "value": 14, "level": "" },
+                "Excluded Files": { "value": 7, "level": "" },
+                "Excluded Directories": { "value": 2, "level": "" },
+                "Errors": { "value": 1, "level": "error" },
+                "Ignored Errors": { "value": 0, "level": "notice" }
+            },
+            "logs": []
+        }))
+        .unwrap();
+
+        let result = estimate_result(&task);
+        assert_eq!(result.total_bytes, 1048576);
+        assert_eq!(result.file_count, 120);
+        assert_eq!(result.directory_count, 14);
+        assert_eq!(result.excluded_count, 9);
+        assert_eq!(result.excluded_bytes, 2048);
+        assert_eq!(result.error_count, 1);
+
+        // Counters can be null for a task that reported none
+        let task: TaskDetail = serde_json::from_value(serde_json::json!({
+            "id": "t2",
+            "startTime": "2024-01-01T00:00:00Z",
+            "kind": "Estimate",
+            "description": "Estimating /empty",
+            "status": "SUCCESS",
+            "counters": null
+        }))
+        .unwrap();
+        let result = estimate_result(&task);
+        assert_eq!(result.total_bytes, 0);
+        assert_eq!(result.excluded_count, 0);
+    }
+
+    fn retention_snapshots(snapshots: serde_json::Value) -> Vec<crate::types::Snapshot> {
+        serde_json::from_value(snapshots).unwrap()
+    }
+
+    fn retention(
+        latest: Option<i64>,
+        hourly: Option<i64>,
+        daily: Option<i64>,
+        weekly: Option<i64>,
+        monthly: Option<i64>,
+        annual: Option<i64>,
+    ) -> crate::types::RetentionPolicy {
+        crate::types::RetentionPolicy {
+            keep_latest: latest,
+            keep_hourly: hourly,
+            keep_daily: daily,
+            keep_weekly: weekly,
+            keep_monthly: monthly,
+            keep_annual: annual,
+            ignore_identical_snapshots: None,
+        }
+    }
+
+    #[test]
+    fn test_preview_retention_keep_latest() {
+        use crate::commands::kopia::preview_retention;
+
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "a", "startTime": "2024-03-01T10:00:00Z"},
+            {"id": "c", "startTime": "2024-03-01T12:00:00Z"},
+            {"id": "b", "startTime": "2024-03-01T11:00:00Z"}
+        ]));
+        let policy = retention(Some(2), None, None, None, None, None);
+
+        let preview = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(preview.kept, vec!["c", "b"]);
+        assert_eq!(preview.pruned, vec!["a"]);
+        assert_eq!(preview.reasons["c"], vec!["latest-1"]);
+        assert_eq!(preview.reasons["b"], vec!["latest-2"]);
+        assert!(!preview.reasons.contains_key("a"));
+    }
+
+    #[test]
+    fn test_preview_retention_keeps_newest_per_period() {
+        use crate::commands::kopia::preview_retention;
+
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "d3-late", "startTime": "2024-03-03T20:00:00Z"},
+            {"id": "d3-early", "startTime": "2024-03-03T08:00:00Z"},
+            {"id": "d2", "startTime": "2024-03-02T09:00:00Z"},
+            {"id": "d1", "startTime": "2024-03-01T09:00:00Z"}
+        ]));
+        let policy = retention(None, None, Some(3), None, None, None);
+
+        let preview = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(preview.kept, vec!["d3-late", "d2", "d1"]);
+        assert_eq!(preview.pruned, vec!["d3-early"]);
+        assert_eq!(preview.reasons["d1"], vec!["daily-3"]);
+
+        // Periods follow the time zone: 23:30 UTC is already the next day at +02:00
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "late", "startTime": "2024-03-01T23:30:00Z"},
+            {"id": "early", "startTime": "2024-03-01T21:00:00Z"}
+        ]));
+        let policy = retention(None, None, Some(5), None, None, None);
+        let utc = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(utc.pruned, vec!["early"]);
+        let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let local = preview_retention(&snapshots, &policy, &offset);
+        assert!(local.pruned.is_empty());
+    }
+
+    #[test]
+    fn test_preview_retention_limits_periods_to_cutoff() {
+        use crate::commands::kopia::preview_retention;
+
+        // keepDaily 3 only looks back 3 days from the newest snapshot, even
+        // though fewer than 3 days have been kept
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "today", "startTime": "2024-03-20T09:00:00Z"},
+            {"id": "5-days", "startTime": "2024-03-15T09:00:00Z"},
+            {"id": "10-days", "startTime": "2024-03-10T09:00:00Z"}
+        ]));
+        let policy = retention(None, None, Some(3), None, None, None);
+
+        let preview = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(preview.kept, vec!["today"]);
+        assert_eq!(preview.pruned, vec!["5-days", "10-days"]);
+    }
+
+    #[test]
+    fn test_preview_retention_combines_rules() {
+        use crate::commands::kopia::preview_retention;
+
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "now", "startTime": "2024-06-15T12:00:00Z"},
+            {"id": "hour-ago", "startTime": "2024-06-15T11:00:00Z"},
+            {"id": "last-week", "startTime": "2024-06-08T12:00:00Z"},
+            {"id": "last-month", "startTime": "2024-05-10T12:00:00Z"},
+            {"id": "last-year", "startTime": "2023-12-31T12:00:00Z"},
+            {"id": "ancient", "startTime": "2019-01-01T12:00:00Z"}
+        ]));
+        let policy = retention(Some(1), Some(48), Some(14), Some(4), Some(24), Some(3));
+
+        let preview = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(
+            preview.reasons["now"],
+            vec![
+                "latest-1",
+                "annual-1",
+                "monthly-1",
+                "weekly-1",
+                "daily-1",
+                "hourly-1"
+            ]
+        );
+        assert_eq!(preview.reasons["hour-ago"], vec!["hourly-2"]);
+        assert_eq!(preview.reasons["last-week"], vec!["weekly-2", "daily-2"]);
+        assert_eq!(preview.reasons["last-month"], vec!["monthly-2"]);
+        assert_eq!(preview.reasons["last-year"], vec!["annual-2", "monthly-3"]);
+        // Older than 3 years before the newest snapshot
+        assert_eq!(preview.pruned, vec!["ancient"]);
+    }
+
+    #[test]
+    fn test_preview_retention_weeks_use_iso_weeks() {
+        use crate::commands::kopia::preview_retention;
+
+        // 2024-12-30 (Monday) belongs to ISO week 2025-01 together with 2025-01-02
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "thu", "startTime": "2025-01-02T12:00:00Z"},
+            {"id": "mon", "startTime": "2024-12-30T12:00:00Z"},
+            {"id": "sun", "startTime": "2024-12-29T12:00:00Z"}
+        ]));
+        let policy = retention(None, None, None, Some(4), None, None);
+
+        let preview = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(preview.kept, vec!["thu", "sun"]);
+        assert_eq!(preview.pruned, vec!["mon"]);
+    }
+
+    #[test]
+    fn test_preview_retention_incomplete_and_pinned() {
+        use crate::commands::kopia::preview_retention;
+
+        let snapshots = retention_snapshots(serde_json::json!([
+            {"id": "running", "startTime": "2024-03-04T00:00:00Z", "incomplete": "checkpoint"},
+            {"id": "complete", "startTime": "2024-03-03T00:00:00Z"},
+            {"id": "canceled", "startTime": "2024-03-02T00:00:00Z", "incomplete": "canceled"},
+            {"id": "pinned", "startTime": "2024-03-01T00:00:00Z", "pins": ["keep"]},
+            {"id": "unparsable", "startTime": "yesterday"}
+        ]));
+        let policy = retention(Some(1), None, None, None, None, None);
+
+        let preview = preview_retention(&snapshots, &policy, &chrono::Utc);
+        assert_eq!(preview.reasons["running"], vec!["incomplete"]);
+        // Incomplete snapshots don't count towards "latest"
+        assert_eq!(preview.reasons["complete"], vec!["latest-1"]);
+        assert!(preview.kept.contains(&"pinned".to_string()));
+        assert!(preview.kept.contains(&"unparsable".to_string()));
+        assert!(preview.kept.contains(&"running".to_string()));
+        assert_eq!(preview.pruned, vec!["canceled"]);
+
+        assert_eq!(
+            preview_retention(&[], &policy, &chrono::Utc),
+            crate::types::RetentionPreview::default()
+        );
+    }
+
+    #[test]
+    fn test_restore_file_destination() {
+        use crate::commands::kopia::restore_file_destination;
+        use std::path::Path;
+
+        assert_eq!(
+            restore_file_destination("/restore", "docs/report.pdf").unwrap(),
+            Path::new("/restore").join("docs").join("report.pdf")
+        );
+        assert_eq!(
+            restore_file_destination("/restore", "./notes.txt").unwrap(),
+            Path::new("/restore").join("notes.txt")
+        );
+
+        for invalid in [
+            "",
+            ".",
+            "../escape.txt",
+            "docs/../../escape.txt",
+            "/etc/passwd",
+        ] {
+            assert!(
+                matches!(
+                    restore_file_destination("/restore", invalid),
+                    Err(KopiaError::InvalidInput { .. })
+                ),
+                "{} should be rejected",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup_ratio() {
+        use crate::commands::kopia::dedup_ratio;
+
+        assert_eq!(dedup_ratio(300, 100), 3.0);
+        assert_eq!(dedup_ratio(100, 100), 1.0);
+        assert_eq!(dedup_ratio(50, 100), 0.5);
+        assert_eq!(dedup_ratio(0, 0), 1.0);
+        assert_eq!(dedup_ratio(100, 0), 1.0);
+    }
+
+    #[test]
+    fn test_parse_content_stats() {
+        use crate::commands::kopia::parse_content_stats;
+
+        let output = "Count: 1234\n\
+                      Total Bytes: 5000000\n\
+                      Total Packed: 2000000 (compression 60.0%)\n\
+                      By Method:\n  \
+                        (uncompressed)         count: 34 size: 100000\n  \
+                        zstd-fastest           count: 1200 size: 4900000 packed: 1900000 compression: 61.2%\n\
+                      Average content size: 4051\n";
+
+        let stats = parse_content_stats(output).unwrap();
+        assert_eq!(stats.count, 1234);
+        assert_eq!(stats.total_original_size, 5000000);
+        assert_eq!(stats.total_packed_size, 2000000);
+        assert_eq!(stats.dedup_ratio, 2.5);
+
+        assert!(parse_content_stats("Count: 0\nTotal Bytes: 0\n").is_none());
+        assert_eq!(
+            parse_content_stats("Count: 0\nTotal Bytes: 0\nTotal Packed: 0\n")
+                .unwrap()
+                .dedup_ratio,
+            1.0
+        );
+    }
+
+    #[test]
+    fn test_snapshot_verify_args_without_source() {
+        use crate::commands::kopia::snapshot_verify_args;
+        use crate::types::VerifyRequest;
+
+        let request = VerifyRequest {
+            directory_id: Some("kroot".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            snapshot_verify_args(None, &request).unwrap(),
+            vec!["snapshot", "verify", "--directory-id=kroot"]
+        );
+    }
+
+    #[test]
+    fn test_finished_snapshot() {
+        use crate::commands::kopia::finished_snapshot;
+        use crate::types::SnapshotSource;
+
+        let status = |state: &str, last: Option<&str>| -> SnapshotSource {
+            serde_json::from_value(serde_json::json!({
+                "source": {"userName": "alice", "host": "laptop", "path": "/data"},
+                "status": state,
+                "schedule": {},
+                "lastSnapshot": last.map(|id| serde_json::json!({
+                    "id": id,
+                    "rootID": "kroot",
+                    "startTime": "2024-01-01T00:00:00Z"
+                }))
+            }))
+            .unwrap()
+        };
+
+        assert!(finished_snapshot(&status("UPLOADING", Some("new")), Some("old")).is_none());
+        assert!(finished_snapshot(&status("IDLE", Some("old")), Some("old")).is_none());
+        assert!(finished_snapshot(&status("IDLE", None), None).is_none());
+        assert_eq!(
+            finished_snapshot(&status("IDLE", Some("new")), Some("old"))
+                .unwrap()
+                .id,
+            "new"
+        );
+        assert_eq!(
+            finished_snapshot(&status("IDLE", Some("first")), None)
+                .unwrap()
+                .id,
+            "first"
+        );
+    }
+
+    #[test]
+    fn test_verification_errors() {
+        use crate::commands::kopia::verification_errors;
+
+        let output = "Processed 120 objects.\n\
+                      error processing kroot/docs/a.txt: unable to open object: not found\n\
+                      \n\
+                      Finished processing 120 objects.\n\
+                      found 1 errors";
+        assert_eq!(
+            verification_errors(output),
+            vec![
+                "error processing kroot/docs/a.txt: unable to open object: not found",
+                "found 1 errors"
+            ]
+        );
+
+        assert_eq!(
+            verification_errors("  unexpected failure \n\n"),
+            vec!["unexpected failure"]
+        );
+        assert!(verification_errors("").is_empty());
+    }
+
+    /// Chunks served from memory, for `write_chunks`
+    struct TestChunks(std::collections::VecDeque<crate::error::Result<Vec<u8>>>);
+
+    impl crate::commands::kopia::ChunkSource for TestChunks {
+        async fn next_chunk(&mut self) -> crate::error::Result<Option<impl AsRef<[u8]>>> {
+            self.0.pop_front().transpose()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_chunks_streams_to_writer() {
+        use crate::commands::kopia::write_chunks;
+
+        let chunks = TestChunks(
+            vec![
+                Ok(b"hello ".to_vec()),
+                Ok(Vec::new()),
+                Ok(b"chunked world".to_vec()),
+            ]
+            .into(),
+        );
+        let mut output = Vec::new();
+        let mut progress = Vec::new();
+
+        let written = write_chunks(
+            chunks,
+            &mut output,
+            std::path::Path::new("file.txt"),
+            |written| progress.push(written),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(written, 19);
+        assert_eq!(output, b"hello chunked world");
+        assert_eq!(progress, vec![6, 6, 19]);
+    }
+
+    #[tokio::test]
+    async fn test_write_chunks_stops_on_stream_error() {
+        use crate::commands::kopia::write_chunks;
+        use crate::error::KopiaError;
+
+        let chunks = TestChunks(
+            vec![
+                Ok(b"partial".to_vec()),
+                Err(KopiaError::operation_failed("download", "connection reset")),
+                Ok(b"never written".to_vec()),
+            ]
+            .into(),
+        );
+        let mut output = Vec::new();
+
+        let result = write_chunks(
+            chunks,
+            &mut output,
+            std::path::Path::new("file.txt"),
+            |_| {},
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert!(!String::from_utf8_lossy(&output).contains("never written"));
+    }
+
+    #[test]
+    fn test_tree_export_writer() {
+        use crate::commands::kopia::TreeExportWriter;
+        use crate::types::{DirectoryEntry, TreeExportSummary};
+
+        let entry = |name: &str, entry_type: &str, obj: &str| -> DirectoryEntry {
+            serde_json::from_value(serde_json::json!({
+                "name": name,
+                "type": entry_type,
+                "mode": "0644",
+                "size": 10,
+                "mtime": "2024-01-01T00:00:00Z",
+                "obj": obj
+            }))
+            .unwrap()
+        };
+
+        let mut writer = TreeExportWriter::new(Vec::new(), "kroot").unwrap();
+        writer.open_directory(&entry("docs", "d", "kdocs")).unwrap();
+        writer.write_entry(&entry("a.txt", "f", "ia")).unwrap();
+        writer
+            .write_truncated_directory(&entry("deep", "d", "kdeep"))
+            .unwrap();
+        writer.close_directory().unwrap();
+        writer.write_entry(&entry("b.txt", "f", "ib")).unwrap();
+        let output = writer
+            .finish(&TreeExportSummary {
+                entries: 4,
+                truncated: true,
+            })
+            .unwrap();
+
+        let document: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            document,
+            serde_json::json!({
+                "rootID": "kroot",
+                "entries": [
+                    {
+                        "name": "docs",
+                        "type": "d",
+                        "size": 10,
+                        "mtime": "2024-01-01T00:00:00Z",
+                        "objectID": "kdocs",
+                        "entries": [
+                            {
+                                "name": "a.txt",
+                                "type": "f",
+                                "size": 10,
+                                "mtime": "2024-01-01T00:00:00Z",
+                                "objectID": "ia"
+                            },
+                            {
+                                "name": "deep",
+                                "type": "d",
+                                "size": 10,
+                                "mtime": "2024-01-01T00:00:00Z",
+                                "objectID": "kdeep",
+                                "truncated": true
+                            }
+                        ]
+                    },
+                    {
+                        "name": "b.txt",
+                        "type": "f",
+                        "size": 10,
+                        "mtime": "2024-01-01T00:00:00Z",
+                        "objectID": "ib"
+                    }
+                ],
+                "entryCount": 4,
+                "truncated": true
+            })
+        );
+    }
+
+    #[test]
+    fn test_tree_export_writer_empty_tree() {
+        use crate::commands::kopia::TreeExportWriter;
+        use crate::types::TreeExportSummary;
+
+        let writer = TreeExportWriter::new(Vec::new(), "kempty").unwrap();
+        let output = writer.finish(&TreeExportSummary::default()).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            r#"{"rootID":"kempty","entries":[],"entryCount":0,"truncated":false}"#
+        );
+    }
+
+    #[test]
+    fn test_restore_status_running() {
+        use crate::commands::kopia::restore_status_from_task;
+        use crate::types::TaskDetail;
+
+        let task: TaskDetail = serde_json::from_value(serde_json::json!({
+            "id": "r1",
+            "startTime": "2024-01-01T00:00:00Z",
+            "kind": "Restore",
+            "description": "Restoring to /tmp/restore",
+            "status": "RUNNING",
+            "progressInfo": "Restored 42 files",
+            "counters": {
+                "Restored Files": { "value": 42, "level": "" },
+                "Restored Directories": { "value": 5, "level": "" },
+                "Restored Symlinks": { "value": 1, "level": "" },
+                "Restored Bytes": { "value": 524288, "units": "bytes", "level": "" },
+                "Ignored Errors": { "value": 2, "level": "error" },
+                "Skipped Files": { "value": 3, "level": "" },
+                "Skipped Bytes": { "value": 1024, "units": "bytes", "level": "" }
+            },
+            "logs": []
+        }))
+        .unwrap();
+
+        let status = restore_status_from_task(&task);
+        assert_eq!(status.status, "RUNNING");
+        assert_eq!(status.restored_files, 42);
+        assert_eq!(status.restored_directories, 5);
+        assert_eq!(status.restored_bytes, 524288);
+        assert_eq!(status.skipped_files, 3);
+        assert_eq!(status.skipped_bytes, 1024);
+        assert_eq!(status.ignored_errors, 2);
+        assert!(!status.finished);
+        assert!(status.error_message.is_none());
+    }
+
+    #[test]
+    fn test_restore_status_missing_counters() {
+        use crate::commands::kopia::restore_status_from_task;
+        use crate::types::TaskDetail;
+
+        let task: TaskDetail = serde_json::from_value(serde_json::json!({
+            "id": "r2",
+            "startTime": "2024-01-01T00:00:00Z",
+            "endTime": "2024-01-01T00:00:01Z",
+            "kind": "Restore",
+            "description": "Restoring to /tmp/restore",
+            "status": "FAILED",
+            "errorMessage": "unable to create directory",
+            "counters": {
+                "Restored Files": { "value": 7, "level": "" }
+            }
+        }))
+        .unwrap();
+
+        let status = restore_status_from_task(&task);
+        assert_eq!(status.restored_files, 7);
+        assert_eq!(status.restored_bytes, 0);
+        assert_eq!(status.skipped_files, 0);
+        assert_eq!(status.ignored_errors, 0);
+        assert!(status.finished);
+        assert_eq!(
+            status.error_message.as_deref(),
+            Some("unable to create directory")
+        );
+    }
+
+    #[test]
+    fn test_endpoint_probe() {
+        use crate::commands::kopia::endpoint_probe;
+
+        assert_eq!(
+            endpoint_probe("get", "api/v1/sources").unwrap(),
+            (reqwest::Method::GET, "/api/v1/sources".to_string())
+        );
+        assert_eq!(
+            endpoint_probe("POST", "/api/v1/control/flush").unwrap(),
+            (reqwest::Method::POST, "/api/v1/control/flush".to_string())
+        );
+
+        assert!(endpoint_probe("CONNECT", "/api/v1/sources").is_err());
+        assert!(endpoint_probe("GET", "/").is_err());
+        assert!(endpoint_probe("GET", "https://example.com/api/v1").is_err());
+    }
+
+    #[test]
+    fn test_endpoint_supported() {
+        use crate::commands::kopia::endpoint_supported;
+        use reqwest::Method;
+
+        assert!(endpoint_supported(&Method::GET, 200));
+        assert!(endpoint_supported(&Method::GET, 400));
+        assert!(endpoint_supported(&Method::GET, 500));
+        assert!(!endpoint_supported(&Method::GET, 404));
+        assert!(!endpoint_supported(&Method::GET, 405));
+
+        // OPTIONS answers 405 for every path the server routes
+        assert!(endpoint_supported(&Method::OPTIONS, 405));
+        assert!(!endpoint_supported(&Method::OPTIONS, 404));
+    }
+
+    #[test]
+    fn test_parse_kopia_version() {
+        use crate::commands::kopia::parse_kopia_version;
+        use crate::types::KopiaVersion;
+
+        assert_eq!(
+            parse_kopia_version(
+                "0.21.1 build: 0e6bd72b0a0d8b3a1d2c6e1d7cbd7a1e5c4f1a2b from: kopia/kopia\n"
+            ),
+            Some(KopiaVersion {
+                version: "0.21.1".to_string(),
+                build_version: "0.21.1".to_string(),
+                build_info: "0e6bd72b0a0d8b3a1d2c6e1d7cbd7a1e5c4f1a2b".to_string(),
+                source_repo: Some("kopia/kopia".to_string()),
+            })
+        );
+
+        let version =
+            parse_kopia_version("v0.17.0 build: abc123 from: example/kopia-fork").unwrap();
+        assert_eq!(version.version, "0.17.0");
+        assert_eq!(version.build_version, "v0.17.0");
+        assert_eq!(version.source_repo.as_deref(), Some("example/kopia-fork"));
+
+        // Development builds may report only a version
+        let version = parse_kopia_version("\n0.22.0-dev\n").unwrap();
+        assert_eq!(version.version, "0.22.0-dev");
+        assert_eq!(version.build_info, "");
+        assert_eq!(version.source_repo, None);
+
+        assert_eq!(parse_kopia_version(""), None);
+        assert_eq!(parse_kopia_version("  \n "), None);
+    }
+
+    #[test]
+    fn test_stop_blocking_tasks() {
+        use crate::commands::kopia::{stop_blocking_tasks, stop_refused_reason};
+        use crate::types::Task;
+
+        let task = |id: &str, kind: &str, status: &str| -> Task {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "startTime": "2024-01-01T00:00:00Z",
+                "kind": kind,
+                "description": "",
+                "status": status
+            }))
+            .unwrap()
+        };
+
+        let tasks = vec![
+            task("1", "Snapshot", "RUNNING"),
+            task("2", "Restore", "CANCELING"),
+            task("3", "Maintenance", "RUNNING"),
+            task("4", "Estimate", "RUNNING"),
+            task("5", "Snapshot", "SUCCESS"),
+            task("6", "Snapshot", "RUNNING"),
+        ];
+
+        let blocking = stop_blocking_tasks(&tasks);
+        let ids: Vec<&str> = blocking.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["1", "2", "6"]);
+        assert_eq!(
+            stop_refused_reason(&blocking, &[]).as_deref(),
+            Some("3 tasks are running (Restore, Snapshot)")
+        );
+
+        let blocking = stop_blocking_tasks(&tasks[..1]);
+        assert_eq!(
+            stop_refused_reason(&blocking, &[]).as_deref(),
+            Some("1 task is running (Snapshot)")
+        );
+
+        assert!(stop_blocking_tasks(&tasks[2..5]).is_empty());
+        assert_eq!(stop_refused_reason(&[], &[]), None);
+    }
+
+    #[test]
+    fn test_stop_refused_by_app_operations() {
+        use crate::app_state::AppState;
+        use crate::commands::kopia::stop_refused_reason;
+
+        let state = AppState::new();
+        let _restore = state.begin_operation("repo", "Restore");
+        let _verify = state.begin_operation("repo", "Verify");
+        let _other = state.begin_operation("other", "Maintenance");
+
+        let operations = state.app_operations().list(Some("repo"));
+        assert_eq!(
+            stop_refused_reason(&[], &operations).as_deref(),
+            Some("2 tasks are running (Restore, Verify)")
+        );
+        assert_eq!(
+            stop_refused_reason(&[], &operations[..1]).as_deref(),
+            Some("1 task is running (Restore)")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_recovers_from_connection_errors() {
+        use crate::commands::kopia::retry_request;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let calls = AtomicU32::new(0);
+        let result = retry_request(|| async {
+            if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(KopiaError::HttpRequestFailed {
+                    message: "connection reset by peer".to_string(),
+                    status_code: None,
+                    operation: "Get repository status".to_string(),
+                })
+            } else {
+                Ok(42)
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok(42));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_request_gives_up() {
+        use crate::commands::kopia::{retry_request, TRANSIENT_RETRY_ATTEMPTS};
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let connection_error = || KopiaError::HttpRequestFailed {
+            message: "operation timed out".to_string(),
+            status_code: None,
+            operation: "List tasks".to_string(),
+        };
+
+        let calls = AtomicU32::new(0);
+        let result: Result<(), KopiaError> = retry_request(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(connection_error())
+        })
+        .await;
+        assert_eq!(result, Err(connection_error()));
+        assert_eq!(calls.load(Ordering::SeqCst), TRANSIENT_RETRY_ATTEMPTS);
+
+        // HTTP errors aren't retried
+        let http_error = KopiaError::HttpRequestFailed {
+            message: "Internal Server Error".to_string(),
+            status_code: Some(500),
+            operation: "List tasks".to_string(),
+        };
+        let calls = AtomicU32::new(0);
+        let result: Result<(), KopiaError> = retry_request(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(http_error.clone())
+        })
+        .await;
+        assert_eq!(result, Err(http_error));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_build_all_snapshots() {
+        use crate::commands::kopia::build_all_snapshots;
+        use crate::fan_out::FanOutResult;
+        use crate::types::{SnapshotsResponse, SourceInfo};
+
+        let source = |user_name: &str, host: &str, path: &str| SourceInfo {
+            user_name: user_name.to_string(),
+            host: host.to_string(),
+            path: path.to_string(),
+        };
+        let response = |count: i64| SnapshotsResponse {
+            snapshots: Vec::new(),
+            unfiltered_count: count,
+            unique_count: count,
+        };
+
+        // Sources are keyed user@host:path, including Windows paths
+        let home = source("alice", "laptop", "/home/alice").to_string();
+        let docs = source("bob", "desktop", "C:\\Users\\bob\\Documents").to_string();
+        assert_eq!(home, "alice@laptop:/home/alice");
+        assert_eq!(docs, "bob@desktop:C:\\Users\\bob\\Documents");
+
+        let result = build_all_snapshots(FanOutResult {
+            completed: vec![(home.clone(), response(3)), (docs.clone(), response(1))],
+            timed_out: vec!["carol@nas:/srv".to_string()],
+            failed: vec![("dave@nas:/data".to_string(), "boom".to_string())],
+        });
+
+        assert!(result.partial);
+        assert_eq!(result.snapshots.len(), 2);
+        assert_eq!(result.snapshots[&home].unfiltered_count, 3);
+        assert_eq!(result.snapshots[&docs].unfiltered_count, 1);
+        assert_eq!(result.timed_out_sources, vec!["carol@nas:/srv"]);
+        assert_eq!(result.failed_sources, vec!["dave@nas:/data"]);
+    }
+
+    #[test]
+    fn test_pack_contents() {
+        use crate::commands::kopia::pack_contents;
+        use crate::types::{BlobContent, ContentIndexEntry};
+
+        let entries: Vec<ContentIndexEntry> = serde_json::from_value(serde_json::json!([
+            {
+                "packFile": "p0a1b2c3d4e5f6",
+                "time": 1700000000,
+                "originalLength": 8192,
+                "length": 4120,
+                "packOffset": 4200,
+                "compression": 256,
+                "contentID": "k5c1f2e3d4",
+                "deleted": false,
+                "formatVersion": 2
+            },
+            {
+                "packFile": "p0a1b2c3d4e5f6",
+                "time": 1700000000,
+                "originalLength": 4096,
+                "length": 4136,
+                "contentID": "0a1b2c3d",
+                "deleted": false,
+                "formatVersion": 2
+            },
+            {
+                "packFile": "p0a1b2c3d4e5f6",
+                "time": 1700000100,
+                "originalLength": 100,
+                "length": 128,
+                "packOffset": 8320,
+                "contentID": "1f2e3d4c",
+                "deleted": true,
+                "formatVersion": 2
+            },
+            {
+                "packFile": "q9f8e7d6c5b4a3",
+                "time": 1700000000,
+                "originalLength": 512,
+                "length": 540,
+                "contentID": "xa1b2c3",
+                "deleted": false,
+                "formatVersion": 2
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            pack_contents(entries.clone(), "p0a1b2c3d4e5f6"),
+            vec![
+                BlobContent {
+                    content_id: "0a1b2c3d".to_string(),
+                    length: 4136,
+                    offset: 0,
+                    original_length: 4096,
+                },
+                BlobContent {
+                    content_id: "k5c1f2e3d4".to_string(),
+                    length: 4120,
+                    offset: 4200,
+                    original_length: 8192,
+                },
+            ]
+        );
+        assert_eq!(pack_contents(entries.clone(), "q9f8e7d6c5b4a3").len(), 1);
+        assert!(pack_contents(entries, "pmissing").is_empty());
+    }
+
+    #[test]
+    fn test_validate_blob_id() {
+        use crate::commands::kopia::validate_blob_id;
+
+        assert!(validate_blob_id("p0a1b2c3d4e5f6-s1a2b3c4d5e6f7a8-c1").is_ok());
+        assert!(validate_blob_id("xn0_4a8c1b2d3e4f5a6b7c8d9e0f1a2b3c4d-s1a2b3c4d-c1").is_ok());
+
+        for blob_id in ["", "--config-file=/tmp/x", "p1 p2", "../p1", "p1;rm"] {
+            assert!(
+                matches!(
+                    validate_blob_id(blob_id),
+                    Err(KopiaError::InvalidInput { .. })
+                ),
+                "{:?} should be rejected",
+                blob_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_upload_percent() {
+        use crate::commands::kopia::upload_percent;
+        use crate::types::UploadCounters;
+
+        let counters = |hashed, cached, estimated| UploadCounters {
+            hashed_bytes: hashed,
+            cached_bytes: cached,
+            estimated_bytes: estimated,
+            ..Default::default()
+        };
+
+        assert_eq!(upload_percent(&counters(250, 250, 1000)), Some(50.0));
+        assert_eq!(upload_percent(&counters(0, 0, 1000)), Some(0.0));
+        // Estimate lags behind the actual bytes
+        assert_eq!(upload_percent(&counters(900, 300, 1000)), Some(100.0));
+        // No estimate yet
+        assert_eq!(upload_percent(&counters(100, 0, 0)), None);
+        assert_eq!(upload_percent(&counters(100, 0, -1)), None);
+    }
+
+    #[test]
+    fn test_upload_throughput() {
+        use crate::commands::kopia::upload_throughput;
+        use std::time::Duration;
+
+        assert_eq!(
+            upload_throughput(1000, 1500, Duration::from_millis(500)),
+            Some(1000.0)
+        );
+        assert_eq!(
+            upload_throughput(1000, 1000, Duration::from_millis(500)),
+            Some(0.0)
+        );
+        assert_eq!(upload_throughput(1000, 1500, Duration::ZERO), None);
+        // Counter reset by a new upload
+        assert_eq!(
+            upload_throughput(1500, 100, Duration::from_millis(500)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_build_upload_progress() {
+        use crate::commands::kopia::build_upload_progress;
+        use crate::types::SnapshotSource;
+        use std::time::Duration;
+
+        let source = |status: &str, upload: Option<serde_json::Value>| -> SnapshotSource {
+            let mut value = serde_json::json!({
+                "source": {"userName": "user", "host": "host", "path": "/data"},
+                "status": status,
+                "schedule": {}
+            });
+            if let Some(upload) = upload {
+                value["upload"] = upload;
+            }
+            serde_json::from_value(value).unwrap()
+        };
+
+        let first = source(
+            "UPLOADING",
+            Some(
+                serde_json::json!({"hashedBytes": 100, "cachedBytes": 100, "estimatedBytes": 1000}),
+            ),
+        );
+        let second = source(
+            "UPLOADING",
+            Some(serde_json::json!({
+                "hashedBytes": 300,
+                "cachedBytes": 200,
+                "estimatedBytes": 1000,
+                "hashedFiles": 3,
+                "directory": "docs"
+            })),
+        );
+
+        let progress = build_upload_progress(&first, &second, Duration::from_millis(500));
+        assert_eq!(progress.status, "UPLOADING");
+        assert_eq!(progress.bytes_done, 500);
+        assert_eq!(progress.percent_complete, Some(50.0));
+        assert_eq!(progress.hashed_files, 3);
+        assert_eq!(progress.current_directory.as_deref(), Some("docs"));
+        assert_eq!(progress.throughput_bytes_per_sec, Some(600.0));
+
+        let idle = build_upload_progress(
+            &source("IDLE", None),
+            &source("IDLE", None),
+            Duration::from_millis(500),
+        );
+        assert_eq!(idle.status, "IDLE");
+        assert_eq!(idle.percent_complete, None);
+        assert_eq!(idle.current_directory, None);
+        assert_eq!(idle.throughput_bytes_per_sec, None);
+    }
+
+    #[test]
+    fn test_policy_restore_journal() {
+        use crate::commands::kopia::{
+            read_policy_restore_journal, write_policy_restore_journal, PendingPolicyRestore,
+        };
+        use crate::types::{PolicyDefinition, SourceInfo};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("repository.policy-restore.json");
+        assert!(read_policy_restore_journal(&path).unwrap().is_none());
+
+        let source = SourceInfo {
+            user_name: "user".to_string(),
+            host: "host".to_string(),
+            path: "/data".to_string(),
+        };
+        let policy: PolicyDefinition =
+            serde_json::from_value(serde_json::json!({"files": {"ignore": ["*.tmp"]}})).unwrap();
+
+        for policy in [Some(policy), None] {
+            let pending = PendingPolicyRestore {
+                source: source.clone(),
+                policy,
+            };
+            write_policy_restore_journal(&path, &pending).unwrap();
+            let read = read_policy_restore_journal(&path).unwrap().unwrap();
+            assert_eq!(read.source, pending.source);
+            assert_eq!(
+                serde_json::to_value(&read.policy).unwrap(),
+                serde_json::to_value(&pending.policy).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_notify_on_task_failure() {
+        use crate::commands::kopia::notify_on_task_failure;
+        use crate::settings::AppSettings;
+        use crate::types::{SourceInfo, Task};
+
+        let task = |kind: &str, description: &str| -> Task {
+            serde_json::from_value(serde_json::json!({
+                "id": "1",
+                "startTime": "2024-01-01T00:00:00Z",
+                "kind": kind,
+                "description": description,
+                "status": "FAILED"
+            }))
+            .unwrap()
+        };
+        let source = |path: &str| SourceInfo {
+            user_name: "alice".to_string(),
+            host: "laptop".to_string(),
+            path: path.to_string(),
+        };
+
+        let mut settings = AppSettings::default();
+        settings.set_source_notify_on_error("repo-a", &source("/quiet"), false);
+        settings.set_source_notify_on_error("repo-a", &source("/loud"), true);
+
+        let quiet = task("Snapshot", "alice@laptop:/quiet at 2024-01-01T00:00:00Z");
+        assert!(!notify_on_task_failure(&settings, "repo-a", &quiet));
+        // Overrides are per repository
+        assert!(notify_on_task_failure(&settings, "repo-b", &quiet));
+
+        let loud = task("Snapshot", "alice@laptop:/loud");
+        assert!(notify_on_task_failure(&settings, "repo-a", &loud));
+        let unset = task("Snapshot", "alice@laptop:/other");
+        assert!(notify_on_task_failure(&settings, "repo-a", &unset));
+        let maintenance = task("Maintenance", "alice@laptop:/quiet");
+        assert!(notify_on_task_failure(&settings, "repo-a", &maintenance));
+    }
+
+    #[test]
+    fn test_active_operations_from_partial_fan_out() {
+        use crate::commands::kopia::active_operations_from;
+        use crate::fan_out::FanOutResult;
+
+        let mut finished = running_task("2", "2024-01-01T00:00:00Z", 0);
+        finished.status = "SUCCESS".to_string();
+
+        let complete = active_operations_from(FanOutResult {
+            completed: vec![("repo-a".to_string(), vec![finished.clone()])],
+            timed_out: vec![],
+            failed: vec![],
+        });
+        assert!(!complete.active);
+        assert!(!complete.partial);
+        assert!(!complete.may_be_active());
+
+        // Servers that couldn't be checked count as busy
+        let partial = active_operations_from(FanOutResult {
+            completed: vec![("repo-a".to_string(), vec![finished])],
+            timed_out: vec!["repo-b".to_string()],
+            failed: vec![("repo-c".to_string(), "connection refused".to_string())],
+        });
+        assert!(!partial.active);
+        assert!(partial.partial);
+        assert_eq!(partial.failed_repo_ids, vec!["repo-b", "repo-c"]);
+        assert!(partial.may_be_active());
+
+        let running = active_operations_from(FanOutResult {
+            completed: vec![(
+                "repo-a".to_string(),
+                vec![running_task("1", "2024-01-01T00:00:00Z", 0)],
+            )],
+            timed_out: vec![],
+            failed: vec![],
+        });
+        assert!(running.active);
+        assert_eq!(running.count, 1);
+        assert!(running.may_be_active());
+    }
+
+    /// Snapshot tree served from memory, for `verify_restore_tree`
+    struct TestTree {
+        directories: std::collections::HashMap<String, Vec<crate::types::DirectoryEntry>>,
+        contents: std::collections::HashMap<String, Vec<u8>>,
+    }
+
+    impl crate::commands::kopia::SnapshotTree for TestTree {
+        async fn list_directory(
+            &mut self,
+            object_id: &str,
+        ) -> crate::error::Result<crate::types::DirectoryObject> {
+            Ok(crate::types::DirectoryObject {
+                stream: None,
+                entries: self.directories[object_id].clone(),
+            })
+        }
+
+        async fn matches_file(
+            &mut self,
+            object_id: &str,
+            local: &std::path::Path,
+        ) -> crate::error::Result<bool> {
+            Ok(std::fs::read(local)? == self.contents[object_id])
+        }
+    }
+
+    fn tree_entry(
+        name: &str,
+        entry_type: &str,
+        size: i64,
+        obj: &str,
+    ) -> crate::types::DirectoryEntry {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "type": entry_type,
+            "mode": "0644",
+            "size": size,
+            "mtime": "2024-01-01T00:00:00Z",
+            "obj": obj
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_verify_restore_tree_against_local_dir() {
+        use crate::commands::kopia::verify_restore_tree;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("same.txt"), b"hello").unwrap();
+        std::fs::write(dir.path().join("edited.txt"), b"HELLO").unwrap();
+        std::fs::write(dir.path().join("short.txt"), b"abc").unwrap();
+        std::fs::create_dir(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/a.txt"), b"nested").unwrap();
+
+        let mut tree = TestTree {
+            directories: [
+                (
+                    "kroot".to_string(),
+                    vec![
+                        tree_entry("same.txt", "f", 5, "Osame"),
+                        tree_entry("edited.txt", "f", 5, "Oedited"),
+                        tree_entry("short.txt", "f", 10, "Oshort"),
+                        tree_entry("gone.txt", "f", 1, "Ogone"),
+                        tree_entry("docs", "d", 0, "kdocs"),
+                        tree_entry("link", "s", 0, "Olink"),
+                    ],
+                ),
+                ("kdocs".to_string(), vec![tree_entry("a.txt", "f", 6, "Oa")]),
+            ]
+            .into(),
+            contents: [
+                ("Osame".to_string(), b"hello".to_vec()),
+                ("Oedited".to_string(), b"hello".to_vec()),
+                ("Oa".to_string(), b"nested".to_vec()),
+            ]
+            .into(),
+        };
+
+        let sizes_only = verify_restore_tree(&mut tree, "kroot".into(), dir.path(), false, 64, 100)
+            .await
+            .unwrap();
+        assert_eq!(sizes_only.matched, 3);
+        assert_eq!(sizes_only.mismatched, vec!["short.txt"]);
+        assert_eq!(sizes_only.missing, vec!["gone.txt"]);
+        assert!(!sizes_only.truncated);
+
+        let contents = verify_restore_tree(&mut tree, "kroot".into(), dir.path(), true, 64, 100)
+            .await
+            .unwrap();
+        assert_eq!(contents.matched, 2);
+        assert_eq!(contents.mismatched, vec!["edited.txt", "short.txt"]);
+
+        let shallow = verify_restore_tree(&mut tree, "kroot".into(), dir.path(), false, 1, 100)
+            .await
+            .unwrap();
+        assert_eq!(shallow.matched, 2);
+        assert!(shallow.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_verify_restore_tree_rejects_unsafe_names() {
+        use crate::commands::kopia::{is_safe_entry_name, verify_restore_tree};
+
+        assert!(is_safe_entry_name("file.txt"));
+        assert!(is_safe_entry_name("..hidden"));
+        for name in ["", ".", "..", "/etc", "a/b", "a\\b", "../x"] {
+            assert!(!is_safe_entry_name(name), "{:?} should be unsafe", name);
+        }
+
+        let root = tempfile::tempdir().unwrap();
+        let local = root.path().join("restored");
+        std::fs::create_dir(&local).unwrap();
+        std::fs::write(root.path().join("outside.txt"), b"secret").unwrap();
+
+        let mut tree = TestTree {
+            directories: [(
+                "kroot".to_string(),
+                vec![tree_entry("..", "d", 0, "kparent")],
+            )]
+            .into(),
+            contents: Default::default(),
+        };
+        tree.directories.insert(
+            "kparent".into(),
+            vec![tree_entry("outside.txt", "f", 6, "Oout")],
+        );
+
+        let result = verify_restore_tree(&mut tree, "kroot".into(), &local, false, 64, 100).await;
+        assert!(result.is_err());
+    }
+
+    /// Local HTTP server answering every request with an empty 200 after
+    /// `delay`; returns its URL and the most requests it had in flight at once
+    async fn slow_server(
+        delay: std::time::Duration,
+    ) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let max = max_in_flight.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let (in_flight, max) = (in_flight.clone(), max.clone());
+                tokio::spawn(async move {
+                    // Read the headers and the body announced by content-length
+                    let mut request = Vec::new();
+                    let mut buffer = [0u8; 4096];
+                    loop {
+                        let read = socket.read(&mut buffer).await.unwrap_or(0);
+                        if read == 0 {
+                            return;
+                        }
+                        request.extend_from_slice(&buffer[..read]);
+                        let text = String::from_utf8_lossy(&request).to_lowercase();
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let length = text
+                                .lines()
+                                .find_map(|line| line.strip_prefix("content-length:"))
+                                .and_then(|value| value.trim().parse::<usize>().ok())
+                                .unwrap_or(0);
+                            if request.len() >= end + 4 + length {
+                                break;
+                            }
+                        }
+                    }
+
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    let _ = socket
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                        )
+                        .await;
+                });
+            }
+        });
+
+        (url, max_in_flight)
+    }
+
+    /// Run two `set_policy_locked` calls at once and return the most requests
+    /// the server had in flight
+    async fn max_concurrent_policy_sets(first: &str, second: &str) -> usize {
+        use crate::app_state::AppState;
+        use crate::commands::kopia::set_policy_locked;
+        use crate::types::PolicyDefinition;
+
+        let (url, max_in_flight) = slow_server(std::time::Duration::from_millis(100)).await;
+        let state = AppState::new();
+        let client = reqwest::Client::builder().no_proxy().build().unwrap();
+        let policy = PolicyDefinition::default();
+
+        let set = |path| {
+            set_policy_locked(
+                &state,
+                &client,
+                &url,
+                "repo",
+                Some("user"),
+                Some("host"),
+                Some(path),
+                &policy,
+            )
+        };
+        let (first, second) = tokio::join!(set(first), set(second));
+        first.unwrap();
+        second.unwrap();
+
+        max_in_flight.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn test_policy_set_same_target_requests_serialize() {
+        assert_eq!(max_concurrent_policy_sets("/data", "/data").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_policy_set_different_targets_requests_overlap() {
+        assert_eq!(max_concurrent_policy_sets("/data", "/other").await, 2);
+    }
 }
@@ -88,6 +88,7 @@ mod tests {
             control_password: Some("control-password-456".to_string()),
             cert_sha256: "abc123def456".to_string(),
             pid: 12345,
+            startup_warnings: Vec::new(),
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -115,6 +116,7 @@ mod tests {
             control_password: None,
             cert_sha256: "deadbeef".to_string(),
             pid: 999,
+            startup_warnings: Vec::new(),
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -210,6 +212,7 @@ mod tests {
             control_password: Some("control".to_string()),
             cert_sha256: "abc123".to_string(),
             pid: 12345,
+            startup_warnings: Vec::new(),
         };
 
         let cloned = info.clone();
@@ -296,6 +299,7 @@ mod tests {
             control_password: None,
             cert_sha256: "abc123".to_string(),
             pid: 12345,
+            startup_warnings: Vec::new(),
         };
 
         let debug_str = format!("{:?}", info);
@@ -340,4 +344,98 @@ mod tests {
     fn test_get_ready_waiter() {
         // This would test the ready waiter functionality
     }
+
+    #[test]
+    fn test_start_options_default_is_valid() {
+        use crate::kopia_server::ServerStartOptions;
+
+        assert!(ServerStartOptions::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_start_options_reject_non_loopback_address() {
+        use crate::kopia_server::ServerStartOptions;
+
+        let options = ServerStartOptions {
+            address: Some("0.0.0.0:51515".to_string()),
+            log_level: None,
+        };
+        assert!(options.validate().is_err());
+
+        let options = ServerStartOptions {
+            address: Some("127.0.0.1:51515".to_string()),
+            log_level: Some("debug".to_string()),
+        };
+        assert!(options.validate().is_ok());
+    }
+
+    #[test]
+    fn test_set_start_options_rejects_invalid_log_level() {
+        use crate::kopia_server::ServerStartOptions;
+
+        let mut server = KopiaServer::new();
+        let result = server.set_start_options(ServerStartOptions {
+            address: None,
+            log_level: Some("verbose".to_string()),
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_insecure_skip_cert_verify_gating() {
+        use crate::kopia_server::insecure_cert_verify_allowed;
+
+        let mut server = KopiaServer::new();
+
+        // Disabling is always allowed
+        assert!(server.set_insecure_skip_cert_verify(false).is_ok());
+
+        // Enabling depends on the build (always allowed in debug builds)
+        assert_eq!(
+            server.set_insecure_skip_cert_verify(true).is_ok(),
+            insecure_cert_verify_allowed()
+        );
+        if cfg!(debug_assertions) {
+            assert!(insecure_cert_verify_allowed());
+        }
+    }
+
+    #[test]
+    fn test_heartbeat_tracker_counts_consecutive_failures() {
+        use crate::kopia_server::{HeartbeatTracker, HEARTBEAT_FAILURE_THRESHOLD};
+
+        let mut tracker = HeartbeatTracker::default();
+        for _ in 1..HEARTBEAT_FAILURE_THRESHOLD {
+            assert_eq!(tracker.observe("repo", false), None);
+        }
+        assert_eq!(
+            tracker.observe("repo", false),
+            Some(HEARTBEAT_FAILURE_THRESHOLD)
+        );
+
+        // Counting restarts after the threshold is reported
+        assert_eq!(tracker.observe("repo", false), None);
+
+        // A successful heartbeat resets the count
+        let mut tracker = HeartbeatTracker::default();
+        for _ in 1..HEARTBEAT_FAILURE_THRESHOLD {
+            tracker.observe("repo", false);
+        }
+        assert_eq!(tracker.observe("repo", true), None);
+        assert_eq!(tracker.observe("repo", false), None);
+
+        // Servers are counted separately, and stopped ones are forgotten
+        let mut tracker = HeartbeatTracker::default();
+        for _ in 1..HEARTBEAT_FAILURE_THRESHOLD {
+            tracker.observe("a", false);
+            tracker.observe("b", false);
+        }
+        tracker.retain_running(&["b".to_string()]);
+        assert_eq!(tracker.observe("a", false), None);
+        assert_eq!(
+            tracker.observe("b", false),
+            Some(HEARTBEAT_FAILURE_THRESHOLD)
+        );
+    }
 }
@@ -0,0 +1,57 @@
+/// Unit tests for logging.rs
+/// Tests the level filter and line format
+#[cfg(test)]
+mod tests {
+    use crate::logging::*;
+    use log::{Level, LevelFilter};
+
+    #[test]
+    fn test_app_records_follow_max_level() {
+        let target = "kopia_desktop_lib::commands::kopia";
+
+        assert!(record_enabled(target, Level::Info, LevelFilter::Info));
+        assert!(!record_enabled(target, Level::Debug, LevelFilter::Info));
+        assert!(record_enabled(target, Level::Trace, LevelFilter::Trace));
+        assert!(!record_enabled(target, Level::Error, LevelFilter::Off));
+    }
+
+    #[test]
+    fn test_other_crates_are_capped_at_info() {
+        assert!(record_enabled(
+            "reqwest::connect",
+            Level::Info,
+            LevelFilter::Trace
+        ));
+        assert!(!record_enabled(
+            "reqwest::connect",
+            Level::Debug,
+            LevelFilter::Trace
+        ));
+        assert!(!record_enabled(
+            "hyper::proto",
+            Level::Info,
+            LevelFilter::Warn
+        ));
+    }
+
+    #[test]
+    fn test_format_record() {
+        assert_eq!(
+            format_record(
+                "2024-01-01T12:00:00.000+00:00",
+                Level::Warn,
+                "kopia_desktop_lib::reconnect",
+                "Repository 'repo' unreachable"
+            ),
+            "2024-01-01T12:00:00.000+00:00 WARN  kopia_desktop_lib::reconnect: Repository 'repo' unreachable\n"
+        );
+    }
+
+    #[test]
+    fn test_log_file_path() {
+        assert_eq!(
+            log_file_path("/home/user/.config/kopia"),
+            std::path::PathBuf::from("/home/user/.config/kopia").join(LOG_FILE_NAME)
+        );
+    }
+}
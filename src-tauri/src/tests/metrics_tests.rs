@@ -0,0 +1,97 @@
+/// Unit tests for metrics.rs
+/// Tests per-command counters and latency percentile estimates
+#[cfg(test)]
+mod tests {
+    use crate::metrics::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_empty_registry_snapshot() {
+        let registry = MetricsRegistry::default();
+        assert!(registry.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_record_counts_and_errors() {
+        let registry = MetricsRegistry::default();
+        registry.record("snapshots_list", Duration::from_millis(20), true);
+        registry.record("snapshots_list", Duration::from_millis(30), false);
+        registry.record("repository_status", Duration::from_millis(1), true);
+
+        let snapshot = registry.snapshot();
+        assert_eq!(snapshot.len(), 2);
+
+        let list = &snapshot["snapshots_list"];
+        assert_eq!(list.count, 2);
+        assert_eq!(list.errors, 1);
+
+        let status = &snapshot["repository_status"];
+        assert_eq!(status.count, 1);
+        assert_eq!(status.errors, 0);
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let registry = MetricsRegistry::default();
+        for _ in 0..90 {
+            registry.record("op", Duration::from_millis(8), true);
+        }
+        for _ in 0..10 {
+            registry.record("op", Duration::from_millis(400), true);
+        }
+
+        let metrics = &registry.snapshot()["op"];
+        assert_eq!(metrics.count, 100);
+        assert_eq!(metrics.p50_ms, 10);
+        // p95 falls in the 250-500ms bucket but is capped at the observed max
+        assert_eq!(metrics.p95_ms, 400);
+    }
+
+    #[test]
+    fn test_percentile_overflow_bucket_uses_max() {
+        let registry = MetricsRegistry::default();
+        registry.record("slow", Duration::from_secs(45), true);
+
+        let metrics = &registry.snapshot()["slow"];
+        assert_eq!(metrics.p50_ms, 45_000);
+        assert_eq!(metrics.p95_ms, 45_000);
+    }
+
+    #[test]
+    fn test_command_metrics_serialization() {
+        let metrics = CommandMetrics {
+            count: 3,
+            p50_ms: 10,
+            p95_ms: 25,
+            errors: 1,
+        };
+        let json = serde_json::to_value(&metrics).unwrap();
+        assert_eq!(json["count"], 3);
+        assert_eq!(json["p50Ms"], 10);
+        assert_eq!(json["p95Ms"], 25);
+        assert_eq!(json["errors"], 1);
+    }
+
+    #[test]
+    fn test_command_timer_records_in_app_state() {
+        use crate::app_state::AppState;
+        use crate::error::KopiaError;
+
+        let state = AppState::new();
+        let ok = CommandTimer::new("tasks_list", state.metrics()).finish(Ok(1));
+        assert_eq!(ok.unwrap(), 1);
+        let failed: crate::error::Result<()> = CommandTimer::new("tasks_list", state.metrics())
+            .finish(Err(KopiaError::ServerNotRunning));
+        assert!(failed.is_err());
+
+        // Leaving a command early (e.g. with `?`) skips `finish` and counts as an error
+        drop(CommandTimer::new("tasks_list", state.metrics()));
+
+        let snapshot = state.metrics().snapshot();
+        assert_eq!(snapshot["tasks_list"].count, 3);
+        assert_eq!(snapshot["tasks_list"].errors, 2);
+
+        // Each app state has its own registry
+        assert!(AppState::new().metrics().snapshot().is_empty());
+    }
+}
@@ -1,22 +1,33 @@
 //! Test modules for Kopia Desktop
 //!
 //! Organized test suite covering all aspects of the application:
+//! - App state (app_state_tests)
 //! - Error handling (advanced_error_tests, error_edge_cases_tests)
-//! - Command functionality (commands_tests, kopia_commands_tests)
-//! - Concurrency (concurrency_tests)
+//! - Command functionality (commands_tests, kopia_commands_tests, policy_helpers_tests)
+//! - Concurrency (concurrency_tests, fan_out_tests)
 //! - Integration (integration_tests, kopia_api_integration_tests)
-//! - Server functionality (kopia_server_tests)
+//! - Logging and metrics collection (logging_tests, metrics_tests)
+//! - Server functionality (kopia_server_tests, reconnect_tests)
+//! - Settings and power-aware scheduling (settings_tests, power_tests)
 //! - System utilities (system_tests)
 //! - Type definitions (types_tests, types_advanced_tests, types_unit_tests)
 
 mod advanced_error_tests;
+mod app_state_tests;
 mod commands_tests;
 mod concurrency_tests;
 mod error_edge_cases_tests;
+mod fan_out_tests;
 mod integration_tests;
 mod kopia_api_integration_tests;
 mod kopia_commands_tests;
 mod kopia_server_tests;
+mod logging_tests;
+mod metrics_tests;
+mod policy_helpers_tests;
+mod power_tests;
+mod reconnect_tests;
+mod settings_tests;
 mod system_tests;
 mod types_advanced_tests;
 mod types_tests;
@@ -0,0 +1,225 @@
+/// Unit tests for commands/policy_helpers.rs
+/// Tests ignore rule matching against sample paths and cron descriptions
+#[cfg(test)]
+mod tests {
+    use crate::commands::policy_helpers::{describe_cron_at, match_globs, CronSchedule};
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn matched(patterns: &[&str], path: &str) -> bool {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        match_globs(&patterns, &[path.to_string()])[0].matched
+    }
+
+    #[test]
+    fn test_name_patterns_match_at_any_depth() {
+        assert!(matched(&["*.tmp"], "a.tmp"));
+        assert!(matched(&["*.tmp"], "dir/sub/a.tmp"));
+        assert!(!matched(&["*.tmp"], "a.tmp.txt"));
+        assert!(matched(&["file?.log"], "logs/file1.log"));
+        assert!(!matched(&["file?.log"], "logs/file10.log"));
+    }
+
+    #[test]
+    fn test_anchored_patterns() {
+        assert!(matched(&["/build"], "build"));
+        assert!(!matched(&["/build"], "src/build"));
+        assert!(matched(&["docs/*.md"], "docs/readme.md"));
+        assert!(!matched(&["docs/*.md"], "docs/sub/readme.md"));
+        // Leading slash in sample paths is ignored
+        assert!(matched(&["/build"], "/build"));
+    }
+
+    #[test]
+    fn test_double_star() {
+        assert!(matched(&["**/node_modules"], "node_modules"));
+        assert!(matched(&["**/node_modules"], "a/b/node_modules"));
+        assert!(matched(&["a/**/z"], "a/z"));
+        assert!(matched(&["a/**/z"], "a/b/c/z"));
+        assert!(matched(&["logs/**"], "logs/2024/app.log"));
+        assert!(!matched(&["logs/**"], "logs"));
+    }
+
+    #[test]
+    fn test_directory_only_patterns() {
+        assert!(matched(&["cache/"], "cache/"));
+        assert!(!matched(&["cache/"], "cache"));
+        // Files below an ignored directory are ignored too
+        assert!(matched(&["cache/"], "project/cache/data.bin"));
+    }
+
+    #[test]
+    fn test_negation_last_rule_wins() {
+        let patterns = &["*.log", "!important.log"];
+        assert!(matched(patterns, "debug.log"));
+        assert!(!matched(patterns, "important.log"));
+
+        // Can't re-include a file inside an ignored directory
+        assert!(matched(&["logs/", "!logs/keep.log"], "logs/keep.log"));
+    }
+
+    #[test]
+    fn test_character_classes_and_escapes() {
+        assert!(matched(&["[abc].txt"], "b.txt"));
+        assert!(!matched(&["[abc].txt"], "d.txt"));
+        assert!(matched(&["img[0-9]"], "img7"));
+        assert!(matched(&["[!0-9]*"], "x1"));
+        assert!(!matched(&["[!0-9]*"], "1x"));
+        assert!(matched(&["\\#notes"], "#notes"));
+        assert!(matched(&["a[b"], "a[b"));
+    }
+
+    #[test]
+    fn test_comments_blank_lines_and_reported_pattern() {
+        let patterns = vec!["# comment".to_string(), "".to_string(), "*.bak".to_string()];
+        let samples = vec!["x.bak".to_string(), "x.txt".to_string()];
+
+        let results = match_globs(&patterns, &samples);
+        assert!(results[0].matched);
+        assert_eq!(results[0].pattern.as_deref(), Some("*.bak"));
+        assert!(!results[1].matched);
+        assert!(results[1].pattern.is_none());
+    }
+
+    #[test]
+    fn test_windows_separators() {
+        assert!(matched(&["build/out"], "build\\out"));
+    }
+
+    fn noon_jan_1() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(12, 0, 0)
+            .unwrap()
+    }
+
+    fn describe(expr: &str) -> String {
+        CronSchedule::parse(expr).unwrap().describe()
+    }
+
+    fn next_runs(expr: &str, count: usize) -> Vec<String> {
+        CronSchedule::parse(expr)
+            .unwrap()
+            .next_runs(noon_jan_1(), count)
+            .iter()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_describe_common_cron_expressions() {
+        assert_eq!(describe("0 2 * * *"), "Every day at 2:00 AM");
+        assert_eq!(describe("0 2,14 * * *"), "Every day at 2:00 AM and 2:00 PM");
+        assert_eq!(describe("* * * * *"), "Every minute");
+        assert_eq!(describe("*/15 * * * *"), "Every 15 minutes");
+        assert_eq!(describe("0 * * * *"), "Every hour");
+        assert_eq!(describe("0 */6 * * *"), "Every 6 hours");
+        assert_eq!(describe("15 */2 * * *"), "Every 2 hours at minute 15");
+        assert_eq!(describe("30 18 * * 1-5"), "At 6:30 PM on weekdays");
+        assert_eq!(describe("0 3 * * 0"), "At 3:00 AM on Sunday");
+        assert_eq!(
+            describe("15 14 1,15 * *"),
+            "At 2:15 PM on days 1 and 15 of the month"
+        );
+        assert_eq!(
+            describe("0,30 9-17 * * *"),
+            "Every 30 minutes between 9:00 AM and 5:59 PM"
+        );
+        assert_eq!(
+            describe("0 9 * jan,JUL sun"),
+            "At 9:00 AM on Sunday in January and July"
+        );
+    }
+
+    #[test]
+    fn test_cron_macros_and_comments() {
+        let result = describe_cron_at("@daily # nightly backup", noon_jan_1());
+        assert!(result.valid);
+        assert_eq!(result.normalized.as_deref(), Some("0 0 * * *"));
+        assert_eq!(result.description.as_deref(), Some("Every day at 12:00 AM"));
+
+        let result = describe_cron_at("  0   2 *  * * ", noon_jan_1());
+        assert_eq!(result.normalized.as_deref(), Some("0 2 * * *"));
+    }
+
+    #[test]
+    fn test_invalid_cron_expressions() {
+        for expr in [
+            "",
+            "* * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "* * * foo *",
+            "@reboot",
+        ] {
+            let result = describe_cron_at(expr, noon_jan_1());
+            assert!(!result.valid, "{expr} should be invalid");
+            assert!(result.error.is_some());
+            assert!(result.description.is_none());
+            assert!(result.next_runs.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_cron_next_runs() {
+        assert_eq!(
+            next_runs("0 2 * * *", 2),
+            vec!["2024-01-02 02:00", "2024-01-03 02:00"]
+        );
+        assert_eq!(
+            next_runs("*/15 * * * *", 3),
+            vec!["2024-01-01 12:15", "2024-01-01 12:30", "2024-01-01 12:45"]
+        );
+        // 2024-01-01 is a Monday
+        assert_eq!(
+            next_runs("0 22 * * 1-5", 2),
+            vec!["2024-01-01 22:00", "2024-01-02 22:00"]
+        );
+        // Leap days only
+        assert_eq!(
+            next_runs("5 0 29 2 *", 2),
+            vec!["2024-02-29 00:05", "2028-02-29 00:05"]
+        );
+    }
+
+    #[test]
+    fn test_cron_day_of_month_or_day_of_week() {
+        // Both restricted: either day matches
+        assert_eq!(
+            next_runs("0 9 1 * mon", 3),
+            vec!["2024-01-08 09:00", "2024-01-15 09:00", "2024-01-22 09:00"]
+        );
+        assert_eq!(
+            describe("0 9 1 * mon"),
+            "At 9:00 AM on day 1 of the month or on Monday"
+        );
+        // 7 is Sunday too
+        assert_eq!(next_runs("0 3 * * 7", 1), vec!["2024-01-07 03:00"]);
+    }
+
+    #[test]
+    fn test_recommended_policy_for() {
+        use crate::commands::policy_helpers::recommended_policy_for;
+        use crate::error::KopiaError;
+
+        let photos = recommended_policy_for("photos").unwrap();
+        assert_eq!(
+            photos.compression.unwrap().compressor_name.as_deref(),
+            Some("none")
+        );
+        assert_eq!(photos.retention.unwrap().keep_annual, Some(10));
+
+        let code = recommended_policy_for("code").unwrap();
+        let files = code.files.unwrap();
+        assert!(files.ignore.unwrap().contains(&"node_modules/".to_string()));
+        assert_eq!(files.ignore_cache_dirs, Some(true));
+        assert!(code.scheduling.is_none());
+
+        assert!(matches!(
+            recommended_policy_for("music"),
+            Err(KopiaError::InvalidInput { ref field, .. }) if field == "profile"
+        ));
+    }
+}
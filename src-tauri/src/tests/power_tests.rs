@@ -0,0 +1,106 @@
+/// Unit tests for power.rs
+/// Tests power state parsing and pause decisions
+#[cfg(test)]
+mod tests {
+    use crate::power::*;
+
+    #[test]
+    fn test_should_pause() {
+        let on_battery = PowerState {
+            on_battery: Some(true),
+            metered: None,
+        };
+        assert!(on_battery.should_pause());
+
+        let metered = PowerState {
+            on_battery: Some(false),
+            metered: Some(true),
+        };
+        assert!(metered.should_pause());
+
+        let on_ac = PowerState {
+            on_battery: Some(false),
+            metered: Some(false),
+        };
+        assert!(!on_ac.should_pause());
+        assert!(on_ac.is_known());
+
+        let unknown = PowerState::default();
+        assert!(!unknown.should_pause());
+        assert!(!unknown.is_known());
+    }
+
+    #[test]
+    fn test_parse_pmset_output() {
+        let battery = "Now drawing from 'Battery Power'\n -InternalBattery-0 85%; discharging";
+        assert_eq!(parse_pmset_output(battery), Some(true));
+        assert_eq!(
+            parse_pmset_output("Now drawing from 'AC Power'"),
+            Some(false)
+        );
+        assert_eq!(parse_pmset_output(""), None);
+    }
+
+    #[test]
+    fn test_parse_nm_metered() {
+        assert_eq!(parse_nm_metered("u 1\n"), Some(true));
+        assert_eq!(parse_nm_metered("u 3"), Some(true));
+        assert_eq!(parse_nm_metered("u 2"), Some(false));
+        assert_eq!(parse_nm_metered("u 4"), Some(false));
+        assert_eq!(parse_nm_metered("u 0"), None);
+        assert_eq!(parse_nm_metered(""), None);
+    }
+
+    #[test]
+    fn test_unpaused_sources_skips_user_paused() {
+        use crate::types::SourcesResponse;
+
+        let sources: SourcesResponse = serde_json::from_value(serde_json::json!({
+            "localUsername": "alice",
+            "localHost": "laptop",
+            "multiUser": false,
+            "sources": [
+                {"source": {"userName": "alice", "host": "laptop", "path": "/docs"}, "status": "IDLE", "schedule": {}},
+                {"source": {"userName": "alice", "host": "laptop", "path": "/music"}, "status": "PAUSED", "schedule": {}},
+                {"source": {"userName": "alice", "host": "laptop", "path": "/code"}, "status": "UPLOADING", "schedule": {}}
+            ]
+        }))
+        .unwrap();
+
+        let paths: Vec<String> = unpaused_sources(&sources)
+            .into_iter()
+            .map(|s| s.path)
+            .collect();
+        assert_eq!(paths, vec!["/docs", "/code"]);
+    }
+
+    #[test]
+    fn test_monitor_pauses_tracks_running_servers() {
+        use crate::types::SourceInfo;
+
+        let source = SourceInfo {
+            user_name: "alice".to_string(),
+            host: "laptop".to_string(),
+            path: "/docs".to_string(),
+        };
+        let mut pauses = MonitorPauses::default();
+        assert!(pauses.is_empty());
+
+        pauses.record("repo-a".to_string(), vec![source.clone()]);
+        // A server with nothing to pause still counts as handled
+        pauses.record("repo-b".to_string(), vec![]);
+        assert!(pauses.contains("repo-a"));
+        assert!(pauses.contains("repo-b"));
+        assert!(!pauses.contains("repo-c"));
+        assert_eq!(pauses.sources("repo-a"), &[source]);
+        assert!(pauses.sources("repo-c").is_empty());
+
+        // Stopped servers lose their pauses
+        pauses.retain_running(&["repo-a".to_string(), "repo-c".to_string()]);
+        assert!(pauses.contains("repo-a"));
+        assert!(!pauses.contains("repo-b"));
+
+        pauses.remove("repo-a");
+        assert!(pauses.is_empty());
+    }
+}
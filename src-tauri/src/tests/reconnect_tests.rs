@@ -0,0 +1,165 @@
+/// Unit tests for reconnect.rs
+/// Tests connectivity tracking, reconnect decisions and backoff
+#[cfg(test)]
+mod tests {
+    use crate::reconnect::*;
+    use std::time::{Duration, Instant};
+
+    /// Fail checks 30s apart until the tracker asks for a reconnect
+    ///
+    /// Returns the time of the check that did.
+    fn lose(tracker: &mut ReconnectTracker, repo_id: &str, start: Instant) -> Instant {
+        let mut now = start;
+        for _ in 1..RECONNECT_FAILURE_THRESHOLD {
+            assert_eq!(tracker.observe(repo_id, false, now), ReconnectAction::None);
+            now += Duration::from_secs(30);
+        }
+        assert_eq!(
+            tracker.observe(repo_id, false, now),
+            ReconnectAction::Reconnect
+        );
+        now
+    }
+
+    #[test]
+    fn test_never_connected_repository_is_ignored() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        assert_eq!(tracker.observe("repo", false, now), ReconnectAction::None);
+        assert!(tracker.lost_repos().is_empty());
+    }
+
+    #[test]
+    fn test_lost_connection_triggers_reconnect() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        assert_eq!(tracker.observe("repo", true, now), ReconnectAction::None);
+        lose(&mut tracker, "repo", now);
+        assert_eq!(tracker.lost_repos(), vec!["repo".to_string()]);
+    }
+
+    #[test]
+    fn test_single_failed_check_doesnt_reconnect() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        tracker.observe("repo", true, now);
+        assert_eq!(tracker.observe("repo", false, now), ReconnectAction::None);
+        // A successful check in between starts counting from zero again
+        assert_eq!(tracker.observe("repo", true, now), ReconnectAction::None);
+        for _ in 1..RECONNECT_FAILURE_THRESHOLD {
+            assert_eq!(tracker.observe("repo", false, now), ReconnectAction::None);
+        }
+        assert!(tracker.lost_repos().is_empty());
+    }
+
+    #[test]
+    fn test_failures_must_span_initial_backoff() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        tracker.observe("repo", true, now);
+        for _ in 0..RECONNECT_FAILURE_THRESHOLD + 2 {
+            assert_eq!(tracker.observe("repo", false, now), ReconnectAction::None);
+        }
+        assert_eq!(
+            tracker.observe(
+                "repo",
+                false,
+                now + Duration::from_secs(RECONNECT_INITIAL_BACKOFF_SECS)
+            ),
+            ReconnectAction::Reconnect
+        );
+    }
+
+    #[test]
+    fn test_failed_reconnect_backs_off() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        tracker.observe("repo", true, now);
+        let now = lose(&mut tracker, "repo", now);
+        tracker.reconnect_failed("repo", now);
+
+        let before_backoff = now + reconnect_backoff(1) - Duration::from_secs(1);
+        assert_eq!(
+            tracker.observe("repo", false, before_backoff),
+            ReconnectAction::None
+        );
+        assert_eq!(
+            tracker.observe("repo", false, now + reconnect_backoff(1)),
+            ReconnectAction::Reconnect
+        );
+    }
+
+    #[test]
+    fn test_recovery_reports_attempts_and_downtime() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        tracker.observe("repo", true, now);
+        let lost_at = lose(&mut tracker, "repo", now);
+        tracker.reconnect_failed("repo", lost_at);
+        tracker.reconnect_failed("repo", lost_at);
+
+        // Downtime counts from the first failed check
+        let action = tracker.observe("repo", true, now + Duration::from_secs(90));
+        assert_eq!(
+            action,
+            ReconnectAction::Recovered(RepositoryReconnectedEvent {
+                repo_id: "repo".to_string(),
+                attempts: 2,
+                downtime_secs: 90,
+            })
+        );
+        assert!(tracker.lost_repos().is_empty());
+
+        // Subsequent healthy checks don't report again
+        assert_eq!(tracker.observe("repo", true, now), ReconnectAction::None);
+    }
+
+    #[test]
+    fn test_retain_running_keeps_lost_repositories() {
+        let mut tracker = ReconnectTracker::default();
+        let now = Instant::now();
+
+        tracker.observe("stopped", true, now);
+        tracker.observe("lost", true, now);
+        lose(&mut tracker, "lost", now);
+        tracker.retain_running(&[]);
+
+        assert_eq!(tracker.lost_repos(), vec!["lost".to_string()]);
+        // "stopped" was forgotten, so a failed check no longer triggers a reconnect
+        assert_eq!(
+            tracker.observe("stopped", false, now),
+            ReconnectAction::None
+        );
+
+        tracker.remove("lost");
+        assert!(tracker.lost_repos().is_empty());
+    }
+
+    #[test]
+    fn test_reconnect_backoff() {
+        assert_eq!(reconnect_backoff(0), Duration::from_secs(30));
+        assert_eq!(reconnect_backoff(1), Duration::from_secs(30));
+        assert_eq!(reconnect_backoff(2), Duration::from_secs(60));
+        assert_eq!(reconnect_backoff(3), Duration::from_secs(120));
+        assert_eq!(reconnect_backoff(6), Duration::from_secs(600));
+        assert_eq!(reconnect_backoff(u32::MAX), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn test_reconnected_event_serialization() {
+        let event = RepositoryReconnectedEvent {
+            repo_id: "repo".to_string(),
+            attempts: 1,
+            downtime_secs: 42,
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["repoId"], "repo");
+        assert_eq!(json["downtimeSecs"], 42);
+    }
+}
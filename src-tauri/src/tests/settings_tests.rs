@@ -0,0 +1,133 @@
+/// Unit tests for settings.rs
+/// Tests loading defaults, persisting updates and tolerating bad files
+#[cfg(test)]
+mod tests {
+    use crate::settings::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_load_missing_file_uses_defaults() {
+        let temp_dir = tempdir().unwrap();
+        let store = SettingsStore::load(temp_dir.path().to_str().unwrap());
+        assert_eq!(store.get(), AppSettings::default());
+        assert!(!store.get().power_aware_scheduling);
+    }
+
+    #[test]
+    fn test_update_persists_settings() {
+        let temp_dir = tempdir().unwrap();
+        let config_dir = temp_dir.path().to_str().unwrap();
+
+        let mut store = SettingsStore::load(config_dir);
+        let updated = store.update(|s| s.power_aware_scheduling = true).unwrap();
+        assert!(updated.power_aware_scheduling);
+
+        let reloaded = SettingsStore::load(config_dir);
+        assert!(reloaded.get().power_aware_scheduling);
+    }
+
+    #[test]
+    fn test_invalid_file_uses_defaults() {
+        let temp_dir = tempdir().unwrap();
+        std::fs::write(temp_dir.path().join(SETTINGS_FILE_NAME), "not json").unwrap();
+
+        let store = SettingsStore::load(temp_dir.path().to_str().unwrap());
+        assert_eq!(store.get(), AppSettings::default());
+    }
+
+    #[test]
+    fn test_settings_file_not_discovered_as_repository() {
+        assert!(!SETTINGS_FILE_NAME.ends_with(".config"));
+    }
+
+    #[test]
+    fn test_source_notify_override_replaces_existing() {
+        use crate::types::SourceInfo;
+
+        let source = SourceInfo {
+            user_name: "alice".to_string(),
+            host: "laptop".to_string(),
+            path: "/home/alice".to_string(),
+        };
+        let mut settings = AppSettings::default();
+
+        settings.set_source_notify_on_error("repo-a", &source, true);
+        settings.set_source_notify_on_error("repo-b", &source, true);
+        settings.set_source_notify_on_error("repo-a", &source, false);
+
+        assert_eq!(settings.source_notify_overrides.len(), 2);
+        let repo_a = settings
+            .source_notify_overrides
+            .iter()
+            .find(|o| o.repo_id == "repo-a")
+            .unwrap();
+        assert!(!repo_a.notify_on_error);
+
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(
+            json["sourceNotifyOverrides"][0]["source"]["userName"],
+            "alice"
+        );
+    }
+
+    #[test]
+    fn test_clear_tasks_before_only_moves_forward() {
+        let at = |t: &str| {
+            chrono::DateTime::parse_from_rfc3339(t)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.tasks_cleared_before("repo-a"), None);
+
+        settings.clear_tasks_before("repo-a", at("2024-01-02T00:00:00Z"));
+        settings.clear_tasks_before("repo-a", at("2024-01-01T00:00:00Z"));
+        assert_eq!(
+            settings.tasks_cleared_before("repo-a"),
+            Some(at("2024-01-02T00:00:00Z"))
+        );
+        assert_eq!(settings.tasks_cleared_before("repo-b"), None);
+    }
+
+    #[test]
+    fn test_record_throughput_dedupes_and_trims() {
+        use crate::settings::{ThroughputSample, MAX_THROUGHPUT_SAMPLES};
+
+        let sample = |minute: usize| ThroughputSample {
+            repo_id: "repo".to_string(),
+            time: format!("2024-01-01T{:02}:{:02}:00Z", minute / 60, minute % 60),
+            bytes_per_second: 1000.0,
+            source: "alice@laptop:/docs".to_string(),
+        };
+
+        let mut settings = AppSettings::default();
+        settings.record_throughput(vec![sample(2), sample(1)]);
+        settings.record_throughput(vec![sample(1)]);
+        assert_eq!(settings.upload_throughput, vec![sample(1), sample(2)]);
+        assert!(settings.has_throughput_sample(&sample(2)));
+
+        settings.record_throughput((0..MAX_THROUGHPUT_SAMPLES + 10).map(sample).collect());
+        assert_eq!(settings.upload_throughput.len(), MAX_THROUGHPUT_SAMPLES);
+        assert_eq!(settings.upload_throughput[0], sample(10));
+    }
+
+    #[test]
+    fn test_source_notify_on_error_lookup() {
+        use crate::types::SourceInfo;
+
+        let source = SourceInfo {
+            user_name: "alice".to_string(),
+            host: "laptop".to_string(),
+            path: "/home/alice".to_string(),
+        };
+        let mut settings = AppSettings::default();
+        assert_eq!(settings.source_notify_on_error("repo-a", &source), None);
+
+        settings.set_source_notify_on_error("repo-a", &source, false);
+        assert_eq!(
+            settings.source_notify_on_error("repo-a", &source),
+            Some(false)
+        );
+        assert_eq!(settings.source_notify_on_error("repo-b", &source), None);
+    }
+}
@@ -92,4 +92,441 @@ mod tests {
         let info = get_system_info().await.unwrap();
         assert_eq!(info.os, "macos");
     }
+
+    #[test]
+    fn test_sha256_file() {
+        use crate::commands::system::sha256_file;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("kopia");
+        std::fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            sha256_file(path.to_str().unwrap()).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert!(sha256_file(temp_dir.path().join("missing").to_str().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_hash_matches() {
+        use crate::commands::system::hash_matches;
+
+        assert!(hash_matches("ab12", "AB12\n"));
+        assert!(!hash_matches("ab12", "ab13"));
+    }
+    #[test]
+    fn test_storage_usage_categories() {
+        use crate::commands::system::storage_usage;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("repository.config"), vec![0u8; 100]).unwrap();
+        std::fs::create_dir_all(root.join("cache/abc")).unwrap();
+        std::fs::write(root.join("cache/abc/blob"), vec![0u8; 300]).unwrap();
+        std::fs::create_dir(root.join("logs")).unwrap();
+        std::fs::write(root.join("logs/app.log"), vec![0u8; 50]).unwrap();
+
+        let usage = storage_usage(root);
+        assert_eq!(usage.config_bytes, 100);
+        assert_eq!(usage.cache_bytes, 300);
+        assert_eq!(usage.logs_bytes, 50);
+        assert_eq!(usage.total_bytes, 450);
+        assert!(usage.inaccessible.is_empty());
+    }
+
+    #[test]
+    fn test_storage_usage_missing_dir() {
+        use crate::commands::system::storage_usage;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let usage = storage_usage(&temp_dir.path().join("missing"));
+        assert_eq!(usage.total_bytes, 0);
+        assert!(usage.inaccessible.is_empty());
+    }
+
+    #[test]
+    fn test_vss_availability() {
+        use crate::commands::system::vss_availability;
+
+        let status = vss_availability(Ok(()), true);
+        assert!(status.available);
+        assert!(status.reason.is_none());
+
+        let status = vss_availability(Ok(()), false);
+        assert!(!status.available);
+        assert!(status.reason.unwrap().contains("administrator"));
+
+        let status = vss_availability(Err("service is disabled".to_string()), true);
+        assert!(!status.available);
+        assert_eq!(status.reason.as_deref(), Some("service is disabled"));
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_vss_status_unavailable_off_windows() {
+        let status = crate::commands::system::vss_status().await.unwrap();
+        assert!(!status.available);
+        assert!(status.reason.is_some());
+    }
+
+    #[test]
+    fn test_parse_mounts() {
+        use crate::commands::system::{mount_filesystem, parse_mount_output, parse_proc_mounts};
+        use std::path::Path;
+
+        let mounts = parse_proc_mounts(
+            "/dev/sda1 / ext4 rw,relatime 0 0\n\
+             /dev/sdb1 /media/My\\040Stick vfat rw 0 0\n\
+             server:/export /mnt/nfs nfs4 rw 0 0\n",
+        );
+        assert_eq!(
+            mounts[1],
+            ("/media/My Stick".to_string(), "vfat".to_string())
+        );
+        assert_eq!(
+            mount_filesystem(&mounts, Path::new("/media/My Stick/restore")),
+            Some("vfat".to_string())
+        );
+        assert_eq!(
+            mount_filesystem(&mounts, Path::new("/mnt/nfsdata")),
+            Some("ext4".to_string())
+        );
+
+        let mounts = parse_mount_output(
+            "/dev/disk3s1s1 on / (apfs, sealed, local, read-only, journaled)\n\
+             /dev/disk4s1 on /Volumes/USB DRIVE (msdos, local, nodev, nosuid, noowners)\n",
+        );
+        assert_eq!(
+            mount_filesystem(&mounts, Path::new("/Volumes/USB DRIVE/photos")),
+            Some("msdos".to_string())
+        );
+    }
+
+    #[test]
+    fn test_restore_option_support() {
+        use crate::commands::system::restore_option_support;
+        use crate::types::FilesystemOutput;
+
+        let options = FilesystemOutput {
+            write_sparse_files: Some(true),
+            write_files_atomically: Some(true),
+            skip_owners: Some(true),
+            ..Default::default()
+        };
+
+        let fat = restore_option_support(Some("vfat"), &options);
+        let options_checked: Vec<&str> = fat.iter().map(|r| r.option.as_str()).collect();
+        assert_eq!(
+            options_checked,
+            vec![
+                "writeSparseFiles",
+                "writeFilesAtomically",
+                "skipPermissions"
+            ]
+        );
+        assert!(fat.iter().all(|r| !r.supported && r.reason.is_some()));
+
+        let ext4 = restore_option_support(Some("ext4"), &options);
+        assert!(ext4.iter().all(|r| r.supported && r.reason.is_none()));
+
+        let nfs = restore_option_support(Some("nfs4"), &options);
+        assert!(!nfs[1].supported);
+
+        let unknown = restore_option_support(None, &options);
+        assert!(unknown.iter().all(|r| r.supported && r.reason.is_some()));
+    }
+
+    #[test]
+    fn test_restore_ui_state() {
+        use crate::commands::system::restore_ui_state;
+        use crate::settings::AppSettings;
+
+        let repo_ids = vec!["repository".to_string(), "repository-abc123".to_string()];
+
+        let mut settings = AppSettings {
+            last_repo_id: Some("repository-abc123".to_string()),
+            last_view: Some("/snapshots".to_string()),
+            ..Default::default()
+        };
+        let state = restore_ui_state(&settings, &repo_ids);
+        assert_eq!(state.repo_id, "repository-abc123");
+        assert_eq!(state.view.as_deref(), Some("/snapshots"));
+
+        // A removed repository falls back to the default one
+        settings.last_repo_id = Some("repository-gone".to_string());
+        assert_eq!(restore_ui_state(&settings, &repo_ids).repo_id, "repository");
+
+        let state = restore_ui_state(&AppSettings::default(), &repo_ids);
+        assert_eq!(state.repo_id, "repository");
+        assert_eq!(state.view, None);
+    }
+
+    #[test]
+    fn test_copy_config_dir() {
+        use crate::commands::system::{copy_config_dir, validate_relocation_target};
+        use crate::settings::{SETTINGS_FILE_NAME, SETTINGS_TEMP_FILE_NAME};
+        use std::path::Path;
+
+        let old = tempfile::tempdir().unwrap();
+        let new = tempfile::tempdir().unwrap();
+        let target = new.path().join("kopia");
+
+        std::fs::write(old.path().join(SETTINGS_FILE_NAME), "{}").unwrap();
+        std::fs::write(old.path().join(SETTINGS_TEMP_FILE_NAME), "{}").unwrap();
+        // Only the settings file itself is left behind, not names sharing its prefix
+        std::fs::write(old.path().join("kopia-desktop-settings.json.bak"), "{}").unwrap();
+        std::fs::write(
+            old.path().join("repository.config"),
+            r#"{"caching":{"cacheDirectory":"cache/abc"}}"#,
+        )
+        .unwrap();
+        std::fs::create_dir_all(old.path().join("logs/cli")).unwrap();
+        std::fs::write(old.path().join("logs/cli/1.log"), "log").unwrap();
+
+        assert!(validate_relocation_target(old.path(), &target).is_ok());
+        assert!(validate_relocation_target(old.path(), &old.path().join("sub")).is_err());
+        assert!(validate_relocation_target(old.path(), Path::new("relative/dir")).is_err());
+        assert!(validate_relocation_target(&target, old.path()).is_err());
+
+        std::fs::create_dir_all(&target).unwrap();
+        let copied = copy_config_dir(old.path(), &target).unwrap();
+        assert_eq!(
+            copied,
+            vec![
+                "kopia-desktop-settings.json.bak",
+                "logs",
+                "repository.config"
+            ]
+        );
+
+        assert!(!target.join(SETTINGS_FILE_NAME).exists());
+        assert!(!target.join(SETTINGS_TEMP_FILE_NAME).exists());
+        assert_eq!(
+            std::fs::read_to_string(target.join("logs/cli/1.log")).unwrap(),
+            "log"
+        );
+        let config: serde_json::Value = serde_json::from_str(
+            &std::fs::read_to_string(target.join("repository.config")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            config["caching"]["cacheDirectory"].as_str().map(Path::new),
+            Some(old.path().join("cache/abc").as_path())
+        );
+
+        // The target now holds data, so it can't receive another copy
+        assert!(validate_relocation_target(old.path(), &target).is_err());
+    }
+
+    #[test]
+    fn test_absolute_cache_directory() {
+        use crate::commands::system::absolute_cache_directory;
+        use std::path::Path;
+
+        let dir = Path::new("/home/alice/.config/kopia");
+        let updated = absolute_cache_directory(
+            r#"{"caching":{"cacheDirectory":"../../.cache/kopia/x"}}"#,
+            dir,
+        )
+        .unwrap();
+        assert!(updated.contains("/home/alice/.config/kopia/../../.cache/kopia/x"));
+
+        assert_eq!(
+            absolute_cache_directory(r#"{"caching":{"cacheDirectory":"/var/cache/kopia"}}"#, dir),
+            None
+        );
+        assert_eq!(absolute_cache_directory(r#"{"storage":{}}"#, dir), None);
+    }
+
+    #[test]
+    fn test_parse_df_available() {
+        use crate::commands::system::parse_df_available;
+
+        let output = "Filesystem     1024-blocks      Used Available Capacity Mounted on\n\
+                      /dev/sda1        102400000  51200000  51200000      50% /\n";
+        assert_eq!(parse_df_available(output), Some(51_200_000 * 1024));
+
+        // Filesystem names may contain spaces
+        let output = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                      map auto_home 0 0 0 100% /Volumes/Backup Disk\n";
+        assert_eq!(parse_df_available(output), Some(0));
+
+        assert_eq!(parse_df_available("Filesystem"), None);
+    }
+
+    #[test]
+    fn test_parse_log_level() {
+        use crate::commands::system::{log_level_name, parse_log_level};
+        use crate::error::KopiaError;
+
+        for name in ["error", "warn", "info", "debug", "trace"] {
+            let filter = parse_log_level(name).unwrap();
+            assert_eq!(log_level_name(filter), name);
+        }
+        assert_eq!(parse_log_level(" DEBUG ").unwrap(), log::LevelFilter::Debug);
+        assert_eq!(log_level_name(log::LevelFilter::Off), "off");
+
+        for invalid in ["", "off", "verbose"] {
+            assert!(matches!(
+                parse_log_level(invalid),
+                Err(KopiaError::InvalidInput { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_orphaned_mount_points() {
+        use crate::commands::system::orphaned_mount_points;
+        use std::path::Path;
+
+        let mounts = vec![
+            ("/".to_string(), "ext4".to_string()),
+            ("/tmp/kopia-mount123".to_string(), "fuse.kopia".to_string()),
+            ("/tmp/kopia-mount456".to_string(), "fuse.kopia".to_string()),
+            ("/tmp/other/kopia-mount789".to_string(), "fuse".to_string()),
+            ("/media/usb".to_string(), "vfat".to_string()),
+        ];
+        let active = vec!["/tmp/kopia-mount456".to_string()];
+
+        assert_eq!(
+            orphaned_mount_points(Path::new("/tmp"), &mounts, &active),
+            vec!["/tmp/kopia-mount123"]
+        );
+        assert!(orphaned_mount_points(Path::new("/var/tmp"), &mounts, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_leftover_mount_dirs() {
+        use crate::commands::system::leftover_mount_dirs;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let empty = temp_dir.path().join("kopia-mount111");
+        let not_empty = temp_dir.path().join("kopia-mount222");
+        std::fs::create_dir(&empty).unwrap();
+        std::fs::create_dir(&not_empty).unwrap();
+        std::fs::write(not_empty.join("file.txt"), "data").unwrap();
+        std::fs::create_dir(temp_dir.path().join("unrelated")).unwrap();
+        std::fs::write(temp_dir.path().join("kopia-mount333"), "a file").unwrap();
+
+        assert_eq!(
+            leftover_mount_dirs(temp_dir.path()),
+            vec![empty.to_str().unwrap().to_string()]
+        );
+    }
+
+    #[test]
+    fn test_effective_config() {
+        use crate::commands::system::effective_config;
+        use crate::fan_out::DEFAULT_FAN_OUT_TIMEOUT_SECS;
+        use crate::settings::AppSettings;
+        use crate::types::ThrottleLimits;
+
+        let config = effective_config(&AppSettings::default(), None);
+        assert_eq!(config.fan_out_timeout_secs, DEFAULT_FAN_OUT_TIMEOUT_SECS);
+        assert!(!config.auto_reconnect);
+        assert!(config.throttle.is_none());
+        assert!(config.http_connect_timeout_secs <= config.http_operation_timeout_secs);
+
+        let settings = AppSettings {
+            fan_out_timeout_secs: Some(42),
+            auto_reconnect: true,
+            wait_for_tasks_on_quit: true,
+            ..Default::default()
+        };
+        let throttle = ThrottleLimits {
+            max_upload_speed_bytes_per_second: Some(1_000_000.0),
+            ..Default::default()
+        };
+        let config = effective_config(&settings, Some(throttle.clone()));
+        assert_eq!(config.fan_out_timeout_secs, 42);
+        assert!(config.auto_reconnect);
+        assert!(config.wait_for_tasks_on_quit);
+        assert_eq!(config.throttle, Some(throttle));
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["fanOutTimeoutSecs"], 42);
+        assert!(json.get("healthCheckRetries").is_some());
+    }
+
+    #[test]
+    fn test_hash_directory_tree() {
+        use crate::commands::system::hash_directory_tree;
+        use std::time::{Duration, Instant};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(root.join("a.txt"), b"abc").unwrap();
+        std::fs::create_dir(root.join("docs")).unwrap();
+        std::fs::write(root.join("docs").join("copy.txt"), b"abc").unwrap();
+        std::fs::write(root.join("docs").join("other.txt"), b"hello").unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let summary = hash_directory_tree(root, "SHA256", 100, deadline).unwrap();
+        assert_eq!(summary.algorithm, "sha256");
+        assert_eq!(summary.file_count, 3);
+        assert_eq!(summary.total_bytes, 11);
+        assert_eq!(summary.duplicate_files, 1);
+        assert_eq!(summary.duplicate_bytes, 3);
+        assert!(!summary.truncated);
+        assert_eq!(summary.sample_hashes[0].path, "a.txt");
+        assert_eq!(
+            summary.sample_hashes[0].hash,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(summary.sample_hashes[1].hash, summary.sample_hashes[0].hash);
+
+        let summary = hash_directory_tree(root, "sha512", 2, deadline).unwrap();
+        assert_eq!(summary.file_count, 2);
+        assert_eq!(summary.sample_hashes[0].hash.len(), 128);
+        assert!(summary.truncated);
+
+        // An expired deadline stops before hashing anything
+        let summary = hash_directory_tree(root, "sha256", 100, Instant::now()).unwrap();
+        assert_eq!(summary.file_count, 0);
+        assert!(summary.truncated);
+
+        assert!(hash_directory_tree(root, "md5", 100, deadline).is_err());
+        assert!(hash_directory_tree(&root.join("missing"), "sha256", 100, deadline).is_err());
+    }
+
+    #[test]
+    fn test_verify_file_compares_in_chunks() {
+        use crate::commands::system::verify_file;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+        // Larger than one chunk, with the difference in the second one
+        let contents: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &contents).unwrap();
+
+        assert!(verify_file(&path, contents.as_slice()).is_ok());
+
+        let mut changed = contents.clone();
+        changed[150_000] ^= 1;
+        assert!(verify_file(&path, changed.as_slice()).is_err());
+        assert!(verify_file(&path, &contents[..199_999]).is_err());
+        let mut longer = contents.clone();
+        longer.push(0);
+        assert!(verify_file(&path, longer.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_hash_file_checks_deadline_while_reading() {
+        use crate::commands::system::hash_file;
+        use std::time::{Duration, Instant};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("abc.txt");
+        std::fs::write(&path, "abc").unwrap();
+
+        let later = Instant::now() + Duration::from_secs(60);
+        assert_eq!(
+            hash_file(&path, "sha256", later).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let error = hash_file(&path, "sha256", Instant::now()).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+    }
 }
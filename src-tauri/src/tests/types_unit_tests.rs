@@ -206,4 +206,122 @@ mod tests {
         let debug_str = format!("{:?}", request);
         assert!(debug_str.contains("RepositoryCreateRequest"));
     }
+
+    #[test]
+    fn test_restore_verification_default_and_serialization() {
+        let verification = RestoreVerification::default();
+        assert_eq!(verification.matched, 0);
+        assert!(verification.mismatched.is_empty());
+        assert!(verification.missing.is_empty());
+        assert!(!verification.truncated);
+
+        let verification = RestoreVerification {
+            matched: 3,
+            mismatched: vec!["docs/a.txt".to_string()],
+            missing: vec!["docs/b.txt".to_string()],
+            truncated: true,
+        };
+        let json = serde_json::to_value(&verification).unwrap();
+        assert_eq!(json["matched"], 3);
+        assert_eq!(json["mismatched"][0], "docs/a.txt");
+        assert_eq!(json["truncated"], true);
+    }
+
+    #[test]
+    fn test_files_policy_default_one_file_system() {
+        let mut policy = PolicyDefinition::default();
+        policy
+            .files
+            .get_or_insert_with(Default::default)
+            .one_file_system = Some(true);
+
+        let json = serde_json::to_value(&policy).unwrap();
+        assert_eq!(json["files"]["oneFileSystem"], true);
+        assert!(json["files"]["ignore"].is_null());
+    }
+
+    #[test]
+    fn test_snapshot_counters_beyond_i32() {
+        let big = i32::MAX as i64 + 10;
+        let json = serde_json::json!({
+            "id": "snap1",
+            "startTime": "2024-01-01T00:00:00Z",
+            "stats": {
+                "totalSize": big * 1024,
+                "fileCount": big,
+                "dirCount": big,
+                "errorCount": 0
+            },
+            "storageStats": {
+                "newData": { "fileObjects": big },
+                "runningTotal": { "contents": big }
+            }
+        });
+
+        let snapshot: Snapshot = serde_json::from_value(json).unwrap();
+        let stats = snapshot.stats.unwrap();
+        assert_eq!(stats.file_count, big);
+        assert_eq!(stats.dir_count, big);
+        assert_eq!(stats.total_size, big * 1024);
+        // Missing counters default to zero
+        assert_eq!(stats.cached_files, 0);
+        assert_eq!(stats.excluded_dir_count, 0);
+
+        let storage = snapshot.storage_stats.unwrap();
+        assert_eq!(storage.new_data.file_object_count, big);
+        assert_eq!(storage.new_data.contents, 0);
+        assert_eq!(storage.running_total.contents, big);
+    }
+
+    #[test]
+    fn test_upload_counters_beyond_i32() {
+        let big = i32::MAX as i64 + 1;
+        let json = serde_json::json!({
+            "hashedFiles": big,
+            "cachedFiles": big,
+            "estimatedFiles": big
+        });
+
+        let counters: UploadCounters = serde_json::from_value(json).unwrap();
+        assert_eq!(counters.hashed_files, big);
+        assert_eq!(counters.cached_files, big);
+        assert_eq!(counters.excluded_files, 0);
+        assert!(counters.directory.is_empty());
+    }
+
+    #[test]
+    fn test_maintenance_info_deserialization() {
+        let json = serde_json::json!({
+            "params": {
+                "owner": "alice@laptop",
+                "quick": { "enabled": true, "interval": 3600000000000i64 },
+                "full": { "enabled": true, "interval": 86400000000000i64 },
+                "logRetention": { "maxCount": 10000 }
+            },
+            "schedule": {
+                "nextFullMaintenance": "2024-01-02T00:00:00Z",
+                "nextQuickMaintenance": "2024-01-01T01:00:00Z",
+                "runs": {
+                    "snapshot-gc": [
+                        { "start": "2024-01-01T00:00:00Z", "end": "2024-01-01T00:01:00Z", "success": true }
+                    ]
+                }
+            }
+        });
+
+        let info: MaintenanceInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(info.params.owner, "alice@laptop");
+        assert!(info.params.quick.enabled);
+        assert_eq!(info.params.full.interval, 86_400_000_000_000);
+        assert_eq!(
+            info.schedule.next_quick_maintenance.as_deref(),
+            Some("2024-01-01T01:00:00Z")
+        );
+        assert!(info.schedule.runs["snapshot-gc"][0].success);
+
+        // Empty output (e.g. maintenance never configured) uses defaults
+        let empty: MaintenanceInfo = serde_json::from_str("{}").unwrap();
+        assert!(empty.params.owner.is_empty());
+        assert!(empty.schedule.runs.is_empty());
+    }
 }
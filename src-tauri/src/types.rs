@@ -32,7 +32,7 @@ where
 // Core Types
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SourceInfo {
     pub user_name: String,
@@ -40,6 +40,13 @@ pub struct SourceInfo {
     pub path: String,
 }
 
+/// Formats as `user@host:path`, like Kopia's `SourceInfo.String()`
+impl std::fmt::Display for SourceInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}:{}", self.user_name, self.host, self.path)
+    }
+}
+
 // ============================================================================
 // Repository Types
 // ============================================================================
@@ -122,6 +129,16 @@ pub struct AlgorithmOption {
     pub deprecated: Option<bool>,
 }
 
+/// Splitter option annotated for the repository creation UI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SplitterInfo {
+    pub id: String,
+    /// True for the server's default splitter
+    pub recommended: bool,
+    pub deprecated: bool,
+    pub description: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageConfig {
     #[serde(rename = "type")]
@@ -225,6 +242,23 @@ pub struct SourcesResponse {
     pub sources: Vec<SnapshotSource>,
 }
 
+/// Default `user@host` identity for new sources, as reported by the server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceIdentity {
+    pub user_name: String,
+    pub host: String,
+}
+
+impl From<&SourcesResponse> for SourceIdentity {
+    fn from(sources: &SourcesResponse) -> Self {
+        Self {
+            user_name: sources.local_username.clone(),
+            host: sources.local_host.clone(),
+        }
+    }
+}
+
 // SourceStatus matches serverapi.SourceStatus
 // Official API field: `json:"schedule"` maps to SchedulingPolicy
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -246,19 +280,19 @@ pub struct SnapshotSource {
 
 // UploadCounters matches upload.Counters from official Kopia
 // See: snapshot/upload/upload_progress.go:169-201
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct UploadCounters {
     pub cached_bytes: i64,
     pub hashed_bytes: i64,
     pub uploaded_bytes: i64,
     pub estimated_bytes: i64,
-    pub cached_files: i32,
-    pub hashed_files: i32,
-    pub excluded_files: i32,
-    pub excluded_dirs: i32,
-    pub errors: i32,
-    pub ignored_errors: i32,
+    pub cached_files: i64,
+    pub hashed_files: i64,
+    pub excluded_files: i64,
+    pub excluded_dirs: i64,
+    pub errors: i64,
+    pub ignored_errors: i64,
     pub estimated_files: i64,
     pub directory: String,
     pub last_error_path: String,
@@ -267,19 +301,19 @@ pub struct UploadCounters {
 
 // SnapshotStats matches snapshot.Stats from official Kopia
 // See: snapshot/stats.go:10-37
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct SnapshotStats {
     pub total_size: i64,          // JSON: "totalSize"
     pub excluded_total_size: i64, // JSON: "excludedTotalSize"
-    pub file_count: i32,          // JSON: "fileCount"
-    pub cached_files: i32,        // JSON: "cachedFiles"
-    pub non_cached_files: i32,    // JSON: "nonCachedFiles"
-    pub dir_count: i32,           // JSON: "dirCount"
-    pub excluded_file_count: i32, // JSON: "excludedFileCount"
-    pub excluded_dir_count: i32,  // JSON: "excludedDirCount"
-    pub ignored_error_count: i32, // JSON: "ignoredErrorCount"
-    pub error_count: i32,         // JSON: "errorCount"
+    pub file_count: i64,          // JSON: "fileCount"
+    pub cached_files: i64,        // JSON: "cachedFiles"
+    pub non_cached_files: i64,    // JSON: "nonCachedFiles"
+    pub dir_count: i64,           // JSON: "dirCount"
+    pub excluded_file_count: i64, // JSON: "excludedFileCount"
+    pub excluded_dir_count: i64,  // JSON: "excludedDirCount"
+    pub ignored_error_count: i64, // JSON: "ignoredErrorCount"
+    pub error_count: i64,         // JSON: "errorCount"
 }
 
 // StorageStats matches snapshot.StorageStats
@@ -293,17 +327,53 @@ pub struct StorageStats {
 
 // StorageUsageDetails matches snapshot.StorageUsageDetails
 // See: snapshot/manifest.go:194-219
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct StorageUsageDetails {
     pub object_bytes: i64,
     pub original_content_bytes: i64,
     pub packed_content_bytes: i64,
     #[serde(rename = "fileObjects")]
-    pub file_object_count: i32,
+    pub file_object_count: i64,
     #[serde(rename = "dirObjects")]
-    pub dir_object_count: i32,
-    pub contents: i32,
+    pub dir_object_count: i64,
+    pub contents: i64,
+}
+
+/// Content-level counters of a snapshot (see `snapshot_content_stats`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentStats {
+    pub contents: i64,
+    pub packed_bytes: i64,
+    pub original_bytes: i64,
+    pub file_objects: i64,
+    pub dir_objects: i64,
+}
+
+impl From<&StorageUsageDetails> for ContentStats {
+    fn from(details: &StorageUsageDetails) -> Self {
+        Self {
+            contents: details.contents,
+            packed_bytes: details.packed_content_bytes,
+            original_bytes: details.original_content_bytes,
+            file_objects: details.file_object_count,
+            dir_objects: details.dir_object_count,
+        }
+    }
+}
+
+/// Storage statistics recorded with a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotContentStats {
+    pub snapshot_id: String,
+    /// False for snapshots made by Kopia versions that didn't record storage stats
+    pub stats_available: bool,
+    /// Data this snapshot added to the repository
+    pub new_data: Option<ContentStats>,
+    /// Totals for everything the snapshot references
+    pub running_total: Option<ContentStats>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -372,6 +442,29 @@ pub struct SnapshotEditRequest {
     pub remove_pins: Option<Vec<String>>,
 }
 
+/// Snapshot that didn't finish (see `incomplete_snapshots`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncompleteSnapshot {
+    pub id: String,
+    /// Start time (RFC 3339)
+    pub time: String,
+    /// Why the snapshot is incomplete, e.g. "canceled"
+    pub reason: String,
+}
+
+/// Outcome of applying a retention policy (see `retention_preview`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionPreview {
+    /// IDs of snapshots that would be kept, newest first
+    pub kept: Vec<String>,
+    /// IDs of snapshots that would be deleted, newest first
+    pub pruned: Vec<String>,
+    /// Why each kept snapshot is retained, e.g. ["latest-1", "daily-1"]
+    pub reasons: HashMap<String, Vec<String>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SnapshotDeleteRequest {
@@ -397,11 +490,53 @@ pub struct MultipleSourceActionResponse {
     pub sources: std::collections::HashMap<String, SourceActionResponse>,
 }
 
+/// Repository content totals (see `content_stats`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryContentStats {
+    pub count: u64,
+    /// Bytes stored after compression
+    pub total_packed_size: u64,
+    /// Bytes of content before compression
+    pub total_original_size: u64,
+    /// `total_original_size / total_packed_size`
+    pub dedup_ratio: f64,
+}
+
+/// Content index entry as printed by `kopia content list --json`
+/// See: repo/content/index/info.go
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ContentIndexEntry {
+    #[serde(rename = "contentID")]
+    pub content_id: String,
+    /// Pack blob holding the content
+    pub pack_file: String,
+    pub pack_offset: u64,
+    /// Bytes stored in the pack, after compression and encryption
+    pub length: u64,
+    pub original_length: u64,
+    pub deleted: bool,
+}
+
+/// Content stored in a pack blob (see `blob_contents`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlobContent {
+    pub content_id: String,
+    /// Bytes stored in the pack
+    pub length: u64,
+    /// Byte offset within the pack
+    pub offset: u64,
+    /// Bytes before compression
+    pub original_length: u64,
+}
+
 /// ThrottleLimits for repository operations (detailed configuration)
 /// Used by GET/PUT /api/v1/repo/throttle endpoints for fine-grained control.
 /// This is more detailed than ThrottlingLimits (status-only bandwidth limits).
 /// See: repo/blob/throttling/throttler.go (Limits)
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ThrottleLimits {
     /// Read operations per second limit
@@ -427,6 +562,82 @@ pub struct ThrottleLimits {
     pub concurrent_writes: Option<i32>,
 }
 
+/// Sources without snapshots found by `sources_prune`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcesPruneResult {
+    /// Sources without any snapshot
+    pub candidates: Vec<SourceInfo>,
+    /// Candidates whose source and policy were deleted
+    pub removed: Vec<SourceInfo>,
+    /// Candidates that couldn't be deleted (as `user@host:path`)
+    pub failed: Vec<String>,
+}
+
+/// Snapshot summary for a single source (see `sources_summary`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceSummary {
+    pub source: SourceInfo,
+    pub snapshot_count: usize,
+    pub last_snapshot_time: Option<String>,
+    pub last_snapshot_size: Option<i64>,
+}
+
+/// Snapshot summaries across all sources
+///
+/// `partial` is set when some sources couldn't be queried; those are listed in
+/// `timedOutSources` or `failedSources` (as `user@host:path`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourcesSummary {
+    pub sources: Vec<SourceSummary>,
+    pub partial: bool,
+    pub timed_out_sources: Vec<String>,
+    pub failed_sources: Vec<String>,
+}
+
+/// A scheduled snapshot that didn't run (see `missed_snapshots`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissedSnapshot {
+    pub source: SourceInfo,
+    /// When the snapshot was due (RFC 3339)
+    pub scheduled_time: String,
+    pub missed_by_secs: i64,
+}
+
+/// Time since a source's last snapshot (see `sources_by_staleness`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceStaleness {
+    pub source: SourceInfo,
+    /// When the last snapshot finished (RFC 3339), None if never backed up
+    pub last_snapshot_time: Option<String>,
+    pub age_secs: Option<u64>,
+}
+
+/// Snapshots of every source (see `snapshots_list_all`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllSnapshots {
+    /// Snapshots by source, keyed `user@host:path`
+    pub snapshots: HashMap<String, SnapshotsResponse>,
+    pub partial: bool,
+    pub timed_out_sources: Vec<String>,
+    pub failed_sources: Vec<String>,
+}
+
+/// Sources for which `run_missed_now` started a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunMissedResult {
+    pub triggered: Vec<SourceInfo>,
+    pub partial: bool,
+    pub timed_out_sources: Vec<String>,
+    pub failed_sources: Vec<String>,
+}
+
 // ============================================================================
 // Directory & File Browsing Types
 // ============================================================================
@@ -469,8 +680,8 @@ pub struct DirectorySummary {
     pub symlinks: Option<i64>,            // TotalSymlinkCount
     pub max_time: Option<String>,         // MaxModTime (RFC3339Nano format)
     pub incomplete: Option<String>,       // IncompleteReason - empty if complete
-    pub num_failed: Option<i32>,          // FatalErrorCount
-    pub num_ignored_errors: Option<i32>,  // IgnoredErrorCount
+    pub num_failed: Option<i64>,          // FatalErrorCount
+    pub num_ignored_errors: Option<i64>,  // IgnoredErrorCount
     pub errors: Option<Vec<FailedEntry>>, // FailedEntries
 }
 
@@ -482,6 +693,29 @@ pub struct FailedEntry {
     pub error: String,
 }
 
+/// A distinct version of a file found in a source's snapshots (see `file_versions`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    /// Oldest snapshot containing this version
+    pub snapshot_id: String,
+    pub snapshot_time: String,
+    /// Object ID to pass to `object_download`
+    pub object_id: String,
+    pub size: Option<i64>,
+}
+
+/// Versions of a file (see `file_versions`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersions {
+    pub versions: Vec<FileVersion>,
+    pub partial: bool,
+    /// Snapshots that weren't searched in time, by ID
+    pub timed_out_snapshots: Vec<String>,
+    pub failed_snapshots: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RootEntry {
@@ -489,6 +723,111 @@ pub struct RootEntry {
     pub summ: Option<DirectorySummary>,
 }
 
+/// Options of a snapshot verification (matches `kopia snapshot verify` flags)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyRequest {
+    /// Stop after this many errors (0 = don't stop)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_errors: Option<u32>,
+    /// Share of files whose contents are read back in full, 0-100
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verify_files_percent: Option<f64>,
+    /// Verify only this directory object
+    #[serde(rename = "directoryID", skip_serializing_if = "Option::is_none")]
+    pub directory_id: Option<String>,
+    /// Verify only this file object
+    #[serde(rename = "fileID", skip_serializing_if = "Option::is_none")]
+    pub file_id: Option<String>,
+}
+
+/// Outcome of `snapshot_create_and_verify`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotVerification {
+    pub snapshot_id: String,
+    pub verified: bool,
+    /// Problems reported by the verification (empty if verified)
+    pub errors: Vec<String>,
+}
+
+/// File selected for `restore_files`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreFileItem {
+    pub object_id: String,
+    /// Path under the target folder, e.g. `docs/report.pdf`
+    pub relative_path: String,
+}
+
+/// Outcome of restoring one file with `restore_files`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreFileResult {
+    pub relative_path: String,
+    pub success: bool,
+    /// Bytes written (0 if the file failed)
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Payload of the `download-progress` event
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+    pub object_id: String,
+    pub bytes_written: u64,
+    /// From the response's Content-Length, if the server sent one
+    pub total_bytes: Option<u64>,
+}
+
+/// Payload of the `restore-files-progress` event
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreFilesProgress {
+    pub repo_id: String,
+    pub total_files: usize,
+    /// Files finished so far, successfully or not
+    pub completed_files: usize,
+    pub failed_files: usize,
+    pub bytes_written: u64,
+}
+
+/// Version of the bundled Kopia binary (see `server_version`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KopiaVersion {
+    /// Version without a leading "v", e.g. "0.21.1"
+    pub version: String,
+    /// Version as reported by the binary
+    pub build_version: String,
+    /// Commit the binary was built from, if known
+    pub build_info: String,
+    /// GitHub repository the build came from, e.g. "kopia/kopia"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_repo: Option<String>,
+}
+
+/// Progress of a restore task (see `restore_status`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreStatus {
+    /// Task status: RUNNING, CANCELING, SUCCESS, FAILED or CANCELED
+    pub status: String,
+    pub restored_files: i64,
+    pub restored_directories: i64,
+    pub restored_bytes: i64,
+    /// Files left alone because they already existed
+    pub skipped_files: i64,
+    pub skipped_bytes: i64,
+    pub ignored_errors: i64,
+    /// True once the task is no longer running
+    pub finished: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RestoreRequest {
@@ -543,6 +882,30 @@ pub struct RestoreOptions {
     pub min_size_for_placeholder: Option<i64>,
 }
 
+/// Result of `snapshot_tree_export`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TreeExportSummary {
+    /// Number of entries written
+    pub entries: u64,
+    /// True when the depth or entry limit left part of the tree out
+    pub truncated: bool,
+}
+
+/// Result of comparing restored local files against a snapshot
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreVerification {
+    /// Number of files whose size (and content, if compared) matched
+    pub matched: u64,
+    /// Relative paths of files that differ from the snapshot
+    pub mismatched: Vec<String>,
+    /// Relative paths present in the snapshot but missing locally
+    pub missing: Vec<String>,
+    /// True when the depth or file limit stopped the walk early
+    pub truncated: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct MountResponse {
@@ -580,7 +943,7 @@ pub struct PolicyWithTarget {
     pub policy: PolicyDefinition,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PolicyTarget {
     pub user_name: Option<String>,
@@ -588,6 +951,80 @@ pub struct PolicyTarget {
     pub path: Option<String>,
 }
 
+impl From<&SourceInfo> for PolicyTarget {
+    fn from(source: &SourceInfo) -> Self {
+        Self {
+            user_name: Some(source.user_name.clone()),
+            host: Some(source.host.clone()),
+            path: Some(source.path.clone()),
+        }
+    }
+}
+
+/// Formats like Kopia's `SourceInfo.String()`: `(global)`, `@host`, `user@host`
+/// or `user@host:path`
+impl std::fmt::Display for PolicyTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let host = self.host.as_deref().unwrap_or_default();
+        let user_name = self.user_name.as_deref().unwrap_or_default();
+
+        match self.path.as_deref() {
+            _ if host.is_empty() => write!(f, "(global)"),
+            Some(path) if !path.is_empty() => write!(f, "{}@{}:{}", user_name, host, path),
+            _ => write!(f, "{}@{}", user_name, host),
+        }
+    }
+}
+
+/// Ignore rules that apply to a target once inheritance is resolved
+/// (see `resolve_effective_ignores`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveIgnores {
+    /// Ignore patterns, most general policy first
+    pub explicit: Vec<String>,
+    /// Dot-ignore file names (e.g. `.kopiaignore`)
+    pub dot_ignore_files: Vec<String>,
+    /// Policies that contributed at least one rule, most general first
+    pub inherited_from: Vec<PolicyTarget>,
+    /// Where each rule was defined
+    pub origins: Vec<IgnoreRuleOrigin>,
+}
+
+/// One effective policy field compared with the target's own policy
+/// (see `policy_overrides`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyFieldOverride {
+    /// Dotted JSON path, e.g. `retention.keepDaily`
+    pub field: String,
+    /// Value set on the target itself, if any
+    pub defined_value: Option<serde_json::Value>,
+    pub effective_value: Option<serde_json::Value>,
+    /// True if the effective value comes from a parent policy or the defaults
+    pub inherited: bool,
+}
+
+/// Policy that defined one ignore rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnoreRuleOrigin {
+    pub rule: String,
+    /// True for a dot-ignore file name, false for an ignore pattern
+    pub dot_ignore_file: bool,
+    pub target: PolicyTarget,
+}
+
+/// Outcome of deleting one policy in `policies_delete_batch`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyDeleteResult {
+    pub target: PolicyTarget,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PolicyDefinition {
@@ -668,7 +1105,7 @@ pub struct TimeOfDay {
 /// FilesPolicy matches policy.FilesPolicy from official Kopia
 /// See: snapshot/policy/files_policy.go:3-16
 /// IMPORTANT: JSON field is "ignoreDotFiles" (NOT camelCase "dotIgnoreFiles")
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FilesPolicy {
     pub ignore: Option<Vec<String>>,
@@ -738,7 +1175,7 @@ pub struct ErrorHandlingPolicy {
     pub ignore_unknown_types: Option<bool>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UploadPolicy {
     pub max_parallel_snapshots: Option<i64>,
@@ -836,6 +1273,16 @@ pub struct TasksResponse {
     pub tasks: Vec<Task>,
 }
 
+/// Latest sources and tasks fetched from a server (see `last_known_state`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LastKnownState {
+    pub sources: Vec<SnapshotSource>,
+    pub tasks: Vec<Task>,
+    /// When either list was last fetched (RFC 3339), empty if never
+    pub received_at: String,
+}
+
 // Task matches uitask.Info
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -880,6 +1327,51 @@ pub struct CounterValue {
     pub level: String,
 }
 
+/// Entry in the recent activity feed, built from finished tasks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all_fields = "camelCase")]
+pub enum ActivityItem {
+    /// Snapshot task that finished (outcome: "success" or "canceled")
+    SnapshotCompleted {
+        task_id: String,
+        time: String,
+        source: String,
+        outcome: String,
+    },
+    /// Restore task that finished (outcome: "success" or "canceled")
+    RestoreCompleted {
+        task_id: String,
+        time: String,
+        target: String,
+        outcome: String,
+    },
+    /// Maintenance task that finished (outcome: "success" or "canceled")
+    MaintenanceRun {
+        task_id: String,
+        time: String,
+        description: String,
+        outcome: String,
+    },
+    /// Any task that failed
+    Error {
+        task_id: String,
+        time: String,
+        kind: String,
+        description: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+    },
+}
+
+/// Task counter flagged with a "warning" or "error" level
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskCounterWarning {
+    pub name: String,
+    pub value: i64,
+    pub level: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase", default)]
 pub struct TasksSummary {
@@ -889,10 +1381,224 @@ pub struct TasksSummary {
     pub canceled: i64,
 }
 
+/// Operations currently in progress (see `has_active_operations`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveOperations {
+    pub active: bool,
+    /// Number of running tasks
+    pub count: usize,
+    /// Distinct kinds of the running tasks (e.g. "Snapshot", "Maintenance")
+    pub kinds: Vec<String>,
+    /// Whether some servers couldn't be checked
+    pub partial: bool,
+    /// Repositories whose server didn't answer or failed
+    pub failed_repo_ids: Vec<String>,
+}
+
+impl ActiveOperations {
+    /// Whether operations may be in progress
+    ///
+    /// Servers that couldn't be checked count as busy, so the user is asked
+    /// before interrupting them.
+    pub fn may_be_active(&self) -> bool {
+        self.active || self.partial
+    }
+}
+
+/// Result of `kopia_server_stop_safe`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SafeStopResult {
+    pub stopped: bool,
+    /// Why the server was left running
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refused_reason: Option<String>,
+    /// Tasks canceled before stopping (with `force`)
+    pub canceled_tasks: usize,
+}
+
+/// Combined progress of all running tasks (see `overall_progress`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverallProgress {
+    pub active_tasks: usize,
+    /// Completion of the tasks that report an estimate, 0-100
+    pub percent: f64,
+    /// Bytes processed by all running tasks
+    pub bytes_done: i64,
+    /// Estimated bytes of the tasks that report an estimate
+    pub bytes_total: i64,
+}
+
+/// Upload progress of a single source (see `source_progress`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadProgress {
+    /// Source status (IDLE, UPLOADING, PAUSED, ...)
+    pub status: String,
+    /// Hashed plus cached bytes
+    pub bytes_done: i64,
+    pub estimated_bytes: i64,
+    /// 0-100, absent until the server has an estimate
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent_complete: Option<f64>,
+    pub hashed_files: i64,
+    pub cached_files: i64,
+    pub estimated_files: i64,
+    pub errors: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current_directory: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput_bytes_per_sec: Option<f64>,
+}
+
+// ============================================================================
+// Maintenance Types (matches `kopia maintenance info --json`)
+// See: cli/command_maintenance_info.go, repo/maintenance/maintenance_params.go
+// ============================================================================
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceInfo {
+    pub params: MaintenanceParams,
+    pub schedule: MaintenanceSchedule,
+}
+
+// MaintenanceParams matches maintenance.Params
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceParams {
+    /// Maintenance owner as `user@host`
+    pub owner: String,
+    pub quick: MaintenanceCycleParams,
+    pub full: MaintenanceCycleParams,
+    /// Extend object lock retention during full maintenance
+    pub extend_object_locks: bool,
+    /// Parallelism of blob listing (0 = Kopia's default)
+    pub list_parallelism: i64,
+}
+
+// CycleParams matches maintenance.CycleParams
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceCycleParams {
+    pub enabled: bool,
+    /// Interval in nanoseconds (Go time.Duration)
+    pub interval: i64,
+}
+
+// Schedule matches maintenance.Schedule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceSchedule {
+    pub next_full_maintenance: Option<String>,
+    pub next_quick_maintenance: Option<String>,
+    /// Recent runs keyed by task name (e.g. "snapshot-gc", "cleanup-logs")
+    pub runs: HashMap<String, Vec<MaintenanceRunInfo>>,
+}
+
+// RunInfo matches maintenance.RunInfo
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceRunInfo {
+    pub start: String,
+    pub end: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Maintenance owner and whether it's this client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceOwner {
+    pub owner: String,
+    pub is_self: bool,
+}
+
+/// Outcome of `maintenance_run_if_due`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceRunIfDueResult {
+    pub ran: bool,
+    /// Why maintenance wasn't run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// At-a-glance repository health (see `repository_health`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryHealth {
+    /// 100 when no issues were found, lowered by each issue's penalty
+    pub score: u8,
+    pub issues: Vec<HealthIssue>,
+}
+
+/// A problem lowering the health score, with what to do about it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthIssue {
+    pub kind: HealthIssueKind,
+    pub message: String,
+    pub suggested_action: String,
+    /// Points taken off the score
+    pub penalty: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HealthIssueKind {
+    MaintenanceDisabled,
+    MaintenanceOverdue,
+    MaintenanceFailed,
+    FailedTasks,
+    SnapshotErrors,
+    NoSnapshots,
+}
+
 // ============================================================================
 // Utility Types
 // ============================================================================
 
+/// Client configuration in effect: compiled defaults merged with the settings
+/// (see `get_effective_config`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfig {
+    /// Readiness checks after starting a server, and the delay between them
+    pub health_check_retries: u32,
+    pub health_check_interval_ms: u64,
+    /// Heartbeat of running servers (see `reconnect::run_server_monitor`)
+    pub heartbeat_interval_secs: u64,
+    pub heartbeat_timeout_secs: u64,
+    pub heartbeat_failure_threshold: u32,
+    pub http_operation_timeout_secs: u64,
+    pub http_connect_timeout_secs: u64,
+    /// Longest wait for the next chunk of a download (no total timeout)
+    pub download_idle_timeout_secs: u64,
+    pub cli_timeout_secs: u64,
+    /// Per-request timeout of aggregate commands (`fanOutTimeoutSecs` or default)
+    pub fan_out_timeout_secs: u64,
+    pub restore_files_concurrency: usize,
+    /// Attempts and initial backoff while the repository is busy
+    pub busy_retry_attempts: u32,
+    pub busy_retry_initial_delay_ms: u64,
+    pub auto_reconnect: bool,
+    pub reconnect_initial_backoff_secs: u64,
+    pub reconnect_max_backoff_secs: u64,
+    pub wait_for_tasks_on_quit: bool,
+    pub quit_wait_secs: u64,
+    pub insecure_skip_cert_verify: bool,
+    pub server_check_for_updates: bool,
+    pub developer_mode: bool,
+    pub log_level: String,
+    /// Repository throttle limits (None if the server couldn't be asked)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throttle: Option<ThrottleLimits>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EstimateRequest {
@@ -909,6 +1615,19 @@ pub struct EstimateResponse {
     pub id: String, // Task ID to poll for results
 }
 
+/// Totals of a finished estimate (see `estimate_snapshot_blocking`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EstimateResult {
+    pub total_bytes: i64,
+    pub file_count: i64,
+    pub directory_count: i64,
+    /// Excluded files and directories
+    pub excluded_count: i64,
+    pub excluded_bytes: i64,
+    pub error_count: i64,
+}
+
 // ============================================================================
 // Notification Types
 // ============================================================================
@@ -921,6 +1640,33 @@ pub struct NotificationProfile {
     pub min_severity: i32, // -100 (Verbose), -10 (Success), 0 (Report), 10 (Warning), 20 (Error)
 }
 
+/// Notification printed by the server with `--kopiaui-notifications`
+/// See: notification/sender/sender.go (Message)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KopiaNotification {
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub body: String,
+    /// Same scale as `NotificationProfile.min_severity`
+    #[serde(default)]
+    pub severity: i32,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// When the app received the notification (RFC 3339)
+    #[serde(default)]
+    pub received_at: String,
+}
+
+/// A notification severity with its display label (see `notification_severity_levels`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeverityLevel {
+    pub value: i32,
+    pub label: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct NotificationMethod {
@@ -928,3 +1674,37 @@ pub struct NotificationMethod {
     pub method_type: String, // "email", "pushover", "webhook"
     pub config: serde_json::Value, // Method-specific configuration
 }
+
+/// Notification profiles exported for use on another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationProfilesExport {
+    pub version: u32,
+    pub profiles: Vec<ExportedNotificationProfile>,
+}
+
+/// One exported profile, with secrets possibly removed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedNotificationProfile {
+    #[serde(flatten)]
+    pub profile: NotificationProfile,
+    /// Whether secrets were removed from the method config
+    #[serde(default)]
+    pub redacted: bool,
+    /// Method config keys whose values were removed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redacted_fields: Vec<String>,
+}
+
+/// Outcome of importing one notification profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationProfileImportResult {
+    pub profile: String,
+    pub imported: bool,
+    /// Secrets that must be re-entered before the profile works
+    pub missing_secrets: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
@@ -61,6 +61,24 @@ export async function removeRepository(repoId: string): Promise<void> {
   return invoke('remove_repository', { repoId });
 }
 
+/**
+ * Import an existing Kopia CLI config and start a server for it
+ * @param configPath - Path to the existing `.config` file
+ */
+export async function adoptExistingConfig(configPath: string): Promise<RepositoryEntry> {
+  return invoke('adopt_existing_config', { configPath });
+}
+
+/**
+ * Connect to a Kopia server the app didn't start (not saved across restarts)
+ * @param info - Server URL, credentials and certificate fingerprint
+ */
+export async function connectExternalServer(
+  info: import('./types').ExternalServer
+): Promise<RepositoryEntry> {
+  return invoke('connect_external_server', { info });
+}
+
 // ============================================================================
 // Kopia Server Lifecycle
 // ============================================================================
@@ -81,6 +99,18 @@ export async function stopKopiaServer(repoId: string): Promise<void> {
   return invoke('kopia_server_stop', { repoId });
 }
 
+/**
+ * Stop a repository's server unless a snapshot or restore is running
+ * @param repoId - Repository identifier
+ * @param force - Cancel running tasks and operations first, then stop
+ */
+export async function stopKopiaServerSafely(
+  repoId: string,
+  force: boolean
+): Promise<import('./types').SafeStopResult> {
+  return invoke('kopia_server_stop_safe', { repoId, force });
+}
+
 /**
  * Get Kopia server status for a repository
  * @param repoId - Repository identifier
@@ -89,6 +119,61 @@ export async function getKopiaServerStatus(repoId: string): Promise<KopiaServerS
   return invoke('kopia_server_status', { repoId });
 }
 
+/**
+ * Restart the Kopia server process for a repository
+ *
+ * Progress is reported through the `server-start-phase` event.
+ * @param repoId - Repository identifier
+ * @param options - Optional start flags, remembered for later restarts
+ */
+export async function restartKopiaServer(
+  repoId: string,
+  options?: import('./types').ServerStartOptions
+): Promise<KopiaServerInfo> {
+  return invoke('kopia_server_restart', { repoId, options });
+}
+
+/**
+ * Get app and server uptime
+ * @param repoId - Repository identifier
+ */
+export async function getUptimeInfo(repoId: string): Promise<import('./types').UptimeInfo> {
+  return invoke('uptime_info', { repoId });
+}
+
+/**
+ * Flush the server's in-memory repository state to storage
+ *
+ * Fails with CONTROL_API_UNAVAILABLE on servers without a control API.
+ * @param repoId - Repository identifier
+ */
+export async function flushKopiaServer(repoId: string): Promise<void> {
+  return invoke('server_flush', { repoId });
+}
+
+/**
+ * Check whether the repository's server handles an API endpoint
+ *
+ * Results are cached until the server restarts.
+ * @param repoId - Repository identifier
+ * @param path - API path, e.g. `/api/v1/repo/throttle`
+ * @param method - HTTP method (default: GET)
+ */
+export async function serverSupportsEndpoint(
+  repoId: string,
+  path: string,
+  method?: string
+): Promise<boolean> {
+  return invoke('server_supports_endpoint', { repoId, path, method });
+}
+
+/**
+ * Get the version of the bundled Kopia binary (cached for the app's lifetime)
+ */
+export async function getKopiaVersion(): Promise<import('./types').KopiaVersion> {
+  return invoke('server_version');
+}
+
 // ============================================================================
 // Repository Management
 // ============================================================================
@@ -121,6 +206,148 @@ export async function disconnectRepository(repoId: string): Promise<void> {
   return invoke('repository_disconnect', { repoId });
 }
 
+/**
+ * Set how long the repository format blob is cached
+ *
+ * Saved to the repository config; the server applies it the next time it opens
+ * the repository.
+ * @param repoId - Repository identifier
+ * @param seconds - Cache duration in seconds
+ */
+export async function setFormatCacheDuration(
+  repoId: string,
+  seconds: number
+): Promise<RepositoryStatus> {
+  return invoke('repository_set_format_cache_duration', { repoId, seconds });
+}
+
+/**
+ * Rename a repository (updates its description and display name)
+ * @param repoId - Repository identifier
+ * @param newName - New display name
+ */
+export async function renameRepository(repoId: string, newName: string): Promise<RepositoryEntry> {
+  return invoke('repository_rename', { repoId, newName });
+}
+
+/**
+ * Assess repository health (score from 0 to 100 with issues and suggested actions)
+ * @param repoId - Repository identifier
+ */
+export async function getRepositoryHealth(
+  repoId: string
+): Promise<import('./types').RepositoryHealth> {
+  return invoke('repository_health', { repoId });
+}
+
+/**
+ * Enable or disable permissive cache loading (cache entries that fail to load
+ * are re-fetched from storage instead of failing)
+ *
+ * Saved to the repository config; the server applies it the next time it opens
+ * the repository.
+ * @param repoId - Repository identifier
+ */
+export async function setPermissiveCache(
+  repoId: string,
+  enabled: boolean
+): Promise<RepositoryStatus> {
+  return invoke('repository_set_permissive_cache', { repoId, enabled });
+}
+
+/**
+ * Open a filesystem repository's storage folder in the file manager
+ * @param repoId - Repository identifier
+ * @returns The opened path
+ */
+export async function openRepositoryStorage(repoId: string): Promise<string> {
+  return invoke('open_repository_storage', { repoId });
+}
+
+/**
+ * List the server's splitters with descriptions and the recommended choice
+ * @param repoId - Repository identifier
+ */
+export async function getSplitterAlgorithms(
+  repoId: string
+): Promise<import('./types').SplitterInfo[]> {
+  return invoke('splitter_algorithms_with_info', { repoId });
+}
+
+/**
+ * Sync repository metadata with storage (picks up snapshots and policies
+ * written by other clients)
+ * @param repoId - Repository identifier
+ */
+export async function syncRepository(repoId: string): Promise<void> {
+  return invoke('repository_sync', { repoId });
+}
+
+/**
+ * Get throttling limits for repository operations
+ * @param repoId - Repository identifier
+ */
+export async function getThrottleLimits(repoId: string): Promise<import('./types').ThrottleLimits> {
+  return invoke('repository_get_throttle', { repoId });
+}
+
+/**
+ * Set throttling limits for repository operations
+ * @param repoId - Repository identifier
+ */
+export async function setThrottleLimits(
+  repoId: string,
+  limits: import('./types').ThrottleLimits
+): Promise<void> {
+  return invoke('repository_set_throttle', { repoId, limits });
+}
+
+/**
+ * Get repository content statistics (runs `kopia content stats`)
+ * @param repoId - Repository identifier
+ */
+export async function getContentStats(
+  repoId: string
+): Promise<import('./types').RepositoryContentStats> {
+  return invoke('content_stats', { repoId });
+}
+
+/**
+ * Get the repository's maximum pack size in bytes
+ * @param repoId - Repository identifier
+ * @returns null when the server doesn't report it
+ */
+export async function getMaxPackSize(repoId: string): Promise<number | null> {
+  return invoke('repository_get_max_pack_size', { repoId });
+}
+
+/**
+ * Change the repository's maximum pack size
+ *
+ * Applies to packs written after servers re-read the repository format;
+ * existing packs are not rewritten.
+ * @param repoId - Repository identifier
+ * @param bytes - Whole number of MiB between 10 and 120, in bytes
+ */
+export async function setMaxPackSize(repoId: string, bytes: number): Promise<RepositoryStatus> {
+  return invoke('repository_set_max_pack_size', { repoId, bytes });
+}
+
+/**
+ * List the contents stored in a pack blob, ordered by offset (requires developer mode)
+ *
+ * Reads the whole index, which can take a while on large repositories. An
+ * unknown or non-pack blob returns an empty list.
+ * @param repoId - Repository identifier
+ * @param blobId - Pack blob ID
+ */
+export async function getBlobContents(
+  repoId: string,
+  blobId: string
+): Promise<import('./types').BlobContent[]> {
+  return invoke('blob_contents', { repoId, blobId });
+}
+
 // ============================================================================
 // System Utilities
 // ============================================================================
@@ -193,6 +420,102 @@ export async function updateRepositoryDescription(
   return invoke('repository_update_description', { repoId, description });
 }
 
+/**
+ * Get per-command call counts, latency percentiles and error counts
+ */
+export async function getCommandMetrics(): Promise<
+  Record<string, import('./types').CommandMetrics>
+> {
+  return invoke('get_command_metrics');
+}
+
+/**
+ * Verify the Kopia binary against the SHA-256 embedded at build time
+ */
+export async function verifyBinaryIntegrity(): Promise<import('./types').BinaryIntegrity> {
+  return invoke('verify_binary_integrity');
+}
+
+/**
+ * Check whether Windows Volume Shadow Copy snapshots can be used by Kopia
+ * (always unavailable on other platforms)
+ */
+export async function getVssStatus(): Promise<import('./types').VssStatus> {
+  return invoke('vss_status');
+}
+
+/**
+ * Stop all servers and quit, optionally waiting for running tasks first
+ *
+ * While waiting, progress is reported through the `quit-waiting` event.
+ * @param wait - Let running tasks finish before canceling them
+ * @param timeoutSecs - Maximum seconds to wait
+ */
+export async function quitGracefully(wait: boolean, timeoutSecs: number): Promise<void> {
+  return invoke('quit_gracefully', { wait, timeoutSecs });
+}
+
+/**
+ * Get the disk usage of the app's config directory, split into config, cache and logs
+ */
+export async function getAppStorageUsage(): Promise<import('./types').AppStorageUsage> {
+  return invoke('app_storage_usage');
+}
+
+/**
+ * Check that the Kopia binary is built for this platform
+ */
+export async function checkBinaryCompatibility(): Promise<import('./types').BinaryCompatibility> {
+  return invoke('check_binary_compatibility');
+}
+
+/**
+ * Change the log level until the app exits
+ * @param level - "error", "warn", "info", "debug" or "trace"
+ * @returns The level now in effect
+ */
+export async function setLogLevel(
+  level: import('./types').LogLevel
+): Promise<import('./types').LogLevel> {
+  return invoke('set_log_level', { level });
+}
+
+/**
+ * Get the current log level
+ */
+export async function getLogLevel(): Promise<import('./types').LogLevel> {
+  return invoke('get_log_level');
+}
+
+/**
+ * Get the configuration the app is running with, for diagnostics
+ * @param repoId - Repository identifier (throttle limits are read from its server)
+ */
+export async function getEffectiveConfig(
+  repoId: string
+): Promise<import('./types').EffectiveConfig> {
+  return invoke('get_effective_config', { repoId });
+}
+
+/**
+ * Hash local files to show how much of a directory is duplicated
+ *
+ * Whole files are compared, so this is a lower bound of what Kopia
+ * deduplicates. Symlinks aren't followed.
+ * @param path - Directory to hash
+ * @param algorithm - Hash algorithm (default: sha256)
+ * @param maxFiles - Stop after this many files (default: 10,000)
+ * @param timeLimitSeconds - Stop after this long (default: 30)
+ */
+export async function hashDirectory(
+  path: string,
+  algorithm?: 'sha256' | 'sha512',
+  maxFiles?: number,
+  timeLimitSeconds?: number
+): Promise<import('./types').DirectoryHashSummary> {
+  return invoke('hash_directory', { path, algorithm, maxFiles, timeLimitSeconds });
+}
+
 // ============================================================================
 // Snapshot Sources
 // ============================================================================
@@ -256,124 +579,521 @@ export async function estimateSnapshot(
   });
 }
 
-// ============================================================================
-// Snapshot History
-// ============================================================================
-
 /**
- * List snapshots for a source
+ * Estimate snapshot size and wait for the result
  * @param repoId - Repository identifier
+ * @param path - Path to estimate
+ * @param timeoutSeconds - Cancel the estimate and fail with TIMEOUT after this long
+ * @param maxExamplesPerBucket - Max examples per bucket (default: 10)
  */
-export async function listSnapshots(
+export async function estimateSnapshotBlocking(
   repoId: string,
-  userName: string,
-  host: string,
   path: string,
-  all = false
-): Promise<import('./types').SnapshotsResponse> {
-  return invoke('snapshots_list', { repoId, userName, host, path, all });
+  timeoutSeconds: number,
+  maxExamplesPerBucket?: number
+): Promise<import('./types').EstimateResult> {
+  return invoke('estimate_snapshot_blocking', {
+    repoId,
+    path,
+    timeoutSeconds,
+    maxExamplesPerBucket: maxExamplesPerBucket || 10,
+  });
 }
 
 /**
- * Edit snapshot metadata (pins, description)
+ * Pause scheduled snapshots for every source
  * @param repoId - Repository identifier
  */
-export async function editSnapshot(
-  repoId: string,
-  request: import('./types').SnapshotEditRequest
-): Promise<void> {
-  return invoke('snapshot_edit', { repoId, request });
+export async function pauseAllSources(
+  repoId: string
+): Promise<import('./types').MultipleSourceActionResponse> {
+  return invoke('sources_pause_all', { repoId });
 }
 
 /**
- * Delete snapshots (requires source information)
+ * Resume scheduled snapshots for every source
  * @param repoId - Repository identifier
  */
-export async function deleteSnapshots(
-  repoId: string,
-  userName: string,
-  host: string,
-  path: string,
-  manifestIDs: string[]
-): Promise<number> {
-  return invoke('snapshot_delete', {
-    repoId,
-    userName,
-    host,
-    path,
-    manifestIds: manifestIDs,
-  });
+export async function resumeAllSources(
+  repoId: string
+): Promise<import('./types').MultipleSourceActionResponse> {
+  return invoke('sources_resume_all', { repoId });
 }
 
-// ============================================================================
-// Snapshot Browsing & Restore
-// ============================================================================
-
 /**
- * Browse directory contents in a snapshot
+ * Summarize snapshots of all sources
+ *
+ * Sources that don't answer in time are listed in `timedOutSources` and the
+ * result is marked `partial`.
  * @param repoId - Repository identifier
+ * @param timeoutSecs - Per-source timeout (defaults to the `fanOutTimeoutSecs` setting)
  */
-export async function browseObject(
+export async function getSourcesSummary(
   repoId: string,
-  objectId: string
-): Promise<import('./types').DirectoryObject> {
-  return invoke('object_browse', { repoId, objectId });
+  timeoutSecs?: number
+): Promise<import('./types').SourcesSummary> {
+  return invoke('sources_summary', { repoId, timeoutSecs });
 }
 
 /**
- * Download a single file from a snapshot
+ * Get upload progress of a single source, with throughput sampled over half a second
  * @param repoId - Repository identifier
  */
-export async function downloadObject(
+export async function getSourceProgress(
   repoId: string,
-  objectId: string,
-  filename: string,
-  targetPath: string
-): Promise<void> {
-  return invoke('object_download', { repoId, objectId, filename, targetPath });
+  userName: string,
+  host: string,
+  path: string
+): Promise<import('./types').UploadProgress> {
+  return invoke('source_progress', { repoId, userName, host, path });
 }
 
 /**
- * Start a restore operation
+ * List sources whose scheduled snapshot was missed (e.g. while asleep)
  * @param repoId - Repository identifier
  */
-export async function restoreStart(
-  repoId: string,
-  request: import('./types').RestoreRequest
-): Promise<string> {
-  return invoke('restore_start', { repoId, request });
+export async function getMissedSnapshots(
+  repoId: string
+): Promise<import('./types').MissedSnapshot[]> {
+  return invoke('missed_snapshots', { repoId });
 }
 
 /**
- * Mount a snapshot as a local filesystem
+ * Start snapshots for all sources with a missed scheduled snapshot
  * @param repoId - Repository identifier
  */
-export async function mountSnapshot(repoId: string, root: string): Promise<string> {
-  return invoke('mount_snapshot', { repoId, root });
+export async function runMissedNow(repoId: string): Promise<import('./types').RunMissedResult> {
+  return invoke('run_missed_now', { repoId });
 }
 
 /**
- * List all mounted snapshots
+ * Get the default `user@host` identity for new sources
  * @param repoId - Repository identifier
  */
-export async function listMounts(repoId: string): Promise<import('./types').MountsResponse> {
-  return invoke('mounts_list', { repoId });
+export async function getDefaultSourceIdentity(
+  repoId: string
+): Promise<import('./types').SourceIdentity> {
+  return invoke('get_default_source_identity', { repoId });
 }
 
 /**
- * Unmount a snapshot
+ * Start a one-off snapshot with a temporary policy override
+ *
+ * Fields set in the override are layered over the source's policy for this
+ * snapshot only; the stored policy is put back once the upload has started.
  * @param repoId - Repository identifier
+ * @param path - Path to snapshot
+ * @param policyOverride - Policy to use for this snapshot only
  */
-export async function unmountSnapshot(repoId: string, objectId: string): Promise<void> {
-  return invoke('mount_unmount', { repoId, objectId });
+export async function createSnapshotWithOverride(
+  repoId: string,
+  path: string,
+  policyOverride: import('./types').PolicyDefinition
+): Promise<import('./types').SourceInfo> {
+  return invoke('snapshot_create_with_override', { repoId, path, policyOverride });
 }
 
-// ============================================================================
-// Policies
-// ============================================================================
-
 /**
- * List all policies
+ * Snapshot a path, wait for it to finish, then verify the new snapshot
+ *
+ * Problems found by the verification are returned in `errors` rather than
+ * failing the call.
+ * @param repoId - Repository identifier
+ * @param path - Path to snapshot
+ * @param verifyFilesPercent - Percentage of file contents to read back (default: none)
+ */
+export async function createAndVerifySnapshot(
+  repoId: string,
+  path: string,
+  verifyFilesPercent?: number
+): Promise<import('./types').SnapshotVerification> {
+  return invoke('snapshot_create_and_verify', { repoId, path, verifyFilesPercent });
+}
+
+/**
+ * Find sources without snapshots and optionally delete them with their policies
+ * @param repoId - Repository identifier
+ * @param removeWithoutSnapshots - Delete the sources found (otherwise only list them)
+ */
+export async function pruneSources(
+  repoId: string,
+  removeWithoutSnapshots: boolean
+): Promise<import('./types').SourcesPruneResult> {
+  return invoke('sources_prune', { repoId, removeWithoutSnapshots });
+}
+
+/**
+ * Get the sources and tasks last fetched from a repository's server, so a view
+ * can render right away instead of waiting for the next poll
+ * @param repoId - Repository identifier
+ */
+export async function getLastKnownState(repoId: string): Promise<import('./types').LastKnownState> {
+  return invoke('last_known_state', { repoId });
+}
+
+/**
+ * List sources by how long ago they were last backed up, stalest first
+ * @param repoId - Repository identifier
+ */
+export async function getSourcesByStaleness(
+  repoId: string
+): Promise<import('./types').SourceStaleness[]> {
+  return invoke('sources_by_staleness', { repoId });
+}
+
+// ============================================================================
+// Snapshot History
+// ============================================================================
+
+/**
+ * List snapshots for a source
+ * @param repoId - Repository identifier
+ */
+export async function listSnapshots(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  all = false
+): Promise<import('./types').SnapshotsResponse> {
+  return invoke('snapshots_list', { repoId, userName, host, path, all });
+}
+
+/**
+ * List snapshots of every source in the repository
+ *
+ * Sources that don't answer in time are listed in `timedOutSources` and the
+ * others are returned with `partial` set.
+ * @param repoId - Repository identifier
+ * @param timeoutSecs - Per-source timeout (default: the `fanOutTimeoutSecs` setting)
+ */
+export async function listAllSnapshots(
+  repoId: string,
+  all = false,
+  timeoutSecs?: number
+): Promise<import('./types').AllSnapshots> {
+  return invoke('snapshots_list_all', { repoId, all, timeoutSecs });
+}
+
+/**
+ * Edit snapshot metadata (pins, description)
+ * @param repoId - Repository identifier
+ */
+export async function editSnapshot(
+  repoId: string,
+  request: import('./types').SnapshotEditRequest
+): Promise<void> {
+  return invoke('snapshot_edit', { repoId, request });
+}
+
+/**
+ * Delete snapshots (requires source information)
+ * @param repoId - Repository identifier
+ */
+export async function deleteSnapshots(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  manifestIDs: string[]
+): Promise<number> {
+  return invoke('snapshot_delete', {
+    repoId,
+    userName,
+    host,
+    path,
+    manifestIds: manifestIDs,
+  });
+}
+
+/**
+ * Export a source's snapshot history as CSV
+ * @param repoId - Repository identifier
+ * @param targetPath - CSV file to write
+ * @returns Number of rows written
+ */
+export async function exportSnapshotsCsv(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  targetPath: string
+): Promise<number> {
+  return invoke('snapshots_export_csv', { repoId, userName, host, path, targetPath });
+}
+
+/**
+ * Get content-level storage statistics recorded with a snapshot
+ * @param repoId - Repository identifier
+ * @param manifestId - Snapshot manifest ID
+ */
+export async function getSnapshotContentStats(
+  repoId: string,
+  manifestId: string
+): Promise<import('./types').SnapshotContentStats> {
+  return invoke('snapshot_content_stats', { repoId, manifestId });
+}
+
+/**
+ * List a source's incomplete snapshots (e.g. canceled ones), oldest first
+ * @param repoId - Repository identifier
+ */
+export async function listIncompleteSnapshots(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string
+): Promise<import('./types').IncompleteSnapshot[]> {
+  return invoke('incomplete_snapshots', { repoId, userName, host, path });
+}
+
+/**
+ * Delete incomplete snapshots of a source
+ *
+ * IDs that don't belong to an incomplete snapshot of the source are rejected.
+ * @param repoId - Repository identifier
+ * @param snapshotIds - IDs returned by `listIncompleteSnapshots`
+ * @returns Number of snapshots deleted
+ */
+export async function cleanupIncompleteSnapshots(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  snapshotIds: string[]
+): Promise<number> {
+  return invoke('cleanup_incomplete_snapshots', { repoId, userName, host, path, snapshotIds });
+}
+
+/**
+ * Verify the snapshots of a source with `kopia snapshot verify`
+ *
+ * Returns the CLI output once done; found errors fail the command.
+ * @param repoId - Repository identifier
+ * @param verifyFilesPercent - Share of files to read back (0-100)
+ * @param maxErrors - Stop after this many errors
+ */
+export async function verifySnapshots(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  verifyFilesPercent?: number,
+  maxErrors?: number
+): Promise<string> {
+  return invoke('snapshot_verify', {
+    repoId,
+    userName,
+    host,
+    path,
+    verifyFilesPercent,
+    maxErrors,
+  });
+}
+
+// ============================================================================
+// Snapshot Browsing & Restore
+// ============================================================================
+
+/**
+ * Browse directory contents in a snapshot
+ * @param repoId - Repository identifier
+ */
+export async function browseObject(
+  repoId: string,
+  objectId: string
+): Promise<import('./types').DirectoryObject> {
+  return invoke('object_browse', { repoId, objectId });
+}
+
+/**
+ * Download a single file from a snapshot
+ *
+ * Progress is reported through the `download-progress` event.
+ * @param repoId - Repository identifier
+ */
+export async function downloadObject(
+  repoId: string,
+  objectId: string,
+  filename: string,
+  targetPath: string
+): Promise<void> {
+  return invoke('object_download', { repoId, objectId, filename, targetPath });
+}
+
+/**
+ * Start a restore operation
+ * @param repoId - Repository identifier
+ */
+export async function restoreStart(
+  repoId: string,
+  request: import('./types').RestoreRequest
+): Promise<string> {
+  return invoke('restore_start', { repoId, request });
+}
+
+/**
+ * Get the progress of a restore started with `restoreStart`
+ * @param repoId - Repository identifier
+ * @param taskId - Task ID returned by `restoreStart`
+ */
+export async function getRestoreStatus(
+  repoId: string,
+  taskId: string
+): Promise<import('./types').RestoreStatus> {
+  return invoke('restore_status', { repoId, taskId });
+}
+
+/**
+ * Mount a snapshot as a local filesystem
+ * @param repoId - Repository identifier
+ */
+export async function mountSnapshot(repoId: string, root: string): Promise<string> {
+  return invoke('mount_snapshot', { repoId, root });
+}
+
+/**
+ * List all mounted snapshots
+ * @param repoId - Repository identifier
+ */
+export async function listMounts(repoId: string): Promise<import('./types').MountsResponse> {
+  return invoke('mounts_list', { repoId });
+}
+
+/**
+ * Unmount a snapshot
+ * @param repoId - Repository identifier
+ */
+export async function unmountSnapshot(repoId: string, objectId: string): Promise<void> {
+  return invoke('mount_unmount', { repoId, objectId });
+}
+
+/**
+ * Find Kopia mount directories that no running server owns
+ * (still mounted after their server died, or empty leftovers)
+ */
+export async function findOrphanedMounts(): Promise<string[]> {
+  return invoke('find_orphaned_mounts');
+}
+
+/**
+ * Force-unmount an orphaned mount and remove its directory
+ * @param path - A path returned by `findOrphanedMounts`
+ */
+export async function cleanupOrphanedMount(path: string): Promise<void> {
+  return invoke('cleanup_orphaned_mount', { path });
+}
+
+/**
+ * Compare restored local files against a snapshot directory
+ * @param repoId - Repository identifier
+ * @param rootId - Object ID of the snapshot directory
+ * @param localPath - Local directory the snapshot was restored to
+ * @param compareContents - Compare file contents, not just sizes
+ */
+export async function verifyRestore(
+  repoId: string,
+  rootId: string,
+  localPath: string,
+  compareContents?: boolean,
+  maxDepth?: number,
+  maxFiles?: number
+): Promise<import('./types').RestoreVerification> {
+  return invoke('verify_restore', {
+    repoId,
+    rootId,
+    localPath,
+    compareContents,
+    maxDepth,
+    maxFiles,
+  });
+}
+
+/**
+ * Browse a directory below a snapshot root by its relative path
+ * (fails with PATH_NOT_FOUND if a component doesn't exist)
+ * @param repoId - Repository identifier
+ * @param rootId - Object ID of the snapshot root
+ * @param relativePath - Path below the root, e.g. `home/user/Documents`
+ */
+export async function browsePath(
+  repoId: string,
+  rootId: string,
+  relativePath: string
+): Promise<import('./types').DirectoryObject> {
+  return invoke('browse_path', { repoId, rootId, relativePath });
+}
+
+/**
+ * List the distinct versions of a file across a source's snapshots
+ *
+ * Each version can be fetched with `downloadObject`.
+ * @param repoId - Repository identifier
+ * @param relativeFilePath - File path relative to the source root
+ */
+export async function getFileVersions(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  relativeFilePath: string
+): Promise<import('./types').FileVersions> {
+  return invoke('file_versions', { repoId, userName, host, path, relativeFilePath });
+}
+
+/**
+ * Check which restore options take effect on the target's filesystem
+ * (e.g. sparse files are ignored on FAT)
+ * @param targetPath - Restore target (need not exist yet)
+ */
+export async function validateRestoreOptions(
+  targetPath: string,
+  options: import('./types').FilesystemOutput
+): Promise<import('./types').RestoreOptionSupport[]> {
+  return invoke('validate_restore_options', { targetPath, options });
+}
+
+/**
+ * Restore selected files into a folder
+ *
+ * Progress is reported through the `restore-files-progress` event. A failed
+ * file doesn't stop the others; results are returned in the order given.
+ * @param repoId - Repository identifier
+ * @param items - Files to restore, with their paths under `targetDir`
+ * @param targetDir - Folder to restore into
+ */
+export async function restoreFiles(
+  repoId: string,
+  items: import('./types').RestoreFileItem[],
+  targetDir: string
+): Promise<import('./types').RestoreFileResult[]> {
+  return invoke('restore_files', { repoId, items, targetDir });
+}
+
+/**
+ * Export a snapshot's file tree as a JSON document
+ * @param repoId - Repository identifier
+ * @param rootId - Object ID of the directory to start from
+ * @param targetPath - File to write the document to
+ * @param maxDepth - Deepest directory level to expand (default: 64)
+ * @param maxEntries - Maximum number of entries to write (default: 1,000,000)
+ */
+export async function exportSnapshotTree(
+  repoId: string,
+  rootId: string,
+  targetPath: string,
+  maxDepth?: number,
+  maxEntries?: number
+): Promise<import('./types').TreeExportSummary> {
+  return invoke('snapshot_tree_export', { repoId, rootId, maxDepth, maxEntries, targetPath });
+}
+
+// ============================================================================
+// Policies
+// ============================================================================
+
+/**
+ * List all policies
  * @param repoId - Repository identifier
  */
 export async function listPolicies(repoId: string): Promise<import('./types').PoliciesResponse> {
@@ -381,60 +1101,219 @@ export async function listPolicies(repoId: string): Promise<import('./types').Po
 }
 
 /**
- * Get policy for a specific target
+ * Get policy for a specific target
+ *
+ * Returns the full PolicyResponse including target and policy.
+ * Use response.policy to access the PolicyDefinition.
+ * @param repoId - Repository identifier
+ */
+export async function getPolicy(
+  repoId: string,
+  userName?: string,
+  host?: string,
+  path?: string
+): Promise<import('./types').PolicyResponse> {
+  return invoke('policy_get', { repoId, userName, host, path });
+}
+
+/**
+ * Resolve effective policy with inheritance
+ * @param repoId - Repository identifier
+ */
+export async function resolvePolicy(
+  repoId: string,
+  userName?: string,
+  host?: string,
+  path?: string,
+  updates?: import('./types').PolicyDefinition
+): Promise<import('./types').ResolvedPolicyResponse> {
+  return invoke('policy_resolve', { repoId, userName, host, path, updates });
+}
+
+/**
+ * Set/update policy
+ * @param repoId - Repository identifier
+ */
+export async function setPolicy(
+  repoId: string,
+  policy: import('./types').PolicyDefinition,
+  userName?: string,
+  host?: string,
+  path?: string
+): Promise<void> {
+  return invoke('policy_set', { repoId, userName, host, path, policy });
+}
+
+/**
+ * Delete policy (revert to inherited)
+ * @param repoId - Repository identifier
+ */
+export async function deletePolicy(
+  repoId: string,
+  userName?: string,
+  host?: string,
+  path?: string
+): Promise<void> {
+  return invoke('policy_delete', { repoId, userName, host, path });
+}
+
+/**
+ * Enable or disable the "one file system" boundary for a policy target
+ *
+ * Returns the resolved policy after the change.
+ * @param repoId - Repository identifier
+ */
+export async function setSourceOneFilesystem(
+  repoId: string,
+  enabled: boolean,
+  userName?: string,
+  host?: string,
+  path?: string
+): Promise<import('./types').ResolvedPolicyResponse> {
+  return invoke('source_set_one_filesystem', { repoId, userName, host, path, enabled });
+}
+
+/**
+ * Get whether the "one file system" boundary is in effect for a target
+ * @param repoId - Repository identifier
+ */
+export async function getSourceOneFilesystem(
+  repoId: string,
+  userName?: string,
+  host?: string,
+  path?: string
+): Promise<boolean> {
+  return invoke('source_get_one_filesystem', { repoId, userName, host, path });
+}
+
+/**
+ * Test ignore rules against sample paths (relative to the source root,
+ * directories marked with a trailing `/`)
+ */
+export async function testGlobs(
+  patterns: string[],
+  samplePaths: string[]
+): Promise<import('./types').GlobMatch[]> {
+  return invoke('test_globs', { patterns, samplePaths });
+}
+
+/**
+ * Delete several policies (revert to inherited) concurrently
  *
- * Returns the full PolicyResponse including target and policy.
- * Use response.policy to access the PolicyDefinition.
+ * Failures don't stop the batch; each target gets its own result.
  * @param repoId - Repository identifier
  */
-export async function getPolicy(
+export async function deletePolicies(
   repoId: string,
+  targets: import('./types').PolicyTarget[]
+): Promise<import('./types').PolicyDeleteResult[]> {
+  return invoke('policies_delete_batch', { repoId, targets });
+}
+
+/**
+ * Set the upload parallelism for a policy target
+ *
+ * Fields left empty are inherited from the parent policy.
+ * Returns the resolved policy after the change.
+ * @param repoId - Repository identifier
+ */
+export async function setSourceParallelism(
+  repoId: string,
+  upload: import('./types').UploadPolicy,
   userName?: string,
   host?: string,
   path?: string
-): Promise<import('./types').PolicyResponse> {
-  return invoke('policy_get', { repoId, userName, host, path });
+): Promise<import('./types').ResolvedPolicyResponse> {
+  return invoke('source_set_parallelism', { repoId, userName, host, path, upload });
 }
 
 /**
- * Resolve effective policy with inheritance
+ * Get the upload parallelism effective for a policy target
  * @param repoId - Repository identifier
  */
-export async function resolvePolicy(
+export async function getSourceParallelism(
   repoId: string,
   userName?: string,
   host?: string,
-  path?: string,
-  updates?: import('./types').PolicyDefinition
+  path?: string
+): Promise<import('./types').UploadPolicy> {
+  return invoke('source_get_parallelism', { repoId, userName, host, path });
+}
+
+/**
+ * Validate a cron expression (as used by `scheduling.cron`) and describe when it runs
+ */
+export async function describeCron(expr: string): Promise<import('./types').CronDescription> {
+  return invoke('describe_cron', { expr });
+}
+
+/**
+ * Apply a recommended starting policy (retention, compression and ignore rules)
+ * to a policy target, keeping its other settings
+ *
+ * Returns the resolved policy after the change.
+ * @param repoId - Repository identifier
+ * @param profile - "documents", "photos", "code" or "general"
+ */
+export async function applyRecommendedPolicy(
+  repoId: string,
+  profile: import('./types').RecommendedPolicyProfile,
+  userName?: string,
+  host?: string,
+  path?: string
 ): Promise<import('./types').ResolvedPolicyResponse> {
-  return invoke('policy_resolve', { repoId, userName, host, path, updates });
+  return invoke('apply_recommended_policy', { repoId, userName, host, path, profile });
 }
 
 /**
- * Set/update policy
+ * Preview the recommended policy for a profile
+ * @param profile - "documents", "photos", "code" or "general"
+ */
+export async function getRecommendedPolicy(
+  profile: import('./types').RecommendedPolicyProfile
+): Promise<import('./types').PolicyDefinition> {
+  return invoke('recommended_policy', { profile });
+}
+
+/**
+ * Resolve the ignore rules that apply to a target across policy inheritance
  * @param repoId - Repository identifier
  */
-export async function setPolicy(
+export async function resolveEffectiveIgnores(
   repoId: string,
-  policy: import('./types').PolicyDefinition,
   userName?: string,
   host?: string,
   path?: string
-): Promise<void> {
-  return invoke('policy_set', { repoId, userName, host, path, policy });
+): Promise<import('./types').EffectiveIgnores> {
+  return invoke('resolve_effective_ignores', { repoId, userName, host, path });
 }
 
 /**
- * Delete policy (revert to inherited)
+ * Compare a target's own policy with its effective policy, field by field
  * @param repoId - Repository identifier
  */
-export async function deletePolicy(
+export async function getPolicyOverrides(
   repoId: string,
   userName?: string,
   host?: string,
   path?: string
-): Promise<void> {
-  return invoke('policy_delete', { repoId, userName, host, path });
+): Promise<import('./types').PolicyFieldOverride[]> {
+  return invoke('policy_overrides', { repoId, userName, host, path });
+}
+
+/**
+ * Preview which snapshots of a source a retention policy would keep
+ * (read-only; pinned snapshots are always kept)
+ * @param repoId - Repository identifier
+ */
+export async function previewRetention(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  retention: import('./types').RetentionPolicy
+): Promise<import('./types').RetentionPreview> {
+  return invoke('retention_preview', { repoId, userName, host, path, retention });
 }
 
 // ============================================================================
@@ -476,6 +1355,205 @@ export async function getTasksSummary(repoId: string): Promise<import('./types')
   return invoke('tasks_summary', { repoId });
 }
 
+/**
+ * Check whether a failed task should raise a notification
+ * (false for snapshots of sources with notifications turned off)
+ * @param repoId - Repository identifier
+ */
+export async function taskNotifyOnFailure(
+  repoId: string,
+  task: import('./types').Task
+): Promise<boolean> {
+  return invoke('task_notify_on_failure', { repoId, task });
+}
+
+/**
+ * List paths excluded by ignore rules during a finished snapshot task
+ * @param repoId - Repository identifier
+ * @param taskId - Snapshot task identifier
+ */
+export async function getSnapshotExcludedPaths(repoId: string, taskId: string): Promise<string[]> {
+  return invoke('snapshot_excluded_paths', { repoId, taskId });
+}
+
+/**
+ * Get task summary grouped by task kind (e.g. "Snapshot", "Maintenance")
+ * @param repoId - Repository identifier
+ */
+export async function getTasksSummaryByKind(
+  repoId: string
+): Promise<Record<string, import('./types').TasksSummary>> {
+  return invoke('tasks_summary_by_kind', { repoId });
+}
+
+/**
+ * Find running tasks that appear to be stuck (no progress between two samples)
+ * @param repoId - Repository identifier
+ * @param olderThanSecs - Minimum seconds the task has been running
+ */
+export async function findStuckTasks(
+  repoId: string,
+  olderThanSecs: number
+): Promise<import('./types').Task[]> {
+  return invoke('tasks_find_stuck', { repoId, olderThanSecs });
+}
+
+/**
+ * Get task counters flagged as warnings or errors (errors first)
+ * @param repoId - Repository identifier
+ * @param taskId - Task identifier
+ */
+export async function getTaskWarnings(
+  repoId: string,
+  taskId: string
+): Promise<import('./types').TaskCounterWarning[]> {
+  return invoke('task_warnings', { repoId, taskId });
+}
+
+/**
+ * Get recent finished tasks and failures as one feed, newest first
+ * @param repoId - Repository identifier
+ * @param limit - Maximum number of items
+ */
+export async function getActivityFeed(
+  repoId: string,
+  limit: number
+): Promise<import('./types').ActivityItem[]> {
+  return invoke('activity_feed', { repoId, limit });
+}
+
+/**
+ * Check whether any operation is in progress
+ * @param repoId - Repository identifier (all running servers if omitted)
+ */
+export async function hasActiveOperations(
+  repoId?: string
+): Promise<import('./types').ActiveOperations> {
+  return invoke('has_active_operations', { repoId });
+}
+
+/**
+ * Limit the upload speed of a running task
+ *
+ * Lowers the repository-wide upload limit until the task finishes, so other
+ * uploads to the same repository slow down too.
+ * @param repoId - Repository identifier
+ * @param taskId - Task identifier
+ * @returns The applied limit in bytes per second
+ */
+export async function throttleTask(
+  repoId: string,
+  taskId: string,
+  bytesPerSecond: number
+): Promise<number> {
+  return invoke('task_throttle', { repoId, taskId, bytesPerSecond });
+}
+
+/**
+ * Get the combined progress of all running tasks for a single progress bar
+ * @param repoId - Repository identifier
+ */
+export async function getOverallProgress(
+  repoId: string
+): Promise<import('./types').OverallProgress> {
+  return invoke('overall_progress', { repoId });
+}
+
+/**
+ * Hide finished tasks from `listTasks` (running tasks are never hidden)
+ * @param repoId - Repository identifier
+ * @param olderThanSecs - Only hide tasks that finished at least this long ago (default: all)
+ * @returns Number of listed tasks that were hidden
+ */
+export async function clearCompletedTasks(repoId: string, olderThanSecs?: number): Promise<number> {
+  return invoke('tasks_clear_completed', { repoId, olderThanSecs });
+}
+
+/**
+ * Get the upload throughput of recently completed snapshots across all
+ * repositories, newest first
+ * @param limit - Maximum number of samples
+ */
+export async function getUploadThroughputHistory(
+  limit: number
+): Promise<import('./types').ThroughputSample[]> {
+  return invoke('upload_throughput_history', { limit });
+}
+
+// ============================================================================
+// Maintenance
+// ============================================================================
+
+/**
+ * Get the maintenance parameters and schedule
+ * @param repoId - Repository identifier
+ */
+export async function getMaintenanceInfo(
+  repoId: string
+): Promise<import('./types').MaintenanceInfo> {
+  return invoke('maintenance_info', { repoId });
+}
+
+/**
+ * Run quick (or full) maintenance now; returns once it has finished
+ * @param repoId - Repository identifier
+ * @param full - Run full instead of quick maintenance
+ */
+export async function runMaintenance(repoId: string, full: boolean): Promise<void> {
+  return invoke('maintenance_run', { repoId, full });
+}
+
+/**
+ * Get the repository's maintenance owner
+ * @param repoId - Repository identifier
+ */
+export async function getMaintenanceOwner(
+  repoId: string
+): Promise<import('./types').MaintenanceOwner> {
+  return invoke('maintenance_owner', { repoId });
+}
+
+/**
+ * Make this client the repository's maintenance owner
+ * @param repoId - Repository identifier
+ */
+export async function claimMaintenance(
+  repoId: string
+): Promise<import('./types').MaintenanceOwner> {
+  return invoke('maintenance_claim', { repoId });
+}
+
+/**
+ * Run maintenance if this client owns it and it's due (returns once finished)
+ * @param repoId - Repository identifier
+ * @param full - Run full instead of quick maintenance
+ */
+export async function runMaintenanceIfDue(
+  repoId: string,
+  full: boolean
+): Promise<import('./types').MaintenanceRunIfDueResult> {
+  return invoke('maintenance_run_if_due', { repoId, full });
+}
+
+/**
+ * Get the next scheduled maintenance time (RFC 3339)
+ *
+ * Null when maintenance is disabled or owned by another client.
+ * @param repoId - Repository identifier
+ */
+export async function getMaintenanceNextTime(repoId: string): Promise<string | null> {
+  return invoke('maintenance_next_time', { repoId });
+}
+
+/**
+ * Cancel running maintenance
+ * @param repoId - Repository identifier
+ * @returns IDs of the canceled operations and tasks (empty if none was running)
+ */
+export async function cancelMaintenance(repoId: string): Promise<string[]> {
+  return invoke('maintenance_cancel', { repoId });
+}
+
 // ============================================================================
 // Notifications
 // ============================================================================
@@ -522,3 +1600,180 @@ export async function testNotificationProfile(
 ): Promise<void> {
   return invoke('notification_profile_test', { repoId, profile });
 }
+
+/**
+ * Export all notification profiles as JSON (secrets removed)
+ * @param repoId - Repository identifier
+ */
+export async function exportNotificationProfiles(repoId: string): Promise<string> {
+  return invoke('notification_profiles_export', { repoId });
+}
+
+/**
+ * Import notification profiles exported with `exportNotificationProfiles`
+ *
+ * Profiles with the same name are replaced. The result lists secrets that must
+ * be re-entered.
+ * @param repoId - Repository identifier
+ * @param json - Exported profiles
+ * @param includeSecrets - Keep secrets present in the JSON
+ */
+export async function importNotificationProfiles(
+  repoId: string,
+  json: string,
+  includeSecrets: boolean
+): Promise<import('./types').NotificationProfileImportResult[]> {
+  return invoke('notification_profiles_import', { repoId, json, includeSecrets });
+}
+
+/**
+ * Choose whether failed snapshots of one source raise a notification
+ * @param repoId - Repository identifier
+ * @param enabled - Notify about failed snapshots of this source
+ */
+export async function setSourceNotifyOnError(
+  repoId: string,
+  userName: string,
+  host: string,
+  path: string,
+  enabled: boolean
+): Promise<import('./types').AppSettings> {
+  return invoke('source_set_notify_on_error', { repoId, userName, host, path, enabled });
+}
+
+/**
+ * List the severities a notification profile can use as its minimum
+ */
+export async function getNotificationSeverityLevels(): Promise<import('./types').SeverityLevel[]> {
+  return invoke('notification_severity_levels');
+}
+
+/**
+ * Get recent error notifications reported by a repository's server, newest first
+ * @param repoId - Repository identifier
+ * @param limit - Maximum number of notifications
+ */
+export async function getErrorNotifications(
+  repoId: string,
+  limit: number
+): Promise<import('./types').KopiaNotification[]> {
+  return invoke('error_notifications', { repoId, limit });
+}
+
+// ============================================================================
+// App Settings
+// ============================================================================
+
+/**
+ * Get the application settings
+ */
+export async function getAppSettings(): Promise<import('./types').AppSettings> {
+  return invoke('get_app_settings');
+}
+
+/**
+ * Enable or disable pausing snapshots while on battery or a metered connection
+ */
+export async function setPowerAwareScheduling(
+  enabled: boolean
+): Promise<import('./types').AppSettings> {
+  return invoke('power_aware_scheduling', { enabled });
+}
+
+/**
+ * Get the detected power and network state
+ */
+export async function getPowerState(): Promise<import('./types').PowerState> {
+  return invoke('get_power_state');
+}
+
+/**
+ * Enable or disable skipping TLS certificate verification (debugging only;
+ * applies the next time servers start)
+ */
+export async function setInsecureSkipCertVerify(
+  enabled: boolean
+): Promise<import('./types').AppSettings> {
+  return invoke('set_insecure_skip_cert_verify', { enabled });
+}
+
+/**
+ * Set the per-request timeout for aggregate commands (undefined = default)
+ */
+export async function setFanOutTimeout(
+  timeoutSecs?: number
+): Promise<import('./types').AppSettings> {
+  return invoke('set_fan_out_timeout', { timeoutSecs });
+}
+
+/**
+ * Let running tasks finish before quitting from the tray or closing the window
+ */
+export async function setWaitForTasksOnQuit(
+  enabled: boolean
+): Promise<import('./types').AppSettings> {
+  return invoke('set_wait_for_tasks_on_quit', { enabled });
+}
+
+/**
+ * Enable or disable reconnecting repositories once their storage is reachable
+ * again (reported through the `repository-reconnected` event)
+ */
+export async function setAutoReconnect(enabled: boolean): Promise<import('./types').AppSettings> {
+  return invoke('set_auto_reconnect', { enabled });
+}
+
+/**
+ * Remember the repository and screen the user has open
+ * @param repoId - Repository identifier
+ * @param view - Frontend route of the open screen
+ */
+export async function saveUiState(
+  repoId: string,
+  view: string
+): Promise<import('./types').AppSettings> {
+  return invoke('save_ui_state', { repoId, view });
+}
+
+/**
+ * Get the repository and screen to restore on startup
+ */
+export async function getUiState(): Promise<import('./types').UiState> {
+  return invoke('get_ui_state');
+}
+
+/**
+ * Get whether servers check for Kopia updates and any version they found
+ */
+export async function getServerUpdateCheck(): Promise<import('./types').ServerUpdateCheck> {
+  return invoke('get_server_update_check');
+}
+
+/**
+ * Enable or disable the Kopia server's update check (applies to servers
+ * started afterwards)
+ */
+export async function setServerCheckForUpdates(
+  enabled: boolean
+): Promise<import('./types').AppSettings> {
+  return invoke('set_server_check_for_updates', { enabled });
+}
+
+/**
+ * Move the repository configs, caches and logs to another directory
+ *
+ * Running servers are stopped and restarted from the new location.
+ * @param newPath - Destination directory
+ * @returns The new config directory
+ */
+export async function relocateConfigDir(newPath: string): Promise<string> {
+  return invoke('relocate_config_dir', { newPath });
+}
+
+/**
+ * Enable or disable developer mode, which unlocks low-level repository
+ * diagnostics such as `getBlobContents`
+ */
+export async function setDeveloperMode(enabled: boolean): Promise<import('./types').AppSettings> {
+  return invoke('set_developer_mode', { enabled });
+}
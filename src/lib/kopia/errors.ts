@@ -28,6 +28,21 @@ function snakeToReadable(str: string): string {
     .join(' ');
 }
 
+/**
+ * Collect the string and number fields of error details for interpolation
+ * (e.g. `{{message}}` in `errors.kopia.invalidInput`)
+ */
+function interpolationValues(details: unknown): Record<string, string | number> {
+  if (typeof details !== 'object' || details === null) {
+    return {};
+  }
+  return Object.fromEntries(
+    Object.entries(details).filter(
+      ([, value]) => typeof value === 'string' || typeof value === 'number'
+    )
+  ) as Record<string, string | number>;
+}
+
 /**
  * Official Kopia API error codes
  * These match the official Kopia server API specification
@@ -51,7 +66,7 @@ export enum OfficialKopiaAPIErrorCode {
 /**
  * Extended error codes for Kopia Desktop
  *
- * Includes official API codes (12) plus essential application-specific codes (5)
+ * Includes official API codes (12) plus essential application-specific codes
  * that drive different UI behaviors.
  *
  * IMPORTANT: Keep this synchronized with src-tauri/src/error.rs
@@ -68,6 +83,16 @@ export enum KopiaErrorCode {
   HTTP_REQUEST_FAILED = 'HTTP_REQUEST_FAILED', // Policy load fallback
   RESPONSE_PARSE_ERROR = 'RESPONSE_PARSE_ERROR', // Policy load fallback
   NOT_FOUND = 'NOT_FOUND', // Policy load fallback
+  INVALID_INPUT = 'INVALID_INPUT', // Rejected before calling Kopia
+  REPOSITORY_UPGRADE_REQUIRED = 'REPOSITORY_UPGRADE_REQUIRED', // Prompt upgrade or Kopia update
+  REPOSITORY_LOCKED = 'REPOSITORY_LOCKED', // Another client is using the repository
+  REPOSITORY_BUSY = 'REPOSITORY_BUSY', // Repository stayed busy (e.g. flushing) after retries
+  PATH_NOT_FOUND = 'PATH_NOT_FOUND', // Missing directory in snapshot deep link
+  CONFIG_LOCKED = 'CONFIG_LOCKED', // Another Kopia process holds the config lock
+  BINARY_INCOMPATIBLE = 'BINARY_INCOMPATIBLE', // Kopia binary built for another platform
+  NOTIFICATION_TEST_FAILED = 'NOTIFICATION_TEST_FAILED', // SMTP connect/auth/send stage of a failed test
+  TIMEOUT = 'TIMEOUT', // Waiting for a server-side operation took too long
+  CONTROL_API_UNAVAILABLE = 'CONTROL_API_UNAVAILABLE', // Hide server control actions
 
   // ============================================================================
   // Generic fallback for all other errors
@@ -75,6 +100,31 @@ export enum KopiaErrorCode {
   OPERATION_FAILED = 'OPERATION_FAILED', // Replaces all unused specific codes
 }
 
+/**
+ * Structured data carried by desktop error codes, keyed by code
+ *
+ * Field names match the variant fields in src-tauri/src/error.rs (snake_case).
+ */
+export interface KopiaErrorDetails {
+  [KopiaErrorCode.INVALID_INPUT]: { field: string; message: string };
+  [KopiaErrorCode.REPOSITORY_UPGRADE_REQUIRED]: {
+    current_version?: string;
+    required_version?: string;
+    message: string;
+  };
+  [KopiaErrorCode.REPOSITORY_LOCKED]: { holder?: string };
+  [KopiaErrorCode.PATH_NOT_FOUND]: { path: string };
+  [KopiaErrorCode.CONFIG_LOCKED]: { config_file: string; pid?: number };
+  [KopiaErrorCode.BINARY_INCOMPATIBLE]: { path: string; expected: string; found: string };
+  [KopiaErrorCode.NOTIFICATION_TEST_FAILED]: {
+    stage?: 'connect' | 'auth' | 'send';
+    message: string;
+  };
+  [KopiaErrorCode.REPOSITORY_BUSY]: { message: string };
+  [KopiaErrorCode.TIMEOUT]: { operation: string; seconds: number };
+  [KopiaErrorCode.CONTROL_API_UNAVAILABLE]: { reason: string };
+}
+
 /**
  * Structured Kopia error
  */
@@ -97,6 +147,13 @@ export class KopiaError extends Error {
     return this.code === code || this.apiErrorCode === code;
   }
 
+  /**
+   * Get the structured data of a desktop error code (undefined for other codes)
+   */
+  detailsFor<C extends keyof KopiaErrorDetails>(code: C): KopiaErrorDetails[C] | undefined {
+    return this.code === code ? (this.details as KopiaErrorDetails[C]) : undefined;
+  }
+
   /**
    * Check if error is a specific official API error code
    */
@@ -130,7 +187,7 @@ export class KopiaError extends Error {
     const errorKey = snakeToCamel(this.code);
 
     const translationKey = `errors.kopia.${errorKey}`;
-    const translated = i18n.t(translationKey);
+    const translated = i18n.t(translationKey, interpolationValues(this.details));
 
     // If translation exists and is different from the key, use it
     if (translated && translated !== translationKey) {
@@ -19,6 +19,14 @@ export interface SourceInfo {
   path: string;
 }
 
+/**
+ * Default `user@host` identity for new sources, as reported by the server
+ */
+export interface SourceIdentity {
+  userName: string;
+  host: string;
+}
+
 /**
  * Repository status information (matches serverapi.StatusResponse)
  * Combines StatusResponse + embedded repo.ClientOptions
@@ -52,6 +60,88 @@ export interface RepositoryStatus {
   };
 }
 
+/**
+ * Kind of problem found by `repository_health`
+ */
+export type HealthIssueKind =
+  | 'maintenanceDisabled'
+  | 'maintenanceOverdue'
+  | 'maintenanceFailed'
+  | 'failedTasks'
+  | 'snapshotErrors'
+  | 'noSnapshots';
+
+/**
+ * A problem lowering the health score, with what to do about it
+ */
+export interface HealthIssue {
+  kind: HealthIssueKind;
+  message: string;
+  suggestedAction: string;
+  /** Points taken off the score */
+  penalty: number;
+}
+
+/**
+ * At-a-glance repository health (see `repository_health`)
+ */
+export interface RepositoryHealth {
+  /** 100 when no issues were found, lowered by each issue's penalty */
+  score: number;
+  issues: HealthIssue[];
+}
+
+/**
+ * Splitter option annotated for the repository creation UI
+ */
+export interface SplitterInfo {
+  id: string;
+  /** True for the server's default splitter */
+  recommended: boolean;
+  deprecated: boolean;
+  description: string;
+}
+
+/**
+ * Throttling limits for repository operations (GET/PUT /api/v1/repo/throttle)
+ * See: repo/blob/throttling/throttler.go (Limits)
+ */
+export interface ThrottleLimits {
+  readsPerSecond?: number;
+  writesPerSecond?: number;
+  listsPerSecond?: number;
+  maxUploadSpeedBytesPerSecond?: number;
+  maxDownloadSpeedBytesPerSecond?: number;
+  concurrentReads?: number;
+  concurrentWrites?: number;
+}
+
+/**
+ * Repository content totals (see `content_stats`)
+ */
+export interface RepositoryContentStats {
+  count: number;
+  /** Bytes stored after compression */
+  totalPackedSize: number;
+  /** Bytes of content before compression */
+  totalOriginalSize: number;
+  /** `totalOriginalSize / totalPackedSize` */
+  dedupRatio: number;
+}
+
+/**
+ * Content stored in a pack blob (see `blob_contents`)
+ */
+export interface BlobContent {
+  contentId: string;
+  /** Bytes stored in the pack */
+  length: number;
+  /** Byte offset within the pack */
+  offset: number;
+  /** Bytes before compression */
+  originalLength: number;
+}
+
 /**
  * Repository creation options
  */
@@ -228,6 +318,30 @@ export interface StorageUsageDetails {
   contents: number;
 }
 
+/**
+ * Content-level counters of a snapshot (see `snapshot_content_stats`)
+ */
+export interface ContentStats {
+  contents: number;
+  packedBytes: number;
+  originalBytes: number;
+  fileObjects: number;
+  dirObjects: number;
+}
+
+/**
+ * Storage statistics recorded with a snapshot
+ */
+export interface SnapshotContentStats {
+  snapshotId: string;
+  /** False for snapshots made by Kopia versions that didn't record storage stats */
+  statsAvailable: boolean;
+  /** Data this snapshot added to the repository */
+  newData: ContentStats | null;
+  /** Totals for everything the snapshot references */
+  runningTotal: ContentStats | null;
+}
+
 /**
  * Upload counters (matches upload.Counters from Kopia)
  * See: snapshot/upload/upload_progress.go:169-201
@@ -272,6 +386,24 @@ export interface SnapshotSource {
   currentTask?: string;
 }
 
+/**
+ * Upload progress of a single source (see `source_progress`)
+ */
+export interface UploadProgress {
+  status: SnapshotSource['status'];
+  /** Hashed plus cached bytes */
+  bytesDone: number;
+  estimatedBytes: number;
+  /** 0-100, absent until the server has an estimate */
+  percentComplete?: number;
+  hashedFiles: number;
+  cachedFiles: number;
+  estimatedFiles: number;
+  errors: number;
+  currentDirectory?: string;
+  throughputBytesPerSec?: number;
+}
+
 /**
  * Sources list response
  */
@@ -282,6 +414,79 @@ export interface SourcesResponse {
   sources: SnapshotSource[];
 }
 
+/**
+ * Per-source results of an action targeting several sources
+ * (matches serverapi.MultipleSourceActionResponse)
+ */
+export interface MultipleSourceActionResponse {
+  sources: Record<string, { success: boolean }>;
+}
+
+/**
+ * Snapshot summary for a single source (see `sources_summary`)
+ */
+export interface SourceSummary {
+  source: SourceInfo;
+  snapshotCount: number;
+  lastSnapshotTime: string | null;
+  lastSnapshotSize: number | null;
+}
+
+/**
+ * Snapshot summaries across all sources
+ *
+ * `partial` is set when some sources couldn't be queried; those are listed in
+ * `timedOutSources` or `failedSources` (as `user@host:path`).
+ */
+export interface SourcesSummary {
+  sources: SourceSummary[];
+  partial: boolean;
+  timedOutSources: string[];
+  failedSources: string[];
+}
+
+/**
+ * A scheduled snapshot that didn't run (see `missed_snapshots`)
+ */
+export interface MissedSnapshot {
+  source: SourceInfo;
+  /** When the snapshot was due (RFC 3339) */
+  scheduledTime: string;
+  missedBySecs: number;
+}
+
+/**
+ * Sources for which `run_missed_now` started a snapshot
+ */
+export interface RunMissedResult {
+  triggered: SourceInfo[];
+  partial: boolean;
+  timedOutSources: string[];
+  failedSources: string[];
+}
+
+/**
+ * Sources without snapshots found by `sources_prune`
+ */
+export interface SourcesPruneResult {
+  /** Sources without any snapshot */
+  candidates: SourceInfo[];
+  /** Candidates whose source and policy were deleted */
+  removed: SourceInfo[];
+  /** Candidates that couldn't be deleted (as `user@host:path`) */
+  failed: string[];
+}
+
+/**
+ * Time since a source's last snapshot (see `sources_by_staleness`)
+ */
+export interface SourceStaleness {
+  source: SourceInfo;
+  /** When the last snapshot finished (RFC 3339), null if never backed up */
+  lastSnapshotTime: string | null;
+  ageSecs: number | null;
+}
+
 /**
  * Snapshots list response
  */
@@ -291,6 +496,28 @@ export interface SnapshotsResponse {
   uniqueCount: number;
 }
 
+/**
+ * Snapshots of every source (see `snapshots_list_all`)
+ */
+export interface AllSnapshots {
+  /** Snapshots by source, keyed `user@host:path` */
+  snapshots: Record<string, SnapshotsResponse>;
+  partial: boolean;
+  timedOutSources: string[];
+  failedSources: string[];
+}
+
+/**
+ * Snapshot that didn't finish (see `incomplete_snapshots`)
+ */
+export interface IncompleteSnapshot {
+  id: string;
+  /** Start time (RFC 3339) */
+  time: string;
+  /** Why the snapshot is incomplete, e.g. "canceled" */
+  reason: string;
+}
+
 /**
  * Snapshot edit request
  */
@@ -311,6 +538,29 @@ export interface SnapshotDeleteRequest {
   deleteSourceAndPolicy?: boolean;
 }
 
+/**
+ * Totals of a finished estimate (see `estimate_snapshot_blocking`)
+ */
+export interface EstimateResult {
+  totalBytes: number;
+  fileCount: number;
+  directoryCount: number;
+  /** Excluded files and directories */
+  excludedCount: number;
+  excludedBytes: number;
+  errorCount: number;
+}
+
+/**
+ * Outcome of `snapshot_create_and_verify`
+ */
+export interface SnapshotVerification {
+  snapshotId: string;
+  verified: boolean;
+  /** Problems reported by the verification (empty if verified) */
+  errors: string[];
+}
+
 // ============================================================================
 // Directory & File Browsing Types
 // ============================================================================
@@ -348,23 +598,28 @@ export interface DirectoryObject {
   entries: DirectoryEntry[];
 }
 
+/**
+ * Filesystem restore options (matches restore.FilesystemOutput)
+ */
+export interface FilesystemOutput {
+  targetPath: string;
+  overwriteFiles?: boolean;
+  overwriteDirectories?: boolean;
+  overwriteSymlinks?: boolean;
+  skipOwners?: boolean;
+  skipPermissions?: boolean;
+  skipTimes?: boolean;
+  ignorePermissionErrors?: boolean;
+  writeFilesAtomically?: boolean;
+  writeSparseFiles?: boolean;
+}
+
 /**
  * Restore request
  */
 export interface RestoreRequest {
   root: string;
-  fsOutput?: {
-    targetPath: string;
-    overwriteFiles?: boolean;
-    overwriteDirectories?: boolean;
-    overwriteSymlinks?: boolean;
-    skipOwners?: boolean;
-    skipPermissions?: boolean;
-    skipTimes?: boolean;
-    ignorePermissionErrors?: boolean;
-    writeFilesAtomically?: boolean;
-    writeSparseFiles?: boolean;
-  };
+  fsOutput?: FilesystemOutput;
   zipFile?: string;
   uncompressedZip?: boolean;
   tarFile?: string;
@@ -376,6 +631,54 @@ export interface RestoreRequest {
   };
 }
 
+/**
+ * Whether a restore option takes effect on the target filesystem
+ */
+export interface RestoreOptionSupport {
+  /** Option name as in `FilesystemOutput` */
+  option: string;
+  supported: boolean;
+  /** Why the option is ignored, or why support is uncertain */
+  reason: string | null;
+}
+
+/**
+ * Result of comparing restored local files against a snapshot
+ */
+export interface RestoreVerification {
+  /** Number of files whose size (and content, if compared) matched */
+  matched: number;
+  /** Relative paths of files that differ from the snapshot */
+  mismatched: string[];
+  /** Relative paths present in the snapshot but missing locally */
+  missing: string[];
+  /** True when the depth or file limit stopped the walk early */
+  truncated: boolean;
+}
+
+/**
+ * A distinct version of a file found in a source's snapshots
+ */
+export interface FileVersion {
+  /** Oldest snapshot containing this version */
+  snapshotId: string;
+  snapshotTime: string;
+  /** Object ID to pass to `object_download` */
+  objectId: string;
+  size: number | null;
+}
+
+/**
+ * Versions of a file (see `file_versions`)
+ */
+export interface FileVersions {
+  versions: FileVersion[];
+  partial: boolean;
+  /** Snapshots that weren't searched in time, by ID */
+  timedOutSnapshots: string[];
+  failedSnapshots: string[];
+}
+
 /**
  * Mounts list response (matches serverapi.MountedSnapshots)
  */
@@ -386,6 +689,75 @@ export interface MountsResponse {
   }>;
 }
 
+/**
+ * File selected for `restore_files`
+ */
+export interface RestoreFileItem {
+  objectId: string;
+  /** Path under the target folder, e.g. `docs/report.pdf` */
+  relativePath: string;
+}
+
+/**
+ * Outcome of restoring one file with `restore_files`
+ */
+export interface RestoreFileResult {
+  relativePath: string;
+  success: boolean;
+  /** Bytes written (0 if the file failed) */
+  bytes: number;
+  error?: string;
+}
+
+/**
+ * Payload of the `restore-files-progress` event
+ */
+export interface RestoreFilesProgress {
+  repoId: string;
+  totalFiles: number;
+  /** Files finished so far, successfully or not */
+  completedFiles: number;
+  failedFiles: number;
+  bytesWritten: number;
+}
+
+/**
+ * Payload of the `download-progress` event
+ */
+export interface DownloadProgress {
+  objectId: string;
+  bytesWritten: number;
+  /** From the response's Content-Length, if the server sent one */
+  totalBytes: number | null;
+}
+
+/**
+ * Result of `snapshot_tree_export`
+ */
+export interface TreeExportSummary {
+  /** Number of entries written */
+  entries: number;
+  /** True when the depth or entry limit left part of the tree out */
+  truncated: boolean;
+}
+
+/**
+ * Progress of a restore task (see `restore_status`)
+ */
+export interface RestoreStatus {
+  status: TaskStatus;
+  restoredFiles: number;
+  restoredDirectories: number;
+  restoredBytes: number;
+  /** Files left alone because they already existed */
+  skippedFiles: number;
+  skippedBytes: number;
+  ignoredErrors: number;
+  /** True once the task is no longer running */
+  finished: boolean;
+  errorMessage?: string;
+}
+
 // ============================================================================
 // Policy Types
 // ============================================================================
@@ -449,20 +821,25 @@ export type OSSnapshotMode = 0 | 1 | 2;
  */
 export type LogDetailLevel = number;
 
+/**
+ * Retention settings of a policy
+ */
+export interface RetentionPolicy {
+  keepLatest?: number;
+  keepHourly?: number;
+  keepDaily?: number;
+  keepWeekly?: number;
+  keepMonthly?: number;
+  keepAnnual?: number;
+  ignoreIdenticalSnapshots?: boolean;
+}
+
 /**
  * Policy definition (complete specification)
  */
 export interface PolicyDefinition {
   noParent?: boolean; // Prevents inheriting policy from parent
-  retention?: {
-    keepLatest?: number;
-    keepHourly?: number;
-    keepDaily?: number;
-    keepWeekly?: number;
-    keepMonthly?: number;
-    keepAnnual?: number;
-    ignoreIdenticalSnapshots?: boolean;
-  };
+  retention?: RetentionPolicy;
   scheduling?: {
     intervalSeconds?: number;
     /** Times of day to run snapshots (array of {hour, min} objects) */
@@ -525,6 +902,15 @@ export interface PolicyDefinition {
   };
 }
 
+/**
+ * Upload parallelism settings of a policy (null/undefined = inherited)
+ */
+export interface UploadPolicy {
+  maxParallelSnapshots?: number | null;
+  maxParallelFileReads?: number | null;
+  parallelUploadAboveSize?: number | null;
+}
+
 /**
  * Policy target (hierarchy level)
  */
@@ -564,6 +950,96 @@ export interface ResolvedPolicyResponse {
   schedulingError?: string;
 }
 
+/**
+ * Result of testing ignore rules against one sample path
+ */
+export interface GlobMatch {
+  path: string;
+  /** Whether the path is ignored by the rules */
+  matched: boolean;
+  /** The rule that decided the result, if any matched */
+  pattern?: string;
+}
+
+/**
+ * Outcome of deleting one policy in `policies_delete_batch`
+ */
+export interface PolicyDeleteResult {
+  target: PolicyTarget;
+  success: boolean;
+  error?: string;
+}
+
+/**
+ * Validated cron expression with a plain-English description
+ */
+export interface CronDescription {
+  valid: boolean;
+  /** Expression with macros expanded, comments removed and whitespace collapsed */
+  normalized?: string;
+  /** When the schedule runs, e.g. "Every day at 2:00 AM" */
+  description?: string;
+  /** Next run times in local time (RFC 3339) */
+  nextRuns: string[];
+  /** Why the expression is invalid */
+  error?: string;
+}
+
+/**
+ * Profiles accepted by `recommended_policy` and `apply_recommended_policy`
+ */
+export type RecommendedPolicyProfile = 'documents' | 'photos' | 'code' | 'general';
+
+/**
+ * Policy that defined one ignore rule
+ */
+export interface IgnoreRuleOrigin {
+  rule: string;
+  /** True for a dot-ignore file name, false for an ignore pattern */
+  dotIgnoreFile: boolean;
+  target: PolicyTarget;
+}
+
+/**
+ * Ignore rules that apply to a target once inheritance is resolved
+ */
+export interface EffectiveIgnores {
+  /** Ignore patterns, most general policy first */
+  explicit: string[];
+  /** Dot-ignore file names (e.g. `.kopiaignore`) */
+  dotIgnoreFiles: string[];
+  /** Policies that contributed at least one rule, most general first */
+  inheritedFrom: PolicyTarget[];
+  /** Where each rule was defined */
+  origins: IgnoreRuleOrigin[];
+}
+
+/**
+ * One effective policy field compared with the target's own policy
+ * (see `policy_overrides`)
+ */
+export interface PolicyFieldOverride {
+  /** Dotted JSON path, e.g. `retention.keepDaily` */
+  field: string;
+  /** Value set on the target itself, if any */
+  definedValue: unknown;
+  effectiveValue: unknown;
+  /** True if the effective value comes from a parent policy or the defaults */
+  inherited: boolean;
+}
+
+/**
+ * Outcome of applying a retention policy (see `retention_preview`)
+ */
+export interface RetentionPreview {
+  /** IDs of snapshots that would be kept, newest first */
+  kept: string[];
+  /** IDs of snapshots that would be deleted, newest first */
+  pruned: string[];
+  /** Why each kept snapshot is retained, e.g. ["latest-1", "daily-1"] */
+  reasons: Record<string, string[]>;
+}
+
 // ============================================================================
 // Task Types
 // ============================================================================
@@ -607,6 +1083,15 @@ export interface CounterValue {
   level?: CounterLevel;
 }
 
+/**
+ * Task counter flagged with a "warning" or "error" level
+ */
+export interface TaskCounterWarning {
+  name: string;
+  value: number;
+  level: CounterLevel;
+}
+
 /**
  * Task detail with logs
  */
@@ -631,6 +1116,67 @@ export interface TasksSummary {
   canceled: number;
 }
 
+/**
+ * Entry in the recent activity feed, built from finished tasks
+ * (outcome is "success" or "canceled"; failures are reported as `Error`)
+ */
+export type ActivityItem =
+  | { type: 'SnapshotCompleted'; taskId: string; time: string; source: string; outcome: string }
+  | { type: 'RestoreCompleted'; taskId: string; time: string; target: string; outcome: string }
+  | {
+      type: 'MaintenanceRun';
+      taskId: string;
+      time: string;
+      description: string;
+      outcome: string;
+    }
+  | {
+      type: 'Error';
+      taskId: string;
+      time: string;
+      kind: string;
+      description: string;
+      message?: string;
+    };
+
+/**
+ * Operations currently in progress (see `has_active_operations`)
+ */
+export interface ActiveOperations {
+  active: boolean;
+  /** Number of running tasks */
+  count: number;
+  /** Distinct kinds of the running tasks (e.g. "Snapshot", "Maintenance") */
+  kinds: string[];
+  /** Whether some servers couldn't be checked */
+  partial: boolean;
+  /** Repositories whose server didn't answer or failed */
+  failedRepoIds: string[];
+}
+
+/**
+ * Combined progress of all running tasks (see `overall_progress`)
+ */
+export interface OverallProgress {
+  activeTasks: number;
+  /** Completion of the tasks that report an estimate, 0-100 */
+  percent: number;
+  /** Bytes processed by all running tasks */
+  bytesDone: number;
+  /** Estimated bytes of the tasks that report an estimate */
+  bytesTotal: number;
+}
+
+/**
+ * Latest sources and tasks fetched from a server (see `last_known_state`)
+ */
+export interface LastKnownState {
+  sources: SnapshotSource[];
+  tasks: Task[];
+  /** When either list was last fetched (RFC 3339), empty if never */
+  receivedAt: string;
+}
+
 // ============================================================================
 // Backup Profile Types
 // ============================================================================
@@ -656,6 +1202,78 @@ export interface BackupProfile {
   updatedAt: string; // ISO8601
 }
 
+// ============================================================================
+// Maintenance Types
+// ============================================================================
+
+/**
+ * Maintenance owner (`user@host`) and whether it's this client
+ */
+export interface MaintenanceOwner {
+  owner: string;
+  isSelf: boolean;
+}
+
+/**
+ * Outcome of `maintenance_run_if_due`
+ */
+export interface MaintenanceRunIfDueResult {
+  ran: boolean;
+  /** Why maintenance wasn't run */
+  reason?: string;
+}
+
+/**
+ * Quick or full maintenance cycle settings
+ */
+export interface MaintenanceCycleParams {
+  enabled: boolean;
+  /** Interval in nanoseconds (Go time.Duration) */
+  interval: number;
+}
+
+/**
+ * Maintenance parameters stored in the repository
+ */
+export interface MaintenanceParams {
+  /** Maintenance owner as `user@host` */
+  owner: string;
+  quick: MaintenanceCycleParams;
+  full: MaintenanceCycleParams;
+  /** Extend object lock retention during full maintenance */
+  extendObjectLocks: boolean;
+  /** Parallelism of blob listing (0 = Kopia's default) */
+  listParallelism: number;
+}
+
+/**
+ * One recorded run of a maintenance task
+ */
+export interface MaintenanceRunInfo {
+  start: string;
+  end: string;
+  success: boolean;
+  error?: string;
+}
+
+/**
+ * Next maintenance times and recent runs
+ */
+export interface MaintenanceSchedule {
+  nextFullMaintenance: string | null;
+  nextQuickMaintenance: string | null;
+  /** Recent runs keyed by task name (e.g. "snapshot-gc", "cleanup-logs") */
+  runs: Record<string, MaintenanceRunInfo[]>;
+}
+
+/**
+ * Maintenance parameters and schedule (see `maintenance_info`)
+ */
+export interface MaintenanceInfo {
+  params: MaintenanceParams;
+  schedule: MaintenanceSchedule;
+}
+
 // ============================================================================
 // Notification Types
 // ============================================================================
@@ -687,6 +1305,14 @@ export function getSeverityLabel(severity: NotificationSeverity): string {
   return i18n.t(`preferences.notificationProfiles.severity.${severityLabels[severity]}`);
 }
 
+/**
+ * A notification severity with its display label (see `notification_severity_levels`)
+ */
+export interface SeverityLevel {
+  value: NotificationSeverity;
+  label: string;
+}
+
 /**
  * Notification method configuration
  */
@@ -743,6 +1369,30 @@ export interface NotificationProfile {
  */
 export type NotificationProfilesResponse = NotificationProfile[];
 
+/**
+ * Notification printed by the server with `--kopiaui-notifications`
+ * See: notification/sender/sender.go (Message)
+ */
+export interface KopiaNotification {
+  subject: string;
+  body: string;
+  severity: NotificationSeverity;
+  headers: Record<string, string>;
+  /** When the app received the notification (RFC 3339) */
+  receivedAt: string;
+}
+
+/**
+ * Outcome of importing one notification profile
+ */
+export interface NotificationProfileImportResult {
+  profile: string;
+  imported: boolean;
+  /** Secrets that must be re-entered before the profile works */
+  missingSecrets: string[];
+  error?: string;
+}
+
 // ============================================================================
 // Kopia Server Lifecycle Types
 // ============================================================================
@@ -752,6 +1402,40 @@ export interface KopiaServerInfo {
   port: number;
   password: string;
   pid: number;
+  /** Warnings printed by Kopia while starting up (cache, time sync, etc.) */
+  startupWarnings: string[];
+}
+
+/**
+ * Optional overrides for the flags used when spawning the server
+ */
+export interface ServerStartOptions {
+  /** Listen address, must be a loopback address (defaults to `127.0.0.1:0`) */
+  address?: string;
+  /** Server log level passed as `--log-level` (e.g. "info", "debug") */
+  logLevel?: string;
+}
+
+/**
+ * Phases reported through the `server-start-phase` event
+ */
+export type ServerStartPhase = 'stopping' | 'starting' | 'waiting_for_ready' | 'ready' | 'failed';
+
+/**
+ * Payload of the `server-start-phase` event
+ */
+export interface ServerStartPhaseEvent {
+  repoId: string;
+  phase: ServerStartPhase;
+}
+
+/**
+ * Payload of the `server-unhealthy` event (the server is restarted afterwards)
+ */
+export interface ServerUnhealthyEvent {
+  repoId: string;
+  /** Consecutive heartbeats that failed */
+  failures: number;
 }
 
 export interface KopiaServerStatus {
@@ -761,6 +1445,27 @@ export interface KopiaServerStatus {
   uptime?: number;
 }
 
+/**
+ * App and server uptime for the status bar
+ */
+export interface UptimeInfo {
+  appUptimeSecs: number;
+  /** Null when the repository's server isn't running */
+  serverUptimeSecs: number | null;
+}
+
+/**
+ * Connection details of a Kopia server not started by the app
+ */
+export interface ExternalServer {
+  /** Server URL, e.g. `https://backup.example.com:51515` */
+  url: string;
+  username: string;
+  password: string;
+  /** SHA-256 fingerprint of the server's TLS certificate (hex, colons allowed) */
+  certFingerprint: string;
+}
+
 /**
  * Repository entry from multi-repo management
  * Represents a repository configuration with its current status
@@ -782,6 +1487,31 @@ export interface RepositoryEntry {
   error?: string;
 }
 
+/**
+ * Version of the bundled Kopia binary (see `server_version`)
+ */
+export interface KopiaVersion {
+  /** Version without a leading "v", e.g. "0.21.1" */
+  version: string;
+  /** Version as reported by the binary */
+  buildVersion: string;
+  /** Commit the binary was built from, if known */
+  buildInfo: string;
+  /** GitHub repository the build came from, e.g. "kopia/kopia" */
+  sourceRepo?: string;
+}
+
+/**
+ * Result of `kopia_server_stop_safe`
+ */
+export interface SafeStopResult {
+  stopped: boolean;
+  /** Why the server was left running */
+  refusedReason?: string;
+  /** Tasks canceled before stopping (with `force`) */
+  canceledTasks: number;
+}
+
 // ============================================================================
 // Storage Configuration Types
 // ============================================================================
@@ -902,3 +1632,243 @@ export interface RepositoryConnectRequest {
   /** Time in seconds to wait for repository sync after connection */
   syncWaitTime?: number;
 }
+
+// ============================================================================
+// Application Types
+// ============================================================================
+
+/**
+ * Summary of the metrics recorded for one command
+ */
+export interface CommandMetrics {
+  count: number;
+  p50Ms: number;
+  p95Ms: number;
+  errors: number;
+}
+
+/**
+ * Application settings (stored in kopia-desktop-settings.json)
+ */
+export interface AppSettings {
+  /** Pause scheduled snapshots while on battery or a metered connection */
+  powerAwareScheduling: boolean;
+  /** Accept any Kopia server certificate instead of pinning it (debugging only) */
+  insecureSkipCertVerify: boolean;
+  /** Per-request timeout for aggregate commands that query many sources (null = default) */
+  fanOutTimeoutSecs: number | null;
+  /** Let running tasks finish before quitting from the tray or closing the window */
+  waitForTasksOnQuit: boolean;
+  /** Reconnect repositories automatically after losing connectivity */
+  autoReconnect: boolean;
+  /** Per-source overrides of whether failed snapshots raise a notification */
+  sourceNotifyOverrides: SourceNotifyOverride[];
+  /** Finished tasks that ended at or before this time (RFC 3339) are hidden, by repository ID */
+  tasksClearedBefore: Record<string, string>;
+  /** Upload throughput of completed snapshots, oldest first */
+  uploadThroughput: ThroughputSample[];
+  /** Repository the user last had open (see `get_ui_state`) */
+  lastRepoId: string | null;
+  /** Screen the user last had open, as a frontend route */
+  lastView: string | null;
+  /** Let Kopia servers check for updates (off by default) */
+  serverCheckForUpdates: boolean;
+  /** Where repository configs live if moved by `relocate_config_dir` (null = default) */
+  configDir: string | null;
+  /** Enable low-level diagnostic commands such as `blob_contents` */
+  developerMode: boolean;
+}
+
+/**
+ * Detected power and network state (null = unknown on this platform)
+ */
+export interface PowerState {
+  onBattery: boolean | null;
+  metered: boolean | null;
+}
+
+/**
+ * Payload of the `power-state-changed` event
+ */
+export interface PowerStateEvent extends PowerState {
+  /** Whether sources are currently paused by power-aware scheduling */
+  paused: boolean;
+}
+
+/**
+ * SHA-256 of the Kopia binary and whether it matches the build-time hash
+ */
+export interface BinaryIntegrity {
+  path: string;
+  sha256: string;
+  /** Null when no expected hash was embedded in this build */
+  matchesExpected: boolean | null;
+}
+
+/**
+ * Windows Volume Shadow Copy (VSS) availability
+ */
+export interface VssStatus {
+  available: boolean;
+  /** Why VSS can't be used, if it can't */
+  reason: string | null;
+  /** Whether the app (and the Kopia servers it starts) runs elevated */
+  elevated: boolean;
+}
+
+/**
+ * Payload of the `quit-waiting` event
+ */
+export interface QuitWaitingEvent {
+  /** Number of tasks still running */
+  count: number;
+  kinds: string[];
+  /** Seconds left before remaining tasks are canceled */
+  remainingSecs: number;
+}
+
+/**
+ * Payload of the `repository-reconnected` event
+ */
+export interface RepositoryReconnectedEvent {
+  repoId: string;
+  /** Reconnect attempts made (0 if the repository recovered by itself) */
+  attempts: number;
+  /** Seconds the repository was unreachable */
+  downtimeSecs: number;
+}
+
+/**
+ * Disk space used by the app's own files in the config directory
+ */
+export interface AppStorageUsage {
+  /** Settings and repository configs (everything except `cache/` and `logs/`) */
+  configBytes: number;
+  cacheBytes: number;
+  logsBytes: number;
+  totalBytes: number;
+  /** Paths that couldn't be read and were counted as zero */
+  inaccessible: string[];
+}
+
+/**
+ * Notification override for one source (see `source_set_notify_on_error`)
+ */
+export interface SourceNotifyOverride {
+  repoId: string;
+  source: SourceInfo;
+  notifyOnError: boolean;
+}
+
+/**
+ * Result of `check_binary_compatibility`
+ */
+export interface BinaryCompatibility {
+  path: string;
+  /** Format and architecture this system runs, e.g. "ELF x86_64" */
+  expected: string;
+  /** Format and architecture of the binary, or "unknown" */
+  resolved: string;
+  compatible: boolean;
+}
+
+/**
+ * Upload throughput of one completed snapshot
+ */
+export interface ThroughputSample {
+  repoId: string;
+  /** When the snapshot finished (RFC 3339) */
+  time: string;
+  bytesPerSecond: number;
+  /** Snapshot source as `user@host:/path` */
+  source: string;
+}
+
+/**
+ * Repository and screen to reopen the app to
+ */
+export interface UiState {
+  repoId: string;
+  view: string | null;
+}
+
+/**
+ * Update check state of the Kopia servers (see `get_server_update_check`)
+ */
+export interface ServerUpdateCheck {
+  enabled: boolean;
+  /** Newer Kopia version announced by a server, if any */
+  availableVersion: string | null;
+}
+
+/**
+ * Log levels accepted by `set_log_level`
+ */
+export type LogLevel = 'error' | 'warn' | 'info' | 'debug' | 'trace';
+
+/**
+ * Client configuration in effect: compiled defaults merged with the settings
+ * (see `get_effective_config`)
+ */
+export interface EffectiveConfig {
+  /** Readiness checks after starting a server, and the delay between them */
+  healthCheckRetries: number;
+  healthCheckIntervalMs: number;
+  /** Heartbeat of running servers */
+  heartbeatIntervalSecs: number;
+  heartbeatTimeoutSecs: number;
+  heartbeatFailureThreshold: number;
+  httpOperationTimeoutSecs: number;
+  httpConnectTimeoutSecs: number;
+  /** Longest wait for the next chunk of a download (no total timeout) */
+  downloadIdleTimeoutSecs: number;
+  cliTimeoutSecs: number;
+  /** Per-request timeout of aggregate commands (`fanOutTimeoutSecs` or default) */
+  fanOutTimeoutSecs: number;
+  restoreFilesConcurrency: number;
+  /** Attempts and initial backoff while the repository is busy */
+  busyRetryAttempts: number;
+  busyRetryInitialDelayMs: number;
+  autoReconnect: boolean;
+  reconnectInitialBackoffSecs: number;
+  reconnectMaxBackoffSecs: number;
+  waitForTasksOnQuit: boolean;
+  quitWaitSecs: number;
+  insecureSkipCertVerify: boolean;
+  serverCheckForUpdates: boolean;
+  developerMode: boolean;
+  logLevel: LogLevel;
+  /** Repository throttle limits (missing if the server couldn't be asked) */
+  throttle?: ThrottleLimits;
+}
+
+/**
+ * Hash of one file found by `hash_directory`
+ */
+export interface FileHash {
+  /** Path relative to the hashed directory */
+  path: string;
+  size: number;
+  hash: string;
+}
+
+/**
+ * Result of `hash_directory`
+ */
+export interface DirectoryHashSummary {
+  algorithm: 'sha256' | 'sha512';
+  /** Files hashed */
+  fileCount: number;
+  /** Bytes hashed */
+  totalBytes: number;
+  /** Files whose content matched an earlier file */
+  duplicateFiles: number;
+  /** Bytes in those duplicate files */
+  duplicateBytes: number;
+  /** Files that couldn't be read, including one cut off by the time limit */
+  skippedFiles: number;
+  /** First files hashed, as examples */
+  sampleHashes: FileHash[];
+  /** True when the file or time limit stopped the walk early */
+  truncated: boolean;
+}
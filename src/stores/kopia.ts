@@ -33,6 +33,7 @@ import {
   getTasksSummary,
   getTask as apiGetTask,
   cancelTask as apiCancelTask,
+  taskNotifyOnFailure,
   listMounts,
   mountSnapshot as apiMountSnapshot,
   unmountSnapshot as apiUnmountSnapshot,
@@ -194,6 +195,22 @@ function setErrorIfChanged(
   }
 }
 
+/**
+ * Notify about a finished task
+ *
+ * Failures of snapshot sources with notifications turned off are skipped.
+ * If the check fails, the notification is shown anyway.
+ */
+async function notifyTaskCompletion(repoId: string, previous: Task, finished: Task) {
+  if (finished.status === 'FAILED') {
+    const notify = await taskNotifyOnFailure(repoId, finished).catch(() => true);
+    if (!notify) {
+      return;
+    }
+  }
+  await notifyTaskComplete(previous.description || previous.kind, finished.status === 'SUCCESS');
+}
+
 interface KopiaStore {
   // Multi-repository state
   repositories: RepositoryEntry[];
@@ -848,10 +865,7 @@ export const useKopiaStore = create<KopiaStore>()(
                     newTask.status === 'FAILED' ||
                     newTask.status === 'CANCELED')
                 ) {
-                  void notifyTaskComplete(
-                    currentTask.description || currentTask.kind,
-                    newTask.status === 'SUCCESS'
-                  );
+                  void notifyTaskCompletion(repoId, currentTask, newTask);
                 }
               }
             }
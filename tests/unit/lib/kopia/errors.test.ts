@@ -14,15 +14,19 @@ import {
 // Mock i18n for testing
 vi.mock('@/lib/i18n/config', () => ({
   default: {
-    t: (key: string) => {
+    t: (key: string, values: Record<string, string | number> = {}) => {
       // Simple mock translations
       const translations: Record<string, string> = {
         'errors.unknownError': 'Unknown error',
         'errors.kopia.serverNotRunning': 'Server is not running',
         'errors.kopia.repositoryNotConnected': 'Repository is not connected',
         'errors.kopia.operationFailed': 'Operation failed',
+        'errors.kopia.invalidInput': 'Invalid input: {{message}}',
+        'errors.kopia.pathNotFound': 'Path not found: {{path}}',
+        'errors.kopia.timeout': '{{operation}} timed out after {{seconds}} seconds.',
       };
-      return translations[key] || key;
+      const translated = translations[key] || key;
+      return translated.replace(/\{\{(\w+)\}\}/g, (_, name: string) => String(values[name]));
     },
   },
 }));
@@ -161,6 +165,27 @@ describe('KopiaError', () => {
       const error = new KopiaError('', 'SERVER_NOT_RUNNING');
       expect(error.getUserMessage()).toBe('Server is not running');
     });
+
+    it('includes the field message of invalid input errors', () => {
+      const error = parseKopiaError({
+        type: 'INVALID_INPUT',
+        data: { field: 'path', message: 'Path must be absolute' },
+      });
+      expect(error.getUserMessage()).toBe('Invalid input: Path must be absolute');
+    });
+
+    it('includes the missing path of path not found errors', () => {
+      const error = parseKopiaError({ type: 'PATH_NOT_FOUND', data: { path: 'home/alice/docs' } });
+      expect(error.getUserMessage()).toBe('Path not found: home/alice/docs');
+    });
+
+    it('includes the operation and wait of timeout errors', () => {
+      const error = parseKopiaError({
+        type: 'TIMEOUT',
+        data: { operation: 'Estimate snapshot', seconds: 30 },
+      });
+      expect(error.getUserMessage()).toBe('Estimate snapshot timed out after 30 seconds.');
+    });
   });
 });
 
@@ -291,6 +316,16 @@ describe('getErrorMessage', () => {
     expect(getErrorMessage('String error')).toBe('String error');
   });
 
+  it('keeps the field message of invalid input errors', () => {
+    const error = {
+      type: 'INVALID_INPUT',
+      data: { field: 'newDir', message: 'The new location must be an empty directory' },
+    };
+    expect(getErrorMessage(error, 'Move failed')).toBe(
+      'Move failed: Invalid input: The new location must be an empty directory'
+    );
+  });
+
   it('adds prefix when provided', () => {
     const error = new Error('Test error');
     expect(getErrorMessage(error, 'Failed to connect')).toBe('Failed to connect: Test error');